@@ -266,7 +266,7 @@ fn rotation_update(scene: &Scene, delta: f32) {
 
     for (movement, entity) in rotation_manager.iter() {
         let mut transform = transform_manager.get_mut(entity);
-        transform.rotate(Quaternion::from_eulers(movement.x * delta, movement.y * delta, movement.z * delta));
+        transform.rotate(Quaternion::from_eulers(Rad(movement.x * delta), Rad(movement.y * delta), Rad(movement.z * delta)));
     }
 }
 
@@ -302,7 +302,7 @@ fn camera_movement(scene: &Scene, delta: f32) {
                 let rotation = transform.rotation();
                 let mut velocity = player.velocity;
 
-                transform.set_rotation(Quaternion::from_eulers(0.0, (-movement_x as f32) * PI * 0.001, 0.0) * rotation);
+                transform.set_rotation(Quaternion::from_eulers(Rad(0.0), Rad((-movement_x as f32) * PI * 0.001), Rad(0.0)) * rotation);
 
                 // Calculate the forward and right vectors.
                 // TODO: Directly retrieve local axis from transform without going through rotation matrix.
@@ -350,7 +350,7 @@ fn camera_movement(scene: &Scene, delta: f32) {
 
                 // Apply a rotation to the camera based on mouse movement.
                 camera_transform.set_rotation(
-                    Quaternion::from_eulers((-movement_y as f32) * PI * 0.001, 0.0, 0.0)
+                    Quaternion::from_eulers(Rad((-movement_y as f32) * PI * 0.001), Rad(0.0), Rad(0.0))
                   * rotation);
             }
     }
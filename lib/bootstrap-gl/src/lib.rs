@@ -44,10 +44,14 @@ macro_rules! gl_proc {
         pub unsafe fn $fn_name( $( $arg: $arg_ty, )* ) $( -> $result )* {
             $fn_name::load();
 
-            match $fn_name::load() {
+            let result = match $fn_name::load() {
                 Some(gl_proc) => gl_proc( $( $arg ),* ),
                 None => panic!("Failed to load gl proc for {}", stringify!( $proc_name )),
-            }
+            };
+
+            $crate::check_error(stringify!( $proc_name ));
+
+            result
         }
 
         pub mod $fn_name {
@@ -71,6 +75,26 @@ macro_rules! gl_proc {
     }
 }
 
+/// Panics if the last gl proc generated an error.
+///
+/// Only enabled with the `error-check` feature, otherwise this does nothing and is compiled away.
+#[cfg(feature = "error-check")]
+unsafe fn check_error(proc_name: &'static str) {
+    // Checking for errors after `get_error` would clear the error it's meant to return.
+    if proc_name == "glGetError" {
+        return;
+    }
+
+    let error = get_error();
+    if error != ErrorCode::NoError {
+        panic!("{} generated OpenGL error {:?}", proc_name, error);
+    }
+}
+
+#[cfg(not(feature = "error-check"))]
+#[inline(always)]
+unsafe fn check_error(_proc_name: &'static str) {}
+
 gl_proc!(glActiveTexture:
     /// Selects active texture unit.
     ///
@@ -116,6 +140,53 @@ gl_proc!(glAttachShader:
     /// - `glInvalidOperation` is generated if shader​ is already attached to program​.
     fn attach_shader(program: ProgramObject, shader: ShaderObject));
 
+gl_proc!(glBeginQuery:
+    /// Delimits the start of a query object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBeginQuery)
+    ///
+    /// Core since version 1.5
+    ///
+    /// `begin_query` and `end_query` delimit the boundaries of a query object. `query` must be a
+    /// name previously returned from a call to `gen_queries`. If a query object with name `id`
+    /// does not yet exist it is created with the type determined by `target`. `target` must be
+    /// one of `SamplesPassed`, `AnySamplesPassed`, or `TimeElapsed`. The behavior of the query
+    /// object depends on its type.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if `begin_query` is executed while a query object
+    ///   of the same `target` is already active.
+    /// - `GL_INVALID_OPERATION` is generated if `query` is zero or not a name returned from a
+    ///   previous call to `gen_queries`, or if it is the name of an existing query object whose
+    ///   type does not match `target`.
+    fn begin_query(target: QueryTarget, query: QueryObject));
+
+gl_proc!(glBindAttribLocation:
+    /// Associates a generic vertex attribute index with a named attribute variable.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBindAttribLocation)
+    ///
+    /// Core since version 2.0
+    ///
+    /// Associates a user-defined attribute variable in the program object specified by `program`
+    /// with a generic vertex attribute index. The name of the user-defined attribute variable is
+    /// passed as a null terminated string in `name`. Attribute bindings do not go into effect
+    /// until `link_program` is called, and may be specified at any time, including before any
+    /// shaders are attached. Any attribute binding that never becomes active in the program
+    /// object is ignored.
+    ///
+    /// `name` must not begin with the reserved prefix "gl_".
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `index` is greater than or equal to
+    ///   `GL_MAX_VERTEX_ATTRIBS`.
+    /// - `GL_INVALID_OPERATION` is generated if `name` starts with the reserved prefix "gl_".
+    /// - `GL_INVALID_VALUE` is generated if `program` is not a value generated by OpenGL.
+    /// - `GL_INVALID_OPERATION` is generated if `program` is not a program object.
+    fn bind_attrib_location(program: ProgramObject, index: AttributeLocation, name: *const u8));
+
 gl_proc!(glBindBuffer:
     /// Binds a named buffer object.
     ///
@@ -221,6 +292,109 @@ gl_proc!(glBindBuffer:
     ///   `gen_buffers`.
     fn bind_buffer(target: BufferTarget, buffer: BufferName));
 
+gl_proc!(glBindBufferRange:
+    /// Binds a range within a buffer object to an indexed buffer target.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBindBufferRange)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Binds a range of the buffer object `buffer` represented by `offset` and `size` to the
+    /// binding point at index `index` of the array of targets specified by `target`. Each
+    /// `target` represents an indexed array of buffer binding points, as well as a single general
+    /// binding point that can be used by other buffer manipulation functions such as
+    /// `bind_buffer`. In addition to binding a range of `buffer` to the indexed buffer binding
+    /// target, `bind_buffer_range` also binds the range to the generic buffer binding point
+    /// specified by `target`.
+    ///
+    /// `offset` specifies the offset in basic machine units into the buffer object `buffer` and
+    /// `size` specifies the amount of data that can be read from the buffer object while used as
+    /// an indexed target.
+    ///
+    /// # Notes
+    ///
+    /// - `target` must be one of `AtomicCounter`, `TransformFeedback`, `Uniform`, or
+    ///   `ShaderStorage`.
+    /// - For `Uniform` targets `offset` must be a multiple of `GL_UNIFORM_BUFFER_OFFSET_ALIGNMENT`.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_ENUM` is generated if `target` is not one of the indexed targets.
+    /// - `GL_INVALID_VALUE` is generated if `index` is greater than or equal to the number of
+    ///   binding points for `target`.
+    /// - `GL_INVALID_VALUE` is generated if `size` is less than or equal to zero, or if
+    ///   `offset + size` is greater than the value of `GL_BUFFER_SIZE`.
+    /// - `GL_INVALID_VALUE` is generated if `offset` is not a multiple of the target's offset
+    ///   alignment.
+    fn bind_buffer_range(
+        target: BufferTarget,
+        index: u32,
+        buffer: BufferName,
+        offset: isize,
+        size: isize));
+
+gl_proc!(glBindFragDataLocation:
+    /// Binds a user-defined fragment shader output to a fragment color number.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBindFragDataLocation)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Explicitly specifies the binding of the user-defined varying out variable `name` to
+    /// fragment shader color number `color_number` for program object `program`. If `name` was
+    /// bound previously, its assigned binding is replaced with `color_number`. `name` must be a
+    /// null-terminated string.
+    ///
+    /// Bindings take effect only when the program is next linked. Any output that isn't bound
+    /// explicitly is assigned a color number automatically by the linker.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `color_number` is greater than or equal to
+    ///   `GL_MAX_DRAW_BUFFERS`.
+    /// - `GL_INVALID_OPERATION` is generated if `name` starts with the reserved "gl_" prefix.
+    /// - `GL_INVALID_OPERATION` is generated if `program` is not the name of a program object.
+    fn bind_frag_data_location(program: ProgramObject, color_number: u32, name: *const u8));
+
+gl_proc!(glBindFramebuffer:
+    /// Binds a framebuffer to a framebuffer target.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBindFramebuffer)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Binds the framebuffer object with name `framebuffer` to the framebuffer target specified
+    /// by `target`. If a framebuffer object is bound to `DrawFramebuffer` or `ReadFramebuffer`,
+    /// it becomes the target for rendering or readback operations, respectively, until it is
+    /// deleted or another framebuffer is bound to the corresponding bind point. Calling
+    /// `bind_framebuffer` with `target` set to `Framebuffer` binds `framebuffer` to both the read
+    /// and draw framebuffer targets. Binding `FramebufferName::null()` binds the default
+    /// framebuffer.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if `framebuffer` is not zero or the name of a
+    ///   framebuffer previously returned from a call to `gen_framebuffers`.
+    fn bind_framebuffer(target: FramebufferTarget, framebuffer: FramebufferName));
+
+gl_proc!(glBindRenderbuffer:
+    /// Binds a renderbuffer to a renderbuffer target.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBindRenderbuffer)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Binds the renderbuffer object with name `renderbuffer` to the renderbuffer target
+    /// specified by `target`. `target` must be `Renderbuffer`. `renderbuffer` is the name of a
+    /// renderbuffer object previously returned from a call to `gen_renderbuffers`, or zero to
+    /// break the existing binding of a renderbuffer object to `target`.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if `renderbuffer` is not zero or the name of a
+    ///   renderbuffer previously returned from a call to `gen_renderbuffers`.
+    fn bind_renderbuffer(target: RenderbufferTarget, renderbuffer: RenderbufferName));
+
 gl_proc!(glBindTexture:
     /// Binds a named texture to a texturing target.
     ///
@@ -375,6 +549,50 @@ gl_proc!(glBlendFunc:
     ///   `GL_MAX_DUAL_SOURCE_DRAW_BUFFERS`, which may be lower than `GL_MAX_DRAW_BUFFERS`.
     fn blend_func(src_factor: SourceFactor, dest_factor: DestFactor));
 
+gl_proc!(glBlitFramebuffer:
+    /// Copies a block of pixels from the read framebuffer to the draw framebuffer.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBlitFramebuffer)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Transfers a rectangle of pixel values from one region of the read framebuffer to another
+    /// region of the draw framebuffer. `mask` is the bitwise OR of a number of values indicating
+    /// which buffers are to be copied. The pixels corresponding to these buffers are copied from
+    /// the source rectangle bounded by the locations (`src_x0`, `src_y0`) and (`src_x1`, `src_y1`)
+    /// to the destination rectangle bounded by the locations (`dst_x0`, `dst_y0`) and (`dst_x1`,
+    /// `dst_y1`). The lower bounds of the rectangle are inclusive, while the upper bounds are
+    /// exclusive.
+    ///
+    /// If the source and destination rectangle dimensions do not match, the source image is
+    /// stretched to fit the destination rectangle. `filter` must be `Nearest` or `Linear` and
+    /// specifies the method of interpolation to be applied if the image is stretched.
+    ///
+    /// If the read framebuffer is multisampled and the draw framebuffer is not, the samples are
+    /// resolved to a single value for each pixel.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if `mask` contains `Depth` or `Stencil` and `filter`
+    ///   is not `Nearest`.
+    /// - `GL_INVALID_OPERATION` is generated if `mask` contains `Depth` or `Stencil` and the
+    ///   source and destination depth and stencil formats do not match.
+    /// - `GL_INVALID_OPERATION` is generated if either framebuffer is multisampled and the
+    ///   dimensions of the source and destination rectangles are not identical.
+    /// - `GL_INVALID_FRAMEBUFFER_OPERATION` is generated if either the read or draw framebuffer
+    ///   is not framebuffer complete.
+    fn blit_framebuffer(
+        src_x0: i32,
+        src_y0: i32,
+        src_x1: i32,
+        src_y1: i32,
+        dst_x0: i32,
+        dst_y0: i32,
+        dst_x1: i32,
+        dst_y1: i32,
+        mask: ClearBufferMask,
+        filter: TextureFilterFunction));
+
 gl_proc!(glBufferData:
     /// Creates and initializes a buffer object's data store.
     ///
@@ -430,6 +648,84 @@ gl_proc!(glBufferData:
     ///   specified size​.
     fn buffer_data(target: BufferTarget, size: isize, data: *const (), usage: BufferUsage));
 
+gl_proc!(glBufferStorage:
+    /// Creates and initializes a buffer object's immutable data store.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBufferStorage)
+    ///
+    /// Core since version 4.4
+    ///
+    /// Creates a new immutable data store for the buffer object currently bound to `target`. The
+    /// data store is created with the specified `size` in bytes and is initialized with data from
+    /// `data` if it's not null. Unlike `buffer_data`, the size and flags of the data store can't
+    /// be changed once it's created.
+    ///
+    /// `flags` specifies how the data store may be mapped. A data store created with
+    /// `BufferAccess::persistent()` can stay mapped while it's used for drawing.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if the reserved buffer object name 0 is bound to
+    ///   `target`.
+    /// - `GL_INVALID_OPERATION` is generated if the buffer bound to `target` already has an
+    ///   immutable data store.
+    /// - `GL_INVALID_VALUE` is generated if `size` is less than or equal to zero.
+    /// - `GL_OUT_OF_MEMORY` is generated if the GL is unable to create a data store with the
+    ///   specified `size`.
+    fn buffer_storage(target: BufferTarget, size: isize, data: *const (), flags: BufferAccess));
+
+gl_proc!(glBufferSubData:
+    /// Updates a subset of a buffer object's data store.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBufferSubData)
+    ///
+    /// Core since version 1.5
+    ///
+    /// Redefines some or all of the data store for the buffer object currently bound to `target`.
+    /// Data starting at byte offset `offset` and extending for `size` bytes is copied to the data
+    /// store from the memory pointed to by `data`. Unlike `buffer_data`, the data store isn't
+    /// reallocated.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `offset` or `size` is negative, or if together they
+    ///   define a region of memory that extends beyond the buffer object's allocated data store.
+    /// - `GL_INVALID_OPERATION` is generated if the reserved buffer object name 0 is bound to
+    ///   `target`.
+    /// - `GL_INVALID_OPERATION` is generated if the buffer object being updated is mapped.
+    fn buffer_sub_data(target: BufferTarget, offset: isize, size: isize, data: *const ()));
+
+gl_proc!(glCheckFramebufferStatus:
+    /// Checks the completeness status of a framebuffer.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glCheckFramebufferStatus)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Returns the completeness status of the framebuffer object bound to `target`. The return
+    /// value is `GL_FRAMEBUFFER_COMPLETE` if the framebuffer is complete, otherwise it indicates
+    /// why the framebuffer is incomplete, see `FramebufferStatus::from_raw`. If an error occurs
+    /// zero is returned.
+    fn check_framebuffer_status(target: FramebufferTarget) -> u32);
+
+gl_proc!(glClientWaitSync:
+    /// Blocks until a sync object is signaled or a timeout expires.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glClientWaitSync)
+    ///
+    /// Core since version 3.2
+    ///
+    /// Causes the client to block until `sync` is signaled or `timeout` nanoseconds have passed.
+    /// A `timeout` of zero tests whether `sync` is signaled without blocking. If `flags` is
+    /// `FlushCommands` pending commands are flushed before blocking, otherwise waiting on a sync
+    /// object whose fence hasn't been sent to the GPU may never return.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `sync` is not the name of an existing sync object.
+    /// - `GL_INVALID_VALUE` is generated if `flags` contains any unsupported flag.
+    fn client_wait_sync(sync: SyncObject, flags: SyncFlags, timeout: u64) -> SyncWaitResult);
+
 gl_proc!(glClear:
     /// Clears buffers to preset values.
     ///
@@ -612,6 +908,19 @@ gl_proc!(glDeleteBuffers:
     /// `GL_INVALID_VALUE` is generated if `num_buffers` is negative.
     fn delete_buffers(num_buffers: i32, buffers: *const BufferName));
 
+gl_proc!(glDeleteFramebuffers:
+    /// Deletes framebuffer objects.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glDeleteFramebuffers)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Deletes the `count` framebuffer objects whose names are stored in the array addressed by
+    /// `framebuffers`. The name zero is reserved by the GL and is silently ignored, should it
+    /// occur in `framebuffers`, as are other unused names. If a framebuffer that is currently
+    /// bound is deleted, the binding reverts to the default framebuffer.
+    fn delete_framebuffers(count: i32, framebuffers: *const FramebufferName));
+
 gl_proc!(glDeleteProgram:
     /// Deletes a program object.
     ///
@@ -635,6 +944,31 @@ gl_proc!(glDeleteProgram:
     /// `get_program_param` with arguments `program_object` and `DeleteStatus`.
     fn delete_program(program_object: ProgramObject));
 
+gl_proc!(glDeleteQueries:
+    /// Deletes named query objects.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glDeleteQueries)
+    ///
+    /// Core since version 1.5
+    ///
+    /// Deletes `count` query objects named by the elements of the array `queries`. After a query
+    /// object is deleted, it has no contents, and its name is free for reuse. Zero and names
+    /// that do not correspond to existing query objects are silently ignored.
+    fn delete_queries(count: i32, queries: *const QueryObject));
+
+gl_proc!(glDeleteRenderbuffers:
+    /// Deletes renderbuffer objects.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glDeleteRenderbuffers)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Deletes the `count` renderbuffer objects whose names are stored in the array addressed by
+    /// `renderbuffers`. The name zero is reserved by the GL and is silently ignored, should it
+    /// occur in `renderbuffers`, as are other unused names. If a renderbuffer that is attached
+    /// to the currently bound framebuffer is deleted, it is first detached.
+    fn delete_renderbuffers(count: i32, renderbuffers: *const RenderbufferName));
+
 gl_proc!(glDeleteShader:
     /// Deletes a shader object.
     ///
@@ -654,6 +988,22 @@ gl_proc!(glDeleteShader:
     /// arguments `shader_object` and `DeleteStatus`.
     fn delete_shader(shader_object: ShaderObject));
 
+gl_proc!(glDeleteSync:
+    /// Deletes a sync object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glDeleteSync)
+    ///
+    /// Core since version 3.2
+    ///
+    /// Deletes the sync object specified by `sync`. If the fence command corresponding to `sync`
+    /// is still pending, the sync object is deleted once it's signaled. Deleting the null sync
+    /// object is silently ignored.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `sync` is neither null nor the name of a sync object.
+    fn delete_sync(sync: SyncObject));
+
 gl_proc!(glDeleteTextures:
     /// Deletes named textures.
     ///
@@ -713,6 +1063,21 @@ gl_proc!(glDepthFunc:
     /// testing is disabled or if no depth buffer exists it is as if the depth test always passes.
     fn depth_func(func: Comparison));
 
+gl_proc!(glDepthMask:
+    /// Enables or disables writing into the depth buffer.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glDepthMask)
+    ///
+    /// Core since version 1.0
+    ///
+    /// `flag` specifies whether the depth buffer is enabled for writing. If `flag` is `False`,
+    /// depth buffer writing is disabled. Otherwise, it is enabled. Initially, depth buffer writing
+    /// is enabled.
+    ///
+    /// Note that the depth mask also applies to `clear`, so depth writing must be enabled for
+    /// the depth buffer to be cleared.
+    fn depth_mask(flag: Boolean));
+
 gl_proc!(glDetachShader:
     /// Detaches a shader object from a program object to which it is attached.
     ///
@@ -801,6 +1166,46 @@ gl_proc!(glDrawArrays:
     ///    object.
     fn draw_arrays(mode: DrawMode, first: i32, count: i32));
 
+gl_proc!(glDrawArraysInstanced:
+    /// Draws multiple instances of a range of elements.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glDrawArraysInstanced)
+    ///
+    /// Core since version 3.1
+    ///
+    /// Behaves identically to `draw_arrays` except that `instance_count` instances of the range
+    /// of elements are executed. Vertex attributes with a non-zero divisor set with
+    /// `vertex_attrib_divisor` advance once every divisor instances rather than once per
+    /// vertex.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `count` or `instance_count` is negative.
+    /// - `GL_INVALID_OPERATION` is generated if a non-zero buffer object name is bound to an
+    ///   enabled array and the buffer object's data store is currently mapped.
+    fn draw_arrays_instanced(mode: DrawMode, first: i32, count: i32, instance_count: i32));
+
+gl_proc!(glDrawBuffers:
+    /// Specifies a list of color buffers to be drawn into.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glDrawBuffers)
+    ///
+    /// Core since version 2.0
+    ///
+    /// Defines an array of buffers into which outputs from the fragment shader data will be
+    /// written. Fragment color number `i` is written into the buffer at `bufs[i]`. For a
+    /// framebuffer object each entry must be one of the framebuffer's color attachments or
+    /// `FramebufferAttachment::none()`, in which case the corresponding output is discarded.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_ENUM` is generated if one of the values in `bufs` is not an accepted value.
+    /// - `GL_INVALID_VALUE` is generated if `n` is less than 0 or greater than
+    ///   `GL_MAX_DRAW_BUFFERS`.
+    /// - `GL_INVALID_OPERATION` is generated if a value other than `GL_NONE` appears more than
+    ///   once in `bufs`.
+    fn draw_buffers(n: i32, bufs: *const FramebufferAttachment));
+
 gl_proc!(glDrawElements:
     /// Renders primitives from array data.
     ///
@@ -842,6 +1247,31 @@ gl_proc!(glDrawElements:
     ///   mapped.
     fn draw_elements(mode: DrawMode, count: i32, index_type: IndexType, offset: usize));
 
+gl_proc!(glDrawElementsInstanced:
+    /// Draws multiple instances of a set of elements.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glDrawElementsInstanced)
+    ///
+    /// Core since version 3.1
+    ///
+    /// Behaves identically to `draw_elements` except that `instance_count` instances of the set
+    /// of elements are executed. Vertex attributes with a non-zero divisor set with
+    /// `vertex_attrib_divisor` advance once every divisor instances rather than once per
+    /// vertex.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `count` or `instance_count` is negative.
+    /// - `GL_INVALID_OPERATION` is generated if a non-zero buffer object name is bound to an
+    ///   enabled array or the element array and the buffer object's data store is currently
+    ///   mapped.
+    fn draw_elements_instanced(
+        mode: DrawMode,
+        count: i32,
+        index_type: IndexType,
+        offset: usize,
+        instance_count: i32));
+
 gl_proc!(glEnable:
     /// Enables server-side GL capabilities.
     ///
@@ -875,6 +1305,87 @@ gl_proc!(glEnableVertexAttribArray:
     /// - `GL_INVALID_OPERATION` is generated if no vertex array object is bound.
     fn enable_vertex_attrib_array(attrib: AttributeLocation));
 
+gl_proc!(glEndQuery:
+    /// Delimits the end of a query object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBeginQuery)
+    ///
+    /// Core since version 1.5
+    ///
+    /// Ends the active query object for `target`. The query's result becomes available
+    /// asynchronously once the commands issued while the query was active have completed; use
+    /// `get_query_object_i32` with `QueryResultParam::ResultAvailable` to check for it without
+    /// blocking.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if `end_query` is executed when a query object of
+    ///   the same `target` is not active.
+    fn end_query(target: QueryTarget));
+
+gl_proc!(glFenceSync:
+    /// Creates a new sync object and inserts it into the GL command stream.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glFenceSync)
+    ///
+    /// Core since version 3.2
+    ///
+    /// Creates a new fence sync object, inserts a fence command into the GL command stream, and
+    /// returns the sync object. The sync object is signaled once `condition` is satisfied, which
+    /// for `GpuCommandsComplete` is once all commands issued before the fence have completed.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `flags` is not zero.
+    fn fence_sync(condition: SyncCondition, flags: u32) -> SyncObject);
+
+gl_proc!(glFramebufferRenderbuffer:
+    /// Attaches a renderbuffer as a logical buffer of a framebuffer object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glFramebufferRenderbuffer)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Attaches a renderbuffer as one of the logical buffers of the framebuffer object bound to
+    /// `target`. `renderbuffer_target` must be `Renderbuffer`. If `renderbuffer` is zero, any
+    /// renderbuffer attached to `attachment` is detached.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if zero is bound to `target`.
+    /// - `GL_INVALID_OPERATION` is generated if `renderbuffer` is not zero or the name of an
+    ///   existing renderbuffer object.
+    fn framebuffer_renderbuffer(
+        target: FramebufferTarget,
+        attachment: FramebufferAttachment,
+        renderbuffer_target: RenderbufferTarget,
+        renderbuffer: RenderbufferName));
+
+gl_proc!(glFramebufferTexture2D:
+    /// Attaches a level of a texture object as a logical buffer of a framebuffer object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glFramebufferTexture)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Attaches a selected mipmap level or image of a texture object as one of the logical
+    /// buffers of the framebuffer object bound to `target`. `texture_target` specifies the
+    /// texture target of `texture`, or for cube map textures which face is to be attached. If
+    /// `texture` is zero, any image attached to `attachment` is detached.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if zero is bound to `target`.
+    /// - `GL_INVALID_OPERATION` is generated if `texture` is not zero and `texture_target` is
+    ///   not compatible with the texture's type.
+    /// - `GL_INVALID_VALUE` is generated if `level` is not a supported level for `texture`.
+    fn framebuffer_texture_2d(
+        target: FramebufferTarget,
+        attachment: FramebufferAttachment,
+        texture_target: Texture2dTarget,
+        texture: TextureObject,
+        level: i32));
+
 gl_proc!(glFrontFace:
     /// Defines front- and back-facing polygons.
     ///
@@ -921,6 +1432,47 @@ gl_proc!(glGenBuffers:
     /// `GL_INVALID_VALUE` is generated if `num_buffers`​ is negative.
     fn gen_buffers(num_buffers: i32, buffers: *mut BufferName));
 
+gl_proc!(glGenFramebuffers:
+    /// Generates framebuffer object names.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGenFramebuffers)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Returns `count` framebuffer object names in `framebuffers`. There is no guarantee that the
+    /// names form a contiguous set of integers; however, it is guaranteed that none of the
+    /// returned names was in use immediately before the call to `gen_framebuffers`. No
+    /// framebuffer objects are associated with the returned names until they are first bound
+    /// by calling `bind_framebuffer`.
+    fn gen_framebuffers(count: i32, framebuffers: *mut FramebufferName));
+
+gl_proc!(glGenQueries:
+    /// Generates query object names.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGenQueries)
+    ///
+    /// Core since version 1.5
+    ///
+    /// Returns `count` query object names in `queries`. There is no guarantee that the names
+    /// form a contiguous set of integers; however, it is guaranteed that none of the returned
+    /// names was in use immediately before the call to `gen_queries`. No query objects are
+    /// associated with the returned names until they are first used by calling `begin_query`.
+    fn gen_queries(count: i32, queries: *mut QueryObject));
+
+gl_proc!(glGenRenderbuffers:
+    /// Generates renderbuffer object names.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGenRenderbuffers)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Returns `count` renderbuffer object names in `renderbuffers`. There is no guarantee that
+    /// the names form a contiguous set of integers; however, it is guaranteed that none of the
+    /// returned names was in use immediately before the call to `gen_renderbuffers`. No
+    /// renderbuffer objects are associated with the returned names until they are first bound
+    /// by calling `bind_renderbuffer`.
+    fn gen_renderbuffers(count: i32, renderbuffers: *mut RenderbufferName));
+
 gl_proc!(glGenTextures:
     /// Generates texture names.
     ///
@@ -957,6 +1509,90 @@ gl_proc!(glGenVertexArrays:
     /// `GL_INVALID_VALUE` is generated if `num_arrays`​ is negative.
     fn gen_vertex_arrays(num_arrays: i32, arrays: *mut VertexArrayName));
 
+gl_proc!(glGenerateMipmap:
+    /// Generates mipmaps for a specified texture target.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGenerateMipmap)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Generates mipmaps for the texture bound to `target`. Mipmap generation replaces texel
+    /// array levels level_base + 1 through q with arrays derived from the level_base array,
+    /// regardless of their previous contents. All other mipmap arrays, including the level_base
+    /// array, are left unchanged by this computation.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_ENUM` is generated if `target` is not one of the accepted texture targets.
+    /// - `GL_INVALID_OPERATION` is generated if `target` is `CubeMap` and the texture bound to
+    ///   it is not cube complete.
+    fn generate_mipmap(target: TextureBindTarget));
+
+gl_proc!(glGetActiveAttrib:
+    /// Returns information about an active attribute variable for the specified program object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGetActiveAttrib)
+    ///
+    /// Core since version 2.0
+    ///
+    /// `index` selects the attribute and must be between 0 and the value of `ActiveAttributes`
+    /// minus one (see `get_program_param`). The attribute's name is written to `name` as a
+    /// null-terminated string of at most `buf_size` characters, and the number of characters
+    /// written (excluding the null terminator) is returned in `length` if it is not null. The
+    /// size of the attribute is returned in `size`, in units of the type returned in `data_type`.
+    ///
+    /// `data_type` returns one of the values in `VariableType`. Attribute variables declared as
+    /// arrays or matrices have a size greater than one.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `program` is not a value generated by OpenGL.
+    /// - `GL_INVALID_OPERATION` is generated if `program` is not a program object.
+    /// - `GL_INVALID_VALUE` is generated if `index` is greater than or equal to the number of
+    ///   active attribute variables in `program`.
+    /// - `GL_INVALID_VALUE` is generated if `buf_size` is less than 0.
+    fn get_active_attrib(
+        program: ProgramObject,
+        index: u32,
+        buf_size: i32,
+        length: *mut i32,
+        size: *mut i32,
+        data_type: *mut u32,
+        name: *mut u8));
+
+gl_proc!(glGetActiveUniform:
+    /// Returns information about an active uniform variable for the specified program object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGetActiveUniform)
+    ///
+    /// Core since version 2.0
+    ///
+    /// `index` selects the uniform and must be between 0 and the value of `ActiveUniforms` minus
+    /// one (see `get_program_param`). The uniform's name is written to `name` as a
+    /// null-terminated string of at most `buf_size` characters, and the number of characters
+    /// written (excluding the null terminator) is returned in `length` if it is not null. The
+    /// size of the uniform is returned in `size`, in units of the type returned in `data_type`.
+    ///
+    /// `data_type` returns one of the values in `VariableType`. Uniform variables declared as
+    /// arrays have a size equal to the highest array element used by the program plus one, and
+    /// their name is returned with "[0]" appended.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `program` is not a value generated by OpenGL.
+    /// - `GL_INVALID_OPERATION` is generated if `program` is not a program object.
+    /// - `GL_INVALID_VALUE` is generated if `index` is greater than or equal to the number of
+    ///   active uniform variables in `program`.
+    /// - `GL_INVALID_VALUE` is generated if `buf_size` is less than 0.
+    fn get_active_uniform(
+        program: ProgramObject,
+        index: u32,
+        buf_size: i32,
+        length: *mut i32,
+        size: *mut i32,
+        data_type: *mut u32,
+        name: *mut u8));
+
 gl_proc!(glGetAttribLocation:
     /// Returns the location of an attribute variable.
     ///
@@ -986,6 +1622,26 @@ gl_proc!(glGetAttribLocation:
     /// - `GL_INVALID_OPERATION` is generated if `program` has not been successfully linked.
     fn get_attrib_location(program: ProgramObject, name: *const u8) -> i32);
 
+gl_proc!(glGetError:
+    /// Returns error information.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGetError)
+    ///
+    /// Core since version 1.0
+    ///
+    /// Returns the value of the error flag. Each detectable error is assigned a numeric code.
+    /// When an error occurs, the error flag is set to the appropriate error code value. No other
+    /// errors are recorded until `get_error` is called, the error code is returned, and the flag
+    /// is reset to `ErrorCode::NoError`.
+    ///
+    /// To allow for distributed implementations, there may be several error flags. If any single
+    /// error flag has recorded an error, the value of that flag is returned and that flag is
+    /// reset to `ErrorCode::NoError` when `get_error` is called. If more than one flag has
+    /// recorded an error, `get_error` returns and clears an arbitrary error flag value. Thus,
+    /// `get_error` should always be called in a loop, until it returns `ErrorCode::NoError`, if
+    /// all error flags are to be reset.
+    fn get_error() -> ErrorCode);
+
 gl_proc!(glGetIntegerv:
     /// Returns the value for simple state variables.
     ///
@@ -1011,6 +1667,8 @@ gl_proc!(glGetIntegerv:
     ///   supported by the current context.
     /// - GL_NUM_EXTENSIONS - `params` returns one value, the number of extensions supported by
     ///   the GL implementation for the current context. See `get_string`.
+    /// - `Viewport` - `params` returns four values: the x and y window coordinates of the
+    ///   viewport, followed by its width and height. See `viewport`.
     fn get_integers(name: IntegerName, params: *mut i32));
 
 gl_proc!(glGetProgramInfoLog:
@@ -1134,6 +1792,34 @@ gl_proc!(glGetProgramiv:
         param_type: ProgramParam,
         param_out: *mut i32));
 
+gl_proc!(glGetQueryObjectiv:
+    /// Returns parameters of a query object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGetQueryObject)
+    ///
+    /// Core since version 1.5
+    ///
+    /// Querying `QueryResultParam::Result` blocks until the result of the query is available.
+    /// Querying `QueryResultParam::ResultAvailable` never blocks and returns whether the result
+    /// is available yet.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if `query` is not the name of a query object, or if
+    ///   the query object named by `query` is currently active.
+    fn get_query_object_i32(query: QueryObject, param: QueryResultParam, result: *mut i32));
+
+gl_proc!(glGetQueryObjectui64v:
+    /// Returns parameters of a query object as a 64 bit integer.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGetQueryObject)
+    ///
+    /// Core since version 3.3
+    ///
+    /// Identical to `get_query_object_i32`, but allows the full range of a `TimeElapsed` query's
+    /// result (in nanoseconds) to be read without overflowing.
+    fn get_query_object_u64(query: QueryObject, param: QueryResultParam, result: *mut u64));
+
 gl_proc!(glGetShaderInfoLog:
     /// Returns the information log for a shader object.
     ///
@@ -1248,6 +1934,39 @@ gl_proc!(glGetString:
     /// information.
     fn get_string(name: StringName) -> *const i8);
 
+gl_proc!(glGetTexImage:
+    /// Returns a texture image.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGetTexImage)
+    ///
+    /// Core since version 1.0
+    ///
+    /// Returns the texture image for the texture bound to `target` into `pixels`. `level`
+    /// specifies the level-of-detail number of the desired image and `format` and `data_type`
+    /// specify the format and type of the desired image array. The rows of the image are
+    /// returned in the same order they were specified, subject to the `PackAlignment` pixel
+    /// storage parameter.
+    ///
+    /// # Notes
+    ///
+    /// - `pixels` must point to enough memory to hold the image for `level` in the requested
+    ///   format. The size of the image can be determined from the texture's width and height
+    ///   and the number of elements in `format`.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `level` is less than 0 or greater than the maximum
+    ///   level for the texture.
+    /// - `GL_INVALID_OPERATION` is generated if `format` is `DepthComponent` and the texture
+    ///   does not have a depth format, or if the texture has a depth format and `format` is not
+    ///   `DepthComponent`.
+    fn get_tex_image(
+        target: Texture2dTarget,
+        level: i32,
+        format: TextureFormat,
+        data_type: TextureDataType,
+        pixels: *mut ()));
+
 gl_proc!(glUniform1f:
     /// Specify the value of a uniform variable for the current program object.
     ///
@@ -1702,6 +2421,59 @@ gl_proc!(glUniformMatrix3fv:
         transpose: Boolean,
         values: *const f32));
 
+gl_proc!(glUniformBlockBinding:
+    /// Assigns a binding point to an active uniform block.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glUniformBlockBinding)
+    ///
+    /// Core since version 3.1
+    ///
+    /// Binding points for active uniform blocks are assigned using `uniform_block_binding`. Each
+    /// of a program's active uniform blocks has a corresponding uniform buffer binding point.
+    /// `program` is the name of a program object for which the command `link_program` has been
+    /// issued in the past.
+    ///
+    /// If successful, `uniform_block_binding` specifies that `program` will use the data store of
+    /// the buffer object bound to the binding point `uniform_block_binding` to extract the values
+    /// of the uniforms in the uniform block identified by `uniform_block_index`.
+    ///
+    /// When a program object is linked or re-linked the uniform buffer object binding point
+    /// assigned to each of its active uniform blocks is reset to zero.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `uniform_block_index` is not an active uniform block
+    ///   index of `program`.
+    /// - `GL_INVALID_VALUE` is generated if `uniform_block_binding` is greater than or equal to
+    ///   the value of `GL_MAX_UNIFORM_BUFFER_BINDINGS`.
+    /// - `GL_INVALID_VALUE` is generated if `program` is not the name of a program object
+    ///   generated by the GL.
+    fn uniform_block_binding(
+        program: ProgramObject,
+        uniform_block_index: UniformBlockIndex,
+        uniform_block_binding: u32));
+
+gl_proc!(glGetUniformBlockIndex:
+    /// Retrieves the index of a named uniform block.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGetUniformBlockIndex)
+    ///
+    /// Core since version 3.1
+    ///
+    /// Retrieves the index of a uniform block within `program`. `uniform_block_name` must be a
+    /// null terminated string. If `uniform_block_name` does not identify an active uniform block
+    /// of `program` the special value `UniformBlockIndex::invalid()` is returned. Indices of the
+    /// active uniform blocks of a program are assigned in consecutive order, beginning with zero.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if `program` is not the name of a program object
+    ///   for which `link_program` has been called in the past.
+    fn get_uniform_block_index(
+        program: ProgramObject,
+        uniform_block_name: *const u8
+    ) -> UniformBlockIndex);
+
 gl_proc!(glGetUniformLocation:
     /// Returns the location of a uniform variable.
     ///
@@ -1854,6 +2626,33 @@ gl_proc!(glLinkProgram:
     ///   and transform feedback mode is active.
     fn link_program(program: ProgramObject));
 
+gl_proc!(glMapBufferRange:
+    /// Maps all or part of a buffer object's data store into client memory.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glMapBufferRange)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Maps `length` bytes of the data store of the buffer bound to `target`, starting `offset`
+    /// bytes into the buffer, and returns a pointer to the mapped range. `access` specifies how the
+    /// mapping may be used. Returns null if the range can't be mapped.
+    ///
+    /// Unless the mapping is persistent, the buffer can't be used by the GL until it's unmapped
+    /// with `unmap_buffer`.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `offset` or `length` is negative, or if
+    ///   `offset + length` is greater than the size of the buffer.
+    /// - `GL_INVALID_OPERATION` is generated if the buffer is already mapped.
+    /// - `GL_INVALID_OPERATION` is generated if `access` includes `BufferAccess::persistent()` but
+    ///   the buffer's data store wasn't created with it.
+    fn map_buffer_range(
+        target: BufferTarget,
+        offset: isize,
+        length: isize,
+        access: BufferAccess) -> *mut ());
+
 gl_proc!(glObjectLabel:
     /// Labels a named object for use in debug messages.
     ///
@@ -1867,7 +2666,28 @@ gl_proc!(glObjectLabel:
     /// number of characters in `label​`. If `length​` is negative, it is implied that label​
     /// contains a null-terminated string. If label​ is `NULL`, any debug label is effectively
     /// removed from the object.
-    fn set_object_label(identifier: DebugMessageId, name: u32, length: i32, label: u8));
+    fn set_object_label(identifier: DebugMessageId, name: u32, length: i32, label: *const u8));
+
+gl_proc!(glPixelStorei:
+    /// Sets pixel storage modes.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glPixelStore)
+    ///
+    /// Core since version 1.0
+    ///
+    /// Sets pixel storage modes that affect the operation of subsequent `read_pixels` as well as
+    /// the unpacking of texture patterns (see `texture_image_2d` and `texture_sub_image_2d`).
+    ///
+    /// `PackAlignment` and `UnpackAlignment` specify the alignment requirements for the start of
+    /// each pixel row in memory. The allowable values are 1 (byte-alignment), 2 (rows aligned to
+    /// even-numbered bytes), 4 (word-alignment), and 8 (rows start on double-word boundaries).
+    /// The initial value for both is 4.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if an alignment is specified as other than 1, 2, 4,
+    ///   or 8.
+    fn pixel_store_i32(param: PixelStoreParam, value: i32));
 
 gl_proc!(glPolygonMode:
     /// Selects the polygon rasterization mode.
@@ -1900,6 +2720,109 @@ gl_proc!(glPolygonMode:
     /// `edge_flag`.
     fn polygon_mode(face: Face, mode: PolygonMode));
 
+gl_proc!(glReadBuffer:
+    /// Selects a color buffer source for pixels.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glReadBuffer)
+    ///
+    /// Core since version 1.0
+    ///
+    /// Specifies the color buffer of the default framebuffer that subsequent `read_pixels` and
+    /// copy commands read from. Double buffered configurations read from `Face::Back` by default,
+    /// and single buffered configurations from `Face::Front`.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if `mode` specifies a buffer that does not exist.
+    fn read_buffer(mode: Face));
+
+gl_proc!(glReadPixels:
+    /// Reads a block of pixels from the frame buffer.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glReadPixels)
+    ///
+    /// Core since version 1.0
+    ///
+    /// Returns pixel data from the frame buffer, starting with the pixel whose lower left corner
+    /// is at location (`x`, `y`), into client memory starting at location `data`. Pixels are
+    /// returned in row order from the lowest to the highest row, left to right in each row. The
+    /// `PackAlignment` pixel storage parameter affects the layout of the returned data.
+    ///
+    /// Values for pixels that lie outside the window connected to the current GL context are
+    /// undefined.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if either `width` or `height` is negative.
+    /// - `GL_INVALID_OPERATION` is generated if `format` is `DepthComponent` and there is no
+    ///   depth buffer.
+    fn read_pixels(
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        format: TextureFormat,
+        data_type: TextureDataType,
+        data: *mut ()));
+
+gl_proc!(glRenderbufferStorage:
+    /// Establishes the data storage, format and dimensions of a renderbuffer object's image.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glRenderbufferStorage)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Equivalent to calling `renderbuffer_storage_multisample` with `samples` set to zero.
+    fn renderbuffer_storage(
+        target: RenderbufferTarget,
+        internal_format: TextureInternalFormat,
+        width: i32,
+        height: i32));
+
+gl_proc!(glRenderbufferStorageMultisample:
+    /// Establishes the data storage, format, dimensions and sample count of a renderbuffer
+    /// object's image.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glRenderbufferStorageMultisample)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Establishes the data storage, format, dimensions and number of samples of the
+    /// renderbuffer object bound to `target`. Upon success any existing data store for the
+    /// renderbuffer image is deleted and the contents of the new data store are undefined.
+    /// `samples` may be zero, in which case the renderbuffer is not multisampled, otherwise the
+    /// implementation may allocate more samples than requested.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `samples` is greater than `GL_MAX_SAMPLES`.
+    /// - `GL_INVALID_ENUM` is generated if `internal_format` is not a color-renderable,
+    ///   depth-renderable, or stencil-renderable format.
+    /// - `GL_INVALID_VALUE` is generated if either of `width` or `height` is negative, or greater
+    ///   than `GL_MAX_RENDERBUFFER_SIZE`.
+    fn renderbuffer_storage_multisample(
+        target: RenderbufferTarget,
+        samples: i32,
+        internal_format: TextureInternalFormat,
+        width: i32,
+        height: i32));
+
+gl_proc!(glScissor:
+    /// Defines the scissor box.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glScissor)
+    ///
+    /// Core since version 1.0
+    ///
+    /// `x` and `y` give the lower-left corner of the box in window coordinates. While
+    /// `ServerCapability::ScissorTest` is enabled, drawing and clearing only affect pixels inside
+    /// the box.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if either `width` or `height` is negative.
+    fn scissor(x: i32, y: i32, width: i32, height: i32));
+
 gl_proc!(glShaderSource:
     /// Replaces the source code in a shader object.
     ///
@@ -2042,6 +2965,42 @@ gl_proc!(glTexImage2D:
         data_type: TextureDataType,
         data: *const ()));
 
+gl_proc!(glTexImage3D:
+    /// Specifies a three-dimensional texture image.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glTexImage3D)
+    ///
+    /// Core since version 1.2
+    ///
+    /// Texturing maps a portion of a specified texture image onto each graphical primitive for
+    /// which texturing is enabled. A three-dimensional texture image is a sequence of
+    /// two-dimensional images stacked along the third (depth) dimension. `data` is read as a
+    /// sequence of `depth` images of `width` by `height` pixels, in the same layout used by
+    /// `texture_image_2d`.
+    ///
+    /// If `data` is null the texture's storage is still allocated but its contents are
+    /// undefined.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `level` is less than 0.
+    /// - `GL_INVALID_VALUE` is generated if `width`, `height`, or `depth` is less than 0 or
+    ///   greater than `GL_MAX_3D_TEXTURE_SIZE`.
+    /// - `GL_INVALID_VALUE` is generated if `border` is not 0.
+    /// - `GL_INVALID_OPERATION` is generated if `format` or `internal_format` is a depth format
+    ///   and `target` is `Texture3d`.
+    fn texture_image_3d(
+        target: Texture3dTarget,
+        level: i32,
+        internal_format: TextureInternalFormat,
+        width: i32,
+        height: i32,
+        depth: i32,
+        border: i32,
+        format: TextureFormat,
+        data_type: TextureDataType,
+        data: *const ()));
+
 gl_proc!(glTexParameteri:
     /// Sets texture parameters.
     ///
@@ -2055,6 +3014,93 @@ gl_proc!(glTexParameteri:
         name: TextureParameterName,
         param: i32));
 
+gl_proc!(glTexSubImage2D:
+    /// Specifies a two-dimensional texture subimage.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glTexSubImage2D)
+    ///
+    /// Core since version 1.1
+    ///
+    /// Texturing maps a portion of a specified texture image onto each graphical primitive for
+    /// which texturing is enabled. `texture_sub_image_2d` redefines a contiguous subregion of an
+    /// existing two-dimensional texture image. The texels referenced by `data` replace the
+    /// portion of the existing texture array with x indices `x_offset` and
+    /// `x_offset + width - 1`, inclusive, and y indices `y_offset` and `y_offset + height - 1`,
+    /// inclusive. This region may not include any texels outside the range of the texture array
+    /// as it was originally specified. It is not an error to specify a subtexture with zero width
+    /// or height, but such a specification has no effect.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if the texture array has not been defined by a
+    ///   previous `texture_image_2d` operation.
+    /// - `GL_INVALID_VALUE` is generated if `level` is less than 0.
+    /// - `GL_INVALID_VALUE` is generated if `x_offset < 0`, `x_offset + width > w`,
+    ///   `y_offset < 0`, or `y_offset + height > h`, where `w` and `h` are the width and height
+    ///   of the texture image being modified.
+    /// - `GL_INVALID_VALUE` is generated if `width` or `height` is less than 0.
+    fn texture_sub_image_2d(
+        target: Texture2dTarget,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: TextureFormat,
+        data_type: TextureDataType,
+        data: *const ()));
+
+gl_proc!(glTexSubImage3D:
+    /// Specifies a three-dimensional texture subimage.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glTexSubImage3D)
+    ///
+    /// Core since version 1.2
+    ///
+    /// Redefines a contiguous subregion of an existing three-dimensional texture image. The
+    /// texels referenced by `data` replace the portion of the existing texture array with x
+    /// indices `x_offset` through `x_offset + width - 1`, y indices `y_offset` through
+    /// `y_offset + height - 1`, and z indices `z_offset` through `z_offset + depth - 1`,
+    /// inclusive. This region may not include any texels outside the range of the texture array
+    /// as it was originally specified. It is not an error to specify a subtexture with zero
+    /// width, height, or depth but such a specification has no effect.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if the texture array has not been defined by a
+    ///   previous `texture_image_3d` operation.
+    /// - `GL_INVALID_VALUE` is generated if `level` is less than 0.
+    /// - `GL_INVALID_VALUE` is generated if the region extends outside of the texture.
+    /// - `GL_INVALID_VALUE` is generated if `width`, `height`, or `depth` is less than 0.
+    fn texture_sub_image_3d(
+        target: Texture3dTarget,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: TextureFormat,
+        data_type: TextureDataType,
+        data: *const ()));
+
+gl_proc!(glUnmapBuffer:
+    /// Releases the mapping of a buffer object's data store.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glMapBuffer)
+    ///
+    /// Core since version 1.5
+    ///
+    /// Unmaps the buffer bound to `target`, invalidating the pointer returned when it was mapped.
+    /// Returns `False` if the buffer's contents were corrupted while it was mapped (e.g. because
+    /// the screen mode changed), in which case the buffer's data must be specified again.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if the buffer is not mapped.
+    fn unmap_buffer(target: BufferTarget) -> Boolean);
+
 gl_proc!(glUseProgram:
     /// Installs a program as part of the current rendering state.
     ///
@@ -2112,6 +3158,69 @@ gl_proc!(glUseProgram:
     /// - `GL_INVALID_OPERATION` is generated if transform feedback mode is active.
     fn use_program(program: ProgramObject));
 
+gl_proc!(glValidateProgram:
+    /// Validates a program object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glValidateProgram)
+    ///
+    /// Core since version 2.0
+    ///
+    /// Checks to see whether the executables contained in `program` can execute given the
+    /// current OpenGL state. The information generated by the validation process will be stored
+    /// in program's information log. The validation information may consist of an empty string,
+    /// or it may be a string containing information about how the current program object
+    /// interacts with the rest of current OpenGL state. This provides a way for OpenGL
+    /// implementers to convey more information about why the current program is inefficient,
+    /// suboptimal, failing to execute, and so on.
+    ///
+    /// The status of the validation operation will be stored as part of the program object's
+    /// state. This value will be set to `true` if the validation succeeded, and `false`
+    /// otherwise. It can be queried by calling `get_program_param` with arguments `program` and
+    /// `ValidateStatus`.
+    ///
+    /// This function is typically useful only during application development. The informational
+    /// string stored in the information log is completely implementation dependent; therefore,
+    /// an application should not expect different OpenGL implementations to produce identical
+    /// information strings.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `program` is not a value generated by OpenGL.
+    /// - `GL_INVALID_OPERATION` is generated if `program` is not a program object.
+    fn validate_program(program: ProgramObject));
+
+gl_proc!(glVertexAttrib4f:
+    /// Sets the current value of a generic vertex attribute.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glVertexAttrib)
+    ///
+    /// Core since version 2.0
+    ///
+    /// The current value is read by shaders for every vertex when the vertex attrib array for
+    /// `attrib` is disabled. It's part of the context state rather than vertex array state.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `attrib` is greater than or equal to
+    ///   `GL_MAX_VERTEX_ATTRIBS`.
+    fn vertex_attrib_4f(attrib: AttributeLocation, x: f32, y: f32, z: f32, w: f32));
+
+gl_proc!(glVertexAttribDivisor:
+    /// Modifies the rate at which generic vertex attributes advance during instanced rendering.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glVertexAttribDivisor)
+    ///
+    /// Core since version 3.3
+    ///
+    /// If `divisor` is zero the attribute at `attrib` advances once per vertex. Otherwise the
+    /// attribute advances once per `divisor` instances of the set of vertices being rendered.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `attrib` is greater than or equal to
+    ///   `GL_MAX_VERTEX_ATTRIBS`.
+    fn vertex_attrib_divisor(attrib: AttributeLocation, divisor: u32));
+
 gl_proc!(glVertexAttribPointer:
     /// Defines an array of generic vertex attribute data.
     ///
@@ -2219,8 +3328,3 @@ gl_proc!(glViewport:
     ///
     /// - `GL_INVALID_VALUE` is generated if either width or height is negative.
     fn viewport(x: i32, y: i32, width: i32, height: i32));
-
-/*
-glGetError:
-    fn get_error() -> ErrorCode
-*/
@@ -121,6 +121,128 @@ impl BitOr for ClearBufferMask {
     }
 }
 
+/// Flags describing how a buffer's data store may be mapped and accessed.
+///
+/// Used with `map_buffer_range` and `buffer_storage`. Flags can be combined with `|`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BufferAccess(pub u32);
+
+impl BufferAccess {
+    /// `GL_MAP_WRITE_BIT`, the mapping may be used to modify the buffer's data.
+    pub fn write() -> BufferAccess {
+        BufferAccess(0x0002)
+    }
+
+    /// `GL_MAP_PERSISTENT_BIT`, the buffer may stay mapped while it's used by the GL.
+    pub fn persistent() -> BufferAccess {
+        BufferAccess(0x0040)
+    }
+
+    /// `GL_MAP_COHERENT_BIT`, writes to a persistent mapping are visible to the GL without an
+    /// explicit barrier.
+    pub fn coherent() -> BufferAccess {
+        BufferAccess(0x0080)
+    }
+
+    /// `GL_MAP_INVALIDATE_BUFFER_BIT`, the previous contents of the whole buffer may be discarded.
+    pub fn invalidate_buffer() -> BufferAccess {
+        BufferAccess(0x0008)
+    }
+}
+
+impl BitOr for BufferAccess {
+    type Output = BufferAccess;
+
+    fn bitor(self, rhs: BufferAccess) -> BufferAccess {
+        BufferAccess(self.0 | rhs.0)
+    }
+}
+
+/// Identifies a framebuffer attachment point.
+///
+/// Color attachments are numbered, so attachment points are represented as a raw value rather
+/// than as an enum.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FramebufferAttachment(pub u32);
+
+impl FramebufferAttachment {
+    /// No attachment (`GL_NONE`), used with `draw_buffers` to discard a fragment output.
+    pub fn none() -> FramebufferAttachment {
+        FramebufferAttachment(0)
+    }
+
+    /// The color attachment point at `index` (`GL_COLOR_ATTACHMENTi`).
+    pub fn color(index: u32) -> FramebufferAttachment {
+        FramebufferAttachment(0x8CE0 + index)
+    }
+
+    pub fn depth() -> FramebufferAttachment {
+        FramebufferAttachment(0x8D00)
+    }
+
+    pub fn stencil() -> FramebufferAttachment {
+        FramebufferAttachment(0x8D20)
+    }
+
+    pub fn depth_stencil() -> FramebufferAttachment {
+        FramebufferAttachment(0x821A)
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FramebufferName(pub u32);
+
+impl FramebufferName {
+    pub const fn null() -> FramebufferName {
+        FramebufferName(0)
+    }
+}
+
+/// The completeness status of a framebuffer, as returned by `check_framebuffer_status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FramebufferStatus {
+    Complete,
+    Undefined,
+    IncompleteAttachment,
+    IncompleteMissingAttachment,
+    IncompleteDrawBuffer,
+    IncompleteReadBuffer,
+    Unsupported,
+    IncompleteMultisample,
+    IncompleteLayerTargets,
+    Unknown(u32),
+}
+
+impl FramebufferStatus {
+    pub fn from_raw(raw: u32) -> FramebufferStatus {
+        use self::FramebufferStatus::*;
+
+        match raw {
+            0x8CD5 => Complete,
+            0x8219 => Undefined,
+            0x8CD6 => IncompleteAttachment,
+            0x8CD7 => IncompleteMissingAttachment,
+            0x8CDB => IncompleteDrawBuffer,
+            0x8CDC => IncompleteReadBuffer,
+            0x8CDD => Unsupported,
+            0x8D56 => IncompleteMultisample,
+            0x8DA8 => IncompleteLayerTargets,
+            _ => Unknown(raw),
+        }
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FramebufferTarget {
+    Framebuffer = 0x8D40,
+    ReadFramebuffer = 0x8CA8,
+    DrawFramebuffer = 0x8CA9,
+}
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Comparison {
@@ -159,6 +281,7 @@ pub enum DebugSeverity {
     High = 0x9146,
     Medium = 0x9147,
     Low = 0x9148,
+    Notification = 0x826B,
 }
 
 #[repr(u32)]
@@ -228,6 +351,8 @@ pub enum ErrorCode {
     StackOverflow = 0x0503,
     StackUnderflow = 0x0504,
     OutOfMemory = 0x0505,
+    InvalidFramebufferOperation = 0x0506,
+    ContextLost = 0x0507,
 }
 
 #[repr(u32)]
@@ -271,10 +396,36 @@ pub enum IndexType {
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum IntegerName {
+    Viewport = 0x0BA2,
+    MaxTextureSize = 0x0D33,
+
+    // Version 1.2
+    Max3dTextureSize = 0x8073,
+
+    // Version 1.3
+    Samples = 0x80A9,
+    MaxCubeMapTextureSize = 0x851C,
+
+    // Version 2.0
+    MaxDrawBuffers = 0x8824,
+    MaxVertexAttribs = 0x8869,
+    MaxTextureImageUnits = 0x8872,
+    MaxVertexTextureImageUnits = 0x8B4C,
+    MaxCombinedTextureImageUnits = 0x8B4D,
+
     // Version 3.0
+    MaxArrayTextureLayers = 0x88FF,
+    MaxColorAttachments = 0x8CDF,
+    MaxRenderbufferSize = 0x84E8,
+    MaxSamples = 0x8D57,
     MajorVersion  = 0x821B,
     MinorVersion  = 0x821C,
     NumExtensions = 0x821D,
+    ContextFlags  = 0x821E,
+
+    // Version 3.1
+    MaxUniformBufferBindings = 0x8A2F,
+    MaxUniformBlockSize = 0x8A30,
 }
 
 #[repr(u32)]
@@ -319,6 +470,35 @@ pub enum ProgramParam {
     ActiveAttributeMaxLength = 0x8B8A,
 }
 
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QueryObject(pub u32);
+
+impl QueryObject {
+    pub const fn null() -> QueryObject {
+        QueryObject(0)
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == QueryObject(0)
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryResultParam {
+    Result = 0x8866,
+    ResultAvailable = 0x8867,
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryTarget {
+    SamplesPassed = 0x8914,
+    AnySamplesPassed = 0x8C2F,
+    TimeElapsed = 0x88BF,
+}
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ServerCapability {
@@ -353,6 +533,7 @@ pub enum ServerCapability {
 
     // Introduced: OpenGL 4.3
     DebugOutput = 0x92E0,
+    DebugOutputSynchronous = 0x8242,
 }
 
 #[repr(C)]
@@ -420,6 +601,49 @@ pub enum StringName {
     Extensions = 0x1F03,
 }
 
+/// The condition that a fence sync object waits for.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncCondition {
+    GpuCommandsComplete = 0x9117,
+}
+
+/// Flags for `client_wait_sync`.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncFlags {
+    None = 0x0,
+
+    /// Flushes pending commands before waiting, which guarantees that the sync object is
+    /// eventually signaled.
+    FlushCommands = 0x1,
+}
+
+/// An opaque handle to a sync object, the `GLsync` type.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SyncObject(pub Sync);
+
+impl SyncObject {
+    pub const fn null() -> SyncObject {
+        SyncObject(0)
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == SyncObject(0)
+    }
+}
+
+/// The result of waiting on a sync object with `client_wait_sync`.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncWaitResult {
+    AlreadySignaled = 0x911A,
+    TimeoutExpired = 0x911B,
+    ConditionSatisfied = 0x911C,
+    WaitFailed = 0x911D,
+}
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Texture2dTarget {
@@ -434,12 +658,59 @@ pub enum Texture2dTarget {
     // GL_PROXY_TEXTURE_CUBE_MAP,
 }
 
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenderbufferName(pub u32);
+
+impl RenderbufferName {
+    pub const fn null() -> RenderbufferName {
+        RenderbufferName(0)
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderbufferTarget {
+    Renderbuffer = 0x8D41,
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PixelStoreParam {
+    PackAlignment = 0x0D05,
+    UnpackAlignment = 0x0CF5,
+    // GL_PACK_SWAP_BYTES,
+    // GL_PACK_LSB_FIRST,
+    // GL_PACK_ROW_LENGTH,
+    // GL_PACK_IMAGE_HEIGHT,
+    // GL_PACK_SKIP_PIXELS,
+    // GL_PACK_SKIP_ROWS,
+    // GL_PACK_SKIP_IMAGES,
+    // GL_UNPACK_SWAP_BYTES,
+    // GL_UNPACK_LSB_FIRST,
+    // GL_UNPACK_ROW_LENGTH,
+    // GL_UNPACK_IMAGE_HEIGHT,
+    // GL_UNPACK_SKIP_PIXELS,
+    // GL_UNPACK_SKIP_ROWS,
+    // GL_UNPACK_SKIP_IMAGES,
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Texture3dTarget {
+    Texture3d = 0x806F,
+    Texture2dArray = 0x8C1A,
+    // GL_PROXY_TEXTURE_3D,
+    // GL_PROXY_TEXTURE_2D_ARRAY,
+}
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TextureBindTarget {
     // GL_TEXTURE_1D,
     Texture2d = 0x0DE1,
     Texture3d = 0x806F,
+    Texture2dArray = 0x8C1A,
     CubeMap = 0x8513,
 }
 
@@ -479,6 +750,16 @@ pub enum TextureFilterFunction {
     LinearMipmapLinear = 0x2703,
 }
 
+impl TextureFilterFunction {
+    /// Returns `true` if the filter function samples from the texture's mipmaps.
+    pub fn uses_mipmaps(self) -> bool {
+        match self {
+            TextureFilterFunction::Nearest | TextureFilterFunction::Linear => false,
+            _ => true,
+        }
+    }
+}
+
 impl Into<i32> for TextureFilterFunction {
     fn into(self) -> i32 {
         unsafe { ::std::mem::transmute(self) }
@@ -492,6 +773,7 @@ pub enum TextureFormat {
     Rgba = 0x1908,
     Bgr = 0x80E0,
     Bgra = 0x80E1,
+    DepthComponent = 0x1902,
     // GL_COLOR_INDEX,
     // GL_RED,
     // GL_GREEN,
@@ -508,6 +790,7 @@ impl TextureFormat {
             TextureFormat::Rgba => 4,
             TextureFormat::Bgr => 3,
             TextureFormat::Bgra => 4,
+            TextureFormat::DepthComponent => 1,
         }
     }
 }
@@ -521,6 +804,18 @@ pub enum TextureInternalFormat {
     Four = 4,
     Rgb = 0x1907,
     Rgba = 0x1908,
+    DepthComponent16 = 0x81A5,
+    DepthComponent24 = 0x81A6,
+    DepthComponent32F = 0x8CAC,
+    Depth24Stencil8 = 0x88F0,
+    Depth32FStencil8 = 0x8CAD,
+    Rgba8 = 0x8058,
+    Rgb16F = 0x881B,
+    Rgb32F = 0x8815,
+    Rgba16F = 0x881A,
+    Rgba32F = 0x8814,
+    Srgb8 = 0x8C41,
+    Srgb8Alpha8 = 0x8C43,
     // GL_ALPHA,
     // GL_ALPHA4,
     // GL_ALPHA8,
@@ -533,8 +828,6 @@ pub enum TextureInternalFormat {
     // GL_COMPRESSED_RGB,
     // GL_COMPRESSED_RGBA,
     // GL_DEPTH_COMPONENT,
-    // GL_DEPTH_COMPONENT16,
-    // GL_DEPTH_COMPONENT24,
     // GL_DEPTH_COMPONENT32,
     // GL_LUMINANCE,
     // GL_LUMINANCE4,
@@ -563,7 +856,6 @@ pub enum TextureInternalFormat {
     // GL_RGBA2,
     // GL_RGBA4,
     // GL_RGB5_A1,
-    // GL_RGBA8,
     // GL_RGB10_A2,
     // GL_RGBA12,
     // GL_RGBA16,
@@ -572,9 +864,7 @@ pub enum TextureInternalFormat {
     // GL_SLUMINANCE_ALPHA,
     // GL_SLUMINANCE8_ALPHA8,
     // GL_SRGB,
-    // GL_SRGB8,
     // GL_SRGB_ALPHA,
-    // GL_SRGB8_ALPHA8,
 }
 
 #[repr(C)]
@@ -596,10 +886,13 @@ impl TextureObject {
 pub enum TextureParameterName {
     MagFilter = 0x2800,
     MinFilter = 0x2801,
+    WrapS = 0x2802,
+    WrapT = 0x2803,
+    WrapR = 0x8072,
+    CompareMode = 0x884C,
+    CompareFunc = 0x884D,
     // GL_DEPTH_STENCIL_TEXTURE_MODE
     // GL_TEXTURE_BASE_LEVEL,
-    // GL_TEXTURE_COMPARE_FUNC,
-    // GL_TEXTURE_COMPARE_MODE,
     // GL_TEXTURE_LOD_BIAS,
     // GL_TEXTURE_MAG_FILTER,
     // GL_TEXTURE_MIN_LOD,
@@ -609,21 +902,48 @@ pub enum TextureParameterName {
     // GL_TEXTURE_SWIZZLE_G,
     // GL_TEXTURE_SWIZZLE_B,
     // GL_TEXTURE_SWIZZLE_A,
-    // GL_TEXTURE_WRAP_S,
-    // GL_TEXTURE_WRAP_T,
-    // GL_TEXTURE_WRAP_R,
+}
+
+/// The value of the `CompareMode` texture parameter.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureCompareMode {
+    None = 0,
+    CompareRefToTexture = 0x884E,
+}
+
+impl Into<i32> for TextureCompareMode {
+    fn into(self) -> i32 {
+        unsafe { ::std::mem::transmute(self) }
+    }
+}
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureWrapFunction {
+    Repeat = 0x2901,
+    ClampToEdge = 0x812F,
+    MirroredRepeat = 0x8370,
+    // GL_CLAMP_TO_BORDER,
+    // GL_MIRROR_CLAMP_TO_EDGE,
+}
+
+impl Into<i32> for TextureWrapFunction {
+    fn into(self) -> i32 {
+        unsafe { ::std::mem::transmute(self) }
+    }
 }
 
 #[repr(i32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TextureParameterTarget {
     Texture2d = 0x0DE1,
+    CubeMap = 0x8513,
+    Texture3d = 0x806F,
+    Texture2dArray = 0x8C1A,
     // GL_TEXTURE_1D,
-    // GL_TEXTURE_3D,
     // GL_TEXTURE_1D_ARRAY,
-    // GL_TEXTURE_2D_ARRAY,
     // GL_TEXTURE_RECTANGLE,
-    // GL_TEXTURE_CUBE_MAP,
 }
 
 #[repr(C)]
@@ -636,6 +956,89 @@ impl UniformLocation {
     }
 }
 
+/// The index of a named uniform block in a program, as returned by `get_uniform_block_index`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UniformBlockIndex(pub u32);
+
+impl UniformBlockIndex {
+    /// The value returned by `get_uniform_block_index` if there is no active uniform block with
+    /// the specified name (`GL_INVALID_INDEX`).
+    pub const fn invalid() -> UniformBlockIndex {
+        UniformBlockIndex(0xFFFFFFFF)
+    }
+}
+
+/// The data type of an active shader variable, as returned by `get_active_uniform` and
+/// `get_active_attrib`.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VariableType {
+    Float = 0x1406,
+    FloatVec2 = 0x8B50,
+    FloatVec3 = 0x8B51,
+    FloatVec4 = 0x8B52,
+    Int = 0x1404,
+    IntVec2 = 0x8B53,
+    IntVec3 = 0x8B54,
+    IntVec4 = 0x8B55,
+    UnsignedInt = 0x1405,
+    UnsignedIntVec2 = 0x8DC6,
+    UnsignedIntVec3 = 0x8DC7,
+    UnsignedIntVec4 = 0x8DC8,
+    Bool = 0x8B56,
+    BoolVec2 = 0x8B57,
+    BoolVec3 = 0x8B58,
+    BoolVec4 = 0x8B59,
+    FloatMat2 = 0x8B5A,
+    FloatMat3 = 0x8B5B,
+    FloatMat4 = 0x8B5C,
+    Sampler1d = 0x8B5D,
+    Sampler2d = 0x8B5E,
+    Sampler3d = 0x8B5F,
+    SamplerCube = 0x8B60,
+    Sampler2dShadow = 0x8B62,
+    Sampler2dArray = 0x8DC1,
+}
+
+impl VariableType {
+    /// Converts a raw type value returned by OpenGL, returning `None` for unsupported types.
+    pub fn from_raw(raw: u32) -> Option<VariableType> {
+        use self::VariableType::*;
+
+        let variable_type = match raw {
+            0x1406 => Float,
+            0x8B50 => FloatVec2,
+            0x8B51 => FloatVec3,
+            0x8B52 => FloatVec4,
+            0x1404 => Int,
+            0x8B53 => IntVec2,
+            0x8B54 => IntVec3,
+            0x8B55 => IntVec4,
+            0x1405 => UnsignedInt,
+            0x8DC6 => UnsignedIntVec2,
+            0x8DC7 => UnsignedIntVec3,
+            0x8DC8 => UnsignedIntVec4,
+            0x8B56 => Bool,
+            0x8B57 => BoolVec2,
+            0x8B58 => BoolVec3,
+            0x8B59 => BoolVec4,
+            0x8B5A => FloatMat2,
+            0x8B5B => FloatMat3,
+            0x8B5C => FloatMat4,
+            0x8B5D => Sampler1d,
+            0x8B5E => Sampler2d,
+            0x8B5F => Sampler3d,
+            0x8B60 => SamplerCube,
+            0x8B62 => Sampler2dShadow,
+            0x8DC1 => Sampler2dArray,
+            _ => return None,
+        };
+
+        Some(variable_type)
+    }
+}
+
 /// TODO: Use NonZero here so that Option<VertexArrayName>::None can be used instead of 0.
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -659,3 +1062,56 @@ impl Default for WindingOrder {
         WindingOrder::CounterClockwise
     }
 }
+
+// ===============
+// CONTEXT OPTIONS
+// ===============
+
+/// Options used by the platform layer when creating a context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextOptions {
+    /// The minimum OpenGL version to request as `(major, minor)`.
+    ///
+    /// If `None` the platform's default context is created, which is usually the newest
+    /// compatibility profile version the driver supports.
+    pub version: Option<(u32, u32)>,
+
+    /// The profile to request. Only used if `version` is specified.
+    pub profile: ContextProfile,
+
+    /// Whether to request a debug context, which enables more thorough debug output from the
+    /// driver at the cost of performance.
+    pub debug: bool,
+
+    pub depth_bits: u8,
+    pub stencil_bits: u8,
+
+    /// Whether to request an sRGB-capable default framebuffer, which is needed for
+    /// `ServerCapability::FramebufferSrgb` to have an effect on the window.
+    pub srgb: bool,
+
+    /// The number of samples per pixel to request for the default framebuffer, or 0 for no
+    /// multisampling. The platform may provide fewer samples if the requested count isn't
+    /// supported.
+    pub samples: u32,
+}
+
+impl Default for ContextOptions {
+    fn default() -> ContextOptions {
+        ContextOptions {
+            version: None,
+            profile: ContextProfile::Compatibility,
+            debug: false,
+            depth_bits: 24,
+            stencil_bits: 8,
+            srgb: true,
+            samples: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContextProfile {
+    Core,
+    Compatibility,
+}
@@ -8,21 +8,358 @@ use std::{mem, ptr};
 use std::ffi::CString;
 
 use self::winapi::*;
+use types::{ContextOptions, ContextProfile};
 
 pub type DeviceContext = HDC;
 pub type Context = (HDC, HGLRC);
 
-pub unsafe fn create_context(device_context: DeviceContext) -> Option<Context> {
+// Constants from WGL_ARB_create_context and WGL_ARB_create_context_profile.
+const WGL_CONTEXT_MAJOR_VERSION_ARB: i32 = 0x2091;
+const WGL_CONTEXT_MINOR_VERSION_ARB: i32 = 0x2092;
+const WGL_CONTEXT_FLAGS_ARB: i32 = 0x2094;
+const WGL_CONTEXT_PROFILE_MASK_ARB: i32 = 0x9126;
+const WGL_CONTEXT_DEBUG_BIT_ARB: i32 = 0x0001;
+const WGL_CONTEXT_CORE_PROFILE_BIT_ARB: i32 = 0x0001;
+const WGL_CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB: i32 = 0x0002;
+
+// Constants from WGL_ARB_pixel_format, WGL_ARB_framebuffer_sRGB, and WGL_ARB_multisample.
+const WGL_DRAW_TO_WINDOW_ARB: i32 = 0x2001;
+const WGL_SUPPORT_OPENGL_ARB: i32 = 0x2010;
+const WGL_DOUBLE_BUFFER_ARB: i32 = 0x2011;
+const WGL_PIXEL_TYPE_ARB: i32 = 0x2013;
+const WGL_COLOR_BITS_ARB: i32 = 0x2014;
+const WGL_DEPTH_BITS_ARB: i32 = 0x2022;
+const WGL_STENCIL_BITS_ARB: i32 = 0x2023;
+const WGL_TYPE_RGBA_ARB: i32 = 0x202B;
+const WGL_FRAMEBUFFER_SRGB_CAPABLE_ARB: i32 = 0x20A9;
+const WGL_SAMPLE_BUFFERS_ARB: i32 = 0x2041;
+const WGL_SAMPLES_ARB: i32 = 0x2042;
+
+type CreateContextAttribsARB = extern "system" fn(HDC, HGLRC, *const i32) -> HGLRC;
+type ChoosePixelFormatARB = extern "system" fn(HDC, *const i32, *const f32, u32, *mut i32, *mut u32) -> BOOL;
+
+/// Creates an OpenGL context for the device context.
+///
+/// If `share` is specified the new context shares its objects (textures, buffers, programs, etc.)
+/// with `share` and every other context `share` already shares with.
+pub unsafe fn create_context(
+    device_context: DeviceContext,
+    options: &ContextOptions,
+    share: Option<Context>,
+) -> Option<Context> {
+    set_pixel_format(device_context, options);
+
     let render_context = opengl32::wglCreateContext(device_context);
     if render_context.is_null() {
         let error = kernel32::GetLastError();
         println!("WARNING: Failed to created OpenGL context, last error: {:#x}", error);
+        return None;
+    }
+
+    // Requesting a specific version, profile, or a debug context requires
+    // `wglCreateContextAttribsARB`, which can only be loaded while a context is current. The
+    // legacy context is only used to load it and is deleted once the real context is created.
+    if options.version.is_none() && !options.debug {
+        if let Some((_, share_context)) = share {
+            if opengl32::wglShareLists(share_context, render_context) == 0 {
+                let error = kernel32::GetLastError();
+                println!("WARNING: Failed to share objects between OpenGL contexts, last error: {:#x}", error);
+                opengl32::wglDeleteContext(render_context);
+                return None;
+            }
+        }
+
+        return Some((device_context, render_context));
+    }
+
+    let old = make_current((device_context, render_context));
+    let create_context_attribs = load_proc("wglCreateContextAttribsARB");
+    make_current(old);
+
+    let create_context_attribs: CreateContextAttribsARB = match create_context_attribs {
+        Some(create_context_attribs) => mem::transmute(create_context_attribs),
+        None => {
+            println!("WARNING: wglCreateContextAttribsARB is not supported, unable to request context version or debug context");
+            opengl32::wglDeleteContext(render_context);
+            return None;
+        },
+    };
+
+    let mut attribs = Vec::new();
+    if let Some((major, minor)) = options.version {
+        let profile_bit = match options.profile {
+            ContextProfile::Core => WGL_CONTEXT_CORE_PROFILE_BIT_ARB,
+            ContextProfile::Compatibility => WGL_CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB,
+        };
+
+        attribs.extend_from_slice(&[
+            WGL_CONTEXT_MAJOR_VERSION_ARB, major as i32,
+            WGL_CONTEXT_MINOR_VERSION_ARB, minor as i32,
+            WGL_CONTEXT_PROFILE_MASK_ARB, profile_bit,
+        ]);
+    }
+    if options.debug {
+        attribs.extend_from_slice(&[WGL_CONTEXT_FLAGS_ARB, WGL_CONTEXT_DEBUG_BIT_ARB]);
+    }
+    attribs.push(0);
+
+    let share_context = share.map(|(_, share_context)| share_context).unwrap_or(ptr::null_mut());
+    let attrib_context = create_context_attribs(device_context, share_context, attribs.as_ptr());
+    opengl32::wglDeleteContext(render_context);
+
+    if attrib_context.is_null() {
+        let error = kernel32::GetLastError();
+        println!("WARNING: Failed to create OpenGL context with {:?}, last error: {:#x}", options, error);
         None
     } else {
-        Some((device_context, render_context))
+        Some((device_context, attrib_context))
+    }
+}
+
+/// Sets the pixel format of the window's default framebuffer.
+///
+/// A window's pixel format can only be set once, so if the window already has one it's left as-is
+/// and the requested depth, stencil, sRGB, and multisample options are ignored.
+unsafe fn set_pixel_format(device_context: DeviceContext, options: &ContextOptions) {
+    if gdi32::GetPixelFormat(device_context) != 0 {
+        return;
+    }
+
+    let pfd = pixel_format_descriptor(options);
+
+    // The legacy pixel format descriptor can't request an sRGB-capable or multisampled
+    // framebuffer, so fall back to a regular format if `wglChoosePixelFormatARB` isn't available.
+    let arb_pixel_format = if options.srgb || options.samples > 1 {
+        choose_pixel_format_arb(options)
+    } else {
+        None
+    };
+
+    let pixel_format = match arb_pixel_format {
+        Some((pixel_format, srgb, samples)) => {
+            if srgb != options.srgb {
+                println!("WARNING: Unable to find an sRGB-capable pixel format, gamma correction might not work");
+            }
+            if samples < options.samples {
+                println!("WARNING: Unable to find a pixel format with {} samples, using {} samples instead", options.samples, samples);
+            }
+            pixel_format
+        },
+        None => {
+            if options.srgb {
+                println!("WARNING: Unable to find an sRGB-capable pixel format, gamma correction might not work");
+            }
+            if options.samples > 1 {
+                println!("WARNING: Unable to find a multisampled pixel format, antialiasing will not work");
+            }
+            gdi32::ChoosePixelFormat(device_context, &pfd)
+        },
+    };
+    if pixel_format == 0 {
+        let error_code = kernel32::GetLastError();
+        println!("WARNING: Unable to find appropriate pixel format, OpenGL rendering might not work, last error: 0x{:x}", error_code);
+    }
+
+    let result = gdi32::SetPixelFormat(device_context, pixel_format, &pfd);
+    if result == 0 {
+        let error_code = kernel32::GetLastError();
+        println!("WARNING: Failed to set pixel format, OpenGL rendering might not work, last error: 0x{:x}", error_code);
     }
 }
 
+/// Finds a pixel format with the requested sRGB and multisample options using
+/// `wglChoosePixelFormatARB`.
+///
+/// If no format supports everything that was requested, the sample count is halved until a
+/// format is found (down to no multisampling at all), and then the search is repeated without
+/// sRGB. Returns the pixel format along with whether it's sRGB-capable and its sample count.
+///
+/// Like `wglCreateContextAttribsARB`, `wglChoosePixelFormatARB` can only be loaded while a
+/// context is current. A window's pixel format can only be set once, so the temporary context is
+/// created for a hidden window rather than the window the format is for.
+unsafe fn choose_pixel_format_arb(options: &ContextOptions) -> Option<(i32, bool, u32)> {
+    let hidden_window = match create_hidden_window(1, 1) {
+        Some(hidden_window) => hidden_window,
+        None => return None,
+    };
+    let (_, hidden_device_context) = hidden_window;
+
+    let pfd = pixel_format_descriptor(options);
+    let hidden_pixel_format = gdi32::ChoosePixelFormat(hidden_device_context, &pfd);
+    gdi32::SetPixelFormat(hidden_device_context, hidden_pixel_format, &pfd);
+
+    let temp_context = opengl32::wglCreateContext(hidden_device_context);
+    if temp_context.is_null() {
+        destroy_hidden_window(hidden_window);
+        return None;
+    }
+
+    let old = make_current((hidden_device_context, temp_context));
+    let choose_pixel_format = load_proc("wglChoosePixelFormatARB");
+
+    let mut srgb_candidates = vec![options.srgb];
+    if options.srgb {
+        srgb_candidates.push(false);
+    }
+
+    let mut sample_candidates = Vec::new();
+    let mut samples = options.samples;
+    while samples > 1 {
+        sample_candidates.push(samples);
+        samples /= 2;
+    }
+    sample_candidates.push(0);
+
+    let mut found = None;
+    if let Some(choose_pixel_format) = choose_pixel_format {
+        let choose_pixel_format: ChoosePixelFormatARB = mem::transmute(choose_pixel_format);
+
+        'search: for &srgb in &srgb_candidates {
+            for &samples in &sample_candidates {
+                let mut attribs = vec![
+                    WGL_DRAW_TO_WINDOW_ARB, TRUE,
+                    WGL_SUPPORT_OPENGL_ARB, TRUE,
+                    WGL_DOUBLE_BUFFER_ARB, TRUE,
+                    WGL_PIXEL_TYPE_ARB, WGL_TYPE_RGBA_ARB,
+                    WGL_COLOR_BITS_ARB, 32,
+                    WGL_DEPTH_BITS_ARB, options.depth_bits as i32,
+                    WGL_STENCIL_BITS_ARB, options.stencil_bits as i32,
+                ];
+                if srgb {
+                    attribs.extend_from_slice(&[WGL_FRAMEBUFFER_SRGB_CAPABLE_ARB, TRUE]);
+                }
+                if samples > 1 {
+                    attribs.extend_from_slice(&[
+                        WGL_SAMPLE_BUFFERS_ARB, 1,
+                        WGL_SAMPLES_ARB, samples as i32,
+                    ]);
+                }
+                attribs.push(0);
+
+                let mut pixel_format = 0;
+                let mut num_formats = 0;
+                let result = choose_pixel_format(
+                    hidden_device_context,
+                    attribs.as_ptr(),
+                    ptr::null(),
+                    1,
+                    &mut pixel_format,
+                    &mut num_formats);
+                if result == TRUE && num_formats > 0 {
+                    found = Some((pixel_format, srgb, samples));
+                    break 'search;
+                }
+            }
+        }
+    }
+
+    make_current(old);
+    opengl32::wglDeleteContext(temp_context);
+    destroy_hidden_window(hidden_window);
+
+    found
+}
+
+fn pixel_format_descriptor(options: &ContextOptions) -> PIXELFORMATDESCRIPTOR {
+    PIXELFORMATDESCRIPTOR {
+        nSize: mem::size_of::<PIXELFORMATDESCRIPTOR>() as WORD,
+        nVersion: 1,
+        dwFlags: PFD_DRAW_TO_WINDOW | PFD_SUPPORT_OPENGL | PFD_DOUBLEBUFFER,
+        iPixelType: PFD_TYPE_RGBA,
+        cColorBits: 32,
+        cRedBits: 0,
+        cRedShift: 0,
+        cGreenBits: 0,
+        cGreenShift: 0,
+        cBlueBits: 0,
+        cBlueShift: 0,
+        cAlphaBits: 0,
+        cAlphaShift: 0,
+        cAccumBits: 0,
+        cAccumRedBits: 0,
+        cAccumGreenBits: 0,
+        cAccumBlueBits: 0,
+        cAccumAlphaBits: 0,
+        cDepthBits: options.depth_bits,
+        cStencilBits: options.stencil_bits,
+        cAuxBuffers: 0,
+        iLayerType: PFD_MAIN_PLANE,
+        bReserved: 0,
+        dwLayerMask: 0,
+        dwVisibleMask: 0,
+        dwDamageMask: 0
+    }
+}
+
+/// A hidden window that provides the default framebuffer for an offscreen context.
+pub type HiddenWindow = (HWND, HDC);
+
+static HIDDEN_WINDOW_CLASS: &'static str = "bootstrap-gl-hidden";
+
+/// Creates a hidden window with a client area of `width` by `height` pixels.
+///
+/// OpenGL on Windows can only create a context for a window's device context, so offscreen
+/// contexts are created for a window that's never shown.
+pub unsafe fn create_hidden_window(width: i32, height: i32) -> Option<HiddenWindow> {
+    let instance = kernel32::GetModuleHandleW(ptr::null());
+    let class_name = to_wide(HIDDEN_WINDOW_CLASS);
+
+    let class_info = WNDCLASSEXW {
+        cbSize: mem::size_of::<WNDCLASSEXW>() as u32,
+        style: CS_OWNDC,
+        lpfnWndProc: Some(user32::DefWindowProcW),
+        cbClsExtra: 0,
+        cbWndExtra: 0,
+        hInstance: instance,
+        hIcon: ptr::null_mut(),
+        hCursor: ptr::null_mut(),
+        hbrBackground: ptr::null_mut(),
+        lpszMenuName: ptr::null_mut(),
+        lpszClassName: class_name.as_ptr(),
+        hIconSm: ptr::null_mut(),
+    };
+
+    // The class is only registered once per process, later registrations fail harmlessly.
+    user32::RegisterClassExW(&class_info);
+
+    // A popup window has no border, so its client area is the full size of the window.
+    let window = user32::CreateWindowExW(
+        0,
+        class_name.as_ptr(),
+        class_name.as_ptr(),
+        WS_POPUP,
+        0,
+        0,
+        width,
+        height,
+        ptr::null_mut(),
+        ptr::null_mut(),
+        instance,
+        ptr::null_mut());
+    if window.is_null() {
+        let error = kernel32::GetLastError();
+        println!("WARNING: Failed to create hidden window, last error: {:#x}", error);
+        return None;
+    }
+
+    Some((window, user32::GetDC(window)))
+}
+
+/// Returns the device context used to create a context for a hidden window.
+pub fn hidden_window_device_context(window: HiddenWindow) -> DeviceContext {
+    window.1
+}
+
+pub unsafe fn destroy_hidden_window(window: HiddenWindow) {
+    let (window, device_context) = window;
+    user32::ReleaseDC(window, device_context);
+    user32::DestroyWindow(window);
+}
+
+fn to_wide(string: &str) -> Vec<u16> {
+    string.encode_utf16().chain(Some(0)).collect()
+}
+
 pub unsafe fn destroy_context(context: Context) {
     let (_, render_context) = context;
     let result = opengl32::wglMakeCurrent(::std::ptr::null_mut(), ::std::ptr::null_mut());
@@ -33,6 +370,10 @@ pub unsafe fn destroy_context(context: Context) {
     assert!(result == 1, "Failed to delete context: {:?}", render_context);
 }
 
+/// Loads a pointer to the named OpenGL proc from the current context.
+///
+/// Returns `None` if the proc isn't supported by the current context, or if no context is
+/// current.
 pub unsafe fn load_proc(proc_name: &str) -> Option<extern "system" fn()> {
     let string = CString::new(proc_name).unwrap();
     let cstr = string.as_ptr();
@@ -47,6 +388,7 @@ pub unsafe fn load_proc(proc_name: &str) -> Option<extern "system" fn()> {
             kernel32::GetLastError(),
             actual_dc,
             actual_context);
+        return None;
     }
 
     Some(mem::transmute(ptr))
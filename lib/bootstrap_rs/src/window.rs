@@ -83,6 +83,10 @@ pub enum Message {
     Close,
     Destroy,
     Paint,
+
+    /// The new width and height of the window's client area in pixels.
+    Resize(i32, i32),
+
     KeyUp(ScanCode),
     KeyDown(ScanCode),
 
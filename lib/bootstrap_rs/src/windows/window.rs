@@ -3,7 +3,6 @@ use std::{mem, ptr};
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use super::input::{register_raw_input, handle_raw_input};
-use super::gdi32;
 use super::ToCU16Str;
 use super::kernel32;
 use super::winapi::*;
@@ -95,49 +94,6 @@ impl Window {
             _ => panic!("invalid result from winmm::timeBeginPeriod()"),
         }
 
-        let pfd = PIXELFORMATDESCRIPTOR {
-            nSize: mem::size_of::<PIXELFORMATDESCRIPTOR>() as WORD,
-            nVersion: 1,
-            dwFlags: PFD_DRAW_TO_WINDOW | PFD_SUPPORT_OPENGL | PFD_DOUBLEBUFFER,
-            iPixelType: PFD_TYPE_RGBA,
-            cColorBits: 32,
-            cRedBits: 0,
-            cRedShift: 0,
-            cGreenBits: 0,
-            cGreenShift: 0,
-            cBlueBits: 0,
-            cBlueShift: 0,
-            cAlphaBits: 0,
-            cAlphaShift: 0,
-            cAccumBits: 0,
-            cAccumRedBits: 0,
-            cAccumGreenBits: 0,
-            cAccumBlueBits: 0,
-            cAccumAlphaBits: 0,
-            cDepthBits: 24,
-            cStencilBits: 8,
-            cAuxBuffers: 0,
-            iLayerType: PFD_MAIN_PLANE,
-            bReserved: 0,
-            dwLayerMask: 0,
-            dwVisibleMask: 0,
-            dwDamageMask: 0
-        };
-
-        unsafe {
-            let pixel_format = gdi32::ChoosePixelFormat(device_context, &pfd);
-            if pixel_format == 0 {
-                let error_code = kernel32::GetLastError();
-                println!("WARNING: Unable to find appropriate pixel format, OpenGL rendering might not work, last error: 0x{:x}", error_code);
-            }
-
-            let result = gdi32::SetPixelFormat(device_context, pixel_format, &pfd);
-            if result == 0 {
-                let error_code = kernel32::GetLastError();
-                println!("WARNING: Failed to set pixel format, OpenGL rendering might not work, last error: 0x{:x}", error_code);
-            }
-        }
-
         window
     }
 
@@ -298,6 +254,11 @@ unsafe extern "system" fn message_callback(
                 return 0;
             },
             WM_DESTROY => messages.push_back(Destroy),
+            WM_SIZE => {
+                let width = ( lParam as u16 ) as i32;
+                let height = ( ( lParam >> 16 ) as u16 ) as i32;
+                messages.push_back(Resize(width, height));
+            },
             //WM_PAINT => messages.push_back(Paint), // TODO We need a user defined window proc to allow painting outside of the main loop.
             WM_SYSKEYDOWN | WM_KEYDOWN => messages.push_back(KeyDown(convert_windows_scancode(wParam, lParam))),
             WM_SYSKEYUP | WM_KEYUP => messages.push_back(KeyUp(convert_windows_scancode(wParam, lParam))),
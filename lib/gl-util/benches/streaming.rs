@@ -0,0 +1,114 @@
+//! Compares re-specifying a vertex buffer's data every frame with writing it through a mapping.
+
+#![feature(test)]
+
+extern crate gl_util as gl;
+extern crate test;
+
+use gl::*;
+use gl::context::Context;
+use test::Bencher;
+
+/// The number of quads written each iteration.
+const QUAD_COUNT: usize = 1024;
+
+/// Two triangles with 3 position elements per vertex.
+const FLOATS_PER_QUAD: usize = 6 * 3;
+
+fn write_quads(data: &mut [f32], frame: usize) {
+    let offset = frame as f32 * 0.001;
+    for (index, quad) in data.chunks_mut(FLOATS_PER_QUAD).enumerate() {
+        let x = (index % 32) as f32 / 16.0 - 1.0 + offset;
+        let y = (index / 32) as f32 / 16.0 - 1.0;
+        let size = 1.0 / 16.0;
+
+        quad.copy_from_slice(&[
+            x,        y,        0.0,
+            x + size, y,        0.0,
+            x + size, y + size, 0.0,
+            x,        y,        0.0,
+            x + size, y + size, 0.0,
+            x,        y + size, 0.0,
+        ]);
+    }
+}
+
+fn create_vertex_array(context: &Context, vertex_buffer: VertexBuffer) -> VertexArray {
+    let vertex_array = VertexArray::new(context, vertex_buffer);
+    vertex_array.map_attrib_location("position", AttributeLocation::from_index(0));
+    vertex_array
+}
+
+fn set_attribs(vertex_buffer: &mut VertexBuffer) {
+    vertex_buffer.set_attrib_f32(
+        "position",
+        AttribLayout {
+            elements: 3,
+            offset: 0,
+            stride: 0,
+        },
+    );
+}
+
+#[bench]
+fn stream_quads_set_data(bencher: &mut Bencher) {
+    let context = Context::offscreen(256, 256).unwrap();
+    let mut data = vec![0.0; QUAD_COUNT * FLOATS_PER_QUAD];
+
+    let mut vertex_buffer = VertexBuffer::new(&context);
+    vertex_buffer.set_data_f32(&*data);
+    set_attribs(&mut vertex_buffer);
+    let mut vertex_array = create_vertex_array(&context, vertex_buffer);
+
+    let mut frame = 0;
+    bencher.iter(|| {
+        write_quads(&mut *data, frame);
+        vertex_array.vertex_buffer_mut().unwrap().set_data_f32(&*data);
+        DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles).draw();
+        frame += 1;
+    });
+}
+
+#[bench]
+fn stream_quads_map(bencher: &mut Bencher) {
+    let context = Context::offscreen(256, 256).unwrap();
+
+    let mut vertex_buffer = VertexBuffer::new(&context);
+    vertex_buffer.allocate_f32(QUAD_COUNT * FLOATS_PER_QUAD, false);
+    set_attribs(&mut vertex_buffer);
+    let mut vertex_array = create_vertex_array(&context, vertex_buffer);
+
+    let mut frame = 0;
+    bencher.iter(|| {
+        {
+            let vertex_buffer = vertex_array.vertex_buffer_mut().unwrap();
+            let mut mapped = vertex_buffer.map_write();
+            write_quads(mapped.as_f32_mut(), frame);
+        }
+        DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles).draw();
+        frame += 1;
+    });
+}
+
+#[bench]
+fn stream_quads_map_persistent(bencher: &mut Bencher) {
+    let context = Context::offscreen(256, 256).unwrap();
+
+    let mut vertex_buffer = VertexBuffer::new(&context);
+    // NOTE: This overwrites data that the previous draw may still be reading, a real renderer
+    // would write to a different region of the buffer each frame.
+    vertex_buffer.allocate_f32(QUAD_COUNT * FLOATS_PER_QUAD, true);
+    set_attribs(&mut vertex_buffer);
+    let mut vertex_array = create_vertex_array(&context, vertex_buffer);
+
+    let mut frame = 0;
+    bencher.iter(|| {
+        {
+            let vertex_buffer = vertex_array.vertex_buffer_mut().unwrap();
+            let mut mapped = vertex_buffer.map_write();
+            write_quads(mapped.as_f32_mut(), frame);
+        }
+        DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles).draw();
+        frame += 1;
+    });
+}
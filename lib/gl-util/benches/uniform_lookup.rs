@@ -0,0 +1,149 @@
+//! Compares looking up uniform locations through `glGetUniformLocation` every time with the
+//! location cache on `Program`.
+//!
+//! Mimics the way polygon sets uniforms: the same set of uniform names is set for every draw, and
+//! many of those names don't exist in the program.
+
+#![feature(test)]
+
+extern crate gl_util as gl;
+extern crate test;
+
+use gl::*;
+use gl::context::Context;
+use gl::shader::*;
+use test::Bencher;
+
+static VERT_SOURCE: &'static str = r#"
+#version 330 core
+
+uniform mat4 model_transform;
+
+in vec4 position;
+
+void main() {
+    gl_Position = model_transform * position;
+}
+"#;
+
+static FRAG_SOURCE: &'static str = r#"
+#version 330 core
+
+uniform vec4 surface_color;
+uniform float surface_shininess;
+
+out vec4 fragment_color;
+
+void main() {
+    fragment_color = surface_color * surface_shininess;
+}
+"#;
+
+/// The uniforms set for each draw, only three of which exist in the program.
+static UNIFORM_NAMES: &'static [&'static str] = &[
+    "model_transform",
+    "normal_transform",
+    "view_normal_transform",
+    "view_transform",
+    "model_view_transform",
+    "projection_transform",
+    "model_view_projection",
+    "global_ambient",
+    "camera_position",
+    "surface_color",
+    "surface_specular",
+    "surface_shininess",
+    "light_type",
+    "light_color",
+    "light_strength",
+    "light_position",
+];
+
+static IDENTITY: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 1.0, 0.0,
+    0.0, 0.0, 0.0, 1.0];
+
+/// The number of draws in each iteration, roughly one frame's worth.
+const DRAWS_PER_ITER: usize = 100;
+
+fn create_vertex_array(context: &Context) -> VertexArray {
+    let mut vertex_buffer = VertexBuffer::new(context);
+    vertex_buffer.set_data_f32(&[
+        -1.0, -1.0, 0.0, 1.0,
+         1.0, -1.0, 0.0, 1.0,
+         0.0,  1.0, 0.0, 1.0,
+    ]);
+    vertex_buffer.set_attrib_f32(
+        "position",
+        AttribLayout {
+            elements: 4,
+            stride: 0,
+            offset: 0,
+        },
+    );
+    VertexArray::new(context, vertex_buffer)
+}
+
+#[bench]
+fn uniform_lookup_uncached(bencher: &mut Bencher) {
+    let context = Context::offscreen(256, 256).unwrap();
+    let program = Program::from_sources(&context, VERT_SOURCE, FRAG_SOURCE).unwrap();
+
+    bencher.iter(|| {
+        let mut found = 0;
+        for _ in 0..DRAWS_PER_ITER {
+            for &name in UNIFORM_NAMES {
+                if program.query_uniform_location(name).is_some() {
+                    found += 1;
+                }
+            }
+        }
+        found
+    });
+}
+
+#[bench]
+fn uniform_lookup_cached(bencher: &mut Bencher) {
+    let context = Context::offscreen(256, 256).unwrap();
+    let program = Program::from_sources(&context, VERT_SOURCE, FRAG_SOURCE).unwrap();
+
+    bencher.iter(|| {
+        let mut found = 0;
+        for _ in 0..DRAWS_PER_ITER {
+            for &name in UNIFORM_NAMES {
+                if program.uniform_location(name).is_some() {
+                    found += 1;
+                }
+            }
+        }
+        found
+    });
+}
+
+#[bench]
+fn draw_builder_uniforms(bencher: &mut Bencher) {
+    let context = Context::offscreen(256, 256).unwrap();
+    let program = Program::from_sources(&context, VERT_SOURCE, FRAG_SOURCE).unwrap();
+    let vertex_array = create_vertex_array(&context);
+
+    // Sets the uniforms the way polygon does, which includes building the draw builders.
+    bencher.iter(|| {
+        for _ in 0..DRAWS_PER_ITER {
+            let mut draw_builder = DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles);
+            draw_builder.program(&program);
+
+            for &name in UNIFORM_NAMES {
+                if name.ends_with("transform") || name.ends_with("projection") {
+                    draw_builder.uniform(name, GlMatrix {
+                        data: &IDENTITY,
+                        transpose: false,
+                    });
+                } else {
+                    draw_builder.uniform(name, 1.0f32);
+                }
+            }
+        }
+    });
+}
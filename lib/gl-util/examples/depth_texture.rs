@@ -0,0 +1,100 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate gl_util as gl;
+
+use bootstrap::window::*;
+use gl::*;
+use gl::context::Context;
+use gl::shader::*;
+use gl::texture::*;
+
+static VERT_SOURCE: &'static str = r#"
+#version 330 core
+
+in vec4 position;
+
+out vec2 frag_uv;
+
+void main() {
+    frag_uv = position.xy * 0.5 + 0.5;
+    gl_Position = position;
+}
+"#;
+
+static FRAG_SOURCE: &'static str = r#"
+#version 330 core
+
+uniform sampler2D depth_texture;
+
+in vec2 frag_uv;
+
+out vec4 fragment_color;
+
+void main() {
+    float depth = texture(depth_texture, frag_uv).r;
+    fragment_color = vec4(depth, depth, depth, 1.0);
+}
+"#;
+
+static VERTEX_DATA: &'static [f32] = &[
+    -1.0,  3.0, 0.0,
+     3.0, -1.0, 0.0,
+    -1.0, -1.0, 0.0,
+];
+
+const SIZE: usize = 256;
+
+fn main() {
+    // Create window and initialize OpenGL.
+    let mut window = Window::new("gl-util - depth texture example").unwrap();
+    let context = Context::from_window(&window).unwrap();
+
+    // Compile and link shaders into a shader program.
+    let program = Program::from_sources(&context, VERT_SOURCE, FRAG_SOURCE).unwrap();
+
+    // Create the vertex buffer for a single triangle that covers the whole screen.
+    let mut vertex_buffer = VertexBuffer::new(&context);
+    vertex_buffer.set_data_f32(VERTEX_DATA);
+    vertex_buffer.set_attrib_f32(
+        "position",
+        AttribLayout {
+            elements: 3,
+            offset: 0,
+            stride: 0,
+        });
+
+    let vertex_array = VertexArray::new(&context, vertex_buffer);
+
+    // Create an empty depth texture and fill it with a radial gradient. Normally the depth data
+    // would come from rendering into the texture, but uploading it directly lets us check that
+    // the texture can be sampled without needing a framebuffer.
+    let mut depth_texture = Texture2d::depth(&context, SIZE, SIZE, DepthFormat::Depth32F).unwrap();
+
+    let mut depth_data = Vec::with_capacity(SIZE * SIZE);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let dx = x as f32 / SIZE as f32 - 0.5;
+            let dy = y as f32 / SIZE as f32 - 0.5;
+            let distance = (dx * dx + dy * dy).sqrt() * 2.0;
+            depth_data.push(distance.min(1.0));
+        }
+    }
+    depth_texture.sub_image(0, 0, SIZE as u32, SIZE as u32, &*depth_data);
+
+    'outer: loop {
+        while let Some(message) = window.next_message() {
+            match message {
+                Message::Close => break 'outer,
+                _ => {},
+            }
+        }
+
+        context.clear();
+        DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+            .program(&program)
+            .map_attrib_name("position", "position")
+            .uniform("depth_texture", &depth_texture)
+            .winding(WindingOrder::Clockwise)
+            .draw();
+        context.swap_buffers();
+    }
+}
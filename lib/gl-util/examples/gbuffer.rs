@@ -0,0 +1,188 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate gl_util as gl;
+
+use bootstrap::window::*;
+use gl::*;
+use gl::context::Context;
+use gl::framebuffer::*;
+use gl::shader::*;
+use gl::texture::*;
+
+static GEOMETRY_VERT_SOURCE: &'static str = r#"
+#version 330 core
+
+in vec4 position;
+in vec3 normal;
+
+out vec3 frag_normal;
+out vec2 frag_position;
+
+void main() {
+    frag_normal = normal;
+    frag_position = position.xy;
+    gl_Position = position;
+}
+"#;
+
+static GEOMETRY_FRAG_SOURCE: &'static str = r#"
+#version 330 core
+
+in vec3 frag_normal;
+in vec2 frag_position;
+
+out vec4 out_albedo;
+out vec4 out_normal;
+
+void main() {
+    // Checkerboard albedo so that the albedo buffer is easy to tell apart from the others.
+    vec2 cell = floor(frag_position * 8.0);
+    float checker = mod(cell.x + cell.y, 2.0);
+    out_albedo = mix(vec4(0.8, 0.3, 0.2, 1.0), vec4(0.9, 0.8, 0.3, 1.0), checker);
+
+    out_normal = vec4(normalize(frag_normal) * 0.5 + 0.5, 1.0);
+}
+"#;
+
+static DISPLAY_VERT_SOURCE: &'static str = r#"
+#version 330 core
+
+in vec4 position;
+
+out vec2 frag_uv;
+
+void main() {
+    frag_uv = position.xy * 0.5 + 0.5;
+    gl_Position = position;
+}
+"#;
+
+static DISPLAY_FRAG_SOURCE: &'static str = r#"
+#version 330 core
+
+uniform sampler2D buffer;
+uniform int is_depth;
+
+in vec2 frag_uv;
+
+out vec4 fragment_color;
+
+void main() {
+    vec4 value = texture(buffer, frag_uv);
+    if (is_depth != 0) {
+        fragment_color = vec4(value.rrr, 1.0);
+    } else {
+        fragment_color = value;
+    }
+}
+"#;
+
+/// A triangle tilted away from the camera, with each corner's normal pointing a different way.
+///
+/// Each vertex is a position followed by a normal.
+static TRIANGLE_DATA: &'static [f32] = &[
+    -0.8, -0.8, -0.5,   -1.0, -1.0, 1.0,
+     0.8, -0.8, -0.5,    1.0, -1.0, 1.0,
+     0.0,  0.8,  0.5,    0.0,  1.0, 1.0,
+];
+
+static FULL_SCREEN_DATA: &'static [f32] = &[
+    -1.0,  3.0, 0.0,
+     3.0, -1.0, 0.0,
+    -1.0, -1.0, 0.0,
+];
+
+const SIZE: usize = 256;
+
+fn main() {
+    // Create window and initialize OpenGL.
+    let mut window = Window::new("gl-util - g-buffer example").unwrap();
+    let context = Context::from_window(&window).unwrap();
+
+    // The geometry pass writes `out_albedo` to color attachment 0 and `out_normal` to color
+    // attachment 1, so bind the outputs to match before linking.
+    let geometry_program = ProgramBuilder::new(&context)
+        .bind_frag_output("out_albedo", 0)
+        .bind_frag_output("out_normal", 1)
+        .build_from_sources(GEOMETRY_VERT_SOURCE, GEOMETRY_FRAG_SOURCE)
+        .unwrap();
+    let display_program = Program::from_sources(&context, DISPLAY_VERT_SOURCE, DISPLAY_FRAG_SOURCE).unwrap();
+
+    let mut triangle_buffer = VertexBuffer::new(&context);
+    triangle_buffer.set_data_f32(TRIANGLE_DATA);
+    triangle_buffer.set_attrib_f32(
+        "position",
+        AttribLayout {
+            elements: 3,
+            offset: 0,
+            stride: 6,
+        });
+    triangle_buffer.set_attrib_f32(
+        "normal",
+        AttribLayout {
+            elements: 3,
+            offset: 3,
+            stride: 6,
+        });
+    let triangle = VertexArray::new(&context, triangle_buffer);
+
+    let mut full_screen_buffer = VertexBuffer::new(&context);
+    full_screen_buffer.set_data_f32(FULL_SCREEN_DATA);
+    full_screen_buffer.set_attrib_f32(
+        "position",
+        AttribLayout {
+            elements: 3,
+            offset: 0,
+            stride: 0,
+        });
+    let full_screen = VertexArray::new(&context, full_screen_buffer);
+
+    // Create the G-buffer: albedo and normal color targets plus a depth texture.
+    let albedo = Texture2d::render_target(&context, TextureInternalFormat::Rgba8, SIZE, SIZE).unwrap();
+    let normal = Texture2d::render_target(&context, TextureInternalFormat::Rgba16F, SIZE, SIZE).unwrap();
+    let depth = Texture2d::depth(&context, SIZE, SIZE, DepthFormat::Depth24).unwrap();
+
+    let mut gbuffer = Framebuffer::new(&context);
+    gbuffer.attach_color(0, &albedo);
+    gbuffer.attach_color(1, &normal);
+    gbuffer.attach_depth(&depth);
+    gbuffer.set_draw_buffers(&[0, 1]);
+    gbuffer.check().expect("G-buffer is incomplete");
+
+    'outer: loop {
+        while let Some(message) = window.next_message() {
+            match message {
+                Message::Close => break 'outer,
+                Message::Resize(width, height) => context.resize(width, height),
+                _ => {},
+            }
+        }
+
+        // Write albedo, normal, and depth in a single pass.
+        gbuffer.clear();
+        DrawBuilder::new(&context, &triangle, DrawMode::Triangles)
+            .program(&geometry_program)
+            .framebuffer(&gbuffer)
+            .viewport(0, 0, SIZE as i32, SIZE as i32)
+            .map_attrib_name("position", "position")
+            .map_attrib_name("normal", "normal")
+            .depth_test(Comparison::Less)
+            .draw();
+
+        // Show the three buffers side by side.
+        let (width, height) = context.dimensions();
+        let width = width / 3;
+
+        context.clear();
+        for (index, &(buffer, is_depth)) in [(&albedo, 0), (&normal, 0), (&depth, 1)].iter().enumerate() {
+            DrawBuilder::new(&context, &full_screen, DrawMode::Triangles)
+                .program(&display_program)
+                .viewport(width * index as i32, 0, width, height)
+                .map_attrib_name("position", "position")
+                .uniform("buffer", buffer)
+                .uniform("is_depth", is_depth)
+                .winding(WindingOrder::Clockwise)
+                .draw();
+        }
+        context.swap_buffers();
+    }
+}
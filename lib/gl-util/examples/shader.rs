@@ -72,9 +72,7 @@ fn main() {
     let context = Context::from_window(&window).unwrap();
 
     // Compile and link shaders into a shader program.
-    let vert_shader = Shader::new(&context, VERT_SOURCE, ShaderType::Vertex).unwrap();
-    let frag_shader = Shader::new(&context, FRAG_SOURCE, ShaderType::Fragment).unwrap();
-    let program = Program::new(&context, &[vert_shader, frag_shader]).unwrap();
+    let program = Program::from_sources(&context, VERT_SOURCE, FRAG_SOURCE).unwrap();
 
     // Create the vertex buffer and set the vertex attribs.
     let mut vertex_buffer = VertexBuffer::new(&context);
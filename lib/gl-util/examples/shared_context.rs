@@ -0,0 +1,91 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate gl_util as gl;
+
+use bootstrap::window::*;
+use gl::*;
+use gl::context::Context;
+use gl::shader::*;
+use std::rc::Rc;
+
+static VERT_SOURCE: &'static str = r#"
+#version 330 core
+
+in vec4 position;
+
+void main() {
+    gl_Position = position;
+}
+"#;
+
+static FRAG_SOURCE: &'static str = r#"
+#version 330 core
+
+uniform vec4 surface_color;
+
+out vec4 fragment_color;
+
+void main() {
+    fragment_color = surface_color;
+}
+"#;
+
+static VERTEX_POSITIONS: [f32; 9] = [
+    -1.0, -1.0, 0.0,
+     1.0, -1.0, 0.0,
+     0.0,  1.0, 0.0,
+];
+
+fn main() {
+    // Open two windows, giving each its own context. The second context shares objects with the
+    // first, so buffers and programs created with either can be used with both.
+    let mut editor_window = Window::new("gl-util - shared context example (editor)").unwrap();
+    let mut game_window = Window::new("gl-util - shared context example (game)").unwrap();
+    let editor_context = Context::from_window(&editor_window).unwrap();
+    let game_context = editor_context.new_shared(&game_window).unwrap();
+
+    // Create the vertex buffer and program once with the editor context.
+    let mut vertex_buffer = VertexBuffer::new(&editor_context);
+    vertex_buffer.set_data_f32(&VERTEX_POSITIONS[..]);
+    vertex_buffer.set_attrib_f32(
+        "position",
+        AttribLayout {
+            elements: 3,
+            offset: 0,
+            stride: 0,
+        });
+    let vertex_buffer = Rc::new(vertex_buffer);
+
+    let program = Program::from_sources(&editor_context, VERT_SOURCE, FRAG_SOURCE).unwrap();
+
+    // Vertex arrays aren't shared between contexts, so each context needs its own vertex array
+    // for the shared vertex buffer.
+    let editor_vertex_array = VertexArray::new(&editor_context, vertex_buffer.clone());
+    let game_vertex_array = VertexArray::new(&game_context, vertex_buffer);
+
+    'outer: loop {
+        for window in &mut [&mut editor_window, &mut game_window] {
+            while let Some(message) = window.next_message() {
+                match message {
+                    Message::Close => break 'outer,
+                    _ => {},
+                }
+            }
+        }
+
+        editor_context.clear();
+        DrawBuilder::new(&editor_context, &editor_vertex_array, DrawMode::Triangles)
+            .program(&program)
+            .map_attrib_name("position", "position")
+            .uniform("surface_color", (1.0, 0.5, 0.0, 1.0))
+            .draw();
+        editor_context.swap_buffers();
+
+        game_context.clear();
+        DrawBuilder::new(&game_context, &game_vertex_array, DrawMode::Triangles)
+            .program(&program)
+            .map_attrib_name("position", "position")
+            .uniform("surface_color", (0.0, 0.5, 1.0, 1.0))
+            .draw();
+        game_context.swap_buffers();
+    }
+}
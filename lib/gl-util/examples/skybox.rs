@@ -0,0 +1,145 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate gl_util as gl;
+
+use bootstrap::window::*;
+use gl::*;
+use gl::context::Context;
+use gl::shader::*;
+use gl::texture::*;
+use std::time::Instant;
+
+static VERT_SOURCE: &'static str = r#"
+#version 330 core
+
+in vec4 position;
+
+out vec2 frag_position;
+
+void main() {
+    frag_position = position.xy;
+    gl_Position = position;
+}
+"#;
+
+static FRAG_SOURCE: &'static str = r#"
+#version 330 core
+
+uniform samplerCube skybox;
+uniform float yaw;
+
+in vec2 frag_position;
+
+out vec4 fragment_color;
+
+void main() {
+    // Build a view direction for a camera looking down -Z, then spin it around the Y axis.
+    vec3 view_direction = normalize(vec3(frag_position, -1.0));
+    float s = sin(yaw);
+    float c = cos(yaw);
+    vec3 direction = vec3(
+        c * view_direction.x + s * view_direction.z,
+        view_direction.y,
+        -s * view_direction.x + c * view_direction.z);
+
+    fragment_color = texture(skybox, direction);
+}
+"#;
+
+static VERTEX_DATA: &'static [f32] = &[
+    -1.0,  3.0, 0.0,
+     3.0, -1.0, 0.0,
+    -1.0, -1.0, 0.0,
+];
+
+const FACE_SIZE: usize = 64;
+const CHECKER_SIZE: usize = 8;
+
+/// A distinct color for each face, in the order +X, -X, +Y, -Y, +Z, -Z.
+static FACE_COLORS: [(u8, u8, u8); 6] = [
+    (255, 64, 64),
+    (64, 255, 255),
+    (64, 255, 64),
+    (255, 64, 255),
+    (64, 64, 255),
+    (255, 255, 64),
+];
+
+fn main() {
+    // Create window and initialize OpenGL.
+    let mut window = Window::new("gl-util - skybox example").unwrap();
+    let context = Context::from_window(&window).unwrap();
+
+    // Compile and link shaders into a shader program.
+    let program = Program::from_sources(&context, VERT_SOURCE, FRAG_SOURCE).unwrap();
+
+    // Create the vertex buffer for a single triangle that covers the whole screen.
+    let mut vertex_buffer = VertexBuffer::new(&context);
+    vertex_buffer.set_data_f32(VERTEX_DATA);
+    vertex_buffer.set_attrib_f32(
+        "position",
+        AttribLayout {
+            elements: 3,
+            offset: 0,
+            stride: 0,
+        });
+
+    let vertex_array = VertexArray::new(&context, vertex_buffer);
+
+    // Generate a checkerboard for each face, tinted so the faces can be told apart.
+    let face_data = FACE_COLORS
+        .iter()
+        .map(|&color| build_face(color))
+        .collect::<Vec<_>>();
+    let face = |index: usize| FaceImage {
+        width: FACE_SIZE,
+        height: FACE_SIZE,
+        data: &*face_data[index],
+    };
+
+    let skybox = TextureCube::new(
+        &context,
+        TextureFormat::Rgb,
+        TextureInternalFormat::Rgb,
+        &[face(0), face(1), face(2), face(3), face(4), face(5)])
+        .unwrap();
+
+    let start_time = Instant::now();
+
+    'outer: loop {
+        while let Some(message) = window.next_message() {
+            match message {
+                Message::Close => break 'outer,
+                _ => {},
+            }
+        }
+
+        let elapsed = start_time.elapsed();
+        let yaw = elapsed.as_secs() as f32 * 0.5 + elapsed.subsec_nanos() as f32 * 0.5e-9;
+
+        context.clear();
+        DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+            .program(&program)
+            .map_attrib_name("position", "position")
+            .uniform("skybox", &skybox)
+            .uniform("yaw", yaw)
+            .winding(WindingOrder::Clockwise)
+            .draw();
+        context.swap_buffers();
+    }
+}
+
+fn build_face(color: (u8, u8, u8)) -> Vec<(u8, u8, u8)> {
+    let (red, green, blue) = color;
+    let mut data = Vec::with_capacity(FACE_SIZE * FACE_SIZE);
+    for y in 0..FACE_SIZE {
+        for x in 0..FACE_SIZE {
+            if (x / CHECKER_SIZE + y / CHECKER_SIZE) % 2 == 0 {
+                data.push((red, green, blue));
+            } else {
+                data.push((red / 2, green / 2, blue / 2));
+            }
+        }
+    }
+
+    data
+}
@@ -0,0 +1,122 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate gl_util as gl;
+
+use bootstrap::window::*;
+use gl::*;
+use gl::context::Context;
+use gl::shader::*;
+use gl::texture::*;
+use std::time::Instant;
+
+static VERT_SOURCE: &'static str = r#"
+#version 330 core
+
+in vec4 position;
+
+out vec2 frag_uv;
+
+void main() {
+    frag_uv = position.xy * 0.5 + 0.5;
+    gl_Position = position;
+}
+"#;
+
+static FRAG_SOURCE: &'static str = r#"
+#version 330 core
+
+uniform sampler3D volume;
+uniform float slice;
+
+in vec2 frag_uv;
+
+out vec4 fragment_color;
+
+void main() {
+    fragment_color = texture(volume, vec3(frag_uv, slice));
+}
+"#;
+
+static VERTEX_DATA: &'static [f32] = &[
+    -1.0,  3.0, 0.0,
+     3.0, -1.0, 0.0,
+    -1.0, -1.0, 0.0,
+];
+
+const SIZE: usize = 8;
+
+fn main() {
+    // Create window and initialize OpenGL.
+    let mut window = Window::new("gl-util - volume texture example").unwrap();
+    let context = Context::from_window(&window).unwrap();
+
+    // Compile and link shaders into a shader program.
+    let program = Program::from_sources(&context, VERT_SOURCE, FRAG_SOURCE).unwrap();
+
+    // Create the vertex buffer for a single triangle that covers the whole screen.
+    let mut vertex_buffer = VertexBuffer::new(&context);
+    vertex_buffer.set_data_f32(VERTEX_DATA);
+    vertex_buffer.set_attrib_f32(
+        "position",
+        AttribLayout {
+            elements: 3,
+            offset: 0,
+            stride: 0,
+        });
+
+    let vertex_array = VertexArray::new(&context, vertex_buffer);
+
+    // Generate a volume where each texel's color is its position in the volume, so that moving
+    // through the slices fades the blue channel in and out.
+    let mut volume_data = Vec::with_capacity(SIZE * SIZE * SIZE);
+    for z in 0..SIZE {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                volume_data.push((
+                    (x * 255 / (SIZE - 1)) as u8,
+                    (y * 255 / (SIZE - 1)) as u8,
+                    (z * 255 / (SIZE - 1)) as u8,
+                ));
+            }
+        }
+    }
+
+    let mut volume = Texture3d::new(
+        &context,
+        TextureFormat::Rgb,
+        TextureInternalFormat::Rgb,
+        SIZE,
+        SIZE,
+        SIZE,
+        &*volume_data)
+        .unwrap();
+    volume.set_wrap(
+        TextureWrapFunction::ClampToEdge,
+        TextureWrapFunction::ClampToEdge,
+        TextureWrapFunction::MirroredRepeat);
+
+    let start_time = Instant::now();
+
+    'outer: loop {
+        while let Some(message) = window.next_message() {
+            match message {
+                Message::Close => break 'outer,
+                _ => {},
+            }
+        }
+
+        // Sweep back and forth through the volume, mirrored wrapping on the depth coordinate
+        // turns a steadily increasing slice into a ping-pong.
+        let elapsed = start_time.elapsed();
+        let slice = elapsed.as_secs() as f32 * 0.25 + elapsed.subsec_nanos() as f32 * 0.25e-9;
+
+        context.clear();
+        DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+            .program(&program)
+            .map_attrib_name("position", "position")
+            .uniform("volume", &volume)
+            .uniform("slice", slice)
+            .winding(WindingOrder::Clockwise)
+            .draw();
+        context.swap_buffers();
+    }
+}
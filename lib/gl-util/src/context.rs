@@ -1,68 +1,255 @@
 use bootstrap::window::Window;
 use gl;
 use gl::*;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::ffi::CStr;
 use std::ptr;
 use std::rc::Rc;
+use std::thread;
 
+pub use gl::{ContextProfile, DebugSeverity, DebugSource, DebugType, ErrorCode};
+
+/// Configures and creates a `Context`.
+#[derive(Debug, Clone)]
+pub struct ContextBuilder {
+    options: ContextOptions,
+    share: Option<(gl::Context, ShareGroup)>,
+}
+
+impl ContextBuilder {
+    /// Creates a builder with the platform's default options.
+    pub fn new() -> ContextBuilder {
+        ContextBuilder {
+            options: ContextOptions::default(),
+            share: None,
+        }
+    }
+
+    /// Requests a minimum OpenGL version.
+    ///
+    /// Building the context fails with `Error::UnsupportedVersion` if the driver can't provide at
+    /// least this version.
+    pub fn version(&mut self, major: u32, minor: u32) -> &mut ContextBuilder {
+        self.options.version = Some((major, minor));
+        self
+    }
+
+    /// Sets the profile to request. Only used if a version is requested with `version()`.
+    pub fn profile(&mut self, profile: ContextProfile) -> &mut ContextBuilder {
+        self.options.profile = profile;
+        self
+    }
+
+    /// Requests a debug context, which makes the driver report more problems through the debug
+    /// callback (see `Context::set_debug_callback()`).
+    pub fn debug(&mut self, debug: bool) -> &mut ContextBuilder {
+        self.options.debug = debug;
+        self
+    }
+
+    /// Sets the number of bits in the window's depth buffer. Defaults to 24.
+    pub fn depth_bits(&mut self, bits: u8) -> &mut ContextBuilder {
+        self.options.depth_bits = bits;
+        self
+    }
+
+    /// Sets the number of bits in the window's stencil buffer. Defaults to 8.
+    pub fn stencil_bits(&mut self, bits: u8) -> &mut ContextBuilder {
+        self.options.stencil_bits = bits;
+        self
+    }
+
+    /// Sets whether the window gets an sRGB-capable framebuffer, and whether writes to it are
+    /// converted from linear to sRGB.
+    ///
+    /// Defaults to `true`. The conversion can be toggled after the context is created with
+    /// `Context::set_srgb_enabled()`.
+    pub fn srgb(&mut self, srgb: bool) -> &mut ContextBuilder {
+        self.options.srgb = srgb;
+        self
+    }
+
+    /// Requests a multisampled framebuffer for the window with `samples` samples per pixel.
+    ///
+    /// Defaults to 0, which disables multisampling. If the platform doesn't support the requested
+    /// sample count the closest lower count is used instead, use `Context::samples()` to find the
+    /// sample count the context actually got.
+    pub fn samples(&mut self, samples: u32) -> &mut ContextBuilder {
+        self.options.samples = samples;
+        self
+    }
+
+    /// Makes the new context share its objects with `context`.
+    ///
+    /// See `Context::new_shared()` for which objects are shared.
+    pub fn share_with(&mut self, context: &Context) -> &mut ContextBuilder {
+        self.share = Some((context.raw(), context.share_group()));
+        self
+    }
+
+    /// Creates a context for the specified window with the configured options.
+    pub fn build(&self, window: &Window) -> Result<Context, Error> {
+        let device_context = window.platform().device_context();
+        Context::from_device_context(device_context, &self.options, self.share.as_ref())
+    }
+
+    /// Creates an offscreen context with the configured options.
+    ///
+    /// See `Context::offscreen()` for how offscreen contexts differ from window contexts.
+    pub fn build_offscreen(&self, width: i32, height: i32) -> Result<Context, Error> {
+        unsafe {
+            let hidden_window = match gl::create_hidden_window(width, height) {
+                Some(hidden_window) => hidden_window,
+                None => return Err(Error::NoDeviceContext),
+            };
+
+            let device_context = gl::hidden_window_device_context(hidden_window);
+            match Context::from_device_context(device_context, &self.options, self.share.as_ref()) {
+                Ok(mut context) => {
+                    context.hidden_window = Some(hidden_window);
+                    Ok(context)
+                },
+                Err(error) => {
+                    gl::destroy_hidden_window(hidden_window);
+                    Err(error)
+                },
+            }
+        }
+    }
+}
+
+/// The bit in `GL_CONTEXT_FLAGS` that's set for debug contexts.
+const CONTEXT_FLAG_DEBUG_BIT: i32 = 0x2;
+
+/// An OpenGL rendering context.
+///
+/// A context can only be used on the thread that created it. Using the context, or any of the
+/// objects created with it, from another thread panics.
 #[derive(Debug)]
 pub struct Context {
     raw: gl::Context,
     inner: Rc<RefCell<ContextInner>>,
+
+    /// Boxed so that its address can be given to OpenGL as the debug callback's user parameter.
+    debug_callback: Box<DebugCallback>,
+
+    /// The window created for an offscreen context, destroyed along with the context.
+    hidden_window: Option<gl::HiddenWindow>,
+
+    options: ContextOptions,
+    version: (u32, u32),
+    is_debug: bool,
+    vendor: String,
+    renderer: String,
+    version_string: String,
+    samples: u32,
+    limits: GlLimits,
 }
 
 impl Context {
     /// Creates a new rendering context for the specified window.
+    ///
+    /// The context is created with the platform's default options, use `ContextBuilder` to
+    /// request a specific version or a debug context.
     pub fn from_window(window: &Window) -> Result<Context, Error> {
-        let device_context = window.platform().device_context();
-        Context::from_device_context(device_context)
+        ContextBuilder::new().build(window)
     }
 
-    /// Initializes global OpenGL state and creates the OpenGL context needed to perform rendering.
-    fn from_device_context(device_context: gl::DeviceContext) -> Result<Context, Error> {
-        pub extern "system" fn debug_callback(
-            source: DebugSource,
-            message_type: DebugType,
-            object_id: u32,
-            severity: DebugSeverity,
-            _length: i32,
-            message: *const u8,
-            _user_param: *mut ()
-        ) {
-            use std::ffi::CStr;
-
-            let message = unsafe { CStr::from_ptr(message as *const _) }.to_string_lossy();
-
-            println!(
-                r#"Recieved some kind of debug message.
-                source: {:?},
-                type: {:?},
-                object_id: 0x{:x},
-                severity: {:?},
-                message: {}"#,
-                source,
-                message_type,
-                object_id,
-                severity,
-                message);
+    /// Creates a new rendering context that doesn't render to a visible window.
+    ///
+    /// Offscreen contexts are meant for tests and tools that run on machines without a display,
+    /// e.g. rendering thumbnails. The context is created for a hidden window that's `width` by
+    /// `height` pixels, but the contents of a hidden window's framebuffer are undefined, so render
+    /// into a `Framebuffer` and read the results back from its attachments instead.
+    ///
+    /// `swap_buffers()` does nothing for an offscreen context.
+    pub fn offscreen(width: i32, height: i32) -> Result<Context, Error> {
+        ContextBuilder::new().build_offscreen(width, height)
+    }
+
+    /// Returns `true` if the context was created with `offscreen()`.
+    pub fn is_offscreen(&self) -> bool {
+        self.hidden_window.is_some()
+    }
+
+    /// Creates a new rendering context for the specified window that shares objects with this one.
+    ///
+    /// Buffers, textures, renderbuffers, and programs created with either context can be used
+    /// with the other. Vertex arrays and framebuffers are never shared by OpenGL, so they can only
+    /// be used with the context that created them. The new context is created with the same
+    /// version, profile, and debug options as this one.
+    ///
+    /// Shared objects are deleted using the context that created them, so an object that
+    /// outlives its context isn't deleted until every context it's shared with is destroyed.
+    pub fn new_shared(&self, window: &Window) -> Result<Context, Error> {
+        ContextBuilder {
+            options: self.options,
+            share: None,
         }
+            .share_with(self)
+            .build(window)
+    }
 
+    /// Initializes global OpenGL state and creates the OpenGL context needed to perform rendering.
+    fn from_device_context(
+        device_context: gl::DeviceContext,
+        options: &ContextOptions,
+        share: Option<&(gl::Context, ShareGroup)>,
+    ) -> Result<Context, Error> {
         unsafe {
-            let context =
-                gl::create_context(device_context)
-                .ok_or(Error::UnableToCreateRenderContext)?;
+            let share_context = share.map(|&(share_context, _)| share_context);
+            let context = match gl::create_context(device_context, options, share_context) {
+                Some(context) => context,
+                None => return match options.version {
+                    Some(requested) => Err(Error::UnsupportedVersion {
+                        requested: requested,
+                        actual: None,
+                    }),
+                    None => Err(Error::UnableToCreateRenderContext),
+                },
+            };
+            register_thread_context(context);
+
+            let (version, is_debug) = {
+                let _guard = ::context::ContextGuard::new(context);
+
+                let mut major = 0;
+                let mut minor = 0;
+                let mut flags = 0;
+                gl::get_integers(IntegerName::MajorVersion, &mut major);
+                gl::get_integers(IntegerName::MinorVersion, &mut minor);
+                gl::get_integers(IntegerName::ContextFlags, &mut flags);
+
+                ((major as u32, minor as u32), flags & CONTEXT_FLAG_DEBUG_BIT != 0)
+            };
+
+            // The driver may give us a newer version than requested, but never an older one.
+            if let Some(requested) = options.version {
+                if version < requested {
+                    gl::destroy_context(context);
+                    unregister_thread_context(context);
+                    return Err(Error::UnsupportedVersion {
+                        requested: requested,
+                        actual: Some(version),
+                    });
+                }
+            }
+
+            let debug_callback = Box::new(DebugCallback(RefCell::new(None)));
 
-            {
+            let (vendor, renderer, version_string, samples, limits) = {
                 let _guard = ::context::ContextGuard::new(context);
 
                 gl::enable(ServerCapability::DebugOutput);
-                gl::debug_message_callback(Some(debug_callback), ptr::null_mut());
+                gl::debug_message_callback(
+                    Some(debug_message_callback),
+                    &*debug_callback as *const DebugCallback as *mut ());
 
-                let vendor = CStr::from_ptr(gl::get_string(StringName::Vendor)).to_str().unwrap();
-                let renderer = CStr::from_ptr(gl::get_string(StringName::Renderer)).to_str().unwrap();
-                let version = CStr::from_ptr(gl::get_string(StringName::Version)).to_str().unwrap();
-                let glsl_version = CStr::from_ptr(gl::get_string(StringName::ShadingLanguageVersion)).to_str().unwrap();
+                let vendor = get_string(StringName::Vendor);
+                let renderer = get_string(StringName::Renderer);
+                let version = get_string(StringName::Version);
+                let glsl_version = get_string(StringName::ShadingLanguageVersion);
 
                 println!("OpenGL Information:");
                 println!("\tvendor: {}", vendor);
@@ -73,42 +260,308 @@ impl Context {
                 // Load a bunch of proc pointers for funsies.
                 gl::get_attrib_location::load();
                 gl::gen_vertex_arrays::load();
-                gl::enable(ServerCapability::FramebufferSrgb);
+                if options.srgb {
+                    gl::enable(ServerCapability::FramebufferSrgb);
+                }
                 gl::enable(ServerCapability::Blend);
-            }
+
+                let mut samples = 0;
+                gl::get_integers(IntegerName::Samples, &mut samples);
+
+                (vendor, renderer, version, samples as u32, GlLimits::query())
+            };
+
+            // The initial viewport covers the whole window, so we use it to find the size of the
+            // window.
+            let viewport = {
+                let _guard = ::context::ContextGuard::new(context);
+
+                let mut viewport = [0; 4];
+                gl::get_integers(IntegerName::Viewport, viewport.as_mut_ptr());
+                Viewport {
+                    x: viewport[0],
+                    y: viewport[1],
+                    width: viewport[2],
+                    height: viewport[3],
+                }
+            };
+
+            let share_group = match share {
+                Some(&(_, ref share_group)) => share_group.clone(),
+                None => ShareGroup::new(),
+            };
 
             let inner = Rc::new(RefCell::new(ContextInner {
                 raw: context,
+                alive: Rc::new(Cell::new(true)),
+                share_group: share_group,
 
-                server_srgb_enabled: true,
+                server_srgb_enabled: options.srgb,
                 server_cull_enabled: false,
                 server_depth_test_enabled: false,
                 server_blend_enabled: true,
+                server_multisample_enabled: true,
 
                 bound_vertex_array: None,
+                bound_framebuffer: Some(FramebufferName::null()),
+                active_queries: HashMap::new(),
                 front_polygon_mode: PolygonMode::default(),
                 back_polygon_mode: PolygonMode::default(),
                 program: None,
                 cull_mode: Face::default(),
                 winding_order: WindingOrder::default(),
                 depth_test: Comparison::Less,
+                depth_write: true,
                 blend: Default::default(),
+                viewport: viewport,
+                default_viewport: viewport,
+                default_multisample: true,
+                dimensions: (viewport.width, viewport.height),
+                clear_color: (0.0, 0.0, 0.0, 0.0),
+                limits: limits,
             }));
 
             Ok(Context {
                 raw: context,
                 inner: inner,
+                debug_callback: debug_callback,
+
+                hidden_window: None,
+
+                options: *options,
+                version: version,
+                is_debug: is_debug,
+                vendor: vendor,
+                renderer: renderer,
+                version_string: version_string,
+                samples: samples,
+                limits: limits,
             })
         }
     }
 
-    /// TODO: Take clear mask (and values) as parameters.
+    /// Returns the OpenGL version of the context as `(major, minor)`.
+    ///
+    /// This may be newer than the version requested with `ContextBuilder::version()`.
+    pub fn version(&self) -> (u32, u32) {
+        self.version
+    }
+
+    /// Returns `true` if the context is a debug context.
+    pub fn is_debug(&self) -> bool {
+        self.is_debug
+    }
+
+    /// Returns the name of the company responsible for the OpenGL implementation.
+    pub fn vendor(&self) -> &str {
+        &*self.vendor
+    }
+
+    /// Returns the name of the renderer, which is usually the name of the graphics card.
+    pub fn renderer(&self) -> &str {
+        &*self.renderer
+    }
+
+    /// Returns the full version string reported by the driver, which includes vendor-specific
+    /// information after the version number.
+    pub fn version_string(&self) -> &str {
+        &*self.version_string
+    }
+
+    /// Returns the number of samples per pixel in the window's framebuffer, or 0 if it isn't
+    /// multisampled.
+    ///
+    /// This may be fewer than the sample count requested with `ContextBuilder::samples()`.
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// Returns the implementation limits of the context.
+    pub fn limits(&self) -> &GlLimits {
+        &self.limits
+    }
+
+    /// Clears the color, depth, and stencil buffers of the window.
+    ///
+    /// The color buffer is cleared to the color set with `set_clear_color()`.
     pub fn clear(&self) {
+        self.clear_flags(true, true, true);
+    }
+
+    /// Clears the selected buffers of the window.
+    ///
+    /// This is useful for clearing only the depth buffer between camera passes so that the
+    /// second pass draws on top of the first. Does nothing if no buffers are selected.
+    pub fn clear_flags(&self, color: bool, depth: bool, stencil: bool) {
+        if let Some(mask) = clear_mask(color, depth, stencil) {
+            let _guard = ::context::ContextGuard::new(self.raw);
+            let mut inner = self.inner.borrow_mut();
+            inner.bind_framebuffer(FramebufferName::null());
+            if depth { inner.depth_write(true); }
+            unsafe { gl::clear(mask); }
+        }
+    }
+
+    /// Sets the color used when clearing color buffers.
+    ///
+    /// The clear color applies to all clears made with the context, including
+    /// `Framebuffer::clear()`. The default clear color is transparent black.
+    pub fn set_clear_color(&self, red: f32, green: f32, blue: f32, alpha: f32) {
+        let _guard = ::context::ContextGuard::new(self.raw);
+        self.inner.borrow_mut().clear_color((red, green, blue, alpha));
+    }
+
+    /// Sets the value read from an attribute location by vertex arrays that don't map any vertex
+    /// data to it.
+    ///
+    /// The value is shared by every vertex array in the context. The default value for every
+    /// location is `(0.0, 0.0, 0.0, 1.0)`.
+    pub fn set_default_attrib(&self, attrib_location: AttributeLocation, value: (f32, f32, f32, f32)) {
+        let _guard = ::context::ContextGuard::new(self.raw);
+        unsafe { gl::vertex_attrib_4f(attrib_location, value.0, value.1, value.2, value.3); }
+    }
+
+    /// Sets whether writes to the window's framebuffer and to sRGB framebuffer attachments are
+    /// converted from linear to sRGB.
+    ///
+    /// The conversion is enabled when the context is created unless it was disabled with
+    /// `ContextBuilder::srgb()`. Only has an effect on the window if it has an sRGB-capable
+    /// framebuffer.
+    pub fn set_srgb_enabled(&self, enabled: bool) {
+        let _guard = ::context::ContextGuard::new(self.raw);
+        self.inner.borrow_mut().enable_server_srgb(enabled);
+    }
+
+    /// Sets whether draws that don't override it with `DrawBuilder::multisample()` are
+    /// multisampled.
+    ///
+    /// Defaults to `true`. Only has an effect when drawing to a multisampled framebuffer.
+    pub fn set_multisample_enabled(&self, enabled: bool) {
+        self.inner.borrow_mut().default_multisample = enabled;
+    }
+
+    /// Sets the viewport used by draws that don't specify one with `DrawBuilder::viewport()`.
+    ///
+    /// The default viewport is reset to cover the full window when `resize()` is called.
+    pub fn set_viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.inner.borrow_mut().default_viewport = Viewport {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        };
+    }
+
+    /// Notifies the context that the window has been resized.
+    ///
+    /// This records the new dimensions and resets the default viewport to cover the full window.
+    /// Call this when the window sends `Message::Resize`.
+    pub fn resize(&self, width: i32, height: i32) {
+        let _guard = ::context::ContextGuard::new(self.raw);
+        let mut inner = self.inner.borrow_mut();
+        inner.dimensions = (width, height);
+        inner.default_viewport = Viewport {
+            x: 0,
+            y: 0,
+            width: width,
+            height: height,
+        };
+        inner.viewport(None);
+    }
+
+    /// Returns the width and height of the window in pixels, as of the last call to `resize()`.
+    pub fn dimensions(&self) -> (i32, i32) {
+        self.inner.borrow().dimensions
+    }
+
+    /// Reads back a block of pixels from the window's framebuffer.
+    ///
+    /// The pixels are returned as tightly packed RGBA bytes, with the rows ordered top-down
+    /// (the opposite of OpenGL's convention) so that the data can be written out directly as
+    /// an image. `x` and `y` specify the lower left corner of the block in window coordinates.
+    ///
+    /// The result is undefined for an offscreen context, read from the attachments of a
+    /// `Framebuffer` instead (e.g. with `Texture2d::read_data()`).
+    pub fn read_pixels(&self, x: i32, y: i32, width: i32, height: i32) -> Vec<u8> {
+        self.read_buffer_pixels(Face::Back, x, y, width, height)
+    }
+
+    /// Reads back a block of pixels from the window's front buffer, using the same layout as
+    /// `read_pixels()`.
+    ///
+    /// After `swap_buffers()` the contents of the back buffer are undefined, but the front buffer
+    /// holds the presented frame. Pixels that are covered by other windows or are offscreen may
+    /// still be undefined, since they're not owned by the context.
+    pub fn read_front_pixels(&self, x: i32, y: i32, width: i32, height: i32) -> Vec<u8> {
+        self.read_buffer_pixels(Face::Front, x, y, width, height)
+    }
+
+    fn read_buffer_pixels(&self, buffer: Face, x: i32, y: i32, width: i32, height: i32) -> Vec<u8> {
+        let _guard = ::context::ContextGuard::new(self.raw);
+        self.inner.borrow_mut().bind_framebuffer(FramebufferName::null());
+
+        let row_len = width as usize * TextureFormat::Rgba.elements();
+        let mut pixels = vec![0u8; row_len * height as usize];
+        unsafe {
+            gl::read_buffer(buffer);
+            gl::pixel_store_i32(PixelStoreParam::PackAlignment, 1);
+            gl::read_pixels(
+                x,
+                y,
+                width,
+                height,
+                TextureFormat::Rgba,
+                TextureDataType::u8,
+                pixels.as_mut_ptr() as *mut ());
+
+            // Restore the default so that other reads from the window aren't affected.
+            gl::read_buffer(Face::Back);
+        }
+
+        // Flip the rows so that the first row is the top of the image.
+        let mut flipped = Vec::with_capacity(pixels.len());
+        for row in pixels.chunks(row_len.max(1)).rev() {
+            flipped.extend_from_slice(row);
+        }
+
+        flipped
+    }
+
+    /// Sets a function to receive the debug messages generated by the driver.
+    ///
+    /// By default debug messages are printed to stdout. Messages are delivered synchronously
+    /// once a callback is set, so `callback` is called from within the OpenGL call that
+    /// generated the message, which makes it a good place to set a breakpoint. Messages generated
+    /// while `callback` is running are printed instead of being passed to `callback`.
+    pub fn set_debug_callback<F>(&self, callback: F) where F: 'static + FnMut(&DebugMessage) {
+        *self.debug_callback.0.borrow_mut() = Some(Box::new(callback));
+
         let _guard = ::context::ContextGuard::new(self.raw);
-        unsafe { gl::clear(ClearBufferMask::Color | ClearBufferMask::Depth); }
+        unsafe { gl::enable(ServerCapability::DebugOutputSynchronous); }
     }
 
+    /// Returns the error generated by an earlier OpenGL call, if any, and clears it.
+    ///
+    /// OpenGL records at most one error at a time (per error flag) and discards later errors
+    /// until the recorded one is checked, so the returned error isn't necessarily from the most
+    /// recent call. Enable the `gl-debug` feature to panic as soon as any call generates an
+    /// error instead.
+    pub fn check_error(&self) -> Option<ErrorCode> {
+        let _guard = ::context::ContextGuard::new(self.raw);
+        match unsafe { gl::get_error() } {
+            ErrorCode::NoError => None,
+            error => Some(error),
+        }
+    }
+
+    /// Presents the window's back buffer.
+    ///
+    /// Does nothing for an offscreen context, since there's no window to present to.
     pub fn swap_buffers(&self) {
+        if self.is_offscreen() {
+            return;
+        }
+
         let _guard = ::context::ContextGuard::new(self.raw);
         unsafe { gl::platform::swap_buffers(self.raw); }
     }
@@ -120,25 +573,54 @@ impl Context {
     pub(crate) fn inner(&self) -> Rc<RefCell<ContextInner>> {
         self.inner.clone()
     }
+
+    /// Returns the flag that tracks whether the context is still alive.
+    ///
+    /// Resources that only hold the raw context keep a copy of the flag so that they can skip
+    /// deleting their GL objects once the context has been destroyed.
+    pub(crate) fn alive(&self) -> Rc<Cell<bool>> {
+        self.inner.borrow().alive.clone()
+    }
+
+    pub(crate) fn share_group(&self) -> ShareGroup {
+        self.inner.borrow().share_group.clone()
+    }
 }
 
 #[derive(Debug)]
 pub(crate) struct ContextInner {
     raw: gl::Context,
 
+    /// Set to `false` when the `Context` is dropped. Destroying the context deletes all of its
+    /// objects, so resources that outlive it must not try to delete them again.
+    alive: Rc<Cell<bool>>,
+
+    /// Identifies the contexts that this context shares objects with.
+    share_group: ShareGroup,
+
     server_srgb_enabled: bool,
     server_cull_enabled: bool,
     server_depth_test_enabled: bool,
     server_blend_enabled: bool,
+    server_multisample_enabled: bool,
 
     bound_vertex_array: Option<VertexArrayName>,
+    bound_framebuffer: Option<FramebufferName>,
+    active_queries: HashMap<QueryTarget, QueryObject>,
     front_polygon_mode: PolygonMode,
     back_polygon_mode: PolygonMode,
     program: Option<ProgramObject>,
     cull_mode: Face,
     winding_order: WindingOrder,
     depth_test: Comparison,
+    depth_write: bool,
     blend: (SourceFactor, DestFactor),
+    viewport: Viewport,
+    default_viewport: Viewport,
+    default_multisample: bool,
+    dimensions: (i32, i32),
+    clear_color: (f32, f32, f32, f32),
+    limits: GlLimits,
 }
 
 impl ContextInner {
@@ -146,6 +628,18 @@ impl ContextInner {
         self.raw
     }
 
+    pub(crate) fn is_alive(&self) -> bool {
+        self.alive.get()
+    }
+
+    pub(crate) fn limits(&self) -> &GlLimits {
+        &self.limits
+    }
+
+    pub(crate) fn share_group(&self) -> &ShareGroup {
+        &self.share_group
+    }
+
     pub(crate) fn bind_vertex_array(&mut self, vertex_array_name: VertexArrayName) {
         if Some(vertex_array_name) != self.bound_vertex_array {
             unsafe { gl::bind_vertex_array(vertex_array_name); }
@@ -160,6 +654,65 @@ impl ContextInner {
         }
     }
 
+    pub(crate) fn bind_framebuffer(&mut self, framebuffer_name: FramebufferName) {
+        if Some(framebuffer_name) != self.bound_framebuffer {
+            unsafe { gl::bind_framebuffer(FramebufferTarget::Framebuffer, framebuffer_name); }
+            self.bound_framebuffer = Some(framebuffer_name);
+        }
+    }
+
+    pub(crate) fn unbind_framebuffer(&mut self, framebuffer_name: FramebufferName) {
+        if Some(framebuffer_name) == self.bound_framebuffer {
+            unsafe { gl::bind_framebuffer(FramebufferTarget::Framebuffer, FramebufferName::null()); }
+            self.bound_framebuffer = Some(FramebufferName::null());
+        }
+    }
+
+    /// Marks the framebuffer binding as unknown, e.g. after binding separate read and draw
+    /// framebuffers, so that the next call to `bind_framebuffer()` always rebinds.
+    pub(crate) fn forget_framebuffer(&mut self) {
+        self.bound_framebuffer = None;
+    }
+
+    pub(crate) fn begin_query(&mut self, target: QueryTarget, query: QueryObject) {
+        if let Some(&active) = self.active_queries.get(&query_slot(target)) {
+            panic!(
+                "Cannot begin {:?} query {:?} while query {:?} of the same kind is active, queries of the same kind cannot be nested",
+                target,
+                query,
+                active);
+        }
+
+        unsafe { gl::begin_query(target, query); }
+        self.active_queries.insert(query_slot(target), query);
+    }
+
+    pub(crate) fn end_query(&mut self, target: QueryTarget, query: QueryObject) {
+        assert!(
+            self.active_queries.get(&query_slot(target)) == Some(&query),
+            "Cannot end {:?} query {:?} because it is not active",
+            target,
+            query);
+
+        unsafe { gl::end_query(target); }
+        self.active_queries.remove(&query_slot(target));
+    }
+
+    /// Forgets `query` if it's active. Deleting an active query implicitly ends it.
+    pub(crate) fn forget_query(&mut self, target: QueryTarget, query: QueryObject) {
+        if self.active_queries.get(&query_slot(target)) == Some(&query) {
+            self.active_queries.remove(&query_slot(target));
+        }
+    }
+
+    pub(crate) fn clear_color(&mut self, color: (f32, f32, f32, f32)) {
+        if color != self.clear_color {
+            let (red, green, blue, alpha) = color;
+            unsafe { gl::clear_color(red, green, blue, alpha); }
+            self.clear_color = color;
+        }
+    }
+
     pub(crate) fn polygon_mode(&mut self, mode: PolygonMode) {
         if mode != self.front_polygon_mode || mode != self.back_polygon_mode {
             unsafe { gl::polygon_mode(Face::FrontAndBack, mode); }
@@ -179,6 +732,16 @@ impl ContextInner {
         }
     }
 
+    pub(crate) fn enable_server_srgb(&mut self, enabled: bool) {
+        if enabled != self.server_srgb_enabled {
+            match enabled {
+                true => unsafe { gl::enable(ServerCapability::FramebufferSrgb); },
+                false => unsafe { gl::disable(ServerCapability::FramebufferSrgb); },
+            }
+            self.server_srgb_enabled = enabled;
+        }
+    }
+
     pub(crate) fn enable_server_cull(&mut self, enabled: bool) {
         if enabled != self.server_cull_enabled {
             match enabled {
@@ -199,6 +762,19 @@ impl ContextInner {
         }
     }
 
+    /// Enables or disables multisampling, or restores the default set with
+    /// `Context::set_multisample_enabled()` if `enabled` is `None`.
+    pub(crate) fn multisample(&mut self, enabled: Option<bool>) {
+        let enabled = enabled.unwrap_or(self.default_multisample);
+        if enabled != self.server_multisample_enabled {
+            match enabled {
+                true => unsafe { gl::enable(ServerCapability::Multisample); },
+                false => unsafe { gl::disable(ServerCapability::Multisample); },
+            }
+            self.server_multisample_enabled = enabled;
+        }
+    }
+
     pub(crate) fn cull_mode(&mut self, face: Face) {
         if self.cull_mode != face {
             unsafe { gl::cull_face(face); }
@@ -220,24 +796,251 @@ impl ContextInner {
         }
     }
 
+    /// Enables or disables writing to the depth buffer.
+    ///
+    /// Depth writes also mask depth clears, so they must be re-enabled before clearing.
+    pub(crate) fn depth_write(&mut self, enabled: bool) {
+        if enabled != self.depth_write {
+            unsafe { gl::depth_mask(enabled.into()); }
+            self.depth_write = enabled;
+        }
+    }
+
     pub(crate) fn blend(&mut self, source_factor: SourceFactor, dest_factor: DestFactor) {
         if (source_factor, dest_factor) != self.blend {
             unsafe { gl::blend_func(source_factor, dest_factor); }
             self.blend = (source_factor, dest_factor);
         }
     }
+
+    /// Sets the viewport, or restores the default viewport if `viewport` is `None`.
+    pub(crate) fn viewport(&mut self, viewport: Option<Viewport>) {
+        let viewport = viewport.unwrap_or(self.default_viewport);
+        if viewport != self.viewport {
+            unsafe { gl::viewport(viewport.x, viewport.y, viewport.width, viewport.height); }
+            self.viewport = viewport;
+        }
+    }
+}
+
+/// Combines the selected buffers into a mask for `gl::clear()`, or returns `None` if no buffers
+/// are selected.
+pub(crate) fn clear_mask(color: bool, depth: bool, stencil: bool) -> Option<ClearBufferMask> {
+    [
+        (color, ClearBufferMask::Color),
+        (depth, ClearBufferMask::Depth),
+        (stencil, ClearBufferMask::Stencil),
+    ]
+        .iter()
+        .filter(|&&(selected, _)| selected)
+        .map(|&(_, mask)| mask)
+        .fold(None, |acc, mask| match acc {
+            Some(acc) => Some(acc | mask),
+            None => Some(mask),
+        })
+}
+
+/// Returns the target used to track whether a query of `target`'s kind is active.
+///
+/// All occlusion query targets share a single slot, since OpenGL doesn't allow an occlusion query
+/// to begin while one of a different occlusion target is active.
+fn query_slot(target: QueryTarget) -> QueryTarget {
+    match target {
+        QueryTarget::SamplesPassed | QueryTarget::AnySamplesPassed => QueryTarget::SamplesPassed,
+        QueryTarget::TimeElapsed => QueryTarget::TimeElapsed,
+    }
+}
+
+/// A rectangular region of the window, in pixels, with the origin at the bottom-left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Viewport {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
 }
 
 impl Drop for Context {
     fn drop(&mut self) {
+        assert_thread_context(self.raw);
+        self.inner.borrow().alive.set(false);
+
         unsafe {
             gl::make_current(self.raw);
             gl::debug_message_callback(None, ptr::null_mut());
-            gl::destroy_context(self.raw)
+            gl::destroy_context(self.raw);
+            unregister_thread_context(self.raw);
+
+            if let Some(hidden_window) = self.hidden_window.take() {
+                gl::destroy_hidden_window(hidden_window);
+            }
+        }
+    }
+}
+
+/// Identifies a group of contexts that share objects.
+///
+/// Every context belongs to exactly one share group. Contexts created with
+/// `Context::new_shared()` join the group of the context they're created from.
+#[derive(Debug, Clone)]
+pub(crate) struct ShareGroup(Rc<()>);
+
+impl ShareGroup {
+    fn new() -> ShareGroup {
+        ShareGroup(Rc::new(()))
+    }
+}
+
+impl PartialEq for ShareGroup {
+    fn eq(&self, other: &ShareGroup) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ShareGroup {}
+
+/// Implementation-dependent limits of an OpenGL context.
+///
+/// The limits are queried once when the context is created, see `Context::limits()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlLimits {
+    /// The maximum width and height of a 2D texture, and of each layer of a texture array.
+    pub max_texture_size: usize,
+
+    /// The maximum width, height, and depth of a 3D texture.
+    pub max_3d_texture_size: usize,
+
+    /// The maximum width and height of each face of a cube map.
+    pub max_cube_map_texture_size: usize,
+
+    /// The maximum number of layers in a texture array.
+    pub max_array_texture_layers: usize,
+
+    /// The maximum width and height of a renderbuffer.
+    pub max_renderbuffer_size: usize,
+
+    /// The maximum number of textures that can be sampled in the fragment shader.
+    pub max_texture_image_units: usize,
+
+    /// The maximum number of textures that can be sampled in the vertex shader. May be 0 on
+    /// implementations that don't support texture fetches in the vertex shader.
+    pub max_vertex_texture_image_units: usize,
+
+    /// The maximum number of textures that can be bound for a single draw, across all shader
+    /// stages.
+    pub max_combined_texture_image_units: usize,
+
+    /// The maximum number of vertex attributes.
+    pub max_vertex_attribs: usize,
+
+    /// The maximum size of a uniform block in bytes.
+    pub max_uniform_block_size: usize,
+
+    /// The number of uniform buffer binding points.
+    pub max_uniform_buffer_bindings: usize,
+
+    /// The maximum number of color attachments of a framebuffer.
+    pub max_color_attachments: usize,
+
+    /// The maximum number of color attachments that can be drawn to at once.
+    pub max_draw_buffers: usize,
+
+    /// The maximum number of samples in a multisampled renderbuffer.
+    pub max_samples: usize,
+}
+
+impl GlLimits {
+    /// Queries the limits of the current context.
+    unsafe fn query() -> GlLimits {
+        fn get(name: IntegerName) -> usize {
+            let mut value = 0;
+            unsafe { gl::get_integers(name, &mut value); }
+            value as usize
+        }
+
+        GlLimits {
+            max_texture_size: get(IntegerName::MaxTextureSize),
+            max_3d_texture_size: get(IntegerName::Max3dTextureSize),
+            max_cube_map_texture_size: get(IntegerName::MaxCubeMapTextureSize),
+            max_array_texture_layers: get(IntegerName::MaxArrayTextureLayers),
+            max_renderbuffer_size: get(IntegerName::MaxRenderbufferSize),
+            max_texture_image_units: get(IntegerName::MaxTextureImageUnits),
+            max_vertex_texture_image_units: get(IntegerName::MaxVertexTextureImageUnits),
+            max_combined_texture_image_units: get(IntegerName::MaxCombinedTextureImageUnits),
+            max_vertex_attribs: get(IntegerName::MaxVertexAttribs),
+            max_uniform_block_size: get(IntegerName::MaxUniformBlockSize),
+            max_uniform_buffer_bindings: get(IntegerName::MaxUniformBufferBindings),
+            max_color_attachments: get(IntegerName::MaxColorAttachments),
+            max_draw_buffers: get(IntegerName::MaxDrawBuffers),
+            max_samples: get(IntegerName::MaxSamples),
         }
     }
 }
 
+/// Reads one of the strings describing the current context.
+unsafe fn get_string(name: StringName) -> String {
+    CStr::from_ptr(gl::get_string(name)).to_string_lossy().into_owned()
+}
+
+/// A message generated by the driver through the `KHR_debug` debug output.
+#[derive(Debug, Clone)]
+pub struct DebugMessage {
+    pub source: DebugSource,
+    pub message_type: DebugType,
+    pub id: u32,
+    pub severity: DebugSeverity,
+    pub message: String,
+}
+
+struct DebugCallback(RefCell<Option<Box<FnMut(&DebugMessage)>>>);
+
+impl ::std::fmt::Debug for DebugCallback {
+    fn fmt(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        let is_set = self.0.try_borrow().map(|callback| callback.is_some()).unwrap_or(true);
+        write!(formatter, "DebugCallback {{ is_set: {} }}", is_set)
+    }
+}
+
+extern "system" fn debug_message_callback(
+    source: DebugSource,
+    message_type: DebugType,
+    id: u32,
+    severity: DebugSeverity,
+    _length: i32,
+    message: *const u8,
+    user_param: *mut ()
+) {
+    let message = DebugMessage {
+        source: source,
+        message_type: message_type,
+        id: id,
+        severity: severity,
+        message: unsafe { CStr::from_ptr(message as *const _) }.to_string_lossy().into_owned(),
+    };
+
+    // The user parameter is the context's debug callback, which lives as long as the context.
+    let debug_callback = unsafe { &*(user_param as *const DebugCallback) };
+    if let Ok(mut callback) = debug_callback.0.try_borrow_mut() {
+        if let Some(ref mut callback) = *callback {
+            callback(&message);
+            return;
+        }
+    }
+
+    println!(
+        r#"Recieved some kind of debug message.
+        source: {:?},
+        type: {:?},
+        id: 0x{:x},
+        severity: {:?},
+        message: {}"#,
+        message.source,
+        message.message_type,
+        message.id,
+        message.severity,
+        message.message);
+}
+
 #[derive(Debug)]
 pub enum Error {
     /// Indicates that the program was unable to find an active device context.
@@ -250,13 +1053,71 @@ pub enum Error {
     ///
     /// This might happen because reasons.
     UnableToCreateRenderContext,
+
+    /// Indicates that the driver doesn't support the OpenGL version requested with
+    /// `ContextBuilder::version()`.
+    ///
+    /// `actual` is the version of the context the driver created, or `None` if the driver refused
+    /// to create a context with the requested version at all.
+    UnsupportedVersion {
+        requested: (u32, u32),
+        actual: Option<(u32, u32)>,
+    },
+}
+
+/// Labels an OpenGL object so that it can be identified in debug messages and graphics debuggers.
+///
+/// Does nothing if `glObjectLabel` isn't available, i.e. if the driver supports neither OpenGL 4.3
+/// nor `KHR_debug`. The object's context must be current.
+pub(crate) fn set_object_label(identifier: DebugMessageId, name: u32, label: &str) {
+    unsafe {
+        if gl::set_object_label::load().is_none() {
+            return;
+        }
+
+        gl::set_object_label(identifier, name, label.len() as i32, label.as_ptr());
+    }
+}
+
+thread_local! {
+    /// The contexts that were created on the current thread.
+    ///
+    /// An OpenGL context can only be current on one thread at a time, and making it current on
+    /// another thread fails in ways that are hard to track down, so each context may only be
+    /// used on the thread that created it.
+    static THREAD_CONTEXTS: RefCell<Vec<gl::Context>> = RefCell::new(Vec::new());
+}
+
+fn register_thread_context(context: gl::Context) {
+    THREAD_CONTEXTS.with(|contexts| contexts.borrow_mut().push(context));
+}
+
+fn unregister_thread_context(context: gl::Context) {
+    THREAD_CONTEXTS.with(|contexts| contexts.borrow_mut().retain(|&other| other != context));
+}
+
+/// Panics if `context` wasn't created on the current thread.
+fn assert_thread_context(context: gl::Context) {
+    let is_thread_context = THREAD_CONTEXTS.with(|contexts| contexts.borrow().contains(&context));
+    assert!(
+        is_thread_context,
+        "OpenGL context {:?} used on thread {:?}, but it was created on a different thread. Contexts and the objects created with them can only be used on the thread that created the context",
+        context,
+        thread::current().name().unwrap_or("<unnamed>")
+    );
 }
 
 #[derive(Debug)]
 pub(crate) struct ContextGuard(gl::Context);
 
 impl ContextGuard {
+    /// Makes `context` current until the guard is dropped.
+    ///
+    /// # Panics
+    ///
+    /// - If `context` was created on a different thread.
     pub fn new(context: gl::Context) -> ContextGuard {
+        assert_thread_context(context);
         let old = unsafe { gl::make_current(context) };
         ContextGuard(old)
     }
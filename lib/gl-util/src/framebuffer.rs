@@ -0,0 +1,402 @@
+//! Offscreen render targets.
+//!
+//! A `Framebuffer` collects a set of images (textures or renderbuffers) that draws can render
+//! into instead of the window. Use `DrawBuilder::framebuffer()` to draw into a framebuffer.
+
+use context::{Context, ContextInner, ShareGroup};
+use gl;
+use gl::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use texture::Texture2d;
+
+pub use gl::{ClearBufferMask, FramebufferStatus};
+
+/// A framebuffer object, used to render into textures and renderbuffers.
+///
+/// The framebuffer doesn't own its attachments. Attached textures and renderbuffers must outlive
+/// any draws into the framebuffer, otherwise the framebuffer becomes incomplete.
+#[derive(Debug)]
+pub struct Framebuffer {
+    framebuffer_name: FramebufferName,
+
+    context: Rc<RefCell<ContextInner>>,
+}
+
+impl Framebuffer {
+    /// Creates a new framebuffer with no attachments.
+    pub fn new(context: &Context) -> Framebuffer {
+        let mut framebuffer_name = FramebufferName::null();
+        unsafe {
+            let _guard = ::context::ContextGuard::new(context.raw());
+            gl::gen_framebuffers(1, &mut framebuffer_name);
+        }
+
+        Framebuffer {
+            framebuffer_name: framebuffer_name,
+
+            context: context.inner(),
+        }
+    }
+
+    /// Attaches a texture as the color attachment at `index`.
+    ///
+    /// Only color attachment 0 is drawn into by default, use `set_draw_buffers()` to write to
+    /// multiple color attachments at once.
+    ///
+    /// # Panics
+    ///
+    /// - If `texture` is not shared with the framebuffer's context.
+    /// - If `index` is not less than `GL_MAX_COLOR_ATTACHMENTS`.
+    pub fn attach_color(&mut self, index: usize, texture: &Texture2d) {
+        let attachment = self.color_attachment(index);
+        self.attach_texture(attachment, texture);
+    }
+
+    /// Attaches a renderbuffer as the color attachment at `index`.
+    ///
+    /// # Panics
+    ///
+    /// - If `renderbuffer` is not shared with the framebuffer's context.
+    /// - If `index` is not less than `GL_MAX_COLOR_ATTACHMENTS`.
+    pub fn attach_color_renderbuffer(&mut self, index: usize, renderbuffer: &Renderbuffer) {
+        let attachment = self.color_attachment(index);
+        self.attach_renderbuffer(attachment, renderbuffer);
+    }
+
+    /// Sets which color attachments the fragment shader's outputs are written to.
+    ///
+    /// Fragment output `i` is written to color attachment `attachments[i]`, so binding the
+    /// shader's outputs to locations with `ProgramBuilder::bind_frag_output()` and calling
+    /// `set_draw_buffers(&[0, 1, 2])` writes outputs 0, 1, and 2 to color attachments 0, 1,
+    /// and 2. The draw buffers are part of the framebuffer's state, so they only need to be set
+    /// once.
+    ///
+    /// # Panics
+    ///
+    /// - If `attachments` has more than `GL_MAX_DRAW_BUFFERS` elements.
+    /// - If any element of `attachments` is not less than `GL_MAX_COLOR_ATTACHMENTS`.
+    pub fn set_draw_buffers(&mut self, attachments: &[usize]) {
+        let buffers = attachments
+            .iter()
+            .map(|&index| self.color_attachment(index))
+            .collect::<Vec<_>>();
+
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+
+        let max_draw_buffers = context.limits().max_draw_buffers;
+        assert!(
+            buffers.len() <= max_draw_buffers,
+            "Cannot draw to {} color attachments, only {} draw buffers are supported",
+            buffers.len(),
+            max_draw_buffers
+        );
+
+        context.bind_framebuffer(self.framebuffer_name);
+        unsafe { gl::draw_buffers(buffers.len() as i32, buffers.as_ptr()); }
+    }
+
+    /// Attaches a depth texture as the depth attachment.
+    ///
+    /// See `Texture2d::depth()` for creating a depth texture.
+    ///
+    /// # Panics
+    ///
+    /// - If `texture` is not shared with the framebuffer's context.
+    pub fn attach_depth(&mut self, texture: &Texture2d) {
+        self.attach_texture(FramebufferAttachment::depth(), texture);
+    }
+
+    /// Attaches a renderbuffer as the depth and stencil attachment.
+    ///
+    /// Use a renderbuffer rather than a depth texture when the depth buffer doesn't need to be
+    /// sampled after rendering. The renderbuffer should have a combined depth-stencil format
+    /// such as `TextureInternalFormat::Depth24Stencil8`.
+    ///
+    /// # Panics
+    ///
+    /// - If `renderbuffer` is not shared with the framebuffer's context.
+    pub fn attach_depth_stencil(&mut self, renderbuffer: &Renderbuffer) {
+        self.attach_renderbuffer(FramebufferAttachment::depth_stencil(), renderbuffer);
+    }
+
+    /// Checks that the framebuffer can be rendered into.
+    ///
+    /// Returns the reason the framebuffer is incomplete if it's not, e.g. because it has no
+    /// attachments or its attachments have different sample counts.
+    pub fn check(&self) -> Result<(), FramebufferStatus> {
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+        context.bind_framebuffer(self.framebuffer_name);
+
+        let status = unsafe { gl::check_framebuffer_status(FramebufferTarget::Framebuffer) };
+        match FramebufferStatus::from_raw(status) {
+            FramebufferStatus::Complete => Ok(()),
+            status => Err(status),
+        }
+    }
+
+    /// Clears the color and depth attachments of the framebuffer.
+    pub fn clear(&self) {
+        self.clear_flags(true, true, false);
+    }
+
+    /// Clears the selected attachments of the framebuffer.
+    ///
+    /// Works the same as `Context::clear_flags()` for the window. Does nothing if no buffers are
+    /// selected.
+    pub fn clear_flags(&self, color: bool, depth: bool, stencil: bool) {
+        if let Some(mask) = ::context::clear_mask(color, depth, stencil) {
+            let mut context = self.context.borrow_mut();
+            let _guard = ::context::ContextGuard::new(context.raw());
+            context.bind_framebuffer(self.framebuffer_name);
+            if depth { context.depth_write(true); }
+
+            unsafe { gl::clear(mask); }
+        }
+    }
+
+    /// Clears the selected attachments of the framebuffer within a rectangle, leaving the rest of
+    /// the framebuffer untouched.
+    ///
+    /// `x` and `y` give the bottom-left corner of the rectangle in pixels. Useful when several
+    /// images share one texture, such as the tiles of an atlas.
+    pub fn clear_region(&self, x: i32, y: i32, width: i32, height: i32, color: bool, depth: bool, stencil: bool) {
+        if let Some(mask) = ::context::clear_mask(color, depth, stencil) {
+            let mut context = self.context.borrow_mut();
+            let _guard = ::context::ContextGuard::new(context.raw());
+            context.bind_framebuffer(self.framebuffer_name);
+            if depth { context.depth_write(true); }
+
+            // The scissor test isn't used anywhere else, so it's only enabled for the clear.
+            unsafe {
+                gl::scissor(x, y, width, height);
+                gl::enable(ServerCapability::ScissorTest);
+                gl::clear(mask);
+                gl::disable(ServerCapability::ScissorTest);
+            }
+        }
+    }
+
+    /// Copies the contents of the framebuffer into another framebuffer.
+    ///
+    /// If `target` is `None` the contents are copied into the window's framebuffer. `width` and
+    /// `height` give the size of the region to copy, starting at the bottom-left corner of both
+    /// framebuffers. `mask` specifies which of the color, depth, and stencil buffers to copy.
+    ///
+    /// Blitting from a multisampled framebuffer into a single-sampled one resolves the samples,
+    /// which is how multisampled offscreen rendering is displayed.
+    pub fn blit_to(
+        &self,
+        target: Option<&Framebuffer>,
+        width: i32,
+        height: i32,
+        mask: ClearBufferMask,
+    ) {
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+
+        if let Some(target) = target {
+            assert!(
+                Rc::ptr_eq(&self.context, &target.context),
+                "Target framebuffer's context does not match source framebuffer's context"
+            );
+        }
+
+        let target_name = target.map(Framebuffer::inner).unwrap_or(FramebufferName::null());
+        unsafe {
+            gl::bind_framebuffer(FramebufferTarget::ReadFramebuffer, self.framebuffer_name);
+            gl::bind_framebuffer(FramebufferTarget::DrawFramebuffer, target_name);
+            gl::blit_framebuffer(
+                0, 0, width, height,
+                0, 0, width, height,
+                mask,
+                TextureFilterFunction::Nearest);
+        }
+
+        // The read and draw bindings no longer match, so make sure the next draw rebinds.
+        context.forget_framebuffer();
+    }
+
+    pub(crate) fn inner(&self) -> FramebufferName {
+        self.framebuffer_name
+    }
+
+    pub(crate) fn context_matches(&self, context: &Rc<RefCell<ContextInner>>) -> bool {
+        Rc::ptr_eq(&self.context, context)
+    }
+
+    fn color_attachment(&self, index: usize) -> FramebufferAttachment {
+        let max_color_attachments = self.context.borrow().limits().max_color_attachments;
+        assert!(
+            index < max_color_attachments,
+            "Color attachment {} is out of range, only {} color attachments are supported",
+            index,
+            max_color_attachments
+        );
+
+        FramebufferAttachment::color(index as u32)
+    }
+
+    fn attach_texture(&mut self, attachment: FramebufferAttachment, texture: &Texture2d) {
+        let mut context = self.context.borrow_mut();
+        assert!(
+            &texture.share_group == context.share_group(),
+            "Specified texture is not shared with framebuffer's context"
+        );
+
+        let _guard = ::context::ContextGuard::new(context.raw());
+        context.bind_framebuffer(self.framebuffer_name);
+        unsafe {
+            gl::framebuffer_texture_2d(
+                FramebufferTarget::Framebuffer,
+                attachment,
+                Texture2dTarget::Texture2d,
+                texture.inner(),
+                0);
+        }
+    }
+
+    fn attach_renderbuffer(&mut self, attachment: FramebufferAttachment, renderbuffer: &Renderbuffer) {
+        let mut context = self.context.borrow_mut();
+        assert!(
+            &renderbuffer.share_group == context.share_group(),
+            "Specified renderbuffer is not shared with framebuffer's context"
+        );
+
+        let _guard = ::context::ContextGuard::new(context.raw());
+        context.bind_framebuffer(self.framebuffer_name);
+        unsafe {
+            gl::framebuffer_renderbuffer(
+                FramebufferTarget::Framebuffer,
+                attachment,
+                RenderbufferTarget::Renderbuffer,
+                renderbuffer.renderbuffer_name);
+        }
+    }
+
+    /// Labels the framebuffer so that it can be identified in debug messages and graphics debuggers
+    /// such as RenderDoc.
+    ///
+    /// Does nothing if the driver doesn't support `KHR_debug`.
+    pub fn set_debug_label(&self, label: &str) {
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+
+        // The framebuffer object isn't created until it's first bound.
+        context.bind_framebuffer(self.framebuffer_name);
+        ::context::set_object_label(DebugMessageId::GL_FRAMEBUFFER, self.framebuffer_name.0, label);
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        let mut context = self.context.borrow_mut();
+        if !context.is_alive() {
+            return;
+        }
+
+        let _guard = ::context::ContextGuard::new(context.raw());
+        unsafe { gl::delete_framebuffers(1, &self.framebuffer_name); }
+        context.unbind_framebuffer(self.framebuffer_name);
+    }
+}
+
+/// Image storage that can be attached to a `Framebuffer` but can't be sampled.
+///
+/// Renderbuffers are useful for attachments whose contents are only needed while rendering, such
+/// as the depth buffer for an offscreen color pass, and for multisampled rendering.
+#[derive(Debug)]
+pub struct Renderbuffer {
+    renderbuffer_name: RenderbufferName,
+    width: usize,
+    height: usize,
+    samples: u32,
+
+    pub(crate) context: gl::Context,
+    context_alive: Rc<Cell<bool>>,
+    pub(crate) share_group: ShareGroup,
+}
+
+impl Renderbuffer {
+    /// Creates a new renderbuffer with the specified format and size.
+    pub fn new(
+        context: &Context,
+        internal_format: TextureInternalFormat,
+        width: usize,
+        height: usize,
+    ) -> Renderbuffer {
+        Renderbuffer::multisample(context, internal_format, width, height, 0)
+    }
+
+    /// Creates a new multisampled renderbuffer.
+    ///
+    /// A `samples` of 0 creates a renderbuffer that isn't multisampled. All multisampled
+    /// attachments of a framebuffer must have the same number of samples. The driver may
+    /// allocate more samples than requested.
+    pub fn multisample(
+        context: &Context,
+        internal_format: TextureInternalFormat,
+        width: usize,
+        height: usize,
+        samples: u32,
+    ) -> Renderbuffer {
+        let context_alive = context.alive();
+        let share_group = context.share_group();
+        let context = context.raw();
+        let _guard = ::context::ContextGuard::new(context);
+
+        let mut renderbuffer_name = RenderbufferName::null();
+        unsafe {
+            gl::gen_renderbuffers(1, &mut renderbuffer_name);
+            gl::bind_renderbuffer(RenderbufferTarget::Renderbuffer, renderbuffer_name);
+            gl::renderbuffer_storage_multisample(
+                RenderbufferTarget::Renderbuffer,
+                samples as i32,
+                internal_format,
+                width as i32,
+                height as i32);
+            gl::bind_renderbuffer(RenderbufferTarget::Renderbuffer, RenderbufferName::null());
+        }
+
+        Renderbuffer {
+            renderbuffer_name: renderbuffer_name,
+            width: width,
+            height: height,
+            samples: samples,
+
+            context: context,
+            context_alive: context_alive,
+            share_group: share_group,
+        }
+    }
+
+    /// Returns the width of the renderbuffer in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the renderbuffer in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the number of samples that were requested for the renderbuffer.
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+}
+
+impl Drop for Renderbuffer {
+    fn drop(&mut self) {
+        if !self.context_alive.get() {
+            return;
+        }
+
+        unsafe {
+            let _guard = ::context::ContextGuard::new(self.context);
+            gl::delete_renderbuffers(1, &self.renderbuffer_name);
+        }
+    }
+}
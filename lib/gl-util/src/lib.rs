@@ -12,14 +12,17 @@
 extern crate bootstrap_rs as bootstrap;
 extern crate bootstrap_gl as gl;
 
-use context::{Context, ContextInner};
+use context::{Context, ContextInner, ShareGroup, Viewport};
+use framebuffer::Framebuffer;
+use query::OcclusionQuery;
 use gl::*;
 use shader::Program;
-use std::mem;
-use std::cell::RefCell;
+use std::{mem, ptr, slice};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::ops::Range;
 use std::rc::Rc;
-use texture::Texture2d;
+use texture::{Texture2d, Texture2dArray, Texture3d, TextureCube};
 
 pub use gl::{
     AttributeLocation,
@@ -27,6 +30,7 @@ pub use gl::{
     DestFactor,
     DrawMode,
     Face,
+    IndexType,
     PolygonMode,
     ShaderType,
     SourceFactor,
@@ -34,7 +38,10 @@ pub use gl::{
 };
 
 pub mod context;
+pub mod framebuffer;
+pub mod query;
 pub mod shader;
+pub mod sync;
 pub mod texture;
 
 #[cfg(target_os="windows")]
@@ -48,15 +55,33 @@ pub mod platform;
 pub struct VertexBuffer {
     buffer_name: BufferName,
     len: usize,
+
+    /// The number of floats the buffer's storage has room for.
+    capacity: usize,
     element_len: usize,
     attribs: HashMap<String, AttribLayout>,
 
+    /// `true` while the buffer is mapped with `map_write()`, unless the mapping is persistent.
+    mapped: bool,
+
+    /// The persistent mapping of the buffer's storage, if it was allocated with
+    /// `allocate_f32()` and the context supports persistent mapping.
+    persistent_mapping: Option<*mut f32>,
+
+    /// Whether the context supports `glBufferStorage`, which is needed for persistent mapping.
+    supports_persistent: bool,
+
     pub(crate) context: gl::Context,
+    context_alive: Rc<Cell<bool>>,
+    pub(crate) share_group: ShareGroup,
 }
 
 impl VertexBuffer {
     /// Creates a new `VertexBuffer` object.
     pub fn new(context: &Context) -> VertexBuffer {
+        let context_alive = context.alive();
+        let share_group = context.share_group();
+        let supports_persistent = context.version() >= (4, 4);
         let context = context.raw();
 
         let mut buffer_name = BufferName::null();
@@ -68,15 +93,31 @@ impl VertexBuffer {
         VertexBuffer {
             buffer_name: buffer_name,
             len: 0,
+            capacity: 0,
             element_len: 0,
             attribs: HashMap::new(),
 
+            mapped: false,
+            persistent_mapping: None,
+            supports_persistent: supports_persistent,
+
             context: context,
+            context_alive: context_alive,
+            share_group: share_group,
         }
     }
 
     /// Fills the buffer with the contents of the data slice.
+    ///
+    /// The buffer's storage is only reallocated if `data` doesn't fit in it, otherwise the data
+    /// is copied into the existing storage.
+    ///
+    /// # Panics
+    ///
+    /// - If the buffer is mapped.
+    /// - If the buffer's storage was allocated with `allocate_f32()` using persistent mapping.
     pub fn set_data_f32(&mut self, data: &[f32]) {
+        self.assert_mutable_storage();
         self.len = data.len();
 
         let data_ptr = data.as_ptr() as *const ();
@@ -85,15 +126,119 @@ impl VertexBuffer {
         unsafe {
             let _guard = ::context::ContextGuard::new(self.context);
             gl::bind_buffer(BufferTarget::Array, self.buffer_name);
-            gl::buffer_data(
-                BufferTarget::Array,
-                byte_count as isize,
-                data_ptr,
-                BufferUsage::StaticDraw);
+            if data.len() <= self.capacity {
+                gl::buffer_sub_data(BufferTarget::Array, 0, byte_count as isize, data_ptr);
+            } else {
+                gl::buffer_data(
+                    BufferTarget::Array,
+                    byte_count as isize,
+                    data_ptr,
+                    BufferUsage::StaticDraw);
+                self.capacity = data.len();
+            }
             gl::bind_buffer(BufferTarget::Array, BufferName::null());
         }
     }
 
+    /// Allocates uninitialized storage for `len` floats, to be filled in with `map_write()`.
+    ///
+    /// This is meant for vertex data that's rewritten every frame. If `persistent` is `true` and
+    /// the context supports OpenGL 4.4, the storage is mapped once when it's allocated and stays
+    /// mapped while it's used for drawing, so `map_write()` doesn't have to map and unmap the
+    /// buffer each time. Writes to a persistent mapping are visible to draws immediately, so the
+    /// caller is responsible for not overwriting data that an earlier draw may still be reading.
+    /// Otherwise the buffer is mapped and unmapped for each call to `map_write()`.
+    ///
+    /// Persistent storage can't be reallocated, so `allocate_f32()` and `set_data_f32()` panic
+    /// for a buffer that was allocated with persistent mapping.
+    ///
+    /// # Panics
+    ///
+    /// - If the buffer is mapped.
+    /// - If the buffer's storage was already allocated with persistent mapping.
+    pub fn allocate_f32(&mut self, len: usize, persistent: bool) {
+        self.assert_mutable_storage();
+        self.len = len;
+        self.capacity = len;
+
+        let byte_count = (len * mem::size_of::<f32>()) as isize;
+
+        unsafe {
+            let _guard = ::context::ContextGuard::new(self.context);
+            gl::bind_buffer(BufferTarget::Array, self.buffer_name);
+            if persistent && self.supports_persistent && len > 0 {
+                let access = BufferAccess::write() | BufferAccess::persistent() | BufferAccess::coherent();
+                gl::buffer_storage(BufferTarget::Array, byte_count, ptr::null(), access);
+                let mapping = gl::map_buffer_range(BufferTarget::Array, 0, byte_count, access);
+                assert!(!mapping.is_null(), "Failed to persistently map vertex buffer");
+                self.persistent_mapping = Some(mapping as *mut f32);
+            } else {
+                gl::buffer_data(
+                    BufferTarget::Array,
+                    byte_count,
+                    ptr::null(),
+                    BufferUsage::StreamDraw);
+            }
+            gl::bind_buffer(BufferTarget::Array, BufferName::null());
+        }
+    }
+
+    /// Maps the buffer's storage so that its contents can be written directly.
+    ///
+    /// The previous contents of the buffer are discarded, so the whole buffer should be written
+    /// before it's drawn. The buffer is unmapped when the returned `MappedBuffer` is dropped. If
+    /// the buffer was allocated with persistent mapping (see `allocate_f32()`) the persistent
+    /// mapping is returned instead and nothing is unmapped.
+    ///
+    /// # Panics
+    ///
+    /// - If the buffer is empty.
+    /// - If the buffer is already mapped, e.g. because a previous `MappedBuffer` was leaked.
+    pub fn map_write(&mut self) -> MappedBuffer {
+        assert!(self.len > 0, "Cannot map an empty vertex buffer");
+        assert!(!self.mapped, "Vertex buffer is already mapped");
+
+        let guard = ::context::ContextGuard::new(self.context);
+        let data = match self.persistent_mapping {
+            Some(mapping) => mapping,
+            None => unsafe {
+                let byte_count = (self.len * mem::size_of::<f32>()) as isize;
+                gl::bind_buffer(BufferTarget::Array, self.buffer_name);
+                let mapping = gl::map_buffer_range(
+                    BufferTarget::Array,
+                    0,
+                    byte_count,
+                    BufferAccess::write() | BufferAccess::invalidate_buffer());
+                gl::bind_buffer(BufferTarget::Array, BufferName::null());
+                assert!(!mapping.is_null(), "Failed to map vertex buffer");
+
+                self.mapped = true;
+                mapping as *mut f32
+            },
+        };
+
+        MappedBuffer {
+            data: data,
+            buffer: self,
+            _guard: guard,
+        }
+    }
+
+    /// Returns `true` if the buffer is mapped with `map_write()` and can't be used for drawing.
+    ///
+    /// Buffers allocated with persistent mapping can always be used for drawing, so this is
+    /// always `false` for them.
+    pub fn is_mapped(&self) -> bool {
+        self.mapped
+    }
+
+    fn assert_mutable_storage(&self) {
+        assert!(!self.mapped, "Cannot respecify the storage of a mapped vertex buffer");
+        assert!(
+            self.persistent_mapping.is_none(),
+            "Cannot respecify the storage of a vertex buffer allocated with persistent mapping");
+    }
+
     /// Specifies how the data for a particular vertex attribute is laid out in the buffer.
     ///
     /// `layout` specifies the layout of the vertex attributes. `AttribLayout` includes the three
@@ -108,13 +253,40 @@ impl VertexBuffer {
     ) {
         // Calculate the number of elements based on the attribute.
         // TODO: Verify that each attrib has the same element length.
-        self.element_len = (self.len - layout.offset) / layout.elements + layout.stride;
+        let stride = if layout.stride == 0 { layout.elements } else { layout.stride };
+        self.element_len = (self.len - layout.offset + stride - layout.elements) / stride;
         self.attribs.insert(attrib.into(), layout);
     }
+
+    /// Removes all attribute layouts set with `set_attrib_f32()`.
+    ///
+    /// This is useful when replacing the buffer's data with data that has a different layout.
+    pub fn clear_attribs(&mut self) {
+        self.attribs.clear();
+        self.element_len = 0;
+    }
+
+    /// Labels the buffer so that it can be identified in debug messages and graphics debuggers
+    /// such as RenderDoc.
+    ///
+    /// Does nothing if the driver doesn't support `KHR_debug`.
+    pub fn set_debug_label(&self, label: &str) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        unsafe {
+            // The buffer object isn't created until it's first bound.
+            gl::bind_buffer(BufferTarget::Array, self.buffer_name);
+            gl::bind_buffer(BufferTarget::Array, BufferName::null());
+        }
+        ::context::set_object_label(DebugMessageId::GL_BUFFER, self.buffer_name.0, label);
+    }
 }
 
 impl Drop for VertexBuffer {
     fn drop(&mut self) {
+        if !self.context_alive.get() {
+            return;
+        }
+
         unsafe {
             let _guard = ::context::ContextGuard::new(self.context);
             gl::delete_buffers(1, &mut self.buffer_name);
@@ -122,6 +294,50 @@ impl Drop for VertexBuffer {
     }
 }
 
+/// A view of a mapped `VertexBuffer`, created with `VertexBuffer::map_write()`.
+///
+/// The buffer's context is kept current and the buffer is unmapped when the `MappedBuffer` is
+/// dropped. The buffer can't be drawn while it's mapped.
+#[derive(Debug)]
+pub struct MappedBuffer<'a> {
+    data: *mut f32,
+    buffer: &'a mut VertexBuffer,
+    _guard: ::context::ContextGuard,
+}
+
+impl<'a> MappedBuffer<'a> {
+    /// Returns the mapped data as floats.
+    pub fn as_f32_mut(&mut self) -> &mut [f32] {
+        unsafe { slice::from_raw_parts_mut(self.data, self.buffer.len) }
+    }
+
+    /// Returns the mapped data as raw bytes.
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        let byte_count = self.buffer.len * mem::size_of::<f32>();
+        unsafe { slice::from_raw_parts_mut(self.data as *mut u8, byte_count) }
+    }
+}
+
+impl<'a> Drop for MappedBuffer<'a> {
+    fn drop(&mut self) {
+        if !self.buffer.mapped {
+            return;
+        }
+
+        unsafe {
+            gl::bind_buffer(BufferTarget::Array, self.buffer.buffer_name);
+            let result = gl::unmap_buffer(BufferTarget::Array);
+            gl::bind_buffer(BufferTarget::Array, BufferName::null());
+
+            if result == Boolean::False {
+                println!("WARNING: Vertex buffer data was corrupted while it was mapped, the buffer's contents are undefined");
+            }
+        }
+
+        self.buffer.mapped = false;
+    }
+}
+
 /// Describes the layout of vertex data in a `VertexBuffer`.
 ///
 /// See [`VertexBuffer::set_attrib_f32()`][VertexBuffer::set_attrib_f32] for more information.
@@ -139,13 +355,21 @@ pub struct AttribLayout {
 pub struct IndexBuffer {
     buffer_name: BufferName,
     len: usize,
+    index_type: IndexType,
+
+    /// The size in bytes of the buffer's storage.
+    capacity: usize,
 
     pub(crate) context: gl::Context,
+    context_alive: Rc<Cell<bool>>,
+    pub(crate) share_group: ShareGroup,
 }
 
 impl IndexBuffer {
     /// Creates a new index buffer.
     pub fn new(context: &Context) -> IndexBuffer {
+        let context_alive = context.alive();
+        let share_group = context.share_group();
         let context = context.raw();
         let mut buffer_name = BufferName::null();
         unsafe {
@@ -156,33 +380,167 @@ impl IndexBuffer {
         IndexBuffer {
             buffer_name: buffer_name,
             len: 0,
+            index_type: IndexType::UnsignedInt,
+            capacity: 0,
 
             context: context,
+            context_alive: context_alive,
+            share_group: share_group,
         }
     }
 
     /// Fills the index buffer with the provided data.
+    ///
+    /// The buffer's storage is only reallocated if `data` doesn't fit in it, otherwise the data
+    /// is copied into the existing storage.
     pub fn set_data_u32(&mut self, data: &[u32]) {
+        self.set_data(data, IndexType::UnsignedInt);
+    }
+
+    /// Fills the index buffer with the provided 16 bit data.
+    ///
+    /// Uses half the memory of `set_data_u32()` for meshes with few enough vertices. The buffer's
+    /// storage is reused the same way.
+    pub fn set_data_u16(&mut self, data: &[u16]) {
+        self.set_data(data, IndexType::UnsignedShort);
+    }
+
+    /// Gets the type of the indices in the buffer, based on which method last set its data.
+    pub fn index_type(&self) -> IndexType {
+        self.index_type
+    }
+
+    fn set_data<T>(&mut self, data: &[T], index_type: IndexType) {
         self.len = data.len();
+        self.index_type = index_type;
 
         let data_ptr = data.as_ptr() as *const ();
-        let byte_count = data.len() * mem::size_of::<u32>();
+        let byte_count = data.len() * mem::size_of::<T>();
 
         unsafe {
             let _guard = ::context::ContextGuard::new(self.context);
-            gl::bind_buffer(BufferTarget::ElementArray, self.buffer_name);
+
+            // The element array binding is part of the bound vertex array's state, so upload
+            // through the array buffer binding to avoid changing the index buffer of whatever
+            // vertex array is bound.
+            gl::bind_buffer(BufferTarget::Array, self.buffer_name);
+            if byte_count <= self.capacity {
+                gl::buffer_sub_data(BufferTarget::Array, 0, byte_count as isize, data_ptr);
+            } else {
+                gl::buffer_data(
+                    BufferTarget::Array,
+                    byte_count as isize,
+                    data_ptr,
+                    BufferUsage::StaticDraw);
+                self.capacity = byte_count;
+            }
+            gl::bind_buffer(BufferTarget::Array, BufferName::null());
+        }
+    }
+
+    /// Labels the buffer so that it can be identified in debug messages and graphics debuggers
+    /// such as RenderDoc.
+    ///
+    /// Does nothing if the driver doesn't support `KHR_debug`.
+    pub fn set_debug_label(&self, label: &str) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        unsafe {
+            // The buffer object isn't created until it's first bound. Bind it as an array buffer
+            // since the element array binding is part of the bound vertex array's state.
+            gl::bind_buffer(BufferTarget::Array, self.buffer_name);
+            gl::bind_buffer(BufferTarget::Array, BufferName::null());
+        }
+        ::context::set_object_label(DebugMessageId::GL_BUFFER, self.buffer_name.0, label);
+    }
+}
+
+impl Drop for IndexBuffer {
+    fn drop(&mut self) {
+        if !self.context_alive.get() {
+            return;
+        }
+
+        unsafe {
+            let _guard = ::context::ContextGuard::new(self.context);
+            gl::delete_buffers(1, &mut self.buffer_name);
+        }
+    }
+}
+
+/// Represents a buffer of uniform data that can be shared between shader programs.
+///
+/// A uniform buffer provides the data for a uniform block declared in a shader, which allows
+/// uniforms that are the same for many draw calls (e.g. the camera's view and projection
+/// matrices) to be uploaded once rather than once per draw. Use
+/// `Program::uniform_block_binding()` to associate a block with a binding point and
+/// `DrawBuilder::uniform_block()` to bind the buffer to that binding point when drawing.
+///
+/// `UniformBuffer` doesn't know anything about the layout of the block it provides data for, the
+/// data passed to `set_data_f32()` is copied into the buffer verbatim. Blocks should be declared
+/// with `layout(std140)` and the caller is responsible for laying out the data accordingly, e.g.
+/// `vec3` and `vec4` members are aligned to 16 bytes, each element of an array is padded to
+/// 16 bytes, and a `mat4` is four `vec4` columns.
+#[derive(Debug)]
+pub struct UniformBuffer {
+    buffer_name: BufferName,
+    byte_len: usize,
+
+    pub(crate) context: gl::Context,
+    context_alive: Rc<Cell<bool>>,
+    pub(crate) share_group: ShareGroup,
+}
+
+impl UniformBuffer {
+    /// Creates a new uniform buffer.
+    pub fn new(context: &Context) -> UniformBuffer {
+        let context_alive = context.alive();
+        let share_group = context.share_group();
+        let context = context.raw();
+        let mut buffer_name = BufferName::null();
+        unsafe {
+            let _guard = ::context::ContextGuard::new(context);
+            gl::gen_buffers(1, &mut buffer_name);
+        }
+
+        UniformBuffer {
+            buffer_name: buffer_name,
+            byte_len: 0,
+
+            context: context,
+            context_alive: context_alive,
+            share_group: share_group,
+        }
+    }
+
+    /// Fills the uniform buffer with the provided data, replacing its previous contents.
+    ///
+    /// The data must already be laid out according to the std140 rules, see the type-level
+    /// documentation for more information. Uniform buffers are expected to be updated
+    /// frequently (e.g. once per frame), so the buffer is given a dynamic usage hint.
+    pub fn set_data_f32(&mut self, data: &[f32]) {
+        self.byte_len = data.len() * mem::size_of::<f32>();
+
+        let data_ptr = data.as_ptr() as *const ();
+
+        unsafe {
+            let _guard = ::context::ContextGuard::new(self.context);
+            gl::bind_buffer(BufferTarget::Uniform, self.buffer_name);
             gl::buffer_data(
-                BufferTarget::ElementArray,
-                byte_count as isize,
+                BufferTarget::Uniform,
+                self.byte_len as isize,
                 data_ptr,
-                BufferUsage::StaticDraw);
-            gl::bind_buffer(BufferTarget::ElementArray, BufferName::null());
+                BufferUsage::DynamicDraw);
+            gl::bind_buffer(BufferTarget::Uniform, BufferName::null());
         }
     }
 }
 
-impl Drop for IndexBuffer {
+impl Drop for UniformBuffer {
     fn drop(&mut self) {
+        if !self.context_alive.get() {
+            return;
+        }
+
         unsafe {
             let _guard = ::context::ContextGuard::new(self.context);
             gl::delete_buffers(1, &mut self.buffer_name);
@@ -190,17 +548,35 @@ impl Drop for IndexBuffer {
     }
 }
 
+/// Groups a vertex buffer and an optional index buffer into a single mesh that can be drawn.
+///
+/// Vertex arrays are never shared between contexts, so drawing the same buffers with multiple
+/// contexts requires a vertex array for each context. Pass the buffers as an `Rc` to use them
+/// with more than one vertex array.
 #[derive(Debug)]
 pub struct VertexArray {
     vertex_array_name: VertexArrayName,
-    vertex_buffer: VertexBuffer,
-    index_buffer: Option<IndexBuffer>,
+    vertex_buffer: Rc<VertexBuffer>,
+    index_buffer: Option<Rc<IndexBuffer>>,
 
     context: Rc<RefCell<ContextInner>>,
 }
 
 impl VertexArray {
-    pub fn new(context: &Context, vertex_buffer: VertexBuffer) -> VertexArray {
+    /// Creates a new vertex array for the vertex buffer.
+    ///
+    /// # Panics
+    ///
+    /// - If `vertex_buffer` was not created with `context` or a context it shares objects with.
+    pub fn new<V>(context: &Context, vertex_buffer: V) -> VertexArray
+        where V: Into<Rc<VertexBuffer>>
+    {
+        let vertex_buffer = vertex_buffer.into();
+        assert!(
+            context.share_group() == vertex_buffer.share_group,
+            "Specified vertex buffer is not shared with vertex array's context"
+        );
+
         let mut vertex_array_name = VertexArrayName::null();
         let context_inner = context.inner();
         unsafe {
@@ -221,7 +597,26 @@ impl VertexArray {
         }
     }
 
-    pub fn with_index_buffer(context: &Context, vertex_buffer: VertexBuffer, index_buffer: IndexBuffer) -> VertexArray {
+    /// Creates a new vertex array for the vertex buffer and index buffer.
+    ///
+    /// # Panics
+    ///
+    /// - If `vertex_buffer` or `index_buffer` were not created with `context` or a context it
+    ///   shares objects with.
+    pub fn with_index_buffer<V, I>(context: &Context, vertex_buffer: V, index_buffer: I) -> VertexArray
+        where V: Into<Rc<VertexBuffer>>, I: Into<Rc<IndexBuffer>>
+    {
+        let vertex_buffer = vertex_buffer.into();
+        let index_buffer = index_buffer.into();
+        assert!(
+            context.share_group() == vertex_buffer.share_group,
+            "Specified vertex buffer is not shared with vertex array's context"
+        );
+        assert!(
+            context.share_group() == index_buffer.share_group,
+            "Specified index buffer is not shared with vertex array's context"
+        );
+
         let mut vertex_array_name = VertexArrayName::null();
         let context_inner = context.inner();
         unsafe {
@@ -242,11 +637,140 @@ impl VertexArray {
             context: context_inner,
         }
     }
+
+    /// Maps a vertex attribute to an attribute location.
+    ///
+    /// The mapping is stored in the vertex array, so it only needs to be done once for all
+    /// programs that use the same location for the attribute. Use `ProgramBuilder::bind_attrib()`
+    /// to give an attribute the same location in every program.
+    ///
+    /// # Panics
+    ///
+    /// - If the the vertex buffer does not have an attribute named `buffer_attrib_name`.
+    pub fn map_attrib_location(&self, buffer_attrib_name: &str, attrib_location: AttributeLocation) {
+        let layout = match self.vertex_buffer.attribs.get(buffer_attrib_name) {
+            Some(&attrib_data) => attrib_data,
+            None => panic!("Vertex buffer has no attribute \"{}\"", buffer_attrib_name),
+        };
+
+        self.set_attrib_pointer(layout, attrib_location);
+    }
+
+    fn set_attrib_pointer(&self, layout: AttribLayout, attrib_location: AttributeLocation) {
+        unsafe {
+            let mut context = self.context.borrow_mut();
+            let _guard = ::context::ContextGuard::new(context.raw());
+            context.bind_vertex_array(self.vertex_array_name);
+
+            // The attribute reads from whichever buffer is bound when the pointer is set.
+            gl::bind_buffer(BufferTarget::Array, self.vertex_buffer.buffer_name);
+            gl::enable_vertex_attrib_array(attrib_location);
+            gl::vertex_attrib_pointer(
+                attrib_location,
+                layout.elements as i32,
+                GlType::Float,
+                False,
+                (layout.stride * mem::size_of::<f32>()) as i32, // TODO: Correctly handle non-f32
+                layout.offset * mem::size_of::<f32>());         // attrib data types.
+        }
+    }
+
+    /// Maps an attribute of a separate per-instance buffer to an attribute location.
+    ///
+    /// The attribute advances once per instance rather than once per vertex when drawing with
+    /// `DrawBuilder::instances()`, starting at instance `first_instance` in the buffer. This
+    /// allows several groups of instances to share one buffer by remapping the attribute before
+    /// each group is drawn. The vertex array doesn't keep `instance_buffer` alive, so the
+    /// attribute must be remapped if the buffer is replaced.
+    ///
+    /// # Panics
+    ///
+    /// - If `instance_buffer` is not shared with the vertex array's context.
+    /// - If `instance_buffer` does not have an attribute named `buffer_attrib_name`.
+    pub fn map_instance_attrib_location(
+        &self,
+        instance_buffer: &VertexBuffer,
+        buffer_attrib_name: &str,
+        attrib_location: AttributeLocation,
+        first_instance: usize,
+    ) {
+        assert!(
+            self.context.borrow().share_group() == &instance_buffer.share_group,
+            "Specified instance buffer is not shared with vertex array's context"
+        );
+        let layout = match instance_buffer.attribs.get(buffer_attrib_name) {
+            Some(&attrib_data) => attrib_data,
+            None => panic!("Instance buffer has no attribute \"{}\"", buffer_attrib_name),
+        };
+
+        // A stride of 0 means the attribute's data is tightly packed.
+        let stride = match layout.stride {
+            0 => layout.elements,
+            stride => stride,
+        };
+
+        unsafe {
+            let mut context = self.context.borrow_mut();
+            let _guard = ::context::ContextGuard::new(context.raw());
+            context.bind_vertex_array(self.vertex_array_name);
+
+            gl::bind_buffer(BufferTarget::Array, instance_buffer.buffer_name);
+            gl::enable_vertex_attrib_array(attrib_location);
+            gl::vertex_attrib_pointer(
+                attrib_location,
+                layout.elements as i32,
+                GlType::Float,
+                False,
+                (layout.stride * mem::size_of::<f32>()) as i32,
+                (layout.offset + first_instance * stride) * mem::size_of::<f32>());
+            gl::vertex_attrib_divisor(attrib_location, 1);
+        }
+    }
+
+    /// Returns the vertex array's vertex buffer so that its data can be updated, e.g. with
+    /// `VertexBuffer::map_write()`.
+    ///
+    /// Returns `None` if the vertex buffer is shared with another vertex array.
+    pub fn vertex_buffer_mut(&mut self) -> Option<&mut VertexBuffer> {
+        Rc::get_mut(&mut self.vertex_buffer)
+    }
+
+    /// Returns the vertex array's index buffer so that its data can be updated.
+    ///
+    /// Returns `None` if the vertex array doesn't have an index buffer, or if the index buffer is
+    /// shared with another vertex array.
+    pub fn index_buffer_mut(&mut self) -> Option<&mut IndexBuffer> {
+        self.index_buffer.as_mut().and_then(Rc::get_mut)
+    }
+
+    /// Stops reading vertex data for an attribute location.
+    ///
+    /// This undoes `map_attrib_location()`, e.g. when the vertex buffer's data no longer has the
+    /// attribute mapped to `attrib_location`.
+    pub fn unmap_attrib_location(&self, attrib_location: AttributeLocation) {
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+        context.bind_vertex_array(self.vertex_array_name);
+        unsafe { gl::disable_vertex_attrib_array(attrib_location); }
+    }
+
+    /// Labels the vertex array so that it can be identified in debug messages and graphics debuggers
+    /// such as RenderDoc.
+    ///
+    /// Does nothing if the driver doesn't support `KHR_debug`.
+    pub fn set_debug_label(&self, label: &str) {
+        let _guard = ::context::ContextGuard::new(self.context.borrow().raw());
+        ::context::set_object_label(DebugMessageId::GL_VERTEX_ARRAY, self.vertex_array_name.0, label);
+    }
 }
 
 impl Drop for VertexArray {
     fn drop(&mut self) {
         let mut context = self.context.borrow_mut();
+        if !context.is_alive() {
+            return;
+        }
+
         let _guard = ::context::ContextGuard::new(context.raw());
         unsafe { gl::delete_vertex_arrays(1, &mut self.vertex_array_name); }
         context.unbind_vertex_array(self.vertex_array_name);
@@ -262,16 +786,35 @@ pub struct DrawBuilder<'a> {
     program: Option<&'a Program>,
     cull: Option<Face>,
     depth_test: Option<Comparison>,
+    depth_write: bool,
+    instances: Option<usize>,
+    index_range: Option<Range<usize>>,
     winding_order: WindingOrder,
     blend: (SourceFactor, DestFactor),
+    viewport: Option<Viewport>,
+    multisample: Option<bool>,
+    framebuffer: Option<&'a Framebuffer>,
+    validate: bool,
     uniforms: HashMap<UniformLocation, UniformValue<'a>>,
+    uniform_blocks: HashMap<u32, &'a UniformBuffer>,
+
+    /// Uniforms removed with `clear_uniform()`, and the zero value each is reset to when drawing.
+    cleared_uniforms: HashMap<UniformLocation, UniformValue<'a>>,
 
     context: Rc<RefCell<ContextInner>>,
 }
 
 impl<'a> DrawBuilder<'a> {
+    /// Creates a new draw builder for the vertex array.
+    ///
+    /// # Panics
+    ///
+    /// - If `vertex_array` was not created with `context`.
     pub fn new(context: &Context, vertex_array: &'a VertexArray, draw_mode: DrawMode) -> DrawBuilder<'a> {
-        // TODO: Make sure `vertex_array` comes from the right context.
+        assert!(
+            context.raw() == vertex_array.context.borrow().raw(),
+            "Specified vertex array's context does not match draw builder's context"
+        );
 
         DrawBuilder {
             vertex_array: vertex_array,
@@ -280,9 +823,18 @@ impl<'a> DrawBuilder<'a> {
             program: None,
             cull: None,
             depth_test: None,
+            depth_write: true,
+            instances: None,
+            index_range: None,
             winding_order: WindingOrder::default(),
             blend: Default::default(),
+            viewport: None,
+            multisample: None,
+            framebuffer: None,
+            validate: false,
             uniforms: HashMap::new(),
+            uniform_blocks: HashMap::new(),
+            cleared_uniforms: HashMap::new(),
 
             context: context.inner(),
         }
@@ -295,8 +847,8 @@ impl<'a> DrawBuilder<'a> {
 
     pub fn program(&mut self, program: &'a Program) -> &mut DrawBuilder<'a> {
         assert!(
-            self.context.borrow().raw() == program.context,
-            "Specified program's context does not match draw builder's context"
+            self.context.borrow().share_group() == &program.share_group,
+            "Specified program is not shared with draw builder's context"
         );
         self.program = Some(program);
         self
@@ -312,6 +864,48 @@ impl<'a> DrawBuilder<'a> {
         self
     }
 
+    /// Enables or disables writing to the depth buffer. Depth writes are enabled by default.
+    ///
+    /// Disabling depth writes is useful for geometry that should be depth tested against the
+    /// scene without occluding anything drawn after it, such as a skybox.
+    pub fn depth_write(&mut self, enabled: bool) -> &mut DrawBuilder<'a> {
+        self.depth_write = enabled;
+        self
+    }
+
+    /// Draws `count` instances of the vertex array in a single draw call.
+    ///
+    /// Per-instance data is read from attributes mapped with
+    /// `VertexArray::map_instance_attrib_location()`, and the instance being drawn is available
+    /// to shaders as `gl_InstanceID`.
+    pub fn instances(&mut self, count: usize) -> &mut DrawBuilder<'a> {
+        self.instances = Some(count);
+        self
+    }
+
+    /// Draws only the indices in `range` instead of the whole index buffer.
+    ///
+    /// Useful for drawing part of a mesh, e.g. when parts of the mesh use different programs.
+    ///
+    /// # Panics
+    ///
+    /// - If the vertex array doesn't have an index buffer.
+    /// - If `range` isn't within the vertex array's index buffer.
+    pub fn index_range(&mut self, range: Range<usize>) -> &mut DrawBuilder<'a> {
+        let index_count = self.vertex_array.index_buffer
+            .as_ref()
+            .expect("Cannot draw a range of indices without an index buffer")
+            .len;
+        assert!(
+            range.start <= range.end && range.end <= index_count,
+            "Index range {:?} is out of bounds for {} indices",
+            range,
+            index_count);
+
+        self.index_range = Some(range);
+        self
+    }
+
     pub fn winding(&mut self, winding_order: WindingOrder) -> &mut DrawBuilder<'a> {
         self.winding_order = winding_order;
         self
@@ -326,6 +920,84 @@ impl<'a> DrawBuilder<'a> {
         self
     }
 
+    /// Restricts rendering to a rectangular region of the window.
+    ///
+    /// `x` and `y` specify the lower-left corner of the region in pixels. Draws that don't set a
+    /// viewport use the context's default viewport, which covers the full window unless it's
+    /// changed with `Context::set_viewport()`.
+    pub fn viewport(&mut self, x: i32, y: i32, width: i32, height: i32) -> &mut DrawBuilder<'a> {
+        self.viewport = Some(Viewport {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        });
+        self
+    }
+
+    /// Enables or disables multisampling for the draw.
+    ///
+    /// Draws that don't set this use the context's default, which is set with
+    /// `Context::set_multisample_enabled()`. Disabling multisampling is useful for UI and other
+    /// passes that should have hard pixel edges.
+    pub fn multisample(&mut self, enabled: bool) -> &mut DrawBuilder<'a> {
+        self.multisample = Some(enabled);
+        self
+    }
+
+    /// Renders into `framebuffer` instead of the window.
+    ///
+    /// The viewport isn't adjusted to match the framebuffer, so draws into a framebuffer that's a
+    /// different size than the window should also set the viewport with `viewport()`.
+    ///
+    /// # Panics
+    ///
+    /// - If `framebuffer` was not created with the draw builder's context.
+    pub fn framebuffer(&mut self, framebuffer: &'a Framebuffer) -> &mut DrawBuilder<'a> {
+        assert!(
+            framebuffer.context_matches(&self.context),
+            "Specified framebuffer's context does not match draw builder's context"
+        );
+
+        self.framebuffer = Some(framebuffer);
+        self
+    }
+
+    /// Enables or disables validating the program before each draw.
+    ///
+    /// When enabled, `draw()` calls `Program::validate()` once all draw state has been applied
+    /// and panics with the program's info log if validation fails. This is off by default since
+    /// validation is too expensive to do every frame outside of debugging.
+    pub fn validate(&mut self, validate: bool) -> &mut DrawBuilder<'a> {
+        self.validate = validate;
+        self
+    }
+
+    /// Binds a uniform buffer to a uniform buffer binding point for the draw.
+    ///
+    /// Any uniform block in the program that was associated with `binding_point` using
+    /// `Program::uniform_block_binding()` reads its data from `buffer`. Setting a buffer for a
+    /// binding point replaces any buffer previously set for that binding point.
+    ///
+    /// # Panics
+    ///
+    /// - If `buffer` is not shared with the draw builder's context.
+    /// - If `buffer` is empty, since OpenGL doesn't allow binding an empty buffer range.
+    pub fn uniform_block(
+        &mut self,
+        binding_point: u32,
+        buffer: &'a UniformBuffer,
+    ) -> &mut DrawBuilder<'a> {
+        assert!(
+            &buffer.share_group == self.context.borrow().share_group(),
+            "Specified uniform buffer is not shared with draw builder's context"
+        );
+        assert!(buffer.byte_len > 0, "Cannot bind an empty uniform buffer");
+
+        self.uniform_blocks.insert(binding_point, buffer);
+        self
+    }
+
     /// Maps a vertex attribute to an attribute location for the current program.
     ///
     /// # Panics
@@ -336,26 +1008,7 @@ impl<'a> DrawBuilder<'a> {
         buffer_attrib_name: &str,
         attrib_location: AttributeLocation
     ) -> &mut DrawBuilder<'a> {
-        let layout = match self.vertex_array.vertex_buffer.attribs.get(buffer_attrib_name) {
-            Some(&attrib_data) => attrib_data,
-            None => panic!("Vertex buffer has no attribute \"{}\"", buffer_attrib_name),
-        };
-
-        unsafe {
-            let mut context = self.context.borrow_mut();
-            let _guard = ::context::ContextGuard::new(context.raw());
-            context.bind_vertex_array(self.vertex_array.vertex_array_name);
-
-            gl::enable_vertex_attrib_array(attrib_location);
-            gl::vertex_attrib_pointer(
-                attrib_location,
-                layout.elements as i32,
-                GlType::Float,
-                False,
-                (layout.stride * mem::size_of::<f32>()) as i32, // TODO: Correctly handle non-f32
-                layout.offset * mem::size_of::<f32>());         // attrib data types.
-        }
-
+        self.vertex_array.map_attrib_location(buffer_attrib_name, attrib_location);
         self
     }
 
@@ -384,21 +1037,7 @@ impl<'a> DrawBuilder<'a> {
             None => return self,
         };
 
-        unsafe {
-            let mut context = self.context.borrow_mut();
-            let _guard = ::context::ContextGuard::new(context.raw());
-            context.bind_vertex_array(self.vertex_array.vertex_array_name);
-
-            gl::enable_vertex_attrib_array(attrib);
-            gl::vertex_attrib_pointer(
-                attrib,
-                layout.elements as i32,
-                GlType::Float,
-                False,
-                (layout.stride * mem::size_of::<f32>()) as i32,
-                layout.offset * mem::size_of::<f32>());
-        }
-
+        self.vertex_array.set_attrib_pointer(layout, attrib);
         self
     }
 
@@ -408,6 +1047,9 @@ impl<'a> DrawBuilder<'a> {
     /// so it is always safe to speculatively set uniform values even if the shader program may
     /// not use that uniform.
     ///
+    /// Setting the same uniform more than once replaces the previous value, even if the new value
+    /// has a different type. Only the latest value is sent to the program when `draw()` is called.
+    ///
     /// # Panics
     ///
     /// - If the program has not been set using `program()`.
@@ -424,18 +1066,94 @@ impl<'a> DrawBuilder<'a> {
             self.program.expect("Cannot set a uniform without a shader program");
 
         // TODO: This checking is bad? Or maybe not? I don't remember.
-        let uniform_location = match program.get_uniform_location(name) {
+        let uniform_location = match program.uniform_location(name) {
             Some(location) => location,
             None => return self,
         };
 
-        // Add uniform to the uniform map.
+        // Add uniform to the uniform map, replacing any previous value for the location.
+        self.cleared_uniforms.remove(&uniform_location);
         self.uniforms.insert(uniform_location, value);
 
         self
     }
 
+    /// Removes a uniform value previously set with `uniform()`, resetting the uniform to zero.
+    ///
+    /// OpenGL retains uniform values in the program object, so rather than leaving the program
+    /// with whatever value was last sent, subsequent draws set the uniform to zero (or to texture
+    /// unit 0 for samplers) until it's set again with `uniform()`. This means a value set by a
+    /// previous draw, e.g. `light_radius` for a point light, never leaks into a draw that cleared
+    /// it.
+    ///
+    /// Like `uniform()`, `clear_uniform()` silently ignores names that do not exist in the shader
+    /// program. Uniforms that weren't set on this draw builder are reset based on the type
+    /// reported by the program, array elements other than the first are only reset if they were
+    /// set on this draw builder.
+    ///
+    /// Cleared samplers are pointed at texture unit 0, the same as in a freshly linked program,
+    /// rather than at whichever unit they used before. If the draw also binds a texture with
+    /// `uniform()` that texture will be on unit 0, so a cleared sampler will read from it.
+    ///
+    /// # Panics
+    ///
+    /// - If the program has not been set using `program()`.
+    pub fn clear_uniform(&mut self, name: &str) -> &mut DrawBuilder<'a> {
+        let program =
+            self.program.expect("Cannot clear a uniform without a shader program");
+
+        let location = match program.uniform_location(name) {
+            Some(location) => location,
+            None => return self,
+        };
+
+        let zero = match self.uniforms.remove(&location) {
+            Some(previous) => Some(previous.zeroed()),
+            None => program.uniforms()
+                .iter()
+                .find(|uniform| uniform.location == location)
+                .and_then(|uniform| uniform.gl_type)
+                .and_then(UniformValue::zero_for_type),
+        };
+        if let Some(zero) = zero {
+            self.cleared_uniforms.insert(location, zero);
+        }
+
+        self
+    }
+
+    /// Removes all uniform values set with `uniform()`, resetting them to zero.
+    ///
+    /// Samplers are reset to texture unit 0, see `clear_uniform()` for details.
+    pub fn clear_uniforms(&mut self) -> &mut DrawBuilder<'a> {
+        for (location, previous) in self.uniforms.drain() {
+            self.cleared_uniforms.insert(location, previous.zeroed());
+        }
+        self
+    }
+
+    /// Performs the draw with `query` active, tracking whether any of the draw's fragments pass
+    /// the depth test.
+    ///
+    /// # Panics
+    ///
+    /// - If another occlusion query is already active.
+    pub fn draw_with_query(&mut self, query: &mut OcclusionQuery) {
+        query.begin();
+        self.draw();
+        query.end();
+    }
+
+    /// Performs the draw.
+    ///
+    /// # Panics
+    ///
+    /// - If the vertex array's vertex buffer is mapped.
     pub fn draw(&mut self) {
+        assert!(
+            !self.vertex_array.vertex_buffer.is_mapped(),
+            "Cannot draw with a vertex buffer that is mapped");
+
         let mut context = self.context.borrow_mut();
         let _guard = ::context::ContextGuard::new(context.raw());
 
@@ -456,33 +1174,103 @@ impl<'a> DrawBuilder<'a> {
         } else {
             context.enable_server_depth_test(false);
         }
+        context.depth_write(self.depth_write);
 
         let (source_factor, dest_factor) = self.blend;
         context.blend(source_factor, dest_factor);
 
+        context.viewport(self.viewport);
+        context.multisample(self.multisample);
+        context.bind_framebuffer(
+            self.framebuffer
+                .map(Framebuffer::inner)
+                .unwrap_or(FramebufferName::null()));
+
+        // Bind uniform buffers.
+        for (&binding_point, buffer) in &self.uniform_blocks {
+            unsafe {
+                gl::bind_buffer_range(
+                    BufferTarget::Uniform,
+                    binding_point,
+                    buffer.buffer_name,
+                    0,
+                    buffer.byte_len as isize);
+            }
+        }
+
+        // Make sure there are enough texture units for all of the texture uniforms.
+        let texture_count = self.uniforms
+            .values()
+            .filter(|uniform| uniform.is_texture())
+            .count();
+        let max_texture_units = context.limits().max_combined_texture_image_units;
+        assert!(
+            texture_count <= max_texture_units,
+            "Draw uses {} textures but only {} texture units are supported",
+            texture_count,
+            max_texture_units
+        );
+
         let mut active_texture = 0;
         // Apply uniforms.
         for (&location, uniform) in &self.uniforms {
             self.apply(uniform, location, &mut active_texture);
         }
 
+        // Reset cleared uniforms. These are never textures, so they don't use any texture units.
+        for (&location, zero) in &self.cleared_uniforms {
+            self.apply(zero, location, &mut active_texture);
+        }
+
         unsafe {
             // TODO: Do a better job tracking VAO and VBO state? I don't know how that would be
             // accomplished, but I don't honestly undertand VAOs so maybe I should figure that out
             // first.
             context.bind_vertex_array(self.vertex_array.vertex_array_name);
 
-            if let Some(indices) = self.vertex_array.index_buffer.as_ref() {
-                gl::draw_elements(
+            if self.validate {
+                if let Some(program) = self.program {
+                    if let Err(log) = program.validate() {
+                        panic!("Program failed validation before drawing:\n{}", log);
+                    }
+                }
+            }
+
+            // The offset of the indices to draw is given in bytes.
+            let (index_count, index_offset) = match (self.vertex_array.index_buffer.as_ref(), self.index_range.as_ref()) {
+                (Some(indices), Some(range)) => {
+                    let index_size = match indices.index_type {
+                        IndexType::UnsignedByte => 1,
+                        IndexType::UnsignedShort => 2,
+                        IndexType::UnsignedInt => 4,
+                    };
+                    (range.end - range.start, range.start * index_size)
+                },
+                (Some(indices), None) => (indices.len, 0),
+                (None, _) => (0, 0),
+            };
+
+            match (self.vertex_array.index_buffer.as_ref(), self.instances) {
+                (Some(indices), None) => gl::draw_elements(
                     self.draw_mode,
-                    indices.len as i32,
-                    IndexType::UnsignedInt,
-                    0);
-            } else {
-                gl::draw_arrays(
+                    index_count as i32,
+                    indices.index_type,
+                    index_offset),
+                (Some(indices), Some(instances)) => gl::draw_elements_instanced(
+                    self.draw_mode,
+                    index_count as i32,
+                    indices.index_type,
+                    index_offset,
+                    instances as i32),
+                (None, None) => gl::draw_arrays(
+                    self.draw_mode,
+                    0,
+                    self.vertex_array.vertex_buffer.element_len as i32),
+                (None, Some(instances)) => gl::draw_arrays_instanced(
                     self.draw_mode,
                     0,
-                    self.vertex_array.vertex_buffer.element_len as i32);
+                    self.vertex_array.vertex_buffer.element_len as i32,
+                    instances as i32),
             }
         }
     }
@@ -531,6 +1319,33 @@ impl<'a> DrawBuilder<'a> {
                     gl::uniform_i32x1(location, *active_texture);
                 }
 
+                *active_texture += 1;
+            }
+            UniformValue::CubeTexture(texture) => {
+                unsafe {
+                    texture::set_active_texture(*active_texture as u32);
+                    gl::bind_texture(TextureBindTarget::CubeMap, texture.inner());
+                    gl::uniform_i32x1(location, *active_texture);
+                }
+
+                *active_texture += 1;
+            }
+            UniformValue::Texture3d(texture) => {
+                unsafe {
+                    texture::set_active_texture(*active_texture as u32);
+                    gl::bind_texture(TextureBindTarget::Texture3d, texture.inner());
+                    gl::uniform_i32x1(location, *active_texture);
+                }
+
+                *active_texture += 1;
+            }
+            UniformValue::TextureArray(texture) => {
+                unsafe {
+                    texture::set_active_texture(*active_texture as u32);
+                    gl::bind_texture(TextureBindTarget::Texture2dArray, texture.inner());
+                    gl::uniform_i32x1(location, *active_texture);
+                }
+
                 *active_texture += 1;
             }
         }
@@ -549,6 +1364,69 @@ pub enum UniformValue<'a> {
     u32(u32),
     Matrix(GlMatrix<'a>),
     Texture(&'a Texture2d),
+    CubeTexture(&'a TextureCube),
+    Texture3d(&'a Texture3d),
+    TextureArray(&'a Texture2dArray),
+}
+
+/// The data for a zeroed matrix uniform, large enough for either a 3x3 or 4x4 matrix.
+static ZERO_MATRIX: [f32; 16] = [0.0; 16];
+
+impl<'a> UniformValue<'a> {
+    /// Gets the zero value of the same type, used to reset cleared uniforms.
+    ///
+    /// Textures are replaced with texture unit 0.
+    fn zeroed(&self) -> UniformValue<'a> {
+        match *self {
+            UniformValue::f32(_) => UniformValue::f32(0.0),
+            UniformValue::f32x2(_) => UniformValue::f32x2((0.0, 0.0)),
+            UniformValue::f32x3(_) => UniformValue::f32x3((0.0, 0.0, 0.0)),
+            UniformValue::f32x4(_) => UniformValue::f32x4((0.0, 0.0, 0.0, 0.0)),
+            UniformValue::i32(_) => UniformValue::i32(0),
+            UniformValue::u32(_) => UniformValue::u32(0),
+            UniformValue::Matrix(ref matrix) => UniformValue::Matrix(GlMatrix {
+                data: &ZERO_MATRIX[..matrix.data.len()],
+                transpose: matrix.transpose,
+            }),
+            UniformValue::Texture(_)
+            | UniformValue::CubeTexture(_)
+            | UniformValue::Texture3d(_)
+            | UniformValue::TextureArray(_) => UniformValue::i32(0),
+        }
+    }
+
+    /// Gets the zero value for a uniform of type `gl_type`, or `None` if `UniformValue` can't
+    /// represent the type.
+    fn zero_for_type(gl_type: VariableType) -> Option<UniformValue<'a>> {
+        let zero = match gl_type {
+            VariableType::Float => UniformValue::f32(0.0),
+            VariableType::FloatVec2 => UniformValue::f32x2((0.0, 0.0)),
+            VariableType::FloatVec3 => UniformValue::f32x3((0.0, 0.0, 0.0)),
+            VariableType::FloatVec4 => UniformValue::f32x4((0.0, 0.0, 0.0, 0.0)),
+            VariableType::Int | VariableType::Bool => UniformValue::i32(0),
+            VariableType::UnsignedInt => UniformValue::u32(0),
+            VariableType::FloatMat3 => UniformValue::Matrix(GlMatrix { data: &ZERO_MATRIX[..9], transpose: false }),
+            VariableType::FloatMat4 => UniformValue::Matrix(GlMatrix { data: &ZERO_MATRIX, transpose: false }),
+            VariableType::Sampler1d
+            | VariableType::Sampler2d
+            | VariableType::Sampler3d
+            | VariableType::SamplerCube
+            | VariableType::Sampler2dShadow
+            | VariableType::Sampler2dArray => UniformValue::i32(0),
+            _ => return None,
+        };
+        Some(zero)
+    }
+
+    fn is_texture(&self) -> bool {
+        match *self {
+            UniformValue::Texture(_)
+            | UniformValue::CubeTexture(_)
+            | UniformValue::Texture3d(_)
+            | UniformValue::TextureArray(_) => true,
+            _ => false,
+        }
+    }
 }
 
 impl<'a> From<f32> for UniformValue<'a> {
@@ -623,6 +1501,24 @@ impl<'a> From<&'a Texture2d> for UniformValue<'a> {
     }
 }
 
+impl<'a> From<&'a TextureCube> for UniformValue<'a> {
+    fn from(from: &'a TextureCube) -> UniformValue<'a> {
+        UniformValue::CubeTexture(from)
+    }
+}
+
+impl<'a> From<&'a Texture3d> for UniformValue<'a> {
+    fn from(from: &'a Texture3d) -> UniformValue<'a> {
+        UniformValue::Texture3d(from)
+    }
+}
+
+impl<'a> From<&'a Texture2dArray> for UniformValue<'a> {
+    fn from(from: &'a Texture2dArray) -> UniformValue<'a> {
+        UniformValue::TextureArray(from)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GlMatrix<'a> {
     pub data: &'a [f32],
@@ -0,0 +1,255 @@
+//! Asynchronous queries for information about rendering, such as how long the GPU spent on a
+//! set of draws or whether a draw produced any visible fragments.
+//!
+//! Query results are produced by the GPU some time after the query ends, usually a frame or two
+//! later. Reading a result before it's available stalls the CPU until the GPU catches up, so
+//! prefer checking for results with the non-blocking methods and keeping multiple queries in
+//! flight.
+
+use context::{Context, ContextInner};
+use gl;
+use gl::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Measures how much GPU time is spent on the commands issued between `begin()` and `end()`.
+///
+/// Only one timer query can be active at a time, so timer queries can't be nested. Beginning a
+/// timer query while another one is active panics.
+#[derive(Debug)]
+pub struct TimerQuery(Query);
+
+impl TimerQuery {
+    /// Creates a new timer query.
+    pub fn new(context: &Context) -> TimerQuery {
+        TimerQuery(Query::new(context, QueryTarget::TimeElapsed))
+    }
+
+    /// Starts timing GPU commands.
+    ///
+    /// Beginning the query discards any previous result, so read the result of the last
+    /// measurement before reusing the query.
+    ///
+    /// # Panics
+    ///
+    /// - If any timer query is already active, including this one.
+    pub fn begin(&mut self) {
+        self.0.begin();
+    }
+
+    /// Stops timing GPU commands.
+    ///
+    /// # Panics
+    ///
+    /// - If the query is not active.
+    pub fn end(&mut self) {
+        self.0.end();
+    }
+
+    /// Times the GPU commands issued by `func`.
+    ///
+    /// This is equivalent to calling `begin()` before and `end()` after `func`, and returns the
+    /// value returned by `func`.
+    ///
+    /// # Panics
+    ///
+    /// - If any timer query is already active, including one started inside `func`.
+    pub fn scope<F, T>(&mut self, func: F) -> T where F: FnOnce() -> T {
+        self.begin();
+        let result = func();
+        self.end();
+        result
+    }
+
+    /// Returns the elapsed GPU time if the result is available, without blocking.
+    ///
+    /// Returns `None` if the GPU hasn't finished the timed commands yet, or if the query has
+    /// never been ended.
+    pub fn result(&self) -> Option<Duration> {
+        self.0.result().map(nanoseconds_to_duration)
+    }
+
+    /// Returns the elapsed GPU time, blocking until the result is available.
+    ///
+    /// # Panics
+    ///
+    /// - If the query has never been ended, since there is no result to wait for.
+    pub fn wait_result(&self) -> Duration {
+        nanoseconds_to_duration(self.0.wait_result())
+    }
+}
+
+/// Determines whether any fragments pass the depth test for the draws issued between `begin()`
+/// and `end()`.
+///
+/// Only one occlusion query can be active at a time, regardless of whether it counts samples.
+/// Beginning an occlusion query while another one is active panics.
+#[derive(Debug)]
+pub struct OcclusionQuery(Query);
+
+impl OcclusionQuery {
+    /// Creates a new occlusion query that only tracks whether any samples passed.
+    ///
+    /// This is cheaper than counting samples since the GPU can stop tracking as soon as the
+    /// first sample passes.
+    pub fn new(context: &Context) -> OcclusionQuery {
+        OcclusionQuery(Query::new(context, QueryTarget::AnySamplesPassed))
+    }
+
+    /// Creates a new occlusion query that counts the number of samples that passed.
+    ///
+    /// Use `samples_passed()` to read the count.
+    pub fn counting(context: &Context) -> OcclusionQuery {
+        OcclusionQuery(Query::new(context, QueryTarget::SamplesPassed))
+    }
+
+    /// Starts tracking samples.
+    ///
+    /// Beginning the query discards any previous result.
+    ///
+    /// # Panics
+    ///
+    /// - If any occlusion query is already active, including this one.
+    pub fn begin(&mut self) {
+        self.0.begin();
+    }
+
+    /// Stops tracking samples.
+    ///
+    /// # Panics
+    ///
+    /// - If the query is not active.
+    pub fn end(&mut self) {
+        self.0.end();
+    }
+
+    /// Returns whether any samples passed if the result is available, without blocking.
+    ///
+    /// Returns `None` if the GPU hasn't finished the tracked draws yet, or if the query has never
+    /// been ended.
+    pub fn any_samples_passed(&self) -> Option<bool> {
+        self.0.result().map(|samples| samples != 0)
+    }
+
+    /// Returns the number of samples that passed if the result is available, without blocking.
+    ///
+    /// Returns `None` if the GPU hasn't finished the tracked draws yet, or if the query has never
+    /// been ended.
+    ///
+    /// # Panics
+    ///
+    /// - If the query was not created with `counting()`.
+    pub fn samples_passed(&self) -> Option<u64> {
+        assert!(
+            self.0.target == QueryTarget::SamplesPassed,
+            "Cannot read the sample count of an occlusion query that was not created with OcclusionQuery::counting()");
+
+        self.0.result()
+    }
+
+    /// Returns whether any samples passed, blocking until the result is available.
+    ///
+    /// # Panics
+    ///
+    /// - If the query has never been ended, since there is no result to wait for.
+    pub fn wait_any_samples_passed(&self) -> bool {
+        self.0.wait_result() != 0
+    }
+}
+
+/// The state shared by all query types.
+#[derive(Debug)]
+struct Query {
+    query_object: QueryObject,
+    target: QueryTarget,
+    has_result: bool,
+
+    context: Rc<RefCell<ContextInner>>,
+}
+
+impl Query {
+    fn new(context: &Context, target: QueryTarget) -> Query {
+        let mut query_object = QueryObject::null();
+        unsafe {
+            let _guard = ::context::ContextGuard::new(context.raw());
+            gl::gen_queries(1, &mut query_object);
+        }
+
+        Query {
+            query_object: query_object,
+            target: target,
+            has_result: false,
+
+            context: context.inner(),
+        }
+    }
+
+    fn begin(&mut self) {
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+        context.begin_query(self.target, self.query_object);
+        self.has_result = false;
+    }
+
+    fn end(&mut self) {
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+        context.end_query(self.target, self.query_object);
+        self.has_result = true;
+    }
+
+    fn result(&self) -> Option<u64> {
+        if !self.has_result {
+            return None;
+        }
+
+        let _guard = ::context::ContextGuard::new(self.context.borrow().raw());
+        let mut available = 0;
+        unsafe {
+            gl::get_query_object_i32(
+                self.query_object,
+                QueryResultParam::ResultAvailable,
+                &mut available);
+        }
+
+        if available != 0 {
+            Some(self.read_result())
+        } else {
+            None
+        }
+    }
+
+    fn wait_result(&self) -> u64 {
+        assert!(self.has_result, "Cannot wait for the result of a query that has not been ended");
+
+        let _guard = ::context::ContextGuard::new(self.context.borrow().raw());
+        self.read_result()
+    }
+
+    fn read_result(&self) -> u64 {
+        let mut result = 0;
+        unsafe {
+            gl::get_query_object_u64(self.query_object, QueryResultParam::Result, &mut result);
+        }
+
+        result
+    }
+}
+
+impl Drop for Query {
+    fn drop(&mut self) {
+        let mut context = self.context.borrow_mut();
+        if !context.is_alive() {
+            return;
+        }
+
+        let _guard = ::context::ContextGuard::new(context.raw());
+        unsafe { gl::delete_queries(1, &self.query_object); }
+        context.forget_query(self.target, self.query_object);
+    }
+}
+
+fn nanoseconds_to_duration(nanoseconds: u64) -> Duration {
+    Duration::new(nanoseconds / 1_000_000_000, (nanoseconds % 1_000_000_000) as u32)
+}
@@ -1,8 +1,13 @@
-use context::Context;
+use context::{Context, ShareGroup};
 use gl;
 use gl::*;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::mem;
+use std::rc::Rc;
+
+pub use gl::{UniformBlockIndex, UniformLocation, VariableType};
 
 /// Represents a single shader which can be used to create a `Program`.
 #[derive(Debug, Clone)]
@@ -11,10 +16,12 @@ pub struct Shader {
     shader_type: ShaderType,
 
     context: ::gl::Context,
+    context_alive: Rc<Cell<bool>>,
 }
 
 impl Shader {
     pub fn new<T: AsRef<str>>(context: &Context, source: T, shader_type: ShaderType) -> Result<Shader, ShaderError> {
+        let context_alive = context.alive();
         let context = context.raw();
 
         let _context = ::context::ContextGuard::new(context);
@@ -43,10 +50,15 @@ impl Shader {
                 shader_type: shader_type,
 
                 context: context,
+                context_alive: context_alive,
             }),
             ShaderCompileStatus::Failure => {
                 let log = shader_log(shader_object);
-                Err(ShaderError::CompileError(log))
+                unsafe { gl::delete_shader(shader_object); }
+                Err(ShaderError::CompileError {
+                    shader_type: shader_type,
+                    log: log,
+                })
             }
         }
     }
@@ -54,6 +66,10 @@ impl Shader {
 
 impl Drop for Shader {
     fn drop(&mut self) {
+        if !self.context_alive.get() {
+            return;
+        }
+
         let _context = ::context::ContextGuard::new(self.context);
         unsafe { gl::delete_shader(self.shader_object); }
     }
@@ -68,8 +84,12 @@ pub enum ShaderError {
 
     /// Indicates that an error occurred while compiling the the shader.
     ///
-    /// The inner string is the error log retrieved from OpenGL.
-    CompileError(String)
+    /// `log` is the shader info log retrieved from OpenGL, which will contain the line numbers
+    /// of the errors in the shader source.
+    CompileError {
+        shader_type: ShaderType,
+        log: String,
+    },
 }
 
 fn compile_status(shader_object: ShaderObject) -> ShaderCompileStatus {
@@ -129,22 +149,59 @@ enum ShaderCompileStatus {
 #[derive(Debug)]
 pub struct Program {
     program_object: ProgramObject,
+    uniforms: Vec<UniformInfo>,
+    attributes: Vec<AttribInfo>,
+
+    /// Cache of uniform locations by name, including names that don't exist in the program.
+    uniform_locations: RefCell<HashMap<String, Option<UniformLocation>>>,
 
     pub(crate) context: ::gl::Context,
+    context_alive: Rc<Cell<bool>>,
+    pub(crate) share_group: ShareGroup,
 }
 
 impl Program {
+    /// Creates a program from vertex and fragment shader source.
+    ///
+    /// This is a convenience for the common case of a program with just a vertex and fragment
+    /// shader. The intermediate shader objects are cleaned up once the program has been linked.
+    /// If either shader fails to compile the returned `ProgramError::ShaderError` identifies
+    /// which one failed. Use `Program::new()` for programs with other shader stages.
+    pub fn from_sources(
+        context: &Context,
+        vert_source: &str,
+        frag_source: &str,
+    ) -> Result<Program, ProgramError> {
+        ProgramBuilder::new(context).build_from_sources(vert_source, frag_source)
+    }
+
     /// Creates a program with the provided shaders.
     pub fn new(context: &Context, shaders: &[Shader]) -> Result<Program, ProgramError> {
+        ProgramBuilder::new(context).build(shaders)
+    }
+
+    fn link(
+        context: &Context,
+        shaders: &[Shader],
+        attrib_bindings: &[(String, AttributeLocation)],
+        frag_bindings: &[(String, u32)],
+    ) -> Result<Program, ProgramError> {
+        let context_alive = context.alive();
+        let share_group = context.share_group();
         let context = context.raw();
 
         let _guard = ::context::ContextGuard::new(context);
 
         // Create shader program.
-        let program = Program {
+        let mut program = Program {
             program_object: unsafe { gl::create_program() },
+            uniforms: Vec::new(),
+            attributes: Vec::new(),
+            uniform_locations: RefCell::new(HashMap::new()),
 
             context: context,
+            context_alive: context_alive,
+            share_group: share_group,
         };
         if program.inner().is_null() {
             return Err(ProgramError::CreateProgramError);
@@ -155,6 +212,22 @@ impl Program {
             unsafe { gl::attach_shader(program.inner(), shader.shader_object); }
         }
 
+        // Bind attribute locations, which must be done before linking.
+        for &(ref name, location) in attrib_bindings {
+            let mut null_terminated = name.clone();
+            null_terminated.push('\0');
+
+            unsafe { gl::bind_attrib_location(program.inner(), location, null_terminated.as_ptr()); }
+        }
+
+        // Bind fragment outputs, which also must be done before linking.
+        for &(ref name, color_number) in frag_bindings {
+            let mut null_terminated = name.clone();
+            null_terminated.push('\0');
+
+            unsafe { gl::bind_frag_data_location(program.inner(), color_number, null_terminated.as_ptr()); }
+        }
+
         // Link the program and detach the shaders.
         unsafe { gl::link_program(program.inner()); }
 
@@ -166,7 +239,19 @@ impl Program {
         // Check for errors.
         let link_status = link_status(program.inner());
         match link_status {
-            ProgramLinkStatus::Success => Ok(program),
+            ProgramLinkStatus::Success => {
+                program.uniforms = active_uniforms(program.inner());
+                program.attributes = active_attributes(program.inner());
+
+                // Seed the location cache with the active uniforms so that the common case never
+                // has to query OpenGL.
+                *program.uniform_locations.borrow_mut() = program.uniforms
+                    .iter()
+                    .map(|uniform| (uniform.name.clone(), Some(uniform.location)))
+                    .collect();
+
+                Ok(program)
+            },
             ProgramLinkStatus::Failure => {
                 let log = program_log(program.inner());
                 Err(ProgramError::LinkError(log))
@@ -175,7 +260,14 @@ impl Program {
     }
 
     /// Gets a vertex attribute location from the program.
+    ///
+    /// If the attribute's location was bound with `ProgramBuilder::bind_attrib()` the bound
+    /// location is returned.
     pub fn get_attrib(&self, name: &str) -> Option<AttributeLocation> {
+        if let Some(attrib) = self.attributes.iter().find(|attrib| attrib.name == name) {
+            return Some(attrib.location);
+        }
+
         let _guard = ::context::ContextGuard::new(self.context);
 
         let mut null_terminated = String::from(name);
@@ -191,7 +283,94 @@ impl Program {
         }
     }
 
-    pub(crate) fn get_uniform_location(&self, name: &str) -> Option<UniformLocation> {
+    /// Gets the index of a named uniform block in the program.
+    ///
+    /// Returns `None` if the program has no active uniform block named `name`. The index can be
+    /// passed to `uniform_block_binding()` to associate the block with a buffer binding point.
+    pub fn uniform_block_index(&self, name: &str) -> Option<UniformBlockIndex> {
+        let _guard = ::context::ContextGuard::new(self.context);
+
+        let mut null_terminated = String::from(name);
+        null_terminated.push('\0');
+
+        let index = unsafe {
+            gl::get_uniform_block_index(self.inner(), null_terminated.as_ptr())
+        };
+
+        if index == UniformBlockIndex::invalid() {
+            None
+        } else {
+            Some(index)
+        }
+    }
+
+    /// Associates a uniform block in the program with a uniform buffer binding point.
+    ///
+    /// When drawing with the program the block reads its data from the `UniformBuffer` bound to
+    /// `binding_point` with `DrawBuilder::uniform_block()`. The binding is stored in the program,
+    /// so it only needs to be set once after the program is created.
+    pub fn uniform_block_binding(&self, index: UniformBlockIndex, binding_point: u32) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        unsafe { gl::uniform_block_binding(self.inner(), index, binding_point); }
+    }
+
+    /// Checks whether the program can execute given the current OpenGL state.
+    ///
+    /// Validation catches problems that otherwise fail silently when drawing, such as samplers
+    /// of different types sharing a texture unit. On failure the program's info log is returned.
+    /// Validation is relatively expensive and is meant for debugging, see
+    /// `DrawBuilder::validate()` for validating automatically before each draw.
+    pub fn validate(&self) -> Result<(), String> {
+        let _guard = ::context::ContextGuard::new(self.context);
+
+        let mut result = 0;
+        unsafe {
+            gl::validate_program(self.inner());
+            gl::get_program_param(self.inner(), ProgramParam::ValidateStatus, &mut result);
+        }
+
+        if result == 0 {
+            Err(program_log(self.inner()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the active uniform variables declared by the program.
+    ///
+    /// Uniforms that are declared in the shader source but not used are optimized out by the
+    /// driver and so are not included.
+    pub fn uniforms(&self) -> &[UniformInfo] {
+        &*self.uniforms
+    }
+
+    /// Returns the active vertex attributes declared by the program.
+    pub fn attributes(&self) -> &[AttribInfo] {
+        &*self.attributes
+    }
+
+    /// Gets the location of a uniform in the program, or `None` if the program has no active
+    /// uniform with that name.
+    ///
+    /// Results are cached, including for names that aren't in the program, so only the first
+    /// lookup for a given name queries OpenGL.
+    pub fn uniform_location(&self, name: &str) -> Option<UniformLocation> {
+        if let Some(&location) = self.uniform_locations.borrow().get(name) {
+            return location;
+        }
+
+        let location = self.query_uniform_location(name);
+        self.uniform_locations.borrow_mut().insert(String::from(name), location);
+        location
+    }
+
+    /// Queries OpenGL for the location of a uniform in the program, bypassing the cache used by
+    /// `uniform_location()`.
+    ///
+    /// Every call goes to the driver, so prefer `uniform_location()`. Only public so that the
+    /// benchmarks can measure what the cache saves.
+    #[doc(hidden)]
+    pub fn query_uniform_location(&self, name: &str) -> Option<UniformLocation> {
         let _guard = ::context::ContextGuard::new(self.context);
 
         let mut null_terminated = String::from(name);
@@ -212,15 +391,126 @@ impl Program {
     pub(crate) fn inner(&self) -> ProgramObject {
         self.program_object
     }
+
+    /// Labels the program so that it can be identified in debug messages and graphics debuggers
+    /// such as RenderDoc.
+    ///
+    /// Does nothing if the driver doesn't support `KHR_debug`.
+    pub fn set_debug_label(&self, label: &str) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        ::context::set_object_label(DebugMessageId::GL_PROGRAM, self.program_object.0, label);
+    }
 }
 
 impl Drop for Program {
     fn drop(&mut self) {
+        if !self.context_alive.get() {
+            return;
+        }
+
         let _guard = ::context::ContextGuard::new(self.context);
         unsafe { gl::delete_program(self.inner()); }
     }
 }
 
+/// A builder for configuring a `Program` before it's linked.
+///
+/// `Program::new()` and `Program::from_sources()` are shorthands for building a program with no
+/// additional configuration.
+#[derive(Debug)]
+pub struct ProgramBuilder<'a> {
+    context: &'a Context,
+    attrib_bindings: Vec<(String, AttributeLocation)>,
+    frag_bindings: Vec<(String, u32)>,
+}
+
+impl<'a> ProgramBuilder<'a> {
+    /// Creates a new builder for a program in the specified context.
+    pub fn new(context: &'a Context) -> ProgramBuilder<'a> {
+        ProgramBuilder {
+            context: context,
+            attrib_bindings: Vec::new(),
+            frag_bindings: Vec::new(),
+        }
+    }
+
+    /// Binds a vertex attribute to a fixed location.
+    ///
+    /// Binding the same attributes to the same locations in multiple programs allows a single
+    /// `VertexArray` configuration to be shared between all of them, see
+    /// `VertexArray::map_attrib_location()`. Bindings for attributes the program doesn't use are
+    /// ignored.
+    pub fn bind_attrib<T: Into<String>>(
+        &mut self,
+        name: T,
+        location: AttributeLocation,
+    ) -> &mut ProgramBuilder<'a> {
+        self.attrib_bindings.push((name.into(), location));
+        self
+    }
+
+    /// Binds a fragment shader output to a fixed color number.
+    ///
+    /// When drawing into a `Framebuffer` the output bound to color number `i` is written to the
+    /// attachment at index `i` of the framebuffer's draw buffers, see
+    /// `Framebuffer::set_draw_buffers()`. Outputs that aren't bound are assigned color numbers
+    /// by the driver, which is only reliable for shaders with a single output.
+    pub fn bind_frag_output<T: Into<String>>(
+        &mut self,
+        name: T,
+        color_number: u32,
+    ) -> &mut ProgramBuilder<'a> {
+        self.frag_bindings.push((name.into(), color_number));
+        self
+    }
+
+    /// Links the provided shaders into a program.
+    pub fn build(&self, shaders: &[Shader]) -> Result<Program, ProgramError> {
+        Program::link(self.context, shaders, &*self.attrib_bindings, &*self.frag_bindings)
+    }
+
+    /// Compiles vertex and fragment shader source and links them into a program.
+    ///
+    /// See `Program::from_sources()` for more information.
+    pub fn build_from_sources(
+        &self,
+        vert_source: &str,
+        frag_source: &str,
+    ) -> Result<Program, ProgramError> {
+        let vert_shader = Shader::new(self.context, vert_source, ShaderType::Vertex)?;
+        let frag_shader = Shader::new(self.context, frag_source, ShaderType::Fragment)?;
+        self.build(&[vert_shader, frag_shader])
+    }
+}
+
+/// Describes an active uniform variable in a `Program`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformInfo {
+    /// The name of the uniform. For arrays this is the name of the array without the "[0]"
+    /// suffix reported by OpenGL.
+    pub name: String,
+    pub location: UniformLocation,
+
+    /// The type of the uniform, or `None` if the type isn't one supported by `VariableType`.
+    pub gl_type: Option<VariableType>,
+
+    /// The number of elements in the uniform, which is 1 for uniforms that aren't arrays.
+    pub array_len: usize,
+}
+
+/// Describes an active vertex attribute in a `Program`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttribInfo {
+    pub name: String,
+    pub location: AttributeLocation,
+
+    /// The type of the attribute, or `None` if the type isn't one supported by `VariableType`.
+    pub gl_type: Option<VariableType>,
+
+    /// The number of elements in the attribute, which is 1 for attributes that aren't arrays.
+    pub array_len: usize,
+}
+
 #[repr(i32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(dead_code)]
@@ -242,6 +532,17 @@ pub enum ProgramError {
     /// variables that are declared differently between different shaders in the program. The
     /// wrapped error message will contain information about the source of the error.
     LinkError(String),
+
+    /// Indicates that one of the program's shaders failed to compile.
+    ///
+    /// Only returned by `Program::from_sources()`, which compiles the shaders itself.
+    ShaderError(ShaderError),
+}
+
+impl From<ShaderError> for ProgramError {
+    fn from(from: ShaderError) -> ProgramError {
+        ProgramError::ShaderError(from)
+    }
 }
 
 fn link_status(program_object: ProgramObject) -> ProgramLinkStatus {
@@ -252,6 +553,115 @@ fn link_status(program_object: ProgramObject) -> ProgramLinkStatus {
     }
 }
 
+fn active_uniforms(program_object: ProgramObject) -> Vec<UniformInfo> {
+    active_variables(
+        program_object,
+        ProgramParam::ActiveUniforms,
+        ProgramParam::ActiveUniformMaxLength,
+        gl::get_active_uniform,
+    )
+    .into_iter()
+    .filter_map(|(name, size, data_type)| {
+        let mut null_terminated = name.clone();
+        null_terminated.push('\0');
+
+        let raw_location = unsafe {
+            gl::get_uniform_location(program_object, null_terminated.as_ptr())
+        };
+
+        // Uniforms in named uniform blocks don't have a location.
+        if raw_location == -1 {
+            return None;
+        }
+
+        Some(UniformInfo {
+            name: name,
+            location: UniformLocation::from_index(raw_location as u32),
+            gl_type: VariableType::from_raw(data_type),
+            array_len: size as usize,
+        })
+    })
+    .collect()
+}
+
+fn active_attributes(program_object: ProgramObject) -> Vec<AttribInfo> {
+    active_variables(
+        program_object,
+        ProgramParam::ActiveAttributes,
+        ProgramParam::ActiveAttributeMaxLength,
+        gl::get_active_attrib,
+    )
+    .into_iter()
+    .filter_map(|(name, size, data_type)| {
+        let mut null_terminated = name.clone();
+        null_terminated.push('\0');
+
+        let raw_location = unsafe {
+            gl::get_attrib_location(program_object, null_terminated.as_ptr())
+        };
+
+        // Built-in attributes like `gl_VertexID` are reported as active but don't have a
+        // location.
+        if raw_location == -1 {
+            return None;
+        }
+
+        Some(AttribInfo {
+            name: name,
+            location: AttributeLocation::from_index(raw_location as u32),
+            gl_type: VariableType::from_raw(data_type),
+            array_len: size as usize,
+        })
+    })
+    .collect()
+}
+
+/// Queries the name, size, and raw type of each of the program's active uniforms or attributes.
+fn active_variables(
+    program_object: ProgramObject,
+    count_param: ProgramParam,
+    max_length_param: ProgramParam,
+    get_active: unsafe fn(ProgramObject, u32, i32, *mut i32, *mut i32, *mut u32, *mut u8),
+) -> Vec<(String, i32, u32)> {
+    let mut count = 0;
+    let mut max_length = 0;
+    unsafe {
+        gl::get_program_param(program_object, count_param, &mut count);
+        gl::get_program_param(program_object, max_length_param, &mut max_length);
+    }
+
+    let mut name_buffer = vec![0u8; max_length as usize];
+    let mut variables = Vec::with_capacity(count as usize);
+    for index in 0..count as u32 {
+        let mut length = 0;
+        let mut size = 0;
+        let mut data_type = 0;
+        unsafe {
+            get_active(
+                program_object,
+                index,
+                max_length,
+                &mut length,
+                &mut size,
+                &mut data_type,
+                name_buffer.as_mut_ptr());
+        }
+
+        let mut name = String::from_utf8_lossy(&name_buffer[..length as usize]).into_owned();
+
+        // Array names are reported with the subscript for the first element, but the array is
+        // referred to by its bare name everywhere else.
+        if name.ends_with("[0]") {
+            let len = name.len() - 3;
+            name.truncate(len);
+        }
+
+        variables.push((name, size, data_type));
+    }
+
+    variables
+}
+
 fn program_log(program_object: ProgramObject) -> String {
     // Get the length of the info log.
     let mut info_log_length = 0;
@@ -0,0 +1,92 @@
+//! Fences for tracking when the GPU has finished with previously issued commands.
+//!
+//! Fences are mainly useful for streaming data through a persistently mapped buffer (see
+//! `VertexBuffer::allocate_f32()`). Writes to a persistent mapping are visible to the GPU
+//! immediately, so overwriting data that an earlier draw is still reading corrupts that draw.
+//! The usual pattern is to treat the buffer as a ring of three regions:
+//!
+//! 1. Allocate the buffer with room for three frames of data and set up an attribute layout for
+//!    each region, using `AttribLayout::offset` to point at the start of the region.
+//! 2. Each frame, pick the next region. If a fence was inserted the last time the region was
+//!    used, `client_wait()` on it (this rarely blocks, since the GPU is usually no more than a
+//!    frame or two behind).
+//! 3. Write the frame's data into the region through `VertexBuffer::map_write()` and draw using
+//!    the region's attributes.
+//! 4. Insert a new fence after the frame's draws and store it with the region.
+//!
+//! Buffers that aren't persistently mapped don't need fences, since mapping them with
+//! `map_write()` lets the driver synchronize with the GPU.
+
+use context::{Context, ContextInner};
+use gl;
+use gl::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// A marker in the GPU command stream that's signaled once all commands issued before it have
+/// completed.
+///
+/// The fence is checked and deleted using the context that created it. A fence that outlives its
+/// context isn't deleted, since destroying the context deletes it.
+#[derive(Debug)]
+pub struct Fence {
+    sync_object: SyncObject,
+
+    context: Rc<RefCell<ContextInner>>,
+}
+
+impl Fence {
+    /// Inserts a fence after all commands issued with `context` so far.
+    pub fn insert(context: &Context) -> Fence {
+        let sync_object = unsafe {
+            let _guard = ::context::ContextGuard::new(context.raw());
+            gl::fence_sync(SyncCondition::GpuCommandsComplete, 0)
+        };
+
+        Fence {
+            sync_object: sync_object,
+
+            context: context.inner(),
+        }
+    }
+
+    /// Returns `true` if the GPU has completed all commands issued before the fence, without
+    /// blocking.
+    pub fn is_signaled(&self) -> bool {
+        self.wait(SyncFlags::None, 0)
+    }
+
+    /// Blocks until the GPU has completed all commands issued before the fence, or until
+    /// `timeout` has passed.
+    ///
+    /// Returns `true` if the fence was signaled, or `false` if the timeout expired first. Pending
+    /// commands are flushed before waiting, so the fence is always signaled eventually.
+    pub fn client_wait(&self, timeout: Duration) -> bool {
+        let nanoseconds = timeout.as_secs()
+            .saturating_mul(1_000_000_000)
+            .saturating_add(timeout.subsec_nanos() as u64);
+        self.wait(SyncFlags::FlushCommands, nanoseconds)
+    }
+
+    fn wait(&self, flags: SyncFlags, timeout: u64) -> bool {
+        let _guard = ::context::ContextGuard::new(self.context.borrow().raw());
+        match unsafe { gl::client_wait_sync(self.sync_object, flags, timeout) } {
+            SyncWaitResult::AlreadySignaled | SyncWaitResult::ConditionSatisfied => true,
+            SyncWaitResult::TimeoutExpired => false,
+            SyncWaitResult::WaitFailed => panic!("Failed to wait on fence {:?}", self.sync_object),
+        }
+    }
+}
+
+impl Drop for Fence {
+    fn drop(&mut self) {
+        let context = self.context.borrow();
+        if !context.is_alive() {
+            return;
+        }
+
+        let _guard = ::context::ContextGuard::new(context.raw());
+        unsafe { gl::delete_sync(self.sync_object); }
+    }
+}
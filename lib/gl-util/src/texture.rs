@@ -1,23 +1,39 @@
-use context::Context;
+use context::{Context, ShareGroup};
 use gl;
+use gl::{Comparison, DebugMessageId, PixelStoreParam};
+use std::cell::Cell;
+use std::ptr;
+use std::rc::Rc;
 
 pub use gl::{
     TextureObject, TextureFilterFunction, TextureFormat, TextureBindTarget, Texture2dTarget,
-    TextureInternalFormat, TextureDataType, TextureParameterName, TextureParameterTarget};
+    Texture3dTarget,
+    TextureInternalFormat, TextureDataType, TextureParameterName, TextureParameterTarget,
+    TextureWrapFunction, TextureCompareMode};
 
 #[derive(Debug)]
 pub struct Texture2d {
     texture_object: TextureObject,
+    data_format: TextureFormat,
+    data_type: TextureDataType,
+    width: usize,
+    height: usize,
+    has_mipmaps: bool,
 
-    context: ::gl::Context,
+    pub(crate) context: ::gl::Context,
+    context_alive: Rc<Cell<bool>>,
+    pub(crate) share_group: ShareGroup,
 }
 
 impl Texture2d {
     /// Constructs a new `Texture2d` from the specified data.
     ///
+    /// The texture is created with linear filtering and repeat wrapping. Use `set_filtering()`
+    /// and `set_wrap()` to change them.
+    ///
     /// # Panics
     ///
-    /// - If `width * height != data.len()`.
+    /// - If `data` doesn't contain exactly `width * height` pixels in `data_format`.
     pub fn new<T: TextureData>(
         context: &Context,
         data_format: TextureFormat,
@@ -26,17 +42,101 @@ impl Texture2d {
         height: usize,
         data: &[T],
     ) -> Result<Texture2d, Error> {
-        let context = context.raw();
-        let _guard = ::context::ContextGuard::new(context);
+        check_data("texture", data_format, None, (width, height, 1), data);
 
-        let expected_pixels = width * height * data_format.elements() / T::ELEMENTS;
-        assert!(
-            expected_pixels == data.len(),
-            "Wrong number of pixels in texture, width: {}, height: {}, expected pixels: {}, actual pixels: {}",
+        let mut texture = Texture2d::allocate(
+            context,
+            data_format,
+            internal_format,
+            width,
+            height,
+            T::DATA_TYPE,
+            data.as_ptr() as *const ())?;
+
+        // Set the filtering explicitly, the default minification filter samples mipmaps which
+        // the texture doesn't have, so sampling it would give black.
+        texture.set_filtering(TextureFilterFunction::Linear, TextureFilterFunction::Linear);
+        texture.set_wrap(TextureWrapFunction::Repeat, TextureWrapFunction::Repeat);
+
+        Ok(texture)
+    }
+
+    /// Constructs a new `Texture2d` with a depth format and no initial data.
+    ///
+    /// Depth textures are meant to be rendered into, e.g. as the target of a shadow map pass,
+    /// and can then be sampled as a `sampler2D` to read the raw depth values or, after calling
+    /// `set_compare()`, as a `sampler2DShadow`. The texture is created with linear filtering and
+    /// clamps texture coordinates to the edge.
+    pub fn depth(
+        context: &Context,
+        width: usize,
+        height: usize,
+        format: DepthFormat,
+    ) -> Result<Texture2d, Error> {
+        let internal_format = match format {
+            DepthFormat::Depth16 => TextureInternalFormat::DepthComponent16,
+            DepthFormat::Depth24 => TextureInternalFormat::DepthComponent24,
+            DepthFormat::Depth32F => TextureInternalFormat::DepthComponent32F,
+        };
+
+        let mut texture = Texture2d::allocate(
+            context,
+            TextureFormat::DepthComponent,
+            internal_format,
+            width,
+            height,
+            TextureDataType::f32,
+            ptr::null())?;
+
+        texture.set_filtering(TextureFilterFunction::Linear, TextureFilterFunction::Linear);
+        texture.set_wrap(TextureWrapFunction::ClampToEdge, TextureWrapFunction::ClampToEdge);
+
+        Ok(texture)
+    }
+
+    /// Constructs a new `Texture2d` with no initial data, meant to be used as a color attachment
+    /// of a `Framebuffer`.
+    ///
+    /// The texture is created with linear filtering and clamp-to-edge wrapping so that sampling
+    /// it after rendering doesn't bleed across the edges of the image.
+    pub fn render_target(
+        context: &Context,
+        internal_format: TextureInternalFormat,
+        width: usize,
+        height: usize,
+    ) -> Result<Texture2d, Error> {
+        let mut texture = Texture2d::allocate(
+            context,
+            TextureFormat::Rgba,
+            internal_format,
             width,
             height,
-            expected_pixels,
-            data.len());
+            TextureDataType::u8,
+            ptr::null())?;
+
+        texture.set_filtering(TextureFilterFunction::Linear, TextureFilterFunction::Linear);
+        texture.set_wrap(TextureWrapFunction::ClampToEdge, TextureWrapFunction::ClampToEdge);
+
+        Ok(texture)
+    }
+
+    /// Creates the texture object and its storage, uploading `data` if it's not null.
+    fn allocate(
+        context: &Context,
+        data_format: TextureFormat,
+        internal_format: TextureInternalFormat,
+        width: usize,
+        height: usize,
+        data_type: TextureDataType,
+        data: *const (),
+    ) -> Result<Texture2d, Error> {
+        let max_size = context.limits().max_texture_size;
+        check_size((width, height, 1), width.max(height), max_size)?;
+
+        let context_alive = context.alive();
+        let share_group = context.share_group();
+        let context = context.raw();
+        let _guard = ::context::ContextGuard::new(context);
 
         let mut texture_object = TextureObject::null();
         unsafe { gl::gen_textures(1, &mut texture_object); }
@@ -56,32 +156,194 @@ impl Texture2d {
                 height as i32,
                 0,
                 data_format,
-                T::DATA_TYPE,
-                data.as_ptr() as *const ());
+                data_type,
+                data);
 
-            gl::texture_parameter_i32(
-                TextureParameterTarget::Texture2d,
-                TextureParameterName::MinFilter,
-                TextureFilterFunction::Nearest.into());
-            gl::texture_parameter_i32(
-                TextureParameterTarget::Texture2d,
-                TextureParameterName::MagFilter,
-                TextureFilterFunction::Nearest.into());
             gl::bind_texture(TextureBindTarget::Texture2d, TextureObject::null());
         }
 
         Ok(Texture2d {
             texture_object: texture_object,
+            data_format: data_format,
+            data_type: data_type,
+            width: width,
+            height: height,
+            has_mipmaps: false,
 
             context: context,
+            context_alive: context_alive,
+            share_group: share_group,
         })
     }
 
+    /// Sets the filter functions used when sampling the texture.
+    ///
+    /// `min` is used when the texture is minified and `mag` when it is magnified. If `min`
+    /// samples mipmaps then the mipmap chain is generated from the texture's current data.
+    ///
+    /// # Panics
+    ///
+    /// - If `mag` is a mipmap filter, mipmaps are never used for magnification.
+    pub fn set_filtering(&mut self, min: TextureFilterFunction, mag: TextureFilterFunction) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        set_filter_params(self.texture_object, TextureBindTarget::Texture2d, min, mag);
+        self.has_mipmaps = min.uses_mipmaps();
+    }
+
+    /// Sets how texture coordinates outside of the [0, 1] range are handled.
+    ///
+    /// `s` is the wrap mode for the horizontal texture coordinate and `t` for the vertical.
+    pub fn set_wrap(&mut self, s: TextureWrapFunction, t: TextureWrapFunction) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        set_wrap_params(
+            self.texture_object,
+            TextureBindTarget::Texture2d,
+            &[(TextureParameterName::WrapS, s), (TextureParameterName::WrapT, t)]);
+    }
+
+    /// Enables depth comparison when sampling the texture.
+    ///
+    /// With comparison enabled, sampling the texture through a `sampler2DShadow` compares the
+    /// reference value in the texture coordinate's third component to the stored depth using
+    /// `comparison`, returning 1.0 if the comparison passes and 0.0 otherwise. With linear
+    /// filtering the results of neighboring texels are blended, giving basic percentage-closer
+    /// filtering for free.
+    ///
+    /// Comparison only has an effect on depth textures, see `Texture2d::depth()`.
+    pub fn set_compare(&mut self, comparison: Comparison) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        unsafe {
+            gl::bind_texture(TextureBindTarget::Texture2d, self.texture_object);
+            gl::texture_parameter_i32(
+                TextureParameterTarget::Texture2d,
+                TextureParameterName::CompareMode,
+                TextureCompareMode::CompareRefToTexture.into());
+            gl::texture_parameter_i32(
+                TextureParameterTarget::Texture2d,
+                TextureParameterName::CompareFunc,
+                comparison as i32);
+            gl::bind_texture(TextureBindTarget::Texture2d, TextureObject::null());
+        }
+    }
+
+    /// Disables depth comparison, so that sampling the texture returns the stored depth values.
+    pub fn clear_compare(&mut self) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        unsafe {
+            gl::bind_texture(TextureBindTarget::Texture2d, self.texture_object);
+            gl::texture_parameter_i32(
+                TextureParameterTarget::Texture2d,
+                TextureParameterName::CompareMode,
+                TextureCompareMode::None.into());
+            gl::bind_texture(TextureBindTarget::Texture2d, TextureObject::null());
+        }
+    }
+
+    /// Replaces a rectangular region of the texture's data.
+    ///
+    /// `x` and `y` are the offset of the region from the start of the texture and `width` and
+    /// `height` are its dimensions. `data` is laid out the same as the data passed to `new()` and
+    /// must be in the same format the texture was created with. If the texture is using mipmaps
+    /// they are regenerated after the data is updated.
+    ///
+    /// # Panics
+    ///
+    /// - If the region doesn't fit inside the texture.
+    /// - If `data` doesn't contain exactly `width * height` pixels in the texture's format.
+    /// - If `T` is a different type of data than the texture was created with.
+    pub fn sub_image<T: TextureData>(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[T],
+    ) {
+        let (x, y, width, height) = (x as usize, y as usize, width as usize, height as usize);
+        assert!(
+            x + width <= self.width && y + height <= self.height,
+            "Sub-image region does not fit in texture, region: ({}, {}) {}x{}, texture: {}x{}",
+            x,
+            y,
+            width,
+            height,
+            self.width,
+            self.height);
+
+        check_data("sub-image", self.data_format, Some(self.data_type), (width, height, 1), data);
+
+        let _guard = ::context::ContextGuard::new(self.context);
+        unsafe {
+            gl::bind_texture(TextureBindTarget::Texture2d, self.texture_object);
+            gl::texture_sub_image_2d(
+                Texture2dTarget::Texture2d,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                self.data_format,
+                T::DATA_TYPE,
+                data.as_ptr() as *const ());
+            if self.has_mipmaps {
+                gl::generate_mipmap(TextureBindTarget::Texture2d);
+            }
+            gl::bind_texture(TextureBindTarget::Texture2d, TextureObject::null());
+        }
+    }
+
+    /// Reads the texture's data back from the GPU.
+    ///
+    /// The data is returned as bytes in the format the texture was created with, with the same
+    /// layout as the data passed to `new()`. Depth textures are returned as one normalized byte
+    /// per pixel.
+    pub fn read_data(&self) -> Vec<u8> {
+        let mut data = vec![0u8; self.width * self.height * self.data_format.elements()];
+
+        let _guard = ::context::ContextGuard::new(self.context);
+        unsafe {
+            gl::pixel_store_i32(PixelStoreParam::PackAlignment, 1);
+            gl::bind_texture(TextureBindTarget::Texture2d, self.texture_object);
+            gl::get_tex_image(
+                Texture2dTarget::Texture2d,
+                0,
+                self.data_format,
+                TextureDataType::u8,
+                data.as_mut_ptr() as *mut ());
+            gl::bind_texture(TextureBindTarget::Texture2d, TextureObject::null());
+        }
+
+        data
+    }
+
+    /// Returns the width of the texture in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the texture in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Creates a placeholder texture that doesn't have any storage.
+    ///
+    /// No GL texture object is created, binding an empty texture unbinds the texture unit, so
+    /// sampling it in a shader gives undefined results (usually black). This makes it cheap to
+    /// create and drop, but it's not useful as a fallback for a missing texture. Create a
+    /// 1x1 texture with `new()` once and reuse it instead.
     pub fn empty(context: &Context) -> Texture2d {
         Texture2d {
             texture_object: TextureObject::null(),
+            data_format: TextureFormat::Rgba,
+            data_type: TextureDataType::u8,
+            width: 0,
+            height: 0,
+            has_mipmaps: false,
 
             context: context.raw(),
+            context_alive: context.alive(),
+            share_group: context.share_group(),
         }
     }
 
@@ -89,15 +351,696 @@ impl Texture2d {
     pub(crate) fn inner(&self) -> TextureObject {
         self.texture_object
     }
+
+    /// Labels the texture so that it can be identified in debug messages and graphics debuggers
+    /// such as RenderDoc.
+    ///
+    /// Does nothing if the driver doesn't support `KHR_debug`.
+    pub fn set_debug_label(&self, label: &str) {
+        if self.texture_object.is_null() {
+            return;
+        }
+
+        let _guard = ::context::ContextGuard::new(self.context);
+        ::context::set_object_label(DebugMessageId::GL_TEXTURE, self.texture_object.0, label);
+    }
 }
 
 impl Drop for Texture2d {
     fn drop(&mut self) {
+        if !self.context_alive.get() {
+            return;
+        }
+
+        let _guard = ::context::ContextGuard::new(self.context);
+        unsafe { gl::delete_textures(1, &mut self.inner()); }
+    }
+}
+
+/// The internal format for a depth texture created with `Texture2d::depth()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DepthFormat {
+    /// 16-bit normalized integer depth.
+    Depth16,
+
+    /// 24-bit normalized integer depth.
+    Depth24,
+
+    /// 32-bit floating point depth.
+    Depth32F,
+}
+
+/// A cube map texture, made up of six square faces of the same size.
+///
+/// Cube maps are sampled with a direction vector rather than a texture coordinate, which makes
+/// them suitable for skyboxes and environment reflections. Use a `samplerCube` uniform to sample
+/// a cube map in a shader.
+#[derive(Debug)]
+pub struct TextureCube {
+    texture_object: TextureObject,
+    size: usize,
+
+    context: ::gl::Context,
+    context_alive: Rc<Cell<bool>>,
+}
+
+impl TextureCube {
+    /// Constructs a new `TextureCube` from the data for each of its faces.
+    ///
+    /// `faces` must be in the order +X, -X, +Y, -Y, +Z, -Z. The texture is created with linear
+    /// filtering and clamps texture coordinates to the edge of each face to avoid visible seams.
+    ///
+    /// # Panics
+    ///
+    /// - If any of the faces is not square.
+    /// - If the faces are not all the same size.
+    /// - If any face's data doesn't contain exactly `width * height` pixels.
+    pub fn new<T: TextureData>(
+        context: &Context,
+        data_format: TextureFormat,
+        internal_format: TextureInternalFormat,
+        faces: &[FaceImage<T>; 6],
+    ) -> Result<TextureCube, Error> {
+        const FACE_TARGETS: [Texture2dTarget; 6] = [
+            Texture2dTarget::CubeMapPositiveX,
+            Texture2dTarget::CubeMapNegativeX,
+            Texture2dTarget::CubeMapPositiveY,
+            Texture2dTarget::CubeMapNegativeY,
+            Texture2dTarget::CubeMapPositiveZ,
+            Texture2dTarget::CubeMapNegativeZ,
+        ];
+
+        let size = faces[0].width;
+        for (target, face) in FACE_TARGETS.iter().zip(faces.iter()) {
+            assert!(
+                face.width == face.height,
+                "Cube map face {:?} is not square, width: {}, height: {}",
+                target,
+                face.width,
+                face.height);
+            assert!(
+                face.width == size,
+                "Cube map face {:?} does not match the size of the other faces, expected: {}, actual: {}",
+                target,
+                size,
+                face.width);
+
+            check_data(
+                &format!("cube map face {:?}", target),
+                data_format,
+                None,
+                (face.width, face.height, 1),
+                face.data);
+        }
+
+        let max_size = context.limits().max_cube_map_texture_size;
+        check_size((size, size, 1), size, max_size)?;
+
+        let context_alive = context.alive();
+        let context = context.raw();
+        let _guard = ::context::ContextGuard::new(context);
+
+        let mut texture_object = TextureObject::null();
+        unsafe { gl::gen_textures(1, &mut texture_object); }
+
+        // Check if the texture object was successfully created.
+        if texture_object.is_null() {
+            return Err(Error::FailedToGenerateTexture);
+        }
+
+        unsafe {
+            gl::bind_texture(TextureBindTarget::CubeMap, texture_object);
+            for (&target, face) in FACE_TARGETS.iter().zip(faces.iter()) {
+                gl::texture_image_2d(
+                    target,
+                    0,
+                    internal_format,
+                    size as i32,
+                    size as i32,
+                    0,
+                    data_format,
+                    T::DATA_TYPE,
+                    face.data.as_ptr() as *const ());
+            }
+            gl::bind_texture(TextureBindTarget::CubeMap, TextureObject::null());
+        }
+
+        set_filter_params(
+            texture_object,
+            TextureBindTarget::CubeMap,
+            TextureFilterFunction::Linear,
+            TextureFilterFunction::Linear);
+        set_wrap_params(
+            texture_object,
+            TextureBindTarget::CubeMap,
+            &[
+                (TextureParameterName::WrapS, TextureWrapFunction::ClampToEdge),
+                (TextureParameterName::WrapT, TextureWrapFunction::ClampToEdge),
+                (TextureParameterName::WrapR, TextureWrapFunction::ClampToEdge),
+            ]);
+
+        Ok(TextureCube {
+            texture_object: texture_object,
+            size: size,
+
+            context: context,
+            context_alive: context_alive,
+        })
+    }
+
+    /// Returns the width and height of each face of the cube map in pixels.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Sets the filter functions used when sampling the cube map.
+    ///
+    /// Cube maps use linear filtering by default. See `Texture2d::set_filtering()`.
+    ///
+    /// # Panics
+    ///
+    /// - If `mag` is a mipmap filter, mipmaps are never used for magnification.
+    pub fn set_filtering(&mut self, min: TextureFilterFunction, mag: TextureFilterFunction) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        set_filter_params(self.texture_object, TextureBindTarget::CubeMap, min, mag);
+    }
+
+    /// Sets how texture coordinates outside of each face are handled.
+    ///
+    /// `s` is the wrap mode for the horizontal texture coordinate of each face and `t` for the
+    /// vertical. Cube maps are clamped to the edges of each face by default, other modes may
+    /// show seams where the faces meet.
+    pub fn set_wrap(&mut self, s: TextureWrapFunction, t: TextureWrapFunction) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        set_wrap_params(
+            self.texture_object,
+            TextureBindTarget::CubeMap,
+            &[(TextureParameterName::WrapS, s), (TextureParameterName::WrapT, t)]);
+    }
+
+    /// Labels the texture so that it can be identified in debug messages and graphics debuggers
+    /// such as RenderDoc.
+    ///
+    /// Does nothing if the driver doesn't support `KHR_debug`.
+    pub fn set_debug_label(&self, label: &str) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        ::context::set_object_label(DebugMessageId::GL_TEXTURE, self.texture_object.0, label);
+    }
+
+    /// Returns the OpenGL primitive managed by this object.
+    pub(crate) fn inner(&self) -> TextureObject {
+        self.texture_object
+    }
+}
+
+impl Drop for TextureCube {
+    fn drop(&mut self) {
+        if !self.context_alive.get() {
+            return;
+        }
+
         let _guard = ::context::ContextGuard::new(self.context);
         unsafe { gl::delete_textures(1, &mut self.inner()); }
     }
 }
 
+/// The data for a single face of a `TextureCube`.
+#[derive(Debug, Clone, Copy)]
+pub struct FaceImage<'a, T: 'a> {
+    pub width: usize,
+    pub height: usize,
+    pub data: &'a [T],
+}
+
+/// A three-dimensional texture, used for volume data.
+///
+/// Use a `sampler3D` uniform to sample a 3D texture in a shader.
+#[derive(Debug)]
+pub struct Texture3d {
+    texture_object: TextureObject,
+    data_format: TextureFormat,
+    data_type: TextureDataType,
+    width: usize,
+    height: usize,
+    depth: usize,
+    has_mipmaps: bool,
+
+    context: ::gl::Context,
+    context_alive: Rc<Cell<bool>>,
+}
+
+impl Texture3d {
+    /// Constructs a new `Texture3d` from the specified data.
+    ///
+    /// `data` is laid out as `depth` consecutive slices, each of which is laid out the same as
+    /// the data for a `Texture2d`. The texture is created with linear filtering and repeat
+    /// wrapping. Use `set_filtering()` and `set_wrap()` to change them.
+    ///
+    /// # Panics
+    ///
+    /// - If `data` doesn't contain exactly `width * height * depth` pixels.
+    pub fn new<T: TextureData>(
+        context: &Context,
+        data_format: TextureFormat,
+        internal_format: TextureInternalFormat,
+        width: usize,
+        height: usize,
+        depth: usize,
+        data: &[T],
+    ) -> Result<Texture3d, Error> {
+        check_data("texture", data_format, None, (width, height, depth), data);
+
+        let max_size = context.limits().max_3d_texture_size;
+        check_size((width, height, depth), width.max(height).max(depth), max_size)?;
+
+        let context_alive = context.alive();
+        let context = context.raw();
+        let _guard = ::context::ContextGuard::new(context);
+
+        let mut texture_object = TextureObject::null();
+        unsafe { gl::gen_textures(1, &mut texture_object); }
+
+        // Check if the texture object was successfully created.
+        if texture_object.is_null() {
+            return Err(Error::FailedToGenerateTexture);
+        }
+
+        unsafe {
+            gl::bind_texture(TextureBindTarget::Texture3d, texture_object);
+            gl::texture_image_3d(
+                Texture3dTarget::Texture3d,
+                0,
+                internal_format,
+                width as i32,
+                height as i32,
+                depth as i32,
+                0,
+                data_format,
+                T::DATA_TYPE,
+                data.as_ptr() as *const ());
+            gl::bind_texture(TextureBindTarget::Texture3d, TextureObject::null());
+        }
+
+        let mut texture = Texture3d {
+            texture_object: texture_object,
+            data_format: data_format,
+            data_type: T::DATA_TYPE,
+            width: width,
+            height: height,
+            depth: depth,
+            has_mipmaps: false,
+
+            context: context,
+            context_alive: context_alive,
+        };
+
+        texture.set_filtering(TextureFilterFunction::Linear, TextureFilterFunction::Linear);
+        texture.set_wrap(
+            TextureWrapFunction::Repeat,
+            TextureWrapFunction::Repeat,
+            TextureWrapFunction::Repeat);
+
+        Ok(texture)
+    }
+
+    /// Sets the filter functions used when sampling the texture.
+    ///
+    /// See `Texture2d::set_filtering()` for more information.
+    ///
+    /// # Panics
+    ///
+    /// - If `mag` is a mipmap filter, mipmaps are never used for magnification.
+    pub fn set_filtering(&mut self, min: TextureFilterFunction, mag: TextureFilterFunction) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        set_filter_params(self.texture_object, TextureBindTarget::Texture3d, min, mag);
+        self.has_mipmaps = min.uses_mipmaps();
+    }
+
+    /// Sets how texture coordinates outside of the [0, 1] range are handled.
+    ///
+    /// `s`, `t`, and `r` are the wrap modes for the width, height, and depth texture coordinates
+    /// respectively.
+    pub fn set_wrap(&mut self, s: TextureWrapFunction, t: TextureWrapFunction, r: TextureWrapFunction) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        set_wrap_params(
+            self.texture_object,
+            TextureBindTarget::Texture3d,
+            &[
+                (TextureParameterName::WrapS, s),
+                (TextureParameterName::WrapT, t),
+                (TextureParameterName::WrapR, r),
+            ]);
+    }
+
+    /// Replaces a box-shaped region of the texture's data.
+    ///
+    /// `x`, `y`, and `z` are the offset of the region from the start of the texture and `width`,
+    /// `height`, and `depth` are its dimensions. `data` is laid out the same as the data passed
+    /// to `new()` and must be in the same format the texture was created with. If the texture
+    /// is using mipmaps they are regenerated after the data is updated.
+    ///
+    /// # Panics
+    ///
+    /// - If the region doesn't fit inside the texture.
+    /// - If `data` doesn't contain exactly `width * height * depth` pixels in the texture's
+    ///   format.
+    /// - If `T` is a different type of data than the texture was created with.
+    pub fn sub_image<T: TextureData>(
+        &mut self,
+        x: u32,
+        y: u32,
+        z: u32,
+        width: u32,
+        height: u32,
+        depth: u32,
+        data: &[T],
+    ) {
+        let (x, y, z) = (x as usize, y as usize, z as usize);
+        let (width, height, depth) = (width as usize, height as usize, depth as usize);
+        assert!(
+            x + width <= self.width && y + height <= self.height && z + depth <= self.depth,
+            "Sub-image region does not fit in texture, region: ({}, {}, {}) {}x{}x{}, texture: {}x{}x{}",
+            x,
+            y,
+            z,
+            width,
+            height,
+            depth,
+            self.width,
+            self.height,
+            self.depth);
+
+        check_data("sub-image", self.data_format, Some(self.data_type), (width, height, depth), data);
+
+        let _guard = ::context::ContextGuard::new(self.context);
+        unsafe {
+            gl::bind_texture(TextureBindTarget::Texture3d, self.texture_object);
+            gl::texture_sub_image_3d(
+                Texture3dTarget::Texture3d,
+                0,
+                x as i32,
+                y as i32,
+                z as i32,
+                width as i32,
+                height as i32,
+                depth as i32,
+                self.data_format,
+                T::DATA_TYPE,
+                data.as_ptr() as *const ());
+            if self.has_mipmaps {
+                gl::generate_mipmap(TextureBindTarget::Texture3d);
+            }
+            gl::bind_texture(TextureBindTarget::Texture3d, TextureObject::null());
+        }
+    }
+
+    /// Returns the width of the texture in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the texture in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the depth of the texture in pixels.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the OpenGL primitive managed by this object.
+    pub(crate) fn inner(&self) -> TextureObject {
+        self.texture_object
+    }
+}
+
+impl Drop for Texture3d {
+    fn drop(&mut self) {
+        if !self.context_alive.get() {
+            return;
+        }
+
+        let _guard = ::context::ContextGuard::new(self.context);
+        unsafe { gl::delete_textures(1, &mut self.inner()); }
+    }
+}
+
+/// An array of 2D textures that share a size and format.
+///
+/// Each layer of the array is a separate image, but the whole array is bound to a single
+/// `sampler2DArray` uniform and sampled with the layer index as the third texture coordinate.
+/// Unlike packing images into an atlas, filtering never bleeds between layers.
+#[derive(Debug)]
+pub struct Texture2dArray {
+    texture_object: TextureObject,
+    data_format: TextureFormat,
+
+    /// The type of the data set with `set_layer_data()`, or `None` if no data has been set yet.
+    /// Every layer must use the same type.
+    data_type: Option<TextureDataType>,
+    width: usize,
+    height: usize,
+    layers: usize,
+    has_mipmaps: bool,
+
+    context: ::gl::Context,
+    context_alive: Rc<Cell<bool>>,
+}
+
+impl Texture2dArray {
+    /// Constructs a new `Texture2dArray` with storage for `layers` images.
+    ///
+    /// The contents of each layer are undefined until they are set with `set_layer_data()`.
+    /// The texture is created with linear filtering and repeat wrapping. Use `set_filtering()`
+    /// and `set_wrap()` to change them.
+    pub fn new(
+        context: &Context,
+        data_format: TextureFormat,
+        internal_format: TextureInternalFormat,
+        width: usize,
+        height: usize,
+        layers: usize,
+    ) -> Result<Texture2dArray, Error> {
+        let limits = *context.limits();
+        check_size((width, height, layers), width.max(height), limits.max_texture_size)?;
+        check_size((width, height, layers), layers, limits.max_array_texture_layers)?;
+
+        let context_alive = context.alive();
+        let context = context.raw();
+        let _guard = ::context::ContextGuard::new(context);
+
+        let mut texture_object = TextureObject::null();
+        unsafe { gl::gen_textures(1, &mut texture_object); }
+
+        // Check if the texture object was successfully created.
+        if texture_object.is_null() {
+            return Err(Error::FailedToGenerateTexture);
+        }
+
+        unsafe {
+            gl::bind_texture(TextureBindTarget::Texture2dArray, texture_object);
+            gl::texture_image_3d(
+                Texture3dTarget::Texture2dArray,
+                0,
+                internal_format,
+                width as i32,
+                height as i32,
+                layers as i32,
+                0,
+                data_format,
+                TextureDataType::u8,
+                ptr::null());
+            gl::bind_texture(TextureBindTarget::Texture2dArray, TextureObject::null());
+        }
+
+        set_filter_params(
+            texture_object,
+            TextureBindTarget::Texture2dArray,
+            TextureFilterFunction::Linear,
+            TextureFilterFunction::Linear);
+        set_wrap_params(
+            texture_object,
+            TextureBindTarget::Texture2dArray,
+            &[
+                (TextureParameterName::WrapS, TextureWrapFunction::Repeat),
+                (TextureParameterName::WrapT, TextureWrapFunction::Repeat),
+            ]);
+
+        Ok(Texture2dArray {
+            texture_object: texture_object,
+            data_format: data_format,
+            data_type: None,
+            width: width,
+            height: height,
+            layers: layers,
+            has_mipmaps: false,
+
+            context: context,
+            context_alive: context_alive,
+        })
+    }
+
+    /// Replaces the data for one layer of the array.
+    ///
+    /// `data` is laid out the same as the data for a `Texture2d` and must be in the same format
+    /// the array was created with. If the array is using mipmaps they are regenerated after the
+    /// data is updated, so prefer setting the data for every layer before enabling mipmapped
+    /// filtering.
+    ///
+    /// # Panics
+    ///
+    /// - If `layer` is not less than the number of layers in the array.
+    /// - If `data` doesn't contain exactly `width * height` pixels in the array's format.
+    /// - If `T` is a different type of data than was used for the other layers.
+    pub fn set_layer_data<T: TextureData>(&mut self, layer: usize, data: &[T]) {
+        assert!(
+            layer < self.layers,
+            "Layer index out of bounds, layer: {}, layers: {}",
+            layer,
+            self.layers);
+
+        check_data(
+            "texture layer",
+            self.data_format,
+            self.data_type,
+            (self.width, self.height, 1),
+            data);
+        self.data_type = Some(T::DATA_TYPE);
+
+        let _guard = ::context::ContextGuard::new(self.context);
+        unsafe {
+            gl::bind_texture(TextureBindTarget::Texture2dArray, self.texture_object);
+            gl::texture_sub_image_3d(
+                Texture3dTarget::Texture2dArray,
+                0,
+                0,
+                0,
+                layer as i32,
+                self.width as i32,
+                self.height as i32,
+                1,
+                self.data_format,
+                T::DATA_TYPE,
+                data.as_ptr() as *const ());
+            if self.has_mipmaps {
+                gl::generate_mipmap(TextureBindTarget::Texture2dArray);
+            }
+            gl::bind_texture(TextureBindTarget::Texture2dArray, TextureObject::null());
+        }
+    }
+
+    /// Sets the filter functions used when sampling the texture.
+    ///
+    /// See `Texture2d::set_filtering()` for more information.
+    ///
+    /// # Panics
+    ///
+    /// - If `mag` is a mipmap filter, mipmaps are never used for magnification.
+    pub fn set_filtering(&mut self, min: TextureFilterFunction, mag: TextureFilterFunction) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        set_filter_params(self.texture_object, TextureBindTarget::Texture2dArray, min, mag);
+        self.has_mipmaps = min.uses_mipmaps();
+    }
+
+    /// Sets how texture coordinates outside of the [0, 1] range are handled.
+    ///
+    /// `s` is the wrap mode for the horizontal texture coordinate and `t` for the vertical.
+    /// The layer coordinate is never wrapped.
+    pub fn set_wrap(&mut self, s: TextureWrapFunction, t: TextureWrapFunction) {
+        let _guard = ::context::ContextGuard::new(self.context);
+        set_wrap_params(
+            self.texture_object,
+            TextureBindTarget::Texture2dArray,
+            &[(TextureParameterName::WrapS, s), (TextureParameterName::WrapT, t)]);
+    }
+
+    /// Returns the width of each layer in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of each layer in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the number of layers in the array.
+    pub fn layers(&self) -> usize {
+        self.layers
+    }
+
+    /// Returns the OpenGL primitive managed by this object.
+    pub(crate) fn inner(&self) -> TextureObject {
+        self.texture_object
+    }
+}
+
+impl Drop for Texture2dArray {
+    fn drop(&mut self) {
+        if !self.context_alive.get() {
+            return;
+        }
+
+        let _guard = ::context::ContextGuard::new(self.context);
+        unsafe { gl::delete_textures(1, &mut self.inner()); }
+    }
+}
+
+/// Sets the filter functions for a texture, generating its mipmaps if `min` uses them.
+///
+/// The texture's context must be current.
+///
+/// # Panics
+///
+/// - If `mag` is a mipmap filter, mipmaps are never used for magnification.
+fn set_filter_params(
+    texture_object: TextureObject,
+    target: TextureBindTarget,
+    min: TextureFilterFunction,
+    mag: TextureFilterFunction,
+) {
+    assert!(!mag.uses_mipmaps(), "Magnification filter cannot use mipmaps: {:?}", mag);
+
+    let parameter_target = parameter_target(target);
+    unsafe {
+        gl::bind_texture(target, texture_object);
+        if min.uses_mipmaps() {
+            gl::generate_mipmap(target);
+        }
+        gl::texture_parameter_i32(parameter_target, TextureParameterName::MinFilter, min.into());
+        gl::texture_parameter_i32(parameter_target, TextureParameterName::MagFilter, mag.into());
+        gl::bind_texture(target, TextureObject::null());
+    }
+}
+
+/// Sets the wrap modes for a texture, each paired with the coordinate it applies to.
+///
+/// The texture's context must be current.
+fn set_wrap_params(
+    texture_object: TextureObject,
+    target: TextureBindTarget,
+    wraps: &[(TextureParameterName, TextureWrapFunction)],
+) {
+    let parameter_target = parameter_target(target);
+    unsafe {
+        gl::bind_texture(target, texture_object);
+        for &(name, wrap) in wraps {
+            gl::texture_parameter_i32(parameter_target, name, wrap.into());
+        }
+        gl::bind_texture(target, TextureObject::null());
+    }
+}
+
+fn parameter_target(target: TextureBindTarget) -> TextureParameterTarget {
+    match target {
+        TextureBindTarget::Texture2d => TextureParameterTarget::Texture2d,
+        TextureBindTarget::Texture3d => TextureParameterTarget::Texture3d,
+        TextureBindTarget::Texture2dArray => TextureParameterTarget::Texture2dArray,
+        TextureBindTarget::CubeMap => TextureParameterTarget::CubeMap,
+    }
+}
+
 pub trait TextureData {
     const DATA_TYPE: TextureDataType;
     const ELEMENTS: usize;
@@ -126,6 +1069,67 @@ impl TextureData for (u8, u8, u8, u8) {
 #[derive(Debug)]
 pub enum Error {
     FailedToGenerateTexture,
+
+    /// The requested texture is larger than the implementation supports.
+    ///
+    /// `dimensions` is the requested width, height, and depth (or layer count) of the texture,
+    /// and `max_size` is the limit that was exceeded. See `Context::limits()`.
+    TooLarge {
+        dimensions: (usize, usize, usize),
+        max_size: usize,
+    },
+}
+
+/// Checks that `data` holds exactly the pixels for a region of `dimensions` in `data_format`.
+///
+/// `data_type` is the type of data the texture was created with. Textures store the data in
+/// their internal format regardless, but mixing types is almost always a mistake, such as
+/// uploading bytes to a float texture that was meant to hold values outside of [0, 1].
+///
+/// # Panics
+///
+/// - If `T` is a different type of data than `data_type`.
+/// - If `data` doesn't contain exactly the number of elements for the region. Data for part of
+///   a pixel isn't rounded away, so a tuple type with a different number of elements than
+///   `data_format` only fits if it adds up to whole pixels.
+fn check_data<T: TextureData>(
+    what: &str,
+    data_format: TextureFormat,
+    data_type: Option<TextureDataType>,
+    dimensions: (usize, usize, usize),
+    data: &[T],
+) {
+    if let Some(data_type) = data_type {
+        assert!(
+            T::DATA_TYPE == data_type,
+            "Wrong type of data for {}, expected: {:?}, actual: {:?}",
+            what,
+            data_type,
+            T::DATA_TYPE);
+    }
+
+    let (width, height, depth) = dimensions;
+    let expected_elements = width * height * depth * data_format.elements();
+    let actual_elements = data.len() * T::ELEMENTS;
+    assert!(
+        expected_elements == actual_elements,
+        "Wrong amount of data for {}, dimensions: {}x{}x{}, format: {:?}, expected elements: {}, actual elements: {}",
+        what,
+        width,
+        height,
+        depth,
+        data_format,
+        expected_elements,
+        actual_elements);
+}
+
+/// Returns `Error::TooLarge` if `size` exceeds `max_size`.
+fn check_size(dimensions: (usize, usize, usize), size: usize, max_size: usize) -> Result<(), Error> {
+    if size > max_size {
+        Err(Error::TooLarge { dimensions: dimensions, max_size: max_size })
+    } else {
+        Ok(())
+    }
 }
 
 pub unsafe fn set_active_texture(index: u32) {
@@ -0,0 +1,405 @@
+//! Tests for gl-util that need a live OpenGL context.
+//!
+//! Each test creates its own offscreen context, so no window is needed.
+
+extern crate gl_util;
+
+use gl_util::*;
+use gl_util::context::{Context, ContextBuilder};
+use gl_util::framebuffer::Framebuffer;
+use gl_util::shader::*;
+use gl_util::texture::*;
+
+static VERT_SOURCE: &'static str = r#"
+    #version 330 core
+
+    in vec4 position;
+
+    void main() {
+        gl_Position = position;
+    }
+"#;
+
+static FRAG_SOURCE: &'static str = r#"
+    #version 330 core
+
+    uniform float red;
+    uniform float green;
+
+    out vec4 fragment_color;
+
+    void main() {
+        fragment_color = vec4(red, green, 0.0, 1.0);
+    }
+"#;
+
+const SIZE: usize = 4;
+
+fn position_location() -> AttributeLocation {
+    AttributeLocation::from_index(0)
+}
+
+/// Builds a triangle that covers the whole viewport.
+fn fullscreen_triangle(context: &Context) -> VertexArray {
+    let mut vertex_buffer = VertexBuffer::new(context);
+    vertex_buffer.set_data_f32(&[
+        -1.0, -1.0, 0.0, 1.0,
+         3.0, -1.0, 0.0, 1.0,
+        -1.0,  3.0, 0.0, 1.0,
+    ]);
+    vertex_buffer.set_attrib_f32(
+        "position",
+        AttribLayout {
+            elements: 4,
+            stride: 0,
+            offset: 0,
+        });
+    VertexArray::new(context, vertex_buffer)
+}
+
+fn color_program(context: &Context) -> Program {
+    ProgramBuilder::new(context)
+        .bind_attrib("position", position_location())
+        .build_from_sources(VERT_SOURCE, FRAG_SOURCE)
+        .unwrap()
+}
+
+/// Builds a framebuffer that draws into a `SIZE` by `SIZE` RGBA texture.
+fn render_target(context: &Context) -> (Framebuffer, Texture2d) {
+    let texture = Texture2d::render_target(context, TextureInternalFormat::Rgba, SIZE, SIZE).unwrap();
+    let mut framebuffer = Framebuffer::new(context);
+    framebuffer.attach_color(0, &texture);
+    (framebuffer, texture)
+}
+
+/// Gets the first pixel of `texture`.
+fn first_pixel(texture: &Texture2d) -> [u8; 4] {
+    let data = texture.read_data();
+    [data[0], data[1], data[2], data[3]]
+}
+
+#[test]
+fn clear_uniform_resets_value() {
+    let context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+    let vertex_array = fullscreen_triangle(&context);
+    let program = color_program(&context);
+    let (framebuffer, texture) = render_target(&context);
+
+    let mut draw_builder = DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles);
+    draw_builder
+        .program(&program)
+        .framebuffer(&framebuffer)
+        .viewport(0, 0, SIZE as i32, SIZE as i32)
+        .map_attrib_location("position", position_location());
+
+    // Setting the same name twice takes the latest value.
+    draw_builder
+        .uniform("red", 0.0f32)
+        .uniform("red", 1.0f32)
+        .uniform("green", 1.0f32)
+        .draw();
+    assert_eq!(first_pixel(&texture), [255, 255, 0, 255]);
+
+    // A cleared uniform doesn't keep the value from the previous draw, even though OpenGL
+    // retains it in the program.
+    draw_builder.clear_uniform("green").draw();
+    assert_eq!(first_pixel(&texture), [255, 0, 0, 255]);
+
+    // Setting a cleared uniform again sends the new value.
+    draw_builder.uniform("green", 1.0f32).draw();
+    assert_eq!(first_pixel(&texture), [255, 255, 0, 255]);
+
+    draw_builder.clear_uniforms().draw();
+    assert_eq!(first_pixel(&texture), [0, 0, 0, 255]);
+
+    // Uniforms set by a different draw builder are reset based on the program's reflection data.
+    DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+        .program(&program)
+        .framebuffer(&framebuffer)
+        .viewport(0, 0, SIZE as i32, SIZE as i32)
+        .map_attrib_location("position", position_location())
+        .uniform("red", 1.0f32)
+        .draw();
+    assert_eq!(first_pixel(&texture), [255, 0, 0, 255]);
+
+    DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+        .program(&program)
+        .framebuffer(&framebuffer)
+        .viewport(0, 0, SIZE as i32, SIZE as i32)
+        .map_attrib_location("position", position_location())
+        .clear_uniform("red")
+        .draw();
+    assert_eq!(first_pixel(&texture), [0, 0, 0, 255]);
+
+    assert_eq!(context.check_error(), None);
+}
+
+#[test]
+#[should_panic(expected = "Specified vertex buffer is not shared with vertex array's context")]
+fn vertex_array_wrong_context() {
+    let context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+    let other_context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+
+    let vertex_buffer = VertexBuffer::new(&other_context);
+    VertexArray::new(&context, vertex_buffer);
+}
+
+#[test]
+#[should_panic(expected = "Specified program is not shared with draw builder's context")]
+fn draw_program_wrong_context() {
+    let context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+    let other_context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+
+    let vertex_array = fullscreen_triangle(&context);
+    let program = color_program(&other_context);
+    DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles).program(&program);
+}
+
+#[test]
+fn draw_program_shared_context() {
+    let context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+    let shared_context = ContextBuilder::new()
+        .share_with(&context)
+        .build_offscreen(SIZE as i32, SIZE as i32)
+        .unwrap();
+
+    // Objects created with a shared context can be used with the original one.
+    let vertex_array = fullscreen_triangle(&context);
+    let program = color_program(&shared_context);
+    let (framebuffer, texture) = render_target(&context);
+    DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+        .program(&program)
+        .framebuffer(&framebuffer)
+        .viewport(0, 0, SIZE as i32, SIZE as i32)
+        .map_attrib_location("position", position_location())
+        .uniform("red", 1.0f32)
+        .uniform("green", 0.0f32)
+        .draw();
+    assert_eq!(first_pixel(&texture), [255, 0, 0, 255]);
+}
+
+#[test]
+fn shader_compile_error_log() {
+    let context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+
+    let broken_frag = r#"
+        #version 330 core
+
+        out vec4 fragment_color;
+
+        void main() {
+            fragment_color = not_a_variable;
+        }
+    "#;
+
+    // The info log from the driver is passed through, so the error names the problem.
+    match ProgramBuilder::new(&context).build_from_sources(VERT_SOURCE, broken_frag) {
+        Err(ProgramError::ShaderError(ShaderError::CompileError { shader_type, ref log })) => {
+            assert_eq!(shader_type, ShaderType::Fragment);
+            assert!(log.contains("not_a_variable"), "Log doesn't mention the error: {}", log);
+        }
+        result => panic!("Expected fragment compile error, got {:?}", result.map(|_| ())),
+    }
+
+    let broken_vert = r#"
+        #version 330 core
+
+        in vec4 position;
+
+        void main() {
+            gl_Position = position * not_a_variable;
+        }
+    "#;
+
+    match ProgramBuilder::new(&context).build_from_sources(broken_vert, FRAG_SOURCE) {
+        Err(ProgramError::ShaderError(ShaderError::CompileError { shader_type, ref log })) => {
+            assert_eq!(shader_type, ShaderType::Vertex);
+            assert!(log.contains("not_a_variable"), "Log doesn't mention the error: {}", log);
+        }
+        result => panic!("Expected vertex compile error, got {:?}", result.map(|_| ())),
+    }
+}
+
+#[test]
+fn program_link_error_log() {
+    let context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+
+    // The fragment shader reads a varying that the vertex shader never writes, which compiles
+    // but fails to link.
+    let unmatched_frag = r#"
+        #version 330 core
+
+        in vec4 missing_varying;
+
+        out vec4 fragment_color;
+
+        void main() {
+            fragment_color = missing_varying;
+        }
+    "#;
+
+    match ProgramBuilder::new(&context).build_from_sources(VERT_SOURCE, unmatched_frag) {
+        // Drivers word link errors differently, so only check that the log was passed through.
+        Err(ProgramError::LinkError(ref log)) => assert!(!log.is_empty(), "Link error has no log"),
+        result => panic!("Expected link error, got {:?}", result.map(|_| ())),
+    }
+}
+
+#[test]
+#[should_panic(expected = "Wrong amount of data for texture")]
+fn texture_partial_pixel_data() {
+    let context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+
+    // 3 RGB pixels take 9 bytes, which isn't a whole number of 4 byte tuples. Rounding the
+    // expected amount down would accept 2 tuples and read past the end of the data.
+    let data = [(255u8, 255u8, 255u8, 255u8); 2];
+    let _ = Texture2d::new(&context, TextureFormat::Rgb, TextureInternalFormat::Rgb, 3, 1, &data);
+}
+
+#[test]
+#[should_panic(expected = "Wrong type of data for sub-image")]
+fn texture_sub_image_wrong_type() {
+    let context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+
+    let data = [0u8; SIZE * SIZE * 4];
+    let mut texture = Texture2d::new(
+        &context,
+        TextureFormat::Rgba,
+        TextureInternalFormat::Rgba,
+        SIZE,
+        SIZE,
+        &data).unwrap();
+
+    // The right amount of data, but floats for a texture created from bytes.
+    texture.sub_image(0, 0, 1, 1, &[1.0f32, 1.0, 1.0, 1.0]);
+}
+
+#[test]
+fn texture_tuple_data() {
+    let context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+
+    // Tuple data is counted by its elements, so each 4 byte tuple is one RGBA pixel.
+    let data = [(10u8, 20u8, 30u8, 40u8); 3];
+    let mut texture = Texture2d::new(
+        &context,
+        TextureFormat::Rgba,
+        TextureInternalFormat::Rgba,
+        3,
+        1,
+        &data).unwrap();
+    texture.sub_image(1, 0, 1, 1, &[(50u8, 60u8, 70u8, 80u8)]);
+
+    assert_eq!(texture.read_data(), vec![10, 20, 30, 40, 50, 60, 70, 80, 10, 20, 30, 40]);
+}
+
+#[test]
+fn drop_resources_before_context() {
+    let context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+
+    {
+        let vertex_array = fullscreen_triangle(&context);
+        let program = color_program(&context);
+        let (framebuffer, texture) = render_target(&context);
+        DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+            .program(&program)
+            .framebuffer(&framebuffer)
+            .viewport(0, 0, SIZE as i32, SIZE as i32)
+            .map_attrib_location("position", position_location())
+            .uniform("red", 1.0f32)
+            .uniform("green", 1.0f32)
+            .draw();
+        assert_eq!(first_pixel(&texture), [255, 255, 0, 255]);
+    }
+
+    // Every object was deleted while its context was still alive, which must not generate errors.
+    assert_eq!(context.check_error(), None);
+}
+
+#[test]
+fn drop_resources_after_context() {
+    let context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+
+    let vertex_array = fullscreen_triangle(&context);
+    let program = color_program(&context);
+    let (framebuffer, texture) = render_target(&context);
+
+    // Destroying the context deletes all of its objects, so dropping the resources afterwards
+    // must not try to make the dead context current and delete them again.
+    drop(context);
+    drop(vertex_array);
+    drop(program);
+    drop(framebuffer);
+    drop(texture);
+}
+
+#[test]
+fn drop_shared_resources_after_context() {
+    let context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+    let shared_context = ContextBuilder::new()
+        .share_with(&context)
+        .build_offscreen(SIZE as i32, SIZE as i32)
+        .unwrap();
+
+    let program = color_program(&context);
+    let (framebuffer, texture) = render_target(&context);
+
+    // Dropping objects that outlive the context that created them must leave the contexts they
+    // were shared with untouched.
+    drop(context);
+    drop(program);
+    drop(framebuffer);
+    drop(texture);
+    assert_eq!(shared_context.check_error(), None);
+
+    let vertex_array = fullscreen_triangle(&shared_context);
+    let program = color_program(&shared_context);
+    let (framebuffer, texture) = render_target(&shared_context);
+    DrawBuilder::new(&shared_context, &vertex_array, DrawMode::Triangles)
+        .program(&program)
+        .framebuffer(&framebuffer)
+        .viewport(0, 0, SIZE as i32, SIZE as i32)
+        .map_attrib_location("position", position_location())
+        .uniform("red", 0.0f32)
+        .uniform("green", 1.0f32)
+        .draw();
+    assert_eq!(first_pixel(&texture), [0, 255, 0, 255]);
+    assert_eq!(shared_context.check_error(), None);
+}
+
+#[test]
+fn framebuffer_clear_region() {
+    let context = Context::offscreen(SIZE as i32, SIZE as i32).unwrap();
+    let vertex_array = fullscreen_triangle(&context);
+    let program = color_program(&context);
+    let (framebuffer, texture) = render_target(&context);
+
+    DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+        .program(&program)
+        .framebuffer(&framebuffer)
+        .viewport(0, 0, SIZE as i32, SIZE as i32)
+        .map_attrib_location("position", position_location())
+        .uniform("red", 0.0f32)
+        .uniform("green", 1.0f32)
+        .draw();
+
+    // Only the bottom-left quarter is cleared, the rest keeps the drawn color.
+    context.set_clear_color(0.0, 0.0, 1.0, 1.0);
+    framebuffer.clear_region(0, 0, SIZE as i32 / 2, SIZE as i32 / 2, true, false, false);
+
+    let data = texture.read_data();
+    let pixel = |x: usize, y: usize| {
+        let offset = (y * SIZE + x) * 4;
+        [data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]
+    };
+    assert_eq!(pixel(0, 0), [0, 0, 255, 255]);
+    assert_eq!(pixel(SIZE / 2 - 1, SIZE / 2 - 1), [0, 0, 255, 255]);
+    assert_eq!(pixel(SIZE / 2, 0), [0, 255, 0, 255]);
+    assert_eq!(pixel(0, SIZE / 2), [0, 255, 0, 255]);
+    assert_eq!(pixel(SIZE - 1, SIZE - 1), [0, 255, 0, 255]);
+
+    // The scissor test doesn't stay enabled for later full clears.
+    framebuffer.clear_flags(true, false, false);
+    let data = texture.read_data();
+    assert_eq!(&data[data.len() - 4..], &[0, 0, 255, 255]);
+    assert_eq!(context.check_error(), None);
+}
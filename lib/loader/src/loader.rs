@@ -9,9 +9,12 @@ use std::dynamic_lib::DynamicLibrary;
 use std::path::Path;
 use std::mem;
 use std::thread;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{Read, Write};
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering, ATOMIC_BOOL_INIT};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use bootstrap::time::Timer;
 use bootstrap::window::Window;
@@ -19,6 +22,10 @@ use bootstrap::windows::file::file_modified;
 
 const TARGET_FRAME_TIME_MS: f32 = 1.0 / 60.0 * 1000.0;
 
+// NOTE: These must match the paths used by the engine's crash handler in `gunship::crash`.
+const CRASH_REPORT_DIR: &'static str = "crash_reports";
+const LAST_CRASH_FILE: &'static str = "crash_reports/last_crash.txt";
+
 type EngineInit = fn (Rc<RefCell<Window>>) -> Box<()>;
 type EngineReload = fn (&()) -> Box<()>;
 type EngineUpdateAndRender = fn (&mut ());
@@ -60,6 +67,76 @@ fn load_engine_procs(lib: &DynamicLibrary) -> (EngineUpdateAndRender, EngineClos
     (engine_update_and_render, engine_close, engine_drop)
 }
 
+/// Checks for a crash report left behind by a previous run of the game and prints its location.
+///
+/// The marker file is removed once it has been reported so that the same crash isn't reported
+/// twice.
+fn report_previous_crash() {
+    let mut path = String::new();
+    let found = File::open(LAST_CRASH_FILE)
+        .and_then(|mut file| file.read_to_string(&mut path))
+        .is_ok();
+
+    if found {
+        println!("The game crashed on its last run, crash report written to {}", path);
+        let _ = fs::remove_file(LAST_CRASH_FILE);
+    }
+}
+
+/// Writes a minimal crash report for structured exceptions raised by the game DLL.
+///
+/// Vectored exception handlers see every first-chance exception, so only exceptions that are
+/// going to bring the process down are reported. The handler always continues the search so that
+/// the exception is still handled (or not) normally.
+#[allow(non_snake_case)]
+unsafe extern "system" fn exception_handler(ExceptionInfo: *mut winapi::EXCEPTION_POINTERS) -> winapi::LONG {
+    const EXCEPTION_CONTINUE_SEARCH: winapi::LONG = 0;
+    const EXCEPTION_ACCESS_VIOLATION: winapi::DWORD = 0xC0000005;
+    const EXCEPTION_ILLEGAL_INSTRUCTION: winapi::DWORD = 0xC000001D;
+    const EXCEPTION_INT_DIVIDE_BY_ZERO: winapi::DWORD = 0xC0000094;
+    const EXCEPTION_STACK_OVERFLOW: winapi::DWORD = 0xC00000FD;
+
+    static HANDLING_EXCEPTION: AtomicBool = ATOMIC_BOOL_INIT;
+
+    let record = &*(*ExceptionInfo).ExceptionRecord;
+    match record.ExceptionCode {
+        EXCEPTION_ACCESS_VIOLATION
+        | EXCEPTION_ILLEGAL_INSTRUCTION
+        | EXCEPTION_INT_DIVIDE_BY_ZERO
+        | EXCEPTION_STACK_OVERFLOW => {},
+        _ => return EXCEPTION_CONTINUE_SEARCH,
+    }
+
+    // Don't recurse if writing the report raises another exception, or report exceptions raised
+    // on other threads while we're writing it.
+    if HANDLING_EXCEPTION.swap(true, Ordering::SeqCst) {
+        return EXCEPTION_CONTINUE_SEARCH;
+    }
+
+    let timestamp = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs(),
+        Err(_) => 0,
+    };
+    let directory = Path::new(CRASH_REPORT_DIR).join(timestamp.to_string());
+
+    let result = fs::create_dir_all(&directory)
+        .and_then(|_| File::create(directory.join("exception.txt")))
+        .and_then(|mut report| {
+            writeln!(report, "[exception]")?;
+            writeln!(report, "code: 0x{:08X}", record.ExceptionCode)?;
+            writeln!(report, "address: {:?}", record.ExceptionAddress)
+        })
+        .and_then(|_| File::create(LAST_CRASH_FILE))
+        .and_then(|mut last_crash| write!(last_crash, "{}", directory.display()));
+
+    match result {
+        Ok(()) => println!("Crash report written to {}", directory.display()),
+        Err(error) => println!("Failed to write crash report: {:?}", error),
+    }
+
+    EXCEPTION_CONTINUE_SEARCH
+}
+
 /// # TODO
 ///
 /// - Keep track of the temp files made and then delete them when done running.
@@ -68,6 +145,9 @@ fn load_engine_procs(lib: &DynamicLibrary) -> (EngineUpdateAndRender, EngineClos
 pub fn run_loader(src_lib: &str) {
     let mut counter = 0..;
 
+    report_previous_crash();
+    unsafe { kernel32::AddVectoredExceptionHandler(1, Some(exception_handler)); }
+
     // Statically create a window and load the renderer for the engine.
     let instance = bootstrap::init();
     let window = Window::new("Gunship Game", instance);
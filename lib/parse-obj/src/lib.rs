@@ -1,3 +1,4 @@
+use std::ops::Range;
 use std::path::Path;
 use std::slice;
 use std::str::FromStr;
@@ -16,6 +17,17 @@ pub struct Obj {
     texcoord_indices: Vec<Vec<usize>>,
     normals: Vec<Vector3>,
     normal_indices: Vec<Vec<usize>>,
+    material_groups: Vec<MaterialGroup>,
+}
+
+/// A run of consecutive faces that use the same material, as set by a `usemtl` directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaterialGroup {
+    /// The name of the material, as given to `usemtl`.
+    pub material: String,
+
+    /// The indices of the faces in the group.
+    pub faces: Range<usize>,
 }
 
 impl Obj {
@@ -72,6 +84,7 @@ impl Obj {
         let mut texcoord_indices = Vec::new();
         let mut normals = Vec::new();
         let mut normal_indices = Vec::new();
+        let mut material_groups: Vec<MaterialGroup> = Vec::new();
 
         for line in file_text.lines() {
             let mut tokens = line.split_whitespace();
@@ -195,7 +208,19 @@ impl Obj {
                 "c_interp" => {},
                 "d_interp" => {},
                 "lod" => {},
-                "usemtl" => {},
+                // Start a new material group, ending the current one.
+                "usemtl" => {
+                    let material = try!(tokens.next().ok_or(Error::MissingDirectiveData));
+                    let face_count = position_indices.len();
+                    if let Some(group) = material_groups.last_mut() {
+                        group.faces.end = face_count;
+                    }
+
+                    material_groups.push(MaterialGroup {
+                        material: material.into(),
+                        faces: face_count..face_count,
+                    });
+                },
                 "shadow_obj" => {},
                 "trace_obj" => {},
                 "ctech" => {},
@@ -207,6 +232,14 @@ impl Obj {
             }
         }
 
+        // The last material group runs to the end of the file.
+        if let Some(group) = material_groups.last_mut() {
+            group.faces.end = position_indices.len();
+        }
+
+        // Groups without faces, e.g. from consecutive `usemtl` directives, are dropped.
+        material_groups.retain(|group| group.faces.start != group.faces.end);
+
         // Check that either all of the faces of texcoords or none do.
         if texcoord_indices.len() != 0 && texcoord_indices.len() != position_indices.len() {
             return Err(Error::MismatchedFaceData);
@@ -224,6 +257,7 @@ impl Obj {
             texcoord_indices: texcoord_indices,
             normals: normals,
             normal_indices: normal_indices,
+            material_groups: material_groups,
         })
     }
 
@@ -301,6 +335,13 @@ impl Obj {
         &*self.normal_indices
     }
 
+    /// Gets the material groups of the faces, in the order they appear in the file.
+    ///
+    /// Faces before the first `usemtl` directive aren't in any group.
+    pub fn material_groups(&self) -> &[MaterialGroup] {
+        &*self.material_groups
+    }
+
     /// Returns an iterator over the faces in mesh.
     pub fn faces(&self) -> FaceIter {
         FaceIter {
@@ -22,6 +22,20 @@ vn 1.0 0.0 0.0
 f 1/1/1 2/1/1 3/1/1
 "#;
 
+static MATERIAL_GROUPS_OBJ: &'static str = r#"
+v -1.0 -1.0 0.0
+v 1.0 -1.0 0.0
+v 0.0 1.0 0.0
+
+f 1// 2// 3//
+usemtl brick
+f 1// 2// 3//
+f 1// 2// 3//
+usemtl unused
+usemtl glass
+f 1// 2// 3//
+"#;
+
 #[test]
 fn test_iterator() {
     {
@@ -48,3 +62,16 @@ fn test_iterator() {
         assert_eq!(Some(((0.0, 1.0, 0.0, 1.0), Some((1.0, 1.0, 1.0)), Some((1.0, 0.0, 0.0)))), face.next());
     }
 }
+
+#[test]
+fn test_material_groups() {
+    let obj = Obj::from_str(MATERIAL_GROUPS_OBJ).unwrap();
+
+    assert_eq!(obj.position_indices().len(), 4);
+    assert_eq!(
+        obj.material_groups(),
+        &[
+            MaterialGroup { material: "brick".into(), faces: 1..3 },
+            MaterialGroup { material: "glass".into(), faces: 3..4 },
+        ]);
+}
@@ -46,18 +46,18 @@ impl<'a> Lexer<'a> {
         };
 
         // See if token is an identifier.
-        if character.is_ident() {
+        if character.is_ident_start() {
             return self.parse_ident(start_index)
         }
 
         // See if token is a number literal.
         if character.is_numeric_part() {
-            return unimplemented!();
+            return self.parse_number_literal(start_index);
         }
 
         // See if character is string literal.
         if character == '"' {
-            return unimplemented!();
+            return self.parse_string_literal(start_index);
         }
 
         // See if token is program literal.
@@ -70,6 +70,9 @@ impl<'a> Lexer<'a> {
             ';' => Token::SemiColon,
             '=' => Token::Eq,
             ':' => Token::Colon,
+            ',' => Token::Comma,
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
 
             _ => {
                 self.is_done = true;
@@ -113,6 +116,37 @@ impl<'a> Lexer<'a> {
         Ok((Token::Identifier, Span::new(start_index, self.source.len())))
     }
 
+    fn parse_number_literal(&mut self, start_index: usize) -> Result<(Token, Span)> {
+        // Take everything that could be part of a number and leave validating it to the parser.
+        while let Some(&(end_index, character)) = self.chars.peek() {
+            if !character.is_numeric_part() && character != '.' {
+                return Ok((Token::NumberLiteral, Span::new(start_index, end_index)));
+            }
+
+            // Consume the item we peeked at.
+            self.chars.next();
+        }
+
+        Ok((Token::NumberLiteral, Span::new(start_index, self.source.len())))
+    }
+
+    fn parse_string_literal(&mut self, start_index: usize) -> Result<(Token, Span)> {
+        // Walk through the source string until the closing quote. Escape sequences aren't
+        // supported.
+        while let Some((end_index, character)) = self.chars.next() {
+            if character == '"' {
+                return Ok((Token::StringLiteral, Span::new(start_index + 1, end_index)));
+            }
+        }
+
+        // Uh-oh, we got to the end and never closed the quotes.
+        self.is_done = true;
+        Err(Error {
+            span: Span::new(start_index, self.source.len()),
+            data: ErrorData::UnclosedStringLiteral,
+        })
+    }
+
     fn parse_program_literal(&mut self, start_index: usize) -> Result<(Token, Span)> {
         // Start at depth 1 because we've already removed the opening '{'.
         let mut depth = 1;
@@ -152,6 +186,7 @@ pub struct Error {
 pub enum ErrorData {
     IllegalSymbol(char),
     UnclosedProgramLiteral,
+    UnclosedStringLiteral,
 }
 
 trait CharacterParseExt {
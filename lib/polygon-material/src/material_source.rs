@@ -2,14 +2,19 @@ use parser::{Parser, Error as ParseError};
 use std::fs::File;
 use std::io::Error as IoError;
 use std::io::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Represents the contents of a material file that has been loaded into memory but has not been
 /// sent to the renderer.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq)]
 pub struct MaterialSource {
     pub properties: Vec<PropertySource>,
     pub programs: Vec<ProgramSource>,
+
+    /// The file the material was loaded from, if any.
+    ///
+    /// Set by `from_file()`, and used by renderers to say which material failed to build.
+    pub path: Option<PathBuf>,
 }
 
 impl MaterialSource {
@@ -18,7 +23,9 @@ impl MaterialSource {
         let mut contents = String::new();
         file.read_to_string(&mut contents)?;
 
-        MaterialSource::from_str(&*contents)
+        let mut source = MaterialSource::from_str(&*contents)?;
+        source.path = Some(path.as_ref().to_path_buf());
+        Ok(source)
     }
 
     pub fn from_str<T: AsRef<str>>(source: T) -> Result<MaterialSource, Error> {
@@ -61,10 +68,15 @@ impl ProgramSource {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PropertySource {
     pub name: String,
     pub property_type: PropertyType,
+
+    /// The value given to the property when a material is built from the source, if declared.
+    ///
+    /// The default always matches `property_type`.
+    pub default: Option<PropertyDefault>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -73,7 +85,28 @@ pub enum PropertyType {
     Color,
     Texture2d,
     f32,
+    Vector2,
     Vector3,
+    Vector4,
+    Matrix4,
+}
+
+/// The default value declared for a property, e.g. `property surface_shininess: f32 = 4.0;`.
+///
+/// Colors are given as `(r, g, b)` or `(r, g, b, a)`, vectors as a tuple of their components,
+/// and matrices as a tuple of their 16 elements in row-major order. Textures are
+/// given as a path in quotes, relative to the material file. Renderers can't load textures on
+/// their own, so resolving the path is left to whoever loaded the material.
+#[derive(Debug, Clone, PartialEq)]
+#[allow(bad_style)]
+pub enum PropertyDefault {
+    Color([f32; 4]),
+    Texture2d(String),
+    f32(f32),
+    Vector2([f32; 2]),
+    Vector3([f32; 3]),
+    Vector4([f32; 4]),
+    Matrix4([f32; 16]),
 }
 
 /// Represents an error in parsing a material source file.
@@ -1,5 +1,5 @@
 use lexer::{Lexer, Error as TokenError};
-use material_source::{MaterialSource, ProgramSource, PropertySource, PropertyType};
+use material_source::{MaterialSource, ProgramSource, PropertyDefault, PropertySource, PropertyType};
 use token::*;
 
 #[derive(Debug)]
@@ -35,6 +35,7 @@ impl<'a> Parser<'a> {
         Ok(MaterialSource {
             properties: properties,
             programs: programs,
+            path: None,
         })
     }
 
@@ -62,24 +63,138 @@ impl<'a> Parser<'a> {
                 "Color" => PropertyType::Color,
                 "Texture2d" => PropertyType::Texture2d,
                 "f32" => PropertyType::f32,
+                "Vector2" => PropertyType::Vector2,
                 "Vector3" => PropertyType::Vector3,
+                "Vector4" => PropertyType::Vector4,
+                "Matrix4" => PropertyType::Matrix4,
                 _ => return Err(Error::BadPropertyType(span)),
             },
             _ => return Err(Error::ExpectedIdent(span)),
         };
 
         let (token, span) = self.lexer.next()?;
-        match token {
-            Token::SemiColon => {},
+        let default = match token {
+            Token::SemiColon => None,
+            Token::Eq => {
+                let default = self.parse_property_default(property_type)?;
+
+                let (token, span) = self.lexer.next()?;
+                match token {
+                    Token::SemiColon => {},
+                    _ => return Err(Error::ExpectedSemiColon(span)),
+                }
+
+                Some(default)
+            },
             _ => return Err(Error::ExpectedSemiColon(span)),
-        }
+        };
 
         Ok(PropertySource {
             name: ident,
             property_type: property_type,
+            default: default,
         })
     }
 
+    /// Parses the default value of a property.
+    ///
+    /// # Preconditions
+    ///
+    /// - The "=" following the property type was already pulled from the lexer.
+    fn parse_property_default(&mut self, property_type: PropertyType) -> Result<PropertyDefault, Error> {
+        let (token, span) = self.lexer.next()?;
+        match (property_type, token) {
+            (PropertyType::f32, Token::NumberLiteral) => {
+                let value = self.parse_number(span)?;
+                Ok(PropertyDefault::f32(value))
+            },
+
+            (PropertyType::Texture2d, Token::StringLiteral) => {
+                Ok(PropertyDefault::Texture2d(self.source[span].into()))
+            },
+
+            (PropertyType::Color, Token::OpenParen) => {
+                let components = self.parse_tuple(span)?;
+                match components.len() {
+                    3 => Ok(PropertyDefault::Color([components[0], components[1], components[2], 1.0])),
+                    4 => Ok(PropertyDefault::Color([components[0], components[1], components[2], components[3]])),
+                    _ => Err(self.bad_default(span)),
+                }
+            },
+
+            (PropertyType::Vector2, Token::OpenParen) => {
+                let components = self.parse_tuple(span)?;
+                match components.len() {
+                    2 => Ok(PropertyDefault::Vector2([components[0], components[1]])),
+                    _ => Err(self.bad_default(span)),
+                }
+            },
+
+            (PropertyType::Vector3, Token::OpenParen) => {
+                let components = self.parse_tuple(span)?;
+                match components.len() {
+                    3 => Ok(PropertyDefault::Vector3([components[0], components[1], components[2]])),
+                    _ => Err(self.bad_default(span)),
+                }
+            },
+
+            (PropertyType::Vector4, Token::OpenParen) => {
+                let components = self.parse_tuple(span)?;
+                match components.len() {
+                    4 => Ok(PropertyDefault::Vector4([components[0], components[1], components[2], components[3]])),
+                    _ => Err(self.bad_default(span)),
+                }
+            },
+
+            (PropertyType::Matrix4, Token::OpenParen) => {
+                let components = self.parse_tuple(span)?;
+                if components.len() != 16 {
+                    return Err(self.bad_default(span));
+                }
+
+                let mut elements = [0.0; 16];
+                elements.copy_from_slice(&components);
+                Ok(PropertyDefault::Matrix4(elements))
+            },
+
+            _ => Err(self.bad_default(span)),
+        }
+    }
+
+    /// Parses a parenthesized, comma-separated list of numbers.
+    ///
+    /// # Preconditions
+    ///
+    /// - The opening "(" was already pulled from the lexer.
+    fn parse_tuple(&mut self, start_span: Span) -> Result<Vec<f32>, Error> {
+        let mut components = Vec::new();
+        loop {
+            let (token, span) = self.lexer.next()?;
+            match token {
+                Token::NumberLiteral => components.push(self.parse_number(span)?),
+                _ => return Err(self.bad_default(Span::new(start_span.begin, span.end))),
+            }
+
+            let (token, span) = self.lexer.next()?;
+            match token {
+                Token::Comma => {},
+                Token::CloseParen => return Ok(components),
+                _ => return Err(self.bad_default(Span::new(start_span.begin, span.end))),
+            }
+        }
+    }
+
+    fn parse_number(&self, span: Span) -> Result<f32, Error> {
+        self.source[span].parse().map_err(|_| self.bad_default(span))
+    }
+
+    fn bad_default(&self, span: Span) -> Error {
+        Error::BadPropertyDefault {
+            span: span,
+            line: span.line(self.source),
+        }
+    }
+
     /// Parses a program item.
     ///
     /// # Preconditions
@@ -117,6 +232,14 @@ pub enum Error {
     ExpectedSemiColon(Span),
     BadPropertyType(Span),
     BadProgramType(Span),
+
+    /// The default value of a property couldn't be parsed, or doesn't match the property's type.
+    ///
+    /// `line` is the line of the material source the default is on, counting from 1.
+    BadPropertyDefault {
+        span: Span,
+        line: usize,
+    },
 }
 
 impl From<TokenError> for Error {
@@ -13,11 +13,16 @@ pub enum Token {
     /* Structural symbols */
     Colon,
     SemiColon,
+    Comma,
     OpenCurly,
     CloseCurly,
+    OpenParen,
+    CloseParen,
 
     /* Literal */
     ProgramLiteral,
+    NumberLiteral,
+    StringLiteral,
 
     /* Name components */
     Identifier,
@@ -43,6 +48,11 @@ impl Span {
             end: end,
         }
     }
+
+    /// Gets the line in `source` that the span starts on, counting from 1.
+    pub fn line(&self, source: &str) -> usize {
+        source[..self.begin].matches('\n').count() + 1
+    }
 }
 
 impl Index<Span> for str {
@@ -3,7 +3,7 @@
 extern crate polygon_material as material;
 
 use material::lexer::{Error as TokenError, ErrorData, Lexer};
-use material::material_source::{PropertySource, PropertyType, ProgramSource, MaterialSource, Error as MaterialSourceError};
+use material::material_source::{PropertyDefault, PropertySource, PropertyType, ProgramSource, MaterialSource, Error as MaterialSourceError};
 use material::parser::Error as ParseError;
 use material::token::*;
 
@@ -66,17 +66,21 @@ fn lex_properties() {
             PropertySource {
                 name: "surface_color".to_string(),
                 property_type: PropertyType::Color,
+            default: None,
             },
             PropertySource {
                 name: "another_thing".to_string(),
                 property_type: PropertyType::f32,
+            default: None,
             },
             PropertySource {
                 name: "some_vec".to_string(),
                 property_type: PropertyType::Vector3,
+            default: None,
             }
         ],
         programs: vec![],
+        path: None,
     });
 
     verify_lexer(SOURCE, EXPECTED_TOKENS, expected_material);
@@ -133,6 +137,7 @@ fn lex_program() {
             ProgramSource::Vertex(" foo.bar(); ".to_string()),
             ProgramSource::Fragment("\n            fn program keyworkds do_stuff() {\n                bar.foo();\n            }\n        ".to_string()),
         ],
+        path: None,
     });
 
     verify_lexer(SOURCE, EXPECTED_TOKENS, expected_material);
@@ -167,3 +172,153 @@ fn lex_program_error() {
 
     verify_lexer(SOURCE, EXPECTED_TOKENS, expected_material);
 }
+
+#[test]
+fn lex_property_defaults() {
+    static SOURCE: &'static str = r#"
+        property surface_color: Color = (1.0, 0.5, 0.25, 1.0);
+        property surface_shininess: f32 = -4;
+        property offset: Vector3 = (0.0, 1.5, 0.0);
+        property surface_diffuse: Texture2d = "textures/diffuse.bmp";
+    "#;
+
+    static EXPECTED_TOKENS: &'static [Result<(Token, &'static str), (ErrorData, &'static str)>] = &[
+        Ok((Token::Property, "property")),
+        Ok((Token::Identifier, "surface_color")),
+        Ok((Token::Colon, ":")),
+        Ok((Token::Identifier, "Color")),
+        Ok((Token::Eq, "=")),
+        Ok((Token::OpenParen, "(")),
+        Ok((Token::NumberLiteral, "1.0")),
+        Ok((Token::Comma, ",")),
+        Ok((Token::NumberLiteral, "0.5")),
+        Ok((Token::Comma, ",")),
+        Ok((Token::NumberLiteral, "0.25")),
+        Ok((Token::Comma, ",")),
+        Ok((Token::NumberLiteral, "1.0")),
+        Ok((Token::CloseParen, ")")),
+        Ok((Token::SemiColon, ";")),
+
+        Ok((Token::Property, "property")),
+        Ok((Token::Identifier, "surface_shininess")),
+        Ok((Token::Colon, ":")),
+        Ok((Token::Identifier, "f32")),
+        Ok((Token::Eq, "=")),
+        Ok((Token::NumberLiteral, "-4")),
+        Ok((Token::SemiColon, ";")),
+
+        Ok((Token::Property, "property")),
+        Ok((Token::Identifier, "offset")),
+        Ok((Token::Colon, ":")),
+        Ok((Token::Identifier, "Vector3")),
+        Ok((Token::Eq, "=")),
+        Ok((Token::OpenParen, "(")),
+        Ok((Token::NumberLiteral, "0.0")),
+        Ok((Token::Comma, ",")),
+        Ok((Token::NumberLiteral, "1.5")),
+        Ok((Token::Comma, ",")),
+        Ok((Token::NumberLiteral, "0.0")),
+        Ok((Token::CloseParen, ")")),
+        Ok((Token::SemiColon, ";")),
+
+        Ok((Token::Property, "property")),
+        Ok((Token::Identifier, "surface_diffuse")),
+        Ok((Token::Colon, ":")),
+        Ok((Token::Identifier, "Texture2d")),
+        Ok((Token::Eq, "=")),
+        Ok((Token::StringLiteral, "textures/diffuse.bmp")),
+        Ok((Token::SemiColon, ";")),
+
+        Ok((Token::EndOfFile, "")),
+    ];
+
+    let expected_material = Ok(MaterialSource {
+        properties: vec![
+            PropertySource {
+                name: "surface_color".to_string(),
+                property_type: PropertyType::Color,
+                default: Some(PropertyDefault::Color([1.0, 0.5, 0.25, 1.0])),
+            },
+            PropertySource {
+                name: "surface_shininess".to_string(),
+                property_type: PropertyType::f32,
+                default: Some(PropertyDefault::f32(-4.0)),
+            },
+            PropertySource {
+                name: "offset".to_string(),
+                property_type: PropertyType::Vector3,
+                default: Some(PropertyDefault::Vector3([0.0, 1.5, 0.0])),
+            },
+            PropertySource {
+                name: "surface_diffuse".to_string(),
+                property_type: PropertyType::Texture2d,
+                default: Some(PropertyDefault::Texture2d("textures/diffuse.bmp".to_string())),
+            },
+        ],
+        programs: vec![],
+        path: None,
+    });
+
+    verify_lexer(SOURCE, EXPECTED_TOKENS, expected_material);
+}
+
+#[test]
+fn parse_bad_property_default() {
+    static SOURCE: &'static str = "property surface_color: Color;\nproperty surface_shininess: f32 = (1.0, 2.0);\n";
+
+    let expected = Err(MaterialSourceError::ParseError(ParseError::BadPropertyDefault {
+        span: Span::new(65, 66),
+        line: 2,
+    }));
+    assert_eq!(expected, MaterialSource::from_str(SOURCE));
+
+    // A color needs three or four components.
+    let result = MaterialSource::from_str("property surface_color: Color = (1.0, 2.0);");
+    match result {
+        Err(MaterialSourceError::ParseError(ParseError::BadPropertyDefault { line: 1, .. })) => {}
+        _ => panic!("Expected bad property default, got {:?}", result),
+    }
+}
+
+#[test]
+fn parse_vector_and_matrix_properties() {
+    static SOURCE: &'static str = r#"
+        property uv_offset: Vector2 = (0.5, 0.25);
+        property tint: Vector4;
+        property uv_transform: Matrix4 = (
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0
+        );
+    "#;
+
+    let expected_material = Ok(MaterialSource {
+        properties: vec![
+            PropertySource {
+                name: "uv_offset".to_string(),
+                property_type: PropertyType::Vector2,
+                default: Some(PropertyDefault::Vector2([0.5, 0.25])),
+            },
+            PropertySource {
+                name: "tint".to_string(),
+                property_type: PropertyType::Vector4,
+                default: None,
+            },
+            PropertySource {
+                name: "uv_transform".to_string(),
+                property_type: PropertyType::Matrix4,
+                default: Some(PropertyDefault::Matrix4([
+                    1.0, 0.0, 0.0, 0.0,
+                    0.0, 1.0, 0.0, 0.0,
+                    0.0, 0.0, 1.0, 0.0,
+                    0.0, 0.0, 0.0, 1.0,
+                ])),
+            },
+        ],
+        programs: vec![],
+        path: None,
+    });
+
+    assert_eq!(expected_material, MaterialSource::from_str(SOURCE));
+}
@@ -0,0 +1,113 @@
+use matrix::Matrix4;
+use point::Point;
+use vector::Vector3;
+
+/// An axis-aligned bounding box, the box between the corners `min` and `max`.
+///
+/// Boxes are used as bounding volumes where a sphere would be too loose, e.g. for long thin
+/// meshes. Boxes that only touch count as overlapping, and points on the surface of a box are
+/// inside of it.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Aabb {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Aabb {
+    /// Creates a new box between the given corners.
+    ///
+    /// Each coordinate of `min` should be no larger than the same coordinate of `max`.
+    pub fn new(min: Point, max: Point) -> Aabb {
+        Aabb {
+            min: min,
+            max: max,
+        }
+    }
+
+    /// Creates the smallest box that contains all of `points`.
+    ///
+    /// If `points` is empty the box is the single point at the origin.
+    pub fn from_points(points: &[Point]) -> Aabb {
+        if points.is_empty() {
+            return Aabb::new(Point::origin(), Point::origin());
+        }
+
+        let mut min = Point::max();
+        let mut max = Point::min();
+        for point in points {
+            min = Point::new(min.x.min(point.x), min.y.min(point.y), min.z.min(point.z));
+            max = Point::new(max.x.max(point.x), max.y.max(point.y), max.z.max(point.z));
+        }
+        Aabb::new(min, max)
+    }
+
+    /// Gets the point in the middle of the box.
+    pub fn center(&self) -> Point {
+        Point::new(
+            (self.min.x + self.max.x) * 0.5,
+            (self.min.y + self.max.y) * 0.5,
+            (self.min.z + self.max.z) * 0.5)
+    }
+
+    /// Gets half of the size of the box along each axis, i.e. the offset from the center to
+    /// `max`.
+    pub fn extents(&self) -> Vector3 {
+        (self.max - self.min) * 0.5
+    }
+
+    /// Creates the smallest box that contains both boxes.
+    pub fn merge(&self, other: &Aabb) -> Aabb {
+        Aabb::new(
+            Point::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y), self.min.z.min(other.min.z)),
+            Point::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y), self.max.z.max(other.max.z)))
+    }
+
+    /// Grows the box by `margin` in every direction.
+    ///
+    /// A negative margin shrinks the box. Shrinking by more than half of the box's size leaves
+    /// `min` larger than `max`, and the box contains nothing.
+    pub fn expand(&self, margin: f32) -> Aabb {
+        let margin = Vector3::new(margin, margin, margin);
+        Aabb::new(self.min - margin, self.max + margin)
+    }
+
+    /// Returns `true` if `point` is inside the box or on its surface.
+    pub fn contains_point(&self, point: Point) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x
+            && point.y >= self.min.y && point.y <= self.max.y
+            && point.z >= self.min.z && point.z <= self.max.z
+    }
+
+    /// Returns `true` if the two boxes overlap.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x
+            && self.min.y <= other.max.y && self.max.y >= other.min.y
+            && self.min.z <= other.max.z && self.max.z >= other.min.z
+    }
+
+    /// Transforms the box by `transform`, giving the smallest axis-aligned box that contains the
+    /// transformed box.
+    ///
+    /// Rotated boxes are larger than the box they came from, so transforming a box many times
+    /// makes it grow. Transform the original box each time instead.
+    pub fn transformed(&self, transform: &Matrix4) -> Aabb {
+        // Arvo's method: each axis of the result starts at the translation and adds the smallest
+        // and largest contribution of each axis of the source box, which avoids transforming all
+        // eight corners.
+        let translation = transform.translation_part();
+        let mut min = [translation.x, translation.y, translation.z];
+        let mut max = min;
+        let source_min = [self.min.x, self.min.y, self.min.z];
+        let source_max = [self.max.x, self.max.y, self.max.z];
+        for row in 0..3 {
+            for col in 0..3 {
+                let first = transform[row][col] * source_min[col];
+                let second = transform[row][col] * source_max[col];
+                min[row] += first.min(second);
+                max[row] += first.max(second);
+            }
+        }
+
+        Aabb::new(Point::new(min[0], min[1], min[2]), Point::new(max[0], max[1], max[2]))
+    }
+}
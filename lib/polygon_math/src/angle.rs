@@ -0,0 +1,41 @@
+//! Angle types that keep track of their units.
+//!
+//! Functions that take an angle accept anything that converts into `Rad`, so both
+//! `Orientation::axis_angle(axis, Rad(PI))` and `Orientation::axis_angle(axis, Deg(180.0))` work,
+//! but a bare `f32` doesn't compile, since there's no way to tell which units it's in.
+
+use std::fmt::{self, Display, Formatter};
+
+use PI;
+
+/// An angle in radians.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Rad(pub f32);
+
+/// An angle in degrees.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Deg(pub f32);
+
+impl From<Deg> for Rad {
+    fn from(from: Deg) -> Rad {
+        Rad(from.0 * PI / 180.0)
+    }
+}
+
+impl From<Rad> for Deg {
+    fn from(from: Rad) -> Deg {
+        Deg(from.0 * 180.0 / PI)
+    }
+}
+
+impl Display for Rad {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{} rad", self.0)
+    }
+}
+
+impl Display for Deg {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}°", self.0)
+    }
+}
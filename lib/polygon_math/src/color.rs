@@ -1,5 +1,8 @@
+use std::fmt::{self, Display, Formatter};
 use std::slice;
 
+use {ApproxEq, Lerp};
+
 pub const RED:   Color = Color { r: 1.0, b: 0.0, g: 0.0, a: 1.0 };
 pub const WHITE: Color = Color { r: 1.0, b: 1.0, g: 1.0, a: 1.0 };
 pub const BLUE:  Color = Color { r: 0.0, b: 1.0, g: 0.0, a: 1.0 };
@@ -46,6 +49,117 @@ impl Color {
             a: 1.0,
         }
     }
+
+    /// Constructs an opaque `Color` from hue, saturation, and value.
+    ///
+    /// `hue` is in degrees around the color wheel, with red at 0, green at 120, and blue at 240.
+    /// Hues outside of `0..360` wrap around. `saturation` and `value` are in `0..1`. The
+    /// conversion works directly on the color's components, so the result is as linear as the
+    /// value passed in.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Color {
+        let hue = (hue % 360.0 + 360.0) % 360.0 / 60.0;
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+
+        let min = value - chroma;
+        Color::rgb(r + min, g + min, b + min)
+    }
+
+    /// Converts the color to hue, saturation, and value, ignoring alpha.
+    ///
+    /// The hue is in degrees in `0..360`, see `from_hsv()`. Grays have no hue, so their hue is 0,
+    /// and black has no saturation either.
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let chroma = max - min;
+
+        let hue = if chroma <= 0.0 {
+            0.0
+        } else if max == self.r {
+            60.0 * ((self.g - self.b) / chroma)
+        } else if max == self.g {
+            60.0 * ((self.b - self.r) / chroma + 2.0)
+        } else {
+            60.0 * ((self.r - self.g) / chroma + 4.0)
+        };
+        let hue = if hue < 0.0 { hue + 360.0 } else { hue };
+
+        let saturation = if max <= 0.0 { 0.0 } else { chroma / max };
+        (hue, saturation, max)
+    }
+
+    /// Constructs an opaque `Color` from a hex code in the form `0xRRGGBB`, e.g. `0xFF8800`.
+    ///
+    /// Hex codes are in sRGB, as in CSS and most color pickers, so the color is converted to
+    /// linear space. Bits above the lowest 24 are ignored.
+    pub fn from_hex(hex: u32) -> Color {
+        let channel = |shift: u32| ((hex >> shift) & 0xFF) as f32 / 255.0;
+        Color::rgb(channel(16), channel(8), channel(0)).to_linear()
+    }
+
+    /// Parses a hex code in the form `#RRGGBB` or `#RRGGBBAA`, e.g. `"#FF8800"`.
+    ///
+    /// The `#` is optional and the digits can be in either case. As with `from_hex()` the color is
+    /// converted from sRGB to linear space, but alpha is used as-is.
+    pub fn from_hex_str(hex: &str) -> Result<Color, ParseColorError> {
+        let digits = if hex.starts_with('#') { &hex[1..] } else { hex };
+
+        let length = digits.chars().count();
+        if length != 6 && length != 8 {
+            return Err(ParseColorError::InvalidLength { length: length });
+        }
+
+        // Alpha defaults to opaque when there are only 6 digits.
+        let mut values = [0, 0, 0, 0xFF];
+        for (index, character) in digits.chars().enumerate() {
+            let digit = match character.to_digit(16) {
+                Some(digit) => digit as u8,
+                None => return Err(ParseColorError::InvalidDigit { character: character }),
+            };
+            values[index / 2] = if index % 2 == 0 { digit << 4 } else { values[index / 2] | digit };
+        }
+
+        let [r, g, b, a] = values;
+        let srgb = Color::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a as f32 / 255.0);
+        Ok(srgb.to_linear())
+    }
+
+    /// Converts the color from sRGB to linear space, treating its components as sRGB encoded.
+    ///
+    /// Alpha isn't affected.
+    pub fn to_linear(&self) -> Color {
+        let convert = |component: f32| {
+            if component <= 0.04045 {
+                component / 12.92
+            } else {
+                ((component + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        Color::new(convert(self.r), convert(self.g), convert(self.b), self.a)
+    }
+
+    /// Converts the color from linear space to sRGB, e.g. to show it in a color picker.
+    ///
+    /// This is the inverse of `to_linear()`. Alpha isn't affected.
+    pub fn to_srgb(&self) -> Color {
+        let convert = |component: f32| {
+            if component <= 0.0031308 {
+                component * 12.92
+            } else {
+                1.055 * component.powf(1.0 / 2.4) - 0.055
+            }
+        };
+        Color::new(convert(self.r), convert(self.g), convert(self.b), self.a)
+    }
 }
 
 impl Default for Color {
@@ -59,6 +173,29 @@ impl Default for Color {
     }
 }
 
+impl ApproxEq for Color {
+    fn approx_eq(self, other: Color, epsilon: f32) -> bool {
+        self.r.approx_eq(other.r, epsilon)
+            && self.g.approx_eq(other.g, epsilon)
+            && self.b.approx_eq(other.b, epsilon)
+            && self.a.approx_eq(other.a, epsilon)
+    }
+}
+
+impl Lerp for Color {
+    /// Interpolates each component of the color, including alpha.
+    ///
+    /// Colors are in linear space, so blending is physically correct, e.g. halfway between black
+    /// and white is half as bright as white.
+    fn lerp(t: f32, from: Color, to: Color) -> Color {
+        Color::new(
+            Lerp::lerp(t, from.r, to.r),
+            Lerp::lerp(t, from.g, to.g),
+            Lerp::lerp(t, from.b, to.b),
+            Lerp::lerp(t, from.a, to.a))
+    }
+}
+
 impl From<[f32; 3]> for Color {
     fn from(from: [f32; 3]) -> Color {
         let [r, g, b] = from;
@@ -141,3 +278,28 @@ impl AsRef<[f32]> for Color {
         unsafe { slice::from_raw_parts(ptr, 4) }
     }
 }
+
+/// An error that occurred while parsing a hex color code, see `Color::from_hex_str()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseColorError {
+    /// The hex code doesn't have 6 or 8 digits.
+    InvalidLength {
+        length: usize,
+    },
+
+    /// The hex code contains a character that isn't a hex digit.
+    InvalidDigit {
+        character: char,
+    },
+}
+
+impl Display for ParseColorError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            ParseColorError::InvalidLength { length } =>
+                write!(formatter, "Hex color has {} digits, expected 6 or 8", length),
+            ParseColorError::InvalidDigit { character } =>
+                write!(formatter, "Hex color contains {:?}, which is not a hex digit", character),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use aabb::Aabb;
+use matrix::Matrix4;
+use plane::Plane;
+use point::Point;
+use sphere::Sphere;
+use vector::Vector3;
+
+/// How much of a volume is inside of a frustum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Containment {
+    /// The volume is entirely inside of the frustum.
+    Inside,
+
+    /// The volume is partly inside of the frustum, or might be.
+    ///
+    /// The tests are conservative, so volumes near the corners of the frustum can be reported
+    /// as intersecting when they're just outside of it.
+    Intersecting,
+
+    /// The volume is entirely outside of the frustum.
+    Outside,
+}
+
+/// The volume that a camera can see, bounded by six planes.
+///
+/// Each plane faces into the frustum, so points in front of all of the planes are inside of it.
+/// This is mostly used for culling objects that aren't visible.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    /// The planes bounding the frustum, in the order left, right, bottom, top, near, far.
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extracts the frustum from a projection matrix, or from a combined view and projection
+    /// matrix.
+    ///
+    /// The frustum is in the space the matrix transforms from, so passing just the projection
+    /// matrix gives the frustum in camera space and passing `projection * view` gives it in world
+    /// space. Works for both perspective and orthographic projections.
+    pub fn from_matrix(matrix: &Matrix4) -> Frustum {
+        // Gribb and Hartmann: a point is inside the clip volume when `-w <= x <= w` and so on, and
+        // each of those comparisons is a plane made of the sum or difference of two of the
+        // matrix's rows.
+        let plane = |row: usize, sign: f32| {
+            let a = matrix[3][0] + sign * matrix[row][0];
+            let b = matrix[3][1] + sign * matrix[row][1];
+            let c = matrix[3][2] + sign * matrix[row][2];
+            let d = matrix[3][3] + sign * matrix[row][3];
+
+            let normal = Vector3::new(a, b, c);
+            let magnitude = normal.magnitude();
+            Plane::new(normal / magnitude, -d / magnitude)
+        };
+
+        Frustum {
+            planes: [
+                plane(0, 1.0),
+                plane(0, -1.0),
+                plane(1, 1.0),
+                plane(1, -1.0),
+                plane(2, 1.0),
+                plane(2, -1.0),
+            ],
+        }
+    }
+
+    /// Returns `true` if `point` is inside the frustum or on its surface.
+    pub fn contains_point(&self, point: Point) -> bool {
+        self.planes.iter().all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+
+    /// Checks how much of `sphere` is inside the frustum.
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> Containment {
+        let mut containment = Containment::Inside;
+        for plane in &self.planes {
+            let distance = plane.signed_distance(sphere.center);
+            if distance < -sphere.radius {
+                return Containment::Outside;
+            }
+
+            if distance < sphere.radius {
+                containment = Containment::Intersecting;
+            }
+        }
+
+        containment
+    }
+
+    /// Checks how much of `aabb` is inside the frustum.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> Containment {
+        let mut containment = Containment::Inside;
+        for plane in &self.planes {
+            // The corner furthest along the plane's normal is the last to leave the frustum
+            // through this plane, and the opposite corner is the first.
+            let normal = plane.normal;
+            let positive_vertex = Point::new(
+                if normal.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+                if normal.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+                if normal.z >= 0.0 { aabb.max.z } else { aabb.min.z });
+            let negative_vertex = Point::new(
+                if normal.x >= 0.0 { aabb.min.x } else { aabb.max.x },
+                if normal.y >= 0.0 { aabb.min.y } else { aabb.max.y },
+                if normal.z >= 0.0 { aabb.min.z } else { aabb.max.z });
+
+            if plane.signed_distance(positive_vertex) < 0.0 {
+                return Containment::Outside;
+            }
+
+            if plane.signed_distance(negative_vertex) < 0.0 {
+                containment = Containment::Intersecting;
+            }
+        }
+
+        containment
+    }
+}
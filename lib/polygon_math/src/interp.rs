@@ -0,0 +1,196 @@
+//! Interpolation curves for animating values, e.g. moving a camera or fading in UI.
+//!
+//! The easing functions take a `t` in `0..1` and return the eased value, also in `0..1`, which
+//! can then be passed to `Lerp::lerp()`. Values of `t` outside of `0..1` are clamped.
+
+use Clamp;
+use point::Point;
+use vector::Vector3;
+
+/// Smoothly interpolates from 0 to 1 as `x` goes from `edge0` to `edge1`.
+///
+/// Matches GLSL's `smoothstep()`: the result is 0 below `edge0`, 1 above `edge1`, and follows
+/// the cubic `3t² - 2t³` in between, so the slope is zero at both edges.
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = Clamp::clamp((x - edge0) / (edge1 - edge0), 0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Like `smoothstep()` but using Ken Perlin's quintic `6t⁵ - 15t⁴ + 10t³`, which also has zero
+/// second derivative at both edges.
+pub fn smootherstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = Clamp::clamp((x - edge0) / (edge1 - edge0), 0.0, 1.0);
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+/// Starts slowly and accelerates.
+pub fn ease_in_quad(t: f32) -> f32 {
+    let t = Clamp::clamp(t, 0.0, 1.0);
+    t * t
+}
+
+/// Starts quickly and decelerates.
+pub fn ease_out_quad(t: f32) -> f32 {
+    let t = Clamp::clamp(t, 0.0, 1.0);
+    t * (2.0 - t)
+}
+
+/// Accelerates until halfway, then decelerates.
+pub fn ease_in_out_quad(t: f32) -> f32 {
+    let t = Clamp::clamp(t, 0.0, 1.0);
+    if t < 0.5 {
+        2.0 * t * t
+    } else {
+        let from_end = 1.0 - t;
+        1.0 - 2.0 * from_end * from_end
+    }
+}
+
+/// Like `ease_in_quad()` but with a sharper acceleration.
+pub fn ease_in_cubic(t: f32) -> f32 {
+    let t = Clamp::clamp(t, 0.0, 1.0);
+    t * t * t
+}
+
+/// Like `ease_out_quad()` but with a sharper deceleration.
+pub fn ease_out_cubic(t: f32) -> f32 {
+    let from_end = 1.0 - Clamp::clamp(t, 0.0, 1.0);
+    1.0 - from_end * from_end * from_end
+}
+
+/// Like `ease_in_out_quad()` but with a sharper acceleration and deceleration.
+pub fn ease_in_out_cubic(t: f32) -> f32 {
+    let t = Clamp::clamp(t, 0.0, 1.0);
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        let from_end = 1.0 - t;
+        1.0 - 4.0 * from_end * from_end * from_end
+    }
+}
+
+/// Values that can be interpolated along a Catmull-Rom spline, see `catmull_rom()`.
+pub trait CatmullRom {
+    fn catmull_rom(p0: Self, p1: Self, p2: Self, p3: Self, t: f32) -> Self;
+}
+
+/// Interpolates between `p1` and `p2` along a uniform Catmull-Rom spline.
+///
+/// `p0` and `p3` are the neighbouring control points, which set the tangent at `p1` to
+/// `(p2 - p0) / 2` and at `p2` to `(p3 - p1) / 2`. Consecutive segments share a tangent at the
+/// point they meet, so a curve made of several segments has no kinks. Returns `p1` when `t` is 0
+/// and `p2` when `t` is 1.
+pub fn catmull_rom<T: CatmullRom>(p0: T, p1: T, p2: T, p3: T, t: f32) -> T {
+    T::catmull_rom(p0, p1, p2, p3, t)
+}
+
+impl CatmullRom for f32 {
+    fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+        let t2 = t * t;
+        let t3 = t2 * t;
+        0.5 * (
+            2.0 * p1
+          + (p2 - p0) * t
+          + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+          + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3
+        )
+    }
+}
+
+impl CatmullRom for Vector3 {
+    fn catmull_rom(p0: Vector3, p1: Vector3, p2: Vector3, p3: Vector3, t: f32) -> Vector3 {
+        Vector3::new(
+            catmull_rom(p0.x, p1.x, p2.x, p3.x, t),
+            catmull_rom(p0.y, p1.y, p2.y, p3.y, t),
+            catmull_rom(p0.z, p1.z, p2.z, p3.z, t),
+        )
+    }
+}
+
+impl CatmullRom for Point {
+    fn catmull_rom(p0: Point, p1: Point, p2: Point, p3: Point, t: f32) -> Point {
+        Point::new(
+            catmull_rom(p0.x, p1.x, p2.x, p3.x, t),
+            catmull_rom(p0.y, p1.y, p2.y, p3.y, t),
+            catmull_rom(p0.z, p1.z, p2.z, p3.z, t),
+        )
+    }
+}
+
+/// A smooth curve through a list of points, e.g. for a camera path.
+///
+/// The curve is made of Catmull-Rom segments between each pair of consecutive points, with the
+/// first and last points repeated as the missing neighbours at the ends. `sample()` spreads `t`
+/// over the segments by their chord length, i.e. the straight-line distance between their
+/// points, so long segments take up more of the curve than short ones. Within a segment the
+/// speed still varies a bit, which is fine for camera paths but means the curve isn't exactly
+/// parameterized by arc length.
+#[derive(Debug, Clone)]
+pub struct Spline {
+    points: Vec<Point>,
+
+    /// The chord length from the first point to each point.
+    distances: Vec<f32>,
+}
+
+impl Spline {
+    /// Creates a spline that passes through each of `points` in order.
+    ///
+    /// # Panics
+    ///
+    /// - If `points` is empty.
+    pub fn new(points: Vec<Point>) -> Spline {
+        assert!(!points.is_empty(), "Spline needs at least one point");
+
+        let mut distances = Vec::with_capacity(points.len());
+        let mut total = 0.0;
+        distances.push(total);
+        for pair in points.windows(2) {
+            total += pair[0].distance(&pair[1]);
+            distances.push(total);
+        }
+
+        Spline {
+            points: points,
+            distances: distances,
+        }
+    }
+
+    /// Gets the points that the spline passes through.
+    pub fn points(&self) -> &[Point] {
+        &*self.points
+    }
+
+    /// Gets the total chord length of the spline, which is a little shorter than the curve.
+    pub fn length(&self) -> f32 {
+        *self.distances.last().unwrap()
+    }
+
+    /// Gets the point on the spline at `t`, where 0 is the first point and 1 is the last point.
+    ///
+    /// Values of `t` outside of `0..1` are clamped.
+    pub fn sample(&self, t: f32) -> Point {
+        let last = self.points.len() - 1;
+        if last == 0 {
+            return self.points[0];
+        }
+
+        // Find the first segment that ends past `distance`.
+        let distance = Clamp::clamp(t, 0.0, 1.0) * self.length();
+        let segment = self.distances[1..]
+            .iter()
+            .position(|&end| end >= distance)
+            .unwrap_or(last - 1);
+
+        // Repeated points make segments with no length, which would otherwise divide by zero.
+        let start = self.distances[segment];
+        let segment_length = self.distances[segment + 1] - start;
+        let local_t = if segment_length > 0.0 { (distance - start) / segment_length } else { 0.0 };
+
+        let p0 = self.points[segment.saturating_sub(1)];
+        let p1 = self.points[segment];
+        let p2 = self.points[segment + 1];
+        let p3 = self.points[(segment + 2).min(last)];
+        catmull_rom(p0, p1, p2, p3, local_t)
+    }
+}
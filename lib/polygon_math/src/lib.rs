@@ -2,20 +2,39 @@
 #![feature(slice_patterns)]
 #![cfg_attr(test, feature(test))]
 
+// NOTE: This is a "standard" workaround for Rust's nasty macro visibility rules. Once the new
+// macro system arrives this can be removed.
+#[macro_use]
+mod macros;
+
+pub mod aabb;
+pub mod angle;
 pub mod color;
+pub mod frustum;
+pub mod interp;
 pub mod matrix;
 pub mod orientation;
+pub mod plane;
 pub mod point;
 pub mod quaternion;
+pub mod ray;
+pub mod sphere;
 pub mod vector;
 
 #[cfg(test)]
 mod test;
 
+pub use aabb::Aabb;
+pub use angle::{Deg, Rad};
 pub use color::Color;
+pub use frustum::{Containment, Frustum};
+pub use interp::Spline;
 pub use matrix::{Matrix3, Matrix4};
 pub use orientation::Orientation;
+pub use plane::Plane;
 pub use point::Point;
+pub use ray::Ray;
+pub use sphere::Sphere;
 pub use std::f32::consts::PI;
 pub use vector::{Vector2, Vector3};
 
@@ -30,10 +49,37 @@ pub trait IsZero {
 
 impl IsZero for f32 {
     fn is_zero(self) -> bool {
-        self.abs() < EPSILON
+        self.approx_eq(0.0, EPSILON)
     }
 }
 
+/// Approximate equality for floating point values, which rarely come out exactly equal after a
+/// few calculations.
+///
+/// Values are compared component-wise. Each component is allowed to differ by `epsilon` when it's
+/// small, and by `epsilon` relative to its magnitude when it's larger than 1, so that large values
+/// aren't held to a precision that `f32` can't represent. `EPSILON` is a reasonable default.
+pub trait ApproxEq {
+    fn approx_eq(self, other: Self, epsilon: f32) -> bool;
+}
+
+impl ApproxEq for f32 {
+    fn approx_eq(self, other: f32, epsilon: f32) -> bool {
+        // Infinities are only equal to themselves, otherwise the scale would be infinite too.
+        if self.is_infinite() || other.is_infinite() {
+            return self == other;
+        }
+
+        let scale = f32::max(1.0, f32::max(self.abs(), other.abs()));
+        (self - other).abs() <= epsilon * scale
+    }
+}
+
+/// Checks if `a` and `b` are approximately equal, see `ApproxEq`.
+pub fn approx_eq<T: ApproxEq>(a: T, b: T, epsilon: f32) -> bool {
+    a.approx_eq(b, epsilon)
+}
+
 pub trait Clamp {
     fn clamp(self, min: Self, max: Self) -> Self;
 }
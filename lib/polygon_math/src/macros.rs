@@ -0,0 +1,22 @@
+/// Asserts that two values are approximately equal, see `ApproxEq`.
+///
+/// The epsilon defaults to `EPSILON`, a different one can be passed as a third argument.
+#[macro_export]
+macro_rules! assert_approx_eq {
+    ($left: expr, $right: expr) => {
+        assert_approx_eq!($left, $right, $crate::EPSILON)
+    };
+    ($left: expr, $right: expr, $epsilon: expr) => {
+        match (&$left, &$right, $epsilon) {
+            (left, right, epsilon) => {
+                if !$crate::approx_eq(*left, *right, epsilon) {
+                    panic!(
+                        "assertion failed: `left ≈ right` (epsilon: {})\n  left: `{:?}`\n right: `{:?}`",
+                        epsilon,
+                        left,
+                        right);
+                }
+            }
+        }
+    };
+}
@@ -2,11 +2,12 @@ use std::ops::{Index, IndexMut, Mul};
 use std::fmt::{Debug, Formatter, Error};
 use std::cmp::PartialEq;
 
+use angle::Rad;
 use vector::Vector3;
 use orientation::Orientation;
 use point::Point;
 use quaternion::Quaternion;
-use super::{IsZero, Dot};
+use super::{ApproxEq, EPSILON, Dot};
 
 /// A 4x4 matrix that can be used to represent a combination of translation, rotation, and scale.
 ///
@@ -59,7 +60,7 @@ impl Matrix4 {
     /// Details
     /// -------
     ///
-    /// The resulting matrix will have the rotations applied in the order x -> y -> z.
+    /// The rotations are combined as `Rx * Ry * Rz`, the same as `EulerOrder::Xyz`.
     pub fn rotation(x: f32, y: f32, z: f32) -> Matrix4 {
         let s1 = x.sin();
         let c1 = x.cos();
@@ -81,6 +82,29 @@ impl Matrix4 {
         q.into()
     }
 
+    /// Converts the rotation part of the matrix to a quaternion.
+    ///
+    /// See `Quaternion::from_matrix()` for details.
+    pub fn to_quaternion(&self) -> Quaternion {
+        Quaternion::from_matrix(self)
+    }
+
+    /// Creates a transform that scales by `scale`, then rotates by `rotation`, then translates to
+    /// `position`.
+    ///
+    /// This is the same as `from_point(position) * rotation_matrix * from_scale_vector(scale)`,
+    /// but builds the matrix directly instead of multiplying three matrices together.
+    pub fn trs(position: Point, rotation: Quaternion, scale: Vector3) -> Matrix4 {
+        let Quaternion { v: Vector3 { x, y, z }, w } = rotation;
+
+        Matrix4([
+            [(w*w + x*x - y*y - z*z) * scale.x, (2.0*x*y - 2.0*w*z) * scale.y,     (2.0*x*z + 2.0*w*y) * scale.z,     position.x],
+            [(2.0*x*y + 2.0*w*z) * scale.x,     (w*w - x*x + y*y - z*z) * scale.y, (2.0*y*z - 2.0*w*x) * scale.z,     position.y],
+            [(2.0*x*z - 2.0*w*y) * scale.x,     (2.0*y*z + 2.0*w*x) * scale.y,     (w*w - x*x - y*y + z*z) * scale.z, position.z],
+            [0.0,                               0.0,                               0.0,                               1.0],
+        ])
+    }
+
     pub fn from_matrix3(other: Matrix3) -> Matrix4 {
         Matrix4([
             [other[0][0], other[0][1], other[0][2], 0.0],
@@ -100,6 +124,48 @@ impl Matrix4 {
         ])
     }
 
+    /// Creates a new orthographic projection matrix.
+    ///
+    /// The resulting matrix maps the box bounded by `left`, `right`, `bottom`, `top`, and the
+    /// planes at distances `near` and `far` along the negative z axis to the unit cube, without
+    /// any perspective foreshortening. As in OpenGL, depth goes from -1 at the near plane to 1 at
+    /// the far plane.
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Matrix4 {
+        Matrix4([
+            [2.0 / (right - left), 0.0,                  0.0,                -(right + left) / (right - left)],
+            [0.0,                  2.0 / (top - bottom), 0.0,                -(top + bottom) / (top - bottom)],
+            [0.0,                  0.0,                  -2.0 / (far - near), -(far + near) / (far - near)],
+            [0.0,                  0.0,                  0.0,                1.0],
+        ])
+    }
+
+    /// Creates a new perspective projection matrix with the given vertical field of view.
+    ///
+    /// `aspect_ratio` is the width of the view divided by its height. The view is centered on the
+    /// negative z axis, see `perspective_off_center()` for the details of the projection.
+    pub fn perspective<A: Into<Rad>>(fov: A, aspect_ratio: f32, near: f32, far: f32) -> Matrix4 {
+        let Rad(fov) = fov.into();
+        let top = near * (fov * 0.5).tan();
+        let right = aspect_ratio * top;
+        Matrix4::perspective_off_center(-right, right, -top, top, near, far)
+    }
+
+    /// Creates a new perspective projection matrix for a frustum that isn't necessarily centered
+    /// on the view direction, as needed for portals or for each eye of a VR headset.
+    ///
+    /// `left`, `right`, `bottom`, and `top` are the edges of the view on the near plane, which is
+    /// at distance `near` along the negative z axis. The resulting matrix maps the frustum to the
+    /// unit cube once x, y, and z are divided by w. As in OpenGL, depth goes from -1 at the near
+    /// plane to 1 at the far plane.
+    pub fn perspective_off_center(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Matrix4 {
+        Matrix4([
+            [2.0 * near / (right - left), 0.0,                         (right + left) / (right - left), 0.0],
+            [0.0,                         2.0 * near / (top - bottom), (top + bottom) / (top - bottom), 0.0],
+            [0.0,                         0.0,                         -(far + near) / (far - near),    -2.0 * far * near / (far - near)],
+            [0.0,                         0.0,                         -1.0,                            0.0],
+        ])
+    }
+
     pub fn from_scale_vector(scale: Vector3) -> Matrix4 {
         Matrix4([
             [scale.x, 0.0,     0.0,     0.0],
@@ -122,6 +188,100 @@ impl Matrix4 {
         transpose
     }
 
+    /// Calculates the determinant of the matrix.
+    pub fn determinant(&self) -> f32 {
+        let (s, c) = self.sub_determinants();
+        s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+    }
+
+    /// Calculates the inverse of the matrix.
+    ///
+    /// Returns `None` if the matrix isn't invertible, e.g. if it scales an axis to zero. Matrices
+    /// made of translation, rotation, and scale take a faster path that only inverts the upper
+    /// 3x3 part, but any matrix can be inverted, including projection matrices.
+    pub fn inverse(&self) -> Option<Matrix4> {
+        let m = self;
+        if m[3] == [0.0, 0.0, 0.0, 1.0] {
+            // The inverse of an affine transform undoes the translation, then the linear part.
+            let linear = match Matrix3::from(*self).inverse() {
+                Some(linear) => linear,
+                None => return None,
+            };
+            let translation = -(Vector3::from(self.translation_part()) * linear);
+
+            let mut inverse = Matrix4::from_matrix3(linear);
+            inverse[0][3] = translation.x;
+            inverse[1][3] = translation.y;
+            inverse[2][3] = translation.z;
+            return Some(inverse);
+        }
+
+        // The inverse is the transpose of the matrix of cofactors divided by the determinant. Each
+        // cofactor is built from the determinants of 2x2 blocks of the top and bottom two rows.
+        let (s, c) = self.sub_determinants();
+        let determinant = s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0];
+        let row_lengths = (0..4)
+            .map(|row| (0..4).map(|col| m[row][col] * m[row][col]).sum::<f32>().sqrt())
+            .product();
+        let column_lengths = (0..4)
+            .map(|col| (0..4).map(|row| m[row][col] * m[row][col]).sum::<f32>().sqrt())
+            .product();
+        if is_singular(determinant, row_lengths, column_lengths) {
+            return None;
+        }
+
+        let inv_det = 1.0 / determinant;
+        Some(Matrix4([
+            [
+                ( m[1][1] * c[5] - m[1][2] * c[4] + m[1][3] * c[3]) * inv_det,
+                (-m[0][1] * c[5] + m[0][2] * c[4] - m[0][3] * c[3]) * inv_det,
+                ( m[3][1] * s[5] - m[3][2] * s[4] + m[3][3] * s[3]) * inv_det,
+                (-m[2][1] * s[5] + m[2][2] * s[4] - m[2][3] * s[3]) * inv_det,
+            ],
+            [
+                (-m[1][0] * c[5] + m[1][2] * c[2] - m[1][3] * c[1]) * inv_det,
+                ( m[0][0] * c[5] - m[0][2] * c[2] + m[0][3] * c[1]) * inv_det,
+                (-m[3][0] * s[5] + m[3][2] * s[2] - m[3][3] * s[1]) * inv_det,
+                ( m[2][0] * s[5] - m[2][2] * s[2] + m[2][3] * s[1]) * inv_det,
+            ],
+            [
+                ( m[1][0] * c[4] - m[1][1] * c[2] + m[1][3] * c[0]) * inv_det,
+                (-m[0][0] * c[4] + m[0][1] * c[2] - m[0][3] * c[0]) * inv_det,
+                ( m[3][0] * s[4] - m[3][1] * s[2] + m[3][3] * s[0]) * inv_det,
+                (-m[2][0] * s[4] + m[2][1] * s[2] - m[2][3] * s[0]) * inv_det,
+            ],
+            [
+                (-m[1][0] * c[3] + m[1][1] * c[1] - m[1][2] * c[0]) * inv_det,
+                ( m[0][0] * c[3] - m[0][1] * c[1] + m[0][2] * c[0]) * inv_det,
+                (-m[3][0] * s[3] + m[3][1] * s[1] - m[3][2] * s[0]) * inv_det,
+                ( m[2][0] * s[3] - m[2][1] * s[1] + m[2][2] * s[0]) * inv_det,
+            ],
+        ]))
+    }
+
+    /// Calculates the determinants of the 2x2 blocks in the top two rows and in the bottom two
+    /// rows, which the determinant and inverse are built from.
+    fn sub_determinants(&self) -> ([f32; 6], [f32; 6]) {
+        let m = self;
+        let top = [
+            m[0][0] * m[1][1] - m[1][0] * m[0][1],
+            m[0][0] * m[1][2] - m[1][0] * m[0][2],
+            m[0][0] * m[1][3] - m[1][0] * m[0][3],
+            m[0][1] * m[1][2] - m[1][1] * m[0][2],
+            m[0][1] * m[1][3] - m[1][1] * m[0][3],
+            m[0][2] * m[1][3] - m[1][2] * m[0][3],
+        ];
+        let bottom = [
+            m[2][0] * m[3][1] - m[3][0] * m[2][1],
+            m[2][0] * m[3][2] - m[3][0] * m[2][2],
+            m[2][0] * m[3][3] - m[3][0] * m[2][3],
+            m[2][1] * m[3][2] - m[3][1] * m[2][2],
+            m[2][1] * m[3][3] - m[3][1] * m[2][3],
+            m[2][2] * m[3][3] - m[3][2] * m[2][3],
+        ];
+        (top, bottom)
+    }
+
     pub fn x_part(&self) -> Vector3 {
         Vector3::new(self[0][0], self[1][0], self[2][0])
     }
@@ -138,6 +298,58 @@ impl Matrix4 {
         Point::new(self[0][3], self[1][3], self[2][3])
     }
 
+    /// Gets the scale along each of the matrix's axes.
+    ///
+    /// If the matrix mirrors the object, i.e. it has a negative determinant, the x scale is
+    /// negative so that `rotation_part()` is still a rotation.
+    pub fn scale_part(&self) -> Vector3 {
+        let scale = Vector3::new(self.x_part().magnitude(), self.y_part().magnitude(), self.z_part().magnitude());
+        if Matrix3::from(*self).determinant() < 0.0 {
+            scale.set_x(-scale.x)
+        } else {
+            scale
+        }
+    }
+
+    /// Gets the rotation of the matrix, with its scale removed.
+    ///
+    /// The matrix must be made of translation, rotation, and scale with no axis scaled to zero,
+    /// otherwise the result is meaningless. Use `decompose()` when that's not known.
+    pub fn rotation_part(&self) -> Quaternion {
+        let scale = self.scale_part();
+        Quaternion::from_basis(self.x_part() / scale.x, self.y_part() / scale.y, self.z_part() / scale.z)
+    }
+
+    /// Splits the matrix into the position, rotation, and scale that `trs()` would build it from.
+    ///
+    /// Returns `None` if the matrix can't be built that way, i.e. if it's a projection, if it
+    /// shears the object, or if it scales an axis to zero.
+    pub fn decompose(&self) -> Option<(Point, Quaternion, Vector3)> {
+        if self[3] != [0.0, 0.0, 0.0, 1.0] {
+            return None;
+        }
+
+        let scale = self.scale_part();
+        if scale.x.approx_eq(0.0, EPSILON) || scale.y.approx_eq(0.0, EPSILON) || scale.z.approx_eq(0.0, EPSILON) {
+            return None;
+        }
+
+        // Without shear the axes are still perpendicular once the scale is removed.
+        let x_axis = self.x_part() / scale.x;
+        let y_axis = self.y_part() / scale.y;
+        let z_axis = self.z_part() / scale.z;
+        let tolerance = 1e-4;
+        if x_axis.dot(y_axis).abs() > tolerance
+            || y_axis.dot(z_axis).abs() > tolerance
+            || z_axis.dot(x_axis).abs() > tolerance
+        {
+            return None;
+        }
+
+        let rotation = Quaternion::from_basis(x_axis, y_axis, z_axis);
+        Some((self.translation_part(), rotation, scale))
+    }
+
     /// Get the matrix data as a raw array.
     pub fn raw_data(&self) -> &[f32; 16] {
         // It's safe to transmute a pointer to data to a &[f32; 16]
@@ -158,8 +370,14 @@ impl From<Matrix3> for Matrix4 {
 }
 
 impl From<Orientation> for Matrix4 {
-    fn from(q: Orientation) -> Matrix4 {
-        let Orientation(Quaternion { v: Vector3 { x, y, z }, w }) = q;
+    fn from(orientation: Orientation) -> Matrix4 {
+        Matrix4::from(orientation.0)
+    }
+}
+
+impl From<Quaternion> for Matrix4 {
+    fn from(q: Quaternion) -> Matrix4 {
+        let Quaternion { v: Vector3 { x, y, z }, w } = q;
 
         Matrix4([
             [(w*w + x*x - y*y - z*z), (2.0*x*y - 2.0*w*z),     (2.0*x*z + 2.0*w*y),     0.0],
@@ -170,21 +388,18 @@ impl From<Orientation> for Matrix4 {
     }
 }
 
-impl PartialEq for Matrix4 {
-    fn ne(&self, other: &Matrix4) -> bool {
-        let our_data = self.raw_data();
-        let their_data = other.raw_data();
-        for (ours, theirs) in our_data.iter().zip(their_data.iter()) {
-            if !(ours - theirs).is_zero() {
-                return true;
-            }
-        }
-
-        false
+impl ApproxEq for Matrix4 {
+    fn approx_eq(self, other: Matrix4, epsilon: f32) -> bool {
+        self.raw_data()
+            .iter()
+            .zip(other.raw_data().iter())
+            .all(|(&ours, &theirs)| ours.approx_eq(theirs, epsilon))
     }
+}
 
+impl PartialEq for Matrix4 {
     fn eq(&self, other: &Matrix4) -> bool {
-        !(self != other)
+        self.approx_eq(*other, EPSILON)
     }
 }
 
@@ -316,7 +531,7 @@ impl Matrix3 {
     /// Details
     /// -------
     ///
-    /// The resulting matrix will have the rotations applied in the order x -> y -> z.
+    /// The rotations are combined as `Rx * Ry * Rz`, the same as `EulerOrder::Xyz`.
     pub fn rotation(x: f32, y: f32, z: f32) -> Matrix3 {
         let s1 = x.sin();
         let c1 = x.cos();
@@ -353,6 +568,50 @@ impl Matrix3 {
         transpose
     }
 
+    /// Calculates the determinant of the matrix.
+    pub fn determinant(&self) -> f32 {
+        self[0][0] * (self[1][1] * self[2][2] - self[1][2] * self[2][1])
+      - self[0][1] * (self[1][0] * self[2][2] - self[1][2] * self[2][0])
+      + self[0][2] * (self[1][0] * self[2][1] - self[1][1] * self[2][0])
+    }
+
+    /// Calculates the inverse of the matrix.
+    ///
+    /// Returns `None` if the matrix isn't invertible, e.g. if it scales an axis to zero.
+    pub fn inverse(&self) -> Option<Matrix3> {
+        let m = self;
+        let determinant = self.determinant();
+        let row_lengths = (0..3)
+            .map(|row| (0..3).map(|col| m[row][col] * m[row][col]).sum::<f32>().sqrt())
+            .product();
+        let column_lengths = (0..3)
+            .map(|col| (0..3).map(|row| m[row][col] * m[row][col]).sum::<f32>().sqrt())
+            .product();
+        if is_singular(determinant, row_lengths, column_lengths) {
+            return None;
+        }
+
+        // The inverse is the transpose of the matrix of cofactors divided by the determinant.
+        let inv_det = 1.0 / determinant;
+        Some(Matrix3([
+            [
+                (m[1][1] * m[2][2] - m[1][2] * m[2][1]) * inv_det,
+                (m[0][2] * m[2][1] - m[0][1] * m[2][2]) * inv_det,
+                (m[0][1] * m[1][2] - m[0][2] * m[1][1]) * inv_det,
+            ],
+            [
+                (m[1][2] * m[2][0] - m[1][0] * m[2][2]) * inv_det,
+                (m[0][0] * m[2][2] - m[0][2] * m[2][0]) * inv_det,
+                (m[0][2] * m[1][0] - m[0][0] * m[1][2]) * inv_det,
+            ],
+            [
+                (m[1][0] * m[2][1] - m[1][1] * m[2][0]) * inv_det,
+                (m[0][1] * m[2][0] - m[0][0] * m[2][1]) * inv_det,
+                (m[0][0] * m[1][1] - m[0][1] * m[1][0]) * inv_det,
+            ],
+        ]))
+    }
+
     pub fn as_matrix4(&self) -> Matrix4 {
         Matrix4::from_matrix3(*self)
     }
@@ -390,8 +649,14 @@ impl From<Matrix4> for Matrix3 {
 }
 
 impl From<Orientation> for Matrix3 {
-    fn from(q: Orientation) -> Matrix3 {
-        let Quaternion { v: Vector3 { x, y, z }, w } = q.into();
+    fn from(orientation: Orientation) -> Matrix3 {
+        Matrix3::from(orientation.0)
+    }
+}
+
+impl From<Quaternion> for Matrix3 {
+    fn from(q: Quaternion) -> Matrix3 {
+        let Quaternion { v: Vector3 { x, y, z }, w } = q;
 
         Matrix3([
             [(w*w + x*x - y*y - z*z), (2.0*x*y - 2.0*w*z),     (2.0*x*z + 2.0*w*y)    ],
@@ -401,6 +666,21 @@ impl From<Orientation> for Matrix3 {
     }
 }
 
+impl ApproxEq for Matrix3 {
+    fn approx_eq(self, other: Matrix3, epsilon: f32) -> bool {
+        self.raw_data()
+            .iter()
+            .zip(other.raw_data().iter())
+            .all(|(&ours, &theirs)| ours.approx_eq(theirs, epsilon))
+    }
+}
+
+impl PartialEq for Matrix3 {
+    fn eq(&self, other: &Matrix3) -> bool {
+        self.approx_eq(*other, EPSILON)
+    }
+}
+
 impl Index<usize> for Matrix3 {
     type Output = [f32; 3];
 
@@ -450,6 +730,17 @@ impl Mul<Matrix3> for Point {
     }
 }
 
+impl Mul<Vector3> for Matrix3 {
+    type Output = Vector3;
+
+    /// Transforms `rhs` by the matrix.
+    ///
+    /// This is the same as `rhs * self`, the vector is treated as a column vector either way.
+    fn mul(self, rhs: Vector3) -> Vector3 {
+        rhs * self
+    }
+}
+
 impl Mul<Matrix3> for Vector3 {
     type Output = Vector3;
 
@@ -476,3 +767,15 @@ impl Debug for Matrix3 {
         Ok(())
     }
 }
+
+/// Checks if a matrix with the given determinant is too close to singular to invert.
+///
+/// The determinant scales with the size of the matrix's elements (a uniform scale of 0.005 has a
+/// determinant of about 1e-7), so rather than comparing it against a fixed epsilon it's compared
+/// against the products of the matrix's row and column lengths, either of which bounds its
+/// magnitude. The smaller of the two is used since a large translation makes the row lengths a
+/// poor bound for a small scale.
+fn is_singular(determinant: f32, row_lengths: f32, column_lengths: f32) -> bool {
+    let bound = row_lengths.min(column_lengths);
+    bound == 0.0 || determinant.abs() <= bound * EPSILON
+}
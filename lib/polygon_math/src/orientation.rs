@@ -1,6 +1,7 @@
+use angle::Rad;
 use quaternion::Quaternion;
 use std::ops::{Add, AddAssign, Sub, SubAssign, Div, DivAssign, Mul, MulAssign};
-use super::{IsZero, Dot, PI};
+use super::{ApproxEq, EPSILON, Dot, PI};
 use vector::Vector3;
 
 /// An orientation in 3D space.
@@ -32,9 +33,12 @@ impl Orientation {
     ///
     /// - axis - The axis being used to represent the rotation. This should
     ///   be normalized before being passed into `axis_angle()`.
-    pub fn axis_angle(axis: Vector3, angle: f32) -> Orientation {
+    /// - angle - The angle to rotate by, either `Rad` or `Deg`.
+    pub fn axis_angle<A: Into<Rad>>(axis: Vector3, angle: A) -> Orientation {
         assert!(axis.is_normalized());
 
+        let Rad(angle) = angle.into();
+
         // TODO: Do we *need* to normalize the result? Shouldn't it already be normalized?
         let half_angle = angle * 0.5;
         let q = Quaternion::new(axis * half_angle.sin(), half_angle.cos()).normalized();
@@ -42,41 +46,46 @@ impl Orientation {
     }
 
     /// Creates an orientation that rotates an object to look in the specified direction.
+    ///
+    /// The object's forward axis points along `forward`, and it's rolled so that its up axis is
+    /// as close to `up` as possible, i.e. the rotated up axis is in the plane of `forward` and
+    /// `up`. If `up` is parallel to `forward` there's no way to tell which way to roll, so the
+    /// global up axis is used instead, or the global right axis if that's parallel as well.
+    ///
+    /// # Panics
+    ///
+    /// - If either `forward` or `up` is zero.
     pub fn look_rotation(forward: Vector3, up: Vector3) -> Orientation {
-        assert!(!forward.is_zero());
-        assert!(!up.is_zero());
+        assert!(!forward.magnitude_squared().approx_eq(0.0, EPSILON));
+        assert!(!up.magnitude_squared().approx_eq(0.0, EPSILON));
 
-        let source = Vector3::forward();
         let forward = forward.normalized();
         let up = up.normalized();
 
-        let dot = source.dot(forward);
-
-        if (dot + 1.0).is_zero() {
-            // vector a and b point exactly in the opposite direction,
-            // so it is a 180 degrees turn around the up-axis
-            return Orientation::axis_angle(up, PI)
+        // Build an orthonormal basis from the two directions. `forward` is kept exactly, and the
+        // up axis is whatever's left of `up` once the part along `forward` is removed.
+        let right = [up, Vector3::up(), Vector3::right()]
+            .iter()
+            .map(|&up| Vector3::cross(forward, up))
+            .find(|right| !right.magnitude_squared().approx_eq(0.0, EPSILON))
+            .expect("Forward vector is parallel to both the x and y axes")
+            .normalized();
+        let up = Vector3::cross(right, forward);
+
+        if Vector3::forward().dot(forward).approx_eq(-1.0, EPSILON) {
+            // `forward` points exactly backwards, so it is a 180 degree turn around the up axis.
+            return Orientation::axis_angle(up, Rad(PI));
         }
 
-        if (dot - 1.0).is_zero() {
-            // Vector a and b point exactly in the same direction
-            // so we return the identity quaternion.
-            return Orientation::new()
-        }
-
-        // let rot_angle = dot.acos();
-        // let rot_axis = Vector3::cross(source, forward).normalized();// source.cross(forward).normalized();
-        // return Orientation::axis_angle(rot_axis, rot_angle)
-
-        // TODO: Correctly take the up vector into account.
-        unimplemented!();
+        Orientation(Quaternion::from_basis(right, up, -forward))
     }
 
     /// Creates a quaternion from a set of euler angles.
-    pub fn from_eulers(x: f32, y: f32, z: f32) -> Orientation {
-        Orientation::axis_angle(Vector3::new(1.0, 0.0, 0.0), x)
-      + Orientation::axis_angle(Vector3::new(0.0, 1.0, 0.0), y)
-      + Orientation::axis_angle(Vector3::new(0.0, 0.0, 1.0), z)
+    ///
+    /// The rotations are combined in the default `EulerOrder::Xyz` order, use
+    /// `Quaternion::from_eulers_order()` for other orders.
+    pub fn from_eulers<A: Into<Rad>>(x: A, y: A, z: A) -> Orientation {
+        Orientation(Quaternion::from_eulers(x, y, z))
     }
 
     /// Retrieves the rotation represented by the `Orientation` as a rotation about an axis.
@@ -87,7 +96,7 @@ impl Orientation {
 
         let angle = 2.0 * self.0.w.acos();
         let s = (1.0 - self.0.w * self.0.w).sqrt();
-        if s.is_zero() {
+        if s.approx_eq(0.0, EPSILON) {
             // If s is 0, axis is arbitrary.
             (Vector3::new(1.0, 0.0, 0.0), angle)
         } else {
@@ -95,40 +104,11 @@ impl Orientation {
         }
     }
 
-    /// Retrieves the rotation represented by the quaternion as euler angles.
-    pub fn as_eulers(mut self) -> Vector3 {
-        self.0.normalize();
-
-        // Extract quaternion components.
-        let Vector3 { x, y, z } = self.0.v;
-        let w = self.0.w;
-
-        // Implementation taken from here: http://www.euclideanspace.com/maths/geometry/rotations/conversions/quaternionToEuler/
-        let test = x * y + z * w;
-
-        // Test for singularity at north pole.
-        if test > 0.499 {
-            let heading = 2.0 * f32::atan2(x,w);
-            let attitude = PI / 2.0;
-            let bank = 0.0;
-            return Vector3::new(bank, heading, attitude);
-        }
-
-        // Test for singularity at south pole.
-        if test < -0.499 {
-            let heading = -2.0 * f32::atan2(x,w);
-            let attitude = -PI / 2.0;
-            let bank = 0.0;
-            return Vector3::new(bank, heading, attitude);
-        }
-
-        let sqx = x * x;
-        let sqy = y * y;
-        let sqz = z * z;
-        let heading = f32::atan2(2.0 * y * w - 2.0 * x * z, 1.0 - 2.0 * sqy - 2.0 * sqz);
-        let attitude = f32::asin(2.0 * test);
-        let bank = f32::atan2(2.0 * x * w - 2.0 * y * z, 1.0 - 2.0 * sqx - 2.0 * sqz);
-        return Vector3::new(bank, heading, attitude);
+    /// Retrieves the rotation represented by the orientation as euler angles.
+    ///
+    /// The angles match `from_eulers()`, see `Quaternion::to_eulers()` for details.
+    pub fn as_eulers(self) -> Vector3 {
+        self.0.to_eulers()
     }
 
     /// Gets the right direction for the orientation.
@@ -219,7 +199,7 @@ impl Mul<f32> for Orientation {
 
     fn mul(self, rhs: f32) -> Orientation {
         let (axis, angle) = self.as_axis_angle();
-        Orientation::axis_angle(axis, angle * rhs)
+        Orientation::axis_angle(axis, Rad(angle * rhs))
     }
 }
 
@@ -233,12 +213,7 @@ impl Mul<Vector3> for Orientation {
     type Output = Vector3;
 
     fn mul(self, rhs: Vector3) -> Vector3 {
-        let rhs_quat = Quaternion { v: rhs, w: 1.0 };
-        let result_quat = self.0 * rhs_quat * self.0.conjugate();
-
-        // TODO: Should `result_quat.w` always be 1.0? If so should we be asserting or something?
-        // Should we be normalizing the result quaterning before extracting the vector?
-        result_quat.v
+        self.0 * rhs
     }
 }
 
@@ -247,7 +222,7 @@ impl Div<f32> for Orientation {
 
     fn div(self, rhs: f32) -> Orientation {
         let (axis, angle) = self.as_axis_angle();
-        Orientation::axis_angle(axis, angle / rhs)
+        Orientation::axis_angle(axis, Rad(angle / rhs))
     }
 }
 
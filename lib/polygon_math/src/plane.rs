@@ -0,0 +1,46 @@
+use point::Point;
+use vector::Vector3;
+use Dot;
+
+/// An infinite plane in 3D space.
+///
+/// The plane is made of the points `p` where `normal.dot(p) == distance`, so `distance` is how far
+/// the plane is from the origin along `normal`. The side of the plane that `normal` points
+/// towards is the front of the plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3,
+    pub distance: f32,
+}
+
+impl Plane {
+    /// Creates a new plane with the given normal and distance from the origin.
+    ///
+    /// `normal` should be normalized, otherwise distances to the plane are scaled by its length.
+    pub fn new(normal: Vector3, distance: f32) -> Plane {
+        Plane {
+            normal: normal,
+            distance: distance,
+        }
+    }
+
+    /// Creates the plane through `point` that faces along `normal`.
+    ///
+    /// The normal is normalized.
+    pub fn from_point_normal(point: Point, normal: Vector3) -> Plane {
+        let normal = normal.normalized();
+        Plane::new(normal, normal.dot(Vector3::from(point)))
+    }
+
+    /// Calculates how far `point` is in front of the plane.
+    ///
+    /// The distance is negative for points behind the plane.
+    pub fn signed_distance(&self, point: Point) -> f32 {
+        self.normal.dot(Vector3::from(point)) - self.distance
+    }
+
+    /// Finds the point on the plane closest to `point`.
+    pub fn closest_point(&self, point: Point) -> Point {
+        point - self.normal * self.signed_distance(point)
+    }
+}
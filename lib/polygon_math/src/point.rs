@@ -4,6 +4,7 @@ use std::mem;
 use std::f32;
 use std::slice;
 
+use {ApproxEq, Lerp};
 use vector::Vector3;
 
 /// A point in 3D space.
@@ -156,6 +157,21 @@ impl Neg for Point {
 
 /// We lie about Point being Eq because it's needed for Ord. For our purposes we don't
 /// care that it's not technically true according to the spec.
+impl ApproxEq for Point {
+    fn approx_eq(self, other: Point, epsilon: f32) -> bool {
+        self.x.approx_eq(other.x, epsilon)
+            && self.y.approx_eq(other.y, epsilon)
+            && self.z.approx_eq(other.z, epsilon)
+            && self.w.approx_eq(other.w, epsilon)
+    }
+}
+
+impl Lerp for Point {
+    fn lerp(t: f32, from: Point, to: Point) -> Point {
+        from + (to - from) * t
+    }
+}
+
 impl Eq for Point {}
 
 impl PartialOrd for Point {
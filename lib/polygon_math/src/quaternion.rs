@@ -12,11 +12,14 @@
 ///
 /// [hamilton product]: https://en.wikipedia.org/wiki/Quaternion#Hamilton_product
 
+use angle::Rad;
+use matrix::{Matrix3, Matrix4};
 use orientation::Orientation;
+use point::Point;
 use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
 
 use vector::Vector3;
-use super::{IsZero, Dot};
+use super::{ApproxEq, EPSILON, IsZero, Dot, PI};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Quaternion {
@@ -44,48 +47,56 @@ impl Quaternion {
     }
 
     /// Gets the length of the quaternion.
-    pub fn len(self) -> f32 {
-        Quaternion::dot(self, self).sqrt()
+    pub fn magnitude(&self) -> f32 {
+        self.magnitude_squared().sqrt()
     }
 
     /// Gets the squared length of the quaternion.
     ///
-    /// In calculations where you need the squared length of the quaternion, useing `len_sqr()`
-    /// allows you to get it without doing a square root operation followed by a square
-    /// operation.
+    /// In calculations where you need the squared length of the quaternion, using
+    /// `magnitude_squared()` allows you to get it without doing a square root operation followed
+    /// by a square operation.
+    pub fn magnitude_squared(&self) -> f32 {
+        Quaternion::dot(*self, *self)
+    }
+
+    /// Gets the length of the quaternion, the same as `magnitude()`.
+    pub fn len(self) -> f32 {
+        self.magnitude()
+    }
+
+    /// Gets the squared length of the quaternion, the same as `magnitude_squared()`.
     pub fn len_sqr(self) -> f32 {
-        Quaternion::dot(self, self)
+        self.magnitude_squared()
     }
 
     /// Normalizes the quaternion to unit length.
     ///
-    /// The quaternion must not have a length of zero when calling this method.
+    /// # Panics
+    ///
+    /// - If the quaternion has a length of zero.
     pub fn normalize(&mut self) {
-        assert!(!self.is_zero());
+        assert!(!self.magnitude_squared().approx_eq(0.0, EPSILON), "Cannot normalize a zero quaternion");
 
-        let len = self.len();
+        let len = self.magnitude();
         self.v /= len;
         self.w /= len;
     }
 
     /// Creates a normalized copy of the quaternion.
     ///
-    /// The quaternion must not have a length of zero when calling this method.
+    /// # Panics
+    ///
+    /// - If the quaternion has a length of zero.
     pub fn normalized(&self) -> Quaternion {
         let mut temp = *self;
         temp.normalize();
         temp
     }
 
-    /// Determines if the quaternion is normalized (has a length of 1.0).
+    /// Determines if the quaternion is normalized (has a length of 1.0), to within `EPSILON`.
     pub fn is_normalized(&self) -> bool {
-        let mag_sqrd = Quaternion::dot(*self, *self);
-        (mag_sqrd - 1.0).is_zero()
-    }
-
-    /// Calculates the dot product of two quaternions.
-    pub fn dot(first: Quaternion, second: Quaternion) -> f32 {
-        Vector3::dot(first.v, second.v) + first.w * second.w
+        self.magnitude_squared().approx_eq(1.0, EPSILON)
     }
 
     /// Interpolates linearly between two quaternions.
@@ -99,11 +110,195 @@ impl Quaternion {
         first + (second - first) * t
     }
 
+    /// Interpolates linearly between two rotation quaternions and normalizes the result.
+    ///
+    /// `nlerp()` takes the shortest path between the two rotations, the same as `slerp()`, and is
+    /// cheaper but doesn't rotate at a constant speed as `t` goes from 0 to 1. Returns `first`
+    /// when `t` is 0 and `second` when `t` is 1, though `second` may be negated to take the
+    /// shortest path (which represents the same rotation).
+    ///
+    /// Both quaternions must be normalized.
+    pub fn nlerp(first: Quaternion, second: Quaternion, t: f32) -> Quaternion {
+        let (second, _) = Quaternion::shortest_path(first, second);
+
+        // Return the ends exactly, rather than after a round trip through the normalization.
+        if t == 0.0 {
+            first
+        } else if t == 1.0 {
+            second
+        } else {
+            Quaternion::lerp(first, second, t).normalized()
+        }
+    }
+
+    /// Interpolates between two rotation quaternions at a constant angular speed.
+    ///
+    /// `slerp()` takes the shortest path between the two rotations. Returns `first` when `t` is 0
+    /// and `second` when `t` is 1, though `second` may be negated to take the shortest path
+    /// (which represents the same rotation). When the rotations are nearly the same this falls
+    /// back to `nlerp()`, which is indistinguishable at that distance and avoids dividing by the
+    /// sine of an angle that's almost zero.
+    ///
+    /// Both quaternions must be normalized.
+    pub fn slerp(first: Quaternion, second: Quaternion, t: f32) -> Quaternion {
+        // The cosine of the angle between the quaternions above which they're nearly parallel.
+        const NLERP_THRESHOLD: f32 = 0.9995;
+
+        let (second, cos_angle) = Quaternion::shortest_path(first, second);
+        if cos_angle > NLERP_THRESHOLD {
+            return Quaternion::nlerp(first, second, t);
+        }
+
+        if t == 0.0 {
+            return first;
+        } else if t == 1.0 {
+            return second;
+        }
+
+        let angle = cos_angle.acos();
+        let sin_angle = angle.sin();
+        let first_weight = ((1.0 - t) * angle).sin() / sin_angle;
+        let second_weight = (t * angle).sin() / sin_angle;
+        first * first_weight + second * second_weight
+    }
+
+    /// Rotates `point` about the origin by the rotation the quaternion represents.
+    ///
+    /// The quaternion must be normalized, see `Mul<Vector3>`.
+    pub fn rotate_point(self, point: Point) -> Point {
+        Point::from(self * Vector3::from(point))
+    }
+
+    /// Creates the rotation that turns the x, y, and z axes onto `right`, `up`, and `back`.
+    ///
+    /// The basis vectors must be normalized and perpendicular to each other, i.e. they're the
+    /// columns of a rotation matrix.
+    pub fn from_basis(right: Vector3, up: Vector3, back: Vector3) -> Quaternion {
+        // Use the largest of the diagonal terms to keep the division stable.
+        let (m00, m01, m02) = (right.x, up.x, back.x);
+        let (m10, m11, m12) = (right.y, up.y, back.y);
+        let (m20, m21, m22) = (right.z, up.z, back.z);
+        let trace = m00 + m11 + m22;
+        let (x, y, z, w) = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            ((m21 - m12) / s, (m02 - m20) / s, (m10 - m01) / s, 0.25 * s)
+        } else if m00 > m11 && m00 > m22 {
+            let s = (1.0 + m00 - m11 - m22).sqrt() * 2.0;
+            (0.25 * s, (m01 + m10) / s, (m02 + m20) / s, (m21 - m12) / s)
+        } else if m11 > m22 {
+            let s = (1.0 + m11 - m00 - m22).sqrt() * 2.0;
+            ((m01 + m10) / s, 0.25 * s, (m12 + m21) / s, (m02 - m20) / s)
+        } else {
+            let s = (1.0 + m22 - m00 - m11).sqrt() * 2.0;
+            ((m02 + m20) / s, (m12 + m21) / s, 0.25 * s, (m10 - m01) / s)
+        };
+
+        Quaternion::new(Vector3::new(x, y, z), w).normalized()
+    }
+
+    /// Creates the rotation represented by the rotation part of `matrix`.
+    ///
+    /// The upper 3x3 of the matrix must be a pure rotation, i.e. its columns must be normalized
+    /// and perpendicular to each other. Translation is ignored.
+    pub fn from_matrix(matrix: &Matrix4) -> Quaternion {
+        Quaternion::from_basis(matrix.x_part(), matrix.y_part(), matrix.z_part())
+    }
+
+    /// Creates a rotation from euler angles combined in the default `EulerOrder::Xyz` order.
+    ///
+    /// This matches `Orientation::from_eulers()` and `Matrix4::rotation()`.
+    pub fn from_eulers<A: Into<Rad>>(x: A, y: A, z: A) -> Quaternion {
+        Quaternion::from_eulers_order(x, y, z, EulerOrder::Xyz)
+    }
+
+    /// Creates a rotation from euler angles combined in `order`.
+    ///
+    /// `x`, `y`, and `z` are always the angles about the x, y, and z axes, whatever the order.
+    pub fn from_eulers_order<A: Into<Rad>>(x: A, y: A, z: A, order: EulerOrder) -> Quaternion {
+        let angles = [x.into().0, y.into().0, z.into().0];
+        let axis_rotation = |axis: usize| {
+            let half_angle = angles[axis] * 0.5;
+            let mut v = Vector3::zero();
+            v[axis] = half_angle.sin();
+            Quaternion::new(v, half_angle.cos())
+        };
+
+        let (first, second, third) = order.axes();
+        axis_rotation(first) * axis_rotation(second) * axis_rotation(third)
+    }
+
+    /// Retrieves the rotation represented by the quaternion as euler angles, in radians.
+    ///
+    /// The angles are in the default `EulerOrder::Xyz` order, see `to_eulers_order()`.
+    pub fn to_eulers(self) -> Vector3 {
+        self.to_eulers_order(EulerOrder::Xyz)
+    }
+
+    /// Retrieves the rotation represented by the quaternion as euler angles, in radians, such
+    /// that passing them to `from_eulers_order()` with the same `order` gives the same rotation.
+    ///
+    /// The angles about the first and last axes of `order` are in `-PI..PI`, and the angle about
+    /// the middle axis is in `-PI/2..PI/2`.
+    ///
+    /// When the middle angle is at +/-90 degrees the first and last axes line up (gimbal lock),
+    /// so any rotation about them can be expressed with the first axis alone. In that case the
+    /// angle about the last axis is always 0.
+    pub fn to_eulers_order(self, order: EulerOrder) -> Vector3 {
+        let (i, j, k) = order.axes();
+        let m = Matrix3::from(self.normalized());
+
+        // For the rotations combined as `R_i * R_j * R_k`, `m[i][k]` is the sine of the middle
+        // angle. The other terms pick up a sign when the axes aren't in cyclic order.
+        let sign = if order.is_cyclic() { 1.0 } else { -1.0 };
+        let sin_middle = (sign * m[i][k]).max(-1.0).min(1.0);
+
+        let mut angles = Vector3::zero();
+        if sin_middle.abs().approx_eq(1.0, EPSILON) {
+            // `asin()` is very steep here, so snap to exactly 90 degrees rather than amplifying
+            // rounding errors in the matrix.
+            angles[j] = PI * 0.5 * sin_middle.signum();
+            angles[i] = f32::atan2(sign * m[k][j], m[j][j]);
+        } else {
+            angles[j] = sin_middle.asin();
+            angles[i] = f32::atan2(-sign * m[j][k], m[k][k]);
+            angles[k] = f32::atan2(-sign * m[i][j], m[i][i]);
+        }
+        angles
+    }
+
+    /// Flips `second` if needed so that it's on the same side of the hypersphere as `first`,
+    /// returning the flipped quaternion and its dot product with `first`.
+    ///
+    /// `q` and `-q` represent the same rotation, but interpolating towards the one further away
+    /// goes the long way around. When the quaternions are perpendicular both ways are as long, so
+    /// `second` is only flipped if the dot product is clearly negative. Otherwise rounding, e.g.
+    /// `cos(PI / 2)` coming out slightly negative, would pick the direction.
+    fn shortest_path(first: Quaternion, second: Quaternion) -> (Quaternion, f32) {
+        let dot = Quaternion::dot(first, second);
+        if dot < -EPSILON {
+            (second * -1.0, -dot)
+        } else {
+            (second, dot)
+        }
+    }
+
+    /// Calculates the inverse of the quaternion, such that `q * q.inverse()` is the identity.
+    ///
+    /// Unlike `conjugate()` this is correct for quaternions that aren't normalized. For rotation
+    /// quaternions the two are the same, and `conjugate()` is cheaper.
+    ///
+    /// # Panics
+    ///
+    /// - If the quaternion has a length of zero, since it has no inverse.
     pub fn inverse(self) -> Quaternion {
-        (1.0 / self.len_sqr()) * self.conjugate()
+        assert!(!self.magnitude_squared().approx_eq(0.0, EPSILON), "Cannot invert a zero quaternion");
+
+        (1.0 / self.magnitude_squared()) * self.conjugate()
     }
 
     /// Calculates the quaternion representing the opposite rotation.
+    ///
+    /// The conjugate negates the imaginary part, and is the inverse of a normalized quaternion.
     pub fn conjugate(self) -> Quaternion {
         Quaternion {
             v: -self.v,
@@ -112,6 +307,18 @@ impl Quaternion {
     }
 }
 
+impl Dot for Quaternion {
+    type Output = f32;
+
+    /// Calculates the dot product of two quaternions.
+    ///
+    /// For normalized quaternions this is the cosine of the angle between them on the unit
+    /// hypersphere, which is half the angle between the rotations they represent.
+    fn dot(self, rhs: Quaternion) -> f32 {
+        Vector3::dot(self.v, rhs.v) + self.w * rhs.w
+    }
+}
+
 impl From<Orientation> for Quaternion {
     fn from(from: Orientation) -> Quaternion {
         from.0
@@ -175,6 +382,20 @@ impl MulAssign for Quaternion {
     }
 }
 
+/// Rotates a vector by the rotation the quaternion represents.
+///
+/// This is the same as `q * v * q.conjugate()` with `v` as a quaternion with a real part of zero,
+/// but expanded to avoid multiplying out the full Hamilton products. The quaternion must be
+/// normalized, otherwise the vector is also scaled.
+impl Mul<Vector3> for Quaternion {
+    type Output = Vector3;
+
+    fn mul(self, rhs: Vector3) -> Vector3 {
+        let t = 2.0 * Vector3::cross(self.v, rhs);
+        rhs + self.w * t + Vector3::cross(self.v, t)
+    }
+}
+
 impl Mul<f32> for Quaternion {
     type Output = Quaternion;
 
@@ -194,8 +415,61 @@ impl Mul<Quaternion> for f32 {
     }
 }
 
+/// Compares the components of the quaternions.
+///
+/// Note that `q` and `-q` represent the same rotation but aren't approximately equal.
+impl ApproxEq for Quaternion {
+    fn approx_eq(self, other: Quaternion, epsilon: f32) -> bool {
+        self.v.approx_eq(other.v, epsilon) && self.w.approx_eq(other.w, epsilon)
+    }
+}
+
 impl IsZero for Quaternion {
     fn is_zero(self) -> bool {
-        self.len_sqr().is_zero()
+        self.magnitude_squared().is_zero()
+    }
+}
+
+/// The order that euler angle rotations are combined in.
+///
+/// The name lists the axes in the order their rotations are multiplied, e.g. `Xyz` is
+/// `Rx * Ry * Rz`. Since the rightmost rotation is applied to a vector first, `Xyz` rotates
+/// about the world z axis, then y, then x. Equivalently it rotates about the object's own x axis,
+/// then its rotated y axis, then its twice rotated z axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EulerOrder {
+    Xyz,
+    Xzy,
+    Yxz,
+    Yzx,
+    Zxy,
+    Zyx,
+}
+
+impl EulerOrder {
+    /// Gets the indices of the axes in the order their rotations are multiplied.
+    fn axes(self) -> (usize, usize, usize) {
+        match self {
+            EulerOrder::Xyz => (0, 1, 2),
+            EulerOrder::Xzy => (0, 2, 1),
+            EulerOrder::Yxz => (1, 0, 2),
+            EulerOrder::Yzx => (1, 2, 0),
+            EulerOrder::Zxy => (2, 0, 1),
+            EulerOrder::Zyx => (2, 1, 0),
+        }
+    }
+
+    /// Checks if the axes are a rotation of x, y, z rather than a rotation of z, y, x.
+    fn is_cyclic(self) -> bool {
+        match self {
+            EulerOrder::Xyz | EulerOrder::Yzx | EulerOrder::Zxy => true,
+            EulerOrder::Xzy | EulerOrder::Yxz | EulerOrder::Zyx => false,
+        }
+    }
+}
+
+impl Default for EulerOrder {
+    fn default() -> EulerOrder {
+        EulerOrder::Xyz
     }
 }
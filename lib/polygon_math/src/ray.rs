@@ -0,0 +1,182 @@
+use std::f32;
+
+use aabb::Aabb;
+use matrix::Matrix4;
+use plane::Plane;
+use point::Point;
+use sphere::Sphere;
+use vector::{Vector2, Vector3};
+use {ApproxEq, Dot, EPSILON};
+
+/// A half-line starting at `origin` and going on forever along `direction`, mostly used for
+/// picking objects in the scene.
+///
+/// Intersection queries return how far along the ray the intersection is as `t`, such that the
+/// point of intersection is `ray.point_at(t)`. Intersections behind the origin are misses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Point,
+
+    /// The direction of the ray. This is normalized by `new()`, which makes `t` the distance from
+    /// the origin.
+    pub direction: Vector3,
+}
+
+impl Ray {
+    /// Creates a new ray, normalizing `direction`.
+    pub fn new(origin: Point, direction: Vector3) -> Ray {
+        Ray {
+            origin: origin,
+            direction: direction.normalized(),
+        }
+    }
+
+    /// Creates the ray going through a point on the screen, e.g. to find what the mouse is over.
+    ///
+    /// `screen_position` is in pixels from the top-left corner of the viewport, the same as
+    /// sprites, and `viewport_size` is the size of the viewport in pixels. `view` and
+    /// `projection` are the camera's view and projection matrices. The ray starts on the camera's
+    /// near plane, so for a perspective camera it starts just in front of the camera and for an
+    /// orthographic camera every ray has the same direction.
+    ///
+    /// # Panics
+    ///
+    /// - If the combined view and projection matrix isn't invertible, e.g. if the near and far
+    ///   planes are the same.
+    pub fn unproject(screen_position: Vector2, viewport_size: Vector2, view: Matrix4, projection: Matrix4) -> Ray {
+        let inverse = (projection * view)
+            .inverse()
+            .expect("Cannot unproject with a view projection matrix that isn't invertible");
+
+        // Normalized device coordinates have y pointing up, with -1 at the bottom of the screen.
+        let x = screen_position.x / viewport_size.x * 2.0 - 1.0;
+        let y = 1.0 - screen_position.y / viewport_size.y * 2.0;
+
+        let unproject = |depth| {
+            let point = Point { x: x, y: y, z: depth, w: 1.0 } * inverse;
+            Point::new(point.x / point.w, point.y / point.w, point.z / point.w)
+        };
+        let near = unproject(-1.0);
+        let far = unproject(1.0);
+        Ray::new(near, far - near)
+    }
+
+    /// Gets the point at distance `t` along the ray.
+    pub fn point_at(&self, t: f32) -> Point {
+        self.origin + self.direction * t
+    }
+
+    /// Finds where the ray hits `plane`, from either side.
+    ///
+    /// Returns `None` if the plane is behind the ray's origin, or if the ray is parallel to the
+    /// plane, even if the ray lies in the plane.
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<f32> {
+        let facing = plane.normal.dot(self.direction);
+        if facing.approx_eq(0.0, EPSILON) {
+            return None;
+        }
+
+        let t = -plane.signed_distance(self.origin) / facing;
+        if t >= 0.0 {
+            Some(t)
+        } else {
+            None
+        }
+    }
+
+    /// Finds where the ray first hits `sphere`.
+    ///
+    /// If the ray's origin is inside the sphere it hits immediately, and `t` is 0.
+    pub fn intersect_sphere(&self, sphere: &Sphere) -> Option<f32> {
+        // Solve for the points along the ray that are `radius` from the center. The direction is
+        // normalized, so the quadratic's `a` term is 1.
+        let offset = self.origin - sphere.center;
+        let b = offset.dot(self.direction);
+        let c = offset.magnitude_squared() - sphere.radius * sphere.radius;
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+
+        // The origin is outside of the sphere, so there's no hit if the ray points away from it.
+        let discriminant = b * b - c;
+        if b > 0.0 || discriminant < 0.0 {
+            return None;
+        }
+
+        Some(-b - discriminant.sqrt())
+    }
+
+    /// Finds where the ray enters `aabb`.
+    ///
+    /// If the ray's origin is inside the box it hits immediately, and `t` is 0.
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        // Slab method: clip the ray against the pair of planes on each axis, keeping the latest
+        // entry and the earliest exit. Dividing by a zero direction gives infinities, which work
+        // out so that a ray parallel to a pair of planes is only clipped away if it's outside of
+        // them.
+        let origin = [self.origin.x, self.origin.y, self.origin.z];
+        let direction = [self.direction.x, self.direction.y, self.direction.z];
+        let min = [aabb.min.x, aabb.min.y, aabb.min.z];
+        let max = [aabb.max.x, aabb.max.y, aabb.max.z];
+
+        let mut entry = 0.0f32;
+        let mut exit = f32::INFINITY;
+        for axis in 0..3 {
+            let inv_direction = 1.0 / direction[axis];
+            let first = (min[axis] - origin[axis]) * inv_direction;
+            let second = (max[axis] - origin[axis]) * inv_direction;
+            if first.is_nan() || second.is_nan() {
+                // The origin is exactly on a plane that the ray is parallel to, so the ray stays
+                // on the surface of the box along this axis.
+                continue;
+            }
+
+            entry = entry.max(first.min(second));
+            exit = exit.min(first.max(second));
+        }
+
+        if entry <= exit {
+            Some(entry)
+        } else {
+            None
+        }
+    }
+
+    /// Finds where the ray hits the triangle `a`, `b`, `c`, from either side.
+    ///
+    /// Returns `t` along with the barycentric coordinates of the hit, i.e. the weights of `a`,
+    /// `b`, and `c` in the x, y, and z of the vector. They can be used to interpolate vertex
+    /// data such as texture coordinates at the hit. Degenerate triangles are never hit.
+    pub fn intersect_triangle(&self, a: Point, b: Point, c: Point) -> Option<(f32, Vector3)> {
+        // Möller-Trumbore: solve `origin + t * direction = a + u * (b - a) + v * (c - a)` for
+        // `t`, `u`, and `v` using Cramer's rule.
+        let edge_1 = b - a;
+        let edge_2 = c - a;
+        let p = Vector3::cross(self.direction, edge_2);
+        let determinant = edge_1.dot(p);
+        if determinant.approx_eq(0.0, EPSILON) {
+            // The ray is parallel to the triangle.
+            return None;
+        }
+
+        let inv_determinant = 1.0 / determinant;
+        let offset = self.origin - a;
+        let u = offset.dot(p) * inv_determinant;
+        if u < 0.0 || u > 1.0 {
+            return None;
+        }
+
+        let q = Vector3::cross(offset, edge_1);
+        let v = self.direction.dot(q) * inv_determinant;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge_2.dot(q) * inv_determinant;
+        if t < 0.0 {
+            return None;
+        }
+
+        Some((t, Vector3::new(1.0 - u - v, u, v)))
+    }
+}
@@ -0,0 +1,61 @@
+use aabb::Aabb;
+use matrix::Matrix4;
+use point::Point;
+
+/// A sphere in 3D space, mostly used as a cheap bounding volume for intersection tests.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Sphere {
+    pub center: Point,
+    pub radius: f32,
+}
+
+impl Sphere {
+    /// Creates a new sphere with the given center and radius.
+    pub fn new(center: Point, radius: f32) -> Sphere {
+        Sphere {
+            center: center,
+            radius: radius,
+        }
+    }
+
+    /// Creates a sphere that contains all of `points`.
+    ///
+    /// The sphere is centered on the middle of the points' axis-aligned bounding box, which
+    /// isn't always the smallest possible sphere but is cheap to calculate. If `points` is empty
+    /// the sphere is at the origin with a radius of zero.
+    pub fn from_points(points: &[Point]) -> Sphere {
+        if points.is_empty() {
+            return Sphere::new(Point::origin(), 0.0);
+        }
+
+        let center = Aabb::from_points(points).center();
+        let radius_sqr = points
+            .iter()
+            .map(|point| center.distance_sqr(point))
+            .fold(0.0, f32::max);
+        Sphere::new(center, radius_sqr.sqrt())
+    }
+
+    /// Returns `true` if `point` is inside the sphere or on its surface.
+    pub fn contains_point(&self, point: Point) -> bool {
+        self.center.distance_sqr(&point) <= self.radius * self.radius
+    }
+
+    /// Returns `true` if the two spheres overlap. Spheres that only touch count as overlapping.
+    pub fn intersects(&self, other: &Sphere) -> bool {
+        let radii = self.radius + other.radius;
+        self.center.distance_sqr(&other.center) <= radii * radii
+    }
+
+    /// Transforms the sphere by `transform`.
+    ///
+    /// The radius is scaled by the largest scale along any of the transform's axes, so the
+    /// result still contains everything the original sphere did if the transform has a
+    /// non-uniform scale.
+    pub fn transform(&self, transform: Matrix4) -> Sphere {
+        let scale = transform.x_part().magnitude()
+            .max(transform.y_part().magnitude())
+            .max(transform.z_part().magnitude());
+        Sphere::new(self.center * transform, self.radius * scale)
+    }
+}
@@ -0,0 +1,99 @@
+use aabb::Aabb;
+use matrix::Matrix4;
+use point::Point;
+use vector::Vector3;
+use super::Random;
+
+#[test]
+fn from_points() {
+    let points = [
+        Point::new(-1.0, 2.0, 0.0),
+        Point::new(3.0, 0.0, 1.0),
+        Point::new(1.0, 1.0, -4.0),
+    ];
+    let aabb = Aabb::from_points(&points);
+    assert_eq!(aabb, Aabb::new(Point::new(-1.0, 0.0, -4.0), Point::new(3.0, 2.0, 1.0)));
+    assert_eq!(aabb.center(), Point::new(1.0, 1.0, -1.5));
+    assert_eq!(aabb.extents(), Vector3::new(2.0, 1.0, 2.5));
+    for &point in &points {
+        assert!(aabb.contains_point(point));
+    }
+
+    assert_eq!(Aabb::from_points(&[]), Aabb::new(Point::origin(), Point::origin()));
+}
+
+#[test]
+fn contains_point() {
+    let aabb = Aabb::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 2.0, 3.0));
+    assert!(aabb.contains_point(Point::origin()));
+    assert!(aabb.contains_point(Point::new(1.0, 2.0, 3.0)));
+    assert!(!aabb.contains_point(Point::new(1.1, 0.0, 0.0)));
+    assert!(!aabb.contains_point(Point::new(0.0, -1.1, 0.0)));
+    assert!(!aabb.contains_point(Point::new(0.0, 0.0, 3.1)));
+}
+
+#[test]
+fn merge_and_expand() {
+    let first = Aabb::new(Point::new(0.0, 0.0, 0.0), Point::new(1.0, 1.0, 1.0));
+    let second = Aabb::new(Point::new(2.0, -1.0, 0.5), Point::new(3.0, 0.5, 0.75));
+    assert_eq!(first.merge(&second), Aabb::new(Point::new(0.0, -1.0, 0.0), Point::new(3.0, 1.0, 1.0)));
+    assert_eq!(first.merge(&first), first);
+
+    assert_eq!(first.expand(0.5), Aabb::new(Point::new(-0.5, -0.5, -0.5), Point::new(1.5, 1.5, 1.5)));
+    assert_eq!(first.expand(0.5).expand(-0.5), first);
+}
+
+#[test]
+fn intersects() {
+    let aabb = Aabb::new(Point::new(0.0, 0.0, 0.0), Point::new(2.0, 2.0, 2.0));
+    assert!(aabb.intersects(&Aabb::new(Point::new(1.0, 1.0, 1.0), Point::new(3.0, 3.0, 3.0))));
+    assert!(aabb.intersects(&Aabb::new(Point::new(0.5, 0.5, 0.5), Point::new(1.0, 1.0, 1.0))));
+
+    // Touching counts as intersecting.
+    assert!(aabb.intersects(&Aabb::new(Point::new(2.0, 0.0, 0.0), Point::new(3.0, 1.0, 1.0))));
+
+    // Overlapping on two axes isn't enough.
+    assert!(!aabb.intersects(&Aabb::new(Point::new(1.0, 1.0, 2.1), Point::new(3.0, 3.0, 3.0))));
+    assert!(!aabb.intersects(&Aabb::new(Point::new(-2.0, 0.0, 0.0), Point::new(-0.1, 1.0, 1.0))));
+}
+
+#[test]
+fn transformed() {
+    let aabb = Aabb::new(Point::new(-1.0, -2.0, -3.0), Point::new(1.0, 2.0, 3.0));
+
+    // Translation and scale keep the box tight.
+    let transform = Matrix4::translation(10.0, 0.0, 0.0) * Matrix4::scale(2.0, 1.0, -1.0);
+    assert_eq!(
+        aabb.transformed(&transform),
+        Aabb::new(Point::new(8.0, -2.0, -3.0), Point::new(12.0, 2.0, 3.0)));
+
+    // A quarter turn about z swaps the x and y extents.
+    let transformed = aabb.transformed(&Matrix4::rotation(0.0, 0.0, ::std::f32::consts::PI * 0.5));
+    assert_approx_eq!(transformed.extents(), Vector3::new(2.0, 1.0, 3.0), 1e-5);
+}
+
+#[test]
+fn transformed_contains_points() {
+    let mut random = Random::new();
+    for _ in 0..200 {
+        let points: Vec<Point> = (0..8).map(|_| Point::from(random.vector() * 5.0)).collect();
+        let aabb = Aabb::from_points(&points);
+
+        let rotation = random.rotations(1)[0];
+        let scale = random.vector() * 3.0;
+        let transform = Matrix4::trs(Point::from(random.vector() * 20.0), rotation, scale);
+        let transformed = aabb.transformed(&transform).expand(1e-4);
+
+        // Every point in the source box ends up in the transformed box, including its corners.
+        for &point in &points {
+            assert!(transformed.contains_point(point * transform), "{:?} isn't in {:?}", point * transform, transformed);
+        }
+        for corner in 0..8 {
+            let point = Point::new(
+                if corner & 1 == 0 { aabb.min.x } else { aabb.max.x },
+                if corner & 2 == 0 { aabb.min.y } else { aabb.max.y },
+                if corner & 4 == 0 { aabb.min.z } else { aabb.max.z });
+            assert!(transformed.contains_point(point * transform), "{:?} isn't in {:?}", point * transform, transformed);
+        }
+    }
+}
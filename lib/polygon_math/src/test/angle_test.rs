@@ -0,0 +1,37 @@
+use std::f32::consts::PI;
+
+use angle::{Deg, Rad};
+use matrix::Matrix4;
+use orientation::Orientation;
+use quaternion::Quaternion;
+use vector::Vector3;
+
+#[test]
+fn conversion() {
+    assert_approx_eq!(Rad::from(Deg(180.0)).0, PI);
+    assert_approx_eq!(Rad::from(Deg(-90.0)).0, -PI * 0.5);
+    assert_approx_eq!(Deg::from(Rad(PI * 0.25)).0, 45.0);
+    assert_eq!(Rad::from(Deg(0.0)), Rad(0.0));
+
+    let Deg(round_trip) = Deg::from(Rad::from(Deg(123.0)));
+    assert_approx_eq!(round_trip, 123.0, 1e-5);
+}
+
+#[test]
+fn angle_arguments() {
+    // Both units give the same rotation.
+    let axis = Vector3::new(0.0, 1.0, 0.0);
+    assert_approx_eq!(Orientation::axis_angle(axis, Deg(90.0)).0, Orientation::axis_angle(axis, Rad(PI * 0.5)).0);
+    assert_approx_eq!(
+        Quaternion::from_eulers(Deg(30.0), Deg(-45.0), Deg(60.0)),
+        Quaternion::from_eulers(Rad(PI / 6.0), Rad(-PI * 0.25), Rad(PI / 3.0)));
+    assert_approx_eq!(
+        Matrix4::perspective(Deg(60.0), 1.5, 0.1, 100.0),
+        Matrix4::perspective(Rad(PI / 3.0), 1.5, 0.1, 100.0));
+}
+
+#[test]
+fn display() {
+    assert_eq!(Rad(1.5).to_string(), "1.5 rad");
+    assert_eq!(Deg(90.0).to_string(), "90°");
+}
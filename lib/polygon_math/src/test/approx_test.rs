@@ -0,0 +1,69 @@
+use std::f32;
+
+use color::Color;
+use matrix::{Matrix3, Matrix4};
+use point::Point;
+use quaternion::Quaternion;
+use vector::Vector3;
+use {approx_eq, ApproxEq, EPSILON};
+
+#[test]
+fn approx_eq_f32() {
+    assert!(approx_eq(1.0f32, 1.0 + 1e-7, EPSILON));
+    assert!(!approx_eq(1.0f32, 1.0 + 1e-5, EPSILON));
+    assert!(approx_eq(1.0f32, 1.0 + 1e-5, 1e-4));
+
+    // Small values are compared absolutely.
+    assert!(approx_eq(0.0f32, 5e-7, EPSILON));
+    assert!(approx_eq(1e-7f32, -1e-7, EPSILON));
+
+    // Large values are compared relative to their magnitude, since 1e6 + 0.01 can't be told
+    // apart from 1e6 to within EPSILON.
+    assert!(approx_eq(1e6f32, 1e6 + 0.5, EPSILON));
+    assert!(!approx_eq(1e6f32, 1e6 + 10.0, EPSILON));
+    assert!(approx_eq(-1e6f32, -1e6 - 0.5, EPSILON));
+
+    assert!(approx_eq(f32::INFINITY, f32::INFINITY, EPSILON));
+    assert!(!approx_eq(f32::INFINITY, f32::NEG_INFINITY, EPSILON));
+    assert!(!approx_eq(f32::INFINITY, f32::MAX, EPSILON));
+    assert!(!approx_eq(f32::NAN, f32::NAN, EPSILON));
+}
+
+#[test]
+fn approx_eq_components() {
+    let vector = Vector3::new(1.0, -2.0, 3.0);
+    assert!(vector.approx_eq(vector + Vector3::new(0.0, 1e-7, 0.0), EPSILON));
+    assert!(!vector.approx_eq(vector + Vector3::new(0.0, 0.0, 1e-3), EPSILON));
+
+    let point = Point::new(1.0, -2.0, 3.0);
+    assert!(point.approx_eq(point + Vector3::new(1e-7, 0.0, 0.0), EPSILON));
+    assert!(!point.approx_eq(Point::new(1.0, -2.0, 3.1), EPSILON));
+
+    let quat = Quaternion::new(Vector3::new(0.0, 0.6, 0.0), 0.8);
+    assert!(quat.approx_eq(quat, EPSILON));
+    assert!(!quat.approx_eq(Quaternion::identity(), EPSILON));
+
+    // `-q` is the same rotation, but not the same quaternion.
+    assert!(!quat.approx_eq(quat * -1.0, EPSILON));
+
+    let matrix = Matrix4::rotation(0.3, -1.2, 2.0);
+    assert!(matrix.approx_eq(Matrix4::rotation(0.3 + 1e-8, -1.2, 2.0), EPSILON));
+    assert!(!matrix.approx_eq(Matrix4::rotation(0.31, -1.2, 2.0), EPSILON));
+    assert!(Matrix3::from(matrix).approx_eq(Matrix3::rotation(0.3, -1.2, 2.0), 1e-5));
+
+    let color = Color::new(0.1, 0.2, 0.3, 0.4);
+    assert!(color.approx_eq(Color::new(0.1, 0.2, 0.3, 0.4 + 1e-7), EPSILON));
+    assert!(!color.approx_eq(Color::new(0.1, 0.2, 0.3, 1.0), EPSILON));
+}
+
+#[test]
+fn assert_approx_eq() {
+    assert_approx_eq!(0.1 + 0.2, 0.3f32);
+    assert_approx_eq!(Vector3::new(1.0, 2.0, 3.0), Vector3::new(1.0, 2.0, 3.001), 1e-3);
+}
+
+#[test]
+#[should_panic]
+fn assert_approx_eq_fails() {
+    assert_approx_eq!(Vector3::new(1.0, 2.0, 3.0), Vector3::new(1.0, 2.0, 3.1));
+}
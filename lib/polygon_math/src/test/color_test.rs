@@ -0,0 +1,92 @@
+use color::{Color, ParseColorError};
+use Lerp;
+
+#[test]
+fn from_hsv() {
+    assert_approx_eq!(Color::from_hsv(0.0, 1.0, 1.0), Color::rgb(1.0, 0.0, 0.0), 1e-4);
+    assert_approx_eq!(Color::from_hsv(60.0, 1.0, 1.0), Color::rgb(1.0, 1.0, 0.0), 1e-4);
+    assert_approx_eq!(Color::from_hsv(120.0, 1.0, 1.0), Color::rgb(0.0, 1.0, 0.0), 1e-4);
+    assert_approx_eq!(Color::from_hsv(240.0, 1.0, 0.5), Color::rgb(0.0, 0.0, 0.5), 1e-4);
+    assert_approx_eq!(Color::from_hsv(300.0, 0.5, 1.0), Color::rgb(1.0, 0.5, 1.0), 1e-4);
+    assert_approx_eq!(Color::from_hsv(123.0, 0.0, 0.25), Color::rgb(0.25, 0.25, 0.25), 1e-4);
+
+    // Hues wrap around.
+    assert_approx_eq!(Color::from_hsv(360.0, 1.0, 1.0), Color::rgb(1.0, 0.0, 0.0), 1e-4);
+    assert_approx_eq!(Color::from_hsv(-120.0, 1.0, 1.0), Color::from_hsv(240.0, 1.0, 1.0), 1e-4);
+    assert_approx_eq!(Color::from_hsv(420.0, 1.0, 1.0), Color::from_hsv(60.0, 1.0, 1.0), 1e-4);
+}
+
+#[test]
+fn hsv_round_trip() {
+    for step in 0..72 {
+        let hue = step as f32 * 5.0;
+        for &(saturation, value) in &[(1.0, 1.0), (0.5, 0.75), (0.25, 0.1)] {
+            let (h, s, v) = Color::from_hsv(hue, saturation, value).to_hsv();
+            assert_approx_eq!(h, hue, 1e-3);
+            assert_approx_eq!(s, saturation, 1e-5);
+            assert_approx_eq!(v, value, 1e-5);
+        }
+    }
+
+    // Grays and black don't have a hue or saturation.
+    assert_eq!(Color::rgb(0.5, 0.5, 0.5).to_hsv(), (0.0, 0.0, 0.5));
+    assert_eq!(Color::rgb(0.0, 0.0, 0.0).to_hsv(), (0.0, 0.0, 0.0));
+}
+
+#[test]
+fn lerp() {
+    let from = Color::new(0.0, 0.5, 1.0, 0.0);
+    let to = Color::new(1.0, 0.5, 0.0, 1.0);
+    assert_eq!(Color::lerp(0.0, from, to), from);
+    assert_eq!(Color::lerp(1.0, from, to), to);
+    assert_approx_eq!(Color::lerp(0.25, from, to), Color::new(0.25, 0.5, 0.75, 0.25), 1e-4);
+}
+
+#[test]
+fn srgb_conversion() {
+    assert_approx_eq!(Color::rgb(0.0, 1.0, 0.5).to_linear(), Color::rgb(0.0, 1.0, 0.21404), 1e-4);
+    assert_approx_eq!(Color::rgb(0.0, 1.0, 0.21404).to_srgb(), Color::rgb(0.0, 1.0, 0.5), 1e-4);
+
+    // Dark values are on the linear segment of the curve.
+    assert_approx_eq!(Color::rgb(0.02, 0.0, 0.0).to_linear(), Color::rgb(0.02 / 12.92, 0.0, 0.0), 1e-4);
+
+    // Alpha isn't converted.
+    assert_eq!(Color::new(0.5, 0.5, 0.5, 0.5).to_linear().a, 0.5);
+    assert_eq!(Color::new(0.5, 0.5, 0.5, 0.5).to_srgb().a, 0.5);
+
+    for step in 0..21 {
+        let value = step as f32 / 20.0;
+        let color = Color::rgb(value, value, value);
+        assert_approx_eq!(color.to_linear().to_srgb(), color, 1e-4);
+        assert_approx_eq!(color.to_srgb().to_linear(), color, 1e-4);
+    }
+}
+
+#[test]
+fn from_hex() {
+    assert_approx_eq!(Color::from_hex(0xFF0000), Color::rgb(1.0, 0.0, 0.0), 1e-4);
+    assert_approx_eq!(Color::from_hex(0xFF8000), Color::rgb(1.0, 128.0 / 255.0, 0.0).to_linear(), 1e-4);
+    assert_approx_eq!(Color::from_hex(0xFF_00FF00), Color::rgb(0.0, 1.0, 0.0), 1e-4);
+
+    assert_eq!(Color::from_hex_str("#ff8000"), Ok(Color::from_hex(0xFF8000)));
+    assert_eq!(Color::from_hex_str("FF8000"), Ok(Color::from_hex(0xFF8000)));
+    assert_approx_eq!(Color::from_hex_str("#0000FF80").unwrap(), Color::new(0.0, 0.0, 1.0, 128.0 / 255.0), 1e-4);
+
+    // Converting back to sRGB gives the original bytes.
+    let srgb = Color::from_hex_str("#1a2b3c").unwrap().to_srgb();
+    assert_eq!(
+        ((srgb.r * 255.0).round(), (srgb.g * 255.0).round(), (srgb.b * 255.0).round()),
+        (26.0, 43.0, 60.0));
+}
+
+#[test]
+fn from_hex_str_malformed() {
+    assert_eq!(Color::from_hex_str(""), Err(ParseColorError::InvalidLength { length: 0 }));
+    assert_eq!(Color::from_hex_str("#"), Err(ParseColorError::InvalidLength { length: 0 }));
+    assert_eq!(Color::from_hex_str("#fff"), Err(ParseColorError::InvalidLength { length: 3 }));
+    assert_eq!(Color::from_hex_str("#ff80001"), Err(ParseColorError::InvalidLength { length: 7 }));
+    assert_eq!(Color::from_hex_str("##ff8000"), Err(ParseColorError::InvalidLength { length: 7 }));
+    assert_eq!(Color::from_hex_str("#ff80g0"), Err(ParseColorError::InvalidDigit { character: 'g' }));
+    assert_eq!(Color::from_hex_str("#ff 800"), Err(ParseColorError::InvalidDigit { character: ' ' }));
+    assert_eq!(Color::from_hex_str("#ff80é0"), Err(ParseColorError::InvalidDigit { character: 'é' }));
+}
@@ -0,0 +1,116 @@
+use std::f32::consts::PI;
+
+use angle::Rad;
+use aabb::Aabb;
+use frustum::{Containment, Frustum};
+use matrix::Matrix4;
+use point::Point;
+use sphere::Sphere;
+
+/// A frustum with a 90 degree field of view, so each side plane is at 45 degrees, with the near
+/// plane at 1 and the far plane at 10.
+fn perspective() -> Frustum {
+    Frustum::from_matrix(&Matrix4::perspective(Rad(PI * 0.5), 1.0, 1.0, 10.0))
+}
+
+#[test]
+fn perspective_planes() {
+    let frustum = perspective();
+
+    // Just inside and just outside of each plane, at the middle of the frustum's depth.
+    let inside = [
+        Point::new(-4.99, 0.0, -5.0),
+        Point::new(4.99, 0.0, -5.0),
+        Point::new(0.0, -4.99, -5.0),
+        Point::new(0.0, 4.99, -5.0),
+        Point::new(0.0, 0.0, -1.01),
+        Point::new(0.0, 0.0, -9.99),
+    ];
+    let outside = [
+        Point::new(-5.01, 0.0, -5.0),
+        Point::new(5.01, 0.0, -5.0),
+        Point::new(0.0, -5.01, -5.0),
+        Point::new(0.0, 5.01, -5.0),
+        Point::new(0.0, 0.0, -0.99),
+        Point::new(0.0, 0.0, -10.01),
+    ];
+    for (index, (&inside, &outside)) in inside.iter().zip(outside.iter()).enumerate() {
+        assert!(frustum.contains_point(inside), "{:?} should be inside", inside);
+        assert!(!frustum.contains_point(outside), "{:?} should be outside", outside);
+
+        // Each point is just in front of the plane it's testing, and just behind it.
+        let plane = frustum.planes[index];
+        assert!(plane.signed_distance(inside) > 0.0 && plane.signed_distance(inside) < 0.02);
+        assert!(plane.signed_distance(outside) < 0.0 && plane.signed_distance(outside) > -0.02);
+    }
+
+    // Behind the camera.
+    assert!(!frustum.contains_point(Point::new(0.0, 0.0, 5.0)));
+}
+
+#[test]
+fn world_space() {
+    // Moving the camera back moves the frustum with it.
+    let projection = Matrix4::perspective(Rad(PI * 0.5), 1.0, 1.0, 10.0);
+    let view = Matrix4::translation(0.0, 0.0, -5.0);
+    let frustum = Frustum::from_matrix(&(projection * view));
+    assert!(frustum.contains_point(Point::new(4.99, 0.0, 0.0)));
+    assert!(!frustum.contains_point(Point::new(5.01, 0.0, 0.0)));
+    assert!(frustum.contains_point(Point::new(0.0, 0.0, 3.99)));
+    assert!(!frustum.contains_point(Point::new(0.0, 0.0, 4.01)));
+}
+
+#[test]
+fn spheres() {
+    let frustum = perspective();
+    assert_eq!(frustum.intersects_sphere(&Sphere::new(Point::new(0.0, 0.0, -5.0), 1.0)), Containment::Inside);
+
+    // The left plane is at 45 degrees, so the sphere's center is about 0.35 units outside of it.
+    assert_eq!(frustum.intersects_sphere(&Sphere::new(Point::new(-5.5, 0.0, -5.0), 1.0)), Containment::Intersecting);
+    assert_eq!(frustum.intersects_sphere(&Sphere::new(Point::new(-5.5, 0.0, -5.0), 0.3)), Containment::Outside);
+
+    assert_eq!(frustum.intersects_sphere(&Sphere::new(Point::new(0.0, 0.0, -0.5), 0.6)), Containment::Intersecting);
+    assert_eq!(frustum.intersects_sphere(&Sphere::new(Point::new(0.0, 0.0, -0.5), 0.4)), Containment::Outside);
+    assert_eq!(frustum.intersects_sphere(&Sphere::new(Point::new(0.0, 0.0, -10.5), 0.6)), Containment::Intersecting);
+    assert_eq!(frustum.intersects_sphere(&Sphere::new(Point::new(0.0, 0.0, -10.5), 0.4)), Containment::Outside);
+}
+
+#[test]
+fn boxes() {
+    let frustum = perspective();
+    let aabb = Aabb::new(Point::new(-1.0, -1.0, -6.0), Point::new(1.0, 1.0, -4.0));
+    assert_eq!(frustum.intersects_aabb(&aabb), Containment::Inside);
+
+    let aabb = Aabb::new(Point::new(-6.0, -1.0, -6.0), Point::new(-5.5, 1.0, -5.0));
+    assert_eq!(frustum.intersects_aabb(&aabb), Containment::Intersecting);
+
+    let aabb = Aabb::new(Point::new(-9.0, -1.0, -6.0), Point::new(-7.0, 1.0, -5.0));
+    assert_eq!(frustum.intersects_aabb(&aabb), Containment::Outside);
+
+    // Behind the camera.
+    let aabb = Aabb::new(Point::new(-1.0, -1.0, 1.0), Point::new(1.0, 1.0, 2.0));
+    assert_eq!(frustum.intersects_aabb(&aabb), Containment::Outside);
+
+    // Bigger than the whole frustum.
+    let aabb = Aabb::new(Point::new(-100.0, -100.0, -100.0), Point::new(100.0, 100.0, 100.0));
+    assert_eq!(frustum.intersects_aabb(&aabb), Containment::Intersecting);
+}
+
+#[test]
+fn orthographic() {
+    let frustum = Frustum::from_matrix(&Matrix4::orthographic(-2.0, 2.0, -1.0, 1.0, 1.0, 3.0));
+    assert!(frustum.contains_point(Point::new(1.99, 0.99, -2.0)));
+    assert!(frustum.contains_point(Point::new(-1.99, -0.99, -1.01)));
+    assert!(frustum.contains_point(Point::new(0.0, 0.0, -2.99)));
+    assert!(!frustum.contains_point(Point::new(2.01, 0.0, -2.0)));
+    assert!(!frustum.contains_point(Point::new(0.0, -1.01, -2.0)));
+    assert!(!frustum.contains_point(Point::new(0.0, 0.0, -0.99)));
+    assert!(!frustum.contains_point(Point::new(0.0, 0.0, -3.01)));
+
+    // The sides don't get further apart with distance.
+    assert_eq!(frustum.intersects_sphere(&Sphere::new(Point::new(2.5, 0.0, -2.0), 0.4)), Containment::Outside);
+    assert_eq!(frustum.intersects_sphere(&Sphere::new(Point::new(2.5, 0.0, -2.0), 0.6)), Containment::Intersecting);
+    assert_eq!(
+        frustum.intersects_aabb(&Aabb::new(Point::new(-1.0, -0.5, -2.5), Point::new(1.0, 0.5, -1.5))),
+        Containment::Inside);
+}
@@ -0,0 +1,116 @@
+use interp::*;
+use point::Point;
+use vector::Vector3;
+
+#[test]
+fn smoothstep_edges() {
+    for &step in &[smoothstep as fn(f32, f32, f32) -> f32, smootherstep] {
+        assert_eq!(step(1.0, 3.0, 1.0), 0.0);
+        assert_eq!(step(1.0, 3.0, 2.0), 0.5);
+        assert_eq!(step(1.0, 3.0, 3.0), 1.0);
+
+        // Clamped outside of the edges.
+        assert_eq!(step(1.0, 3.0, -5.0), 0.0);
+        assert_eq!(step(1.0, 3.0, 10.0), 1.0);
+    }
+}
+
+#[test]
+fn easing_monotonic() {
+    let easings = [
+        ease_in_quad as fn(f32) -> f32,
+        ease_out_quad,
+        ease_in_out_quad,
+        ease_in_cubic,
+        ease_out_cubic,
+        ease_in_out_cubic,
+    ];
+    for easing in &easings {
+        assert_eq!(easing(0.0), 0.0);
+        assert_eq!(easing(1.0), 1.0);
+        assert_eq!(easing(-1.0), 0.0);
+        assert_eq!(easing(2.0), 1.0);
+
+        let mut previous = 0.0;
+        for step in 1..101 {
+            let value = easing(step as f32 / 100.0);
+            assert!(value >= previous, "Easing decreased from {} to {} at step {}", previous, value, step);
+            previous = value;
+        }
+    }
+
+    // The in-out easings are symmetric around the middle.
+    assert_approx_eq!(ease_in_out_quad(0.5), 0.5);
+    assert_approx_eq!(ease_in_out_cubic(0.5), 0.5);
+}
+
+#[test]
+fn catmull_rom_endpoints() {
+    assert_eq!(catmull_rom(-3.0f32, 1.0, 2.0, 10.0, 0.0), 1.0);
+    assert_eq!(catmull_rom(-3.0f32, 1.0, 2.0, 10.0, 1.0), 2.0);
+
+    let vectors = [
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(1.0, 2.0, 0.0),
+        Vector3::new(3.0, 2.0, -1.0),
+        Vector3::new(4.0, 0.0, 1.0),
+    ];
+    assert_approx_eq!(catmull_rom(vectors[0], vectors[1], vectors[2], vectors[3], 0.0), vectors[1]);
+    assert_approx_eq!(catmull_rom(vectors[0], vectors[1], vectors[2], vectors[3], 1.0), vectors[2]);
+
+    let points = [
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(1.0, 2.0, 0.0),
+        Point::new(3.0, 2.0, -1.0),
+        Point::new(4.0, 0.0, 1.0),
+    ];
+    assert_eq!(catmull_rom(points[0], points[1], points[2], points[3], 0.0), points[1]);
+    assert_eq!(catmull_rom(points[0], points[1], points[2], points[3], 1.0), points[2]);
+
+    // Evenly spaced points on a line give a straight line.
+    let midpoint = catmull_rom(Point::new(0.0, 0.0, 0.0), Point::new(1.0, 0.0, 0.0), Point::new(2.0, 0.0, 0.0), Point::new(3.0, 0.0, 0.0), 0.5);
+    assert_eq!(midpoint, Point::new(1.5, 0.0, 0.0));
+}
+
+#[test]
+fn catmull_rom_continuity() {
+    let knots = [0.0f32, 2.0, -1.0, 3.0, 5.0];
+    let delta = 1e-3;
+
+    // Estimate the slope on either side of the knot where the two segments meet.
+    let before = catmull_rom(knots[0], knots[1], knots[2], knots[3], 1.0)
+               - catmull_rom(knots[0], knots[1], knots[2], knots[3], 1.0 - delta);
+    let after = catmull_rom(knots[1], knots[2], knots[3], knots[4], delta)
+              - catmull_rom(knots[1], knots[2], knots[3], knots[4], 0.0);
+    assert!((before / delta - after / delta).abs() < 0.05, "Slope changed from {} to {}", before / delta, after / delta);
+
+    // Both match the tangent at the knot.
+    let tangent = (knots[3] - knots[1]) * 0.5;
+    assert_approx_eq!(after / delta, tangent, 0.05);
+}
+
+#[test]
+fn spline_sample() {
+    let points = vec![
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(1.0, 0.0, 0.0),
+        Point::new(1.0, 3.0, 0.0),
+        Point::new(1.0, 3.0, 0.0),
+        Point::new(1.0, 3.0, 4.0),
+    ];
+    let spline = Spline::new(points.clone());
+    assert_eq!(spline.length(), 8.0);
+
+    assert_eq!(spline.sample(0.0), points[0]);
+    assert_eq!(spline.sample(1.0), points[4]);
+    assert_eq!(spline.sample(-1.0), points[0]);
+    assert_eq!(spline.sample(2.0), points[4]);
+
+    // The spline passes through each point at its share of the chord length.
+    assert_eq!(spline.sample(1.0 / 8.0), points[1]);
+    assert_eq!(spline.sample(4.0 / 8.0), points[2]);
+
+    let single = Spline::new(vec![Point::new(1.0, 2.0, 3.0)]);
+    assert_eq!(single.length(), 0.0);
+    assert_eq!(single.sample(0.5), Point::new(1.0, 2.0, 3.0));
+}
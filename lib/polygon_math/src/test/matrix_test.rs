@@ -1,4 +1,11 @@
-use matrix::Matrix4;
+use angle::Rad;
+use matrix::{Matrix3, Matrix4};
+use orientation::Orientation;
+use point::Point;
+use quaternion::Quaternion;
+use vector::Vector3;
+use super::Random;
+use Dot;
 use super::test::{Bencher, black_box};
 
 #[test]
@@ -58,6 +65,239 @@ fn matrix_translation()
     assert!(translation_2[3][3] == 1.0);
 }
 
+#[test]
+fn matrix_orthographic() {
+    let ortho = Matrix4::orthographic(-2.0, 2.0, -1.0, 1.0, 1.0, 3.0);
+
+    // corners of the box map to corners of the unit cube
+    assert_eq!(Point::new(2.0, 1.0, -1.0) * ortho, Point::new(1.0, 1.0, -1.0));
+    assert_eq!(Point::new(-2.0, -1.0, -3.0) * ortho, Point::new(-1.0, -1.0, 1.0));
+
+    // no perspective divide, so depth doesn't affect x and y
+    assert_eq!(Point::new(1.0, 0.5, -1.0) * ortho, Point::new(0.5, 0.5, -1.0));
+    assert_eq!(Point::new(1.0, 0.5, -3.0) * ortho, Point::new(0.5, 0.5, 1.0));
+}
+
+/// Transforms `point` by `projection` and does the perspective divide, giving the point in
+/// normalized device coordinates.
+fn project(point: Point, projection: Matrix4) -> Vector3 {
+    let clip = point * projection;
+    Vector3::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w)
+}
+
+fn assert_projects_to(point: Point, projection: Matrix4, expected: Vector3) {
+    let projected = project(point, projection);
+    assert_approx_eq!(projected, expected, 1e-5);
+}
+
+#[test]
+fn matrix_perspective_off_center() {
+    let (left, right, bottom, top, near, far) = (-1.0, 3.0, -0.5, 2.0, 1.0, 10.0);
+    let projection = Matrix4::perspective_off_center(left, right, bottom, top, near, far);
+
+    // Corners of the near plane map to the front of the unit cube.
+    assert_projects_to(Point::new(left, bottom, -near), projection, Vector3::new(-1.0, -1.0, -1.0));
+    assert_projects_to(Point::new(right, top, -near), projection, Vector3::new(1.0, 1.0, -1.0));
+    assert_projects_to(Point::new(left, top, -near), projection, Vector3::new(-1.0, 1.0, -1.0));
+
+    // The far plane is larger by far / near, and maps to the back of the cube.
+    let scale = far / near;
+    assert_projects_to(Point::new(left * scale, bottom * scale, -far), projection, Vector3::new(-1.0, -1.0, 1.0));
+    assert_projects_to(Point::new(right * scale, top * scale, -far), projection, Vector3::new(1.0, 1.0, 1.0));
+
+    // The view direction isn't the center of the view.
+    let center = project(Point::new(0.0, 0.0, -5.0), projection);
+    assert_approx_eq!(center.x, -0.5, 1e-5);
+    assert_approx_eq!(center.y, -0.6, 1e-5);
+}
+
+#[test]
+fn matrix_perspective() {
+    let fov = ::std::f32::consts::PI / 3.0;
+    let projection = Matrix4::perspective(Rad(fov), 2.0, 0.5, 50.0);
+
+    let top = 0.5 * (fov * 0.5).tan();
+    assert_projects_to(Point::new(-2.0 * top, -top, -0.5), projection, Vector3::new(-1.0, -1.0, -1.0));
+    assert_projects_to(Point::new(2.0 * top, top, -0.5), projection, Vector3::new(1.0, 1.0, -1.0));
+    assert_projects_to(Point::new(0.0, 0.0, -50.0), projection, Vector3::new(0.0, 0.0, 1.0));
+
+    assert!(projection == Matrix4::perspective_off_center(-2.0 * top, 2.0 * top, -top, top, 0.5, 50.0));
+}
+
+#[test]
+fn matrix3_inverse() {
+    let matrix = Matrix3::rotation(0.3, -1.2, 2.0) * Matrix3::from_scale_vector(Vector3::new(2.0, 1.0, 0.5));
+    assert_approx_eq!(matrix * matrix.inverse().unwrap(), Matrix3::identity(), 1e-5);
+
+    // Flattening an axis can't be undone.
+    assert!(Matrix3::from_scale_vector(Vector3::new(1.0, 0.0, 1.0)).inverse().is_none());
+}
+
+#[test]
+fn matrix3_matches_matrix4() {
+    let mut random = Random::new();
+    for rotation in random.rotations(100) {
+        let vector = random.vector();
+        let expected = vector * Matrix4::from(rotation);
+
+        let matrix = Matrix3::from(rotation);
+        assert!(matrix == Matrix3::from(Matrix4::from(rotation)));
+        assert_approx_eq!(matrix * vector, expected, 1e-5);
+        assert_approx_eq!(vector * matrix, expected, 1e-5);
+        assert_approx_eq!(matrix.determinant(), 1.0, 1e-5);
+
+        // Rotation matrices are orthonormal, so the transpose undoes the rotation.
+        assert_approx_eq!(matrix.transpose(), matrix.inverse().unwrap(), 1e-5);
+        assert_approx_eq!(matrix.transpose() * expected, vector, 1e-5);
+    }
+
+    let rotation = Matrix3::rotation(0.3, -1.2, 2.0);
+    assert!(rotation == Matrix3::from(Matrix4::rotation(0.3, -1.2, 2.0)));
+    assert!(rotation.as_matrix4() == Matrix4::rotation(0.3, -1.2, 2.0));
+    assert!(rotation != Matrix3::identity());
+}
+
+#[test]
+fn matrix_inverse() {
+    let translation = Matrix4::translation(1.0, -2.0, 30.0);
+    let rotation = Matrix4::rotation(0.3, -1.2, 2.0);
+    let scale = Matrix4::scale(2.0, 1.0, 0.5);
+    let transform = translation * rotation * scale;
+
+    for &matrix in &[Matrix4::identity(), translation, rotation, scale, transform] {
+        let inverse = matrix.inverse().unwrap();
+        assert_approx_eq!(matrix * inverse, Matrix4::identity(), 1e-5);
+        assert_approx_eq!(inverse * matrix, Matrix4::identity(), 1e-5);
+    }
+
+    // Undoing the transform puts points back where they were.
+    let point = Point::new(3.0, 4.0, -5.0);
+    let moved = point * transform;
+    let restored = moved * transform.inverse().unwrap();
+    assert_approx_eq!(restored, point, 1e-4);
+
+    // Flattening an axis can't be undone.
+    assert!(Matrix4::scale(1.0, 1.0, 0.0).inverse().is_none());
+    assert!((translation * Matrix4::scale(0.0, 1.0, 1.0)).inverse().is_none());
+}
+
+#[test]
+fn matrix_inverse_projection() {
+    let ortho = Matrix4::orthographic(-2.0, 2.0, -1.0, 1.0, 1.0, 3.0);
+    assert_approx_eq!(ortho * ortho.inverse().unwrap(), Matrix4::identity(), 1e-5);
+
+    // A perspective projection doesn't have 0, 0, 0, 1 as its last row, so it takes the general
+    // path.
+    let (near, far) = (0.1, 100.0);
+    let mut perspective = Matrix4::new();
+    perspective[0][0] = 1.5;
+    perspective[1][1] = 2.0;
+    perspective[2][2] = -(far + near) / (far - near);
+    perspective[2][3] = -2.0 * far * near / (far - near);
+    perspective[3][2] = -1.0;
+    assert_approx_eq!(perspective * perspective.inverse().unwrap(), Matrix4::identity(), 1e-5);
+    assert_approx_eq!(perspective.inverse().unwrap() * perspective, Matrix4::identity(), 1e-5);
+
+    let view_projection = perspective * Matrix4::rotation(0.2, 0.4, -0.1) * Matrix4::translation(-3.0, 1.0, 2.0);
+    assert_approx_eq!(view_projection * view_projection.inverse().unwrap(), Matrix4::identity(), 1e-5);
+
+    // A matrix with two identical rows has no inverse.
+    let mut singular = perspective;
+    singular[3] = singular[2];
+    assert!(singular.inverse().is_none());
+}
+
+#[test]
+fn matrix_inverse_small_and_large_scale() {
+    let position = Point::new(4.0, -2.0, 7.0);
+    let rotation = Matrix4::rotation(0.3, -1.2, 2.0).to_quaternion();
+
+    for &scale in &[0.005, 1000.0] {
+        let scale_matrix = Matrix4::from_scale_vector(Vector3::new(scale, scale, scale));
+        assert_approx_eq!(scale_matrix * scale_matrix.inverse().unwrap(), Matrix4::identity(), 1e-5);
+
+        let trs = Matrix4::trs(position, rotation, Vector3::new(scale, scale * 2.0, scale * 0.5));
+        assert_approx_eq!(trs * trs.inverse().unwrap(), Matrix4::identity(), 1e-4);
+
+        let linear = Matrix3::from(trs);
+        assert_approx_eq!(linear * linear.inverse().unwrap(), Matrix3::identity(), 1e-4);
+
+        // Projecting a scaled object takes the general path.
+        let mut perspective = Matrix4::new();
+        perspective[0][0] = 1.5;
+        perspective[1][1] = 2.0;
+        perspective[2][2] = -1.002;
+        perspective[2][3] = -0.2002;
+        perspective[3][2] = -1.0;
+        let projected = perspective * trs;
+        assert_approx_eq!(projected * projected.inverse().unwrap(), Matrix4::identity(), 1e-4);
+    }
+
+    // Flattening an axis still makes a small matrix singular.
+    assert!(Matrix4::from_scale_vector(Vector3::new(0.005, 0.005, 0.0)).inverse().is_none());
+    assert!(Matrix3::from(Matrix4::from_scale_vector(Vector3::new(1000.0, 0.0, 1000.0))).inverse().is_none());
+}
+
+#[test]
+fn matrix_determinant() {
+    assert_eq!(Matrix4::identity().determinant(), 1.0);
+    assert_approx_eq!(Matrix4::scale(2.0, 3.0, 4.0).determinant(), 24.0, 1e-5);
+    assert_approx_eq!(Matrix4::rotation(0.3, -1.2, 2.0).determinant(), 1.0, 1e-5);
+}
+
+#[test]
+fn matrix_trs() {
+    let position = Point::new(1.0, -2.0, 3.0);
+    let rotation = Matrix4::rotation(0.3, -1.2, 2.0).to_quaternion();
+    let scale = Vector3::new(2.0, 1.0, 0.5);
+
+    let composed = Matrix4::from_point(position) * Matrix4::from_orientation(Orientation(rotation)) * Matrix4::from_scale_vector(scale);
+    assert_approx_eq!(Matrix4::trs(position, rotation, scale), composed, 1e-4);
+    assert!(Matrix4::trs(Point::origin(), Quaternion::identity(), Vector3::one()) == Matrix4::identity());
+}
+
+#[test]
+fn matrix_decompose_round_trip() {
+    let mut random = Random::new();
+    for rotation in random.rotations(500) {
+        let position = Point::from(random.vector() * 100.0);
+
+        // Keep the scale away from zero, and mirror along x half of the time.
+        let mut scale = random.vector();
+        scale = Vector3::new(scale.x.signum() * (scale.x.abs() * 4.0 + 0.1), scale.y.abs() * 4.0 + 0.1, scale.z.abs() * 4.0 + 0.1);
+
+        let matrix = Matrix4::trs(position, rotation, scale);
+        let (decomposed_position, decomposed_rotation, decomposed_scale) = matrix.decompose().unwrap();
+        assert_approx_eq!(decomposed_position, position, 1e-4);
+        assert_approx_eq!(decomposed_scale, scale, 1e-4);
+        assert_eq!(matrix.translation_part(), decomposed_position);
+        assert_eq!(matrix.scale_part(), decomposed_scale);
+
+        // Mirroring along x is the same as mirroring along y and z and turning halfway around x,
+        // so only the rebuilt matrix has to match when mirrored.
+        if scale.x > 0.0 {
+            let same = Quaternion::dot(decomposed_rotation, rotation).abs();
+            assert_approx_eq!(same, 1.0, 1e-4);
+        }
+        assert_approx_eq!(Matrix4::trs(decomposed_position, decomposed_rotation, decomposed_scale), matrix, 1e-4);
+        assert_approx_eq!(Matrix4::trs(decomposed_position, matrix.rotation_part(), decomposed_scale), matrix, 1e-4);
+    }
+}
+
+#[test]
+fn matrix_decompose_invalid() {
+    // Flattening an axis loses the rotation around it.
+    assert!(Matrix4::scale(1.0, 0.0, 1.0).decompose().is_none());
+
+    // Scaling along an axis that isn't one of the matrix's axes shears it.
+    let rotation = Matrix4::rotation(0.0, 0.0, 0.5);
+    let sheared = Matrix4::scale(3.0, 1.0, 1.0) * rotation;
+    assert!(sheared.decompose().is_none());
+
+    let perspective = Matrix4::perspective(Rad(1.0), 1.5, 0.1, 100.0);
+    assert!(perspective.decompose().is_none());
+}
+
 #[bench]
 fn bench_multiply(bencher: &mut Bencher) {
     let first = Matrix4::identity();
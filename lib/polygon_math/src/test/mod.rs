@@ -1,4 +1,53 @@
 extern crate test;
 
+use quaternion::Quaternion;
+use vector::Vector3;
+
+mod aabb_test;
+mod angle_test;
+mod approx_test;
+mod color_test;
+mod frustum_test;
+mod interp_test;
 mod matrix_test;
+mod orientation_test;
 mod quaternion_test;
+mod ray_test;
+mod sphere_test;
+mod vector_test;
+
+/// Generates pseudo-random values for tests, using a fixed seed so that failures are
+/// reproducible.
+pub struct Random(u32);
+
+impl Random {
+    pub fn new() -> Random {
+        Random(0x2545_f491)
+    }
+
+    /// Gets the next value in `-1..1`.
+    pub fn value(&mut self) -> f32 {
+        // Xorshift.
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / ::std::u32::MAX as f32 * 2.0 - 1.0
+    }
+
+    /// Gets a vector with each component in `-1..1`.
+    pub fn vector(&mut self) -> Vector3 {
+        Vector3::new(self.value(), self.value(), self.value())
+    }
+
+    /// Generates unit quaternions spread over the whole hypersphere.
+    pub fn rotations(&mut self, count: usize) -> Vec<Quaternion> {
+        let mut rotations = Vec::with_capacity(count);
+        while rotations.len() < count {
+            let quat = Quaternion::new(self.vector(), self.value());
+            if quat.magnitude() > 0.1 {
+                rotations.push(quat.normalized());
+            }
+        }
+        rotations
+    }
+}
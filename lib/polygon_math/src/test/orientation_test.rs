@@ -0,0 +1,74 @@
+use orientation::Orientation;
+use vector::Vector3;
+use {Dot, IsZero};
+
+/// Checks that `orientation` looks along `forward` and that its up axis is in the plane of
+/// `forward` and `up`, on the same side as `up`.
+fn assert_looks_along(orientation: Orientation, forward: Vector3, up: Vector3) {
+    let forward = forward.normalized();
+    assert_approx_eq!(orientation.forward(), forward, 1e-5);
+
+    let rotated_up = orientation.up();
+    assert!(rotated_up.dot(forward).abs() < 1e-5, "Up {:?} isn't perpendicular to forward {:?}", rotated_up, forward);
+    assert!(rotated_up.dot(Vector3::cross(forward, up)).abs() < 1e-5, "Up {:?} isn't in the plane of {:?} and {:?}", rotated_up, forward, up);
+    assert!(rotated_up.dot(up) >= 0.0, "Up {:?} faces away from {:?}", rotated_up, up);
+}
+
+#[test]
+fn look_rotation() {
+    let directions = [
+        Vector3::new(1.0, 0.0, 0.0),
+        Vector3::new(-1.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, -1.0),
+        Vector3::new(1.0, 2.0, -3.0),
+        Vector3::new(-0.5, -1.0, 0.25),
+        Vector3::new(0.0, 1.0, 1.0),
+    ];
+    let ups = [
+        Vector3::new(0.0, 1.0, 0.0),
+        Vector3::new(1.0, 0.0, 0.0),
+        Vector3::new(0.3, 1.0, -0.7),
+        Vector3::new(0.0, -1.0, 0.0),
+    ];
+
+    for &forward in &directions {
+        for &up in &ups {
+            if Vector3::cross(forward, up).is_zero() {
+                continue;
+            }
+
+            assert_looks_along(Orientation::look_rotation(forward, up), forward, up);
+        }
+    }
+}
+
+#[test]
+fn look_rotation_rolled() {
+    // Looking straight ahead with a sideways up vector rolls the object onto its side.
+    let orientation = Orientation::look_rotation(Vector3::forward(), Vector3::right());
+    assert_approx_eq!(orientation.forward(), Vector3::forward(), 1e-5);
+    assert_approx_eq!(orientation.up(), Vector3::right(), 1e-5);
+    assert_approx_eq!(orientation.right(), Vector3::down(), 1e-5);
+}
+
+#[test]
+fn look_rotation_backwards() {
+    let orientation = Orientation::look_rotation(Vector3::back(), Vector3::up());
+    assert_approx_eq!(orientation.forward(), Vector3::back(), 1e-5);
+    assert_approx_eq!(orientation.up(), Vector3::up(), 1e-5);
+    assert_approx_eq!(orientation.right(), Vector3::left(), 1e-5);
+
+    // The up vector doesn't have to be perpendicular to the forward vector.
+    let up = Vector3::new(0.0, 1.0, 1.0);
+    assert_looks_along(Orientation::look_rotation(Vector3::back(), up), Vector3::back(), up);
+}
+
+#[test]
+fn look_rotation_parallel_up() {
+    // There's no way to tell how to roll, but forward still has to be correct.
+    for &forward in &[Vector3::up(), Vector3::down(), Vector3::new(2.0, 0.0, 0.0), Vector3::back()] {
+        let orientation = Orientation::look_rotation(forward, forward);
+        assert_approx_eq!(orientation.forward(), forward.normalized(), 1e-5);
+        assert!(orientation.up().dot(forward).abs() < 1e-5);
+    }
+}
@@ -1,8 +1,21 @@
 use std::f32::consts::PI;
 
-use quaternion::Quaternion;
+use angle::Rad;
+use orientation::Orientation;
+use point::Point;
+use quaternion::{EulerOrder, Quaternion};
+use Dot;
+use super::Random;
 use vector::Vector3;
-use matrix::Matrix4;
+use matrix::{Matrix3, Matrix4};
+
+fn axis_angle(axis: Vector3, angle: f32) -> Quaternion {
+    Orientation::axis_angle(axis, Rad(angle)).0
+}
+
+fn to_matrix(quat: Quaternion) -> Matrix4 {
+    Orientation(quat).into()
+}
 
 #[test]
 fn multiplication() {
@@ -10,24 +23,380 @@ fn multiplication() {
     let identity = Quaternion::identity();
     assert_eq!(identity * identity, identity);
 
-    let quat = Quaternion::axis_angle(Vector3::new(1.0, 0.0, 0.0), PI);
+    let quat = axis_angle(Vector3::new(1.0, 0.0, 0.0), PI);
     assert_eq!(identity * quat, quat);
     assert_eq!(quat * identity, quat);
 }
 
 #[test]
 fn as_matrix() {
-    assert_eq!(Quaternion::identity().as_matrix(), Matrix4::identity());
+    assert_eq!(to_matrix(Quaternion::identity()), Matrix4::identity());
+
+    assert_eq!(to_matrix(axis_angle(Vector3::new(1.0, 0.0, 0.0), PI)), Matrix4::rotation(PI, 0.0, 0.0));
+    assert_eq!(to_matrix(axis_angle(Vector3::new(0.0, 1.0, 0.0), PI)), Matrix4::rotation(0.0, PI, 0.0));
+    assert_eq!(to_matrix(axis_angle(Vector3::new(0.0, 0.0, 1.0), PI)), Matrix4::rotation(0.0, 0.0, PI));
+
+    assert_eq!(to_matrix(axis_angle(Vector3::new(1.0, 0.0, 0.0), PI * 0.5)), Matrix4::rotation(PI * 0.5, 0.0, 0.0));
+    assert_eq!(to_matrix(axis_angle(Vector3::new(0.0, 1.0, 0.0), PI * 0.5)), Matrix4::rotation(0.0, PI * 0.5, 0.0));
+    assert_eq!(to_matrix(axis_angle(Vector3::new(0.0, 0.0, 1.0), PI * 0.5)), Matrix4::rotation(0.0, 0.0, PI * 0.5));
+
+    assert_eq!(to_matrix(axis_angle(Vector3::new(1.0, 0.0, 0.0), 0.5)), Matrix4::rotation(0.5, 0.0, 0.0));
+    assert_eq!(to_matrix(axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.5)), Matrix4::rotation(0.0, 0.5, 0.0));
+    assert_eq!(to_matrix(axis_angle(Vector3::new(0.0, 0.0, 1.0), 0.5)), Matrix4::rotation(0.0, 0.0, 0.5));
+}
+
+#[test]
+fn nlerp() {
+    let first = axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.0);
+    let second = axis_angle(Vector3::new(0.0, 1.0, 0.0), PI * 0.5);
+
+    // The ends are returned exactly.
+    assert_eq!(Quaternion::nlerp(first, second, 0.0), first);
+    assert_eq!(Quaternion::nlerp(first, second, 1.0), second);
+
+    // Halfway between two rotations about the same axis is halfway around the axis.
+    assert_approx_eq!(Quaternion::nlerp(first, second, 0.5), axis_angle(Vector3::new(0.0, 1.0, 0.0), PI * 0.25), 1e-5);
+
+    // The result is always normalized.
+    for step in 0..11 {
+        assert!(Quaternion::nlerp(first, second, step as f32 / 10.0).is_normalized());
+    }
+
+    // The negated quaternion is the same rotation, so it's used to take the shortest path.
+    let negated = second * -1.0;
+    assert_eq!(Quaternion::nlerp(first, negated, 1.0), second);
+    assert_approx_eq!(Quaternion::nlerp(first, negated, 0.5), axis_angle(Vector3::new(0.0, 1.0, 0.0), PI * 0.25), 1e-5);
+}
+
+#[test]
+fn slerp() {
+    let axis = Vector3::new(1.0, 0.0, 0.0);
+    let first = axis_angle(axis, 0.2);
+    let second = axis_angle(axis, 1.4);
+
+    assert_eq!(Quaternion::slerp(first, second, 0.0), first);
+    assert_eq!(Quaternion::slerp(first, second, 1.0), second);
+
+    // Unlike `nlerp()` the rotation is at a constant speed, so each step is the same angle.
+    for step in 0..11 {
+        let t = step as f32 / 10.0;
+        let result = Quaternion::slerp(first, second, t);
+        assert!(result.is_normalized(), "{:?} isn't normalized at t = {}", result, t);
+        assert_approx_eq!(result, axis_angle(axis, 0.2 + 1.2 * t), 1e-5);
+    }
+
+    // Takes the shortest path when the second quaternion is on the far side of the hypersphere.
+    let negated = second * -1.0;
+    assert_eq!(Quaternion::slerp(first, negated, 1.0), second);
+    assert_approx_eq!(Quaternion::slerp(first, negated, 0.5), axis_angle(axis, 0.8), 1e-5);
+}
+
+#[test]
+fn slerp_antipodal() {
+    // Rotations half a turn apart are a quarter turn apart on the hypersphere, so there's no
+    // division by zero.
+    let axis = Vector3::new(0.0, 0.0, 1.0);
+    let half_turn = axis_angle(axis, PI);
+    let result = Quaternion::slerp(Quaternion::identity(), half_turn, 0.5);
+    assert_approx_eq!(result, axis_angle(axis, PI * 0.5), 1e-5);
+
+    // `q` and `-q` are opposite on the hypersphere but the same rotation, so there's nothing to
+    // interpolate.
+    let quat = axis_angle(Vector3::new(0.0, 1.0, 0.0), 1.0);
+    for step in 0..11 {
+        let t = step as f32 / 10.0;
+        assert_approx_eq!(Quaternion::slerp(quat, quat * -1.0, t), quat, 1e-5);
+    }
+}
+
+#[test]
+fn slerp_nearly_parallel() {
+    // The angle between these is too small to divide by its sine accurately, so they're
+    // interpolated with `nlerp()`.
+    let axis = Vector3::new(0.0, 1.0, 0.0);
+    let first = axis_angle(axis, 1.0);
+    let second = axis_angle(axis, 1.0 + 1e-4);
+
+    assert_eq!(Quaternion::slerp(first, second, 0.0), first);
+    assert_eq!(Quaternion::slerp(first, second, 1.0), second);
+    assert_approx_eq!(Quaternion::slerp(first, first, 0.5), first, 1e-5);
+
+    let result = Quaternion::slerp(first, second, 0.5);
+    assert!(result.is_normalized(), "{:?} isn't normalized", result);
+    assert_approx_eq!(result, axis_angle(axis, 1.0 + 5e-5), 1e-5);
+}
+
+#[test]
+fn magnitude() {
+    let quat = Quaternion::new(Vector3::new(1.0, 2.0, 2.0), 4.0);
+    assert_eq!(quat.magnitude_squared(), 25.0);
+    assert_eq!(quat.magnitude(), 5.0);
+    assert_eq!(Quaternion::identity().magnitude(), 1.0);
+    assert_eq!(Quaternion::new(Vector3::new(0.0, 0.0, 0.0), 0.0).magnitude(), 0.0);
+}
+
+#[test]
+fn normalize() {
+    let quat = Quaternion::new(Vector3::new(1.0, 2.0, 2.0), 4.0);
+    assert!(!quat.is_normalized());
+
+    let normalized = quat.normalized();
+    assert!(normalized.is_normalized());
+    assert_approx_eq!(normalized, Quaternion::new(Vector3::new(0.2, 0.4, 0.4), 0.8), 1e-5);
+
+    let mut in_place = quat;
+    in_place.normalize();
+    assert_eq!(in_place, normalized);
+
+    assert!(Quaternion::identity().is_normalized());
+    assert!(axis_angle(Vector3::new(0.0, 1.0, 0.0), 2.0).is_normalized());
+}
+
+#[test]
+#[should_panic(expected = "Cannot normalize a zero quaternion")]
+fn normalize_zero() {
+    Quaternion::new(Vector3::new(0.0, 0.0, 0.0), 0.0).normalized();
+}
+
+#[test]
+fn dot() {
+    let first = Quaternion::new(Vector3::new(1.0, 2.0, 3.0), 4.0);
+    let second = Quaternion::new(Vector3::new(5.0, 6.0, 7.0), 8.0);
+    assert_eq!(first.dot(second), 70.0);
+    assert_eq!(Quaternion::dot(second, first), 70.0);
+
+    // The dot product of rotations about the same axis is the cosine of half the angle between
+    // them.
+    let axis = Vector3::new(1.0, 0.0, 0.0);
+    let dot = axis_angle(axis, 0.5).dot(axis_angle(axis, 1.5));
+    assert_approx_eq!(dot, 0.5f32.cos(), 1e-6);
+}
+
+#[test]
+fn conjugate() {
+    let quat = Quaternion::new(Vector3::new(1.0, -2.0, 3.0), 4.0);
+    assert_eq!(quat.conjugate(), Quaternion::new(Vector3::new(-1.0, 2.0, -3.0), 4.0));
+    assert_eq!(quat.conjugate().conjugate(), quat);
+
+    // The conjugate of a rotation undoes it.
+    let rotation = axis_angle(Vector3::new(0.0, 0.0, 1.0), 1.2);
+    assert_approx_eq!(rotation * rotation.conjugate(), Quaternion::identity(), 1e-5);
+    assert_approx_eq!(rotation.conjugate(), axis_angle(Vector3::new(0.0, 0.0, 1.0), -1.2), 1e-5);
+}
+
+#[test]
+fn inverse() {
+    // Inverting a rotation is the same as conjugating it.
+    let rotation = axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.7);
+    assert_approx_eq!(rotation.inverse(), rotation.conjugate(), 1e-5);
 
-    assert_eq!(Quaternion::axis_angle(Vector3::new(1.0, 0.0, 0.0), PI).as_matrix(), Matrix4::rotation(PI, 0.0, 0.0));
-    assert_eq!(Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), PI).as_matrix(), Matrix4::rotation(0.0, PI, 0.0));
-    assert_eq!(Quaternion::axis_angle(Vector3::new(0.0, 0.0, 1.0), PI).as_matrix(), Matrix4::rotation(0.0, 0.0, PI));
+    // Quaternions that aren't normalized are still inverted correctly.
+    let quat = Quaternion::new(Vector3::new(1.0, 2.0, 2.0), 4.0);
+    assert_approx_eq!(quat * quat.inverse(), Quaternion::identity(), 1e-5);
+    assert_approx_eq!(quat.inverse() * quat, Quaternion::identity(), 1e-5);
+    assert_approx_eq!(quat.inverse(), Quaternion::new(Vector3::new(-0.04, -0.08, -0.08), 0.16), 1e-5);
+}
+
+#[test]
+#[should_panic(expected = "Cannot invert a zero quaternion")]
+fn inverse_zero() {
+    Quaternion::new(Vector3::new(0.0, 0.0, 0.0), 0.0).inverse();
+}
+
+#[test]
+fn rotate_vector() {
+    let x = Vector3::new(1.0, 0.0, 0.0);
+    let y = Vector3::new(0.0, 1.0, 0.0);
+    let z = Vector3::new(0.0, 0.0, 1.0);
+
+    // A quarter turn about each axis moves the other two axes onto each other, counterclockwise
+    // when looking down the axis.
+    assert_approx_eq!(axis_angle(x, PI * 0.5) * y, z, 1e-5);
+    assert_approx_eq!(axis_angle(x, PI * 0.5) * z, -y, 1e-5);
+    assert_approx_eq!(axis_angle(y, PI * 0.5) * z, x, 1e-5);
+    assert_approx_eq!(axis_angle(y, PI * 0.5) * x, -z, 1e-5);
+    assert_approx_eq!(axis_angle(z, PI * 0.5) * x, y, 1e-5);
+    assert_approx_eq!(axis_angle(z, PI * 0.5) * y, -x, 1e-5);
+
+    // Vectors along the axis aren't moved.
+    for &axis in &[x, y, z] {
+        assert_approx_eq!(axis_angle(axis, PI * 0.5) * axis, axis, 1e-5);
+    }
+
+    // Rotating matches converting the quaternion to a matrix and transforming by that.
+    let vector = Vector3::new(0.3, -1.2, 2.5);
+    for &axis in &[x, y, z, Vector3::new(1.0, 1.0, -1.0).normalized()] {
+        for &angle in &[PI * 0.5, PI, 0.3, -2.0] {
+            let quat = axis_angle(axis, angle);
+            assert_approx_eq!(quat * vector, vector * to_matrix(quat), 1e-5);
+        }
+    }
+
+    assert_eq!(Quaternion::identity() * vector, vector);
+}
+
+#[test]
+fn rotate_point() {
+    let quat = axis_angle(Vector3::new(0.0, 0.0, 1.0), PI * 0.5);
+    let rotated = quat.rotate_point(Point::new(2.0, 0.0, 3.0));
+    assert_approx_eq!(rotated, Point::new(0.0, 2.0, 3.0), 1e-5);
+    assert_eq!(Orientation(quat) * Vector3::new(2.0, 0.0, 3.0), quat * Vector3::new(2.0, 0.0, 3.0));
+}
+
+/// Checks that two normalized quaternions represent the same rotation, given that `q` and `-q`
+/// are the same rotation.
+fn assert_same_rotation(actual: Quaternion, expected: Quaternion) {
+    let expected = if Quaternion::dot(actual, expected) < 0.0 { expected * -1.0 } else { expected };
+    assert_approx_eq!(actual, expected, 1e-5);
+}
+
+#[test]
+fn from_matrix() {
+    let x = Vector3::new(1.0, 0.0, 0.0);
+    let y = Vector3::new(0.0, 1.0, 0.0);
+    let z = Vector3::new(0.0, 0.0, 1.0);
+
+    assert_same_rotation(Quaternion::from_matrix(&Matrix4::identity()), Quaternion::identity());
+
+    // Half turns are where the trace is smallest, and each picks a different branch.
+    for &axis in &[x, y, z, Vector3::new(1.0, -1.0, 1.0).normalized()] {
+        let quat = axis_angle(axis, PI);
+        assert_same_rotation(Quaternion::from_matrix(&to_matrix(quat)), quat);
+    }
+
+    // Translation doesn't affect the rotation.
+    let quat = axis_angle(y, 1.2);
+    let matrix = Matrix4::translation(1.0, -2.0, 3.0) * to_matrix(quat);
+    assert_same_rotation(matrix.to_quaternion(), quat);
+}
+
+#[test]
+fn from_matrix_round_trip() {
+    for quat in Random::new().rotations(1000) {
+        assert_same_rotation(Quaternion::from_matrix(&to_matrix(quat)), quat);
+    }
+}
+
+#[test]
+fn to_eulers() {
+    let eulers = Orientation::from_eulers(Rad(0.3), Rad(-0.7), Rad(1.1)).0.to_eulers();
+    assert_approx_eq!(eulers, Vector3::new(0.3, -0.7, 1.1), 1e-5);
+
+    // The angles match `Matrix4::rotation()` as well.
+    let quat = Matrix4::rotation(-2.0, 0.4, 2.5).to_quaternion();
+    let eulers = quat.to_eulers();
+    assert_approx_eq!(eulers, Vector3::new(-2.0, 0.4, 2.5), 1e-5);
+    assert_eq!(Orientation(quat).as_eulers(), eulers);
+}
+
+#[test]
+fn to_eulers_round_trip() {
+    for quat in Random::new().rotations(1000) {
+        let Vector3 { x, y, z } = quat.to_eulers();
+        assert_same_rotation(Orientation::from_eulers(Rad(x), Rad(y), Rad(z)).0, quat);
+    }
+}
+
+#[test]
+fn to_eulers_gimbal_lock() {
+    // With the y angle at 90 degrees, rotating about z is the same as rotating about x, so it all
+    // ends up in x.
+    for &pitch in &[PI * 0.5, -PI * 0.5] {
+        let quat = Orientation::from_eulers(Rad(0.4), Rad(pitch), Rad(0.3)).0;
+        let eulers = quat.to_eulers();
+        assert_approx_eq!(eulers.y, pitch, 1e-3);
+        assert_eq!(eulers.z, 0.0);
+        assert_same_rotation(Orientation::from_eulers(Rad(eulers.x), Rad(eulers.y), Rad(eulers.z)).0, quat);
+    }
+}
+
+/// The axes that each order multiplies the rotations in, written out separately from
+/// `EulerOrder`.
+const ORDERS: [(EulerOrder, [usize; 3]); 6] = [
+    (EulerOrder::Xyz, [0, 1, 2]),
+    (EulerOrder::Xzy, [0, 2, 1]),
+    (EulerOrder::Yxz, [1, 0, 2]),
+    (EulerOrder::Yzx, [1, 2, 0]),
+    (EulerOrder::Zxy, [2, 0, 1]),
+    (EulerOrder::Zyx, [2, 1, 0]),
+];
+
+/// Builds the matrix for a rotation about a single axis directly from the angle.
+fn axis_matrix(axis: usize, angle: f32) -> Matrix3 {
+    let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+    let mut matrix = Matrix3::identity();
+    matrix[a][a] = angle.cos();
+    matrix[a][b] = -angle.sin();
+    matrix[b][a] = angle.sin();
+    matrix[b][b] = angle.cos();
+    matrix
+}
+
+fn euler_matrix(angles: Vector3, axes: [usize; 3]) -> Matrix3 {
+    axis_matrix(axes[0], angles[axes[0]])
+        * axis_matrix(axes[1], angles[axes[1]])
+        * axis_matrix(axes[2], angles[axes[2]])
+}
+
+#[test]
+fn from_eulers_order() {
+    let triples = [
+        Vector3::new(0.3, -0.7, 1.1),
+        Vector3::new(-2.0, 0.4, 2.5),
+        Vector3::new(1.5, -1.2, -0.1),
+    ];
+    for &(order, axes) in &ORDERS {
+        for &angles in &triples {
+            let quat = Quaternion::from_eulers_order(Rad(angles.x), Rad(angles.y), Rad(angles.z), order);
+            assert_approx_eq!(Matrix3::from(quat), euler_matrix(angles, axes), 1e-5);
+
+            // The extracted angles may differ, but they have to give the same rotation.
+            let eulers = quat.to_eulers_order(order);
+            assert_approx_eq!(euler_matrix(eulers, axes), euler_matrix(angles, axes), 1e-5);
+        }
+
+        // Angles that are already in range come back unchanged.
+        let quat = Quaternion::from_eulers_order(Rad(0.3), Rad(-0.7), Rad(1.1), order);
+        assert_approx_eq!(quat.to_eulers_order(order), Vector3::new(0.3, -0.7, 1.1), 1e-5);
+    }
+
+    // The default order is the one the other euler angle functions use.
+    assert_eq!(EulerOrder::default(), EulerOrder::Xyz);
+    let quat = Quaternion::from_eulers(Rad(0.3), Rad(-0.7), Rad(1.1));
+    assert_approx_eq!(quat, Orientation::from_eulers(Rad(0.3), Rad(-0.7), Rad(1.1)).0);
+    assert_approx_eq!(Matrix3::from(quat), Matrix3::rotation(0.3, -0.7, 1.1), 1e-5);
+    assert_eq!(quat.to_eulers(), quat.to_eulers_order(EulerOrder::Xyz));
+}
+
+#[test]
+fn to_eulers_order_round_trip() {
+    let rotations = Random::new().rotations(200);
+    for &(order, _) in &ORDERS {
+        for &quat in &rotations {
+            let Vector3 { x, y, z } = quat.to_eulers_order(order);
+            assert_same_rotation(Quaternion::from_eulers_order(Rad(x), Rad(y), Rad(z), order), quat);
+        }
+    }
+}
+
+#[test]
+fn to_eulers_order_gimbal_lock() {
+    // With the middle angle at 90 degrees the first and last axes line up, so it all ends up in
+    // the first angle.
+    for &(order, axes) in &ORDERS {
+        for &middle in &[PI * 0.5, -PI * 0.5] {
+            let mut angles = Vector3::zero();
+            angles[axes[0]] = 0.4;
+            angles[axes[1]] = middle;
+            angles[axes[2]] = 0.3;
 
-    assert_eq!(Quaternion::axis_angle(Vector3::new(1.0, 0.0, 0.0), PI * 0.5).as_matrix(), Matrix4::rotation(PI * 0.5, 0.0, 0.0));
-    assert_eq!(Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), PI * 0.5).as_matrix(), Matrix4::rotation(0.0, PI * 0.5, 0.0));
-    assert_eq!(Quaternion::axis_angle(Vector3::new(0.0, 0.0, 1.0), PI * 0.5).as_matrix(), Matrix4::rotation(0.0, 0.0, PI * 0.5));
+            let quat = Quaternion::from_eulers_order(Rad(angles.x), Rad(angles.y), Rad(angles.z), order);
+            assert_approx_eq!(Matrix3::from(quat), euler_matrix(angles, axes), 1e-5);
 
-    assert_eq!(Quaternion::axis_angle(Vector3::new(1.0, 0.0, 0.0), 0.5).as_matrix(), Matrix4::rotation(0.5, 0.0, 0.0));
-    assert_eq!(Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.5).as_matrix(), Matrix4::rotation(0.0, 0.5, 0.0));
-    assert_eq!(Quaternion::axis_angle(Vector3::new(0.0, 0.0, 1.0), 0.5).as_matrix(), Matrix4::rotation(0.0, 0.0, 0.5));
+            let eulers = quat.to_eulers_order(order);
+            assert_approx_eq!(eulers[axes[1]], middle, 1e-3);
+            assert_eq!(eulers[axes[2]], 0.0);
+            assert_approx_eq!(euler_matrix(eulers, axes), euler_matrix(angles, axes), 1e-5);
+        }
+    }
 }
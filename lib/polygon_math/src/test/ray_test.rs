@@ -0,0 +1,144 @@
+use aabb::Aabb;
+use angle::Rad;
+use matrix::Matrix4;
+use plane::Plane;
+use point::Point;
+use ray::Ray;
+use sphere::Sphere;
+use vector::{Vector2, Vector3};
+
+#[test]
+fn plane_distance() {
+    let plane = Plane::from_point_normal(Point::new(0.0, 2.0, 0.0), Vector3::new(0.0, 3.0, 0.0));
+    assert_eq!(plane, Plane::new(Vector3::up(), 2.0));
+    assert_eq!(plane.signed_distance(Point::new(5.0, 3.0, -1.0)), 1.0);
+    assert_eq!(plane.signed_distance(Point::new(5.0, -1.0, -1.0)), -3.0);
+    assert_eq!(plane.closest_point(Point::new(5.0, -1.0, -1.0)), Point::new(5.0, 2.0, -1.0));
+}
+
+#[test]
+fn ray_plane() {
+    let plane = Plane::new(Vector3::up(), 2.0);
+
+    let ray = Ray::new(Point::origin(), Vector3::new(1.0, 1.0, 0.0));
+    let t = ray.intersect_plane(&plane).unwrap();
+    assert_approx_eq!(ray.point_at(t), Point::new(2.0, 2.0, 0.0), 1e-4);
+
+    // Planes are hit from behind as well.
+    let ray = Ray::new(Point::new(0.0, 5.0, 0.0), Vector3::down());
+    assert_eq!(ray.intersect_plane(&plane), Some(3.0));
+
+    // The plane is behind the ray.
+    let ray = Ray::new(Point::new(0.0, 5.0, 0.0), Vector3::up());
+    assert_eq!(ray.intersect_plane(&plane), None);
+
+    // Rays parallel to the plane never hit it, even when they're in it.
+    let ray = Ray::new(Point::origin(), Vector3::right());
+    assert_eq!(ray.intersect_plane(&plane), None);
+    let ray = Ray::new(Point::new(0.0, 2.0, 0.0), Vector3::right());
+    assert_eq!(ray.intersect_plane(&plane), None);
+}
+
+#[test]
+fn ray_sphere() {
+    let sphere = Sphere::new(Point::new(0.0, 0.0, -10.0), 2.0);
+
+    let ray = Ray::new(Point::origin(), Vector3::forward());
+    assert_eq!(ray.intersect_sphere(&sphere), Some(8.0));
+
+    // Grazing the edge of the sphere.
+    let ray = Ray::new(Point::new(2.0, 0.0, 0.0), Vector3::forward());
+    assert_eq!(ray.intersect_sphere(&sphere), Some(10.0));
+
+    // Misses to the side and behind.
+    let ray = Ray::new(Point::new(2.1, 0.0, 0.0), Vector3::forward());
+    assert_eq!(ray.intersect_sphere(&sphere), None);
+    let ray = Ray::new(Point::origin(), Vector3::back());
+    assert_eq!(ray.intersect_sphere(&sphere), None);
+
+    // Starting inside the sphere hits immediately, whichever way the ray points.
+    let ray = Ray::new(Point::new(0.0, 1.0, -10.0), Vector3::back());
+    assert_eq!(ray.intersect_sphere(&sphere), Some(0.0));
+}
+
+#[test]
+fn ray_aabb() {
+    let aabb = Aabb::new(Point::new(-1.0, -1.0, -6.0), Point::new(1.0, 1.0, -4.0));
+
+    let ray = Ray::new(Point::origin(), Vector3::forward());
+    assert_eq!(ray.intersect_aabb(&aabb), Some(4.0));
+
+    // Diagonal rays enter through whichever face they reach last.
+    let ray = Ray::new(Point::new(-3.0, 0.0, -2.0), Vector3::new(1.0, 0.0, -1.0));
+    let t = ray.intersect_aabb(&aabb).unwrap();
+    assert_approx_eq!(ray.point_at(t), Point::new(-1.0, 0.0, -4.0), 1e-4);
+
+    // Misses to the side and behind.
+    assert_eq!(Ray::new(Point::new(1.5, 0.0, 0.0), Vector3::forward()).intersect_aabb(&aabb), None);
+    assert_eq!(Ray::new(Point::origin(), Vector3::back()).intersect_aabb(&aabb), None);
+    assert_eq!(Ray::new(Point::new(0.0, 0.0, -2.0), Vector3::new(1.0, 0.0, -1.0)).intersect_aabb(&aabb), None);
+
+    // Starting inside the box hits immediately.
+    assert_eq!(Ray::new(Point::new(0.0, 0.0, -5.0), Vector3::up()).intersect_aabb(&aabb), Some(0.0));
+
+    // Rays parallel to a pair of faces only hit if they're between them, including on a face.
+    assert_eq!(Ray::new(Point::new(1.0, 0.0, 0.0), Vector3::forward()).intersect_aabb(&aabb), Some(4.0));
+    assert_eq!(Ray::new(Point::new(0.0, 2.0, -5.0), Vector3::right()).intersect_aabb(&aabb), None);
+}
+
+#[test]
+fn ray_triangle() {
+    let (a, b, c) = (Point::new(0.0, 0.0, -5.0), Point::new(4.0, 0.0, -5.0), Point::new(0.0, 4.0, -5.0));
+
+    let ray = Ray::new(Point::new(1.0, 2.0, 0.0), Vector3::forward());
+    let (t, barycentric) = ray.intersect_triangle(a, b, c).unwrap();
+    assert_approx_eq!(t, 5.0, 1e-5);
+    assert_approx_eq!(barycentric, Vector3::new(0.25, 0.25, 0.5), 1e-5);
+
+    // The barycentrics interpolate the corners to the hit.
+    let hit = Point::from(
+        Vector3::from(a) * barycentric.x + Vector3::from(b) * barycentric.y + Vector3::from(c) * barycentric.z);
+    assert_approx_eq!(hit, ray.point_at(t), 1e-4);
+
+    // Triangles are hit from behind as well.
+    let ray = Ray::new(Point::new(1.0, 2.0, -10.0), Vector3::back());
+    assert!(ray.intersect_triangle(a, b, c).is_some());
+
+    // Misses outside of the edges, behind the ray, and parallel to the triangle.
+    assert!(Ray::new(Point::new(3.0, 3.0, 0.0), Vector3::forward()).intersect_triangle(a, b, c).is_none());
+    assert!(Ray::new(Point::new(-0.1, 2.0, 0.0), Vector3::forward()).intersect_triangle(a, b, c).is_none());
+    assert!(Ray::new(Point::new(1.0, 2.0, 0.0), Vector3::back()).intersect_triangle(a, b, c).is_none());
+    assert!(Ray::new(Point::new(-1.0, 1.0, -5.0), Vector3::right()).intersect_triangle(a, b, c).is_none());
+}
+
+#[test]
+fn unproject() {
+    let viewport = Vector2::new(800.0, 600.0);
+    let projection = Matrix4::perspective(Rad(1.0), 800.0 / 600.0, 0.5, 100.0);
+    let camera = Point::new(1.0, 2.0, 3.0);
+    let view = Matrix4::translation(-camera.x, -camera.y, -camera.z);
+
+    // The center of the screen looks straight down the camera's forward axis.
+    let ray = Ray::unproject(Vector2::new(400.0, 300.0), viewport, view, projection);
+    assert_approx_eq!(ray.origin, Point::new(1.0, 2.0, 2.5), 1e-4);
+    assert_approx_eq!(ray.direction, Vector3::forward(), 1e-4);
+
+    // Every ray passes through the camera, and the point it's fired at projects back to where
+    // it was clicked.
+    let screen_position = Vector2::new(100.0, 50.0);
+    let ray = Ray::unproject(screen_position, viewport, view, projection);
+    let camera_offset = camera - ray.origin;
+    assert!(Vector3::cross(camera_offset, ray.direction).magnitude() < 1e-4);
+
+    let clip = ray.point_at(20.0) * (projection * view);
+    let projected = Vector2::new(
+        (clip.x / clip.w + 1.0) * 0.5 * viewport.x,
+        (1.0 - clip.y / clip.w) * 0.5 * viewport.y);
+    assert!((projected.x - screen_position.x).abs() < 1e-2 && (projected.y - screen_position.y).abs() < 1e-2, "Wrong position: {:?}", projected);
+
+    // Orthographic rays are all parallel.
+    let projection = Matrix4::orthographic(-4.0, 4.0, -3.0, 3.0, 0.5, 100.0);
+    let ray = Ray::unproject(Vector2::new(0.0, 0.0), viewport, view, projection);
+    assert_approx_eq!(ray.origin, Point::new(-3.0, 5.0, 2.5), 1e-4);
+    assert_approx_eq!(ray.direction, Vector3::forward(), 1e-4);
+}
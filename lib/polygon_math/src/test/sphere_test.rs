@@ -0,0 +1,39 @@
+use matrix::Matrix4;
+use point::Point;
+use sphere::Sphere;
+
+#[test]
+fn from_points() {
+    let points = [
+        Point::new(-1.0, 0.0, 0.0),
+        Point::new(3.0, 0.0, 0.0),
+        Point::new(1.0, 1.0, 0.0),
+    ];
+    let sphere = Sphere::from_points(&points);
+    assert_eq!(sphere, Sphere::new(Point::new(1.0, 0.5, 0.0), (4.25f32).sqrt()));
+    for &point in &points {
+        assert!(sphere.contains_point(point));
+    }
+
+    assert_eq!(Sphere::from_points(&[]), Sphere::new(Point::origin(), 0.0));
+}
+
+#[test]
+fn intersects() {
+    let sphere = Sphere::new(Point::origin(), 1.0);
+    assert!(sphere.intersects(&Sphere::new(Point::new(1.5, 0.0, 0.0), 1.0)));
+    assert!(sphere.intersects(&Sphere::new(Point::new(0.0, 2.0, 0.0), 1.0)));
+    assert!(!sphere.intersects(&Sphere::new(Point::new(0.0, 0.0, 2.5), 1.0)));
+
+    // A small sphere entirely inside a larger one.
+    assert!(sphere.intersects(&Sphere::new(Point::new(0.1, 0.0, 0.0), 0.1)));
+}
+
+#[test]
+fn transform() {
+    let sphere = Sphere::new(Point::new(1.0, 0.0, 0.0), 1.0);
+    let transform = Matrix4::translation(0.0, 2.0, 0.0) * Matrix4::scale(1.0, 3.0, 2.0);
+
+    // The radius grows by the largest scale so the sphere still bounds the stretched shape.
+    assert_eq!(sphere.transform(transform), Sphere::new(Point::new(1.0, 2.0, 0.0), 3.0));
+}
@@ -0,0 +1,98 @@
+use std::f32::consts::PI;
+
+use point::Point;
+use vector::Vector3;
+use {Dot, Lerp};
+
+#[test]
+fn lerp() {
+    let from = Vector3::new(1.0, 2.0, 3.0);
+    let to = Vector3::new(-1.0, 4.0, 3.0);
+    assert_eq!(Vector3::lerp(0.0, from, to), from);
+    assert_eq!(Vector3::lerp(1.0, from, to), to);
+    assert_approx_eq!(Vector3::lerp(0.25, from, to), Vector3::new(0.5, 2.5, 3.0), 1e-5);
+
+    // Values outside of 0..1 extrapolate.
+    assert_approx_eq!(Vector3::lerp(2.0, from, to), Vector3::new(-3.0, 6.0, 3.0), 1e-5);
+
+    let point = Point::lerp(0.5, Point::new(0.0, 0.0, 0.0), Point::new(2.0, -4.0, 6.0));
+    assert_eq!(point, Point::new(1.0, -2.0, 3.0));
+}
+
+#[test]
+fn distance() {
+    let first = Vector3::new(1.0, 2.0, 3.0);
+    let second = Vector3::new(4.0, 6.0, 3.0);
+    assert_eq!(Vector3::distance(first, second), 5.0);
+    assert_eq!(Vector3::distance_squared(first, second), 25.0);
+    assert_eq!(Vector3::distance(first, first), 0.0);
+}
+
+#[test]
+fn reflect() {
+    let normal = Vector3::up();
+    assert_approx_eq!(Vector3::reflect(Vector3::new(1.0, -1.0, 0.0), normal), Vector3::new(1.0, 1.0, 0.0), 1e-5);
+
+    // Either side of the surface gives the same result.
+    assert_approx_eq!(Vector3::reflect(Vector3::new(1.0, -1.0, 0.0), -normal), Vector3::new(1.0, 1.0, 0.0), 1e-5);
+
+    // Vectors along the surface aren't changed.
+    assert_approx_eq!(Vector3::reflect(Vector3::new(2.0, 0.0, 3.0), normal), Vector3::new(2.0, 0.0, 3.0), 1e-5);
+
+    assert_eq!(Vector3::reflect(Vector3::zero(), normal), Vector3::zero());
+}
+
+#[test]
+fn project_and_reject() {
+    let vector = Vector3::new(3.0, 4.0, 5.0);
+    let onto = Vector3::new(2.0, 0.0, 0.0);
+    assert_approx_eq!(vector.project_onto(onto), Vector3::new(3.0, 0.0, 0.0), 1e-5);
+    assert_approx_eq!(vector.reject_from(onto), Vector3::new(0.0, 4.0, 5.0), 1e-5);
+
+    // The two parts add back up to the original vector.
+    let onto = Vector3::new(1.0, -2.0, 0.5);
+    assert_approx_eq!(vector.project_onto(onto) + vector.reject_from(onto), vector, 1e-5);
+    assert!(Vector3::cross(vector.project_onto(onto), onto).magnitude() < 1e-5);
+    assert!(vector.reject_from(onto).dot(onto).abs() < 1e-5);
+
+    // There's no direction to project onto the zero vector.
+    assert_eq!(vector.project_onto(Vector3::zero()), Vector3::zero());
+    assert_eq!(vector.reject_from(Vector3::zero()), vector);
+    assert_eq!(Vector3::zero().project_onto(onto), Vector3::zero());
+}
+
+#[test]
+fn clamp_length() {
+    let vector = Vector3::new(3.0, 0.0, 4.0);
+    assert_approx_eq!(vector.clamp_length(2.5), Vector3::new(1.5, 0.0, 2.0), 1e-5);
+    assert_eq!(vector.clamp_length(5.0), vector);
+    assert_eq!(vector.clamp_length(10.0), vector);
+    assert_eq!(vector.clamp_length(0.0), Vector3::zero());
+    assert_eq!(Vector3::zero().clamp_length(1.0), Vector3::zero());
+}
+
+#[test]
+fn min_max() {
+    let first = Vector3::new(1.0, -2.0, 3.0);
+    let second = Vector3::new(-1.0, 2.0, 3.0);
+    assert_eq!(Vector3::min(first, second), Vector3::new(-1.0, -2.0, 3.0));
+    assert_eq!(Vector3::max(first, second), Vector3::new(1.0, 2.0, 3.0));
+}
+
+#[test]
+fn angle_between() {
+    let right = Vector3::right();
+    assert_approx_eq!(Vector3::angle_between(right, Vector3::up()), PI * 0.5, 1e-5);
+    assert_approx_eq!(Vector3::angle_between(right, Vector3::new(1.0, 1.0, 0.0)), PI * 0.25, 1e-5);
+    assert_approx_eq!(Vector3::angle_between(right, Vector3::left()), PI, 1e-5);
+
+    // Parallel vectors of different lengths don't produce NaN from rounding.
+    let vector = Vector3::new(0.1, 0.7, -0.3);
+    assert!(!Vector3::angle_between(vector, vector * 3.0).is_nan());
+    assert!(Vector3::angle_between(vector, vector * 3.0) < 1e-3);
+    assert_approx_eq!(Vector3::angle_between(vector, vector * -3.0), PI, 1e-3);
+
+    // The angle is undefined for the zero vector.
+    assert_eq!(Vector3::angle_between(Vector3::zero(), right), 0.0);
+    assert_eq!(Vector3::angle_between(right, Vector3::zero()), 0.0);
+}
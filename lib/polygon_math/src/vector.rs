@@ -1,4 +1,4 @@
-use {IsZero, Dot, Lerp, Point};
+use {ApproxEq, Clamp, EPSILON, IsZero, Dot, Lerp, Point};
 use std::ops::*;
 use std::fmt::{self, Debug, Formatter};
 
@@ -89,7 +89,7 @@ impl Vector3 {
     ///
     /// If the vector is the zero vector it is not altered.
     pub fn normalize(&mut self) -> f32 {
-        if self.is_zero() {
+        if self.magnitude_squared().approx_eq(0.0, EPSILON) {
             0.0
         } else {
             let magnitude = self.magnitude();
@@ -106,7 +106,7 @@ impl Vector3 {
     ///
     /// If the vector is the zero vector a copy is returned.
     pub fn normalized(&self) -> Vector3 {
-        if self.is_zero() {
+        if self.magnitude_squared().approx_eq(0.0, EPSILON) {
             *self
         } else {
             let mut copy = *self;
@@ -116,7 +116,7 @@ impl Vector3 {
     }
 
     pub fn is_normalized(&self) -> bool {
-        (self.dot(self) - 1.0).is_zero()
+        self.dot(self).approx_eq(1.0, EPSILON)
     }
 
     pub fn magnitude(&self) -> f32 {
@@ -127,6 +127,87 @@ impl Vector3 {
         self.x * self.x + self.y * self.y + self.z * self.z
     }
 
+    /// Calculates the distance between two vectors treated as points.
+    pub fn distance(first: Vector3, second: Vector3) -> f32 {
+        Vector3::distance_squared(first, second).sqrt()
+    }
+
+    /// Calculates the squared distance between two vectors treated as points.
+    ///
+    /// Cheaper than `distance()` since it avoids a square root, which is enough when comparing
+    /// distances to each other.
+    pub fn distance_squared(first: Vector3, second: Vector3) -> f32 {
+        (second - first).magnitude_squared()
+    }
+
+    /// Reflects `incident` off of a surface with the given normal, e.g. to bounce a projectile
+    /// off of a wall.
+    ///
+    /// `normal` must be normalized. It doesn't matter which side of the surface it faces.
+    pub fn reflect(incident: Vector3, normal: Vector3) -> Vector3 {
+        incident - 2.0 * incident.dot(normal) * normal
+    }
+
+    /// Calculates the part of the vector that's parallel to `other`.
+    ///
+    /// `other` doesn't need to be normalized. If `other` is the zero vector there's no direction
+    /// to project onto, so the zero vector is returned.
+    pub fn project_onto(self, other: Vector3) -> Vector3 {
+        let other_magnitude_squared = other.magnitude_squared();
+        if other_magnitude_squared.approx_eq(0.0, EPSILON) {
+            Vector3::zero()
+        } else {
+            other * (self.dot(other) / other_magnitude_squared)
+        }
+    }
+
+    /// Calculates the part of the vector that's perpendicular to `other`.
+    ///
+    /// This is what's left after removing `project_onto(other)`, so if `other` is the zero vector
+    /// the vector is returned unchanged.
+    pub fn reject_from(self, other: Vector3) -> Vector3 {
+        self - self.project_onto(other)
+    }
+
+    /// Shortens the vector to `max` if it's longer than that, keeping its direction.
+    ///
+    /// `max` must not be negative.
+    pub fn clamp_length(self, max: f32) -> Vector3 {
+        debug_assert!(max >= 0.0, "Cannot clamp vector to a negative length {}", max);
+
+        let magnitude_squared = self.magnitude_squared();
+        if magnitude_squared > max * max {
+            self * (max / magnitude_squared.sqrt())
+        } else {
+            self
+        }
+    }
+
+    /// Creates a vector from the smallest of each of the components of `first` and `second`.
+    pub fn min(first: Vector3, second: Vector3) -> Vector3 {
+        Vector3::new(first.x.min(second.x), first.y.min(second.y), first.z.min(second.z))
+    }
+
+    /// Creates a vector from the largest of each of the components of `first` and `second`.
+    pub fn max(first: Vector3, second: Vector3) -> Vector3 {
+        Vector3::new(first.x.max(second.x), first.y.max(second.y), first.z.max(second.z))
+    }
+
+    /// Calculates the angle between two vectors in radians, in the range `0..PI`.
+    ///
+    /// The vectors don't need to be normalized. If either vector is the zero vector the angle is
+    /// undefined, and 0 is returned.
+    pub fn angle_between(first: Vector3, second: Vector3) -> f32 {
+        let magnitudes = (first.magnitude_squared() * second.magnitude_squared()).sqrt();
+        if magnitudes.approx_eq(0.0, EPSILON) {
+            return 0.0;
+        }
+
+        // Rounding can push the cosine just outside of -1..1 for parallel vectors, where `acos()`
+        // would give NaN.
+        Clamp::clamp(first.dot(second) / magnitudes, -1.0, 1.0).acos()
+    }
+
     /// Safely reinterprets a slice of Vector3s to a slice of f32s. This is a cheap operation and
     /// does not copy any data.
     pub fn as_ref(vectors: &[Vector3]) -> &[f32] {
@@ -181,6 +262,12 @@ impl Debug for Vector3 {
     }
 }
 
+impl Lerp for Vector3 {
+    fn lerp(t: f32, from: Vector3, to: Vector3) -> Vector3 {
+        from + (to - from) * t
+    }
+}
+
 impl Dot for Vector3 {
     type Output = f32;
 
@@ -322,6 +409,14 @@ impl Div<Vector3> for f32 {
     }
 }
 
+impl ApproxEq for Vector3 {
+    fn approx_eq(self, other: Vector3, epsilon: f32) -> bool {
+        self.x.approx_eq(other.x, epsilon)
+            && self.y.approx_eq(other.y, epsilon)
+            && self.z.approx_eq(other.z, epsilon)
+    }
+}
+
 impl IsZero for Vector3 {
     fn is_zero(self) -> bool {
         self.dot(self).is_zero()
@@ -479,6 +574,12 @@ impl Default for Vector2 {
     }
 }
 
+impl ApproxEq for Vector2 {
+    fn approx_eq(self, other: Vector2, epsilon: f32) -> bool {
+        self.x.approx_eq(other.x, epsilon) && self.y.approx_eq(other.y, epsilon)
+    }
+}
+
 impl Lerp for Vector2 {
     fn lerp(t: f32, from: Vector2, to: Vector2) -> Vector2 {
         from + (to - from) * t
@@ -20,7 +20,7 @@ fn main() {
     let mesh = utils::load_mesh("resources/meshes/epps_head.obj").unwrap();
 
     // Send the mesh to the GPU.
-    let gpu_mesh = renderer.register_mesh(&mesh);
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
 
     // Create an anchor and register it with the renderer.
     let mut anchor = Anchor::new();
@@ -30,7 +30,7 @@ fn main() {
         .unwrap();
 
     // Send the mesh to the GPU.
-    let gpu_mesh = renderer.register_mesh(&mesh);
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
 
     // Create an anchor and register it with the renderer.
     let anchor = Anchor::new();
@@ -66,10 +66,11 @@ fn main() {
         {
             let anchor = renderer.get_anchor_mut(anchor_id).unwrap();
             let orientation = anchor.orientation();
-            anchor.set_orientation(orientation + Orientation::from_eulers(0.0, 0.0, 0.0005));
+            anchor.set_orientation(orientation + Orientation::from_eulers(Rad(0.0), Rad(0.0), Rad(0.0005)));
         }
 
-        // Render the mesh.
+        // Label the window using the built-in font, then render the mesh with the label on top.
+        renderer.draw_text("Hello, triangle!", (10.0, 10.0), 16.0, Color::rgb(1.0, 1.0, 1.0));
         renderer.draw();
     }
 }
@@ -8,6 +8,7 @@ use polygon::camera::*;
 use polygon::math::*;
 use polygon::material::*;
 use polygon::mesh_instance::*;
+use polygon::texture::*;
 
 pub mod utils;
 
@@ -20,7 +21,7 @@ fn main() {
     let mesh = utils::load_mesh("resources/meshes/epps_head.obj").unwrap();
 
     // Send the mesh to the GPU.
-    let gpu_mesh = renderer.register_mesh(&mesh);
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
 
     // Create an anchor and register it with the renderer.
     let mut anchor = Anchor::new();
@@ -30,11 +31,9 @@ fn main() {
     let material_source = MaterialSource::from_file("resources/materials/diffuse_lit.material").unwrap();
     let material = renderer.build_material(material_source).unwrap();
 
-    // Create a mesh instance, attach it to the anchor, and register it with the renderer.
+    // Create a mesh instance, attach it to the anchor, and register it with the renderer. The
+    // material's properties start out with the defaults declared in the material file.
     let mut mesh_instance = MeshInstance::new(gpu_mesh, material);
-    mesh_instance.material_mut().set_color("surface_color", Color::rgb(1.0, 1.0, 1.0));
-    mesh_instance.material_mut().set_color("surface_specular", Color::rgb(1.0, 1.0, 1.0));
-    mesh_instance.material_mut().set_f32("surface_shininess", 4.0);
     mesh_instance.set_anchor(mesh_anchor_id);
     renderer.register_mesh_instance(mesh_instance);
 
@@ -47,10 +46,28 @@ fn main() {
     camera.set_anchor(camera_anchor_id);
     renderer.register_camera(camera);
 
+    // Add a sky behind the mesh.
+    let skybox = renderer.register_texture_cube(&sky_faces());
+    renderer.set_skybox(Some(skybox));
+
+    // Fade the head into the pale horizon behind it. The head is 2 units from the camera, so it's
+    // about halfway into the fog.
+    renderer.set_fog(Some(Fog {
+        color: Color::new(200.0 / 255.0, 220.0 / 255.0, 1.0, 1.0),
+        mode: FogMode::Linear { start: 1.0, end: 3.5 },
+    }));
+
+    // Drain the color from the whole frame with a post effect.
+    let grayscale_source = MaterialSource::from_file("resources/materials/grayscale.material").unwrap();
+    let grayscale = renderer.build_material(grayscale_source).unwrap();
+    let grayscale_id = renderer.register_material(grayscale);
+    renderer.set_post_effects(vec![grayscale_id]);
+
     'outer: loop {
         while let Some(message) = window.next_message() {
             match message {
                 Message::Close => break 'outer,
+                Message::Resize(width, height) => renderer.resize(width, height),
                 _ => {},
             }
         }
@@ -59,3 +76,39 @@ fn main() {
         renderer.draw();
     }
 }
+
+/// Builds the faces of a simple sky that fades from a pale horizon to deep blue overhead, with
+/// a plain brown ground below the horizon.
+fn sky_faces() -> [Texture2d; 6] {
+    const SIZE: usize = 64;
+
+    let sky_color = |height: f32| -> (u8, u8, u8) {
+        if height < 0.0 {
+            return (60, 50, 40);
+        }
+
+        let lerp = |from: f32, to: f32| (from + (to - from) * height) as u8;
+        (lerp(200.0, 40.0), lerp(220.0, 90.0), lerp(255.0, 200.0))
+    };
+
+    // The first row of each side face is the top of the face, so the height goes from 1 at the
+    // first row to -1 at the last.
+    let side = || {
+        let mut data = Vec::with_capacity(SIZE * SIZE);
+        for row in 0..SIZE {
+            let height = 1.0 - 2.0 * (row as f32 + 0.5) / SIZE as f32;
+            for _ in 0..SIZE {
+                data.push(sky_color(height));
+            }
+        }
+        Texture2d::new(SIZE, SIZE, DataFormat::Rgb, TextureData::u8x3(data))
+    };
+    let solid = |color| Texture2d::new(SIZE, SIZE, DataFormat::Rgb, TextureData::u8x3(vec![color; SIZE * SIZE]));
+
+    // Clamp each face to its edges so that the faces don't show seams where they meet.
+    let mut faces = [side(), side(), solid(sky_color(1.0)), solid(sky_color(-1.0)), side(), side()];
+    for face in &mut faces {
+        face.set_wrap(TextureWrap::ClampToEdge, TextureWrap::ClampToEdge);
+    }
+    faces
+}
@@ -0,0 +1,153 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate polygon;
+
+use bootstrap::window::*;
+use polygon::*;
+use polygon::anchor::*;
+use polygon::camera::*;
+use polygon::geometry::mesh::*;
+use polygon::light::*;
+use polygon::material::*;
+use polygon::math::*;
+use polygon::mesh_instance::*;
+use polygon::texture::*;
+
+/// The size of the generated normal map in pixels.
+const TEXTURE_SIZE: usize = 256;
+
+/// The size of each brick in pixels, including half of the mortar around it.
+const BRICK_WIDTH: usize = 64;
+const BRICK_HEIGHT: usize = 32;
+
+/// The width of the mortar between bricks and of the bevel along their edges, in pixels.
+const MORTAR_WIDTH: f32 = 4.0;
+const BEVEL_WIDTH: f32 = 4.0;
+
+fn main() {
+    // Open a window and create the renderer instance.
+    let mut window = Window::new("Normal Mapping").unwrap();
+    let mut renderer = RendererBuilder::new(&window).build();
+
+    // Send the quad mesh and the brick normal map to the GPU.
+    let gpu_mesh = renderer.register_mesh(&quad()).unwrap();
+    let gpu_normal_map = renderer.register_texture(&brick_normal_map());
+
+    let material_source =
+        MaterialSource::from_file("resources/materials/diffuse_normal_mapped.material").unwrap();
+    let mut material = renderer.build_material(material_source).unwrap();
+    material.set_color("surface_color", Color::rgb(0.8, 0.4, 0.3));
+    material.set_f32("surface_shininess", 16.0);
+    material.set_texture("normal_map", gpu_normal_map);
+
+    // Create a mesh instance, attach it to an anchor, and register it with the renderer.
+    let mesh_anchor_id = renderer.register_anchor(Anchor::new());
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, material);
+    mesh_instance.set_anchor(mesh_anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    // Create a camera and an anchor for it.
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(0.0, 0.0, 2.5));
+    let camera_anchor_id = renderer.register_anchor(camera_anchor);
+
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor_id);
+    renderer.register_camera(camera);
+
+    // Create the light and an anchor for it.
+    let light_anchor_id = renderer.register_anchor(Anchor::new());
+    let mut light = Light::point(3.0, 1.0, Color::rgb(1.0, 1.0, 1.0));
+    light.set_anchor(light_anchor_id);
+    renderer.register_light(light);
+
+    const LIGHT_RADIUS: f32 = 0.75;
+
+    let mut t: f32 = 0.0;
+    'outer: loop {
+        while let Some(message) = window.next_message() {
+            match message {
+                Message::Close => break 'outer,
+                _ => {},
+            }
+        }
+
+        // Orbit the light just above the surface of the quad, so that the bevels on the side of
+        // the bricks facing the light catch it.
+        {
+            let anchor = renderer.get_anchor_mut(light_anchor_id).unwrap();
+            anchor.set_position(Point::new(t.cos() * LIGHT_RADIUS, t.sin() * LIGHT_RADIUS, 0.3));
+        }
+
+        // Render the mesh.
+        renderer.draw();
+
+        t += 0.0005;
+    }
+}
+
+/// Builds a flat quad facing the camera, with the brick texture repeated twice in each direction.
+fn quad() -> Mesh {
+    let positions = [
+        Point::new(-1.0, -1.0, 0.0),
+        Point::new( 1.0, -1.0, 0.0),
+        Point::new( 1.0,  1.0, 0.0),
+        Point::new(-1.0,  1.0, 0.0),
+    ];
+    let texcoords = [
+        Vector2::new(0.0, 0.0),
+        Vector2::new(2.0, 0.0),
+        Vector2::new(2.0, 2.0),
+        Vector2::new(0.0, 2.0),
+    ];
+
+    MeshBuilder::new()
+        .set_position_data(&positions)
+        .set_normal_data(&[Vector3::new(0.0, 0.0, 1.0); 4])
+        .set_texcoord_data(&texcoords)
+        .set_indices(&[0, 1, 2, 0, 2, 3])
+        .generate_tangents()
+        .build()
+        .unwrap()
+}
+
+/// Generates a tangent-space normal map of a brick wall with beveled bricks.
+fn brick_normal_map() -> Texture2d {
+    const STRENGTH: f32 = 2.0;
+
+    let mut data = Vec::with_capacity(TEXTURE_SIZE * TEXTURE_SIZE);
+    for y in 0..TEXTURE_SIZE {
+        for x in 0..TEXTURE_SIZE {
+            // The slope of the brick's surface, using the neighboring pixels on either side.
+            let slope_x = (brick_height(x + 1, y) - brick_height(x + TEXTURE_SIZE - 1, y)) / 2.0;
+            let slope_y = (brick_height(x, y + 1) - brick_height(x, y + TEXTURE_SIZE - 1)) / 2.0;
+            let normal = Vector3::new(-slope_x * STRENGTH, -slope_y * STRENGTH, 1.0).normalized();
+
+            let encode = |component: f32| ((component * 0.5 + 0.5) * 255.0) as u8;
+            data.push((encode(normal.x), encode(normal.y), encode(normal.z)));
+        }
+    }
+
+    let mut texture = Texture2d::new(TEXTURE_SIZE, TEXTURE_SIZE, DataFormat::Rgb, TextureData::u8x3(data));
+    texture.set_color_space(ColorSpace::Linear);
+    texture
+}
+
+/// Gets the height of the brick wall at a pixel, from `0.0` in the mortar to `1.0` on the face of
+/// a brick. Pixels outside the texture wrap around.
+fn brick_height(x: usize, y: usize) -> f32 {
+    let (x, y) = (x % TEXTURE_SIZE, y % TEXTURE_SIZE);
+
+    // Every other row of bricks is offset by half a brick.
+    let row = y / BRICK_HEIGHT;
+    let x = if row % 2 == 0 { x } else { (x + BRICK_WIDTH / 2) % TEXTURE_SIZE };
+
+    // Distance to the nearest edge of the brick.
+    let local_x = (x % BRICK_WIDTH) as f32 + 0.5;
+    let local_y = (y % BRICK_HEIGHT) as f32 + 0.5;
+    let edge_distance = local_x
+        .min(BRICK_WIDTH as f32 - local_x)
+        .min(local_y)
+        .min(BRICK_HEIGHT as f32 - local_y);
+
+    ((edge_distance - MORTAR_WIDTH / 2.0) / BEVEL_WIDTH).max(0.0).min(1.0)
+}
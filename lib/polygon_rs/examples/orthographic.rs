@@ -0,0 +1,76 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate polygon;
+
+use bootstrap::window::*;
+use polygon::*;
+use polygon::anchor::*;
+use polygon::camera::*;
+use polygon::math::*;
+use polygon::mesh_instance::*;
+use polygon::geometry::mesh::*;
+
+static VERTEX_POSITIONS: [f32; 16] = [
+    -0.5, -0.5, 0.0, 1.0,
+     0.5, -0.5, 0.0, 1.0,
+     0.5,  0.5, 0.0, 1.0,
+    -0.5,  0.5, 0.0, 1.0,
+];
+
+static INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+fn main() {
+    // Open a window and create the renderer instance.
+    let mut window = Window::new("Orthographic").unwrap();
+    let mut renderer = RendererBuilder::new(&window).build();
+
+    // Build a unit quad mesh and send it to the GPU.
+    let mesh = MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&VERTEX_POSITIONS))
+        .set_indices(&INDICES)
+        .build()
+        .unwrap();
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
+
+    // Create two quads of the same size, one much farther from the camera than the other. With an
+    // orthographic camera both appear the same size, and both stay square when the window is
+    // resized.
+    let quads = [
+        (Point::new(-1.0, 0.0, 0.0), Color::rgb(1.0, 0.0, 0.0)),
+        (Point::new(1.0, 0.0, -50.0), Color::rgb(0.0, 0.0, 1.0)),
+    ];
+    for &(position, color) in &quads {
+        let mut anchor = Anchor::new();
+        anchor.set_position(position);
+        let anchor_id = renderer.register_anchor(anchor);
+
+        let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+        mesh_instance.set_anchor(anchor_id);
+        mesh_instance.material_mut().set_color("surface_color", color);
+        renderer.register_mesh_instance(mesh_instance);
+    }
+
+    // Create an orthographic camera that shows 2 units above and below the center of the window.
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(0.0, 0.0, 10.0));
+    let camera_anchor_id = renderer.register_anchor(camera_anchor);
+
+    let mut camera = Camera::default();
+    camera.set_orthographic(2.0);
+    camera.set_anchor(camera_anchor_id);
+    renderer.register_camera(camera);
+
+    // Set ambient color to pure white so we don't need to worry about lighting.
+    renderer.set_ambient_light(Color::rgb(1.0, 1.0, 1.0));
+
+    'outer: loop {
+        while let Some(message) = window.next_message() {
+            match message {
+                Message::Close => break 'outer,
+                Message::Resize(width, height) => renderer.resize(width, height),
+                _ => {},
+            }
+        }
+
+        renderer.draw();
+    }
+}
@@ -21,7 +21,7 @@ fn main() {
     let mesh = utils::load_mesh("resources/meshes/epps_head.obj").unwrap();
 
     // Send the mesh to the GPU.
-    let gpu_mesh = renderer.register_mesh(&mesh);
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
 
     // Create an anchor and register it with the renderer.
     let mut anchor = Anchor::new();
@@ -50,8 +50,9 @@ fn main() {
     light_anchor.set_scale(Vector3::new(0.01, 0.01, 0.01));
     let light_anchor_id = renderer.register_anchor(light_anchor);
     let mut light = Light::point(1.0, 1.0, Color::rgb(1.0, 1.0, 1.0));
+    light.set_falloff(Falloff::Attenuation { constant: 1.0, linear: 0.0, quadratic: 4.0 });
     light.set_anchor(light_anchor_id);
-    renderer.register_light(light);
+    let light_id = renderer.register_light(light);
 
     let mut camera = Camera::default();
     camera.set_anchor(camera_anchor_id);
@@ -79,6 +80,12 @@ fn main() {
             anchor.set_position(position);
         }
 
+        // Blink the light on and off.
+        {
+            let light = renderer.get_light_mut(light_id).unwrap();
+            light.set_enabled((t * 2.0).sin() > 0.0);
+        }
+
         // Render the mesh.
         renderer.draw();
 
@@ -0,0 +1,92 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate polygon;
+
+use bootstrap::window::*;
+use polygon::*;
+use polygon::anchor::*;
+use polygon::camera::*;
+use polygon::geometry::mesh::*;
+use polygon::light::*;
+use polygon::math::*;
+use polygon::material::*;
+use polygon::mesh_instance::*;
+
+pub mod utils;
+
+static GROUND_POSITIONS: [f32; 16] = [
+    -2.0, -0.6, -2.0, 1.0,
+    -2.0, -0.6,  2.0, 1.0,
+     2.0, -0.6,  2.0, 1.0,
+     2.0, -0.6, -2.0, 1.0,
+];
+
+static GROUND_INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+fn main() {
+    // Open a window and create the renderer instance.
+    let mut window = Window::new("Shadows").unwrap();
+    let mut renderer = RendererBuilder::new(&window).shadow_resolution(2048).build();
+
+    let material_source = MaterialSource::from_file("resources/materials/diffuse_lit.material").unwrap();
+    let material = renderer.build_material(material_source).unwrap();
+
+    // Load the head mesh and place it above the ground.
+    let mesh = utils::load_mesh("resources/meshes/epps_head.obj").unwrap();
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
+
+    let mesh_anchor_id = renderer.register_anchor(Anchor::new());
+
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, material.clone());
+    mesh_instance.material_mut().set_color("surface_color", Color::rgb(1.0, 1.0, 1.0));
+    mesh_instance.material_mut().set_color("surface_specular", Color::rgb(1.0, 1.0, 1.0));
+    mesh_instance.material_mut().set_f32("surface_shininess", 4.0);
+    mesh_instance.set_anchor(mesh_anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    // Build a ground plane below the head for the shadow to fall on.
+    let ground_normals = [Vector3::up(); 4];
+    let ground_mesh = MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&GROUND_POSITIONS))
+        .set_normal_data(&ground_normals)
+        .set_indices(&GROUND_INDICES)
+        .build()
+        .unwrap();
+    let gpu_ground_mesh = renderer.register_mesh(&ground_mesh).unwrap();
+
+    let ground_anchor_id = renderer.register_anchor(Anchor::new());
+
+    let mut ground_instance = MeshInstance::new(gpu_ground_mesh, material);
+    ground_instance.material_mut().set_color("surface_color", Color::rgb(0.5, 0.5, 0.5));
+    ground_instance.material_mut().set_color("surface_specular", Color::rgb(0.0, 0.0, 0.0));
+    ground_instance.material_mut().set_f32("surface_shininess", 1.0);
+    ground_instance.set_anchor(ground_anchor_id);
+    ground_instance.set_casts_shadows(false);
+    renderer.register_mesh_instance(ground_instance);
+
+    // Create a camera looking down at the head from the front.
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(0.0, 1.0, 3.0));
+    camera_anchor.look_at(Point::origin(), Vector3::up());
+    let camera_anchor_id = renderer.register_anchor(camera_anchor);
+
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor_id);
+    renderer.register_camera(camera);
+
+    // Create a directional light shining down at an angle so the shadow falls behind the head.
+    let mut light = Light::directional(Vector3::new(0.3, -1.0, -0.5), 1.0, Color::rgb(1.0, 1.0, 1.0));
+    light.set_casts_shadows(true);
+    renderer.register_light(light);
+
+    'outer: loop {
+        while let Some(message) = window.next_message() {
+            match message {
+                Message::Close => break 'outer,
+                Message::Resize(width, height) => renderer.resize(width, height),
+                _ => {},
+            }
+        }
+
+        renderer.draw();
+    }
+}
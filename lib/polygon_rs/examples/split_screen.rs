@@ -0,0 +1,106 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate polygon;
+
+use bootstrap::window::*;
+use polygon::*;
+use polygon::anchor::*;
+use polygon::camera::*;
+use polygon::geometry::mesh::*;
+use polygon::light::*;
+use polygon::math::*;
+use polygon::material::*;
+use polygon::mesh_instance::*;
+
+pub mod utils;
+
+/// The render layer for editor gizmos, which only the front camera sees.
+const GIZMO_LAYER: u32 = 2;
+
+fn main() {
+    // Open a window and create the renderer instance.
+    let mut window = Window::new("Split Screen").unwrap();
+    let mut renderer = RendererBuilder::new(&window).build();
+
+    // Load the mesh and send it to the GPU.
+    let mesh = utils::load_mesh("resources/meshes/epps_head.obj").unwrap();
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
+
+    // Create an anchor and register it with the renderer.
+    let mut anchor = Anchor::new();
+    anchor.set_position(Point::new(0.0, 0.0, 0.0));
+    let mesh_anchor_id = renderer.register_anchor(anchor);
+
+    let material_source = MaterialSource::from_file("resources/materials/diffuse_lit.material").unwrap();
+    let material = renderer.build_material(material_source).unwrap();
+
+    // Create a mesh instance, attach it to the anchor, and register it with the renderer.
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, material);
+    mesh_instance.material_mut().set_color("surface_color", Color::rgb(1.0, 1.0, 1.0));
+    mesh_instance.material_mut().set_color("surface_specular", Color::rgb(1.0, 1.0, 1.0));
+    mesh_instance.material_mut().set_f32("surface_shininess", 4.0);
+    mesh_instance.set_anchor(mesh_anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    // Create a camera looking at the front of the head, rendering to the left half of the window.
+    let mut front_anchor = Anchor::new();
+    front_anchor.set_position(Point::new(0.0, 0.0, 2.0));
+    let front_anchor_id = renderer.register_anchor(front_anchor);
+
+    let mut front_camera = Camera::default();
+    front_camera.set_anchor(front_anchor_id);
+    front_camera.set_viewport(Some(ViewportRect::new(0.0, 0.0, 0.5, 1.0)));
+    front_camera.set_layers(1 | GIZMO_LAYER);
+    renderer.register_camera(front_camera);
+
+    // Create a camera looking at the side of the head, rendering to the right half of the window.
+    let mut side_anchor = Anchor::new();
+    side_anchor.set_position(Point::new(2.0, 0.0, 0.0));
+    side_anchor.set_orientation(Orientation::from_eulers(Deg(0.0), Deg(90.0), Deg(0.0)));
+    let side_anchor_id = renderer.register_anchor(side_anchor);
+
+    let mut side_camera = Camera::default();
+    side_camera.set_anchor(side_anchor_id);
+    side_camera.set_viewport(Some(ViewportRect::new(0.5, 0.0, 0.5, 1.0)));
+    renderer.register_camera(side_camera);
+
+    // Add a light so that both sides of the head are lit.
+    let mut light_anchor = Anchor::new();
+    light_anchor.set_position(Point::new(2.0, 2.0, 2.0));
+    let light_anchor_id = renderer.register_anchor(light_anchor);
+
+    let mut light = Light::point(5.0, 1.0, Color::rgb(1.0, 1.0, 1.0));
+    light.set_anchor(light_anchor_id);
+    renderer.register_light(light);
+
+    // Mark the light's position with a small triangle gizmo that only the front camera draws.
+    let gizmo_mesh = MeshBuilder::new()
+        .set_position_data(&[
+            Point::new(-0.1, -0.1, 0.0),
+            Point::new( 0.1, -0.1, 0.0),
+            Point::new( 0.0,  0.1, 0.0),
+        ])
+        .set_indices(&[0, 1, 2])
+        .build()
+        .unwrap();
+    let gpu_gizmo_mesh = renderer.register_mesh(&gizmo_mesh).unwrap();
+
+    let mut gizmo_instance = MeshInstance::new(gpu_gizmo_mesh, renderer.default_material());
+    gizmo_instance.material_mut().set_color("surface_color", Color::rgb(1.0, 1.0, 0.0));
+    gizmo_instance.set_anchor(light_anchor_id);
+    gizmo_instance.set_casts_shadows(false);
+    gizmo_instance.set_layers(GIZMO_LAYER);
+    renderer.register_mesh_instance(gizmo_instance);
+
+    'outer: loop {
+        while let Some(message) = window.next_message() {
+            match message {
+                Message::Close => break 'outer,
+                Message::Resize(width, height) => renderer.resize(width, height),
+                _ => {},
+            }
+        }
+
+        // Render the mesh from both cameras.
+        renderer.draw();
+    }
+}
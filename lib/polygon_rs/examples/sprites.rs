@@ -0,0 +1,114 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate polygon;
+
+use bootstrap::window::*;
+use polygon::*;
+use polygon::anchor::*;
+use polygon::camera::*;
+use polygon::math::*;
+use polygon::mesh_instance::*;
+use polygon::sprite::*;
+use polygon::texture::*;
+
+mod utils;
+
+const BAR_WIDTH: f32 = 200.0;
+const BAR_HEIGHT: f32 = 16.0;
+
+fn main() {
+    // Open a window and create the renderer instance.
+    let mut window = Window::new("Sprites").unwrap();
+    let mut renderer = RendererBuilder::new(&window).build();
+
+    // Draw a mesh behind the sprites so that there's a scene for them to overlay.
+    let mesh = utils::load_mesh("resources/meshes/epps_head.obj").unwrap();
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+    renderer.register_mesh_instance(mesh_instance);
+
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(0.0, 0.0, 2.0));
+    let mut camera = Camera::default();
+    camera.set_anchor(renderer.register_anchor(camera_anchor));
+    renderer.register_camera(camera);
+
+    renderer.set_ambient_light(Color::rgb(1.0, 1.0, 1.0));
+
+    // Every sprite uses the same plain white texture and gets its look from its color, so they're
+    // all drawn with a single draw call.
+    let white = Texture2d::new(1, 1, DataFormat::Rgba, TextureData::u8x4(vec![(255, 255, 255, 255)]));
+    let white = renderer.register_texture(&white);
+
+    // Create a crosshair from a horizontal and a vertical line, centered on their position.
+    let crosshair = [Vector2::new(24.0, 2.0), Vector2::new(2.0, 24.0)];
+    let crosshair_ids = crosshair
+        .iter()
+        .map(|&size| {
+            let sprite_id = renderer.register_sprite(white);
+            let sprite = renderer.get_sprite_mut(sprite_id).unwrap();
+            sprite.size = size;
+            sprite.anchor_point = Vector2::new(0.5, 0.5);
+            sprite_id
+        })
+        .collect::<Vec<_>>();
+
+    // Create a health bar and a stamina bar in the top-left corner. The backgrounds are
+    // registered before the bars, so the bars are drawn on top of them.
+    let bars = [
+        (Vector2::new(16.0, 16.0), Color::rgb(0.9, 0.1, 0.1)),
+        (Vector2::new(16.0, 40.0), Color::rgb(0.1, 0.4, 0.9)),
+    ];
+    for &(position, _) in &bars {
+        let sprite_id = renderer.register_sprite(white);
+        let sprite = renderer.get_sprite_mut(sprite_id).unwrap();
+        sprite.position = position;
+        sprite.size = Vector2::new(BAR_WIDTH, BAR_HEIGHT);
+        sprite.color = Color::new(0.0, 0.0, 0.0, 0.5);
+    }
+    let bar_ids = bars
+        .iter()
+        .map(|&(position, color)| {
+            let sprite_id = renderer.register_sprite(white);
+            let sprite = renderer.get_sprite_mut(sprite_id).unwrap();
+            sprite.position = position;
+            sprite.size = Vector2::new(BAR_WIDTH, BAR_HEIGHT);
+            sprite.color = color;
+            sprite_id
+        })
+        .collect::<Vec<_>>();
+
+    let (top, left, bottom, right) = window.get_rect();
+    let mut center = Vector2::new((right - left) as f32 * 0.5, (bottom - top) as f32 * 0.5);
+
+    let mut t: f32 = 0.0;
+    'outer: loop {
+        while let Some(message) = window.next_message() {
+            match message {
+                Message::Close => break 'outer,
+                Message::Resize(width, height) => {
+                    renderer.resize(width, height);
+
+                    // Sprites are positioned in pixels, so keep the crosshair in the center of
+                    // the resized window.
+                    center = Vector2::new(width as f32 * 0.5, height as f32 * 0.5);
+                },
+                _ => {},
+            }
+        }
+
+        for &sprite_id in &crosshair_ids {
+            renderer.get_sprite_mut(sprite_id).unwrap().position = center;
+        }
+
+        // Drain and refill the bars at different rates.
+        for (index, &sprite_id) in bar_ids.iter().enumerate() {
+            let fill = (t * (index + 1) as f32).sin() * 0.5 + 0.5;
+            renderer.get_sprite_mut(sprite_id).unwrap().size.x = BAR_WIDTH * fill;
+        }
+
+        renderer.draw();
+
+        t += 0.0005;
+    }
+}
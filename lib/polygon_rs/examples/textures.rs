@@ -22,7 +22,7 @@ fn main() {
     let texture = utils::load_texture("resources/textures/structured.bmp");
 
     // Send the mesh to the GPU.
-    let gpu_mesh = renderer.register_mesh(&mesh);
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
 
     // Send the texture to the GPU.
     let gpu_texture = renderer.register_texture(&texture);
@@ -74,7 +74,7 @@ fn main() {
         // Rotate the mesh slightly.
         {
             let anchor = renderer.get_anchor_mut(mesh_anchor_id).unwrap();
-            anchor.set_orientation(Orientation::from_eulers(0.0, 2.0, 0.0) * (t / 2.0));
+            anchor.set_orientation(Orientation::from_eulers(Rad(0.0), Rad(2.0), Rad(0.0)) * (t / 2.0));
         }
 
         // Orbit the light around the mesh.
@@ -2,11 +2,12 @@ extern crate parse_bmp;
 extern crate parse_obj;
 
 use polygon::geometry::mesh::*;
-use polygon::math::Vector2;
+use polygon::math::{PI, Vector2};
 use polygon::texture::Texture2d;
 use self::parse_bmp::Bitmap;
 use self::parse_obj::*;
 use std::path::Path;
+use std::u16;
 
 pub fn load_mesh<P: AsRef<Path>>(path: P) -> Result<Mesh, BuildMeshError> {
     // Load mesh file and normalize indices for OpenGL.
@@ -36,15 +37,34 @@ pub fn load_mesh<P: AsRef<Path>>(path: P) -> Result<Mesh, BuildMeshError> {
         }
     }
 
-    // Create indices list.
+    // Create indices list, using 16 bit indices if there are few enough vertices.
     let indices_count = obj.position_indices().len() as u32 * 3;
-    let indices: Vec<u32> = (0..indices_count).collect();
+    let builder = if indices_count <= u16::MAX as u32 + 1 {
+        let indices: Vec<u16> = (0..indices_count).map(|index| index as u16).collect();
+        MeshBuilder::new().set_indices_u16(&*indices)
+    } else {
+        let indices: Vec<u32> = (0..indices_count).collect();
+        MeshBuilder::new().set_indices(&*indices)
+    };
 
-    MeshBuilder::new()
+    // Make a submesh for each material group, plus one for any faces before the first group.
+    let mut submeshes = Vec::new();
+    if let Some(first) = obj.material_groups().first() {
+        if first.faces.start != 0 {
+            submeshes.push(0..first.faces.start * 3);
+        }
+    }
+    for group in obj.material_groups() {
+        submeshes.push(group.faces.start * 3..group.faces.end * 3);
+    }
+
+    builder
+        .set_submeshes(&*submeshes)
         .set_position_data(&*positions)
         .set_normal_data(&*normals)
         .set_texcoord_data(&*texcoords)
-        .set_indices(&*indices)
+        // Keep edges sharper than 60 degrees hard if the file has no normals.
+        .generate_normals(Some(PI / 3.0))
         .build()
 }
 
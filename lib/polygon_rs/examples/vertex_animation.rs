@@ -0,0 +1,177 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate polygon;
+
+use bootstrap::window::*;
+use polygon::*;
+use polygon::anchor::*;
+use polygon::camera::*;
+use polygon::geometry::mesh::*;
+use polygon::light::*;
+use polygon::math::*;
+use polygon::mesh_instance::*;
+use polygon::texture::*;
+use std::f32::consts::PI;
+
+const COLUMNS: usize = 32;
+const ROWS: usize = 16;
+const FRAMES: usize = 60;
+
+const WIDTH: f32 = 2.0;
+const HEIGHT: f32 = 1.0;
+const AMPLITUDE: f32 = 0.15;
+const WAVE_NUMBER: f32 = 2.0 * PI;
+
+fn main() {
+    // Open a window and create the renderer instance.
+    let mut window = Window::new("Vertex Animation").unwrap();
+    let mut renderer = RendererBuilder::new(&window).build();
+
+    // Build the flag mesh and bake the waving animation for it.
+    let mesh = build_flag_mesh();
+    let (positions, normals) = bake_flag_animation();
+    check_wrap_continuity(&positions);
+
+    // Send the animation textures and the mesh to the GPU.
+    let positions = Texture2d::new(COLUMNS * ROWS, FRAMES, DataFormat::Rgb, TextureData::f32(positions));
+    let normals = Texture2d::new(COLUMNS * ROWS, FRAMES, DataFormat::Rgb, TextureData::f32(normals));
+    let gpu_positions = renderer.register_texture(&positions);
+    let gpu_normals = renderer.register_texture(&normals);
+    let gpu_mesh = renderer.register_vat_mesh(&mesh, gpu_positions, Some(gpu_normals)).unwrap();
+
+    // Create an anchor and register it with the renderer.
+    let mut anchor = Anchor::new();
+    anchor.set_position(Point::new(-WIDTH * 0.5, -HEIGHT * 0.5, 0.0));
+    let mesh_anchor_id = renderer.register_anchor(anchor);
+
+    // Create a mesh instance that plays the animation automatically.
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(mesh_anchor_id);
+    mesh_instance.set_vat_autoplay(30.0);
+    renderer.register_mesh_instance(mesh_instance);
+
+    // Create a camera and an anchor for it.
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(0.0, 0.0, 3.0));
+    let camera_anchor_id = renderer.register_anchor(camera_anchor);
+
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor_id);
+    renderer.register_camera(camera);
+
+    // Light the flag from the front.
+    renderer.register_light(Light::directional(
+        Vector3::new(-1.0, -1.0, -1.0),
+        0.75,
+        Color::rgb(1.0, 1.0, 1.0),
+    ));
+
+    'outer: loop {
+        while let Some(message) = window.next_message() {
+            match message {
+                Message::Close => break 'outer,
+                _ => {},
+            }
+        }
+
+        // Render the mesh.
+        renderer.draw();
+    }
+}
+
+/// Builds a flat grid of vertices in the xy plane.
+fn build_flag_mesh() -> Mesh {
+    let mut positions = Vec::with_capacity(COLUMNS * ROWS);
+    let mut normals = Vec::with_capacity(COLUMNS * ROWS);
+    let mut texcoords = Vec::with_capacity(COLUMNS * ROWS);
+    for row in 0..ROWS {
+        for column in 0..COLUMNS {
+            let u = column as f32 / (COLUMNS - 1) as f32;
+            let v = row as f32 / (ROWS - 1) as f32;
+
+            positions.push(Point::new(u * WIDTH, v * HEIGHT, 0.0));
+            normals.push(Vector3::back());
+            texcoords.push(Vector2::new(u, v));
+        }
+    }
+
+    let mut indices = Vec::with_capacity((COLUMNS - 1) * (ROWS - 1) * 6);
+    for row in 0..ROWS as u32 - 1 {
+        for column in 0..COLUMNS as u32 - 1 {
+            let bottom_left = row * COLUMNS as u32 + column;
+            let bottom_right = bottom_left + 1;
+            let top_left = bottom_left + COLUMNS as u32;
+            let top_right = top_left + 1;
+
+            indices.extend_from_slice(&[
+                bottom_left, bottom_right, top_right,
+                bottom_left, top_right, top_left,
+            ]);
+        }
+    }
+
+    MeshBuilder::new()
+        .set_position_data(&*positions)
+        .set_normal_data(&*normals)
+        .set_texcoord_data(&*texcoords)
+        .set_indices(&*indices)
+        .build()
+        .unwrap()
+}
+
+/// Bakes one full period of a travelling wave, returning the position and normal data.
+///
+/// Each row of the returned data is one frame and each column is one vertex, matching the
+/// layout expected by `Renderer::register_vat_mesh()`. Because the wave's phase covers exactly
+/// one period over all frames, the last frame blends smoothly back into the first.
+fn bake_flag_animation() -> (Vec<f32>, Vec<f32>) {
+    let mut positions = Vec::with_capacity(FRAMES * COLUMNS * ROWS * 3);
+    let mut normals = Vec::with_capacity(FRAMES * COLUMNS * ROWS * 3);
+    for frame in 0..FRAMES {
+        let phase = frame as f32 / FRAMES as f32 * 2.0 * PI;
+
+        for row in 0..ROWS {
+            for column in 0..COLUMNS {
+                let u = column as f32 / (COLUMNS - 1) as f32;
+                let v = row as f32 / (ROWS - 1) as f32;
+                let x = u * WIDTH;
+
+                // The displacement grows along the flag so that the edge at the pole stays put.
+                let angle = WAVE_NUMBER * x - phase;
+                let z = AMPLITUDE * u * angle.sin();
+                positions.extend_from_slice(&[x, v * HEIGHT, z]);
+
+                let slope = AMPLITUDE * (angle.sin() / WIDTH + u * WAVE_NUMBER * angle.cos());
+                let normal = Vector3::new(-slope, 0.0, 1.0).normalized();
+                normals.extend_from_slice(&[normal.x, normal.y, normal.z]);
+            }
+        }
+    }
+
+    (positions, normals)
+}
+
+/// Checks that wrapping from the last frame back to the first doesn't cause a visible jump.
+///
+/// The step from the last frame to the first should be no larger than the largest step between
+/// any two consecutive frames.
+fn check_wrap_continuity(positions: &[f32]) {
+    let frame_len = COLUMNS * ROWS * 3;
+    let frame = |index: usize| &positions[index * frame_len..(index + 1) * frame_len];
+    let step = |from: &[f32], to: &[f32]| {
+        from.iter()
+            .zip(to)
+            .map(|(from, to)| (to - from).abs())
+            .fold(0.0, f32::max)
+    };
+
+    let max_step = (0..FRAMES - 1)
+        .map(|index| step(frame(index), frame(index + 1)))
+        .fold(0.0, f32::max);
+    let wrap_step = step(frame(FRAMES - 1), frame(0));
+
+    assert!(
+        wrap_step <= max_step + 1e-5,
+        "Animation jumps when wrapping, wrap step: {}, max step: {}",
+        wrap_step,
+        max_step);
+}
@@ -0,0 +1,119 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate polygon;
+
+use bootstrap::window::*;
+use polygon::*;
+use polygon::anchor::*;
+use polygon::camera::*;
+use polygon::math::*;
+use polygon::material::*;
+use polygon::mesh_instance::*;
+use polygon::geometry::mesh::*;
+
+/// The number of rows of smaller triangles the triangle is split into.
+const SUBDIVISIONS: u32 = 8;
+
+static VERTEX_COLOR_MATERIAL: &'static str = r#"
+    program frag {
+        @color = @vertex.color;
+    }
+"#;
+
+fn main() {
+    // Open a window and create the renderer instance.
+    let mut window = Window::new("Vertex Colors").unwrap();
+    let mut renderer = RendererBuilder::new(&window).build();
+
+    // Send the triangle mesh to the GPU.
+    let gpu_mesh = renderer.register_mesh(&colored_triangle()).unwrap();
+
+    // Build a material that shows the vertex colors as they are.
+    let material_source = MaterialSource::from_str(VERTEX_COLOR_MATERIAL).unwrap();
+    let material = renderer.build_material(material_source).unwrap();
+
+    // Create an anchor and register it with the renderer.
+    let anchor_id = renderer.register_anchor(Anchor::new());
+
+    // Create a mesh instance, attach it to the anchor, and register it.
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, material);
+    mesh_instance.set_anchor(anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    // Create a camera and an anchor for it.
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(0.0, 0.0, 5.0));
+    let camera_anchor_id = renderer.register_anchor(camera_anchor);
+
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor_id);
+    renderer.register_camera(camera);
+
+    'outer: loop {
+        while let Some(message) = window.next_message() {
+            match message {
+                Message::Close => break 'outer,
+                _ => {},
+            }
+        }
+
+        // Rotate the triangle slightly.
+        {
+            let anchor = renderer.get_anchor_mut(anchor_id).unwrap();
+            let orientation = anchor.orientation();
+            anchor.set_orientation(orientation + Orientation::from_eulers(Rad(0.0), Rad(0.0), Rad(0.0005)));
+        }
+
+        // Render the mesh.
+        renderer.draw();
+    }
+}
+
+/// Builds a triangle split into smaller triangles, coloring each vertex by how close it is to
+/// each corner: red at the bottom left, green at the bottom right, and blue at the top.
+fn colored_triangle() -> Mesh {
+    let corners = [
+        Point::new(-1.0, -1.0, 0.0),
+        Point::new( 1.0, -1.0, 0.0),
+        Point::new( 0.0,  1.0, 0.0),
+    ];
+
+    let mut positions = Vec::new();
+    let mut colors = Vec::new();
+    for row in 0..SUBDIVISIONS + 1 {
+        for column in 0..SUBDIVISIONS + 1 - row {
+            // Barycentric weights of the three corners.
+            let top = row as f32 / SUBDIVISIONS as f32;
+            let right = column as f32 / SUBDIVISIONS as f32;
+            let left = 1.0 - top - right;
+
+            positions.push(Point::new(
+                corners[0].x * left + corners[1].x * right + corners[2].x * top,
+                corners[0].y * left + corners[1].y * right + corners[2].y * top,
+                0.0));
+            colors.push(Color::rgb(left, right, top));
+        }
+    }
+
+    // Each row has one vertex fewer than the row below it.
+    let row_start = |row: u32| (0..row).map(|row| SUBDIVISIONS + 1 - row).sum::<u32>();
+
+    let mut indices = Vec::new();
+    for row in 0..SUBDIVISIONS {
+        let bottom = row_start(row);
+        let top = row_start(row + 1);
+        for column in 0..SUBDIVISIONS - row {
+            indices.extend(&[bottom + column, bottom + column + 1, top + column]);
+
+            if column + 1 < SUBDIVISIONS - row {
+                indices.extend(&[bottom + column + 1, top + column + 1, top + column]);
+            }
+        }
+    }
+
+    MeshBuilder::new()
+        .set_position_data(&positions)
+        .set_color_data(&colors)
+        .set_indices(&indices)
+        .build()
+        .unwrap()
+}
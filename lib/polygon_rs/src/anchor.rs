@@ -1,10 +1,20 @@
 use math::*;
+use math::quaternion::Quaternion;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
 
+/// A transform in the scene that mesh instances, cameras, and lights can be attached to.
+///
+/// An anchor's position, orientation, and scale are relative to its parent, if it has one. See
+/// `set_parent()`.
 #[derive(Debug)]
 pub struct Anchor {
     position: Point,
     orientation: Orientation,
     scale: Vector3,
+    parent: Option<AnchorId>,
+
+    changed: bool,
 }
 
 impl Anchor {
@@ -14,6 +24,9 @@ impl Anchor {
             position: Point::origin(),
             orientation: Orientation::new(),
             scale: Vector3::one(),
+            parent: None,
+
+            changed: true,
         }
     }
 
@@ -24,7 +37,10 @@ impl Anchor {
 
     /// Sets the position of the anchor.
     pub fn set_position(&mut self, position: Point) {
-        self.position = position;
+        if position != self.position {
+            self.position = position;
+            self.changed = true;
+        }
     }
 
     /// Gets the current orientation of the anchor.
@@ -34,7 +50,50 @@ impl Anchor {
 
     /// Sets the orientation of the anchor.
     pub fn set_orientation(&mut self, orientation: Orientation) {
-        self.orientation = orientation;
+        if orientation != self.orientation {
+            self.orientation = orientation;
+            self.changed = true;
+        }
+    }
+
+    /// Rotates the anchor so that its forward axis points at `target`.
+    ///
+    /// The anchor is rolled so that its up axis is as close to `up` as possible. If `up` is
+    /// parallel to the direction of `target` the anchor keeps its current up axis instead, or an
+    /// arbitrary one if that's parallel as well. Does nothing if `target` is at the anchor's
+    /// position, since there's no direction to look in.
+    pub fn look_at(&mut self, target: Point, up: Vector3) {
+        let offset = target - self.position;
+        if offset.is_zero() {
+            return;
+        }
+        let forward = offset.normalized();
+
+        let current_up = self.up();
+        let right = [up, current_up, Vector3::up(), Vector3::right()]
+            .iter()
+            .map(|&up| Vector3::cross(forward, up))
+            .find(|right| !right.is_zero())
+            .expect("Forward vector is parallel to both the x and y axes")
+            .normalized();
+        let up = Vector3::cross(right, forward);
+
+        self.set_orientation(Orientation(Quaternion::from_basis(right, up, -forward)));
+    }
+
+    /// Gets the direction the anchor is facing, relative to its parent.
+    pub fn forward(&self) -> Vector3 {
+        self.orientation.forward()
+    }
+
+    /// Gets the direction to the anchor's right, relative to its parent.
+    pub fn right(&self) -> Vector3 {
+        self.orientation.right()
+    }
+
+    /// Gets the anchor's up direction, relative to its parent.
+    pub fn up(&self) -> Vector3 {
+        self.orientation.up()
     }
 
     /// Gets the current scale of the anchor.
@@ -44,27 +103,68 @@ impl Anchor {
 
     /// Sets the scale of the anchor.
     pub fn set_scale(&mut self, scale: Vector3) {
-        self.scale = scale;
+        if scale != self.scale {
+            self.scale = scale;
+            self.changed = true;
+        }
     }
 
-    /// Calculates the matrix to convert from object space to world space.
-    pub fn matrix(&self) -> Matrix4 {
-        let position = Matrix4::from_point(self.position);
-        let orientation = Matrix4::from(self.orientation);
-        let scale = Matrix4::from_scale_vector(self.scale);
+    /// Gets the anchor's parent, if it has one.
+    pub fn parent(&self) -> Option<AnchorId> {
+        self.parent
+    }
 
-        position * (orientation * scale)
+    /// Attaches the anchor to a parent anchor, or detaches it if `parent` is `None`.
+    ///
+    /// The anchor's transform is relative to its parent, so moving the parent moves the anchor
+    /// along with it. If the parent isn't registered the anchor is treated as if it had no
+    /// parent. Parent chains that loop back on themselves are detected when resolving world
+    /// transforms, see `resolve_world_transforms()`.
+    pub fn set_parent(&mut self, parent: Option<AnchorId>) {
+        if parent != self.parent {
+            self.parent = parent;
+            self.changed = true;
+        }
     }
 
-    /// Calculates the matrix used to convert normals from object space to world space.
-    pub fn normal_matrix(&self) -> Matrix3 {
-        let inv_scale = Matrix3::from_scale_vector(1.0 / self.scale);
-        let orientation: Matrix3 = self.orientation.into();
-        let inv_rotation = orientation.transpose();
-        // let inv_translation = Matrix3::from_point(-self.position);
+    /// Checks if the anchor has moved since the last time the change flag was cleared.
+    ///
+    /// Setting the position, orientation, or scale to the value it already has does not count
+    /// as a change, so it's safe to blindly sync anchors every frame. Renderers use this to
+    /// determine when cached data that depends on the anchor (e.g. static shadow maps) needs to
+    /// be regenerated.
+    pub fn has_changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Clears the change flag for the anchor.
+    ///
+    /// This is done by the renderer at the end of each frame.
+    pub fn clear_changed(&mut self) {
+        self.changed = false;
+    }
+
+    /// Calculates the matrix to convert from object space to the space of the anchor's parent.
+    ///
+    /// For anchors without a parent this is world space, otherwise use
+    /// `resolve_world_transforms()` to get the transform to world space.
+    pub fn matrix(&self) -> Matrix4 {
+        Matrix4::trs(self.position, self.orientation.0, self.scale)
+    }
 
-        let inverse = inv_scale * (inv_rotation);// * inv_translation);
-        inverse.transpose()
+    /// Calculates the matrix used to convert normals from object space to the space of the
+    /// anchor's parent.
+    ///
+    /// This is the inverse-transpose of the rotation and scale of `matrix()`, so that normals
+    /// stay perpendicular to their surface under non-uniform scale. Normals have to be
+    /// normalized after being transformed. If the anchor is scaled to zero on any axis the
+    /// scale is ignored, since there's no meaningful normal for a flattened mesh.
+    pub fn normal_matrix(&self) -> Matrix3 {
+        let model = Matrix3::from(self.matrix());
+        match model.inverse() {
+            Some(inverse) => inverse.transpose(),
+            None => self.orientation.into(),
+        }
     }
 
     /// Calculates the view transform for the camera.
@@ -89,3 +189,127 @@ impl Anchor {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct AnchorId(usize);
 derive_Counter!(AnchorId);
+
+/// The transforms of an anchor in world space, accounting for all of the anchor's parents.
+///
+/// Provides the same transforms as `Anchor`, see `resolve_world_transforms()`.
+#[derive(Debug, Clone, Copy)]
+pub struct WorldTransform {
+    matrix: Matrix4,
+    normal_matrix: Matrix3,
+
+    /// The combined rotation of the anchor and its parents, without any scale.
+    rotation: Matrix4,
+}
+
+impl WorldTransform {
+    /// Gets the position of the anchor in world space.
+    pub fn position(&self) -> Point {
+        self.matrix.translation_part()
+    }
+
+    /// Gets the matrix to convert from object space to world space.
+    pub fn matrix(&self) -> Matrix4 {
+        self.matrix
+    }
+
+    /// Gets the matrix used to convert normals from object space to world space.
+    pub fn normal_matrix(&self) -> Matrix3 {
+        self.normal_matrix
+    }
+
+    /// Calculates the view transform for a camera attached to the anchor.
+    ///
+    /// As with `Anchor::view_matrix()`, scale doesn't affect the view transform.
+    pub fn view_matrix(&self) -> Matrix4 {
+        let position = self.position();
+        let inv_translation = Matrix4::translation(-position.x, -position.y, -position.z);
+        self.rotation.transpose() * inv_translation
+    }
+
+    /// Calculates the inverse view matrix.
+    pub fn inverse_view_matrix(&self) -> Matrix4 {
+        Matrix4::from_point(self.position()) * self.rotation
+    }
+}
+
+/// Indicates that a chain of anchor parents loops back on itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorCycle {
+    /// The anchors in the cycle, with each anchor followed by its parent.
+    pub anchors: Vec<AnchorId>,
+}
+
+impl Display for AnchorCycle {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "Anchors {:?} are each other's parents", self.anchors)
+    }
+}
+
+/// Calculates the world transform of each anchor in `anchors` by walking its chain of parents.
+///
+/// Transforms already in `world_transforms` are reused, so that each anchor's transform is only
+/// calculated once even when it has many children. Anchors whose parent isn't in `anchors` are
+/// treated as having no parent.
+///
+/// Returns an error for the first parent cycle found. The transforms of anchors outside of the
+/// cycle that were resolved before the cycle was found are kept in `world_transforms`, so the
+/// caller can break the cycle and call `resolve_world_transforms()` again to resolve the rest.
+pub fn resolve_world_transforms(
+    anchors: &HashMap<AnchorId, Anchor>,
+    world_transforms: &mut HashMap<AnchorId, WorldTransform>,
+) -> Result<(), AnchorCycle> {
+    let mut chain = Vec::new();
+    for (&anchor_id, anchor) in anchors {
+        if world_transforms.contains_key(&anchor_id) {
+            continue;
+        }
+
+        // Walk up the parent chain until reaching a root or an anchor that's already resolved.
+        chain.clear();
+        chain.push(anchor_id);
+        let mut next_parent = anchor.parent();
+        let mut base = None;
+        while let Some(parent_id) = next_parent {
+            if let Some(&parent_transform) = world_transforms.get(&parent_id) {
+                base = Some(parent_transform);
+                break;
+            }
+
+            let parent = match anchors.get(&parent_id) {
+                Some(parent) => parent,
+                None => break,
+            };
+
+            if let Some(index) = chain.iter().position(|&id| id == parent_id) {
+                return Err(AnchorCycle { anchors: chain[index..].to_vec() });
+            }
+
+            chain.push(parent_id);
+            next_parent = parent.parent();
+        }
+
+        // Resolve the chain from the top down, so that each anchor builds on its parent.
+        for &id in chain.iter().rev() {
+            let anchor = &anchors[&id];
+            let rotation = Matrix4::from(anchor.orientation());
+            let world_transform = match base {
+                Some(parent) => WorldTransform {
+                    matrix: parent.matrix * anchor.matrix(),
+                    normal_matrix: parent.normal_matrix * anchor.normal_matrix(),
+                    rotation: parent.rotation * rotation,
+                },
+                None => WorldTransform {
+                    matrix: anchor.matrix(),
+                    normal_matrix: anchor.normal_matrix(),
+                    rotation: rotation,
+                },
+            };
+
+            world_transforms.insert(id, world_transform);
+            base = Some(world_transform);
+        }
+    }
+
+    Ok(())
+}
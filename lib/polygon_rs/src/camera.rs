@@ -5,23 +5,38 @@ use math::*;
 #[derive(Debug, Clone)]
 pub struct Camera
 {
-    fov: f32,
-    aspect: f32,
+    projection: Projection,
+    aspect: Aspect,
+
+    /// The aspect ratio of the region of the window the camera last rendered to, used when the
+    /// camera follows the window's aspect ratio.
+    viewport_aspect: f32,
+
     near: f32,
     far: f32,
 
+    priority: i32,
+    viewport: Option<ViewportRect>,
+    layers: u32,
+
     anchor: Option<AnchorId>,
 }
 
 impl Camera
 {
-    pub fn new(fov: f32, aspect: f32, near: f32, far: f32) -> Camera {
+    /// Creates a new perspective camera with a fixed aspect ratio.
+    pub fn new<A: Into<Rad>>(fov: A, aspect: f32, near: f32, far: f32) -> Camera {
         Camera {
-            fov: fov,
-            aspect: aspect,
+            projection: Projection::Perspective { fov: fov.into() },
+            aspect: Aspect::Fixed(aspect),
+            viewport_aspect: aspect,
             near: near,
             far: far,
 
+            priority: 0,
+            viewport: None,
+            layers: 1,
+
             anchor: None,
         }
     }
@@ -31,16 +46,21 @@ impl Camera
     /// The projection matrix is the matrix that converts from camera space to
     /// clip space. This effectively converts the viewing frustrum into a unit cube.
     pub fn projection_matrix(&self) -> Matrix4 {
-        let height = 2.0 * self.near * (self.fov * 0.5).tan();
-        let width = self.aspect * height;
+        match self.projection {
+            Projection::Perspective { fov } => {
+                Matrix4::perspective(fov, self.aspect_ratio(), self.near, self.far)
+            },
 
-        let mut projection = Matrix4::new();
-        projection[0][0] = 2.0 * self.near / width;
-        projection[1][1] = 2.0 * self.near / height;
-        projection[2][2] = -(self.far + self.near) / (self.far - self.near);
-        projection[2][3] = -2.0 * self.far * self.near / (self.far - self.near);
-        projection[3][2] = -1.0;
-        projection
+            Projection::Orthographic { half_height } => {
+                let half_width = self.aspect_ratio() * half_height;
+                Matrix4::orthographic(-half_width, half_width, -half_height, half_height, self.near, self.far)
+            },
+        }
+    }
+
+    /// Gets the camera's current projection mode.
+    pub fn projection(&self) -> Projection {
+        self.projection
     }
 
     pub fn anchor(&self) -> Option<AnchorId> {
@@ -51,17 +71,66 @@ impl Camera
         self.anchor = Some(anchor_id);
     }
 
-    pub fn set_fov(&mut self, fov: f32) {
-        debug_assert!(fov > 0.0, "Field of view must be non-negative: {}", fov);
-        debug_assert!(fov < PI * 2.0, "Field of view must be less than 180 degrees: {}", fov);
-        self.fov = fov;
+    /// Sets the camera's field of view, switching to a perspective projection if the camera is
+    /// currently orthographic.
+    ///
+    /// This is the same as `set_perspective()`.
+    pub fn set_fov<A: Into<Rad>>(&mut self, fov: A) {
+        self.set_perspective(fov);
     }
 
-    pub fn set_aspect(&mut self, aspect: f32) {
-        debug_assert!(aspect > 0.0, "Aspect ratio must be non-negative: {}", aspect);
+    /// Makes the camera use a perspective projection with a vertical field of view of `fov`,
+    /// either `Rad` or `Deg`.
+    pub fn set_perspective<A: Into<Rad>>(&mut self, fov: A) {
+        let fov = fov.into();
+        debug_assert!(fov.0 > 0.0, "Field of view must be non-negative: {}", fov);
+        debug_assert!(fov.0 < PI, "Field of view must be less than 180 degrees: {}", Deg::from(fov));
+        self.projection = Projection::Perspective { fov: fov };
+    }
+
+    /// Makes the camera use an orthographic projection that shows `half_height` world units
+    /// above and below the center of the view.
+    ///
+    /// The visible width is determined by the camera's aspect ratio, so the view isn't stretched
+    /// when the window is resized. Objects don't get smaller with distance, which makes this
+    /// useful for 2D games and UI.
+    pub fn set_orthographic(&mut self, half_height: f32) {
+        debug_assert!(half_height > 0.0, "Orthographic half height must be positive: {}", half_height);
+        self.projection = Projection::Orthographic { half_height: half_height };
+    }
+
+    /// Gets how the camera's aspect ratio is determined.
+    pub fn aspect(&self) -> Aspect {
+        self.aspect
+    }
+
+    /// Gets the aspect ratio (width divided by height) currently used by the camera's projection.
+    pub fn aspect_ratio(&self) -> f32 {
+        match self.aspect {
+            Aspect::Fixed(aspect) => aspect,
+            Aspect::Window => self.viewport_aspect,
+        }
+    }
+
+    /// Sets how the camera's aspect ratio is determined.
+    ///
+    /// Cameras created with `Camera::default()` follow the aspect ratio of the region of the
+    /// window they render to.
+    pub fn set_aspect(&mut self, aspect: Aspect) {
+        if let Aspect::Fixed(aspect) = aspect {
+            debug_assert!(aspect > 0.0, "Aspect ratio must be non-negative: {}", aspect);
+        }
         self.aspect = aspect;
     }
 
+    /// Updates the aspect ratio of the region of the window the camera renders to.
+    ///
+    /// Called by the renderer when the window is resized or the camera's viewport changes.
+    pub(crate) fn set_viewport_aspect(&mut self, aspect: f32) {
+        debug_assert!(aspect > 0.0, "Aspect ratio must be non-negative: {}", aspect);
+        self.viewport_aspect = aspect;
+    }
+
     pub fn set_near(&mut self, near: f32) {
         debug_assert!(near > 0.0, "Near plane distance must be non-negative: {}", near);
         debug_assert!(near < self.far, "Near plane distance must be less than far plane distance, near: {}, far: {}", near, self.far);
@@ -73,23 +142,130 @@ impl Camera
         debug_assert!(far > self.near, "Far plane distance must be greater than near plane distance, near: {}, far: {}", self.near, far);
         self.far = far;
     }
+
+    /// Gets the camera's priority.
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Sets the order the camera is rendered in relative to other cameras.
+    ///
+    /// Cameras are rendered from lowest to highest priority, so a camera with a higher priority
+    /// is drawn on top of cameras with a lower priority. Cameras with the same priority are
+    /// rendered in the order they were registered.
+    pub fn set_priority(&mut self, priority: i32) {
+        self.priority = priority;
+    }
+
+    /// Gets the region of the window that the camera renders to, or `None` if it renders to the
+    /// whole window.
+    pub fn viewport(&self) -> Option<ViewportRect> {
+        self.viewport
+    }
+
+    /// Sets the region of the window that the camera renders to.
+    ///
+    /// Pass `None` to render to the whole window, which is the default.
+    pub fn set_viewport(&mut self, viewport: Option<ViewportRect>) {
+        if let Some(viewport) = viewport {
+            debug_assert!(
+                viewport.x >= 0.0 && viewport.y >= 0.0 && viewport.width > 0.0 && viewport.height > 0.0,
+                "Viewport must have non-negative position and positive size: {:?}",
+                viewport);
+            debug_assert!(
+                viewport.x + viewport.width <= 1.0 && viewport.y + viewport.height <= 1.0,
+                "Viewport must fit within the window: {:?}",
+                viewport);
+        }
+        self.viewport = viewport;
+    }
+
+    /// Gets the bitmask of render layers the camera draws.
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// Sets the bitmask of render layers the camera draws, defaults to `1`.
+    ///
+    /// The camera only draws mesh instances that are on at least one of its layers, see
+    /// `MeshInstance::set_layers()`.
+    pub fn set_layers(&mut self, layers: u32) {
+        self.layers = layers;
+    }
 }
 
 impl Default for Camera {
     /// Creates a new
     fn default() -> Camera {
         Camera {
-            fov: PI / 3.0,
-            aspect: 1.0,
+            projection: Projection::Perspective { fov: Rad(PI / 3.0) },
+            aspect: Aspect::Window,
+            viewport_aspect: 1.0,
             near: 0.001,
             far: 1_000.0,
 
+            priority: 0,
+            viewport: None,
+            layers: 1,
+
             anchor: None,
         }
     }
 }
 
+/// How a camera's aspect ratio is determined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aspect {
+    /// The camera always uses the given aspect ratio, even if the image is stretched as a result.
+    Fixed(f32),
+
+    /// The camera matches the aspect ratio of the region of the window it renders to, so that
+    /// the image isn't stretched when the window is resized.
+    Window,
+}
+
+impl Default for Aspect {
+    fn default() -> Aspect {
+        Aspect::Window
+    }
+}
+
+/// The kind of projection a camera uses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Objects get smaller as they get farther from the camera, `fov` is the vertical field of
+    /// view.
+    Perspective { fov: Rad },
+
+    /// Objects are the same size regardless of distance, `half_height` is half the height of
+    /// the view in world units.
+    Orthographic { half_height: f32 },
+}
+
+/// A region of the window, in coordinates normalized to the window's size.
+///
+/// `(0.0, 0.0)` is the bottom-left corner of the window and `(1.0, 1.0)` is the top-right
+/// corner, so the viewport stays the same portion of the window when the window is resized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewportRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ViewportRect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> ViewportRect {
+        ViewportRect {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        }
+    }
+}
+
 /// Identifies an achor that has been registered with the renderer.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct CameraId(usize);
 derive_Counter!(CameraId);
@@ -1,19 +1,80 @@
+use GpuMesh;
 use math::*;
+use skeleton::MAX_BONES;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+use std::u16;
 
 pub type MeshIndex = u32;
 
+/// The index data of a mesh, either as 16 or 32 bit indices.
+///
+/// 16 bit indices use half the memory but can only refer to the first 65536 vertices of a mesh.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Indices {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl Indices {
+    /// Gets the number of indices.
+    pub fn len(&self) -> usize {
+        match *self {
+            Indices::U16(ref indices) => indices.len(),
+            Indices::U32(ref indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the index at `position`, widened to a `MeshIndex` if it's a 16 bit index.
+    ///
+    /// # Panics
+    ///
+    /// - If `position` is out of bounds.
+    pub fn get(&self, position: usize) -> MeshIndex {
+        match *self {
+            Indices::U16(ref indices) => indices[position] as MeshIndex,
+            Indices::U32(ref indices) => indices[position],
+        }
+    }
+
+    /// Gets the size of the index data in bytes.
+    pub fn byte_len(&self) -> usize {
+        match *self {
+            Indices::U16(ref indices) => indices.len() * 2,
+            Indices::U32(ref indices) => indices.len() * 4,
+        }
+    }
+}
+
 /// The raw data representing a mesh in memory.
 ///
-/// Meshes are represented as list of vertex positions and a list of faces.
-/// Each face is represented as 3 indices into the vertex array.
+/// Meshes are represented as list of vertex positions and a list of indices into the vertex
+/// array. How the indices are assembled into primitives is set by the mesh's topology, by default
+/// each face is represented as 3 indices.
 #[derive(Debug, Clone)]
 pub struct Mesh {
     vertex_data: Vec<f32>,
-    indices:     Vec<MeshIndex>,
+    vertex_count: usize,
+    indices:     Indices,
 
     position: VertexAttribute,
     normal:   Option<VertexAttribute>,
+    color:    Option<VertexAttribute>,
+    tangent:  Option<VertexAttribute>,
     texcoord: Vec<VertexAttribute>,
+
+    bone_indices: Option<VertexAttribute>,
+    bone_weights: Option<VertexAttribute>,
+
+    submeshes: Vec<Range<usize>>,
+    topology: Topology,
+    bounding_sphere: Sphere,
+    bounding_box: Aabb,
 }
 
 impl Mesh {
@@ -21,8 +82,13 @@ impl Mesh {
         &*self.vertex_data
     }
 
-    pub fn indices(&self) -> &[MeshIndex] {
-        &*self.indices
+    /// Gets the number of vertices in the mesh.
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    pub fn indices(&self) -> &Indices {
+        &self.indices
     }
 
     pub fn position(&self) -> VertexAttribute {
@@ -33,9 +99,140 @@ impl Mesh {
         self.normal
     }
 
+    /// Gets the attribute for the mesh's vertex colors, stored as four `f32` components per
+    /// vertex.
+    pub fn color(&self) -> Option<VertexAttribute> {
+        self.color
+    }
+
+    /// Gets the attribute for the mesh's tangents, stored as four `f32` components per vertex.
+    ///
+    /// The first three components are the tangent's direction and the fourth is `1.0` or `-1.0`
+    /// depending on the handedness of the tangent space, see `MeshBuilder::set_tangent_data()`.
+    pub fn tangent(&self) -> Option<VertexAttribute> {
+        self.tangent
+    }
+
+    /// Gets the attributes for each of the mesh's texcoord channels.
     pub fn texcoord(&self) -> &[VertexAttribute] {
         &*self.texcoord
     }
+
+    /// Gets the attribute for the indices of the bones that influence each vertex, stored as four
+    /// `f32` components per vertex.
+    ///
+    /// The indices are whole numbers, stored as floats like the rest of the vertex data.
+    pub fn bone_indices(&self) -> Option<VertexAttribute> {
+        self.bone_indices
+    }
+
+    /// Gets the attribute for the weight of each bone that influences each vertex, stored as four
+    /// `f32` components per vertex.
+    pub fn bone_weights(&self) -> Option<VertexAttribute> {
+        self.bone_weights
+    }
+
+    /// Returns `true` if the mesh has bone indices and weights, and is drawn using the pose of
+    /// the mesh instance, see `MeshInstance::set_pose()`.
+    pub fn is_skinned(&self) -> bool {
+        self.bone_indices.is_some() && self.bone_weights.is_some()
+    }
+
+    /// Gets the ranges of indices of each of the mesh's submeshes.
+    ///
+    /// Each submesh can be drawn with a different material, see
+    /// `MeshInstance::set_submesh_materials()`. Meshes built without submeshes have a single
+    /// submesh covering every index.
+    pub fn submeshes(&self) -> &[Range<usize>] {
+        &*self.submeshes
+    }
+
+    /// Gets how the mesh's indices are assembled into primitives.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Gets a sphere in model space that contains every vertex of the mesh.
+    ///
+    /// The sphere bounds the vertex data as it was built, so it doesn't account for skinning or
+    /// vertex animation.
+    pub fn bounding_sphere(&self) -> Sphere {
+        self.bounding_sphere
+    }
+
+    /// Gets the axis-aligned box in model space that contains every vertex of the mesh.
+    ///
+    /// As with `bounding_sphere()`, the box doesn't account for skinning or vertex animation.
+    pub fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+
+    /// Checks that the mesh can be drawn without reading outside of its vertex data.
+    ///
+    /// Renderers check meshes with this before uploading them, see `Renderer::register_mesh()`.
+    pub fn validate(&self) -> Result<(), MeshError> {
+        let vertex_count = self.vertex_count as MeshIndex;
+        let indices = &self.indices;
+        if let Some(index) = (0..indices.len()).map(|position| indices.get(position)).find(|&index| index >= vertex_count) {
+            return Err(MeshError::IndexOutOfBounds { vertex_count: vertex_count, index: index });
+        }
+
+        if !self.topology.is_complete(self.indices.len()) {
+            return Err(match self.topology {
+                Topology::Triangles => MeshError::IncompleteTriangle { index_count: self.indices.len() },
+                topology => MeshError::IncompletePrimitive { topology: topology, index_count: self.indices.len() },
+            });
+        }
+
+        let attributes = Some((VertexAttributeType::Position, self.position)).into_iter()
+            .chain(self.normal.map(|normal| (VertexAttributeType::Normal, normal)))
+            .chain(self.color.map(|color| (VertexAttributeType::Color, color)))
+            .chain(self.tangent.map(|tangent| (VertexAttributeType::Tangent, tangent)))
+            .chain(self.texcoord.iter().map(|&texcoord| (VertexAttributeType::Texcoord, texcoord)))
+            .chain(self.bone_indices.map(|bone_indices| (VertexAttributeType::BoneIndices, bone_indices)))
+            .chain(self.bone_weights.map(|bone_weights| (VertexAttributeType::BoneWeights, bone_weights)));
+        for (attribute_type, attribute) in attributes {
+            // The end of the last vertex's data for the attribute.
+            let stride = if attribute.stride == 0 { attribute.elements } else { attribute.stride };
+            let end = match self.vertex_count {
+                0 => 0,
+                vertex_count => attribute.offset + (vertex_count - 1) * stride + attribute.elements,
+            };
+
+            if end > self.vertex_data.len() {
+                return Err(MeshError::AttributeOutOfBounds {
+                    attribute_type: attribute_type,
+                    attribute: attribute,
+                    vertex_count: self.vertex_count,
+                    data_len: self.vertex_data.len(),
+                });
+            }
+        }
+
+        // Bone indices are used to index the array of bone transforms when drawing.
+        if let Some(bone_indices) = self.bone_indices {
+            let stride = if bone_indices.stride == 0 { bone_indices.elements } else { bone_indices.stride };
+            for vertex in 0..self.vertex_count {
+                let start = bone_indices.offset + vertex * stride;
+                let vertex_bones = &self.vertex_data[start..start + bone_indices.elements];
+                if let Some(&bone) = vertex_bones.iter().find(|&&bone| bone >= MAX_BONES as f32) {
+                    return Err(MeshError::BoneOutOfBounds { vertex: vertex, bone: bone as u32 });
+                }
+            }
+        }
+
+        for submesh in &self.submeshes {
+            if submesh.start > submesh.end || submesh.end > self.indices.len() {
+                return Err(MeshError::SubmeshOutOfBounds {
+                    start: submesh.start,
+                    end: submesh.end,
+                    index_count: self.indices.len(),
+                });
+            }
+        }
+
+        Ok(())
+    }
 }
 
 /// Represents a single vertex in a mesh with all of its supported attributes.
@@ -43,9 +240,21 @@ impl Mesh {
 pub struct Vertex {
     pub position: Point,
     pub normal: Option<Vector3>,
+    pub color: Option<Color>,
+    pub tangent: Option<[f32; 4]>,
+
+    /// The indices of the four bones that influence the vertex, see
+    /// `MeshBuilder::set_bone_index_data()`.
+    pub bone_indices: Option<[u32; 4]>,
+
+    /// The weights of the four bones that influence the vertex, see
+    /// `MeshBuilder::set_bone_weight_data()`.
+    pub bone_weights: Option<[f32; 4]>,
 
-    /// Support an arbitrary number of texture units. The actual maximum is dependent on hardware
-    /// and so is not limited by polygon directly. If the number of
+    /// The texture coordinates of the vertex, one for each texcoord channel.
+    ///
+    /// Every vertex in a mesh must have the same number of texcoords. Renderers may not support
+    /// every channel, see `Renderer::register_mesh()`.
     pub texcoord: Vec<Vector2>,
 }
 
@@ -54,11 +263,64 @@ impl Vertex {
         Vertex {
             position: position,
             normal: None,
+            color: None,
+            tangent: None,
+            bone_indices: None,
+            bone_weights: None,
             texcoord: Vec::new(),
         }
     }
 }
 
+/// How a mesh's indices are assembled into primitives when it's drawn.
+///
+/// Only triangles are lit, meshes made of lines and points are drawn once using the unlit color
+/// of their material. Normals and tangents are only generated for triangles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// Every 3 indices form a triangle.
+    Triangles,
+
+    /// Every 2 indices form a line segment.
+    Lines,
+
+    /// The indices form a connected series of line segments, each index after the first adds a
+    /// segment from the previous index.
+    LineStrip,
+
+    /// Each index is drawn as a point.
+    Points,
+}
+
+impl Topology {
+    /// Gets the number of indices that submeshes must be aligned to, so that each submesh starts
+    /// and ends on a whole primitive.
+    pub fn index_alignment(self) -> usize {
+        match self {
+            Topology::Triangles => 3,
+            Topology::Lines => 2,
+            Topology::LineStrip | Topology::Points => 1,
+        }
+    }
+
+    /// Returns `true` if `index_count` indices form only complete primitives.
+    ///
+    /// A line strip needs at least 2 indices for its first segment, but no indices at all is
+    /// also complete.
+    pub fn is_complete(self, index_count: usize) -> bool {
+        match self {
+            Topology::LineStrip => index_count != 1,
+            topology => index_count % topology.index_alignment() == 0,
+        }
+    }
+}
+
+impl Default for Topology {
+    fn default() -> Topology {
+        Topology::Triangles
+    }
+}
+
 /// A struct describing the single attribute within a mesh's vertex buffer.
 #[derive(Debug, Clone, Copy)]
 pub struct VertexAttribute {
@@ -69,9 +331,115 @@ pub struct VertexAttribute {
     pub offset: usize,
 
     /// The stride in elements between consecutive vertices.
+    ///
+    /// A stride of `0` means that the attribute's data is tightly packed, i.e. the stride is the
+    /// same as `elements`.
     pub stride: usize,
 }
 
+/// How the data for each vertex attribute is arranged in a mesh's vertex buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VertexLayout {
+    /// The data for each attribute is stored contiguously, one attribute after another.
+    Planar,
+
+    /// The attributes of each vertex are stored next to each other, one vertex after another.
+    Interleaved,
+}
+
+impl Default for VertexLayout {
+    fn default() -> VertexLayout {
+        VertexLayout::Planar
+    }
+}
+
+/// An error describing why a mesh can't be drawn, see `Mesh::validate()`.
+#[derive(Debug, Clone, Copy)]
+pub enum MeshError {
+    /// One of the mesh's indices doesn't refer to a vertex.
+    IndexOutOfBounds {
+        vertex_count: MeshIndex,
+        index: MeshIndex,
+    },
+
+    /// The number of indices isn't a multiple of 3, so the last triangle is incomplete.
+    IncompleteTriangle {
+        index_count: usize,
+    },
+
+    /// The indices of a mesh with a topology other than `Topology::Triangles` don't form whole
+    /// primitives, see `Topology::is_complete()`.
+    IncompletePrimitive {
+        topology: Topology,
+        index_count: usize,
+    },
+
+    /// The data for one of the mesh's attributes would be read from past the end of the vertex
+    /// data.
+    AttributeOutOfBounds {
+        attribute_type: VertexAttributeType,
+        attribute: VertexAttribute,
+        vertex_count: usize,
+        data_len: usize,
+    },
+
+    /// One of the bone indices of a skinned mesh is `MAX_BONES` or greater.
+    BoneOutOfBounds {
+        vertex: usize,
+        bone: u32,
+    },
+
+    /// The range of indices of one of the mesh's submeshes is out of bounds.
+    SubmeshOutOfBounds {
+        start: usize,
+        end: usize,
+        index_count: usize,
+    },
+
+    /// A vertex animation needs more textures in the vertex stage than the implementation
+    /// supports, see `Renderer::register_vat_mesh()`.
+    VertexTexturesUnsupported {
+        required: usize,
+        supported: usize,
+    },
+
+    /// The mesh passed to `Renderer::update_mesh()` isn't registered with the renderer.
+    UnregisteredMesh {
+        mesh: GpuMesh,
+    },
+}
+
+impl Display for MeshError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            MeshError::IndexOutOfBounds { vertex_count, index } =>
+                write!(formatter, "Mesh index {} is out of bounds for {} vertices", index, vertex_count),
+            MeshError::IncompleteTriangle { index_count } =>
+                write!(formatter, "Mesh has {} indices, which is not a multiple of 3", index_count),
+            MeshError::IncompletePrimitive { topology, index_count } =>
+                write!(formatter, "Mesh has {} indices, which don't form whole {:?} primitives", index_count, topology),
+            MeshError::AttributeOutOfBounds { attribute_type, attribute, vertex_count, data_len } =>
+                write!(
+                    formatter,
+                    "Mesh {:?} attribute (offset {}, stride {}, {} elements) for {} vertices runs past the end of the {} elements of vertex data",
+                    attribute_type,
+                    attribute.offset,
+                    attribute.stride,
+                    attribute.elements,
+                    vertex_count,
+                    data_len),
+            MeshError::BoneOutOfBounds { vertex, bone } =>
+                write!(formatter, "Mesh vertex {} uses bone {}, but there can only be {} bones", vertex, bone, MAX_BONES),
+            MeshError::SubmeshOutOfBounds { start, end, index_count } =>
+                write!(formatter, "Mesh submesh {}..{} is out of bounds for {} indices", start, end, index_count),
+            MeshError::VertexTexturesUnsupported { required, supported } =>
+                write!(formatter, "Vertex animation needs {} vertex texture units, but only {} are supported", required, supported),
+            MeshError::UnregisteredMesh { mesh } =>
+                write!(formatter, "{:?} isn't registered with the renderer", mesh),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum BuildMeshError {
     IndexOutOfBounds {
@@ -86,13 +454,25 @@ pub enum BuildMeshError {
         expected: usize,
         actual: usize,
     },
+
+    /// Indicates that a submesh's range of indices was out of bounds, or didn't cover whole
+    /// primitives.
+    InvalidSubmesh {
+        start: usize,
+        end: usize,
+        index_count: usize,
+    },
 }
 
 #[derive(Debug, Clone, Copy)]
 pub enum VertexAttributeType {
     Position,
     Normal,
+    Color,
+    Tangent,
     Texcoord,
+    BoneIndices,
+    BoneWeights,
 }
 
 /// Provides a safe interface for building a mesh from raw vertex data.
@@ -107,13 +487,30 @@ pub enum VertexAttributeType {
 /// - Check for different data count for different attributes (e.g. if the position attribute data
 ///   for a different number of elements than the normal attribute).
 /// - Any of the indicies would be out of bounds for the given vertex data.
+/// - Only one of the bone indices and bone weights was set.
+///
+/// The attribute data is laid out in the mesh's vertex buffer as set by `set_layout()`, and the
+/// attributes of the built mesh describe where each attribute's data ended up.
 #[derive(Debug, Clone)]
 pub struct MeshBuilder {
     position_data: Vec<Point>,
     normal_data: Vec<Vector3>,
-    texcoord_data: Vec<Vector2>,
+    color_data: Vec<Color>,
+    tangent_data: Vec<[f32; 4]>,
+    texcoord_data: Vec<Vec<Vector2>>,
+    bone_index_data: Vec<[u32; 4]>,
+    bone_weight_data: Vec<[f32; 4]>,
 
     indices:  Vec<u32>,
+    u16_indices: bool,
+    submeshes: Vec<Range<usize>>,
+
+    generate_normals: bool,
+    split_angle: Option<f32>,
+    generate_tangents: bool,
+
+    layout: VertexLayout,
+    topology: Topology,
 }
 
 impl MeshBuilder {
@@ -121,8 +518,21 @@ impl MeshBuilder {
         MeshBuilder {
             position_data: Vec::new(),
             normal_data:   Vec::new(),
+            color_data:    Vec::new(),
+            tangent_data:  Vec::new(),
             texcoord_data: Vec::new(),
+            bone_index_data: Vec::new(),
+            bone_weight_data: Vec::new(),
             indices:       Vec::new(),
+            u16_indices:   false,
+            submeshes:     Vec::new(),
+
+            generate_normals: false,
+            split_angle: None,
+            generate_tangents: false,
+
+            layout: VertexLayout::default(),
+            topology: Topology::default(),
         }
     }
 
@@ -133,11 +543,29 @@ impl MeshBuilder {
             self.normal_data.push(normal);
         }
 
-        assert!(vertex.texcoord.len() <= 1, "More than one texcoord per vertex is currently not supported");
+        if let Some(color) = vertex.color {
+            self.color_data.push(color);
+        }
+
+        if let Some(tangent) = vertex.tangent {
+            self.tangent_data.push(tangent);
+        }
+
+        if let Some(bone_indices) = vertex.bone_indices {
+            self.bone_index_data.push(bone_indices);
+        }
+
+        if let Some(bone_weights) = vertex.bone_weights {
+            self.bone_weight_data.push(bone_weights);
+        }
 
         // Add each texcoord to its corresponding list.
-        if vertex.texcoord.len() > 0 {
-            self.texcoord_data.push(vertex.texcoord[0])
+        for (channel, texcoord) in vertex.texcoord.into_iter().enumerate() {
+            if channel == self.texcoord_data.len() {
+                self.texcoord_data.push(Vec::new());
+            }
+
+            self.texcoord_data[channel].push(texcoord);
         }
     }
 
@@ -158,19 +586,143 @@ impl MeshBuilder {
         self
     }
 
-    pub fn set_texcoord_data(mut self, texcoord_data: &[Vector2]) -> MeshBuilder {
-        self.texcoord_data.clear();
-        self.texcoord_data.extend(texcoord_data);
+    pub fn set_color_data(mut self, color_data: &[Color]) -> MeshBuilder {
+        self.color_data.clear();
+        self.color_data.extend(color_data);
+        self
+    }
+
+    /// Sets the tangent of each vertex.
+    ///
+    /// Each tangent is given as `[x, y, z, w]`, where `(x, y, z)` is the direction of increasing
+    /// U in the first texcoord channel and `w` is `1.0` or `-1.0` such that the bitangent is
+    /// `cross(normal, tangent) * w`.
+    pub fn set_tangent_data(mut self, tangent_data: &[[f32; 4]]) -> MeshBuilder {
+        self.tangent_data.clear();
+        self.tangent_data.extend(tangent_data);
+        self
+    }
+
+    /// Sets the data for the first texcoord channel.
+    pub fn set_texcoord_data(self, texcoord_data: &[Vector2]) -> MeshBuilder {
+        self.set_texcoord_channel_data(0, texcoord_data)
+    }
+
+    /// Sets the data for the specified texcoord channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` would leave a gap in the channels, i.e. if there is no data for the
+    /// channel before it.
+    pub fn set_texcoord_channel_data(mut self, channel: usize, texcoord_data: &[Vector2]) -> MeshBuilder {
+        assert!(
+            channel <= self.texcoord_data.len(),
+            "Cannot set texcoord channel {} before setting channel {}",
+            channel,
+            self.texcoord_data.len());
+
+        if channel == self.texcoord_data.len() {
+            self.texcoord_data.push(Vec::new());
+        }
+
+        self.texcoord_data[channel].clear();
+        self.texcoord_data[channel].extend(texcoord_data);
+        self
+    }
+
+    /// Sets the indices of the four bones that influence each vertex, making the mesh skinned.
+    ///
+    /// Each index refers to a bone of the mesh instance's `Pose`, and must be less than
+    /// `MAX_BONES`. Vertices influenced by fewer than four bones can use any bone for the unused
+    /// influences and give them a weight of zero. Bone weights must be set as well, see
+    /// `set_bone_weight_data()`.
+    pub fn set_bone_index_data(mut self, bone_index_data: &[[u32; 4]]) -> MeshBuilder {
+        self.bone_index_data.clear();
+        self.bone_index_data.extend(bone_index_data);
+        self
+    }
+
+    /// Sets the weights of the four bones that influence each vertex.
+    ///
+    /// The weights of each vertex should add up to `1.0`. Bone indices must be set as well, see
+    /// `set_bone_index_data()`.
+    pub fn set_bone_weight_data(mut self, bone_weight_data: &[[f32; 4]]) -> MeshBuilder {
+        self.bone_weight_data.clear();
+        self.bone_weight_data.extend(bone_weight_data);
         self
     }
 
     pub fn set_indices(mut self, indices: &[u32]) -> MeshBuilder {
         self.indices.clear();
         self.indices.extend(indices);
+        self.u16_indices = false;
         self
     }
 
-    pub fn build(self) -> Result<Mesh, BuildMeshError> {
+    /// Splits the mesh into submeshes, each covering a range of the mesh's indices.
+    ///
+    /// Each range must start and end on a triangle boundary. If no submeshes are set the mesh
+    /// has a single submesh covering all of its indices.
+    pub fn set_submeshes(mut self, submeshes: &[Range<usize>]) -> MeshBuilder {
+        self.submeshes.clear();
+        self.submeshes.extend(submeshes.iter().cloned());
+        self
+    }
+
+    /// Sets the indices of the mesh as 16 bit indices.
+    ///
+    /// The built mesh keeps the 16 bit indices unless generating normals adds vertices that
+    /// can't be indexed with 16 bits, in which case it falls back to 32 bit indices.
+    pub fn set_indices_u16(mut self, indices: &[u16]) -> MeshBuilder {
+        self.indices.clear();
+        self.indices.extend(indices.iter().map(|&index| index as u32));
+        self.u16_indices = true;
+        self
+    }
+
+    /// Generates normals when the mesh is built if no normal data was set.
+    ///
+    /// Each vertex's normal is the average of the normals of the faces around it, weighted by the
+    /// area of each face. Faces count as being around a vertex if they touch the vertex's
+    /// position, so meshes that duplicate vertices along UV seams or that aren't indexed at all
+    /// still get smooth normals.
+    ///
+    /// If `split_angle` is given, faces only contribute to each other's normals if their normals
+    /// are no more than `split_angle` radians apart, and vertices shared by faces that end up with
+    /// different normals are duplicated. This keeps hard edges, such as a cube's, sharp.
+    pub fn generate_normals(mut self, split_angle: Option<f32>) -> MeshBuilder {
+        self.generate_normals = true;
+        self.split_angle = split_angle;
+        self
+    }
+
+    /// Sets how the vertex data is laid out in the built mesh.
+    ///
+    /// Defaults to `VertexLayout::Planar`.
+    pub fn set_layout(mut self, layout: VertexLayout) -> MeshBuilder {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets how the mesh's indices are assembled into primitives.
+    ///
+    /// Defaults to `Topology::Triangles`.
+    pub fn set_topology(mut self, topology: Topology) -> MeshBuilder {
+        self.topology = topology;
+        self
+    }
+
+    /// Generates tangents when the mesh is built if no tangent data was set.
+    ///
+    /// Tangents are calculated from the positions, normals, and first texcoord channel of the
+    /// mesh, so no tangents are generated if the mesh has no normals or texcoords. Normals
+    /// generated with `generate_normals()` are used, and are generated first.
+    pub fn generate_tangents(mut self) -> MeshBuilder {
+        self.generate_tangents = true;
+        self
+    }
+
+    pub fn build(mut self) -> Result<Mesh, BuildMeshError> {
         // The vertex count is defined by the position data, since position is the only required
         // vertex attribute.
         let vertex_count = self.position_data.len();
@@ -184,14 +736,51 @@ impl MeshBuilder {
             });
         }
 
-        if self.texcoord_data.len() != 0 && self.texcoord_data.len() != vertex_count {
+        if self.color_data.len() != 0 && self.color_data.len() != vertex_count {
+            return Err(BuildMeshError::IncorrectAttributeCount {
+                attribute: VertexAttributeType::Color,
+                expected: vertex_count,
+                actual: self.color_data.len(),
+            });
+        }
+
+        if self.tangent_data.len() != 0 && self.tangent_data.len() != vertex_count {
+            return Err(BuildMeshError::IncorrectAttributeCount {
+                attribute: VertexAttributeType::Tangent,
+                expected: vertex_count,
+                actual: self.tangent_data.len(),
+            });
+        }
+
+        // Skinned meshes need both the bone indices and weights, so one being empty while the
+        // other is set is also an incorrect count.
+        let skinned = self.bone_index_data.len() != 0 || self.bone_weight_data.len() != 0;
+        if skinned && self.bone_index_data.len() != vertex_count {
+            return Err(BuildMeshError::IncorrectAttributeCount {
+                attribute: VertexAttributeType::BoneIndices,
+                expected: vertex_count,
+                actual: self.bone_index_data.len(),
+            });
+        }
+
+        if skinned && self.bone_weight_data.len() != vertex_count {
             return Err(BuildMeshError::IncorrectAttributeCount {
-                attribute: VertexAttributeType::Texcoord,
+                attribute: VertexAttributeType::BoneWeights,
                 expected: vertex_count,
-                actual: self.texcoord_data.len(),
+                actual: self.bone_weight_data.len(),
             });
         }
 
+        for texcoord_data in &self.texcoord_data {
+            if texcoord_data.len() != 0 && texcoord_data.len() != vertex_count {
+                return Err(BuildMeshError::IncorrectAttributeCount {
+                    attribute: VertexAttributeType::Texcoord,
+                    expected: vertex_count,
+                    actual: texcoord_data.len(),
+                });
+            }
+        }
+
         // Make sure all indices at least point to a valid vertex.
         for index in self.indices.iter().cloned() {
             if index >= vertex_count as MeshIndex {
@@ -202,18 +791,49 @@ impl MeshBuilder {
             }
         }
 
+        // Make sure each submesh covers whole primitives.
+        let alignment = self.topology.index_alignment();
+        for submesh in &self.submeshes {
+            if submesh.start > submesh.end
+                || submesh.end > self.indices.len()
+                || submesh.start % alignment != 0
+                || submesh.end % alignment != 0
+            {
+                return Err(BuildMeshError::InvalidSubmesh {
+                    start: submesh.start,
+                    end: submesh.end,
+                    index_count: self.indices.len(),
+                });
+            }
+        }
+
         // TODO: Check for degenerate triangles? Actually, should that be a failure or a warning?
 
+        // Normals and tangents are calculated from the mesh's faces, which only triangles have.
+        let has_faces = self.topology == Topology::Triangles;
+        if self.generate_normals && self.normal_data.len() == 0 && has_faces {
+            self.compute_normals();
+        }
+
+        let has_uv0 = self.texcoord_data.first().map(|texcoord_data| texcoord_data.len() > 0).unwrap_or(false);
+        if self.generate_tangents && self.tangent_data.len() == 0 && self.normal_data.len() > 0 && has_uv0 && has_faces {
+            self.compute_tangents();
+        }
+
         let float_count =
             self.position_data.len() * 4
           + self.normal_data.len() * 3
-          + self.texcoord_data.len() * 2;
+          + self.color_data.len() * 4
+          + self.tangent_data.len() * 4
+          + self.texcoord_data.iter().map(|texcoord_data| texcoord_data.len() * 2).sum::<usize>()
+          + self.bone_index_data.len() * 4
+          + self.bone_weight_data.len() * 4;
 
         // Create the mesh.
         let mut vertex_data = Vec::<f32>::with_capacity(float_count);
 
         // Setup position data.
-        let position_attrib = VertexAttribute {
+        let mut position_attrib = VertexAttribute {
             elements: 4,
             offset: 0,
             stride: 0,
@@ -221,7 +841,7 @@ impl MeshBuilder {
         vertex_data.extend(Point::as_ref(&*self.position_data));
 
         // Setup normal data.
-        let normal_attrib = if self.normal_data.len() > 0 {
+        let mut normal_attrib = if self.normal_data.len() > 0 {
             let attrib = VertexAttribute {
                 elements: 3,
                 offset: vertex_data.len(),
@@ -234,25 +854,318 @@ impl MeshBuilder {
             None
         };
 
+        // Setup color data.
+        let mut color_attrib = if self.color_data.len() > 0 {
+            let attrib = VertexAttribute {
+                elements: 4,
+                offset: vertex_data.len(),
+                stride: 0,
+            };
+            for color in &self.color_data {
+                vertex_data.extend(&[color.r, color.g, color.b, color.a]);
+            }
+
+            Some(attrib)
+        } else {
+            None
+        };
+
+        // Setup tangent data.
+        let mut tangent_attrib = if self.tangent_data.len() > 0 {
+            let attrib = VertexAttribute {
+                elements: 4,
+                offset: vertex_data.len(),
+                stride: 0,
+            };
+            for tangent in &self.tangent_data {
+                vertex_data.extend(tangent);
+            }
+
+            Some(attrib)
+        } else {
+            None
+        };
+
         // Setup texcoord data.
         let mut texcoord_attribs = Vec::new();
-        if self.texcoord_data.len() > 0 {
+        for texcoord_data in self.texcoord_data.iter().take_while(|texcoord_data| texcoord_data.len() > 0) {
             texcoord_attribs.push(VertexAttribute {
                 elements: 2,
                 offset: vertex_data.len(),
                 stride: 0,
             });
-            vertex_data.extend(Vector2::as_ref(&*self.texcoord_data));
+            vertex_data.extend(Vector2::as_ref(&*texcoord_data));
+        }
+
+        // Setup skinning data.
+        let mut bone_index_attrib = if self.bone_index_data.len() > 0 {
+            let attrib = VertexAttribute {
+                elements: 4,
+                offset: vertex_data.len(),
+                stride: 0,
+            };
+            for bone_indices in &self.bone_index_data {
+                vertex_data.extend(bone_indices.iter().map(|&bone| bone as f32));
+            }
+
+            Some(attrib)
+        } else {
+            None
+        };
+
+        let mut bone_weight_attrib = if self.bone_weight_data.len() > 0 {
+            let attrib = VertexAttribute {
+                elements: 4,
+                offset: vertex_data.len(),
+                stride: 0,
+            };
+            for bone_weights in &self.bone_weight_data {
+                vertex_data.extend(bone_weights);
+            }
+
+            Some(attrib)
+        } else {
+            None
+        };
+
+        // Generating normals may have added vertices, so the vertex count is taken again.
+        if self.layout == VertexLayout::Interleaved {
+            let mut attribs = vec![&mut position_attrib];
+            attribs.extend(normal_attrib.as_mut());
+            attribs.extend(color_attrib.as_mut());
+            attribs.extend(tangent_attrib.as_mut());
+            attribs.extend(texcoord_attribs.iter_mut());
+            attribs.extend(bone_index_attrib.as_mut());
+            attribs.extend(bone_weight_attrib.as_mut());
+            vertex_data = interleave(&vertex_data, &mut attribs, self.position_data.len());
         }
 
+        let submeshes = if self.submeshes.is_empty() {
+            vec![0..self.indices.len()]
+        } else {
+            self.submeshes
+        };
+
+        let indices = if self.u16_indices && self.position_data.len() <= u16::MAX as usize + 1 {
+            Indices::U16(self.indices.iter().map(|&index| index as u16).collect())
+        } else {
+            Indices::U32(self.indices)
+        };
+
         // By our powers combined! We are! A mesh.
         Ok(Mesh {
             vertex_data: vertex_data,
-            indices: self.indices,
+            vertex_count: self.position_data.len(),
+            indices: indices,
 
             position: position_attrib,
             normal: normal_attrib,
+            color: color_attrib,
+            tangent: tangent_attrib,
             texcoord: texcoord_attribs,
+
+            bone_indices: bone_index_attrib,
+            bone_weights: bone_weight_attrib,
+
+            submeshes: submeshes,
+            topology: self.topology,
+            bounding_sphere: Sphere::from_points(&*self.position_data),
+            bounding_box: Aabb::from_points(&*self.position_data),
         })
     }
+
+    /// Fills in `tangent_data` from the faces of the mesh, see `generate_tangents()`.
+    ///
+    /// Assumes that the mesh has valid normal and texcoord data.
+    fn compute_tangents(&mut self) {
+        // Accumulate the directions of increasing U and V of each face around each vertex. Like
+        // the face normals the directions are weighted by the area of the face.
+        let mut tangents = vec![Vector3::default(); self.position_data.len()];
+        let mut bitangents = vec![Vector3::default(); self.position_data.len()];
+        for face in self.indices.chunks(3).filter(|face| face.len() == 3) {
+            let (a, b, c) = (face[0] as usize, face[1] as usize, face[2] as usize);
+            let edge_1 = self.position_data[b] - self.position_data[a];
+            let edge_2 = self.position_data[c] - self.position_data[a];
+
+            let uvs = &self.texcoord_data[0];
+            let delta_uv_1 = uvs[b] - uvs[a];
+            let delta_uv_2 = uvs[c] - uvs[a];
+
+            // Faces with degenerate texture coordinates have no meaningful tangent space.
+            let determinant = delta_uv_1.x * delta_uv_2.y - delta_uv_2.x * delta_uv_1.y;
+            if determinant.abs() < EPSILON {
+                continue;
+            }
+
+            let area = Vector3::cross(edge_1, edge_2).magnitude();
+            let tangent = ((edge_1 * delta_uv_2.y - edge_2 * delta_uv_1.y) / determinant).normalized() * area;
+            let bitangent = ((edge_2 * delta_uv_1.x - edge_1 * delta_uv_2.x) / determinant).normalized() * area;
+            for &vertex in &[a, b, c] {
+                tangents[vertex] += tangent;
+                bitangents[vertex] += bitangent;
+            }
+        }
+
+        // Make each tangent perpendicular to the vertex's normal, and record whether the
+        // bitangent points along or against `cross(normal, tangent)`.
+        self.tangent_data = self.normal_data
+            .iter()
+            .zip(tangents.into_iter().zip(bitangents))
+            .map(|(&normal, (tangent, bitangent))| {
+                let tangent = (tangent - normal * normal.dot(tangent)).normalized();
+                let handedness = if Vector3::cross(normal, tangent).dot(bitangent) < 0.0 { -1.0 } else { 1.0 };
+                [tangent.x, tangent.y, tangent.z, handedness]
+            })
+            .collect();
+    }
+
+    /// Fills in `normal_data` from the faces of the mesh, see `generate_normals()`.
+    ///
+    /// Assumes that the indices and attribute data have already been validated.
+    fn compute_normals(&mut self) {
+        // The face normals are left unnormalized, since the length of the cross product is twice
+        // the area of the face.
+        let face_normals = self.indices
+            .chunks(3)
+            .filter(|face| face.len() == 3)
+            .map(|face| {
+                let a = self.position_data[face[0] as usize];
+                let b = self.position_data[face[1] as usize];
+                let c = self.position_data[face[2] as usize];
+                Vector3::cross(b - a, c - a)
+            })
+            .collect::<Vec<_>>();
+        let corner_count = face_normals.len() * 3;
+
+        // Find the faces around each position.
+        let mut faces_at_position = HashMap::new();
+        for (corner, &index) in self.indices[..corner_count].iter().enumerate() {
+            faces_at_position
+                .entry(position_key(self.position_data[index as usize]))
+                .or_insert_with(Vec::new)
+                .push(corner / 3);
+        }
+
+        // Calculate the normal for each corner of each face from the faces around it.
+        let split_cos = self.split_angle.map(f32::cos);
+        let corner_normals = self.indices[..corner_count]
+            .iter()
+            .enumerate()
+            .map(|(corner, &index)| {
+                let face_normal = face_normals[corner / 3].normalized();
+
+                let mut normal = Vector3::default();
+                for &face in &faces_at_position[&position_key(self.position_data[index as usize])] {
+                    let smooth = match split_cos {
+                        Some(split_cos) => face_normal.dot(face_normals[face].normalized()) >= split_cos,
+                        None => true,
+                    };
+
+                    if smooth {
+                        normal += face_normals[face];
+                    }
+                }
+
+                normal.normalized()
+            })
+            .collect::<Vec<_>>();
+
+        // Give each vertex the normal of the corners using it, duplicating the vertex for corners
+        // that ended up with a different normal.
+        let mut normal_data = vec![None; self.position_data.len()];
+        let mut duplicates = HashMap::new();
+        for (corner, normal) in corner_normals.into_iter().enumerate() {
+            let vertex = self.indices[corner] as usize;
+            match normal_data[vertex] {
+                None => normal_data[vertex] = Some(normal),
+                Some(vertex_normal) if vertex_normal == normal => {},
+                Some(_) => {
+                    let key = (vertex, normal.x.to_bits(), normal.y.to_bits(), normal.z.to_bits());
+                    let duplicate = match duplicates.get(&key) {
+                        Some(&duplicate) => duplicate,
+                        None => {
+                            let duplicate = self.duplicate_vertex(vertex);
+                            normal_data.push(Some(normal));
+                            duplicates.insert(key, duplicate);
+                            duplicate
+                        },
+                    };
+
+                    self.indices[corner] = duplicate as MeshIndex;
+                },
+            }
+        }
+
+        // Vertices that aren't part of any face have no meaningful normal.
+        self.normal_data = normal_data
+            .into_iter()
+            .map(|normal| normal.unwrap_or_default())
+            .collect();
+    }
+
+    /// Appends a copy of a vertex's position, color, texcoords, and bone influences, returning
+    /// the new vertex's index.
+    fn duplicate_vertex(&mut self, vertex: usize) -> usize {
+        let position = self.position_data[vertex];
+        self.position_data.push(position);
+
+        if self.color_data.len() > 0 {
+            let color = self.color_data[vertex];
+            self.color_data.push(color);
+        }
+
+        if self.tangent_data.len() > 0 {
+            let tangent = self.tangent_data[vertex];
+            self.tangent_data.push(tangent);
+        }
+
+        for texcoord_data in &mut self.texcoord_data {
+            if texcoord_data.len() > 0 {
+                let texcoord = texcoord_data[vertex];
+                texcoord_data.push(texcoord);
+            }
+        }
+
+        if self.bone_index_data.len() > 0 {
+            let bone_indices = self.bone_index_data[vertex];
+            self.bone_index_data.push(bone_indices);
+        }
+
+        if self.bone_weight_data.len() > 0 {
+            let bone_weights = self.bone_weight_data[vertex];
+            self.bone_weight_data.push(bone_weights);
+        }
+
+        self.position_data.len() - 1
+    }
+}
+
+/// Rearranges planar vertex data so that the attributes of each vertex are next to each other,
+/// updating `attribs` to describe the interleaved data.
+fn interleave(planar_data: &[f32], attribs: &mut [&mut VertexAttribute], vertex_count: usize) -> Vec<f32> {
+    let stride = attribs.iter().map(|attrib| attrib.elements).sum::<usize>();
+
+    let mut vertex_data = Vec::with_capacity(stride * vertex_count);
+    for vertex in 0..vertex_count {
+        for attrib in attribs.iter() {
+            let start = attrib.offset + vertex * attrib.elements;
+            vertex_data.extend(&planar_data[start..start + attrib.elements]);
+        }
+    }
+
+    let mut offset = 0;
+    for attrib in attribs.iter_mut() {
+        attrib.offset = offset;
+        attrib.stride = stride;
+        offset += attrib.elements;
+    }
+
+    vertex_data
+}
+
+/// Gets a hashable key for a position, so that vertices at exactly the same position can be
+/// found.
+fn position_key(point: Point) -> (u32, u32, u32) {
+    // Adding zero turns negative zero into positive zero, so that they're treated as the same.
+    ((point.x + 0.0).to_bits(), (point.y + 0.0).to_bits(), (point.z + 0.0).to_bits())
 }
@@ -1 +1,4 @@
 pub mod mesh;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,408 @@
+use geometry::mesh::*;
+use math::*;
+
+fn assert_vectors_eq(actual: Vector3, expected: Vector3) {
+    assert!(
+        (actual - expected).magnitude() < 1e-5,
+        "Expected {:?} but got {:?}",
+        expected,
+        actual);
+}
+
+/// Gets the normal of each vertex, as `(position, normal)` pairs.
+fn vertex_normals(mesh: &Mesh) -> Vec<(Point, Vector3)> {
+    let normal = mesh.normal().expect("Mesh has no normals");
+
+    (0..mesh.vertex_count())
+        .map(|vertex| {
+            let position = attrib_data(mesh, mesh.position(), vertex);
+            let normal = attrib_data(mesh, normal, vertex);
+            (
+                Point::new(position[0], position[1], position[2]),
+                Vector3::new(normal[0], normal[1], normal[2]),
+            )
+        })
+        .collect()
+}
+
+/// Gets the data for one vertex of an attribute.
+fn attrib_data(mesh: &Mesh, attrib: VertexAttribute, vertex: usize) -> &[f32] {
+    let stride = if attrib.stride == 0 { attrib.elements } else { attrib.stride };
+    let start = attrib.offset + vertex * stride;
+    &mesh.vertex_data()[start..start + attrib.elements]
+}
+
+fn cube() -> MeshBuilder {
+    let positions = (0..8)
+        .map(|corner| Point::new(
+            if corner & 1 == 0 { -1.0 } else { 1.0 },
+            if corner & 2 == 0 { -1.0 } else { 1.0 },
+            if corner & 4 == 0 { -1.0 } else { 1.0 }))
+        .collect::<Vec<_>>();
+
+    // Two triangles per side, wound counter-clockwise when seen from outside the cube.
+    static INDICES: [u32; 36] = [
+        0, 2, 3, 0, 3, 1, // -z
+        4, 5, 7, 4, 7, 6, // +z
+        0, 4, 6, 0, 6, 2, // -x
+        1, 3, 7, 1, 7, 5, // +x
+        0, 1, 5, 0, 5, 4, // -y
+        2, 6, 7, 2, 7, 3, // +y
+    ];
+
+    MeshBuilder::new()
+        .set_position_data(&positions)
+        .set_indices(&INDICES)
+}
+
+#[test]
+fn generate_split_normals_for_cube() {
+    let mesh = cube().generate_normals(Some(PI / 4.0)).build().unwrap();
+
+    // Each corner is split into one vertex per side it touches.
+    let normals = vertex_normals(&mesh);
+    assert_eq!(normals.len(), 24);
+    assert_eq!(mesh.indices().len(), 36);
+
+    // Every face's vertices have the face's normal.
+    let indices = (0..mesh.indices().len()).map(|position| mesh.indices().get(position)).collect::<Vec<_>>();
+    for face in indices.chunks(3) {
+        let (a, _) = normals[face[0] as usize];
+        let (b, _) = normals[face[1] as usize];
+        let (c, _) = normals[face[2] as usize];
+        let face_normal = Vector3::cross(b - a, c - a).normalized();
+
+        for &index in face {
+            assert_vectors_eq(normals[index as usize].1, face_normal);
+        }
+    }
+}
+
+#[test]
+fn generate_smooth_normals_for_unindexed_mesh() {
+    // Two triangles that form a bent quad, without sharing any vertices.
+    let positions = [
+        Point::new(0.0, 0.0, 0.0), Point::new(1.0, 0.0, 0.0), Point::new(0.0, 1.0, 0.0),
+        Point::new(1.0, 0.0, 0.0), Point::new(1.0, 1.0, 1.0), Point::new(0.0, 1.0, 0.0),
+    ];
+    let mesh = MeshBuilder::new()
+        .set_position_data(&positions)
+        .set_indices(&[0, 1, 2, 3, 4, 5])
+        .generate_normals(None)
+        .build()
+        .unwrap();
+
+    // The vertices on the shared edge get the same normal even though they aren't shared.
+    let normals = vertex_normals(&mesh);
+    assert_eq!(normals.len(), 6);
+    assert_vectors_eq(normals[1].1, normals[3].1);
+    assert_vectors_eq(normals[2].1, normals[5].1);
+    assert!((normals[0].1 - normals[1].1).magnitude() > 0.1);
+}
+
+#[test]
+fn generate_smooth_normals_for_icosphere() {
+    // The corners of an icosahedron are the cyclic permutations of (0, ±1, ±φ).
+    let phi = (1.0 + 5.0f32.sqrt()) / 2.0;
+    let mut positions = Vec::new();
+    for &a in &[-1.0, 1.0] {
+        for &b in &[-phi, phi] {
+            positions.push(Point::new(0.0, a, b));
+            positions.push(Point::new(a, b, 0.0));
+            positions.push(Point::new(b, 0.0, a));
+        }
+    }
+
+    // The faces are the triples of corners that are all an edge length of 2 apart, wound so
+    // that they face away from the center.
+    let is_edge = |a: usize, b: usize| ((positions[a] - positions[b]).magnitude() - 2.0).abs() < 1e-4;
+    let mut indices = Vec::new();
+    for a in 0..12 {
+        for b in a + 1..12 {
+            for c in b + 1..12 {
+                if is_edge(a, b) && is_edge(b, c) && is_edge(a, c) {
+                    let normal = Vector3::cross(positions[b] - positions[a], positions[c] - positions[a]);
+                    if normal.dot(Vector3::from(positions[a])) > 0.0 {
+                        indices.extend(&[a as u32, b as u32, c as u32]);
+                    } else {
+                        indices.extend(&[a as u32, c as u32, b as u32]);
+                    }
+                }
+            }
+        }
+    }
+    assert_eq!(indices.len(), 60);
+
+    let mesh = MeshBuilder::new()
+        .set_position_data(&positions)
+        .set_indices(&indices)
+        .generate_normals(Some(PI / 2.0))
+        .build()
+        .unwrap();
+
+    // The faces around each corner are about 71 degrees apart at most, so no vertices are split
+    // and every normal points straight out from the center.
+    let normals = vertex_normals(&mesh);
+    assert_eq!(normals.len(), 12);
+    for (position, normal) in normals {
+        assert_vectors_eq(normal, Vector3::from(position).normalized());
+    }
+}
+
+#[test]
+fn generate_normals_keeps_existing_normals() {
+    let normals = [Vector3::new(0.0, 0.0, 1.0); 8];
+    let mesh = cube()
+        .set_normal_data(&normals)
+        .generate_normals(Some(PI / 4.0))
+        .build()
+        .unwrap();
+
+    let vertex_normals = vertex_normals(&mesh);
+    assert_eq!(vertex_normals.len(), 8);
+    for (_, normal) in vertex_normals {
+        assert_vectors_eq(normal, Vector3::new(0.0, 0.0, 1.0));
+    }
+}
+
+/// Gets the tangent of each vertex.
+fn vertex_tangents(mesh: &Mesh) -> Vec<[f32; 4]> {
+    let tangent = mesh.tangent().expect("Mesh has no tangents");
+
+    (0..mesh.vertex_count())
+        .map(|vertex| {
+            let data = attrib_data(mesh, tangent, vertex);
+            [data[0], data[1], data[2], data[3]]
+        })
+        .collect()
+}
+
+fn quad(texcoords: &[Vector2]) -> MeshBuilder {
+    let positions = [
+        Point::new(-1.0, -1.0, 0.0), Point::new(1.0, -1.0, 0.0),
+        Point::new( 1.0,  1.0, 0.0), Point::new(-1.0, 1.0, 0.0),
+    ];
+    MeshBuilder::new()
+        .set_position_data(&positions)
+        .set_normal_data(&[Vector3::new(0.0, 0.0, 1.0); 4])
+        .set_texcoord_data(texcoords)
+        .set_indices(&[0, 1, 2, 0, 2, 3])
+}
+
+#[test]
+fn generate_tangents_for_quad() {
+    let texcoords = [
+        Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0),
+        Vector2::new(1.0, 1.0), Vector2::new(0.0, 1.0),
+    ];
+    let mesh = quad(&texcoords).generate_tangents().build().unwrap();
+
+    let tangents = vertex_tangents(&mesh);
+    assert_eq!(tangents.len(), 4);
+    for tangent in tangents {
+        assert_vectors_eq(Vector3::new(tangent[0], tangent[1], tangent[2]), Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(tangent[3], 1.0);
+    }
+}
+
+#[test]
+fn generate_tangents_for_mirrored_quad() {
+    // U increases towards -x, so the tangent space is mirrored.
+    let texcoords = [
+        Vector2::new(1.0, 0.0), Vector2::new(0.0, 0.0),
+        Vector2::new(0.0, 1.0), Vector2::new(1.0, 1.0),
+    ];
+    let mesh = quad(&texcoords).generate_tangents().build().unwrap();
+
+    for tangent in vertex_tangents(&mesh) {
+        assert_vectors_eq(Vector3::new(tangent[0], tangent[1], tangent[2]), Vector3::new(-1.0, 0.0, 0.0));
+        assert_eq!(tangent[3], -1.0);
+    }
+}
+
+#[test]
+fn generate_tangents_requires_texcoords() {
+    let mesh = cube().generate_normals(None).generate_tangents().build().unwrap();
+    assert!(mesh.tangent().is_none());
+}
+
+#[test]
+fn interleaved_layout() {
+    let texcoords = [
+        Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0),
+        Vector2::new(1.0, 1.0), Vector2::new(0.0, 1.0),
+    ];
+    let planar = quad(&texcoords).build().unwrap();
+    let interleaved = quad(&texcoords).set_layout(VertexLayout::Interleaved).build().unwrap();
+
+    // Position, normal, and texcoord for each vertex.
+    let position = interleaved.position();
+    assert_eq!((position.offset, position.stride), (0, 9));
+    let normal = interleaved.normal().unwrap();
+    assert_eq!((normal.offset, normal.stride), (4, 9));
+    let texcoord = interleaved.texcoord()[0];
+    assert_eq!((texcoord.offset, texcoord.stride), (7, 9));
+    assert_eq!(interleaved.vertex_data().len(), planar.vertex_data().len());
+
+    // Both layouts hold the same data for each vertex.
+    assert_eq!(interleaved.vertex_count(), 4);
+    for vertex in 0..4 {
+        assert_eq!(attrib_data(&interleaved, position, vertex), attrib_data(&planar, planar.position(), vertex));
+        assert_eq!(attrib_data(&interleaved, normal, vertex), attrib_data(&planar, planar.normal().unwrap(), vertex));
+        assert_eq!(attrib_data(&interleaved, texcoord, vertex), attrib_data(&planar, planar.texcoord()[0], vertex));
+    }
+}
+
+#[test]
+fn validate_mesh() {
+    assert!(cube().build().unwrap().validate().is_ok());
+    assert!(cube().set_layout(VertexLayout::Interleaved).build().unwrap().validate().is_ok());
+
+    let incomplete = cube().set_indices(&[0, 1, 2, 3]).build().unwrap();
+    match incomplete.validate() {
+        Err(MeshError::IncompleteTriangle { index_count: 4 }) => {},
+        result => panic!("Expected an incomplete triangle error but got {:?}", result),
+    }
+}
+
+#[test]
+fn validate_topology() {
+    assert_eq!(cube().build().unwrap().topology(), Topology::Triangles);
+
+    // The cube's 36 indices make whole primitives for every topology.
+    for &topology in &[Topology::Lines, Topology::LineStrip, Topology::Points] {
+        let mesh = cube().set_topology(topology).build().unwrap();
+        assert_eq!(mesh.topology(), topology);
+        assert!(mesh.validate().is_ok());
+    }
+
+    // 3 indices are a triangle or a line strip, but not whole line segments.
+    let lines = cube().set_indices(&[0, 1, 2]).set_topology(Topology::Lines).build().unwrap();
+    match lines.validate() {
+        Err(MeshError::IncompletePrimitive { topology: Topology::Lines, index_count: 3 }) => {},
+        result => panic!("Expected an incomplete primitive error but got {:?}", result),
+    }
+    let strip = cube().set_indices(&[0, 1, 2]).set_topology(Topology::LineStrip).build().unwrap();
+    assert!(strip.validate().is_ok());
+
+    // A single index doesn't make a line strip.
+    let strip = cube().set_indices(&[0]).set_topology(Topology::LineStrip).build().unwrap();
+    match strip.validate() {
+        Err(MeshError::IncompletePrimitive { topology: Topology::LineStrip, index_count: 1 }) => {},
+        result => panic!("Expected an incomplete primitive error but got {:?}", result),
+    }
+    let points = cube().set_indices(&[0]).set_topology(Topology::Points).build().unwrap();
+    assert!(points.validate().is_ok());
+
+    // Submeshes only need to cover whole line segments, and normals aren't generated.
+    let mesh = cube()
+        .set_topology(Topology::Lines)
+        .set_submeshes(&[0..4, 4..36])
+        .generate_normals(None)
+        .build()
+        .unwrap();
+    assert!(mesh.normal().is_none());
+    match cube().set_topology(Topology::Lines).set_submeshes(&[0..3]).build() {
+        Err(BuildMeshError::InvalidSubmesh { start: 0, end: 3, index_count: 36 }) => {},
+        result => panic!("Expected an invalid submesh error but got {:?}", result),
+    }
+}
+
+#[test]
+fn skinned_mesh() {
+    let bone_indices = (0..8).map(|corner| [corner / 4, 0, 0, 0]).collect::<Vec<_>>();
+    let bone_weights = vec![[1.0, 0.0, 0.0, 0.0]; 8];
+
+    assert!(!cube().build().unwrap().is_skinned());
+    let mesh = cube()
+        .set_bone_index_data(&bone_indices)
+        .set_bone_weight_data(&bone_weights)
+        .build()
+        .unwrap();
+    assert!(mesh.is_skinned());
+    assert!(mesh.validate().is_ok());
+    assert_eq!(attrib_data(&mesh, mesh.bone_indices().unwrap(), 5), &[1.0, 0.0, 0.0, 0.0]);
+
+    // Split normals duplicate vertices along with their bone influences, and interleaving keeps
+    // them with the rest of the vertex.
+    let mesh = cube()
+        .set_bone_index_data(&bone_indices)
+        .set_bone_weight_data(&bone_weights)
+        .generate_normals(Some(PI / 4.0))
+        .set_layout(VertexLayout::Interleaved)
+        .build()
+        .unwrap();
+    assert_eq!(mesh.vertex_count(), 24);
+    assert!(mesh.validate().is_ok());
+    for vertex in 0..mesh.vertex_count() {
+        let position = attrib_data(&mesh, mesh.position(), vertex);
+        let bone = if position[2] > 0.0 { 1.0 } else { 0.0 };
+        assert_eq!(attrib_data(&mesh, mesh.bone_indices().unwrap(), vertex)[0], bone);
+        assert_eq!(attrib_data(&mesh, mesh.bone_weights().unwrap(), vertex), &[1.0, 0.0, 0.0, 0.0]);
+    }
+
+    // Bone indices and weights have to be set together.
+    match cube().set_bone_index_data(&bone_indices).build() {
+        Err(BuildMeshError::IncorrectAttributeCount { attribute: VertexAttributeType::BoneWeights, expected: 8, actual: 0 }) => {},
+        result => panic!("Expected an incorrect attribute count error but got {:?}", result),
+    }
+}
+
+#[test]
+fn bounding_sphere() {
+    let mesh = cube().build().unwrap();
+    let bounds = mesh.bounding_sphere();
+    assert_eq!(bounds.center, Point::origin());
+    assert!((bounds.radius - 3.0f32.sqrt()).abs() < 1e-5, "Wrong radius: {}", bounds.radius);
+
+    // The sphere is centered on the mesh, not the origin of its model space.
+    let offset = MeshBuilder::new()
+        .set_position_data(&[Point::new(10.0, 0.0, 0.0), Point::new(12.0, 0.0, 0.0), Point::new(12.0, 2.0, 0.0)])
+        .set_indices(&[0, 1, 2])
+        .build()
+        .unwrap();
+    assert_eq!(offset.bounding_sphere().center, Point::new(11.0, 1.0, 0.0));
+}
+
+#[test]
+fn bounding_box() {
+    let mesh = cube().build().unwrap();
+    assert_eq!(mesh.bounding_box(), Aabb::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0)));
+
+    let offset = MeshBuilder::new()
+        .set_position_data(&[Point::new(10.0, 0.0, 0.0), Point::new(12.0, 0.0, 0.0), Point::new(12.0, 2.0, 0.0)])
+        .set_indices(&[0, 1, 2])
+        .build()
+        .unwrap();
+    assert_eq!(offset.bounding_box(), Aabb::new(Point::new(10.0, 0.0, 0.0), Point::new(12.0, 2.0, 0.0)));
+}
+
+#[test]
+fn index_width() {
+    let mesh = quad(&[]).build().unwrap();
+    assert_eq!(*mesh.indices(), Indices::U32(vec![0, 1, 2, 0, 2, 3]));
+
+    let mesh = quad(&[]).set_indices_u16(&[0, 1, 2, 0, 2, 3]).build().unwrap();
+    assert_eq!(*mesh.indices(), Indices::U16(vec![0, 1, 2, 0, 2, 3]));
+    assert_eq!(mesh.indices().byte_len(), 12);
+    assert_eq!(mesh.indices().get(5), 3);
+}
+
+#[test]
+fn submeshes() {
+    let mesh = quad(&[]).build().unwrap();
+    assert_eq!(mesh.submeshes(), &[0..6]);
+
+    let mesh = quad(&[]).set_submeshes(&[0..3, 3..6]).build().unwrap();
+    assert_eq!(mesh.submeshes(), &[0..3, 3..6]);
+    assert!(mesh.validate().is_ok());
+
+    match quad(&[]).set_submeshes(&[0..4]).build() {
+        Err(BuildMeshError::InvalidSubmesh { start: 0, end: 4, index_count: 6 }) => {},
+        result => panic!("Expected an invalid submesh error but got {:?}", result),
+    }
+    match quad(&[]).set_submeshes(&[3..9]).build() {
+        Err(BuildMeshError::InvalidSubmesh { start: 3, end: 9, index_count: 6 }) => {},
+        result => panic!("Expected an invalid submesh error but got {:?}", result),
+    }
+}
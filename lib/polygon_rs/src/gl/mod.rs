@@ -1,44 +1,309 @@
 pub extern crate gl_util;
 
-use {BuildMaterialError, Counter, GpuMesh, Renderer};
+#[cfg(test)]
+mod test;
+
+use {AmbientMode, BuildMaterialError, Counter, DebugMode, Fog, FogMode, FrameStats, GpuMesh, RegisteredIter, RegisteredIterMut, RenderStats, Renderer};
 use anchor::*;
 use bootstrap::window::Window;
 use camera::*;
-use geometry::mesh::{Mesh, VertexAttribute};
+use geometry::mesh::{Indices, Mesh, MeshError, Topology, VertexAttribute};
+use image::Image;
 use light::*;
 use material::*;
 use mesh_instance::*;
 use math::*;
+use polygon_material::material_source::{PropertyDefault, PropertySource, PropertyType};
 use self::gl_util::*;
-use self::gl_util::context::{Context, Error as ContextError};
+use self::gl_util::context::{Context, ContextBuilder, Error as ContextError};
+use self::gl_util::framebuffer::{ClearBufferMask, Framebuffer, Renderbuffer};
+use self::gl_util::query::TimerQuery;
 use self::gl_util::shader::*;
-use self::gl_util::shader::Shader as GlShader;
 use self::gl_util::texture::{
+    DepthFormat,
+    Error as TextureError,
+    FaceImage,
     Texture2d as GlTexture2d,
+    TextureCube as GlTextureCube,
+    TextureFilterFunction,
     TextureFormat,
     TextureInternalFormat,
+    TextureWrapFunction,
 };
 use shader::Shader;
-use std::collections::HashMap;
-use std::str;
+use skeleton::{MAX_BONES, Pose};
+use std::collections::{HashMap, HashSet};
+use std::{mem, str, u16};
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use sprite::*;
 use stopwatch::Stopwatch;
+use text::{BitmapFont, GlyphQuad};
 use texture::*;
 
 static DEFAULT_SHADER_BYTES: &'static [u8] = include_bytes!("../../resources/materials/diffuse_lit.material");
 
+// Fixed attribute locations shared by every material program, so that each mesh's vertex array
+// only has to be configured once when the mesh is registered.
+const POSITION_LOCATION: AttributeLocation = AttributeLocation(0);
+const NORMAL_LOCATION: AttributeLocation = AttributeLocation(1);
+const UV0_LOCATION: AttributeLocation = AttributeLocation(2);
+
+// The second UV set comes after the instance attributes so that adding it didn't move them.
+const UV1_LOCATION: AttributeLocation = AttributeLocation(10);
+const COLOR_LOCATION: AttributeLocation = AttributeLocation(11);
+const TANGENT_LOCATION: AttributeLocation = AttributeLocation(12);
+const BONE_INDICES_LOCATION: AttributeLocation = AttributeLocation(13);
+const BONE_WEIGHTS_LOCATION: AttributeLocation = AttributeLocation(14);
+
+/// The uniform buffer binding point that the skinned material programs read the bone transforms
+/// from.
+const BONES_BINDING: u32 = 0;
+
+/// The attribute locations of the texcoord channels that materials can read, as `@vertex.uv0`
+/// and `@vertex.uv1`. Additional channels are uploaded but never read, see
+/// `warn_unread_texcoords()`.
+static UV_LOCATIONS: [AttributeLocation; 2] = [UV0_LOCATION, UV1_LOCATION];
+
+// Attribute locations of the per-instance transforms used by instanced draws. Matrix attributes
+// take up one location per column, so the model transform uses locations 3 through 6 and the
+// normal transform uses 7 through 9.
+const MODEL_TRANSFORM_LOCATION: AttributeLocation = AttributeLocation(3);
+const NORMAL_TRANSFORM_LOCATION: AttributeLocation = AttributeLocation(7);
+
+/// The name, location, offset, and size of each column of the per-instance transforms in the
+/// instance buffer. The transforms are stored in column-major order.
+static INSTANCE_ATTRIBS: [(&'static str, AttributeLocation, usize, usize); 7] = [
+    ("model_transform_0", MODEL_TRANSFORM_LOCATION, 0, 4),
+    ("model_transform_1", AttributeLocation(MODEL_TRANSFORM_LOCATION.0 + 1), 4, 4),
+    ("model_transform_2", AttributeLocation(MODEL_TRANSFORM_LOCATION.0 + 2), 8, 4),
+    ("model_transform_3", AttributeLocation(MODEL_TRANSFORM_LOCATION.0 + 3), 12, 4),
+    ("normal_transform_0", NORMAL_TRANSFORM_LOCATION, 16, 3),
+    ("normal_transform_1", AttributeLocation(NORMAL_TRANSFORM_LOCATION.0 + 1), 19, 3),
+    ("normal_transform_2", AttributeLocation(NORMAL_TRANSFORM_LOCATION.0 + 2), 22, 3),
+];
+
+/// The number of floats of instance data for each mesh instance in an instanced draw.
+const INSTANCE_DATA_LEN: usize = 25;
+
+/// The name, location, offset, and size of each attribute of the sprite and text vertices.
+static OVERLAY_ATTRIBS: [(&'static str, AttributeLocation, usize, usize); 3] = [
+    ("position", POSITION_LOCATION, 0, 2),
+    ("uv", UV0_LOCATION, 2, 2),
+    ("color", COLOR_LOCATION, 4, 4),
+];
+
+/// The number of floats for each vertex of the sprites and text drawn over the scene.
+const OVERLAY_VERTEX_LEN: usize = 8;
+
+/// Groups of mesh instances sharing a mesh and material are drawn with a single instanced draw
+/// call if they have more than this many mesh instances.
+const INSTANCING_THRESHOLD: usize = 4;
+
+/// The built-in uniforms that depend on the model transform, which become per-instance values in
+/// instanced draws.
+static MODEL_UNIFORM_NAMES: [&'static str; 5] = [
+    "model_transform",
+    "normal_transform",
+    "view_normal_transform",
+    "model_view_transform",
+    "model_view_projection",
+];
+
+/// The number of lights a material that reads the light arrays receives per draw call.
+///
+/// Must match `MAX_LIGHTS` in `BUILT_IN_UNIFORMS`.
+const MAX_LIGHTS: usize = 8;
+
+/// Half the width, height, and depth of the region around the camera covered by the shadow map.
+const SHADOW_DISTANCE: f32 = 10.0;
+
+static SHADOW_VERT_SOURCE: &'static str = r#"
+    #version 150
+
+    uniform mat4 light_space_model_transform;
+
+    in vec4 vertex_position;
+
+    void main(void) {
+        gl_Position = light_space_model_transform * vertex_position;
+    }
+"#;
+
+static SHADOW_FRAG_SOURCE: &'static str = r#"
+    #version 150
+
+    void main(void) {}
+"#;
+
+static SKYBOX_VERT_SOURCE: &'static str = r#"
+    #version 150
+
+    uniform mat4 skybox_transform;
+
+    in vec3 vertex_position;
+
+    out vec3 direction;
+
+    void main(void) {
+        direction = vertex_position;
+
+        // Use w as the depth so that the sky ends up on the far plane after the perspective
+        // divide, behind everything else in the scene.
+        gl_Position = (skybox_transform * vec4(vertex_position, 1.0)).xyww;
+    }
+"#;
+
+static SKYBOX_FRAG_SOURCE: &'static str = r#"
+    #version 150
+
+    uniform samplerCube skybox;
+
+    in vec3 direction;
+
+    out vec4 fragment_color;
+
+    void main(void) {
+        fragment_color = texture(skybox, direction);
+    }
+"#;
+
+static NORMALS_VERT_SOURCE: &'static str = r#"
+    #version 150
+
+    uniform mat4 model_view_projection;
+    uniform mat3 normal_transform;
+
+    in vec4 vertex_position;
+    in vec3 vertex_normal;
+
+    out vec3 world_normal;
+
+    void main(void) {
+        world_normal = normalize(normal_transform * vertex_normal);
+        gl_Position = model_view_projection * vertex_position;
+    }
+"#;
+
+static NORMALS_FRAG_SOURCE: &'static str = r#"
+    #version 150
+
+    in vec3 world_normal;
+
+    out vec4 fragment_color;
+
+    void main(void) {
+        fragment_color = vec4(normalize(world_normal) * 0.5 + 0.5, 1.0);
+    }
+"#;
+
+static OVERLAY_VERT_SOURCE: &'static str = r#"
+    #version 150
+
+    uniform vec2 screen_size;
+
+    in vec2 vertex_position;
+    in vec2 vertex_uv;
+    in vec4 vertex_color;
+
+    out vec2 uv;
+    out vec4 color;
+
+    void main(void) {
+        uv = vertex_uv;
+        color = vertex_color;
+
+        // Sprites and text are positioned in pixels from the top-left corner of the screen, so
+        // flip y when converting to normalized device coordinates.
+        vec2 position = vertex_position / screen_size * 2.0 - 1.0;
+        gl_Position = vec4(position.x, -position.y, 0.0, 1.0);
+    }
+"#;
+
+static OVERLAY_FRAG_SOURCE: &'static str = r#"
+    #version 150
+
+    uniform sampler2D overlay_texture;
+
+    in vec2 uv;
+    in vec4 color;
+
+    out vec4 fragment_color;
+
+    void main(void) {
+        fragment_color = color * texture(overlay_texture, uv);
+    }
+"#;
+
+/// The corners of the cube the skybox is drawn on. The camera is always inside the cube, so the
+/// winding order of the faces doesn't matter and the skybox is drawn without culling.
+static SKYBOX_POSITIONS: [f32; 24] = [
+    -1.0, -1.0, -1.0,
+     1.0, -1.0, -1.0,
+     1.0,  1.0, -1.0,
+    -1.0,  1.0, -1.0,
+    -1.0, -1.0,  1.0,
+     1.0, -1.0,  1.0,
+     1.0,  1.0,  1.0,
+    -1.0,  1.0,  1.0,
+];
+
+/// A triangle covering the whole screen that post effects are drawn on, as interleaved positions
+/// in clip space and texture coordinates. The parts outside the screen are clipped, leaving
+/// texture coordinates from `(0, 0)` in the bottom-left corner to `(1, 1)` in the top-right.
+static POST_VERTICES: [f32; 12] = [
+    -1.0, -1.0, 0.0, 0.0,
+     3.0, -1.0, 2.0, 0.0,
+    -1.0,  3.0, 0.0, 2.0,
+];
+
+static SKYBOX_INDICES: [u32; 36] = [
+    0, 1, 2, 0, 2, 3, // -Z
+    4, 6, 5, 4, 7, 6, // +Z
+    0, 4, 5, 0, 5, 1, // -Y
+    3, 2, 6, 3, 6, 7, // +Y
+    0, 3, 7, 0, 7, 4, // -X
+    1, 5, 6, 1, 6, 2, // +X
+];
+
 #[derive(Debug)]
 pub struct GlRender {
     context: Context,
 
+    /// The offscreen framebuffer drawn into in place of the window, for renderers created with
+    /// `headless()`.
+    headless_target: Option<HeadlessTarget>,
+
     materials: HashMap<MaterialId, Material>,
     meshes: HashMap<GpuMesh, MeshData>,
     textures: HashMap<GpuTexture, GlTexture2d>,
+    cube_textures: HashMap<GpuTexture, GlTextureCube>,
     mesh_instances: HashMap<MeshInstanceId, MeshInstance>,
     anchors: HashMap<AnchorId, Anchor>,
+
+    /// The world transforms of the anchors, resolved through their parents at the start of each
+    /// frame.
+    world_transforms: HashMap<AnchorId, WorldTransform>,
+
     cameras: HashMap<CameraId, Camera>,
     lights: HashMap<LightId, Light>,
     programs: HashMap<Shader, Program>,
 
+    /// The variants of the material programs that read the model transforms from instance
+    /// attributes. Materials that can't be instanced don't have an instanced program.
+    instanced_programs: HashMap<Shader, Program>,
+
+    /// The variants of the material programs used to draw skinned meshes. Skinned meshes are
+    /// drawn with the regular program, in their bind pose, if the variant failed to build.
+    skinned_programs: HashMap<Shader, Program>,
+
+    camera_timers: HashMap<CameraId, CameraTimer>,
+
+    /// A 1x1 white texture used in place of material textures that haven't been registered.
+    default_texture: GlTexture2d,
+
     material_counter: MaterialId,
     mesh_counter: GpuMesh,
     texture_counter: GpuTexture,
@@ -48,26 +313,301 @@ pub struct GlRender {
     light_counter: LightId,
     shader_counter: Shader,
 
-    ambient_color: Color,
+    ambient: AmbientMode,
+
+    /// The distance fog set with `set_fog()`.
+    fog: Option<Fog>,
+
+    /// The materials run over the scene after it's drawn, set with `set_post_effects()`.
+    post_effects: Vec<MaterialId>,
+
+    /// The offscreen targets the scene is drawn into, which only exist while there are post
+    /// effects.
+    post_targets: Option<PostTargets>,
+
+    /// The triangle that post effects are drawn on.
+    post_vertex_array: VertexArray,
 
     default_material: Material,
+
+    /// Whether output is converted to sRGB and sRGB textures are converted to linear when sampled.
+    gamma_correct: bool,
+
+    /// Whether material build errors include the generated shader source.
+    verbose_material_errors: bool,
+
+    /// The time the renderer was created, used to drive automatic vertex animation playback.
+    start_time: Instant,
+
+    /// Warnings that have already been printed, so that problems found every frame (such as a
+    /// mesh instance using a destroyed mesh) are only reported once.
+    reported_warnings: HashSet<String>,
+
+    /// Programs that read the light arrays and so can draw up to `MAX_LIGHTS` lights at once.
+    /// Other programs are drawn once per light.
+    light_array_programs: HashSet<Shader>,
+
+    /// The names of the light array uniforms for each index, so they aren't formatted every draw.
+    light_uniform_names: Vec<LightUniformNames>,
+
+    /// Programs that sample the shadow map.
+    shadow_programs: HashSet<Shader>,
+
+    /// The program used to draw mesh instances into the shadow map.
+    shadow_program: Program,
+
+    /// The shadow maps of the shadow casting lights, created the first time a light casts shadows.
+    shadow_atlas: Option<ShadowAtlas>,
+
+    /// A depth texture bound in place of the shadow atlas when no light casts shadows, since the
+    /// shadow map sampler needs a depth texture even if it isn't sampled.
+    default_shadow_map: GlTexture2d,
+
+    /// The resolution of shadow maps for lights that don't override it.
+    shadow_resolution: u32,
+
+    /// The submeshes of the registered mesh instances, as pairs of mesh instance and submesh
+    /// index, sorted by shader, material textures, and mesh so that consecutive draws share as
+    /// much GL state as possible.
+    draw_list: Vec<(MeshInstanceId, usize)>,
+
+    /// Statistics for the last frame drawn.
+    frame_stats: FrameStats,
+
+    /// Set when a mesh instance is added, removed, or may have changed, so that `draw_list` needs
+    /// to be rebuilt before the next frame.
+    draw_list_dirty: bool,
+
+    /// The cube map drawn behind the scene, set with `set_skybox()`.
+    skybox: Option<GpuTexture>,
+
+    /// The program used to draw the skybox.
+    skybox_program: Program,
+
+    /// The unit cube the skybox is drawn on.
+    skybox_vertex_array: VertexArray,
+
+    /// The debug visualization set with `set_debug_mode()`.
+    debug_mode: DebugMode,
+
+    /// The program used to draw meshes in `DebugMode::Normals`, built the first time it's used.
+    normals_program: Option<Program>,
+
+    /// Holds the per-instance transforms for the instanced draws, refilled every frame.
+    instance_buffer: VertexBuffer,
+
+    /// Holds the bone transforms of the skinned mesh being drawn, refilled for each skinned draw.
+    bone_buffer: UniformBuffer,
+
+    /// Scratch space for the bone transforms, kept so that its memory is reused between draws.
+    bone_data: Vec<f32>,
+
+    /// The sprites drawn over the scene.
+    sprites: HashMap<SpriteId, Sprite>,
+    sprite_counter: SpriteId,
+
+    /// Holds the quads of the sprites, four vertices per sprite. The buffers' storage is only
+    /// reallocated when there are more sprites than fit in it.
+    sprite_vertex_array: VertexArray,
+
+    /// The number of sprites that the sprite index buffer has indices for.
+    sprite_capacity: usize,
+
+    /// Reused every frame to build the sprite vertices.
+    sprite_vertices: Vec<f32>,
+
+    /// The font used by `draw_text()`, set with `set_font()`.
+    font: BitmapFont,
+
+    /// The texture of `font` on the GPU.
+    font_texture: GlTexture2d,
+
+    /// The program used to draw sprites and text.
+    overlay_program: Program,
+
+    /// Holds the quads of the text drawn this frame. The vertex buffer's storage is only
+    /// reallocated when the text no longer fits in it.
+    text_vertex_array: VertexArray,
+
+    /// The vertices of the text queued with `draw_text()` since the last frame.
+    text_vertices: Vec<f32>,
+
+    /// Reused between calls to `draw_text()` to lay out the text.
+    text_quads: Vec<GlyphQuad>,
 }
 
 impl GlRender {
-    pub fn new(window: &Window) -> Result<GlRender, Error> {
-        let context = Context::from_window(window)?;
+    pub fn new(window: &Window, gamma_correct: bool, samples: u32, shadow_resolution: u32) -> Result<GlRender, Error> {
+        let context = ContextBuilder::new()
+            .srgb(gamma_correct)
+            .samples(samples)
+            .build(window)?;
+
+        GlRender::with_context(context, None, gamma_correct, shadow_resolution)
+    }
+
+    /// Creates a renderer that draws into a `width` by `height` offscreen framebuffer instead of
+    /// a window.
+    ///
+    /// Headless renderers are meant for automated tests and tools that run on machines without a
+    /// display. Use `capture_frame()` to read back the drawn frame. The offscreen framebuffer
+    /// isn't multisampled.
+    pub fn headless(width: i32, height: i32, gamma_correct: bool, shadow_resolution: u32) -> Result<GlRender, Error> {
+        let context = ContextBuilder::new()
+            .srgb(gamma_correct)
+            .build_offscreen(width, height)?;
+        let headless_target = HeadlessTarget::new(&context, width, height, gamma_correct)?;
+
+        GlRender::with_context(context, Some(headless_target), gamma_correct, shadow_resolution)
+    }
+
+    fn with_context(
+        context: Context,
+        headless_target: Option<HeadlessTarget>,
+        gamma_correct: bool,
+        shadow_resolution: u32,
+    ) -> Result<GlRender, Error> {
+        let default_texture = GlTexture2d::new(
+            &context,
+            TextureFormat::Rgba,
+            TextureInternalFormat::Rgba,
+            1,
+            1,
+            &[(255u8, 255u8, 255u8, 255u8)])?;
+        default_texture.set_debug_label("Default texture");
+
+        let mut default_shadow_map = GlTexture2d::depth(&context, 1, 1, DepthFormat::Depth24)?;
+        default_shadow_map.set_compare(Comparison::LessThanOrEqual);
+        default_shadow_map.set_debug_label("Default shadow map");
+
+        let shadow_program = ProgramBuilder::new(&context)
+            .bind_attrib("vertex_position", POSITION_LOCATION)
+            .build_from_sources(SHADOW_VERT_SOURCE, SHADOW_FRAG_SOURCE)
+            .expect("Failed to build shadow program");
+        shadow_program.set_debug_label("Shadow program");
+
+        let skybox_program = ProgramBuilder::new(&context)
+            .bind_attrib("vertex_position", POSITION_LOCATION)
+            .build_from_sources(SKYBOX_VERT_SOURCE, SKYBOX_FRAG_SOURCE)
+            .expect("Failed to build skybox program");
+        skybox_program.set_debug_label("Skybox program");
+
+        let skybox_vertex_array = {
+            let mut vertex_buffer = VertexBuffer::new(&context);
+            vertex_buffer.set_data_f32(&SKYBOX_POSITIONS);
+            vertex_buffer.set_attrib_f32(
+                "position",
+                AttribLayout {
+                    elements: 3,
+                    stride: 0,
+                    offset: 0,
+                });
+
+            let mut index_buffer = IndexBuffer::new(&context);
+            index_buffer.set_data_u32(&SKYBOX_INDICES);
+
+            let vertex_array = VertexArray::with_index_buffer(&context, vertex_buffer, index_buffer);
+            vertex_array.map_attrib_location("position", POSITION_LOCATION);
+            vertex_array.set_debug_label("Skybox");
+            vertex_array
+        };
+
+        let post_vertex_array = {
+            let mut vertex_buffer = VertexBuffer::new(&context);
+            vertex_buffer.set_data_f32(&POST_VERTICES);
+            vertex_buffer.set_attrib_f32(
+                "position",
+                AttribLayout {
+                    elements: 2,
+                    stride: 4,
+                    offset: 0,
+                });
+            vertex_buffer.set_attrib_f32(
+                "uv",
+                AttribLayout {
+                    elements: 2,
+                    stride: 4,
+                    offset: 2,
+                });
+
+            // Post effects are drawn with the material programs, so the triangle's attributes
+            // go where the mesh attributes would.
+            let vertex_array = VertexArray::new(&context, vertex_buffer);
+            vertex_array.map_attrib_location("position", POSITION_LOCATION);
+            vertex_array.map_attrib_location("uv", UV0_LOCATION);
+            vertex_array.set_debug_label("Post effect triangle");
+            vertex_array
+        };
+
+        let instance_buffer = VertexBuffer::new(&context);
+        instance_buffer.set_debug_label("Instance transforms");
+
+        let bone_buffer = UniformBuffer::new(&context);
+
+        let overlay_program = ProgramBuilder::new(&context)
+            .bind_attrib("vertex_position", POSITION_LOCATION)
+            .bind_attrib("vertex_uv", UV0_LOCATION)
+            .bind_attrib("vertex_color", COLOR_LOCATION)
+            .build_from_sources(OVERLAY_VERT_SOURCE, OVERLAY_FRAG_SOURCE)
+            .expect("Failed to build overlay program");
+        overlay_program.set_debug_label("Overlay program");
+
+        let sprite_vertex_array = {
+            // Start with room for a single sprite, the buffers grow as sprites are registered.
+            let mut vertex_buffer = VertexBuffer::new(&context);
+            vertex_buffer.set_data_f32(&[0.0; OVERLAY_VERTEX_LEN * 4]);
+            set_overlay_attribs(&mut vertex_buffer);
+            vertex_buffer.set_debug_label("Sprite vertices");
+
+            let mut index_buffer = IndexBuffer::new(&context);
+            index_buffer.set_data_u32(&sprite_indices(1));
+
+            let vertex_array = VertexArray::with_index_buffer(&context, vertex_buffer, index_buffer);
+            for &(name, location, _, _) in &OVERLAY_ATTRIBS {
+                vertex_array.map_attrib_location(name, location);
+            }
+            vertex_array.set_debug_label("Sprites");
+            vertex_array
+        };
+
+        let text_vertex_array = {
+            // Start with room for a single vertex, the buffer grows as needed when text is drawn.
+            let mut vertex_buffer = VertexBuffer::new(&context);
+            vertex_buffer.set_data_f32(&[0.0; OVERLAY_VERTEX_LEN]);
+            set_overlay_attribs(&mut vertex_buffer);
+            vertex_buffer.set_debug_label("Text vertices");
+
+            let vertex_array = VertexArray::new(&context, vertex_buffer);
+            for &(name, location, _, _) in &OVERLAY_ATTRIBS {
+                vertex_array.map_attrib_location(name, location);
+            }
+            vertex_array.set_debug_label("Text");
+            vertex_array
+        };
+
+        let font = BitmapFont::embedded();
+        let font_texture = upload_texture(&context, font.texture(), gamma_correct);
+        font_texture.set_debug_label("Font texture");
 
         let mut renderer = GlRender {
             context: context,
+            headless_target: headless_target,
 
             materials: HashMap::new(),
             meshes: HashMap::new(),
             textures: HashMap::new(),
+            cube_textures: HashMap::new(),
             mesh_instances: HashMap::new(),
             anchors: HashMap::new(),
+            world_transforms: HashMap::new(),
             cameras: HashMap::new(),
             lights: HashMap::new(),
             programs: HashMap::new(),
+            instanced_programs: HashMap::new(),
+            skinned_programs: HashMap::new(),
+            camera_timers: HashMap::new(),
+
+            default_texture: default_texture,
 
             material_counter: MaterialId::initial(),
             mesh_counter: GpuMesh::initial(),
@@ -78,12 +618,69 @@ impl GlRender {
             light_counter: LightId::initial(),
             shader_counter: Shader::initial(),
 
-            ambient_color: Color::rgb(0.01, 0.01, 0.01),
+            ambient: AmbientMode::Flat(Color::rgb(0.01, 0.01, 0.01)),
+            fog: None,
+
+            post_effects: Vec::new(),
+            post_targets: None,
+            post_vertex_array: post_vertex_array,
 
             // Use temporary value and replace it later.
             default_material: Material::new(Shader::initial()),
+
+            gamma_correct: gamma_correct,
+
+            verbose_material_errors: false,
+
+            start_time: Instant::now(),
+
+            reported_warnings: HashSet::new(),
+
+            light_array_programs: HashSet::new(),
+            light_uniform_names: (0..MAX_LIGHTS).map(LightUniformNames::new).collect(),
+
+            shadow_programs: HashSet::new(),
+            shadow_program: shadow_program,
+            shadow_atlas: None,
+            default_shadow_map: default_shadow_map,
+            shadow_resolution: shadow_resolution,
+
+            draw_list: Vec::new(),
+            frame_stats: FrameStats::default(),
+            draw_list_dirty: false,
+
+            skybox: None,
+            skybox_program: skybox_program,
+            skybox_vertex_array: skybox_vertex_array,
+
+            debug_mode: DebugMode::None,
+            normals_program: None,
+
+            instance_buffer: instance_buffer,
+            bone_buffer: bone_buffer,
+            bone_data: Vec::with_capacity(MAX_BONES * 16),
+
+            sprites: HashMap::new(),
+            sprite_counter: SpriteId::initial(),
+            sprite_vertex_array: sprite_vertex_array,
+            sprite_capacity: 1,
+            sprite_vertices: Vec::new(),
+
+            font: font,
+            font_texture: font_texture,
+            overlay_program: overlay_program,
+            text_vertex_array: text_vertex_array,
+            text_vertices: Vec::new(),
+            text_quads: Vec::new(),
         };
 
+        // Meshes without vertex colors are drawn as if every vertex were white.
+        renderer.context.set_default_attrib(COLOR_LOCATION, (1.0, 1.0, 1.0, 1.0));
+
+        // Meshes without tangents get a fixed right-handed tangent along +x, which only lines up
+        // with the texture for flat surfaces facing +z, but keeps the TBN matrix well formed.
+        renderer.context.set_default_attrib(TANGENT_LOCATION, (1.0, 0.0, 0.0, 1.0));
+
         // Load source code for the default material.
         let default_material_source = str::from_utf8(DEFAULT_SHADER_BYTES).unwrap();
         let material_source = MaterialSource::from_str(default_material_source).unwrap();
@@ -98,340 +695,510 @@ impl GlRender {
 
         Ok(renderer)
     }
-}
 
-impl Drop for GlRender {
-    fn drop(&mut self) {
-        // Empty all containers to force cleanup of OpenGL primitives before we tear down the
-        // GL subsystem.
-        // TODO: Do we have to do this? It would be better if we could tear down the context
-        // without having to cleanup each GL resource, since deleting the context effectively
-        // deletes them all too. I think the problem here comes from the fact that by default
-        // the context gets dropped first, then the resources get dropped, and they can't be
-        // deleted once the context is gone. If we could get them to silently do nothing when
-        // dropped if the context has already been dropped, then we'd get faster shutdown.
-        self.materials.clear();
-        self.meshes.clear();
-        self.textures.clear();
-        self.mesh_instances.clear();
-        self.anchors.clear();
-        self.cameras.clear();
-        self.lights.clear();
-        self.programs.clear();
+    /// Sets whether material build errors include the generated shader source.
+    ///
+    /// See `RendererBuilder::verbose_material_errors()`.
+    pub fn set_verbose_material_errors(&mut self, verbose: bool) {
+        self.verbose_material_errors = verbose;
     }
-}
 
-impl Renderer for GlRender {
-    fn draw(&mut self) {
-        let _stopwatch = Stopwatch::new("GLRender::draw()");
+    /// Reads back the frame drawn by the last call to `draw()`.
+    ///
+    /// `draw()` finishes by swapping buffers, which leaves the contents of the back buffer
+    /// undefined, so the frame is read from the front buffer instead. Capture the frame right
+    /// after `draw()`, before anything else draws to the window. Parts of the window that are
+    /// covered by other windows or are offscreen aren't owned by the renderer and may be captured
+    /// with undefined contents.
+    ///
+    /// Headless renderers read the frame from their offscreen framebuffer, which always holds
+    /// the whole frame.
+    pub fn capture_frame(&self) -> Image {
+        if let Some(ref target) = self.headless_target {
+            let width = target.color.width();
+            let height = target.color.height();
+
+            // Textures store the bottom row first, so flip the rows to match the window capture.
+            let data = target.color.read_data();
+            let mut pixels = Vec::with_capacity(data.len());
+            for row in data.chunks(width * 4).rev() {
+                pixels.extend_from_slice(row);
+            }
 
-        {
-            let _stopwatch = Stopwatch::new("Clearing buffer");
-            self.context.clear();
+            return Image::new(width, height, pixels);
         }
 
-        // TODO: Support rendering multiple cameras.
-        // TODO: Should we warn if there are no cameras?
-        if let Some(camera) = self.cameras.values().next() {
-            let _stopwatch = Stopwatch::new("Rendering camera");
+        let (width, height) = self.context.dimensions();
+        let (width, height) = (width.max(0), height.max(0));
+        let pixels = self.context.read_front_pixels(0, 0, width, height);
+        Image::new(width as usize, height as usize, pixels)
+    }
 
-            let camera_anchor = match camera.anchor() {
-                Some(ref anchor_id) => self.anchors.get(anchor_id).expect("no such anchor exists"),
-                None => unimplemented!(),
-            };
+    /// Captures the frame drawn by the last call to `draw()` and saves it to `path` as an
+    /// uncompressed TGA file.
+    ///
+    /// See `capture_frame()` for when the frame can be captured.
+    pub fn capture_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.capture_frame().save_tga(path)
+    }
 
-            for mesh_instance in self.mesh_instances.values() {
-                let anchor = match mesh_instance.anchor() {
-                    Some(anchor_id) => self.anchors.get(anchor_id).expect("No such anchor exists"),
-                    None => continue,
-                };
+    /// Gives every shadow casting light a tile in the shadow atlas for each camera, and
+    /// invalidates the cached tiles that may have been changed by shadow casters moving since the
+    /// last frame. Every tile is invalidated if `shadows_changed` is set.
+    ///
+    /// The atlas is only laid out again when the set of tiles changes, and tiles that keep their
+    /// place keep their cached shadow maps. A moving caster only invalidates the tiles it was drawn
+    /// into or moved into, so one moving object doesn't re-render every static light's shadows.
+    fn update_shadow_atlas(&mut self, shadows_changed: bool) {
+        // Only directional lights cast shadows, point lights don't have a shadow map yet.
+        let mut requests = Vec::new();
+        for (&light_id, light) in &self.lights {
+            if !light.enabled() || !light.casts_shadows() {
+                continue;
+            }
 
-                let model_transform = anchor.matrix();
-                let normal_transform = anchor.normal_matrix();
+            if let LightData::Directional { .. } = light.data {
+                let resolution = light.shadow_resolution().unwrap_or(self.shadow_resolution);
+                for &camera_id in self.cameras.keys() {
+                    requests.push(((light_id, camera_id), resolution));
+                }
+            }
+        }
+        requests.sort();
 
-                let mesh_data = self.meshes.get(mesh_instance.mesh()).expect("Mesh data does not exist for mesh id");
+        let layout_changed = match self.shadow_atlas {
+            Some(ref atlas) => atlas.layout != requests,
+            None => !requests.is_empty(),
+        };
+        if layout_changed {
+            if requests.is_empty() {
+                self.shadow_atlas = None;
+                return;
+            }
 
-                let _stopwatch = Stopwatch::new("Drawing mesh");
+            let max_size = self.context.limits().max_texture_size as u32;
+            let (size, positions) = pack_shadow_tiles(&requests, max_size);
+            for &((light_id, camera_id), _) in &requests {
+                if !positions.contains_key(&(light_id, camera_id)) {
+                    warn_once(
+                        &mut self.reported_warnings,
+                        format!("The shadow atlas is full, {:?} will not cast shadows for {:?}", light_id, camera_id));
+                }
+            }
 
-                let default_texture = GlTexture2d::empty(&self.context);
+            // Resizing the atlas loses the contents of every tile.
+            let keep_texture = self.shadow_atlas.as_ref().map_or(false, |atlas| atlas.size == size);
+            if !keep_texture {
+                let atlas = ShadowAtlas::new(&self.context, size).expect("Failed to create shadow atlas");
+                self.shadow_atlas = Some(atlas);
+            }
 
-                // Calculate the various transforms needed for rendering.
-                let view_transform = camera_anchor.view_matrix();
-                let model_view_transform = view_transform * model_transform;
-                let projection_transform = camera.projection_matrix();
-                let model_view_projection = projection_transform * model_view_transform;
+            let atlas = self.shadow_atlas.as_mut().unwrap();
+            let mut old_tiles = mem::replace(&mut atlas.tiles, HashMap::new());
+            for &(id, resolution) in &requests {
+                let (x, y) = match positions.get(&id) {
+                    Some(&position) => position,
+                    None => continue,
+                };
 
-                let view_normal_transform = {
-                    let inverse_model = normal_transform.transpose();
-                    let inverse_view = camera_anchor.inverse_view_matrix().into();
-                    let inverse_model_view = inverse_model * inverse_view;
-                    inverse_model_view.transpose()
+                let tile = match old_tiles.remove(&id) {
+                    Some(tile) if tile.x == x && tile.y == y && tile.resolution == resolution => tile,
+                    _ => ShadowTile {
+                        x: x,
+                        y: y,
+                        resolution: resolution,
+                        cached: None,
+                        casters: HashSet::new(),
+                    },
                 };
+                atlas.tiles.insert(id, tile);
+            }
+            atlas.layout = requests;
+        }
 
-                let material = mesh_instance.material();
+        let atlas = match self.shadow_atlas {
+            Some(ref mut atlas) => atlas,
+            None => return,
+        };
 
-                let mut draw_builder = {
-                    let _stopwatch = Stopwatch::new("Initialize DrawBuilder");
+        if shadows_changed {
+            for tile in atlas.tiles.values_mut() {
+                tile.cached = None;
+            }
+            return;
+        }
 
-                    let program = self
-                        .programs
-                        .get(material.shader())
-                        .expect("Material is using a shader that does not exist");
+        // Find the shadow casters that moved and where they are now. Casters whose anchor has
+        // been destroyed are no longer drawn, so they only change the tiles they were drawn into.
+        let mut moved_casters = HashSet::new();
+        let mut moved_bounds = Vec::new();
+        for (&mesh_instance_id, mesh_instance) in &self.mesh_instances {
+            if !mesh_instance.casts_shadows() {
+                continue;
+            }
 
-                    // Set the shader to use.
-                    let mut draw_builder = DrawBuilder::new(
-                        &self.context,
-                        &mesh_data.vertex_array,
-                        DrawMode::Triangles,
-                    );
-                    draw_builder
-                    .program(program)
-                    .cull(Face::Back)
-                    .depth_test(Comparison::Less)
+            let anchor = match mesh_instance.anchor().and_then(|anchor_id| self.world_transforms.get(anchor_id)) {
+                Some(anchor) => anchor,
+                None => {
+                    moved_casters.insert(mesh_instance_id);
+                    continue;
+                },
+            };
+            if anchor_changed(&self.anchors, mesh_instance.anchor()) {
+                moved_casters.insert(mesh_instance_id);
+                if let Some(mesh_data) = self.meshes.get(mesh_instance.mesh()) {
+                    moved_bounds.push(mesh_data.bounding_sphere.transform(anchor.matrix()));
+                }
+            }
+        }
 
-                    // Associate vertex attributes with shader program variables.
-                    .map_attrib_name("position", "vertex_position")
-                    .map_attrib_name("normal", "vertex_normal")
-                    .map_attrib_name("texcoord", "vertex_uv0");
+        for tile in atlas.tiles.values_mut() {
+            let invalidated = match tile.cached {
+                Some(ref cache_key) => {
+                    tile.casters.iter().any(|caster| moved_casters.contains(caster))
+                        || moved_bounds
+                            .iter()
+                            .any(|&bounds| overlaps_shadow_region(cache_key.light_space_transform, bounds))
+                },
+                None => false,
+            };
+            if invalidated {
+                tile.cached = None;
+            }
+        }
+    }
 
-                    draw_builder
-                };
+    /// Splits the draw list into draws of single mesh instances and instanced draws of groups of
+    /// mesh instances that share a mesh and material, returning the draws and the instance data
+    /// for the instanced draws.
+    ///
+    /// Each group is drawn at the position of its first mesh instance in the draw list. Groups
+    /// with no more than `INSTANCING_THRESHOLD` mesh instances are drawn individually. Hidden
+    /// mesh instances are left out.
+    fn batch_draw_list(&self) -> (Vec<DrawItem>, Vec<f32>) {
+        // Each group is marked with whether its mesh instances can be instanced at all. Mesh
+        // instances that can't be instanced get a group of their own.
+        let mut groups: Vec<(bool, usize, Vec<MeshInstanceId>)> = Vec::new();
+        let mut groups_by_mesh: HashMap<(GpuMesh, usize), Vec<usize>> = HashMap::new();
+
+        for &(mesh_instance_id, submesh) in &self.draw_list {
+            let mesh_instance = &self.mesh_instances[&mesh_instance_id];
+            if !mesh_instance.visible() {
+                continue;
+            }
+            let material = mesh_instance.submesh_material(submesh);
+
+            // Mesh instances with missing anchors or meshes are left to the individual path,
+            // which reports them. Vertex animation and the normals debug view both need the
+            // model transforms as uniforms, uniform overrides and poses are per mesh instance,
+            // and billboards have a different model transform for each camera.
+            let can_instance = self.debug_mode != DebugMode::Normals
+                && mesh_instance.uniform_overrides().next().is_none()
+                && mesh_instance.billboard() == BillboardMode::None
+                && self.instanced_programs.contains_key(material.shader())
+                && mesh_instance.anchor().map_or(false, |anchor_id| self.world_transforms.contains_key(anchor_id))
+                && self.meshes
+                    .get(mesh_instance.mesh())
+                    .map_or(false, |mesh_data| mesh_data.vertex_animation.is_none() && !mesh_data.skinned);
+            if !can_instance {
+                groups.push((false, submesh, vec![mesh_instance_id]));
+                continue;
+            }
 
-                // Set uniform transforms.
-                {
-                    let _stopwatch = Stopwatch::new("Transform uniforms");
+            let mesh_groups = groups_by_mesh.entry((*mesh_instance.mesh(), submesh)).or_insert_with(Vec::new);
+            let existing = mesh_groups.iter().cloned().find(|&group_index| {
+                let other = &self.mesh_instances[&groups[group_index].2[0]];
+                other.submesh_material(submesh) == material
+                    && other.receives_shadows() == mesh_instance.receives_shadows()
+                    && other.layers() == mesh_instance.layers()
+            });
 
-                    draw_builder
-                    .uniform(
-                        "model_transform",
-                        GlMatrix {
-                            data: model_transform.raw_data(),
-                            transpose: true,
-                        },
-                    )
-                    .uniform(
-                        "normal_transform",
-                        GlMatrix {
-                            data: normal_transform.raw_data(),
-                            transpose: true,
-                        },
-                    )
-                    .uniform(
-                        "view_normal_transform",
-                        GlMatrix {
-                            data: view_normal_transform.raw_data(),
-                            transpose: true,
-                        },
-                    )
-                    .uniform(
-                        "view_transform",
-                        GlMatrix {
-                            data: view_transform.raw_data(),
-                            transpose: true,
-                        },
-                    )
-                    .uniform(
-                        "model_view_transform",
-                        GlMatrix {
-                            data: model_view_transform.raw_data(),
-                            transpose: true,
-                        },
-                    )
-                    .uniform(
-                        "projection_transform",
-                        GlMatrix {
-                            data: projection_transform.raw_data(),
-                            transpose: true,
-                        },
-                    )
-                    .uniform(
-                        "model_view_projection",
-                        GlMatrix {
-                            data: model_view_projection.raw_data(),
-                            transpose: true,
-                        },
-                    );
-                }
+            match existing {
+                Some(group_index) => groups[group_index].2.push(mesh_instance_id),
+                None => {
+                    mesh_groups.push(groups.len());
+                    groups.push((true, submesh, vec![mesh_instance_id]));
+                },
+            }
+        }
 
-                // Apply material attributes.
-                {
-                    let _stopwatch = Stopwatch::new("Material uniforms");
+        let mut draw_items = Vec::with_capacity(groups.len());
+        let mut instance_data = Vec::new();
+        for (can_instance, submesh, group) in groups {
+            if can_instance && group.len() > INSTANCING_THRESHOLD {
+                let first_instance = instance_data.len() / INSTANCE_DATA_LEN;
+                for mesh_instance_id in &group {
+                    let anchor_id = self.mesh_instances[mesh_instance_id].anchor().unwrap();
+                    let anchor = &self.world_transforms[anchor_id];
+
+                    // Attribute matrices are read one column at a time, so store them transposed.
+                    instance_data.extend_from_slice(anchor.matrix().transpose().raw_data());
+                    instance_data.extend_from_slice(anchor.normal_matrix().transpose().raw_data());
+                }
 
-                    // Set uniform colors.
-                    draw_builder.uniform::<[f32; 4]>("global_ambient", self.ambient_color.into());
-
-                    // Other uniforms.
-                    draw_builder.uniform("camera_position", *camera_anchor.position().as_array());
-
-                    for (name, property) in material.properties() {
-                        match *property {
-                            MaterialProperty::Color(ref color) => {
-                                draw_builder.uniform::<[f32; 4]>(name, color.into());
-                            },
-                            MaterialProperty::f32(value) => {
-                                draw_builder.uniform(name, value);
-                            },
-                            MaterialProperty::Vector3(value) => {
-                                draw_builder.uniform::<[f32; 3]>(name, value.into());
-                            },
-                            MaterialProperty::Texture(ref texture) => {
-                                let gl_texture =
-                                self.textures
-                                .get(texture)
-                                .unwrap_or(&default_texture);
-                                draw_builder.uniform(name, gl_texture);
-                            },
-                        }
-                    }
-                }
-
-                // Render first light without blending so it overrides any objects behind it.
-                // We also render it with light strength 0 so it only renders ambient color.
-                {
-                    let _stopwatch = Stopwatch::new("Draw (no lights)");
-
-                    draw_builder
-                    .uniform("light_type", 0)
-                    .draw();
-                }
-
-                // Render the rest of the lights with blending on the the depth check set to
-                // less than or equal.
-                {
-                    let _stopwatch = Stopwatch::new("Draw with lights");
-
-                    draw_builder
-                    .depth_test(Comparison::LessThanOrEqual)
-                    .blend(SourceFactor::One, DestFactor::One);
-
-                    for light in self.lights.values() {
-                        // Send common light data.
-                        draw_builder.uniform::<[f32; 4]>("light_color", light.color.into());
-                        draw_builder.uniform("light_strength", light.strength);
-
-                        // Send data specific to the current type of light.
-                        match light.data {
-                            LightData::Point { radius } => {
-                                draw_builder.uniform("light_type", 1);
-
-                                // Get the light's anchor.
-                                let light_anchor = match light.anchor() {
-                                    Some(anchor_id) => self.anchors.get(&anchor_id).expect("No such anchor exists"),
-                                    None => panic!("Cannot render light if it's not attached to an anchor"),
-                                };
-
-                                // Send the light's position in world space.
-                                draw_builder.uniform("light_position", *light_anchor.position().as_array());
-
-                                // Send the light's position in view space.
-                                let light_position_view = light_anchor.position() * view_transform;
-                                draw_builder.uniform("light_position_view", *light_position_view.as_array());
-
-                                // Send the point light's radius.
-                                draw_builder.uniform("light_radius", radius);
-                            },
-
-                            LightData::Directional { direction } => {
-                                draw_builder.uniform("light_type", 2);
-
-                                draw_builder.uniform("light_direction", direction.into_array());
-
-                                let direction_view = direction * view_transform;
-                                draw_builder.uniform("light_direction_view", direction_view.into_array());
-                            },
-                        }
-
-                        // Draw the current light.
-                        draw_builder.draw();
-                    }
-                }
+                draw_items.push(DrawItem::Instanced {
+                    mesh_instance_id: group[0],
+                    submesh: submesh,
+                    first_instance: first_instance,
+                    count: group.len(),
+                });
+            } else {
+                draw_items.extend(group.into_iter().map(|mesh_instance_id| DrawItem::Single {
+                    mesh_instance_id: mesh_instance_id,
+                    submesh: submesh,
+                }));
             }
         }
 
-        {
-            let _stopwatch = Stopwatch::new("Swap buffers");
-            self.context.swap_buffers();
-        }
-    }
-
-    fn default_material(&self) -> Material {
-        self.default_material.clone()
+        (draw_items, instance_data)
     }
 
-    fn build_material(&mut self, source: MaterialSource) -> Result<Material, BuildMaterialError> {
-        use polygon_material::material_source::PropertyType;
-
-        // COMPILE SHADER SOURCE
-        // =====================
-
+    /// Compiles the programs for a material, returning the material's program, the variant used
+    /// for instanced draws if the material can be instanced, and the variant used for skinned
+    /// meshes if it could be built.
+    fn compile_material(&mut self, source: &MaterialSource) -> Result<(Program, Option<Program>, Option<Program>), BuildMaterialError> {
         // Generate uniform declarations for the material's properties. This string will be
         // injected into the shader templates.
-        let uniform_declarations = {
-            let mut uniform_declarations = String::new();
-            for property in &source.properties {
-                uniform_declarations.push_str("uniform ");
-
-                let type_str = match property.property_type {
-                    PropertyType::Color => "vec4",
-                    PropertyType::Texture2d => "sampler2D",
-                    PropertyType::f32 => "float",
-                    PropertyType::Vector3 => "vec3",
-                };
-
-                uniform_declarations.push_str(type_str);
-                uniform_declarations.push(' ');
-                uniform_declarations.push_str(&*property.name);
-                uniform_declarations.push_str(";\n");
-            }
+        let uniform_declarations = uniform_declarations(&source.properties);
+
+        // The second UV set is only passed through to materials that read it, so that the other
+        // materials don't use up a varying on it.
+        let uses_uv1 = source.programs.iter().any(|program_source| program_source.source().contains("@vertex.uv1"));
+        let (uv1_vert_declarations, uv1_frag_declarations, uv1_default) = if uses_uv1 {
+            ("in vec2 vertex_uv1;\nout vec2 _vertex_uv1_;", "in vec2 _vertex_uv1_;", "@vertex.uv1 = vertex_uv1;")
+        } else {
+            ("", "", "")
+        };
 
-            uniform_declarations
+        // Post effects get the scene they're drawn over, see `Renderer::set_post_effects()`. The
+        // uniforms are only declared for materials that read the scene, so that they don't
+        // collide with the properties of other materials.
+        static POST_UNIFORMS: &'static str = r#"
+            uniform sampler2D scene_color;
+            uniform sampler2D scene_depth;
+            uniform vec2 screen_size;
+            uniform float time;
+        "#;
+        let post_uniforms = if source.programs.iter().any(|program_source| {
+            program_source.is_fragment()
+                && (program_source.source().contains("scene_color") || program_source.source().contains("scene_depth"))
+        }) {
+            POST_UNIFORMS
+        } else {
+            ""
         };
 
-        static BUILT_IN_UNIFORMS: &'static str = r#"
+        // The transforms that depend on the model transform, see `MODEL_UNIFORM_NAMES`.
+        static MODEL_UNIFORMS: &'static str = r#"
             uniform mat4 model_transform;
             uniform mat3 normal_transform;
-            uniform mat4 view_transform;
             uniform mat3 view_normal_transform;
             uniform mat4 model_view_transform;
-            uniform mat4 projection_transform;
             uniform mat4 model_view_projection;
+        "#;
+
+        // Instanced draws read the model and normal transforms from instance attributes, and
+        // calculate the other transforms from them before the material's vertex program runs.
+        static INSTANCED_MODEL_TRANSFORMS: &'static str = r#"
+            in mat4 instance_model_transform;
+            in mat3 instance_normal_transform;
+
+            // Transforms directions in world space to view space.
+            uniform mat3 view_direction_transform;
+
+            mat4 model_transform;
+            mat3 normal_transform;
+            mat3 view_normal_transform;
+            mat4 model_view_transform;
+            mat4 model_view_projection;
+        "#;
+
+        static INSTANCED_MODEL_SETUP: &'static str = r#"
+            model_transform = instance_model_transform;
+            normal_transform = instance_normal_transform;
+            view_normal_transform = view_direction_transform * normal_transform;
+            model_view_transform = view_transform * model_transform;
+            model_view_projection = projection_transform * model_view_transform;
+        "#;
+
+        static BUILT_IN_UNIFORMS: &'static str = r#"
+            uniform mat4 view_transform;
+            uniform mat4 projection_transform;
 
             uniform vec4 global_ambient;
             uniform vec4 camera_position;
+
+            // 0 means flat ambient light using `global_ambient`, 1 means hemisphere ambient light.
+            uniform int ambient_mode;
+            uniform vec4 ambient_sky_color;
+            uniform vec4 ambient_ground_color;
+            uniform vec3 ambient_up;
+
+            // Gets the ambient light reaching a surface with the given normal in world space.
+            vec4 ambient_light(vec3 world_normal) {
+                if (ambient_mode == 1) {
+                    float sky = dot(world_normal, ambient_up) * 0.5 + 0.5;
+                    return mix(ambient_ground_color, ambient_sky_color, sky);
+                }
+                return global_ambient;
+            }
+
+            uniform vec4 fog_color;
+            uniform vec2 fog_params;
+            uniform int fog_mode;
+
             uniform vec4 light_position;
             uniform vec4 light_position_view;
             uniform float light_strength;
             uniform vec4 light_color;
             uniform int light_type;
             uniform float light_radius;
+            uniform int light_falloff;
+            uniform vec3 light_attenuation;
             uniform vec3 light_direction;
             uniform vec3 light_direction_view;
+            uniform int light_has_shadow;
+            uniform mat4 light_space_transform;
+            uniform vec4 shadow_region;
+
+            uniform bool shadow_enabled;
+            uniform sampler2DShadow shadow_map;
+
+            #define MAX_LIGHTS 8
+            uniform int light_count;
+            uniform int light_types[MAX_LIGHTS];
+            uniform vec4 light_colors[MAX_LIGHTS];
+            uniform float light_strengths[MAX_LIGHTS];
+            uniform vec4 light_positions[MAX_LIGHTS];
+            uniform vec4 light_positions_view[MAX_LIGHTS];
+            uniform float light_radii[MAX_LIGHTS];
+            uniform int light_falloffs[MAX_LIGHTS];
+            uniform vec3 light_attenuations[MAX_LIGHTS];
+            uniform vec3 light_directions[MAX_LIGHTS];
+            uniform vec3 light_directions_view[MAX_LIGHTS];
+            uniform int light_has_shadows[MAX_LIGHTS];
+            uniform mat4 light_space_transforms[MAX_LIGHTS];
+            uniform vec4 shadow_regions[MAX_LIGHTS];
+
+            // Gets how much of a shadow casting light reaches a point in world space, from 0.0 in
+            // full shadow to 1.0 fully lit. `transform` and `region` are the light's
+            // `light_space_transform` and `shadow_region`. The shadow is averaged over a 3x3 block
+            // of texels in the light's tile of the shadow atlas to soften its edges.
+            float shadow_light(mat4 transform, vec4 region, vec4 world_position) {
+                if (!shadow_enabled) {
+                    return 1.0;
+                }
+
+                vec4 atlas_position = transform * world_position;
+                vec3 coords = atlas_position.xyz / atlas_position.w;
+
+                // Points outside of the light's tile are never in shadow.
+                if (any(lessThan(coords.xy, region.xy)) || any(greaterThan(coords.xy, region.zw)) || coords.z > 1.0) {
+                    return 1.0;
+                }
+
+                // Keep the filter from reading the neighboring tiles.
+                vec2 texel_size = 1.0 / vec2(textureSize(shadow_map, 0));
+                vec2 low = region.xy + texel_size * 0.5;
+                vec2 high = region.zw - texel_size * 0.5;
+
+                // Offset the depth slightly to keep surfaces from shadowing themselves.
+                float bias = 0.002;
+
+                float shadow = 0.0;
+                for (int x = -1; x <= 1; x++) {
+                    for (int y = -1; y <= 1; y++) {
+                        vec2 offset = vec2(x, y) * texel_size;
+                        shadow += texture(shadow_map, vec3(clamp(coords.xy + offset, low, high), coords.z - bias));
+                    }
+                }
+                return shadow / 9.0;
+            }
+        "#;
+
+        // Vertex animation textures are sampled in the vertex stage before the material's vertex
+        // program runs. Each row of a VAT holds one frame and each column one vertex, so the
+        // vertex's data is fetched from the current and next frame and interpolated.
+        //
+        // Not all implementations support texture fetches in the vertex stage, so
+        // `register_vat_mesh()` fails if there aren't enough vertex texture units.
+        static VERTEX_ANIMATION: &'static str = r#"
+            uniform bool vat_enabled;
+            uniform bool vat_has_normals;
+            uniform float vat_time;
+            uniform sampler2D vat_positions;
+            uniform sampler2D vat_normals;
+
+            vec3 _vat_fetch_(sampler2D data) {
+                int frames = textureSize(data, 0).y;
+                float time = mod(vat_time, float(frames));
+                int frame = min(int(floor(time)), frames - 1);
+                int next_frame = (frame + 1) % frames;
+
+                vec3 current = texelFetch(data, ivec2(gl_VertexID, frame), 0).rgb;
+                vec3 next = texelFetch(data, ivec2(gl_VertexID, next_frame), 0).rgb;
+                return mix(current, next, time - float(frame));
+            }
+        "#;
+
+        // Skinned meshes blend the transforms of the four bones that influence each vertex, after
+        // any vertex animation. The bone transforms are read from a uniform buffer so that a full
+        // skeleton doesn't use up the vertex stage's uniforms. The normal is transformed without
+        // the inverse transpose, which is only correct for bones without non-uniform scale.
+        static SKINNING: &'static str = r#"
+            // Must match `skeleton::MAX_BONES`.
+            #define MAX_BONES 64
+            layout(std140) uniform Bones {
+                mat4 bone_transforms[MAX_BONES];
+            };
+
+            // Bone indices are stored as floats like the rest of the vertex data.
+            in vec4 vertex_bone_indices;
+            in vec4 vertex_bone_weights;
+        "#;
+
+        static SKINNING_SETUP: &'static str = r#"
+            ivec4 _bones_ = ivec4(vertex_bone_indices);
+            mat4 _skin_transform_ =
+                bone_transforms[_bones_.x] * vertex_bone_weights.x
+                + bone_transforms[_bones_.y] * vertex_bone_weights.y
+                + bone_transforms[_bones_.z] * vertex_bone_weights.z
+                + bone_transforms[_bones_.w] * vertex_bone_weights.w;
+            position = _skin_transform_ * position;
+            normal = normalize(mat3(_skin_transform_) * normal);
         "#;
 
-        // Generate the GLSL source for the vertex shader.
-        let vert_shader = {
+        // Generate the GLSL source for the vertex shader, given the declarations of the model
+        // transforms, the code that sets them up at the start of `main()`, and whether the
+        // vertices are skinned.
+        let vert_source = |model_transforms: &str, model_setup: &str, skinned: bool| {
+            let (skinning, skinning_setup) = if skinned { (SKINNING, SKINNING_SETUP) } else { ("", "") };
+
             static DEFAULT_VERT_MAIN: &'static str = r#"
                 @position = model_view_projection * vertex_position;
 
                 @vertex.position = vertex_position;
                 @vertex.normal = vertex_normal;
                 @vertex.uv0 = vertex_uv0;
+                @vertex.color = vertex_color;
 
                 @vertex.world_position = model_transform * vertex_position;
                 @vertex.world_normal = normalize(normal_transform * vertex_normal);
+                @vertex.world_tangent = vec4(normalize(mat3(model_transform) * vertex_tangent.xyz), vertex_tangent.w);
 
                 @vertex.view_position = model_view_transform * vertex_position;
                 @vertex.view_normal = normalize(view_normal_transform * vertex_normal);
             "#;
 
             // Retrieve source string for the vertex shader.
+            let default_vert_main = format!("{}\n{}", DEFAULT_VERT_MAIN, uv1_default);
             let raw_source =
                 source
                 .programs
                 .iter()
                 .find(|program_source| program_source.is_vertex())
                 .map(|program_source| program_source.source())
-                .unwrap_or(DEFAULT_VERT_MAIN);
+                .unwrap_or(&*default_vert_main);
 
             // Perform text replacements for the various keywords.
             let replaced_source = raw_source
@@ -439,8 +1206,11 @@ impl Renderer for GlRender {
                 .replace("@vertex.position", "_vertex_position_")
                 .replace("@vertex.normal", "_vertex_normal_")
                 .replace("@vertex.uv0", "_vertex_uv0_")
+                .replace("@vertex.uv1", "_vertex_uv1_")
+                .replace("@vertex.color", "_vertex_color_")
                 .replace("@vertex.world_position", "_vertex_world_position_")
                 .replace("@vertex.world_normal", "_vertex_world_normal_")
+                .replace("@vertex.world_tangent", "_vertex_world_tangent_")
                 .replace("@vertex.view_position", "_vertex_view_position_")
                 .replace("@vertex.view_normal", "_vertex_view_normal_");
             let replaced_source = format!(r#"
@@ -450,31 +1220,70 @@ impl Renderer for GlRender {
 
                     {}
 
+                    {}
+
+                    {}
+
+                    {}
+
+                    {}
+
                     in vec4 vertex_position;
                     in vec3 vertex_normal;
                     in vec2 vertex_uv0;
+                    in vec4 vertex_color;
+                    in vec4 vertex_tangent;
 
                     out vec4 _vertex_position_;
                     out vec3 _vertex_normal_;
                     out vec2 _vertex_uv0_;
+                    out vec4 _vertex_color_;
                     out vec4 _vertex_world_position_;
                     out vec3 _vertex_world_normal_;
+                    out vec4 _vertex_world_tangent_;
                     out vec4 _vertex_view_position_;
                     out vec3 _vertex_view_normal_;
 
+                    // The material's vertex program sees the animated vertex data through
+                    // parameters that shadow the raw vertex attributes.
+                    void _vertex_main_(vec4 vertex_position, vec3 vertex_normal, vec2 vertex_uv0) {{
+                        {}
+                    }}
+
                     void main(void) {{
                         {}
+
+                        vec4 position = vertex_position;
+                        vec3 normal = vertex_normal;
+
+                        if (vat_enabled) {{
+                            position = vec4(_vat_fetch_(vat_positions), 1.0);
+
+                            if (vat_has_normals) {{
+                                normal = normalize(_vat_fetch_(vat_normals));
+                            }}
+                        }}
+
+                        {}
+
+                        _vertex_main_(position, normal, vertex_uv0);
                     }}
                 "#,
+                model_transforms,
                 BUILT_IN_UNIFORMS,
+                VERTEX_ANIMATION,
+                skinning,
                 uniform_declarations,
-                replaced_source);
+                uv1_vert_declarations,
+                replaced_source,
+                model_setup,
+                skinning_setup);
 
-            GlShader::new(&self.context, replaced_source, ShaderType::Vertex).map_err(|err| BuildMaterialError)?
+            replaced_source
         };
 
         // Generate the GLSL source for the fragment shader.
-        let frag_shader = {
+        let frag_source = {
             // Retrieve source string for the fragment shader.
             let raw_source =
                 source
@@ -482,7 +1291,7 @@ impl Renderer for GlRender {
                 .iter()
                 .find(|program_source| program_source.is_fragment())
                 .map(|program_source| program_source.source())
-                .ok_or(BuildMaterialError)?;
+                .ok_or_else(|| in_file(BuildMaterialError::MissingFragmentProgram, source))?;
 
             // Perform text replacements for the various keywords.
             let replaced_source = raw_source
@@ -490,8 +1299,11 @@ impl Renderer for GlRender {
                 .replace("@vertex.position", "_vertex_position_")
                 .replace("@vertex.normal", "_vertex_normal_")
                 .replace("@vertex.uv0", "_vertex_uv0_")
+                .replace("@vertex.uv1", "_vertex_uv1_")
+                .replace("@vertex.color", "_vertex_color_")
                 .replace("@vertex.world_position", "_vertex_world_position_")
                 .replace("@vertex.world_normal", "_vertex_world_normal_")
+                .replace("@vertex.world_tangent", "_vertex_world_tangent_")
                 .replace("@vertex.view_position", "_vertex_view_position_")
                 .replace("@vertex.view_normal", "_vertex_view_normal_");
             let replaced_source = format!(r#"
@@ -501,11 +1313,19 @@ impl Renderer for GlRender {
 
                     {}
 
+                    {}
+
+                    {}
+
+                    {}
+
                     in vec4 _vertex_position_;
                     in vec3 _vertex_normal_;
                     in vec2 _vertex_uv0_;
+                    in vec4 _vertex_color_;
                     in vec4 _vertex_world_position_;
                     in vec3 _vertex_world_normal_;
+                    in vec4 _vertex_world_tangent_;
                     in vec4 _vertex_view_position_;
                     in vec3 _vertex_view_normal_;
 
@@ -515,250 +1335,2476 @@ impl Renderer for GlRender {
                         {}
                     }}
                 "#,
+                MODEL_UNIFORMS,
                 BUILT_IN_UNIFORMS,
+                post_uniforms,
                 uniform_declarations,
+                uv1_frag_declarations,
                 replaced_source);
 
-            GlShader::new(&self.context, replaced_source, ShaderType::Fragment).map_err(|err| BuildMaterialError)?
+            replaced_source
         };
 
-        let program = Program::new(&self.context, &[vert_shader, frag_shader]).map_err(|err| BuildMaterialError)?;
+        let program_vert_source = vert_source(MODEL_UNIFORMS, "", false);
+        let program = ProgramBuilder::new(&self.context)
+            .bind_attrib("vertex_position", POSITION_LOCATION)
+            .bind_attrib("vertex_normal", NORMAL_LOCATION)
+            .bind_attrib("vertex_uv0", UV0_LOCATION)
+            .bind_attrib("vertex_uv1", UV1_LOCATION)
+            .bind_attrib("vertex_color", COLOR_LOCATION)
+            .bind_attrib("vertex_tangent", TANGENT_LOCATION)
+            .build_from_sources(&program_vert_source, &frag_source)
+            .map_err(|error| in_file(self.program_error(error, &program_vert_source, &frag_source), source))?;
+
+        // Build the variant used to draw groups of mesh instances with a single draw call. If the
+        // fragment program reads the model transforms they're still uniforms in the instanced
+        // program, and the material can't be instanced.
+        let instanced_program = ProgramBuilder::new(&self.context)
+            .bind_attrib("vertex_position", POSITION_LOCATION)
+            .bind_attrib("vertex_normal", NORMAL_LOCATION)
+            .bind_attrib("vertex_uv0", UV0_LOCATION)
+            .bind_attrib("vertex_uv1", UV1_LOCATION)
+            .bind_attrib("vertex_color", COLOR_LOCATION)
+            .bind_attrib("vertex_tangent", TANGENT_LOCATION)
+            .bind_attrib("instance_model_transform", MODEL_TRANSFORM_LOCATION)
+            .bind_attrib("instance_normal_transform", NORMAL_TRANSFORM_LOCATION)
+            .build_from_sources(&vert_source(INSTANCED_MODEL_TRANSFORMS, INSTANCED_MODEL_SETUP, false), &frag_source)
+            .ok()
+            .filter(|instanced_program| {
+                !instanced_program
+                    .uniforms()
+                    .iter()
+                    .any(|uniform| MODEL_UNIFORM_NAMES.contains(&&*uniform.name))
+            });
 
-        let program_id = self.shader_counter.next();
-        self.programs.insert(program_id, program);
+        // Build the variant used to draw skinned meshes. The program still builds and draws
+        // skinned meshes in their bind pose if this fails, e.g. if the implementation doesn't
+        // support a uniform block as large as the bones.
+        let skinned_vert_source = vert_source(MODEL_UNIFORMS, "", true);
+        let skinned_program = match ProgramBuilder::new(&self.context)
+            .bind_attrib("vertex_position", POSITION_LOCATION)
+            .bind_attrib("vertex_normal", NORMAL_LOCATION)
+            .bind_attrib("vertex_uv0", UV0_LOCATION)
+            .bind_attrib("vertex_uv1", UV1_LOCATION)
+            .bind_attrib("vertex_color", COLOR_LOCATION)
+            .bind_attrib("vertex_tangent", TANGENT_LOCATION)
+            .bind_attrib("vertex_bone_indices", BONE_INDICES_LOCATION)
+            .bind_attrib("vertex_bone_weights", BONE_WEIGHTS_LOCATION)
+            .build_from_sources(&skinned_vert_source, &frag_source)
+        {
+            Ok(skinned_program) => {
+                if let Some(index) = skinned_program.uniform_block_index("Bones") {
+                    skinned_program.uniform_block_binding(index, BONES_BINDING);
+                }
+                Some(skinned_program)
+            },
+            Err(error) => {
+                let error = self.program_error(error, &skinned_vert_source, &frag_source);
+                warn_once(
+                    &mut self.reported_warnings,
+                    format!(
+                        "Failed to build the skinned variant of a material, skinned meshes will be drawn in their bind pose: {}",
+                        error));
+                None
+            },
+        };
 
-        // BUILD MATERIAL OBJECT
-        // =====================
+        // Warn about properties the shader doesn't use, since setting them will silently do
+        // nothing.
+        for property in &source.properties {
+            if !program.uniforms().iter().any(|uniform| uniform.name == property.name) {
+                warn_once(
+                    &mut self.reported_warnings,
+                    format!("Material property \"{}\" is not used by the material's shader", property.name));
+            }
+        }
 
-        let mut material = Material::new(program_id);
+        Ok((program, instanced_program, skinned_program))
+    }
 
-        // Add the properties from the material declaration.
-        for property in source.properties {
-            match property.property_type {
-                PropertyType::Color => material.set_color(property.name, Color::default()),
-                PropertyType::Texture2d => material.set_texture(property.name, GpuTexture::default()),
-                PropertyType::f32 => material.set_f32(property.name, f32::default()),
-                PropertyType::Vector3 => material.set_vector3(property.name, Vector3::default()),
-            };
+    /// Converts an error from building a material's program, appending the generated source of
+    /// the failing shaders if verbose material errors are enabled.
+    fn program_error(&self, error: ProgramError, vert_source: &str, frag_source: &str) -> BuildMaterialError {
+        let error = BuildMaterialError::from(error);
+        if !self.verbose_material_errors {
+            return error;
         }
 
-        Ok(material)
+        match error {
+            BuildMaterialError::VertexCompile(log) =>
+                BuildMaterialError::VertexCompile(format!("{}\nGenerated source:\n{}", log, numbered_lines(vert_source))),
+            BuildMaterialError::FragmentCompile(log) =>
+                BuildMaterialError::FragmentCompile(format!("{}\nGenerated source:\n{}", log, numbered_lines(frag_source))),
+            BuildMaterialError::Link(log) =>
+                BuildMaterialError::Link(format!(
+                    "{}\nGenerated vertex source:\n{}\nGenerated fragment source:\n{}",
+                    log,
+                    numbered_lines(vert_source),
+                    numbered_lines(frag_source))),
+            error => error,
+        }
     }
 
-    fn register_material(&mut self, material: Material) -> MaterialId {
-        let material_id = self.material_counter.next();
+    /// Stores the programs for a material under `program_id`, replacing any existing programs.
+    fn insert_program(
+        &mut self,
+        program_id: Shader,
+        program: Program,
+        instanced_program: Option<Program>,
+        skinned_program: Option<Program>,
+    ) {
+        program.set_debug_label(&format!("{:?}", program_id));
+
+        // Programs that read the light arrays get all of the lights in one draw, everything else
+        // falls back to drawing once per light with the single light uniforms.
+        if program.uniforms().iter().any(|uniform| uniform.name == "light_count") {
+            self.light_array_programs.insert(program_id);
+        } else {
+            self.light_array_programs.remove(&program_id);
+        }
+        if program.uniforms().iter().any(|uniform| uniform.name == "shadow_map") {
+            self.shadow_programs.insert(program_id);
+        } else {
+            self.shadow_programs.remove(&program_id);
+        }
+        self.programs.insert(program_id, program);
 
-        let old = self.materials.insert(material_id, material);
-        assert!(old.is_none());
+        match instanced_program {
+            Some(instanced_program) => {
+                instanced_program.set_debug_label(&format!("{:?} (instanced)", program_id));
+                self.instanced_programs.insert(program_id, instanced_program);
+            },
+            None => {
+                self.instanced_programs.remove(&program_id);
+            },
+        }
 
-        material_id
+        match skinned_program {
+            Some(skinned_program) => {
+                skinned_program.set_debug_label(&format!("{:?} (skinned)", program_id));
+                self.skinned_programs.insert(program_id, skinned_program);
+            },
+            None => {
+                self.skinned_programs.remove(&program_id);
+            },
+        }
     }
+}
 
-    fn get_material(&self, material_id: MaterialId) -> Option<&Material> {
-        self.materials.get(&material_id)
-    }
+impl Renderer for GlRender {
+    fn draw(&mut self) -> RenderStats {
+        let _stopwatch = Stopwatch::new("GLRender::draw()");
+        let draw_start = Instant::now();
+
+        let mut stats = RenderStats::default();
+
+        // Counters for `FrameStats` that `RenderStats` doesn't track.
+        let mut triangles = 0;
+        let mut instances_culled = 0;
+        let mut lights_processed = 0;
+        let mut lit_draws = 0;
+        let mut draw_lights = 0;
+        let mut shadow_maps_rendered = 0;
+        let mut shadow_maps_reused = 0;
+
+        // The program used by the previous draw.
+        let mut last_program = None;
+
+        if self.debug_mode == DebugMode::Normals && self.normals_program.is_none() {
+            let normals_program = ProgramBuilder::new(&self.context)
+                .bind_attrib("vertex_position", POSITION_LOCATION)
+                .bind_attrib("vertex_normal", NORMAL_LOCATION)
+                .build_from_sources(NORMALS_VERT_SOURCE, NORMALS_FRAG_SOURCE)
+                .expect("Failed to build normals program");
+            normals_program.set_debug_label("Normals program");
+            self.normals_program = Some(normals_program);
+        }
 
-    fn register_mesh(&mut self, mesh: &Mesh) -> GpuMesh {
-        // Generate array buffer.
-        let mut vertex_buffer = VertexBuffer::new(&self.context);
-        vertex_buffer.set_data_f32(mesh.vertex_data());
+        // Wireframe mode only affects what's drawn to the screen, the shadow map is still filled
+        // so that the wireframes are shadowed the same as the filled meshes.
+        let polygon_mode = match self.debug_mode {
+            DebugMode::Wireframe => PolygonMode::Line,
+            _ => PolygonMode::Fill,
+        };
 
-        // Configure vertex attributes.
-        let position = mesh.position();
-        vertex_buffer.set_attrib_f32(
-            "position",
-            AttribLayout {
-                elements: position.elements,
-                stride: position.stride,
-                offset: position.offset,
-            });
+        // Adding, removing, or changing mesh instances may change what's in any of the shadow
+        // maps.
+        let shadows_changed = self.draw_list_dirty;
 
-        if let Some(normal) = mesh.normal() {
-            vertex_buffer.set_attrib_f32(
-                "normal",
-                AttribLayout {
-                    elements: normal.elements,
-                    stride: normal.stride,
-                    offset: normal.offset
-                });
+        if self.draw_list_dirty {
+            let _stopwatch = Stopwatch::new("Sort draw list");
+
+            self.draw_list = sorted_draw_list(&self.mesh_instances, &self.meshes);
+            self.draw_list_dirty = false;
         }
 
-        // TODO: Support multiple texcoords.
-        if let Some(texcoord) = mesh.texcoord().first() {
-            vertex_buffer.set_attrib_f32(
-                "texcoord",
-                AttribLayout {
-                    elements: texcoord.elements,
-                    stride: texcoord.stride,
-                    offset: texcoord.offset,
-                });
+        {
+            let _stopwatch = Stopwatch::new("Resolve anchor transforms");
+
+            // Break parent cycles by detaching the first anchor in the cycle, so that the rest of
+            // the anchors still have a transform.
+            self.world_transforms.clear();
+            while let Err(cycle) = resolve_world_transforms(&self.anchors, &mut self.world_transforms) {
+                warn_once(
+                    &mut self.reported_warnings,
+                    format!("{}, {:?} will be detached from its parent", cycle, cycle.anchors[0]));
+                self.anchors
+                    .get_mut(&cycle.anchors[0])
+                    .expect("Anchor in cycle does not exist")
+                    .set_parent(None);
+            }
         }
 
-        let mut index_buffer = IndexBuffer::new(&self.context);
-        index_buffer.set_data_u32(mesh.indices());
+        {
+            let _stopwatch = Stopwatch::new("Update shadow atlas");
+            self.update_shadow_atlas(shadows_changed);
+        }
 
-        let mesh_id = self.mesh_counter.next();
+        // The framebuffer the finished frame goes into, where `None` is the window. While there
+        // are post effects the cameras draw into the post targets instead, and the last effect
+        // draws into the frame.
+        let screen_target = self.headless_target.as_ref().map(|target| &target.framebuffer);
+        let scene_target = self.post_targets.as_ref().map_or(screen_target, |targets| Some(&targets.scene));
 
-        let vertex_array = VertexArray::with_index_buffer(
-            &self.context,
-            vertex_buffer,
-            index_buffer,
-        );
+        {
+            let _stopwatch = Stopwatch::new("Clearing buffer");
 
-        self.meshes.insert(
-            mesh_id,
-            MeshData {
-                vertex_array: vertex_array,
-                position_attribute: mesh.position(),
-                normal_attribute: mesh.normal(),
-                uv_attribute: None,
-                element_count: mesh.indices().len(),
-            });
+            // The skybox is drawn behind everything a camera renders, so if a camera covers the
+            // whole window every pixel gets overwritten and the color buffer doesn't need to be
+            // cleared.
+            let skybox_covers_window = self.skybox
+                .map_or(false, |skybox| self.cube_textures.contains_key(&skybox))
+                && self.cameras.values().any(|camera| {
+                    camera.viewport().is_none()
+                        && camera.anchor().map_or(false, |anchor_id| self.world_transforms.contains_key(&anchor_id))
+                });
+            clear_screen(&self.context, scene_target, !skybox_covers_window, true, true);
+        }
 
-        mesh_id
-    }
+        let draw_items = {
+            let _stopwatch = Stopwatch::new("Batch instances");
+
+            let (draw_items, instance_data) = self.batch_draw_list();
+            if !instance_data.is_empty() {
+                self.instance_buffer.set_data_f32(&instance_data);
+                for &(name, _, offset, elements) in &INSTANCE_ATTRIBS {
+                    self.instance_buffer.set_attrib_f32(
+                        name,
+                        AttribLayout {
+                            elements: elements,
+                            stride: INSTANCE_DATA_LEN,
+                            offset: offset,
+                        });
+                }
+            }
 
-    fn register_texture(&mut self, texture: &Texture2d) -> GpuTexture {
-        let (format, internal_format) = match texture.format() {
-            DataFormat::Rgb => (TextureFormat::Rgb, TextureInternalFormat::Rgb),
-            DataFormat::Rgba => (TextureFormat::Rgba, TextureInternalFormat::Rgba),
-            DataFormat::Bgr => (TextureFormat::Bgr, TextureInternalFormat::Rgb),
-            DataFormat::Bgra => (TextureFormat::Bgra, TextureInternalFormat::Rgba),
+            draw_items
         };
 
-        // Create the Texture2d from the texture data.
-        let texture_result = match texture.data() {
-            &TextureData::f32(ref data) => {
-                GlTexture2d::new(
-                    &self.context,
-                    format,
-                    internal_format,
-                    texture.width(),
-                    texture.height(),
-                    &*data)
-            },
-            &TextureData::u8(ref data) => {
-                GlTexture2d::new(
-                    &self.context,
-                    format,
-                    internal_format,
-                    texture.width(),
-                    texture.height(),
-                    &*data)
-            },
-            &TextureData::u8x3(ref data) => {
-                GlTexture2d::new(
-                    &self.context,
-                    format,
-                    internal_format,
-                    texture.width(),
-                    texture.height(),
-                    &*data)
-            },
-            &TextureData::u8x4(ref data) => {
-                GlTexture2d::new(
-                    &self.context,
-                    format,
-                    internal_format,
-                    texture.width(),
-                    texture.height(),
-                    &*data)
-            },
+        let elapsed_seconds = {
+            let elapsed = self.start_time.elapsed();
+            elapsed.as_secs() as f32 + elapsed.subsec_nanos() as f32 * 1e-9
         };
-        let gl_texture = texture_result.expect("Unable to send texture to GPU");
 
-        // Register the mesh internally.
-        let texture_id = self.texture_counter.next();
+        // Render cameras from lowest to highest priority so that higher priority cameras are drawn
+        // on top. Ties are broken by ID so that the order is stable from frame to frame.
+        let mut camera_ids = self.cameras.keys().cloned().collect::<Vec<_>>();
+        camera_ids.sort_by_key(|camera_id| (self.cameras[camera_id].priority(), *camera_id));
 
-        let old = self.textures.insert(texture_id, gl_texture);
-        assert!(old.is_none());
+        if camera_ids.is_empty() {
+            warn_once(
+                &mut self.reported_warnings,
+                "No cameras are registered, nothing will be rendered".into());
+        }
 
-        texture_id
-    }
+        // Hidden mesh instances are left out of the draw items, but still count as culled for each
+        // camera.
+        let hidden_count = self.draw_list
+            .iter()
+            .filter(|&&(mesh_instance_id, _)| !self.mesh_instances[&mesh_instance_id].visible())
+            .count();
+
+        let (width, height) = self.context.dimensions();
+        let mut rendered_camera = false;
+        for camera_id in camera_ids {
+            let (viewport_x, viewport_y, viewport_width, viewport_height) =
+                viewport_pixels(self.cameras[&camera_id].viewport(), width, height);
+
+            // Keep the camera's aspect ratio in sync with its viewport in case the viewport
+            // changed since the window was last resized. A minimized window has a height of 0, so
+            // skip the camera to avoid dividing by zero.
+            if viewport_width <= 0 || viewport_height <= 0 {
+                continue;
+            }
+            self.cameras
+                .get_mut(&camera_id)
+                .unwrap()
+                .set_viewport_aspect(viewport_width as f32 / viewport_height as f32);
 
-    fn register_mesh_instance(&mut self, mesh_instance: MeshInstance) -> MeshInstanceId {
-        let mesh_instance_id = self.mesh_instance_counter.next();
+            let camera = &self.cameras[&camera_id];
+            let camera_layers = camera.layers();
+            let camera_anchor = match camera.anchor() {
+                Some(anchor_id) => match self.world_transforms.get(&anchor_id) {
+                    Some(camera_anchor) => camera_anchor,
+                    None => {
+                        warn_once(
+                            &mut self.reported_warnings,
+                            format!("{:?} is attached to {:?}, which has been destroyed, the camera will not be rendered", camera_id, anchor_id));
+                        continue;
+                    },
+                },
+                None => unimplemented!(),
+            };
 
-        let old = self.mesh_instances.insert(mesh_instance_id, mesh_instance);
-        assert!(old.is_none());
+            // The whole window is cleared before the first camera, later cameras only clear the
+            // depth buffer so that they draw on top of the earlier cameras.
+            if rendered_camera {
+                let _stopwatch = Stopwatch::new("Clearing depth buffer");
+                clear_screen(&self.context, scene_target, false, true, false);
+            }
+            rendered_camera = true;
 
-        mesh_instance_id
-    }
+            let _stopwatch = Stopwatch::new("Rendering camera");
 
-    fn get_mesh_instance(&self, id: MeshInstanceId) -> Option<&MeshInstance> {
-        self.mesh_instances.get(&id)
-    }
+            let context = &self.context;
+            let camera_timer = self.camera_timers
+                .entry(camera_id)
+                .or_insert_with(|| CameraTimer::new(context));
+            camera_timer.begin();
 
-    fn get_mesh_instance_mut(&mut self, id: MeshInstanceId) -> Option<&mut MeshInstance> {
-        self.mesh_instances.get_mut(&id)
-    }
+            // Gather the data for the enabled lights once per camera, since it doesn't depend on
+            // the mesh instance being drawn.
+            let (mut light_uniforms, shadow_lights) = {
+                let _stopwatch = Stopwatch::new("Gather lights");
 
-    fn register_anchor(&mut self, anchor: Anchor) -> AnchorId {
-        let anchor_id = self.anchor_counter.next();
+                let view_transform = camera_anchor.view_matrix();
+                let mut light_uniforms = Vec::with_capacity(self.lights.len());
+                let mut shadow_lights = Vec::new();
+                for (&light_id, light) in &self.lights {
+                    if !light.enabled() {
+                        continue;
+                    }
 
-        let old = self.anchors.insert(anchor_id, anchor);
-        assert!(old.is_none());
+                    let light_anchor = match light.anchor() {
+                        Some(anchor_id) => match self.world_transforms.get(anchor_id) {
+                            Some(anchor) => Some(anchor),
+                            None => {
+                                warn_once(
+                                    &mut self.reported_warnings,
+                                    format!("{:?} is attached to {:?}, which has been destroyed, the light will be skipped", light_id, anchor_id));
+                                continue;
+                            },
+                        },
+                        None => None,
+                    };
+
+                    // Lights that got a tile in the shadow atlas cast shadows.
+                    let has_tile = self.shadow_atlas
+                        .as_ref()
+                        .map_or(false, |atlas| atlas.tiles.contains_key(&(light_id, camera_id)));
+                    if has_tile {
+                        shadow_lights.push((light_id, light_uniforms.len()));
+                    }
 
-        anchor_id
-    }
+                    light_uniforms.push(LightUniforms::new(light, light_anchor, view_transform));
+                }
 
-    fn get_anchor(&self, anchor_id: AnchorId) -> Option<&Anchor> {
-        self.anchors.get(&anchor_id)
-    }
+                (light_uniforms, shadow_lights)
+            };
+            lights_processed += light_uniforms.len();
+            instances_culled += hidden_count;
+
+            // Render each shadow casting light's tile of the shadow atlas. The tiles cover the
+            // region around the camera so that nearby shadows are always visible.
+            if !shadow_lights.is_empty() {
+                let _stopwatch = Stopwatch::new("Shadow depth pass");
+
+                let atlas = self.shadow_atlas.as_mut().unwrap();
+                for &(light_id, index) in &shadow_lights {
+                    let light = &self.lights[&light_id];
+                    let tile = atlas.tiles.get_mut(&(light_id, camera_id)).unwrap();
+
+                    // Static lights snap the covered region to a grid, so that the camera can move
+                    // a little without invalidating the cached tile.
+                    let center = match light.shadow_update() {
+                        ShadowUpdate::Dynamic => camera_anchor.position(),
+                        ShadowUpdate::Static => snap_shadow_center(camera_anchor.position()),
+                    };
+                    let direction = light_uniforms[index].direction;
+                    let direction = Vector3::new(direction[0], direction[1], direction[2]);
+                    let light_space_transform = directional_shadow_transform(direction, center);
+
+                    let cache_key = ShadowCacheKey {
+                        light_space_transform: light_space_transform,
+                        layers: camera_layers,
+                    };
+                    let reuse = light.shadow_update() == ShadowUpdate::Static && tile.cached == Some(cache_key);
+                    if reuse {
+                        shadow_maps_reused += 1;
+                    } else {
+                        shadow_maps_rendered += 1;
+                        tile.cached = Some(cache_key);
+                        tile.casters.clear();
+
+                        let (x, y, resolution) = (tile.x as i32, tile.y as i32, tile.resolution as i32);
+                        atlas.framebuffer.clear_region(x, y, resolution, resolution, false, true, false);
+
+                        for &(mesh_instance_id, submesh) in &self.draw_list {
+                            let mesh_instance = &self.mesh_instances[&mesh_instance_id];
+                            if !mesh_instance.casts_shadows()
+                                || !mesh_instance.visible()
+                                || mesh_instance.layers() & camera_layers == 0
+                            {
+                                continue;
+                            }
+
+                            // Dangling anchors and meshes are reported by the main pass.
+                            let anchor = match mesh_instance.anchor().and_then(|anchor_id| self.world_transforms.get(anchor_id)) {
+                                Some(anchor) => anchor,
+                                None => continue,
+                            };
+                            let (mesh_data, submesh_range) = match self.meshes.get(mesh_instance.mesh()) {
+                                Some(mesh_data) => match mesh_data.submeshes.get(submesh) {
+                                    Some(submesh_range) => (mesh_data, submesh_range.clone()),
+                                    None => continue,
+                                },
+                                None => continue,
+                            };
+
+                            // Lines and points don't cover any area, so they can't cast shadows.
+                            if mesh_data.topology != Topology::Triangles {
+                                continue;
+                            }
+
+                            // Casters outside of the tile's region are left out, so that they can
+                            // move around without invalidating the tile.
+                            let bounds = mesh_data.bounding_sphere.transform(anchor.matrix());
+                            if !overlaps_shadow_region(light_space_transform, bounds) {
+                                continue;
+                            }
+                            tile.casters.insert(mesh_instance_id);
+
+                            triangles += (submesh_range.end - submesh_range.start) / 3;
+
+                            let light_space_model_transform = light_space_transform * anchor.matrix();
+                            DrawBuilder::new(&self.context, &mesh_data.vertex_array, DrawMode::Triangles)
+                                .index_range(submesh_range)
+                                .program(&self.shadow_program)
+                                .framebuffer(&atlas.framebuffer)
+                                .viewport(x, y, resolution, resolution)
+                                .cull(Face::Back)
+                                .depth_test(Comparison::Less)
+                                .uniform(
+                                    "light_space_model_transform",
+                                    GlMatrix {
+                                        data: light_space_model_transform.raw_data(),
+                                        transpose: true,
+                                    },
+                                )
+                                .draw();
+
+                            stats.draw_calls += 1;
+                            if last_program != Some(BoundProgram::Shadow) {
+                                stats.program_switches += 1;
+                                last_program = Some(BoundProgram::Shadow);
+                            }
+                        }
+                    }
 
-    fn get_anchor_mut(&mut self, anchor_id: AnchorId) -> Option<&mut Anchor> {
-        self.anchors.get_mut(&anchor_id)
-    }
+                    let light = &mut light_uniforms[index];
+                    light.has_shadow = 1;
+                    light.shadow_transform = tile.atlas_transform(atlas.size) * light_space_transform;
+                    light.shadow_region = tile.region(atlas.size);
+                }
+            }
 
-    fn register_camera(&mut self, camera: Camera) -> CameraId {
-        let camera_id = self.camera_counter.next();
+            // The lights that reach the mesh instance being drawn, reused between draws.
+            let mut instance_lights: Vec<&LightUniforms> = Vec::with_capacity(light_uniforms.len());
+
+            for draw_item in &draw_items {
+                // Instanced draws use the first mesh instance in the group for everything that
+                // isn't per-instance, since the rest of the group has the same mesh and material.
+                let (mesh_instance_id, submesh, instances) = match *draw_item {
+                    DrawItem::Single { mesh_instance_id, submesh } => (mesh_instance_id, submesh, None),
+                    DrawItem::Instanced { mesh_instance_id, submesh, first_instance, count } => {
+                        (mesh_instance_id, submesh, Some((first_instance, count)))
+                    },
+                };
+
+                // Groups share their layers, so the whole draw item is skipped if the camera
+                // can't see the first mesh instance.
+                let mesh_instance = &self.mesh_instances[&mesh_instance_id];
+                if mesh_instance.layers() & camera_layers == 0 {
+                    instances_culled += instances.map_or(1, |(_, count)| count);
+                    continue;
+                }
+
+                let anchor = match mesh_instance.anchor() {
+                    Some(anchor_id) => match self.world_transforms.get(anchor_id) {
+                        Some(anchor) => anchor,
+                        None => {
+                            warn_once(
+                                &mut self.reported_warnings,
+                                format!("{:?} is attached to {:?}, which has been destroyed, the mesh instance will not be drawn", mesh_instance_id, anchor_id));
+                            continue;
+                        },
+                    },
+                    None => continue,
+                };
+
+                let (model_transform, normal_transform) = match mesh_instance.billboard() {
+                    BillboardMode::None => (anchor.matrix(), anchor.normal_matrix()),
+                    billboard => billboard_transforms(billboard, anchor, camera_anchor),
+                };
+
+                let mesh_data = match self.meshes.get(mesh_instance.mesh()) {
+                    Some(mesh_data) => mesh_data,
+                    None => {
+                        warn_once(
+                            &mut self.reported_warnings,
+                            format!("{:?} uses {:?}, which has been unregistered, the mesh instance will not be drawn", mesh_instance_id, mesh_instance.mesh()));
+                        continue;
+                    },
+                };
+
+                // The draw list is rebuilt whenever a mesh changes, so the submesh always exists.
+                let submesh_range = mesh_data.submeshes[submesh].clone();
+
+                let _stopwatch = Stopwatch::new("Drawing mesh");
+
+                // Calculate the various transforms needed for rendering.
+                let view_transform = camera_anchor.view_matrix();
+                let model_view_transform = view_transform * model_transform;
+                let projection_transform = camera.projection_matrix();
+                let model_view_projection = projection_transform * model_view_transform;
+
+                // The view transform is only rotation and translation, so its inverse-transpose is
+                // its own rotation part. Instanced draws calculate the view normal transform for
+                // each instance from this.
+                let view_direction_transform = Matrix3::from(view_transform);
+                let view_normal_transform = view_direction_transform * normal_transform;
+
+                let material = mesh_instance.submesh_material(submesh);
+
+                // Only send the mesh instance the point lights whose radius reaches it. Instanced
+                // groups are spread out, and skinned and vertex animated meshes move their
+                // vertices on the GPU, so they don't have bounds to check and get every light.
+                // Lines and points are drawn unlit, so they don't get any lights.
+                instance_lights.clear();
+                if mesh_data.topology == Topology::Triangles {
+                    let bounds = match instances {
+                        None if !mesh_data.skinned && mesh_data.vertex_animation.is_none() => {
+                            Some(mesh_data.bounding_sphere.transform(model_transform))
+                        },
+                        _ => None,
+                    };
+                    instance_lights.extend(light_uniforms.iter().filter(|light| light.reaches(bounds)));
+                }
+                lit_draws += 1;
+                draw_lights += instance_lights.len();
+
+                // Upload the pose of a skinned mesh. The pose is padded with identity transforms
+                // so that the whole uniform block is backed by the buffer, which also leaves
+                // vertices using bones that aren't in the pose where they are.
+                let skinned = instances.is_none()
+                    && mesh_data.skinned
+                    && self.skinned_programs.contains_key(material.shader());
+                if skinned {
+                    let _stopwatch = Stopwatch::new("Bone uniforms");
+
+                    let bone_transforms = mesh_instance.pose().map_or(&[][..], Pose::bone_transforms);
+                    self.bone_data.clear();
+                    for bone in 0..MAX_BONES {
+                        let bone_transform = bone_transforms.get(bone).cloned().unwrap_or_else(Matrix4::identity);
+
+                        // GLSL matrices are column-major, so store them transposed.
+                        self.bone_data.extend_from_slice(bone_transform.transpose().raw_data());
+                    }
+                    self.bone_buffer.set_data_f32(&self.bone_data);
+                }
+
+                let mut draw_builder = {
+                    let _stopwatch = Stopwatch::new("Initialize DrawBuilder");
+
+                    let program = match instances {
+                        Some(_) => &self.instanced_programs[material.shader()],
+                        None if skinned => &self.skinned_programs[material.shader()],
+                        None => self
+                            .programs
+                            .get(material.shader())
+                            .expect("Material is using a shader that does not exist"),
+                    };
+
+                    // Set the shader to use.
+                    let mut draw_builder = screen_draw(
+                        &self.context,
+                        scene_target,
+                        &mesh_data.vertex_array,
+                        mesh_data.topology.into(),
+                    );
+                    draw_builder
+                    .index_range(submesh_range.clone())
+                    .program(program)
+                    .polygon_mode(polygon_mode)
+                    .cull(Face::Back)
+                    .depth_test(Comparison::Less)
+                    .viewport(viewport_x, viewport_y, viewport_width, viewport_height);
+
+                    if let Some((first_instance, count)) = instances {
+                        for &(name, location, _, _) in &INSTANCE_ATTRIBS {
+                            mesh_data.vertex_array.map_instance_attrib_location(
+                                &self.instance_buffer,
+                                name,
+                                location,
+                                first_instance);
+                        }
+
+                        draw_builder.instances(count);
+                    }
+
+                    if skinned {
+                        draw_builder.uniform_block(BONES_BINDING, &self.bone_buffer);
+                    }
+
+                    draw_builder
+                };
+
+                // Set uniform transforms.
+                {
+                    let _stopwatch = Stopwatch::new("Transform uniforms");
+
+                    draw_builder
+                    .uniform(
+                        "model_transform",
+                        GlMatrix {
+                            data: model_transform.raw_data(),
+                            transpose: true,
+                        },
+                    )
+                    .uniform(
+                        "normal_transform",
+                        GlMatrix {
+                            data: normal_transform.raw_data(),
+                            transpose: true,
+                        },
+                    )
+                    .uniform(
+                        "view_normal_transform",
+                        GlMatrix {
+                            data: view_normal_transform.raw_data(),
+                            transpose: true,
+                        },
+                    )
+                    .uniform(
+                        "view_direction_transform",
+                        GlMatrix {
+                            data: view_direction_transform.raw_data(),
+                            transpose: true,
+                        },
+                    )
+                    .uniform(
+                        "view_transform",
+                        GlMatrix {
+                            data: view_transform.raw_data(),
+                            transpose: true,
+                        },
+                    )
+                    .uniform(
+                        "model_view_transform",
+                        GlMatrix {
+                            data: model_view_transform.raw_data(),
+                            transpose: true,
+                        },
+                    )
+                    .uniform(
+                        "projection_transform",
+                        GlMatrix {
+                            data: projection_transform.raw_data(),
+                            transpose: true,
+                        },
+                    )
+                    .uniform(
+                        "model_view_projection",
+                        GlMatrix {
+                            data: model_view_projection.raw_data(),
+                            transpose: true,
+                        },
+                    );
+                }
+
+                // Apply material attributes.
+                {
+                    let _stopwatch = Stopwatch::new("Material uniforms");
+
+                    // Set uniform colors.
+                    set_ambient_uniforms(&mut draw_builder, self.ambient);
+                    set_fog_uniforms(&mut draw_builder, self.fog);
+
+                    // Other uniforms.
+                    draw_builder.uniform("camera_position", *camera_anchor.position().as_array());
+
+                    for (name, property) in material.properties() {
+                        set_property_uniform(&mut draw_builder, name, property, &self.textures, &self.default_texture);
+                    }
+
+                    // Overrides are applied last so that they replace the material's values.
+                    for (name, property) in mesh_instance.uniform_overrides() {
+                        set_property_uniform(&mut draw_builder, name, property, &self.textures, &self.default_texture);
+                    }
+                }
+
+                // Set vertex animation data.
+                match mesh_data.vertex_animation {
+                    Some(ref vertex_animation) => {
+                        let _stopwatch = Stopwatch::new("Vertex animation uniforms");
+
+                        let vat_time = match mesh_instance.vat_playback() {
+                            VatPlayback::Manual(time) => time,
+                            VatPlayback::Auto { frames_per_second } => elapsed_seconds * frames_per_second,
+                        };
+
+                        // `Some(None)` means the mesh doesn't use a normals texture, while `None`
+                        // means the normals texture has been unregistered.
+                        let positions = self.textures.get(&vertex_animation.positions);
+                        let normals = match vertex_animation.normals {
+                            Some(ref normals) => self.textures.get(normals).map(Some),
+                            None => Some(None),
+                        };
+
+                        match (positions, normals) {
+                            (Some(positions), Some(normals)) => {
+                                draw_builder
+                                .uniform("vat_enabled", 1)
+                                .uniform("vat_time", vat_time)
+                                .uniform("vat_positions", positions);
+
+                                match normals {
+                                    Some(normals) => {
+                                        draw_builder
+                                        .uniform("vat_has_normals", 1)
+                                        .uniform("vat_normals", normals);
+                                    },
+                                    None => {
+                                        draw_builder.uniform("vat_has_normals", 0);
+                                    },
+                                }
+                            },
+                            _ => {
+                                warn_once(
+                                    &mut self.reported_warnings,
+                                    format!("{:?} uses a vertex animation texture that has been unregistered, the mesh will not animate", mesh_instance.mesh()));
+                                draw_builder.uniform("vat_enabled", 0);
+                            },
+                        }
+                    },
+                    None => {
+                        draw_builder.uniform("vat_enabled", 0);
+                    },
+                }
+
+                // Set shadow data. The shadow map sampler always needs a depth texture, even if
+                // the mesh instance doesn't receive shadows. The transform and region of each
+                // light's tile are sent along with the rest of the light's uniforms.
+                match self.shadow_atlas.as_ref() {
+                    Some(atlas) if mesh_instance.receives_shadows() => {
+                        draw_builder
+                            .uniform("shadow_enabled", 1)
+                            .uniform("shadow_map", &atlas.texture);
+                    },
+                    _ => {
+                        draw_builder
+                            .uniform("shadow_enabled", 0)
+                            .uniform("shadow_map", &self.default_shadow_map);
+                    },
+                }
+
+                // Every texture uniform is bound for each draw, counting overridden textures once.
+                let is_texture = |property: &MaterialProperty| match *property {
+                    MaterialProperty::Texture(_) => true,
+                    _ => false,
+                };
+                let shadow_texture_count = if self.shadow_programs.contains(material.shader()) { 1 } else { 0 };
+                let texture_count = shadow_texture_count + material
+                    .properties()
+                    .filter(|&(name, property)| {
+                        is_texture(property) && mesh_instance.get_uniform_override(name).is_none()
+                    })
+                    .count()
+                    + mesh_instance
+                        .uniform_overrides()
+                        .filter(|&(_, property)| is_texture(property))
+                        .count()
+                    + mesh_data.vertex_animation.as_ref().map(|vertex_animation| {
+                        1 + vertex_animation.normals.iter().count()
+                    }).unwrap_or(0);
+                let bound_program = match instances {
+                    Some(_) => BoundProgram::InstancedMaterial(*material.shader()),
+                    None if skinned => BoundProgram::SkinnedMaterial(*material.shader()),
+                    None => BoundProgram::Material(*material.shader()),
+                };
+                let draw_triangles = match mesh_data.topology {
+                    Topology::Triangles => (submesh_range.end - submesh_range.start) / 3
+                        * instances.map_or(1, |(_, count)| count),
+                    _ => 0,
+                };
+                let mut record_draw = |stats: &mut RenderStats| {
+                    stats.draw_calls += 1;
+                    triangles += draw_triangles;
+                    stats.texture_binds += texture_count;
+                    match instances {
+                        Some(_) => stats.instanced_draw_calls += 1,
+                        None => stats.individual_draw_calls += 1,
+                    }
+                    if last_program != Some(bound_program) {
+                        stats.program_switches += 1;
+                        last_program = Some(bound_program);
+                    }
+                };
+
+                if mesh_data.topology != Topology::Triangles {
+                    let _stopwatch = Stopwatch::new("Draw unlit");
+
+                    // Lines and points have no surface to light, so they're drawn once with full
+                    // ambient light and no other lights, which shows the material's unlit color.
+                    set_ambient_uniforms(&mut draw_builder, AmbientMode::Flat(Color::rgb(1.0, 1.0, 1.0)));
+                    if self.light_array_programs.contains(material.shader()) {
+                        set_light_arrays(&mut draw_builder, &self.light_uniform_names, &[]);
+                    } else {
+                        draw_builder.uniform("light_type", 0);
+                    }
+                    draw_builder.draw();
+                    record_draw(&mut stats);
+                } else if self.light_array_programs.contains(material.shader()) {
+                    let _stopwatch = Stopwatch::new("Draw with light arrays");
+
+                    // Draw the lights in batches of `MAX_LIGHTS`. The first batch is drawn without
+                    // blending so it overrides any objects behind it, and is drawn even if there
+                    // are no lights so that the ambient color is rendered.
+                    let mut batches = instance_lights.chunks(MAX_LIGHTS);
+                    let first_batch = batches.next().unwrap_or(&[]);
+                    set_light_arrays(&mut draw_builder, &self.light_uniform_names, first_batch);
+                    draw_builder.draw();
+                    record_draw(&mut stats);
+
+                    // Blend any remaining batches on top with the depth check set to less than or
+                    // equal. Ambient light and the fog color were already added by the first
+                    // batch. Wireframes are only drawn once, so skip the remaining batches.
+                    if self.debug_mode != DebugMode::Wireframe {
+                        draw_builder
+                        .depth_test(Comparison::LessThanOrEqual)
+                        .blend(SourceFactor::One, DestFactor::One)
+                        .uniform::<[f32; 4]>("fog_color", Color::rgb(0.0, 0.0, 0.0).into());
+                        set_ambient_uniforms(&mut draw_builder, AmbientMode::Flat(Color::rgb(0.0, 0.0, 0.0)));
+
+                        for batch in batches {
+                            set_light_arrays(&mut draw_builder, &self.light_uniform_names, batch);
+                            draw_builder.draw();
+                            record_draw(&mut stats);
+                        }
+                    }
+                } else {
+                    // Render first light without blending so it overrides any objects behind it.
+                    // We also render it with light strength 0 so it only renders ambient color.
+                    {
+                        let _stopwatch = Stopwatch::new("Draw (no lights)");
+
+                        draw_builder
+                        .uniform("light_type", 0)
+                        .draw();
+                        record_draw(&mut stats);
+                    }
+
+                    // Render the rest of the lights with blending on the the depth check set to
+                    // less than or equal. The fog color was already added by the first draw, so
+                    // the blended lights only fade out. Wireframes are only drawn once, so skip
+                    // the lights.
+                    if self.debug_mode != DebugMode::Wireframe {
+                        let _stopwatch = Stopwatch::new("Draw with lights");
+
+                        draw_builder
+                        .depth_test(Comparison::LessThanOrEqual)
+                        .blend(SourceFactor::One, DestFactor::One)
+                        .uniform::<[f32; 4]>("fog_color", Color::rgb(0.0, 0.0, 0.0).into());
+
+                        for light in &instance_lights {
+                            light.set_single(&mut draw_builder);
+
+                            // Draw the current light.
+                            draw_builder.draw();
+                            record_draw(&mut stats);
+                        }
+                    }
+                }
+
+                // Draw the mesh again on top of itself colored by its normals. Vertex animated and
+                // skinned meshes are skipped since the normals program doesn't animate the
+                // vertices, so the second draw wouldn't line up with the first.
+                if let (DebugMode::Normals, Some(normals_program), None, false) =
+                    (self.debug_mode, self.normals_program.as_ref(), mesh_data.vertex_animation.as_ref(), mesh_data.skinned)
+                {
+                    let _stopwatch = Stopwatch::new("Draw normals");
+
+                    screen_draw(&self.context, scene_target, &mesh_data.vertex_array, mesh_data.topology.into())
+                    .index_range(submesh_range)
+                    .program(normals_program)
+                    .cull(Face::Back)
+                    .depth_test(Comparison::LessThanOrEqual)
+                    .viewport(viewport_x, viewport_y, viewport_width, viewport_height)
+                    .uniform(
+                        "model_view_projection",
+                        GlMatrix {
+                            data: model_view_projection.raw_data(),
+                            transpose: true,
+                        },
+                    )
+                    .uniform(
+                        "normal_transform",
+                        GlMatrix {
+                            data: normal_transform.raw_data(),
+                            transpose: true,
+                        },
+                    )
+                    .draw();
+
+                    stats.draw_calls += 1;
+                    triangles += draw_triangles;
+                    if last_program != Some(BoundProgram::Normals) {
+                        stats.program_switches += 1;
+                        last_program = Some(BoundProgram::Normals);
+                    }
+                }
+            }
+
+            // Draw the skybox after the opaque geometry so that only the pixels that weren't
+            // covered by a mesh are shaded.
+            if let Some(skybox) = self.skybox {
+                match self.cube_textures.get(&skybox) {
+                    Some(skybox_texture) => {
+                        let _stopwatch = Stopwatch::new("Draw skybox");
+
+                        // Strip the translation from the view transform so that the sky stays
+                        // centered on the camera and appears infinitely far away.
+                        let mut view_transform = camera_anchor.view_matrix();
+                        for row in 0..3 {
+                            view_transform[row][3] = 0.0;
+                        }
+                        let skybox_transform = camera.projection_matrix() * view_transform;
+
+                        // The sky is drawn on the far plane, so test against the depth buffer with
+                        // less than or equal and don't write to it.
+                        screen_draw(&self.context, scene_target, &self.skybox_vertex_array, DrawMode::Triangles)
+                        .program(&self.skybox_program)
+                        .polygon_mode(polygon_mode)
+                        .depth_test(Comparison::LessThanOrEqual)
+                        .depth_write(false)
+                        .viewport(viewport_x, viewport_y, viewport_width, viewport_height)
+                        .uniform(
+                            "skybox_transform",
+                            GlMatrix {
+                                data: skybox_transform.raw_data(),
+                                transpose: true,
+                            },
+                        )
+                        .uniform("skybox", skybox_texture)
+                        .draw();
+
+                        stats.draw_calls += 1;
+                        stats.texture_binds += 1;
+                        triangles += SKYBOX_INDICES.len() / 3;
+                        if last_program != Some(BoundProgram::Skybox) {
+                            stats.program_switches += 1;
+                            last_program = Some(BoundProgram::Skybox);
+                        }
+                    },
+                    None => warn_once(
+                        &mut self.reported_warnings,
+                        format!("The skybox uses {:?}, which isn't a registered cube map, the skybox will not be drawn", skybox)),
+                }
+            }
+
+            camera_timer.end();
+        }
+
+        // Run the post effects over what the cameras rendered, each effect reading the output of
+        // the one before it. Sprites and text are drawn afterwards so that the effects don't
+        // apply to them.
+        if let Some(ref targets) = self.post_targets {
+            let _stopwatch = Stopwatch::new("Post effects");
+
+            let mut effects = Vec::with_capacity(self.post_effects.len());
+            for material_id in &self.post_effects {
+                match self.materials.get(material_id) {
+                    Some(material) => effects.push(material),
+                    None => warn_once(
+                        &mut self.reported_warnings,
+                        format!("{:?} is used as a post effect but isn't registered, the effect will be skipped", material_id)),
+                }
+            }
+
+            // If none of the effects can be drawn, the scene is copied into the frame as it is.
+            if effects.is_empty() {
+                targets.scene.blit_to(screen_target, width, height, ClearBufferMask::Color);
+            }
+
+            // The triangle's positions are already in clip space.
+            let identity = Matrix4::identity();
+
+            // The scene is drawn into the first color target, then the effects alternate between
+            // the two targets so that no effect reads the texture it's drawing into.
+            let mut source = 0;
+            for (index, material) in effects.iter().enumerate() {
+                let program = self.programs
+                    .get(material.shader())
+                    .expect("Material is using a shader that does not exist");
+
+                let target = if index + 1 == effects.len() {
+                    screen_target
+                } else {
+                    Some(&targets.color_framebuffers[1 - source])
+                };
+
+                let mut draw_builder = screen_draw(&self.context, target, &self.post_vertex_array, DrawMode::Triangles);
+                draw_builder
+                .program(program)
+                .depth_write(false)
+                .viewport(0, 0, width, height)
+                .uniform(
+                    "model_view_projection",
+                    GlMatrix {
+                        data: identity.raw_data(),
+                        transpose: true,
+                    },
+                )
+                .uniform("scene_color", &targets.colors[source])
+                .uniform("scene_depth", &targets.depth)
+                .uniform("screen_size", [width as f32, height as f32])
+                .uniform("time", elapsed_seconds);
+
+                for (name, property) in material.properties() {
+                    set_property_uniform(&mut draw_builder, name, property, &self.textures, &self.default_texture);
+                }
+
+                draw_builder.draw();
+
+                stats.draw_calls += 1;
+                stats.texture_binds += 2;
+                triangles += 1;
+                let bound_program = BoundProgram::Material(*material.shader());
+                if last_program != Some(bound_program) {
+                    stats.program_switches += 1;
+                    last_program = Some(bound_program);
+                }
+
+                source = 1 - source;
+            }
+        }
+
+        // Draw the sprites over the whole window, on top of what every camera rendered. Sprites are
+        // sorted by texture so that the sprites sharing a texture are drawn with one draw call.
+        if !self.sprites.is_empty() {
+            let _stopwatch = Stopwatch::new("Draw sprites");
+
+            let mut sprites = self.sprites.iter().collect::<Vec<_>>();
+            sprites.sort_by_key(|&(&sprite_id, sprite)| (sprite.texture(), sprite_id));
+
+            self.sprite_vertices.clear();
+            for &(_, sprite) in &sprites {
+                let (min, max) = sprite.corners();
+                let uv = sprite.uv_rect;
+                let color = sprite.color;
+
+                // The top of the sprite shows the top of the uv rect.
+                let corners = [
+                    (min.x, min.y, uv.x, uv.y + uv.height),
+                    (max.x, min.y, uv.x + uv.width, uv.y + uv.height),
+                    (max.x, max.y, uv.x + uv.width, uv.y),
+                    (min.x, max.y, uv.x, uv.y),
+                ];
+                for &(x, y, u, v) in &corners {
+                    self.sprite_vertices.extend_from_slice(&[x, y, u, v, color.r, color.g, color.b, color.a]);
+                }
+            }
+
+            if sprites.len() > self.sprite_capacity {
+                self.sprite_capacity = sprites.len().next_power_of_two();
+                self.sprite_vertex_array
+                    .index_buffer_mut()
+                    .expect("Sprite index buffer is shared")
+                    .set_data_u32(&sprite_indices(self.sprite_capacity));
+            }
+
+            {
+                let vertex_buffer = self.sprite_vertex_array
+                    .vertex_buffer_mut()
+                    .expect("Sprite vertex buffer is shared");
+                vertex_buffer.set_data_f32(&self.sprite_vertices);
+                set_overlay_attribs(vertex_buffer);
+            }
+
+            let mut start = 0;
+            while start < sprites.len() {
+                let texture = sprites[start].1.texture();
+                let count = sprites[start..]
+                    .iter()
+                    .take_while(|&&(_, sprite)| sprite.texture() == texture)
+                    .count();
+
+                // Sprites with an unregistered texture are drawn the same as materials are.
+                let gl_texture = self.textures.get(&texture).unwrap_or(&self.default_texture);
+
+                screen_draw(&self.context, screen_target, &self.sprite_vertex_array, DrawMode::Triangles)
+                .index_range(start * 6..(start + count) * 6)
+                .program(&self.overlay_program)
+                .blend(SourceFactor::SourceAlpha, DestFactor::OneMinusSourceAlpha)
+                .depth_write(false)
+                .viewport(0, 0, width, height)
+                .uniform("screen_size", [width as f32, height as f32])
+                .uniform("overlay_texture", gl_texture)
+                .draw();
+
+                stats.draw_calls += 1;
+                stats.texture_binds += 1;
+                triangles += count * 2;
+                if last_program != Some(BoundProgram::Overlay) {
+                    stats.program_switches += 1;
+                    last_program = Some(BoundProgram::Overlay);
+                }
+
+                start += count;
+            }
+        }
+
+        // Draw the text queued with `draw_text()` over the whole window, on top of the scene and
+        // the sprites.
+        if !self.text_vertices.is_empty() {
+            let _stopwatch = Stopwatch::new("Draw text");
+
+            {
+                let vertex_buffer = self.text_vertex_array
+                    .vertex_buffer_mut()
+                    .expect("Text vertex buffer is shared");
+                vertex_buffer.set_data_f32(&self.text_vertices);
+
+                // Update the vertex count for the new data.
+                set_overlay_attribs(vertex_buffer);
+            }
+
+            screen_draw(&self.context, screen_target, &self.text_vertex_array, DrawMode::Triangles)
+            .program(&self.overlay_program)
+            .blend(SourceFactor::SourceAlpha, DestFactor::OneMinusSourceAlpha)
+            .depth_write(false)
+            .viewport(0, 0, width, height)
+            .uniform("screen_size", [width as f32, height as f32])
+            .uniform("overlay_texture", &self.font_texture)
+            .draw();
+
+            stats.draw_calls += 1;
+            stats.texture_binds += 1;
+            triangles += self.text_vertices.len() / OVERLAY_VERTEX_LEN / 3;
+            if last_program != Some(BoundProgram::Overlay) {
+                stats.program_switches += 1;
+                last_program = Some(BoundProgram::Overlay);
+            }
+
+            // Keep the allocation for next frame's text.
+            self.text_vertices.clear();
+        }
+
+        {
+            let _stopwatch = Stopwatch::new("Swap buffers");
+            self.context.swap_buffers();
+        }
+
+        // Reset change tracking so that next frame only sees anchors that moved since now, which is
+        // how static shadow maps know when they need to be re-rendered.
+        for anchor in self.anchors.values_mut() {
+            anchor.clear_changed();
+        }
+
+        stats.mesh_bytes = self.meshes.values().map(|mesh_data| mesh_data.byte_len).sum();
+
+        self.frame_stats = FrameStats {
+            render: stats,
+            triangles: triangles,
+            instances_culled: instances_culled,
+            lights_processed: lights_processed,
+            lit_draws: lit_draws,
+            draw_lights: draw_lights,
+            shadow_maps_rendered: shadow_maps_rendered,
+            shadow_maps_reused: shadow_maps_reused,
+            cpu_time: draw_start.elapsed(),
+        };
+
+        stats
+    }
+
+    fn frame_stats(&self) -> FrameStats {
+        self.frame_stats
+    }
+
+    fn default_material(&self) -> Material {
+        self.default_material.clone()
+    }
+
+    fn build_material(&mut self, source: MaterialSource) -> Result<Material, BuildMaterialError> {
+        // COMPILE SHADER SOURCE
+        // =====================
+
+        let (program, instanced_program, skinned_program) = self.compile_material(&source)?;
+        let program_id = self.shader_counter.next();
+        self.insert_program(program_id, program, instanced_program, skinned_program);
+
+        // BUILD MATERIAL OBJECT
+        // =====================
+
+        let mut material = Material::new(program_id);
+
+        // Add the properties from the material declaration.
+        for property in source.properties {
+            set_default_property(&mut material, property);
+        }
+
+        Ok(material)
+    }
+
+    fn rebuild_material(&mut self, material: &mut Material, source: MaterialSource) -> Result<(), BuildMaterialError> {
+        // Compile before touching anything so that the old programs are kept if the new source
+        // doesn't build.
+        let (program, instanced_program, skinned_program) = self.compile_material(&source)?;
+        let program_id = *material.shader();
+        self.insert_program(program_id, program, instanced_program, skinned_program);
+
+        // Drop the properties that are no longer declared.
+        let removed = material
+            .properties()
+            .map(|(name, _)| name.clone())
+            .filter(|name| !source.properties.iter().any(|property| property.name == *name))
+            .collect::<Vec<_>>();
+        for name in removed {
+            material.clear_property(&name);
+        }
+
+        // Keep the values of properties that still have the same type, everything else gets its
+        // default value.
+        for property in source.properties {
+            let keep = match (material.get_property(&property.name), property.property_type) {
+                (Some(&MaterialProperty::Color(_)), PropertyType::Color) => true,
+                (Some(&MaterialProperty::Texture(_)), PropertyType::Texture2d) => true,
+                (Some(&MaterialProperty::f32(_)), PropertyType::f32) => true,
+                (Some(&MaterialProperty::Vector2(_)), PropertyType::Vector2) => true,
+                (Some(&MaterialProperty::Vector3(_)), PropertyType::Vector3) => true,
+                (Some(&MaterialProperty::Vector4(_)), PropertyType::Vector4) => true,
+                (Some(&MaterialProperty::Matrix4(_)), PropertyType::Matrix4) => true,
+                _ => false,
+            };
+
+            if !keep {
+                set_default_property(material, property);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn register_material(&mut self, material: Material) -> MaterialId {
+        let material_id = self.material_counter.next();
+
+        let old = self.materials.insert(material_id, material);
+        assert!(old.is_none());
+
+        material_id
+    }
+
+    fn get_material(&self, material_id: MaterialId) -> Option<&Material> {
+        self.materials.get(&material_id)
+    }
+
+    fn materials(&self) -> RegisteredIter<MaterialId, Material> {
+        Box::new(self.materials.iter().map(|(&id, material)| (id, material)))
+    }
+
+    fn register_mesh(&mut self, mesh: &Mesh) -> Result<GpuMesh, MeshError> {
+        mesh.validate()?;
+        warn_unread_texcoords(&mut self.reported_warnings, mesh);
+
+        let mesh_id = self.mesh_counter.next();
+
+        // Generate array buffer.
+        let mut vertex_buffer = VertexBuffer::new(&self.context);
+        vertex_buffer.set_data_f32(mesh.vertex_data());
+        set_vertex_attribs(&mut vertex_buffer, mesh);
+
+        let mut index_buffer = IndexBuffer::new(&self.context);
+        let index_byte_len = set_index_data(&mut index_buffer, mesh.indices());
+
+        // Label the GL objects with the mesh id so that they can be identified in frame captures.
+        vertex_buffer.set_debug_label(&format!("{:?} vertices", mesh_id));
+        index_buffer.set_debug_label(&format!("{:?} indices", mesh_id));
+
+        let vertex_array = VertexArray::with_index_buffer(
+            &self.context,
+            vertex_buffer,
+            index_buffer,
+        );
+
+        map_attrib_locations(&vertex_array, mesh);
+        vertex_array.set_debug_label(&format!("{:?}", mesh_id));
+
+        self.meshes.insert(
+            mesh_id,
+            MeshData {
+                vertex_array: vertex_array,
+                position_attribute: mesh.position(),
+                normal_attribute: mesh.normal(),
+                color_attribute: mesh.color(),
+                tangent_attribute: mesh.tangent(),
+                uv_attributes: mesh.texcoord().to_vec(),
+                element_count: mesh.indices().len(),
+                submeshes: mesh.submeshes().to_vec(),
+                topology: mesh.topology(),
+                skinned: mesh.is_skinned(),
+                bounding_sphere: mesh.bounding_sphere(),
+                byte_len: mesh.vertex_data().len() * 4 + index_byte_len,
+                vertex_animation: None,
+            });
+
+        // Mesh instances that were registered before the mesh only have one draw list entry.
+        self.draw_list_dirty = true;
+
+        Ok(mesh_id)
+    }
+
+    fn update_mesh(&mut self, mesh_id: GpuMesh, mesh: &Mesh) -> Result<(), MeshError> {
+        let mesh_data = match self.meshes.get_mut(&mesh_id) {
+            Some(mesh_data) => mesh_data,
+            None => return Err(MeshError::UnregisteredMesh { mesh: mesh_id }),
+        };
+        mesh.validate()?;
+        warn_unread_texcoords(&mut self.reported_warnings, mesh);
+
+        {
+            let vertex_buffer = mesh_data.vertex_array
+                .vertex_buffer_mut()
+                .expect("Mesh vertex buffer is shared with another vertex array");
+            vertex_buffer.set_data_f32(mesh.vertex_data());
+            vertex_buffer.clear_attribs();
+            set_vertex_attribs(vertex_buffer, mesh);
+        }
+
+        let index_byte_len = set_index_data(
+            mesh_data.vertex_array
+                .index_buffer_mut()
+                .expect("Mesh index buffer is shared with another vertex array"),
+            mesh.indices());
+
+        // The attribute pointers are stored in the vertex array, so they have to be set again in
+        // case the layout of the vertex data changed.
+        map_attrib_locations(&mesh_data.vertex_array, mesh);
+
+        mesh_data.position_attribute = mesh.position();
+        mesh_data.normal_attribute = mesh.normal();
+        mesh_data.color_attribute = mesh.color();
+        mesh_data.tangent_attribute = mesh.tangent();
+        mesh_data.uv_attributes = mesh.texcoord().to_vec();
+        mesh_data.element_count = mesh.indices().len();
+        mesh_data.submeshes = mesh.submeshes().to_vec();
+        mesh_data.topology = mesh.topology();
+        mesh_data.skinned = mesh.is_skinned();
+        mesh_data.bounding_sphere = mesh.bounding_sphere();
+        mesh_data.byte_len = mesh.vertex_data().len() * 4 + index_byte_len;
+
+        // The draw list has an entry for each submesh, which may have changed.
+        self.draw_list_dirty = true;
+
+        Ok(())
+    }
+
+    fn unregister_mesh(&mut self, mesh: GpuMesh) {
+        // Mesh instances using the mesh are no longer drawn, which also removes them from the
+        // shadow maps.
+        if self.meshes.remove(&mesh).is_some() {
+            self.draw_list_dirty = true;
+        }
+    }
+
+    fn register_vat_mesh(
+        &mut self,
+        mesh: &Mesh,
+        positions: GpuTexture,
+        normals: Option<GpuTexture>,
+    ) -> Result<GpuMesh, MeshError> {
+        assert!(self.textures.contains_key(&positions), "No such texture exists for VAT positions");
+        if let Some(ref normals) = normals {
+            assert!(self.textures.contains_key(normals), "No such texture exists for VAT normals");
+        }
+
+        let required_units = if normals.is_some() { 2 } else { 1 };
+        let max_vertex_units = self.context.limits().max_vertex_texture_image_units;
+        if max_vertex_units < required_units {
+            return Err(MeshError::VertexTexturesUnsupported {
+                required: required_units,
+                supported: max_vertex_units,
+            });
+        }
+
+        let mesh_id = self.register_mesh(mesh)?;
+        self.meshes
+            .get_mut(&mesh_id)
+            .expect("Mesh data does not exist for mesh id")
+            .vertex_animation = Some(VertexAnimation {
+                positions: positions,
+                normals: normals,
+            });
+
+        Ok(mesh_id)
+    }
+
+    fn register_texture(&mut self, texture: &Texture2d) -> GpuTexture {
+        let gl_texture = upload_texture(&self.context, texture, self.gamma_correct);
+
+        // Register the texture internally.
+        let texture_id = self.texture_counter.next();
+        gl_texture.set_debug_label(&format!("{:?}", texture_id));
+
+        let old = self.textures.insert(texture_id, gl_texture);
+        assert!(old.is_none());
+
+        texture_id
+    }
+
+    fn register_texture_cube(&mut self, faces: &[Texture2d; 6]) -> GpuTexture {
+        let (format, internal_format) = texture_formats(&faces[0], self.gamma_correct);
+        for face in &faces[1..] {
+            assert!(
+                face.format() == faces[0].format(),
+                "Cube map faces must all have the same format, expected: {:?}, actual: {:?}",
+                faces[0].format(),
+                face.format());
+            assert!(
+                face.filtering() == faces[0].filtering() && face.wrap() == faces[0].wrap(),
+                "Cube map faces must all have the same filtering and wrap settings");
+        }
+
+        let texture_result = match faces[0].data() {
+            &TextureData::f32(_) => GlTextureCube::new(
+                &self.context,
+                format,
+                internal_format,
+                &face_images(faces, |data| match *data {
+                    TextureData::f32(ref data) => Some(&**data),
+                    _ => None,
+                })),
+            &TextureData::u8(_) => GlTextureCube::new(
+                &self.context,
+                format,
+                internal_format,
+                &face_images(faces, |data| match *data {
+                    TextureData::u8(ref data) => Some(&**data),
+                    _ => None,
+                })),
+            &TextureData::u8x3(_) => GlTextureCube::new(
+                &self.context,
+                format,
+                internal_format,
+                &face_images(faces, |data| match *data {
+                    TextureData::u8x3(ref data) => Some(&**data),
+                    _ => None,
+                })),
+            &TextureData::u8x4(_) => GlTextureCube::new(
+                &self.context,
+                format,
+                internal_format,
+                &face_images(faces, |data| match *data {
+                    TextureData::u8x4(ref data) => Some(&**data),
+                    _ => None,
+                })),
+        };
+        let mut gl_texture = texture_result.expect("Unable to send cube map to GPU");
+
+        let (min_filter, mag_filter) = faces[0].filtering();
+        gl_texture.set_filtering(min_filter.into(), mag_filter.into());
+
+        let (wrap_s, wrap_t) = faces[0].wrap();
+        gl_texture.set_wrap(wrap_s.into(), wrap_t.into());
+
+        let texture_id = self.texture_counter.next();
+        gl_texture.set_debug_label(&format!("{:?}", texture_id));
+
+        let old = self.cube_textures.insert(texture_id, gl_texture);
+        assert!(old.is_none());
+
+        texture_id
+    }
+
+    fn unregister_texture(&mut self, texture: GpuTexture) {
+        self.textures.remove(&texture);
+        self.cube_textures.remove(&texture);
+    }
+
+    fn register_mesh_instance(&mut self, mesh_instance: MeshInstance) -> MeshInstanceId {
+        let mesh_instance_id = self.mesh_instance_counter.next();
+
+        let old = self.mesh_instances.insert(mesh_instance_id, mesh_instance);
+        assert!(old.is_none());
+        self.draw_list_dirty = true;
+
+        mesh_instance_id
+    }
+
+    fn get_mesh_instance(&self, id: MeshInstanceId) -> Option<&MeshInstance> {
+        self.mesh_instances.get(&id)
+    }
+
+    fn get_mesh_instance_mut(&mut self, id: MeshInstanceId) -> Option<&mut MeshInstance> {
+        // The caller may change the mesh instance's mesh or material.
+        self.draw_list_dirty = true;
+        self.mesh_instances.get_mut(&id)
+    }
+
+    fn destroy_mesh_instance(&mut self, id: MeshInstanceId) {
+        if self.mesh_instances.remove(&id).is_some() {
+            self.draw_list_dirty = true;
+        }
+    }
+
+    fn mesh_instances(&self) -> RegisteredIter<MeshInstanceId, MeshInstance> {
+        Box::new(self.mesh_instances.iter().map(|(&id, mesh_instance)| (id, mesh_instance)))
+    }
+
+    fn mesh_instances_mut(&mut self) -> RegisteredIterMut<MeshInstanceId, MeshInstance> {
+        // The caller may change any mesh instance's mesh or material.
+        self.draw_list_dirty = true;
+        Box::new(self.mesh_instances.iter_mut().map(|(&id, mesh_instance)| (id, mesh_instance)))
+    }
+
+    fn register_anchor(&mut self, anchor: Anchor) -> AnchorId {
+        let anchor_id = self.anchor_counter.next();
+
+        let old = self.anchors.insert(anchor_id, anchor);
+        assert!(old.is_none());
+
+        anchor_id
+    }
+
+    fn get_anchor(&self, anchor_id: AnchorId) -> Option<&Anchor> {
+        self.anchors.get(&anchor_id)
+    }
+
+    fn get_anchor_mut(&mut self, anchor_id: AnchorId) -> Option<&mut Anchor> {
+        self.anchors.get_mut(&anchor_id)
+    }
+
+    fn destroy_anchor(&mut self, anchor_id: AnchorId) {
+        self.anchors.remove(&anchor_id);
+
+        // The anchor's children are treated as having no parent from now on, which moves them.
+        // Detach them so that the move is picked up by change tracking.
+        for anchor in self.anchors.values_mut() {
+            if anchor.parent() == Some(anchor_id) {
+                anchor.set_parent(None);
+            }
+        }
+    }
+
+    fn anchors(&self) -> RegisteredIter<AnchorId, Anchor> {
+        Box::new(self.anchors.iter().map(|(&id, anchor)| (id, anchor)))
+    }
+
+    fn anchors_mut(&mut self) -> RegisteredIterMut<AnchorId, Anchor> {
+        Box::new(self.anchors.iter_mut().map(|(&id, anchor)| (id, anchor)))
+    }
+
+    fn register_camera(&mut self, camera: Camera) -> CameraId {
+        let camera_id = self.camera_counter.next();
 
         let old = self.cameras.insert(camera_id, camera);
         assert!(old.is_none());
 
-        camera_id
+        camera_id
+    }
+
+    fn get_camera(&self, camera_id: CameraId) -> Option<&Camera> {
+        self.cameras.get(&camera_id)
+    }
+
+    fn get_camera_mut(&mut self, camera_id: CameraId) -> Option<&mut Camera> {
+        self.cameras.get_mut(&camera_id)
+    }
+
+    fn destroy_camera(&mut self, camera_id: CameraId) {
+        self.cameras.remove(&camera_id);
+        self.camera_timers.remove(&camera_id);
+    }
+
+    fn cameras(&self) -> RegisteredIter<CameraId, Camera> {
+        Box::new(self.cameras.iter().map(|(&id, camera)| (id, camera)))
+    }
+
+    fn cameras_mut(&mut self) -> RegisteredIterMut<CameraId, Camera> {
+        Box::new(self.cameras.iter_mut().map(|(&id, camera)| (id, camera)))
+    }
+
+    fn register_light(&mut self, light: Light) -> LightId {
+        let light_id = self.light_counter.next();
+
+        let old = self.lights.insert(light_id, light);
+        assert!(old.is_none());
+
+        light_id
+    }
+
+    fn get_light(&self, light_id: LightId) -> Option<&Light> {
+        self.lights.get(&light_id)
+    }
+
+    fn get_light_mut(&mut self, light_id: LightId) -> Option<&mut Light> {
+        self.lights.get_mut(&light_id)
+    }
+
+    fn destroy_light(&mut self, light_id: LightId) {
+        self.lights.remove(&light_id);
+    }
+
+    fn lights(&self) -> RegisteredIter<LightId, Light> {
+        Box::new(self.lights.iter().map(|(&id, light)| (id, light)))
+    }
+
+    fn lights_mut(&mut self) -> RegisteredIterMut<LightId, Light> {
+        Box::new(self.lights.iter_mut().map(|(&id, light)| (id, light)))
+    }
+
+    fn register_sprite(&mut self, texture: GpuTexture) -> SpriteId {
+        let size = match self.textures.get(&texture) {
+            Some(gl_texture) => Vector2::new(gl_texture.width() as f32, gl_texture.height() as f32),
+            None => Vector2::new(0.0, 0.0),
+        };
+
+        let sprite_id = self.sprite_counter.next();
+
+        let old = self.sprites.insert(sprite_id, Sprite::new(texture, size));
+        assert!(old.is_none());
+
+        sprite_id
+    }
+
+    fn get_sprite(&self, sprite_id: SpriteId) -> Option<&Sprite> {
+        self.sprites.get(&sprite_id)
+    }
+
+    fn get_sprite_mut(&mut self, sprite_id: SpriteId) -> Option<&mut Sprite> {
+        self.sprites.get_mut(&sprite_id)
+    }
+
+    fn destroy_sprite(&mut self, sprite_id: SpriteId) {
+        self.sprites.remove(&sprite_id);
+    }
+
+    fn sprites(&self) -> RegisteredIter<SpriteId, Sprite> {
+        Box::new(self.sprites.iter().map(|(&id, sprite)| (id, sprite)))
+    }
+
+    fn sprites_mut(&mut self) -> RegisteredIterMut<SpriteId, Sprite> {
+        Box::new(self.sprites.iter_mut().map(|(&id, sprite)| (id, sprite)))
+    }
+
+    fn set_ambient(&mut self, ambient: AmbientMode) {
+        self.ambient = ambient;
+    }
+
+    fn resize(&mut self, width: i32, height: i32) {
+        self.context.resize(width, height);
+
+        // Headless renderers draw into a framebuffer of the requested size rather than a window,
+        // so replace it with one of the new size.
+        if self.headless_target.is_some() {
+            let gamma_correct = self.gamma_correct;
+            self.headless_target = Some(
+                HeadlessTarget::new(&self.context, width, height, gamma_correct)
+                .expect("Failed to resize headless framebuffer"));
+        }
+
+        // The post targets always match the size of the frame.
+        if self.post_targets.is_some() {
+            let gamma_correct = self.gamma_correct;
+            self.post_targets = Some(
+                PostTargets::new(&self.context, width, height, gamma_correct)
+                .expect("Failed to resize post effect targets"));
+        }
+
+        // Update the cameras that follow the window's aspect ratio right away, so that their
+        // projection matrices are correct before the next frame is drawn.
+        for camera in self.cameras.values_mut() {
+            let (_, _, viewport_width, viewport_height) = viewport_pixels(camera.viewport(), width, height);
+            if viewport_width > 0 && viewport_height > 0 {
+                camera.set_viewport_aspect(viewport_width as f32 / viewport_height as f32);
+            }
+        }
+    }
+
+    fn set_skybox(&mut self, skybox: Option<GpuTexture>) {
+        self.skybox = skybox;
+    }
+
+    fn set_fog(&mut self, fog: Option<Fog>) {
+        self.fog = fog;
+    }
+
+    fn set_post_effects(&mut self, effects: Vec<MaterialId>) {
+        // The targets are only kept while there are effects, so that the scene is drawn straight
+        // into the frame otherwise.
+        if effects.is_empty() {
+            self.post_targets = None;
+        } else if self.post_targets.is_none() {
+            let (width, height) = self.context.dimensions();
+            self.post_targets = Some(
+                PostTargets::new(&self.context, width, height, self.gamma_correct)
+                .expect("Failed to create post effect targets"));
+        }
+
+        self.post_effects = effects;
+    }
+
+    fn set_debug_mode(&mut self, debug_mode: DebugMode) {
+        self.debug_mode = debug_mode;
+    }
+
+    fn draw_text(&mut self, text: &str, position: (f32, f32), size: f32, color: Color) {
+        self.text_quads.clear();
+        self.font.layout(text, position, size, &mut self.text_quads);
+
+        for quad in &self.text_quads {
+            let corners = [
+                (quad.min.0, quad.min.1, quad.uv_min.0, quad.uv_min.1),
+                (quad.max.0, quad.min.1, quad.uv_max.0, quad.uv_min.1),
+                (quad.max.0, quad.max.1, quad.uv_max.0, quad.uv_max.1),
+                (quad.min.0, quad.max.1, quad.uv_min.0, quad.uv_max.1),
+            ];
+
+            for &corner in &[0, 1, 2, 0, 2, 3] {
+                let (x, y, u, v) = corners[corner];
+                self.text_vertices.extend_from_slice(&[x, y, u, v, color.r, color.g, color.b, color.a]);
+            }
+        }
+    }
+
+    fn set_font(&mut self, font: BitmapFont) {
+        let font_texture = upload_texture(&self.context, font.texture(), self.gamma_correct);
+        font_texture.set_debug_label("Font texture");
+
+        self.font = font;
+        self.font_texture = font_texture;
+    }
+
+    fn set_clear_color(&mut self, color: Color) {
+        self.context.set_clear_color(color.r, color.g, color.b, color.a);
+    }
+
+    fn camera_gpu_time(&self, camera_id: CameraId) -> Option<Duration> {
+        self.camera_timers.get(&camera_id).and_then(|timer| timer.last_time)
+    }
+
+    fn capture_frame(&self) -> Image {
+        GlRender::capture_frame(self)
+    }
+
+    fn capability_report(&self) -> String {
+        let (major, minor) = self.context.version();
+        format!(
+            "vendor: {}\nrenderer: {}\nversion: {}.{} ({})\nsamples: {}\nlimits: {:#?}\n",
+            self.context.vendor(),
+            self.context.renderer(),
+            major,
+            minor,
+            self.context.version_string(),
+            self.context.samples(),
+            self.context.limits())
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    ContextError(ContextError),
+    TextureError(TextureError),
+}
+
+impl From<ContextError> for Error {
+    fn from(from: ContextError) -> Error {
+        Error::ContextError(from)
+    }
+}
+
+impl From<TextureError> for Error {
+    fn from(from: TextureError) -> Error {
+        Error::TextureError(from)
+    }
+}
+
+impl From<ShaderError> for BuildMaterialError {
+    fn from(from: ShaderError) -> BuildMaterialError {
+        match from {
+            ShaderError::CreateShaderError =>
+                BuildMaterialError::Link(String::from("Failed to create shader object")),
+            // Materials only generate vertex and fragment shaders.
+            ShaderError::CompileError { shader_type: ShaderType::Fragment, log } =>
+                BuildMaterialError::FragmentCompile(log),
+            ShaderError::CompileError { log, .. } => BuildMaterialError::VertexCompile(log),
+        }
+    }
+}
+
+impl From<ProgramError> for BuildMaterialError {
+    fn from(from: ProgramError) -> BuildMaterialError {
+        match from {
+            ProgramError::CreateProgramError =>
+                BuildMaterialError::Link(String::from("Failed to create program object")),
+            ProgramError::LinkError(log) => BuildMaterialError::Link(log),
+            ProgramError::ShaderError(error) => error.into(),
+        }
+    }
+}
+
+impl From<TextureFilter> for TextureFilterFunction {
+    fn from(from: TextureFilter) -> TextureFilterFunction {
+        match from {
+            TextureFilter::Nearest => TextureFilterFunction::Nearest,
+            TextureFilter::Linear => TextureFilterFunction::Linear,
+            TextureFilter::NearestMipmapNearest => TextureFilterFunction::NearestMipmapNearest,
+            TextureFilter::LinearMipmapNearest => TextureFilterFunction::LinearMipmapNearest,
+            TextureFilter::NearestMipmapLinear => TextureFilterFunction::NearestMipmapLinear,
+            TextureFilter::LinearMipmapLinear => TextureFilterFunction::LinearMipmapLinear,
+        }
+    }
+}
+
+impl From<TextureWrap> for TextureWrapFunction {
+    fn from(from: TextureWrap) -> TextureWrapFunction {
+        match from {
+            TextureWrap::Repeat => TextureWrapFunction::Repeat,
+            TextureWrap::ClampToEdge => TextureWrapFunction::ClampToEdge,
+            TextureWrap::MirroredRepeat => TextureWrapFunction::MirroredRepeat,
+        }
     }
+}
 
-    fn get_camera(&self, camera_id: CameraId) -> Option<&Camera> {
-        self.cameras.get(&camera_id)
+impl From<Topology> for DrawMode {
+    fn from(from: Topology) -> DrawMode {
+        match from {
+            Topology::Triangles => DrawMode::Triangles,
+            Topology::Lines => DrawMode::Lines,
+            Topology::LineStrip => DrawMode::LineStrip,
+            Topology::Points => DrawMode::Points,
+        }
     }
+}
 
-    fn get_camera_mut(&mut self, camera_id: CameraId) -> Option<&mut Camera> {
-        self.cameras.get_mut(&camera_id)
+/// Describes the layout of the mesh's vertex attributes in `vertex_buffer`.
+fn set_vertex_attribs(vertex_buffer: &mut VertexBuffer, mesh: &Mesh) {
+    let position = mesh.position();
+    vertex_buffer.set_attrib_f32(
+        "position",
+        AttribLayout {
+            elements: position.elements,
+            stride: position.stride,
+            offset: position.offset,
+        });
+
+    if let Some(normal) = mesh.normal() {
+        vertex_buffer.set_attrib_f32(
+            "normal",
+            AttribLayout {
+                elements: normal.elements,
+                stride: normal.stride,
+                offset: normal.offset
+            });
     }
 
-    fn register_light(&mut self, light: Light) -> LightId {
-        let light_id = self.light_counter.next();
+    if let Some(color) = mesh.color() {
+        vertex_buffer.set_attrib_f32(
+            "color",
+            AttribLayout {
+                elements: color.elements,
+                stride: color.stride,
+                offset: color.offset,
+            });
+    }
 
-        let old = self.lights.insert(light_id, light);
-        assert!(old.is_none());
+    if let Some(tangent) = mesh.tangent() {
+        vertex_buffer.set_attrib_f32(
+            "tangent",
+            AttribLayout {
+                elements: tangent.elements,
+                stride: tangent.stride,
+                offset: tangent.offset,
+            });
+    }
 
-        light_id
+    for (channel, texcoord) in mesh.texcoord().iter().enumerate() {
+        vertex_buffer.set_attrib_f32(
+            format!("texcoord{}", channel),
+            AttribLayout {
+                elements: texcoord.elements,
+                stride: texcoord.stride,
+                offset: texcoord.offset,
+            });
     }
 
-    fn get_light(&self, light_id: LightId) -> Option<&Light> {
-        self.lights.get(&light_id)
+    if let Some(bone_indices) = mesh.bone_indices() {
+        vertex_buffer.set_attrib_f32(
+            "bone_indices",
+            AttribLayout {
+                elements: bone_indices.elements,
+                stride: bone_indices.stride,
+                offset: bone_indices.offset,
+            });
     }
 
-    fn get_light_mut(&mut self, light_id: LightId) -> Option<&mut Light> {
-        self.lights.get_mut(&light_id)
+    if let Some(bone_weights) = mesh.bone_weights() {
+        vertex_buffer.set_attrib_f32(
+            "bone_weights",
+            AttribLayout {
+                elements: bone_weights.elements,
+                stride: bone_weights.stride,
+                offset: bone_weights.offset,
+            });
+    }
+}
+
+/// Uploads a mesh's indices to `index_buffer`, returning the size of the uploaded data in bytes.
+///
+/// 32 bit indices are narrowed to 16 bits if every index fits, since most meshes have few enough
+/// vertices and it halves the size of the index data.
+fn set_index_data(index_buffer: &mut IndexBuffer, indices: &Indices) -> usize {
+    match *indices {
+        Indices::U16(ref indices) => index_buffer.set_data_u16(indices),
+        Indices::U32(ref indices) => {
+            if indices.iter().all(|&index| index <= u16::MAX as u32) {
+                let narrowed = indices.iter().map(|&index| index as u16).collect::<Vec<_>>();
+                index_buffer.set_data_u16(&narrowed);
+            } else {
+                index_buffer.set_data_u32(indices);
+            }
+        }
     }
 
-    fn set_ambient_light(&mut self, color: Color) {
-        self.ambient_color = color;
+    match index_buffer.index_type() {
+        IndexType::UnsignedShort => indices.len() * 2,
+        _ => indices.len() * 4,
     }
 }
 
-unsafe impl Send for GlRender {}
+/// Associates the mesh's vertex attributes with the locations bound in every material program.
+///
+/// Locations for attributes that the mesh doesn't have are disabled, so that a vertex array
+/// that's reused for a mesh without normals doesn't keep reading the old normals.
+fn map_attrib_locations(vertex_array: &VertexArray, mesh: &Mesh) {
+    vertex_array.map_attrib_location("position", POSITION_LOCATION);
+
+    if mesh.normal().is_some() {
+        vertex_array.map_attrib_location("normal", NORMAL_LOCATION);
+    } else {
+        vertex_array.unmap_attrib_location(NORMAL_LOCATION);
+    }
 
-#[derive(Debug)]
-pub enum Error {
-    ContextError(ContextError),
+    if mesh.color().is_some() {
+        vertex_array.map_attrib_location("color", COLOR_LOCATION);
+    } else {
+        vertex_array.unmap_attrib_location(COLOR_LOCATION);
+    }
+
+    if mesh.tangent().is_some() {
+        vertex_array.map_attrib_location("tangent", TANGENT_LOCATION);
+    } else {
+        vertex_array.unmap_attrib_location(TANGENT_LOCATION);
+    }
+
+    if mesh.is_skinned() {
+        vertex_array.map_attrib_location("bone_indices", BONE_INDICES_LOCATION);
+        vertex_array.map_attrib_location("bone_weights", BONE_WEIGHTS_LOCATION);
+    } else {
+        vertex_array.unmap_attrib_location(BONE_INDICES_LOCATION);
+        vertex_array.unmap_attrib_location(BONE_WEIGHTS_LOCATION);
+    }
+
+    for (channel, &location) in UV_LOCATIONS.iter().enumerate() {
+        if channel < mesh.texcoord().len() {
+            vertex_array.map_attrib_location(&*format!("texcoord{}", channel), location);
+        } else {
+            vertex_array.unmap_attrib_location(location);
+        }
+    }
 }
 
-impl From<ContextError> for Error {
-    fn from(from: ContextError) -> Error {
-        Error::ContextError(from)
+/// Warns if `mesh` has more texcoord channels than there are `UV_LOCATIONS`, since materials
+/// can't read the extra channels.
+fn warn_unread_texcoords(reported_warnings: &mut HashSet<String>, mesh: &Mesh) {
+    if mesh.texcoord().len() > UV_LOCATIONS.len() {
+        warn_once(
+            reported_warnings,
+            format!(
+                "Mesh has {} texcoord channels, but materials can only read the first {}",
+                mesh.texcoord().len(),
+                UV_LOCATIONS.len()));
+    }
+}
+
+/// Attaches the path of the material file to `error`, if the material was loaded from a file.
+///
+/// Errors for materials built from a string are returned unchanged, since there's no file to
+/// point to.
+fn in_file(error: BuildMaterialError, source: &MaterialSource) -> BuildMaterialError {
+    match source.path {
+        Some(ref path) => BuildMaterialError::File { path: path.clone(), error: Box::new(error) },
+        None => error,
+    }
+}
+
+/// Prefixes each line of `source` with its line number, matching the numbering in GLSL logs.
+fn numbered_lines(source: &str) -> String {
+    source
+        .lines()
+        .enumerate()
+        .map(|(index, line)| format!("{:4}: {}\n", index + 1, line))
+        .collect()
+}
+
+/// Generates the GLSL uniform declarations for a material's properties.
+fn uniform_declarations(properties: &[PropertySource]) -> String {
+    let mut uniform_declarations = String::new();
+    for property in properties {
+        uniform_declarations.push_str("uniform ");
+
+        let type_str = match property.property_type {
+            PropertyType::Color => "vec4",
+            PropertyType::Texture2d => "sampler2D",
+            PropertyType::f32 => "float",
+            PropertyType::Vector2 => "vec2",
+            PropertyType::Vector3 => "vec3",
+            PropertyType::Vector4 => "vec4",
+            PropertyType::Matrix4 => "mat4",
+        };
+
+        uniform_declarations.push_str(type_str);
+        uniform_declarations.push(' ');
+        uniform_declarations.push_str(&*property.name);
+        uniform_declarations.push_str(";\n");
+    }
+
+    uniform_declarations
+}
+
+/// Sets a material property to the default declared in the material source, falling back to the
+/// default value for the property's type.
+///
+/// Texture defaults are paths that the renderer can't load, so texture properties always start
+/// out with the default texture.
+fn set_default_property(material: &mut Material, property: PropertySource) {
+    let name = property.name;
+    match (property.property_type, property.default) {
+        (_, Some(PropertyDefault::Color([r, g, b, a]))) => material.set_color(name, Color::new(r, g, b, a)),
+        (_, Some(PropertyDefault::f32(value))) => material.set_f32(name, value),
+        (_, Some(PropertyDefault::Vector2([x, y]))) => material.set_vector2(name, Vector2::new(x, y)),
+        (_, Some(PropertyDefault::Vector3([x, y, z]))) => material.set_vector3(name, Vector3::new(x, y, z)),
+        (_, Some(PropertyDefault::Vector4(value))) => material.set_vector4(name, value),
+        (_, Some(PropertyDefault::Matrix4(elements))) => {
+            let mut matrix = Matrix4::identity();
+            for (index, element) in elements.iter().enumerate() {
+                matrix[index / 4][index % 4] = *element;
+            }
+            material.set_matrix4(name, matrix);
+        },
+
+        (PropertyType::Color, _) => material.set_color(name, Color::default()),
+        (PropertyType::Texture2d, _) => material.set_texture(name, GpuTexture::default()),
+        (PropertyType::f32, _) => material.set_f32(name, f32::default()),
+        (PropertyType::Vector2, _) => material.set_vector2(name, Vector2::default()),
+        (PropertyType::Vector3, _) => material.set_vector3(name, Vector3::default()),
+        (PropertyType::Vector4, _) => material.set_vector4(name, [0.0; 4]),
+        (PropertyType::Matrix4, _) => material.set_matrix4(name, Matrix4::identity()),
+    };
+}
+
+/// Converts a camera's normalized viewport to pixels in a window of the given size, returning
+/// the x and y position of the bottom-left corner and the width and height.
+fn viewport_pixels(viewport: Option<ViewportRect>, width: i32, height: i32) -> (i32, i32, i32, i32) {
+    match viewport {
+        Some(rect) => (
+            (rect.x * width as f32).round() as i32,
+            (rect.y * height as f32).round() as i32,
+            (rect.width * width as f32).round() as i32,
+            (rect.height * height as f32).round() as i32,
+        ),
+        None => (0, 0, width, height),
+    }
+}
+
+/// Chooses the GL format for a texture's data and the internal format it's stored in.
+fn texture_formats(texture: &Texture2d, gamma_correct: bool) -> (TextureFormat, TextureInternalFormat) {
+    let (format, has_alpha) = match texture.format() {
+        DataFormat::Rgb => (TextureFormat::Rgb, false),
+        DataFormat::Rgba => (TextureFormat::Rgba, true),
+        DataFormat::Bgr => (TextureFormat::Bgr, false),
+        DataFormat::Bgra => (TextureFormat::Bgra, true),
+    };
+
+    // Float data is always stored in a float format so that values outside of [0, 1] aren't
+    // clamped. Byte data is stored as sRGB for color textures so that it's converted to linear
+    // when sampled, otherwise it's stored as-is. Without gamma correction shaders work in gamma
+    // space, so color textures are stored as-is too.
+    let color_space = match gamma_correct {
+        true => texture.color_space(),
+        false => ColorSpace::Linear,
+    };
+    let internal_format = match (texture.data(), color_space, has_alpha) {
+        (&TextureData::f32(_), _, false) => TextureInternalFormat::Rgb32F,
+        (&TextureData::f32(_), _, true) => TextureInternalFormat::Rgba32F,
+        (_, ColorSpace::Srgb, false) => TextureInternalFormat::Srgb8,
+        (_, ColorSpace::Srgb, true) => TextureInternalFormat::Srgb8Alpha8,
+        (_, ColorSpace::Linear, false) => TextureInternalFormat::Rgb,
+        (_, ColorSpace::Linear, true) => TextureInternalFormat::Rgba,
+    };
+
+    (format, internal_format)
+}
+
+/// Sends `texture` to the GPU with its filtering and wrapping modes.
+fn upload_texture(context: &Context, texture: &Texture2d, gamma_correct: bool) -> GlTexture2d {
+    let (format, internal_format) = texture_formats(texture, gamma_correct);
+
+    // Create the Texture2d from the texture data.
+    let texture_result = match texture.data() {
+        &TextureData::f32(ref data) => {
+            GlTexture2d::new(
+                context,
+                format,
+                internal_format,
+                texture.width(),
+                texture.height(),
+                &*data)
+        },
+        &TextureData::u8(ref data) => {
+            GlTexture2d::new(
+                context,
+                format,
+                internal_format,
+                texture.width(),
+                texture.height(),
+                &*data)
+        },
+        &TextureData::u8x3(ref data) => {
+            GlTexture2d::new(
+                context,
+                format,
+                internal_format,
+                texture.width(),
+                texture.height(),
+                &*data)
+        },
+        &TextureData::u8x4(ref data) => {
+            GlTexture2d::new(
+                context,
+                format,
+                internal_format,
+                texture.width(),
+                texture.height(),
+                &*data)
+        },
+    };
+    let mut gl_texture = texture_result.expect("Unable to send texture to GPU");
+
+    let (min_filter, mag_filter) = texture.filtering();
+    gl_texture.set_filtering(min_filter.into(), mag_filter.into());
+
+    let (wrap_s, wrap_t) = texture.wrap();
+    gl_texture.set_wrap(wrap_s.into(), wrap_t.into());
+
+    gl_texture
+}
+
+/// Generates the indices for drawing `count` sprites with four vertices each as two triangles.
+fn sprite_indices(count: usize) -> Vec<u32> {
+    let mut indices = Vec::with_capacity(count * 6);
+    for sprite in 0..count as u32 {
+        let first = sprite * 4;
+        indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+    indices
+}
+
+/// Sets the layout of the sprite or text vertices in `vertex_buffer`, which also updates the
+/// number of vertices drawn to match the buffer's data.
+fn set_overlay_attribs(vertex_buffer: &mut VertexBuffer) {
+    for &(name, _, offset, elements) in &OVERLAY_ATTRIBS {
+        vertex_buffer.set_attrib_f32(
+            name,
+            AttribLayout {
+                elements: elements,
+                stride: OVERLAY_VERTEX_LEN,
+                offset: offset,
+            });
+    }
+}
+
+/// Gathers the data for each face of a cube map, using `data` to get the face's data as `T`.
+///
+/// # Panics
+///
+/// - If `data` returns `None` for any face, i.e. the faces don't all have the same type of data.
+fn face_images<'a, T, F>(faces: &'a [Texture2d; 6], data: F) -> [FaceImage<'a, T>; 6]
+    where F: Fn(&'a TextureData) -> Option<&'a [T]>
+{
+    let face_image = |face: &'a Texture2d| FaceImage {
+        width: face.width(),
+        height: face.height(),
+        data: data(face.data()).expect("Cube map faces must all have the same type of data"),
+    };
+
+    [
+        face_image(&faces[0]),
+        face_image(&faces[1]),
+        face_image(&faces[2]),
+        face_image(&faces[3]),
+        face_image(&faces[4]),
+        face_image(&faces[5]),
+    ]
+}
+
+/// Identifies the program used by a draw, so that program switches can be counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BoundProgram {
+    Shadow,
+    Skybox,
+    Normals,
+    Overlay,
+    Material(Shader),
+    InstancedMaterial(Shader),
+    SkinnedMaterial(Shader),
+}
+
+/// A draw call for the mesh instances in the draw list.
+#[derive(Debug, Clone, Copy)]
+enum DrawItem {
+    /// Draws a submesh of a single mesh instance.
+    Single {
+        mesh_instance_id: MeshInstanceId,
+        submesh: usize,
+    },
+
+    /// Draws the submesh of `count` mesh instances with the same mesh and material for the
+    /// submesh as `mesh_instance_id`, whose transforms start at `first_instance` in the instance
+    /// buffer.
+    Instanced {
+        mesh_instance_id: MeshInstanceId,
+        submesh: usize,
+        first_instance: usize,
+        count: usize,
+    },
+}
+
+/// Sorts the submeshes of mesh instances by shader, then by the textures their materials use,
+/// then by mesh.
+///
+/// Draws that share a shader don't need to switch programs, and draws that share a mesh use the
+/// same vertex array. Ties are broken by ID so that the order is stable. Mesh instances whose
+/// mesh isn't registered get a single entry, so that the draw can report the missing mesh.
+fn sorted_draw_list(
+    mesh_instances: &HashMap<MeshInstanceId, MeshInstance>,
+    meshes: &HashMap<GpuMesh, MeshData>,
+) -> Vec<(MeshInstanceId, usize)> {
+    let mut keyed = mesh_instances
+        .iter()
+        .flat_map(|(&mesh_instance_id, mesh_instance)| {
+            let submesh_count = meshes
+                .get(mesh_instance.mesh())
+                .map_or(1, |mesh_data| mesh_data.submeshes.len());
+            (0..submesh_count).map(move |submesh| (mesh_instance_id, mesh_instance, submesh))
+        })
+        .map(|(mesh_instance_id, mesh_instance, submesh)| {
+            let material = mesh_instance.submesh_material(submesh);
+
+            // Materials don't have an identity of their own, so group them by the textures they
+            // bind, ordered by property name so that equal materials produce equal keys.
+            let mut textures = material
+                .properties()
+                .filter_map(|(name, property)| match *property {
+                    MaterialProperty::Texture(texture) => Some((name, texture)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            textures.sort();
+            let textures = textures.into_iter().map(|(_, texture)| texture).collect::<Vec<_>>();
+
+            ((*material.shader(), textures, *mesh_instance.mesh(), mesh_instance_id, submesh), (mesh_instance_id, submesh))
+        })
+        .collect::<Vec<_>>();
+    keyed.sort_by(|&(ref left, _), &(ref right, _)| left.cmp(right));
+
+    keyed.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// Checks if an anchor or any of its parents has changed since the last frame.
+///
+/// Returns `false` if there's no anchor. Anchors that have been destroyed don't count as
+/// changed, otherwise anything left attached to them would look like it moved every frame.
+fn anchor_changed(anchors: &HashMap<AnchorId, Anchor>, anchor_id: Option<&AnchorId>) -> bool {
+    let mut next = anchor_id.cloned();
+    while let Some(anchor_id) = next {
+        let anchor = match anchors.get(&anchor_id) {
+            Some(anchor) => anchor,
+            None => return false,
+        };
+        if anchor.has_changed() {
+            return true;
+        }
+
+        // Parent cycles are broken when resolving world transforms, so this always ends.
+        next = anchor.parent();
+    }
+
+    false
+}
+
+/// Snaps the center of a static light's shadow map to a grid, so that the cached shadow map can
+/// be reused while the camera moves around within a grid cell.
+///
+/// The grid spacing is a quarter of `SHADOW_DISTANCE`, so the camera always stays well inside the
+/// region covered by the shadow map.
+fn snap_shadow_center(center: Point) -> Point {
+    let spacing = SHADOW_DISTANCE / 4.0;
+    Point::new(
+        (center.x / spacing).round() * spacing,
+        (center.y / spacing).round() * spacing,
+        (center.z / spacing).round() * spacing)
+}
+
+/// Calculates the transform from world space to the clip space of a directional light's shadow
+/// map.
+///
+/// The shadow map is an orthographic projection looking along `direction`, covering
+/// `SHADOW_DISTANCE` in every direction around `center`.
+fn directional_shadow_transform(direction: Vector3, center: Point) -> Matrix4 {
+    let forward = direction.normalized();
+    let reference_up = if forward.y.abs() > 0.99 { Vector3::back() } else { Vector3::up() };
+    let right = Vector3::cross(forward, reference_up).normalized();
+    let up = Vector3::cross(right, forward);
+    let back = -forward;
+
+    // Place the light behind the covered region so that everything in it is in front of the light.
+    let eye = center - forward * SHADOW_DISTANCE;
+    let eye = Vector3::new(eye.x, eye.y, eye.z);
+
+    let mut view = Matrix4::identity();
+    for (row, axis) in [right, up, back].iter().enumerate() {
+        view[row][0] = axis.x;
+        view[row][1] = axis.y;
+        view[row][2] = axis.z;
+        view[row][3] = -axis.dot(eye);
+    }
+
+    let projection = Matrix4::orthographic(
+        -SHADOW_DISTANCE,
+        SHADOW_DISTANCE,
+        -SHADOW_DISTANCE,
+        SHADOW_DISTANCE,
+        0.0,
+        2.0 * SHADOW_DISTANCE);
+
+    projection * view
+}
+
+/// Prints `message` as a warning unless it has already been reported.
+fn warn_once(reported_warnings: &mut HashSet<String>, message: String) {
+    if !reported_warnings.contains(&message) {
+        println!("WARNING: {}", message);
+        reported_warnings.insert(message);
+    }
+}
+
+/// Sends the ambient light to the built-in ambient uniforms, see `Renderer::set_ambient()`.
+fn set_ambient_uniforms(draw_builder: &mut DrawBuilder, ambient: AmbientMode) {
+    let (mode, flat, sky, ground, up) = match ambient {
+        AmbientMode::Flat(color) => (0, color, color, color, Vector3::new(0.0, 1.0, 0.0)),
+        AmbientMode::Hemisphere { sky, ground, up } => {
+            let average = Color::new(
+                (sky.r + ground.r) * 0.5,
+                (sky.g + ground.g) * 0.5,
+                (sky.b + ground.b) * 0.5,
+                (sky.a + ground.a) * 0.5);
+            (1, average, sky, ground, up.normalized())
+        },
+    };
+
+    draw_builder
+        .uniform("ambient_mode", mode)
+        .uniform::<[f32; 4]>("global_ambient", flat.into())
+        .uniform::<[f32; 4]>("ambient_sky_color", sky.into())
+        .uniform::<[f32; 4]>("ambient_ground_color", ground.into())
+        .uniform("ambient_up", up.into_array());
+}
+
+/// Sends the fog settings to the built-in fog uniforms, see `Renderer::set_fog()`.
+///
+/// The uniforms are always set so that a program doesn't keep the fog from an earlier frame
+/// after the fog is removed.
+fn set_fog_uniforms(draw_builder: &mut DrawBuilder, fog: Option<Fog>) {
+    let (color, params, mode) = match fog {
+        None => (Color::rgb(0.0, 0.0, 0.0), [0.0, 0.0], 0),
+        Some(Fog { color, mode: FogMode::Linear { start, end } }) => (color, [start, end], 1),
+        Some(Fog { color, mode: FogMode::Exp { density } }) => (color, [density, 0.0], 2),
+    };
+
+    draw_builder
+        .uniform::<[f32; 4]>("fog_color", color.into())
+        .uniform("fog_params", params)
+        .uniform("fog_mode", mode);
+}
+
+/// Sends a material property to the uniform `name`, using `default_texture` in place of textures
+/// that haven't been registered.
+fn set_property_uniform<'a>(
+    draw_builder: &mut DrawBuilder<'a>,
+    name: &str,
+    property: &'a MaterialProperty,
+    textures: &'a HashMap<GpuTexture, GlTexture2d>,
+    default_texture: &'a GlTexture2d,
+) {
+    match *property {
+        MaterialProperty::Color(ref color) => {
+            draw_builder.uniform::<[f32; 4]>(name, color.into());
+        },
+        MaterialProperty::f32(value) => {
+            draw_builder.uniform(name, value);
+        },
+        MaterialProperty::Vector2(value) => {
+            draw_builder.uniform(name, (value.x, value.y));
+        },
+        MaterialProperty::Vector3(value) => {
+            draw_builder.uniform::<[f32; 3]>(name, value.into());
+        },
+        MaterialProperty::Vector4(value) => {
+            draw_builder.uniform(name, value);
+        },
+        MaterialProperty::Matrix4(ref matrix) => {
+            draw_builder.uniform(
+                name,
+                GlMatrix {
+                    data: matrix.raw_data(),
+                    transpose: true,
+                });
+        },
+        MaterialProperty::Texture(ref texture) => {
+            let gl_texture = textures.get(texture).unwrap_or(default_texture);
+            draw_builder.uniform(name, gl_texture);
+        },
+    }
+}
+
+/// Sends `lights` to the light array uniforms, leaving the remaining elements unset.
+fn set_light_arrays<'a>(draw_builder: &mut DrawBuilder<'a>, names: &[LightUniformNames], lights: &[&'a LightUniforms]) {
+    debug_assert!(lights.len() <= MAX_LIGHTS, "Too many lights for one draw: {}", lights.len());
+
+    draw_builder.uniform("light_count", lights.len() as i32);
+    for (light, names) in lights.iter().zip(names) {
+        light.set_indexed(draw_builder, names);
     }
 }
 
@@ -767,6 +3813,575 @@ struct MeshData {
     vertex_array: VertexArray,
     position_attribute: VertexAttribute,
     normal_attribute: Option<VertexAttribute>,
-    uv_attribute: Option<VertexAttribute>,
+    color_attribute: Option<VertexAttribute>,
+    tangent_attribute: Option<VertexAttribute>,
+    uv_attributes: Vec<VertexAttribute>,
     element_count: usize,
+
+    /// The ranges of indices of the mesh's submeshes.
+    submeshes: Vec<Range<usize>>,
+
+    /// How the mesh's indices are assembled into primitives.
+    topology: Topology,
+
+    /// Whether the mesh has bone indices and weights, see `Mesh::is_skinned()`.
+    skinned: bool,
+
+    /// The model space bounds of the mesh, see `Mesh::bounding_sphere()`.
+    bounding_sphere: Sphere,
+
+    /// The size in bytes of the mesh's vertex and index data on the GPU.
+    byte_len: usize,
+
+    vertex_animation: Option<VertexAnimation>,
+}
+
+/// The textures holding the baked vertex animation for a mesh.
+#[derive(Debug)]
+struct VertexAnimation {
+    positions: GpuTexture,
+    normals: Option<GpuTexture>,
+}
+
+/// Measures the GPU time spent rendering a camera.
+///
+/// Timer results arrive a frame or two after the query ends, so the query isn't restarted until
+/// the previous result has been read. Frames drawn while a result is still pending aren't timed.
+#[derive(Debug)]
+struct CameraTimer {
+    query: TimerQuery,
+    pending: bool,
+    timing: bool,
+    last_time: Option<Duration>,
+}
+
+impl CameraTimer {
+    fn new(context: &Context) -> CameraTimer {
+        CameraTimer {
+            query: TimerQuery::new(context),
+            pending: false,
+            timing: false,
+            last_time: None,
+        }
+    }
+
+    fn begin(&mut self) {
+        if self.pending {
+            match self.query.result() {
+                Some(time) => {
+                    self.last_time = Some(time);
+                    self.pending = false;
+                },
+                None => return,
+            }
+        }
+
+        self.query.begin();
+        self.timing = true;
+    }
+
+    fn end(&mut self) {
+        if self.timing {
+            self.query.end();
+            self.timing = false;
+            self.pending = true;
+        }
+    }
+}
+
+/// The uniform values for a light as seen from the current camera.
+#[derive(Debug)]
+struct LightUniforms {
+    light_type: i32,
+    color: [f32; 4],
+    strength: f32,
+    position: [f32; 4],
+    position_view: [f32; 4],
+    radius: f32,
+    falloff: i32,
+    attenuation: [f32; 3],
+    direction: [f32; 3],
+    direction_view: [f32; 3],
+    has_shadow: i32,
+
+    /// The transform from world space to the light's tile in the shadow atlas, see
+    /// `ShadowTile::atlas_transform()`.
+    shadow_transform: Matrix4,
+
+    /// The bounds of the light's tile in the shadow atlas, see `ShadowTile::region()`.
+    shadow_region: [f32; 4],
+}
+
+impl LightUniforms {
+    fn new(light: &Light, anchor: Option<&WorldTransform>, view_transform: Matrix4) -> LightUniforms {
+        let mut uniforms = LightUniforms {
+            light_type: 0,
+            color: light.color.into(),
+            strength: light.strength,
+            position: [0.0, 0.0, 0.0, 1.0],
+            position_view: [0.0, 0.0, 0.0, 1.0],
+            radius: 0.0,
+            falloff: 0,
+            attenuation: [0.0, 0.0, 0.0],
+            direction: [0.0, 0.0, 0.0],
+            direction_view: [0.0, 0.0, 0.0],
+            has_shadow: 0,
+            shadow_transform: Matrix4::identity(),
+            shadow_region: [0.0, 0.0, 0.0, 0.0],
+        };
+
+        match light.data {
+            LightData::Point { radius, falloff } => {
+                let anchor = anchor.expect("Cannot render light if it's not attached to an anchor");
+
+                uniforms.light_type = 1;
+                uniforms.position = *anchor.position().as_array();
+                uniforms.position_view = *(anchor.position() * view_transform).as_array();
+                uniforms.radius = radius;
+                uniforms.falloff = falloff.uniform_value();
+                uniforms.attenuation = falloff.attenuation();
+            },
+
+            LightData::Directional { direction } => {
+                uniforms.light_type = 2;
+                uniforms.direction = direction.into_array();
+                uniforms.direction_view = (direction * view_transform).into_array();
+            },
+        }
+
+        uniforms
+    }
+
+    /// Returns `true` if the light can affect a mesh instance with the given world space bounds.
+    ///
+    /// Point lights have no effect past their radius, so they only reach the mesh instances they
+    /// overlap. Directional lights reach everything, and every light reaches a mesh instance
+    /// without bounds.
+    fn reaches(&self, bounds: Option<Sphere>) -> bool {
+        match bounds {
+            Some(bounds) if self.light_type == 1 => {
+                let position = Point::new(self.position[0], self.position[1], self.position[2]);
+                Sphere::new(position, self.radius).intersects(&bounds)
+            },
+            _ => true,
+        }
+    }
+
+    /// Sends the light to the single light uniforms used when drawing once per light.
+    fn set_single<'a>(&'a self, draw_builder: &mut DrawBuilder<'a>) {
+        draw_builder
+        .uniform("light_type", self.light_type)
+        .uniform("light_color", self.color)
+        .uniform("light_strength", self.strength)
+        .uniform("light_position", self.position)
+        .uniform("light_position_view", self.position_view)
+        .uniform("light_radius", self.radius)
+        .uniform("light_falloff", self.falloff)
+        .uniform("light_attenuation", self.attenuation)
+        .uniform("light_direction", self.direction)
+        .uniform("light_direction_view", self.direction_view)
+        .uniform("light_has_shadow", self.has_shadow)
+        .uniform(
+            "light_space_transform",
+            GlMatrix {
+                data: self.shadow_transform.raw_data(),
+                transpose: true,
+            },
+        )
+        .uniform("shadow_region", self.shadow_region);
+    }
+
+    /// Sends the light to one element of the light arrays.
+    fn set_indexed<'a>(&'a self, draw_builder: &mut DrawBuilder<'a>, names: &LightUniformNames) {
+        draw_builder
+        .uniform(&names.light_type, self.light_type)
+        .uniform(&names.color, self.color)
+        .uniform(&names.strength, self.strength)
+        .uniform(&names.position, self.position)
+        .uniform(&names.position_view, self.position_view)
+        .uniform(&names.radius, self.radius)
+        .uniform(&names.falloff, self.falloff)
+        .uniform(&names.attenuation, self.attenuation)
+        .uniform(&names.direction, self.direction)
+        .uniform(&names.direction_view, self.direction_view)
+        .uniform(&names.has_shadow, self.has_shadow)
+        .uniform(
+            &names.shadow_transform,
+            GlMatrix {
+                data: self.shadow_transform.raw_data(),
+                transpose: true,
+            },
+        )
+        .uniform(&names.shadow_region, self.shadow_region);
+    }
+}
+
+/// The names of the light array uniforms for one index.
+#[derive(Debug)]
+struct LightUniformNames {
+    light_type: String,
+    color: String,
+    strength: String,
+    position: String,
+    position_view: String,
+    radius: String,
+    falloff: String,
+    attenuation: String,
+    direction: String,
+    direction_view: String,
+    has_shadow: String,
+    shadow_transform: String,
+    shadow_region: String,
+}
+
+impl LightUniformNames {
+    fn new(index: usize) -> LightUniformNames {
+        LightUniformNames {
+            light_type: format!("light_types[{}]", index),
+            color: format!("light_colors[{}]", index),
+            strength: format!("light_strengths[{}]", index),
+            position: format!("light_positions[{}]", index),
+            position_view: format!("light_positions_view[{}]", index),
+            radius: format!("light_radii[{}]", index),
+            falloff: format!("light_falloffs[{}]", index),
+            attenuation: format!("light_attenuations[{}]", index),
+            direction: format!("light_directions[{}]", index),
+            direction_view: format!("light_directions_view[{}]", index),
+            has_shadow: format!("light_has_shadows[{}]", index),
+            shadow_transform: format!("light_space_transforms[{}]", index),
+            shadow_region: format!("shadow_regions[{}]", index),
+        }
+    }
+}
+
+/// The offscreen framebuffer that a headless renderer draws into in place of the window.
+#[derive(Debug)]
+struct HeadlessTarget {
+    framebuffer: Framebuffer,
+    color: GlTexture2d,
+
+    // Kept alive for as long as it's attached to `framebuffer`.
+    _depth_stencil: Renderbuffer,
+}
+
+impl HeadlessTarget {
+    fn new(context: &Context, width: i32, height: i32, gamma_correct: bool) -> Result<HeadlessTarget, TextureError> {
+        let (width, height) = (width.max(1) as usize, height.max(1) as usize);
+
+        let color = GlTexture2d::render_target(context, color_target_format(gamma_correct), width, height)?;
+        color.set_debug_label("Headless color");
+
+        let depth_stencil = Renderbuffer::new(context, TextureInternalFormat::Depth24Stencil8, width, height);
+
+        let mut framebuffer = Framebuffer::new(context);
+        framebuffer.attach_color(0, &color);
+        framebuffer.attach_depth_stencil(&depth_stencil);
+        framebuffer.set_debug_label("Headless framebuffer");
+        framebuffer.check().expect("Headless framebuffer is incomplete");
+
+        Ok(HeadlessTarget {
+            framebuffer: framebuffer,
+            color: color,
+            _depth_stencil: depth_stencil,
+        })
+    }
+}
+
+/// The offscreen targets the scene is drawn into while there are post effects, see
+/// `Renderer::set_post_effects()`.
+#[derive(Debug)]
+struct PostTargets {
+    /// Draws into the first color texture and the depth texture.
+    scene: Framebuffer,
+
+    /// Draws into each of the color textures without depth, so that the depth texture can be
+    /// sampled by the effects.
+    color_framebuffers: [Framebuffer; 2],
+
+    colors: [GlTexture2d; 2],
+    depth: GlTexture2d,
+}
+
+impl PostTargets {
+    fn new(context: &Context, width: i32, height: i32, gamma_correct: bool) -> Result<PostTargets, TextureError> {
+        let (width, height) = (width.max(1) as usize, height.max(1) as usize);
+
+        let colors = [
+            GlTexture2d::render_target(context, color_target_format(gamma_correct), width, height)?,
+            GlTexture2d::render_target(context, color_target_format(gamma_correct), width, height)?,
+        ];
+        colors[0].set_debug_label("Post effect color 0");
+        colors[1].set_debug_label("Post effect color 1");
+
+        let depth = GlTexture2d::depth(context, width, height, DepthFormat::Depth24)?;
+        depth.set_debug_label("Post effect depth");
+
+        let mut scene = Framebuffer::new(context);
+        scene.attach_color(0, &colors[0]);
+        scene.attach_depth(&depth);
+        scene.set_debug_label("Post effect scene framebuffer");
+        scene.check().expect("Post effect scene framebuffer is incomplete");
+
+        let color_framebuffer = |index: usize| {
+            let mut framebuffer = Framebuffer::new(context);
+            framebuffer.attach_color(0, &colors[index]);
+            framebuffer.set_debug_label(&format!("Post effect framebuffer {}", index));
+            framebuffer.check().expect("Post effect framebuffer is incomplete");
+            framebuffer
+        };
+        let color_framebuffers = [color_framebuffer(0), color_framebuffer(1)];
+
+        Ok(PostTargets {
+            scene: scene,
+            color_framebuffers: color_framebuffers,
+            colors: colors,
+            depth: depth,
+        })
+    }
+}
+
+/// The format of offscreen color targets, matching the window's sRGB framebuffer so that the
+/// result looks the same either way.
+fn color_target_format(gamma_correct: bool) -> TextureInternalFormat {
+    if gamma_correct {
+        TextureInternalFormat::Srgb8Alpha8
+    } else {
+        TextureInternalFormat::Rgba8
+    }
+}
+
+/// Calculates the model and normal transforms for a mesh instance attached to `anchor` that's
+/// billboarded toward the camera attached to `camera_anchor`.
+///
+/// The anchor's rotation is replaced so that the rotation of the model-view transform is
+/// cancelled out, entirely for `BillboardMode::Spherical` and aside from the camera's pitch and
+/// roll for `BillboardMode::Cylindrical`. The anchor's position and scale are kept.
+fn billboard_transforms(
+    billboard: BillboardMode,
+    anchor: &WorldTransform,
+    camera_anchor: &WorldTransform,
+) -> (Matrix4, Matrix3) {
+    // The scale along each of the anchor's axes, including the scale of its parents.
+    let matrix = anchor.matrix();
+    let scale = Vector3::new(
+        matrix.x_part().magnitude(),
+        matrix.y_part().magnitude(),
+        matrix.z_part().magnitude());
+
+    let mut rotation = camera_anchor.inverse_view_matrix();
+    for row in 0..3 {
+        rotation[row][3] = 0.0;
+    }
+
+    if billboard == BillboardMode::Cylindrical {
+        // Face the camera's back axis flattened onto the XZ plane. When the camera looks straight
+        // up or down its back axis is vertical, so use the direction the top of the screen faces
+        // instead.
+        let mut back = rotation.z_part().set_y(0.0);
+        if back.magnitude_squared().is_zero() {
+            back = -rotation.y_part().set_y(0.0);
+        }
+        let back = back.normalized();
+        let up = Vector3::up();
+        let right = Vector3::cross(up, back);
+
+        rotation = Matrix4::identity();
+        for (col, axis) in [right, up, back].iter().enumerate() {
+            rotation[0][col] = axis.x;
+            rotation[1][col] = axis.y;
+            rotation[2][col] = axis.z;
+        }
+    }
+
+    let model_transform = Matrix4::from_point(anchor.position()) * (rotation * Matrix4::from_scale_vector(scale));
+
+    // As with `Anchor::normal_matrix()`, the scale is ignored if it's zero on any axis.
+    let normal_transform = match Matrix3::from(model_transform).inverse() {
+        Some(inverse) => inverse.transpose(),
+        None => rotation.into(),
+    };
+
+    (model_transform, normal_transform)
+}
+
+/// Starts a draw into `target`, or into the window if `target` is `None`.
+fn screen_draw<'a>(
+    context: &'a Context,
+    target: Option<&'a Framebuffer>,
+    vertex_array: &'a VertexArray,
+    draw_mode: DrawMode,
+) -> DrawBuilder<'a> {
+    let mut draw_builder = DrawBuilder::new(context, vertex_array, draw_mode);
+    if let Some(target) = target {
+        draw_builder.framebuffer(target);
+    }
+    draw_builder
+}
+
+/// Clears the selected buffers of `target`, or of the window if `target` is `None`.
+fn clear_screen(context: &Context, target: Option<&Framebuffer>, color: bool, depth: bool, stencil: bool) {
+    match target {
+        Some(target) => target.clear_flags(color, depth, stencil),
+        None => context.clear_flags(color, depth, stencil),
+    }
+}
+
+/// The depth texture that every shadow casting light's shadows are rendered into, and the
+/// framebuffer used to render it.
+///
+/// Each light gets its own square tile of the atlas for each camera, since the region a tile
+/// covers follows the camera. Tiles are cached separately, so a static light's tile is only
+/// re-rendered when something that shows up in it changes.
+#[derive(Debug)]
+struct ShadowAtlas {
+    framebuffer: Framebuffer,
+    texture: GlTexture2d,
+
+    /// The width and height of the atlas in texels.
+    size: u32,
+
+    tiles: HashMap<ShadowTileId, ShadowTile>,
+
+    /// The tiles requested when the atlas was last laid out and their resolutions, sorted by ID.
+    /// Tiles that didn't fit in the atlas are included, so that a full atlas isn't laid out
+    /// again every frame.
+    layout: Vec<(ShadowTileId, u32)>,
+}
+
+/// Identifies a tile in the shadow atlas by the light and the camera it's rendered for.
+type ShadowTileId = (LightId, CameraId);
+
+/// A light's shadow map within the shadow atlas.
+#[derive(Debug)]
+struct ShadowTile {
+    /// The bottom-left corner of the tile in texels.
+    x: u32,
+    y: u32,
+
+    /// The width and height of the tile in texels.
+    resolution: u32,
+
+    /// What the tile was last rendered for, or `None` if it has to be re-rendered.
+    cached: Option<ShadowCacheKey>,
+
+    /// The mesh instances drawn into the tile when it was last rendered.
+    casters: HashSet<MeshInstanceId>,
+}
+
+/// Identifies the contents of a shadow atlas tile, so that a static light's tile is only
+/// re-rendered when something about it changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ShadowCacheKey {
+    light_space_transform: Matrix4,
+    layers: u32,
+}
+
+impl ShadowAtlas {
+    fn new(context: &Context, size: u32) -> Result<ShadowAtlas, TextureError> {
+        let mut texture = GlTexture2d::depth(
+            context,
+            size as usize,
+            size as usize,
+            DepthFormat::Depth24)?;
+        texture.set_compare(Comparison::LessThanOrEqual);
+        texture.set_debug_label("Shadow atlas");
+
+        // The shadow pass only writes depth.
+        let mut framebuffer = Framebuffer::new(context);
+        framebuffer.attach_depth(&texture);
+        framebuffer.set_draw_buffers(&[]);
+        framebuffer.set_debug_label("Shadow atlas framebuffer");
+
+        Ok(ShadowAtlas {
+            framebuffer: framebuffer,
+            texture: texture,
+            size: size,
+            tiles: HashMap::new(),
+            layout: Vec::new(),
+        })
+    }
+}
+
+impl ShadowTile {
+    /// Calculates the transform from the clip space of the tile's shadow map to the atlas's
+    /// texture coordinates, with depth mapped to the 0 to 1 range stored in the atlas.
+    fn atlas_transform(&self, atlas_size: u32) -> Matrix4 {
+        let scale = self.resolution as f32 / atlas_size as f32;
+
+        let mut transform = Matrix4::identity();
+        transform[0][0] = scale * 0.5;
+        transform[0][3] = (self.x as f32 + self.resolution as f32 * 0.5) / atlas_size as f32;
+        transform[1][1] = scale * 0.5;
+        transform[1][3] = (self.y as f32 + self.resolution as f32 * 0.5) / atlas_size as f32;
+        transform[2][2] = 0.5;
+        transform[2][3] = 0.5;
+        transform
+    }
+
+    /// Gets the bounds of the tile in the atlas's texture coordinates, as the bottom-left and
+    /// top-right corners.
+    fn region(&self, atlas_size: u32) -> [f32; 4] {
+        let size = atlas_size as f32;
+        [
+            self.x as f32 / size,
+            self.y as f32 / size,
+            (self.x + self.resolution) as f32 / size,
+            (self.y + self.resolution) as f32 / size,
+        ]
+    }
+}
+
+/// Arranges shadow map tiles in a square atlas.
+///
+/// Tiles are placed in rows from largest to smallest. The atlas starts out just big enough for
+/// the largest tile and doubles in size until every tile fits or it reaches `max_size`, in which
+/// case the tiles that don't fit are left out. Returns the size of the atlas and the bottom-left
+/// corner of each tile that was placed.
+fn pack_shadow_tiles(
+    requests: &[(ShadowTileId, u32)],
+    max_size: u32,
+) -> (u32, HashMap<ShadowTileId, (u32, u32)>) {
+    // Largest first, with ties broken by ID so that the layout doesn't change between frames.
+    let mut sorted = requests.to_vec();
+    sorted.sort_by(|&(left_id, left_resolution), &(right_id, right_resolution)| {
+        (right_resolution, left_id).cmp(&(left_resolution, right_id))
+    });
+
+    let largest = sorted.first().map_or(1, |&(_, resolution)| resolution);
+    let mut size = largest.next_power_of_two().min(max_size);
+    loop {
+        let mut positions = HashMap::with_capacity(sorted.len());
+        let (mut x, mut y, mut row_height) = (0, 0, 0);
+        for &(id, resolution) in &sorted {
+            if x + resolution > size {
+                x = 0;
+                y += row_height;
+                row_height = 0;
+            }
+            if x + resolution > size || y + resolution > size {
+                continue;
+            }
+
+            positions.insert(id, (x, y));
+            x += resolution;
+            row_height = row_height.max(resolution);
+        }
+
+        if positions.len() == sorted.len() || size >= max_size {
+            return (size, positions);
+        }
+        size = (size * 2).min(max_size);
+    }
+}
+
+/// Checks if a sphere overlaps the region covered by a directional light's shadow map.
+///
+/// `directional_shadow_transform()` scales every axis by the same amount, so the sphere is still
+/// a sphere in the shadow map's clip space and can be tested against the clip volume directly.
+fn overlaps_shadow_region(light_space_transform: Matrix4, bounds: Sphere) -> bool {
+    let center = bounds.center * light_space_transform;
+    let radius = bounds.radius / SHADOW_DISTANCE;
+    center.x.abs() <= 1.0 + radius
+        && center.y.abs() <= 1.0 + radius
+        && center.z.abs() <= 1.0 + radius
 }
@@ -0,0 +1,1752 @@
+use {Counter, DebugMode, Fog, FogMode, FrameStats, Renderer};
+use anchor::*;
+use bootstrap::window::Window;
+use camera::*;
+use geometry::mesh::*;
+use image::Image;
+use light::*;
+use material::*;
+use math::*;
+use mesh_instance::*;
+use skeleton::*;
+use sprite::*;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use super::GlRender;
+use super::gl_util::IndexType;
+use text::*;
+use texture::*;
+
+static VERTEX_POSITIONS: [f32; 12] = [
+    -1.0, -1.0, 0.0, 1.0,
+     1.0, -1.0, 0.0, 1.0,
+     0.0,  1.0, 0.0, 1.0,
+];
+
+static INDICES: [u32; 3] = [0, 1, 2];
+
+static QUAD_POSITIONS: [f32; 16] = [
+    -1.0, -1.0, 0.0, 1.0,
+     1.0, -1.0, 0.0, 1.0,
+     1.0,  1.0, 0.0, 1.0,
+    -1.0,  1.0, 0.0, 1.0,
+];
+
+static QUAD_INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+const RESOURCE_COUNT: usize = 200;
+
+fn triangle() -> Mesh {
+    MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&VERTEX_POSITIONS))
+        .set_indices(&INDICES)
+        .build()
+        .unwrap()
+}
+
+fn quad() -> Mesh {
+    MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&QUAD_POSITIONS))
+        .set_indices(&QUAD_INDICES)
+        .build()
+        .unwrap()
+}
+
+/// A quad with two texcoord channels: one covering a base texture and one covering the quad's
+/// corner of a shared lightmap.
+fn lightmapped_quad() -> Mesh {
+    static UV0: [f32; 8] = [0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
+    static UV1: [f32; 8] = [0.0, 0.0, 0.5, 0.0, 0.5, 0.5, 0.0, 0.5];
+
+    MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&QUAD_POSITIONS))
+        .set_texcoord_data(Vector2::slice_from_f32_slice(&UV0))
+        .set_texcoord_channel_data(1, Vector2::slice_from_f32_slice(&UV1))
+        .set_indices(&QUAD_INDICES)
+        .build()
+        .unwrap()
+}
+
+fn white_texture() -> Texture2d {
+    Texture2d::new(1, 1, DataFormat::Rgba, TextureData::u8x4(vec![(255, 255, 255, 255)]))
+}
+
+#[test]
+fn destroy_all_resources() {
+    let window = Window::new("destroy_all_resources").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+    let mesh = triangle();
+
+    let mut meshes = Vec::new();
+    let mut textures = Vec::new();
+    let mut anchors = Vec::new();
+    let mut mesh_instances = Vec::new();
+    let mut cameras = Vec::new();
+    let mut lights = Vec::new();
+
+    for _ in 0..RESOURCE_COUNT {
+        let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
+        meshes.push(gpu_mesh);
+        textures.push(renderer.register_texture(&white_texture()));
+
+        let anchor_id = renderer.register_anchor(Anchor::new());
+        anchors.push(anchor_id);
+
+        let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+        mesh_instance.set_anchor(anchor_id);
+        mesh_instances.push(renderer.register_mesh_instance(mesh_instance));
+
+        let mut camera = Camera::default();
+        camera.set_anchor(anchor_id);
+        cameras.push(renderer.register_camera(camera));
+
+        let mut light = Light::point(1.0, 1.0, Color::rgb(1.0, 1.0, 1.0));
+        light.set_anchor(anchor_id);
+        lights.push(renderer.register_light(light));
+    }
+
+    renderer.draw();
+
+    for mesh in meshes { renderer.unregister_mesh(mesh); }
+    for texture in textures { renderer.unregister_texture(texture); }
+    for mesh_instance in mesh_instances { renderer.destroy_mesh_instance(mesh_instance); }
+    for anchor in anchors { renderer.destroy_anchor(anchor); }
+    for camera in cameras { renderer.destroy_camera(camera); }
+    for light in lights { renderer.destroy_light(light); }
+
+    assert!(renderer.meshes.is_empty());
+    assert!(renderer.textures.is_empty());
+    assert!(renderer.mesh_instances.is_empty());
+    assert!(renderer.anchors.is_empty());
+    assert!(renderer.cameras.is_empty());
+    assert!(renderer.camera_timers.is_empty());
+    assert!(renderer.lights.is_empty());
+
+    // Drawing with nothing registered should still work.
+    renderer.draw();
+}
+
+#[test]
+fn draw_skips_dangling_references() {
+    let window = Window::new("draw_skips_dangling_references").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    // A mesh instance whose mesh has been unregistered.
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    let gpu_mesh = renderer.register_mesh(&triangle()).unwrap();
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+    renderer.unregister_mesh(gpu_mesh);
+
+    // A mesh instance and light whose anchor has been destroyed.
+    let destroyed_anchor = renderer.register_anchor(Anchor::new());
+    let mut mesh_instance = MeshInstance::new(renderer.register_mesh(&triangle()).unwrap(), renderer.default_material());
+    mesh_instance.set_anchor(destroyed_anchor);
+    renderer.register_mesh_instance(mesh_instance);
+    let mut light = Light::point(1.0, 1.0, Color::rgb(1.0, 1.0, 1.0));
+    light.set_anchor(destroyed_anchor);
+    renderer.register_light(light);
+    renderer.destroy_anchor(destroyed_anchor);
+
+    renderer.draw();
+
+    // Destroying the camera's anchor skips the camera entirely.
+    renderer.destroy_anchor(camera_anchor);
+    renderer.draw();
+}
+
+#[test]
+fn update_mesh() {
+    let window = Window::new("update_mesh").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    let gpu_mesh = renderer.register_mesh(&quad()).unwrap();
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+
+    renderer.update_mesh(gpu_mesh, &triangle()).unwrap();
+    assert_eq!(renderer.meshes[&gpu_mesh].element_count, INDICES.len());
+
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+
+    // Unregistered meshes can't be updated.
+    renderer.unregister_mesh(gpu_mesh);
+    match renderer.update_mesh(gpu_mesh, &quad()) {
+        Err(MeshError::UnregisteredMesh { mesh }) => assert_eq!(mesh, gpu_mesh),
+        result => panic!("Expected an unregistered mesh error but got {:?}", result),
+    }
+}
+
+#[test]
+fn register_mesh_narrows_indices() {
+    let window = Window::new("register_mesh_narrows_indices").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    // The quad is built with 32 bit indices, but they all fit in 16 bits.
+    let mesh = quad();
+    assert_eq!(*mesh.indices(), Indices::U32(QUAD_INDICES.to_vec()));
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
+    {
+        let mesh_data = renderer.meshes.get_mut(&gpu_mesh).unwrap();
+        assert_eq!(mesh_data.vertex_array.index_buffer_mut().unwrap().index_type(), IndexType::UnsignedShort);
+    }
+
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+    renderer.register_mesh_instance(mesh_instance);
+
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.mesh_bytes, mesh.vertex_data().len() * 4 + QUAD_INDICES.len() * 2);
+}
+
+#[test]
+fn register_invalid_mesh() {
+    let window = Window::new("register_invalid_mesh").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    // Four indices leave the second triangle incomplete.
+    let invalid_mesh = MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&QUAD_POSITIONS))
+        .set_indices(&QUAD_INDICES[..4])
+        .build()
+        .unwrap();
+
+    match renderer.register_mesh(&invalid_mesh) {
+        Err(MeshError::IncompleteTriangle { index_count: 4 }) => {},
+        result => panic!("Expected an incomplete triangle error but got {:?}", result),
+    }
+    assert!(renderer.meshes.is_empty());
+
+    // Updating a mesh with invalid data leaves the old data in place.
+    let gpu_mesh = renderer.register_mesh(&triangle()).unwrap();
+    assert!(renderer.update_mesh(gpu_mesh, &invalid_mesh).is_err());
+    assert_eq!(renderer.meshes[&gpu_mesh].element_count, INDICES.len());
+}
+
+#[test]
+fn draw_multiple_cameras() {
+    let window = Window::new("draw_multiple_cameras").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    let mut mesh_instance = MeshInstance::new(renderer.register_mesh(&quad()).unwrap(), renderer.default_material());
+    mesh_instance.set_anchor(anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    // Drawing with no cameras is allowed, though nothing is rendered.
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+
+    let mut left_camera = Camera::default();
+    left_camera.set_anchor(renderer.register_anchor(Anchor::new()));
+    left_camera.set_viewport(Some(ViewportRect::new(0.0, 0.0, 0.5, 1.0)));
+    left_camera.set_priority(1);
+    let left_camera = renderer.register_camera(left_camera);
+
+    let mut right_camera = Camera::default();
+    right_camera.set_anchor(renderer.register_anchor(Anchor::new()));
+    right_camera.set_viewport(Some(ViewportRect::new(0.5, 0.0, 0.5, 1.0)));
+    let right_camera = renderer.register_camera(right_camera);
+
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+
+    // Each camera's aspect ratio matches its half of the window, rather than the whole window.
+    let (width, height) = renderer.context.dimensions();
+    let expected_aspect = (width as f32 * 0.5).round() / height as f32;
+    let left_projection = renderer.cameras[&left_camera].projection_matrix();
+    let right_projection = renderer.cameras[&right_camera].projection_matrix();
+    let mut expected_camera = Camera::default();
+    expected_camera.set_aspect(Aspect::Fixed(expected_aspect));
+    assert_eq!(left_projection, expected_camera.projection_matrix());
+    assert_eq!(right_projection, expected_camera.projection_matrix());
+}
+
+#[test]
+fn draw_more_lights_than_max() {
+    let window = Window::new("draw_more_lights_than_max").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    // The default material reads the light arrays.
+    let default_shader = *renderer.default_material().shader();
+    assert!(renderer.light_array_programs.contains(&default_shader));
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    let mut mesh_instance = MeshInstance::new(renderer.register_mesh(&quad()).unwrap(), renderer.default_material());
+    mesh_instance.set_anchor(anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    // Enough lights to need more than one batch.
+    for _ in 0..super::MAX_LIGHTS + 3 {
+        let mut light = Light::point(1.0, 1.0, Color::rgb(1.0, 1.0, 1.0));
+        light.set_anchor(anchor_id);
+        renderer.register_light(light);
+    }
+    renderer.register_light(Light::directional(Vector3::new(0.0, 0.0, -1.0), 1.0, Color::rgb(1.0, 1.0, 1.0)));
+
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+}
+
+#[test]
+fn draw_sorts_by_shader() {
+    let window = Window::new("draw_sorts_by_shader").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let flat_source = MaterialSource::from_str(include_str!("../../resources/materials/diffuse_flat.material")).unwrap();
+    let flat_material = renderer.build_material(flat_source).unwrap();
+
+    // Alternate between the two shaders so that drawing in registration order would switch
+    // programs for every draw. Each mesh instance gets its own color so that they aren't
+    // instanced.
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    let gpu_mesh = renderer.register_mesh(&triangle()).unwrap();
+    for index in 0..RESOURCE_COUNT {
+        let material = if index % 2 == 0 { renderer.default_material() } else { flat_material.clone() };
+        let mut mesh_instance = MeshInstance::new(gpu_mesh, material);
+        let shade = index as f32 / RESOURCE_COUNT as f32;
+        mesh_instance.material_mut().set_color("surface_color", Color::rgb(shade, shade, shade));
+        mesh_instance.set_anchor(anchor_id);
+        renderer.register_mesh_instance(mesh_instance);
+    }
+
+    // With no lights both materials draw each mesh instance once.
+    let stats = renderer.draw();
+    assert_eq!(stats.draw_calls, RESOURCE_COUNT);
+    assert_eq!(stats.program_switches, 2);
+    // Only the default material samples the shadow map.
+    assert_eq!(stats.texture_binds, RESOURCE_COUNT / 2);
+
+    // The draw list is reused when nothing has changed.
+    assert_eq!(renderer.draw(), stats);
+}
+
+#[test]
+fn draw_shadows() {
+    let window = Window::new("draw_shadows").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 256).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    let camera_id = renderer.register_camera(camera);
+
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    let gpu_mesh = renderer.register_mesh(&quad()).unwrap();
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    // A mesh instance that doesn't cast shadows isn't drawn in the depth pass.
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(anchor_id);
+    mesh_instance.set_casts_shadows(false);
+    renderer.register_mesh_instance(mesh_instance);
+
+    let mut light = Light::directional(Vector3::new(0.0, -1.0, -1.0), 1.0, Color::rgb(1.0, 1.0, 1.0));
+    light.set_casts_shadows(true);
+    let light_id = renderer.register_light(light);
+
+    // One depth pass draw plus one draw for each mesh instance.
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 3);
+    {
+        let atlas = renderer.shadow_atlas.as_ref().unwrap();
+        assert_eq!(atlas.size, 256);
+        assert_eq!(atlas.tiles[&(light_id, camera_id)].resolution, 256);
+    }
+
+    // Lights can override the shadow map resolution.
+    renderer.get_light_mut(light_id).unwrap().set_shadow_resolution(Some(128));
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    {
+        let atlas = renderer.shadow_atlas.as_ref().unwrap();
+        assert_eq!(atlas.size, 128);
+        assert_eq!(atlas.tiles[&(light_id, camera_id)].resolution, 128);
+    }
+
+    // Each shadow casting light gets its own tile, with the largest tiles placed first.
+    let mut light = Light::directional(Vector3::new(1.0, -1.0, 0.0), 1.0, Color::rgb(1.0, 1.0, 1.0));
+    light.set_casts_shadows(true);
+    let other_light_id = renderer.register_light(light);
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 4);
+    let atlas = renderer.shadow_atlas.as_ref().unwrap();
+    assert_eq!(atlas.size, 512);
+    let tile = &atlas.tiles[&(light_id, camera_id)];
+    let other_tile = &atlas.tiles[&(other_light_id, camera_id)];
+    assert_eq!((other_tile.x, other_tile.y, other_tile.resolution), (0, 0, 256));
+    assert_eq!((tile.x, tile.y, tile.resolution), (256, 0, 128));
+}
+
+#[test]
+fn draw_skybox() {
+    let window = Window::new("draw_skybox").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let face = || {
+        let mut face = Texture2d::new(2, 2, DataFormat::Rgb, TextureData::u8x3(vec![(0, 128, 255); 4]));
+        face.set_filtering(TextureFilter::LinearMipmapLinear, TextureFilter::Nearest);
+        face.set_wrap(TextureWrap::ClampToEdge, TextureWrap::ClampToEdge);
+        face
+    };
+    let skybox = renderer.register_texture_cube(&[face(), face(), face(), face(), face(), face()]);
+    renderer.set_skybox(Some(skybox));
+
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 1);
+    assert_eq!(stats.program_switches, 1);
+
+    // An unregistered skybox isn't drawn.
+    renderer.unregister_texture(skybox);
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 0);
+}
+
+#[test]
+fn draw_debug_modes() {
+    let window = Window::new("draw_debug_modes").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    let mut mesh_instance = MeshInstance::new(renderer.register_mesh(&quad()).unwrap(), renderer.default_material());
+    mesh_instance.set_anchor(anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    // Enough lights to need two batches.
+    for _ in 0..super::MAX_LIGHTS + 1 {
+        let mut light = Light::point(1.0, 1.0, Color::rgb(1.0, 1.0, 1.0));
+        light.set_anchor(anchor_id);
+        renderer.register_light(light);
+    }
+
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 2);
+
+    // Wireframes skip the second batch of lights.
+    renderer.set_debug_mode(DebugMode::Wireframe);
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 1);
+
+    // The normals program is only built once it's needed, and draws each mesh a second time.
+    assert!(renderer.normals_program.is_none());
+    renderer.set_debug_mode(DebugMode::Normals);
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert!(renderer.normals_program.is_some());
+    assert_eq!(stats.draw_calls, 3);
+    assert_eq!(stats.program_switches, 2);
+
+    renderer.set_debug_mode(DebugMode::None);
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 2);
+}
+
+#[test]
+fn draw_instanced() {
+    let window = Window::new("draw_instanced").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    // The default material doesn't read the model transforms in its fragment program.
+    let default_shader = *renderer.default_material().shader();
+    assert!(renderer.instanced_programs.contains_key(&default_shader));
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let gpu_mesh = renderer.register_mesh(&quad()).unwrap();
+    for index in 0..super::INSTANCING_THRESHOLD + 1 {
+        let mut anchor = Anchor::new();
+        anchor.set_position(Point::new(index as f32, 0.0, 0.0));
+        let anchor_id = renderer.register_anchor(anchor);
+
+        let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+        mesh_instance.set_anchor(anchor_id);
+        renderer.register_mesh_instance(mesh_instance);
+    }
+
+    // A mesh instance with different material properties is drawn individually.
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.material_mut().set_f32("surface_shininess", 10.0);
+    mesh_instance.set_anchor(anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.instanced_draw_calls, 1);
+    assert_eq!(stats.individual_draw_calls, 1);
+    assert_eq!(stats.draw_calls, 2);
+}
+
+#[test]
+fn resize_updates_camera_aspect() {
+    let window = Window::new("resize_updates_camera_aspect").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let mut camera = Camera::default();
+    camera.set_anchor(renderer.register_anchor(Anchor::new()));
+    let camera_id = renderer.register_camera(camera);
+
+    let mut fixed_camera = Camera::default();
+    fixed_camera.set_aspect(Aspect::Fixed(2.0));
+    fixed_camera.set_anchor(renderer.register_anchor(Anchor::new()));
+    let fixed_camera_id = renderer.register_camera(fixed_camera);
+
+    let mut expected_camera = Camera::default();
+
+    renderer.resize(1600, 900);
+    expected_camera.set_aspect(Aspect::Fixed(16.0 / 9.0));
+    assert_eq!(renderer.cameras[&camera_id].projection_matrix(), expected_camera.projection_matrix());
+
+    // The projection changes as soon as the window is resized, without drawing a frame.
+    renderer.resize(800, 600);
+    expected_camera.set_aspect(Aspect::Fixed(4.0 / 3.0));
+    assert_eq!(renderer.cameras[&camera_id].projection_matrix(), expected_camera.projection_matrix());
+
+    // Cameras with a fixed aspect ratio ignore the window.
+    assert_eq!(renderer.cameras[&fixed_camera_id].aspect_ratio(), 2.0);
+}
+
+#[test]
+fn rebuild_material() {
+    let window = Window::new("rebuild_material").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let flat_source = MaterialSource::from_str(include_str!("../../resources/materials/diffuse_flat.material")).unwrap();
+    let mut material = renderer.build_material(flat_source).unwrap();
+    material.set_color("surface_color", Color::rgb(1.0, 0.0, 0.0));
+    let shader = *material.shader();
+
+    let lit_source = MaterialSource::from_str(include_str!("../../resources/materials/diffuse_lit.material")).unwrap();
+    renderer.rebuild_material(&mut material, lit_source).unwrap();
+
+    // The shader is rebuilt in place, keeping existing values and adding the new properties.
+    assert_eq!(*material.shader(), shader);
+    assert_eq!(material.get_color("surface_color"), Some(&Color::rgb(1.0, 0.0, 0.0)));
+    assert!(material.get_property("surface_specular").is_some());
+    assert!(material.get_property("surface_shininess").is_some());
+
+    // A source that fails to build leaves the material and its programs alone.
+    let broken_source = MaterialSource::from_str("property surface_color: Color;\n\nprogram vert {\n    @position = model_view_projection * vertex_position;\n}\n").unwrap();
+    let before = material.clone();
+    assert!(renderer.rebuild_material(&mut material, broken_source).is_err());
+    assert_eq!(material, before);
+    assert!(renderer.programs.contains_key(&shader));
+}
+
+#[test]
+fn build_material_errors() {
+    use BuildMaterialError;
+    use std::path::PathBuf;
+
+    let window = Window::new("build_material_errors").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let source = MaterialSource::from_str("program vert {\n    @position = vertex_position;\n}\n").unwrap();
+    match renderer.build_material(source) {
+        Err(BuildMaterialError::MissingFragmentProgram) => {}
+        result => panic!("Expected missing fragment program, got {:?}", result),
+    }
+
+    let source = MaterialSource::from_str("program frag {\n    @color = not_a_variable;\n}\n").unwrap();
+    match renderer.build_material(source) {
+        Err(BuildMaterialError::FragmentCompile(_)) => {}
+        result => panic!("Expected fragment compile error, got {:?}", result),
+    }
+
+    let source = MaterialSource::from_str("program vert {\n    @position = not_a_variable;\n}\n\nprogram frag {\n    @color = vec4(1.0);\n}\n").unwrap();
+    match renderer.build_material(source) {
+        Err(BuildMaterialError::VertexCompile(_)) => {}
+        result => panic!("Expected vertex compile error, got {:?}", result),
+    }
+
+    // Errors for materials loaded from a file say which file failed, and verbose errors include
+    // the generated source.
+    renderer.set_verbose_material_errors(true);
+    let mut source = MaterialSource::from_str("program frag {\n    @color = not_a_variable;\n}\n").unwrap();
+    source.path = Some(PathBuf::from("broken.material"));
+    match renderer.build_material(source) {
+        Err(BuildMaterialError::File { ref path, ref error }) => {
+            assert_eq!(*path, PathBuf::from("broken.material"));
+            match **error {
+                BuildMaterialError::FragmentCompile(ref log) => assert!(log.contains("not_a_variable")),
+                ref error => panic!("Expected fragment compile error, got {:?}", error),
+            }
+        }
+        result => panic!("Expected error in file, got {:?}", result),
+    }
+}
+
+#[test]
+fn build_material_defaults() {
+    let window = Window::new("build_material_defaults").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let source = MaterialSource::from_str(r#"
+        property surface_color: Color = (1.0, 0.5, 0.25);
+        property surface_shininess: f32 = 4.0;
+        property surface_offset: Vector3;
+        property surface_diffuse: Texture2d = "textures/diffuse.bmp";
+
+        program frag {
+            @color = surface_color * surface_shininess * texture(surface_diffuse, @vertex.uv0) + vec4(surface_offset, 0.0);
+        }
+    "#).unwrap();
+    let material = renderer.build_material(source).unwrap();
+
+    assert_eq!(material.get_color("surface_color"), Some(&Color::new(1.0, 0.5, 0.25, 1.0)));
+    assert_eq!(material.get_f32("surface_shininess"), Some(&4.0));
+    assert_eq!(material.get_vector3("surface_offset"), Some(&Vector3::default()));
+    assert_eq!(material.get_property("surface_diffuse"), Some(&MaterialProperty::Texture(GpuTexture::default())));
+}
+
+#[test]
+fn vector_and_matrix_properties() {
+    let source = MaterialSource::from_str(r#"
+        property uv_scroll: Vector2 = (0.5, 0.0);
+        property tint: Vector4;
+        property uv_transform: Matrix4;
+
+        program frag {
+            vec2 uv = (uv_transform * vec4(@vertex.uv0 + uv_scroll, 0.0, 1.0)).xy;
+            @color = tint * vec4(uv, 0.0, 1.0);
+        }
+    "#).unwrap();
+
+    let declarations = super::uniform_declarations(&source.properties);
+    assert!(declarations.contains("uniform vec2 uv_scroll;"));
+    assert!(declarations.contains("uniform vec4 tint;"));
+    assert!(declarations.contains("uniform mat4 uv_transform;"));
+
+    let window = Window::new("vector_and_matrix_properties").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+    let mut material = renderer.build_material(source).unwrap();
+    assert_eq!(material.get_vector2("uv_scroll"), Some(&Vector2::new(0.5, 0.0)));
+    assert_eq!(material.get_vector4("tint"), Some(&[0.0; 4]));
+    assert_eq!(material.get_matrix4("uv_transform"), Some(&Matrix4::identity()));
+
+    // Draw with the new property types to make sure they're uploaded as the right uniform types.
+    material.set_vector4("tint", [1.0, 0.5, 0.25, 1.0]);
+    material.set_matrix4("uv_transform", Matrix4::scale(2.0, 2.0, 1.0));
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+    let mut mesh_instance = MeshInstance::new(renderer.register_mesh(&triangle()).unwrap(), material);
+    mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+    renderer.register_mesh_instance(mesh_instance);
+
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+}
+
+#[test]
+fn draw_multiple_uv_sets() {
+    let window = Window::new("draw_multiple_uv_sets").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let mesh = lightmapped_quad();
+    assert_eq!(mesh.texcoord().len(), 2);
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
+    assert_eq!(renderer.meshes[&gpu_mesh].uv_attributes.len(), 2);
+
+    let source = MaterialSource::from_str(r#"
+        property base_color: Texture2d;
+        property lightmap: Texture2d;
+
+        program frag {
+            @color = texture(base_color, @vertex.uv0) * texture(lightmap, @vertex.uv1);
+        }
+    "#).unwrap();
+    let mut material = renderer.build_material(source).unwrap();
+    let texture = renderer.register_texture(&white_texture());
+    material.set_texture("base_color", texture);
+    material.set_texture("lightmap", texture);
+
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, material.clone());
+    mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+    renderer.register_mesh_instance(mesh_instance);
+
+    // Meshes without a second UV set can still be drawn with the material.
+    let mut mesh_instance = MeshInstance::new(renderer.register_mesh(&quad()).unwrap(), material);
+    mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+    renderer.register_mesh_instance(mesh_instance);
+
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 2);
+
+    // Channels past the second can't be read by materials, so registering them warns.
+    let mesh = MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&QUAD_POSITIONS))
+        .set_texcoord_data(Vector2::slice_from_f32_slice(&[0.0; 8]))
+        .set_texcoord_channel_data(1, Vector2::slice_from_f32_slice(&[0.0; 8]))
+        .set_texcoord_channel_data(2, Vector2::slice_from_f32_slice(&[0.0; 8]))
+        .set_indices(&QUAD_INDICES)
+        .build()
+        .unwrap();
+    renderer.register_mesh(&mesh).unwrap();
+    assert!(renderer.reported_warnings.iter().any(|warning| warning.contains("3 texcoord channels")));
+}
+
+#[test]
+fn draw_vertex_colors() {
+    static COLORS: [f32; 12] = [
+        1.0, 0.0, 0.0, 1.0,
+        0.0, 1.0, 0.0, 1.0,
+        0.0, 0.0, 1.0, 1.0,
+    ];
+
+    let window = Window::new("draw_vertex_colors").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let colors = COLORS.chunks(4).map(|color| Color::new(color[0], color[1], color[2], color[3])).collect::<Vec<_>>();
+    let colored_triangle = MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&VERTEX_POSITIONS))
+        .set_color_data(&colors)
+        .set_indices(&INDICES)
+        .build()
+        .unwrap();
+    assert!(colored_triangle.color().is_some());
+
+    let source = MaterialSource::from_str("program frag {\n    @color = @vertex.color;\n}\n").unwrap();
+    let material = renderer.build_material(source).unwrap();
+
+    // Draw a mesh with vertex colors and one without, which reads white instead.
+    for mesh in &[colored_triangle, triangle()] {
+        let mut mesh_instance = MeshInstance::new(renderer.register_mesh(mesh).unwrap(), material.clone());
+        mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+        renderer.register_mesh_instance(mesh_instance);
+    }
+
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 2);
+}
+
+#[test]
+fn draw_normal_mapped() {
+    static UV0: [f32; 8] = [0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
+
+    let window = Window::new("draw_normal_mapped").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let mut light = Light::point(1.0, 1.0, Color::rgb(1.0, 1.0, 1.0));
+    light.set_anchor(renderer.register_anchor(Anchor::new()));
+    renderer.register_light(light);
+
+    let tangent_quad = MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&QUAD_POSITIONS))
+        .set_normal_data(&[Vector3::new(0.0, 0.0, 1.0); 4])
+        .set_texcoord_data(Vector2::slice_from_f32_slice(&UV0))
+        .set_indices(&QUAD_INDICES)
+        .generate_tangents()
+        .build()
+        .unwrap();
+    assert!(tangent_quad.tangent().is_some());
+
+    // A flat normal map, pointing straight out of the surface.
+    let mut normal_map = Texture2d::new(1, 1, DataFormat::Rgb, TextureData::u8x3(vec![(128, 128, 255)]));
+    normal_map.set_color_space(ColorSpace::Linear);
+
+    let source = MaterialSource::from_str(include_str!("../../resources/materials/diffuse_normal_mapped.material")).unwrap();
+    let mut material = renderer.build_material(source).unwrap();
+    material.set_texture("normal_map", renderer.register_texture(&normal_map));
+
+    // Draw a mesh with tangents and one without, which uses the default tangent instead.
+    for mesh in &[tangent_quad, quad()] {
+        let mut mesh_instance = MeshInstance::new(renderer.register_mesh(mesh).unwrap(), material.clone());
+        mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+        renderer.register_mesh_instance(mesh_instance);
+    }
+
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 2);
+}
+
+#[test]
+fn draw_submeshes() {
+    let window = Window::new("draw_submeshes").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let mesh = MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&QUAD_POSITIONS))
+        .set_indices(&QUAD_INDICES)
+        .set_submeshes(&[0..3, 3..6])
+        .build()
+        .unwrap();
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
+
+    // Each submesh gets its own material.
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    let mut red = renderer.default_material();
+    red.set_color("surface_color", Color::rgb(1.0, 0.0, 0.0));
+    let mut blue = renderer.default_material();
+    blue.set_color("surface_color", Color::rgb(0.0, 0.0, 1.0));
+    mesh_instance.set_submesh_materials(vec![red, blue]);
+    mesh_instance.set_anchor(anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    // A mesh instance with a single material uses it for both submeshes.
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 4);
+
+    // Updating the mesh to a single submesh rebuilds the draw list.
+    renderer.update_mesh(gpu_mesh, &quad()).unwrap();
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 2);
+}
+
+#[test]
+fn anchor_hierarchy() {
+    let window = Window::new("anchor_hierarchy").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    // Each anchor is offset one unit along x from its parent.
+    let root = renderer.register_anchor(Anchor::new());
+    let mut middle_anchor = Anchor::new();
+    middle_anchor.set_position(Point::new(1.0, 0.0, 0.0));
+    middle_anchor.set_parent(Some(root));
+    let middle = renderer.register_anchor(middle_anchor);
+    let mut leaf_anchor = Anchor::new();
+    leaf_anchor.set_position(Point::new(1.0, 0.0, 0.0));
+    leaf_anchor.set_parent(Some(middle));
+    let leaf = renderer.register_anchor(leaf_anchor);
+
+    renderer.draw();
+    assert!((renderer.world_transforms[&leaf].position() - Point::new(2.0, 0.0, 0.0)).is_zero());
+
+    // Rotating the root swings the whole chain around it.
+    let orientation = Orientation::axis_angle(Vector3::new(0.0, 1.0, 0.0), Deg(90.0));
+    renderer.get_anchor_mut(root).unwrap().set_orientation(orientation);
+    renderer.draw();
+
+    let expected = Point::new(2.0, 0.0, 0.0) * Matrix4::from(orientation);
+    let leaf_position = renderer.world_transforms[&leaf].position();
+    assert!((leaf_position - expected).is_zero());
+    assert!(!(leaf_position - Point::new(2.0, 0.0, 0.0)).is_zero());
+}
+
+#[test]
+fn anchor_cycle() {
+    let mut anchors = HashMap::new();
+    let mut counter = AnchorId::initial();
+    let first = counter.next();
+    let second = counter.next();
+    let mut anchor = Anchor::new();
+    anchor.set_parent(Some(second));
+    anchors.insert(first, anchor);
+    let mut anchor = Anchor::new();
+    anchor.set_parent(Some(first));
+    anchors.insert(second, anchor);
+
+    let mut world_transforms = HashMap::new();
+    match resolve_world_transforms(&anchors, &mut world_transforms) {
+        Err(AnchorCycle { ref anchors }) if anchors.len() == 2 => {},
+        result => panic!("Expected a cycle of two anchors but got {:?}", result),
+    }
+
+    // The renderer detaches an anchor to break the cycle.
+    let window = Window::new("anchor_cycle").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+    let first = renderer.register_anchor(Anchor::new());
+    let mut anchor = Anchor::new();
+    anchor.set_parent(Some(first));
+    let second = renderer.register_anchor(anchor);
+    renderer.get_anchor_mut(first).unwrap().set_parent(Some(second));
+
+    renderer.draw();
+    assert_eq!(renderer.world_transforms.len(), 2);
+    let parents = (renderer.get_anchor(first).unwrap().parent(), renderer.get_anchor(second).unwrap().parent());
+    assert!(parents == (None, Some(first)) || parents == (Some(second), None));
+}
+
+/// A UV sphere of radius 1 with `rings` rings of `segments` vertices, plus a vertex at each pole.
+fn sphere(rings: usize, segments: usize) -> Mesh {
+    let mut positions = vec![Point::new(0.0, 1.0, 0.0)];
+    for ring in 1..rings + 1 {
+        let polar = PI * ring as f32 / (rings + 1) as f32;
+        for segment in 0..segments {
+            let azimuth = 2.0 * PI * segment as f32 / segments as f32;
+            positions.push(Point::new(polar.sin() * azimuth.cos(), polar.cos(), -polar.sin() * azimuth.sin()));
+        }
+    }
+    positions.push(Point::new(0.0, -1.0, 0.0));
+    let normals = positions.iter().map(|&position| Vector3::from(position)).collect::<Vec<_>>();
+
+    let bottom = positions.len() as u32 - 1;
+    let vertex = |ring: usize, segment: usize| (1 + ring * segments + segment % segments) as u32;
+    let mut indices = Vec::new();
+    for segment in 0..segments {
+        indices.extend(&[0, vertex(0, segment), vertex(0, segment + 1)]);
+        for ring in 0..rings - 1 {
+            indices.extend(&[vertex(ring, segment), vertex(ring + 1, segment), vertex(ring + 1, segment + 1)]);
+            indices.extend(&[vertex(ring, segment), vertex(ring + 1, segment + 1), vertex(ring, segment + 1)]);
+        }
+        indices.extend(&[vertex(rings - 1, segment), bottom, vertex(rings - 1, segment + 1)]);
+    }
+
+    MeshBuilder::new()
+        .set_position_data(&*positions)
+        .set_normal_data(&*normals)
+        .set_indices(&*indices)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn non_uniform_scale_normals() {
+    let window = Window::new("non_uniform_scale_normals").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let mut anchor = Anchor::new();
+    anchor.set_position(Point::new(0.0, 0.0, -5.0));
+    anchor.set_orientation(Orientation::axis_angle(Vector3::new(0.0, 0.0, 1.0), Deg(30.0)));
+    anchor.set_scale(Vector3::new(2.0, 1.0, 1.0));
+    let anchor_id = renderer.register_anchor(anchor);
+
+    let mesh = sphere(8, 16);
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+
+    // The scaled sphere is an ellipsoid, whose surface normals follow the gradient of
+    // `(x / 2)^2 + y^2 + z^2` in the ellipsoid's own frame, i.e. before rotation.
+    let anchor = renderer.get_anchor(anchor_id).unwrap();
+    let world_transform = renderer.world_transforms[&anchor_id];
+    let orientation = Matrix3::from(anchor.orientation());
+    let center = anchor.position();
+    let attrib = mesh.position();
+    let stride = if attrib.stride == 0 { attrib.elements } else { attrib.stride };
+    for index in 0..mesh.vertex_count() {
+        let data = &mesh.vertex_data()[attrib.offset + index * stride..];
+        let position = Point::new(data[0], data[1], data[2]);
+        let world_position = position * world_transform.matrix();
+        let normal = (Vector3::from(position) * world_transform.normal_matrix()).normalized();
+
+        let scaled = Vector3::new(position.x * 2.0, position.y, position.z);
+        let gradient = Vector3::new(scaled.x / 4.0, scaled.y, scaled.z);
+        let expected = (gradient * orientation).normalized();
+
+        assert!((normal.magnitude() - 1.0).abs() < 1e-4);
+        assert!((normal - expected).magnitude() < 1e-4, "Expected normal {:?} but got {:?}", expected, normal);
+        assert!(normal.dot(world_position - center) > 0.0);
+    }
+}
+
+#[test]
+fn anchor_look_at() {
+    let mut anchor = Anchor::new();
+    assert!((anchor.forward() - Vector3::forward()).is_zero());
+    assert!((anchor.right() - Vector3::right()).is_zero());
+    assert!((anchor.up() - Vector3::up()).is_zero());
+
+    anchor.set_position(Point::new(1.0, 2.0, 3.0));
+    anchor.look_at(Point::new(4.0, 2.0, -1.0), Vector3::up());
+    assert!((anchor.forward() - Vector3::new(0.6, 0.0, -0.8)).is_zero());
+    assert!((anchor.up() - Vector3::up()).is_zero());
+    assert!((anchor.right() - Vector3::new(0.8, 0.0, 0.6)).is_zero());
+
+    // Looking straight down, parallel to the up vector, picks another up axis instead of
+    // producing NaNs.
+    anchor.look_at(Point::new(1.0, -5.0, 3.0), Vector3::up());
+    assert!((anchor.forward() - Vector3::down()).is_zero());
+    assert!(anchor.up().is_normalized());
+    assert!(anchor.right().is_normalized());
+
+    // Looking at its own position leaves the anchor as it was.
+    let orientation = anchor.orientation();
+    anchor.look_at(Point::new(1.0, 2.0, 3.0), Vector3::up());
+    assert_eq!(anchor.orientation(), orientation);
+}
+
+#[test]
+fn draw_visibility_and_layers() {
+    let window = Window::new("draw_visibility_and_layers").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let mut left_camera = Camera::default();
+    left_camera.set_anchor(renderer.register_anchor(Anchor::new()));
+    left_camera.set_viewport(Some(ViewportRect::new(0.0, 0.0, 0.5, 1.0)));
+    left_camera.set_layers(1 | 2);
+    renderer.register_camera(left_camera);
+
+    let mut right_camera = Camera::default();
+    right_camera.set_anchor(renderer.register_anchor(Anchor::new()));
+    right_camera.set_viewport(Some(ViewportRect::new(0.5, 0.0, 0.5, 1.0)));
+    renderer.register_camera(right_camera);
+
+    // One mesh instance on the default layer, seen by both cameras, and one on a layer only
+    // the left camera sees.
+    let gpu_mesh = renderer.register_mesh(&triangle()).unwrap();
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(anchor_id);
+    let default_layer = renderer.register_mesh_instance(mesh_instance);
+
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(anchor_id);
+    mesh_instance.set_layers(2);
+    renderer.register_mesh_instance(mesh_instance);
+
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 3);
+
+    // Hidden mesh instances aren't drawn by any camera.
+    renderer.get_mesh_instance_mut(default_layer).unwrap().set_visible(false);
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 1);
+
+    renderer.get_mesh_instance_mut(default_layer).unwrap().set_visible(true);
+    assert_eq!(renderer.draw().draw_calls, 3);
+}
+
+#[test]
+fn uniform_overrides() {
+    let window = Window::new("uniform_overrides").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let mut material = renderer.default_material();
+    material.set_color("surface_color", Color::rgb(1.0, 1.0, 1.0));
+
+    let gpu_mesh = renderer.register_mesh(&quad()).unwrap();
+    let mut mesh_instance_ids = Vec::new();
+    for _ in 0..super::INSTANCING_THRESHOLD + 2 {
+        let mut mesh_instance = MeshInstance::new(gpu_mesh, material.clone());
+        mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+        mesh_instance_ids.push(renderer.register_mesh_instance(mesh_instance));
+    }
+
+    // The override wins over the material's value without changing the material, and pulls the
+    // mesh instance out of the instanced draw.
+    let red = MaterialProperty::Color(Color::rgb(1.0, 0.0, 0.0));
+    {
+        let tinted = renderer.get_mesh_instance_mut(mesh_instance_ids[0]).unwrap();
+        tinted.set_uniform_override("surface_color", red.clone());
+        assert_eq!(tinted.get_uniform_override("surface_color"), Some(&red));
+        assert_eq!(tinted.material().get_color("surface_color"), Some(&Color::rgb(1.0, 1.0, 1.0)));
+    }
+
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.instanced_draw_calls, 1);
+    assert_eq!(stats.individual_draw_calls, 1);
+
+    // Clearing the override restores the material's value.
+    {
+        let tinted = renderer.get_mesh_instance_mut(mesh_instance_ids[0]).unwrap();
+        assert_eq!(tinted.clear_uniform_override("surface_color"), Some(red));
+        assert_eq!(tinted.get_uniform_override("surface_color"), None);
+    }
+
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.instanced_draw_calls, 1);
+    assert_eq!(stats.individual_draw_calls, 0);
+}
+
+#[test]
+fn iterate_registered_objects() {
+    let window = Window::new("iterate_registered_objects").unwrap();
+    let mut gl_render = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    // The iterators are usable through a trait object.
+    let renderer: &mut Renderer = &mut gl_render;
+
+    let anchor_ids = (0..3).map(|_| renderer.register_anchor(Anchor::new())).collect::<Vec<_>>();
+    let gpu_mesh = renderer.register_mesh(&triangle()).unwrap();
+    let default_material = renderer.default_material();
+    let mesh_instance_id = renderer.register_mesh_instance(MeshInstance::new(gpu_mesh, default_material.clone()));
+    let light_ids = (0..2)
+        .map(|_| renderer.register_light(Light::directional(Vector3::new(0.0, 0.0, -1.0), 1.0, Color::rgb(1.0, 1.0, 1.0))))
+        .collect::<Vec<_>>();
+    let camera_id = renderer.register_camera(Camera::default());
+    let material_id = renderer.register_material(default_material);
+
+    let found_anchors = renderer.anchors().map(|(id, _)| id).collect::<HashSet<_>>();
+    assert_eq!(found_anchors, anchor_ids.iter().cloned().collect::<HashSet<_>>());
+
+    assert_eq!(renderer.mesh_instances().map(|(id, _)| id).collect::<Vec<_>>(), vec![mesh_instance_id]);
+    assert_eq!(renderer.lights().count(), light_ids.len());
+    assert_eq!(renderer.cameras().map(|(id, _)| id).collect::<Vec<_>>(), vec![camera_id]);
+    assert!(renderer.materials().any(|(id, _)| id == material_id));
+
+    // Changes made through the mutable iterators are visible through the getters.
+    for (_, light) in renderer.lights_mut() {
+        light.set_enabled(false);
+    }
+    assert!(light_ids.iter().all(|&light_id| !renderer.get_light(light_id).unwrap().enabled()));
+
+    for (_, mesh_instance) in renderer.mesh_instances_mut() {
+        mesh_instance.set_visible(false);
+    }
+    assert!(!renderer.get_mesh_instance(mesh_instance_id).unwrap().visible());
+
+    for (_, anchor) in renderer.anchors_mut() {
+        anchor.set_position(Point::new(1.0, 2.0, 3.0));
+    }
+    assert_eq!(renderer.get_anchor(anchor_ids[0]).unwrap().position(), Point::new(1.0, 2.0, 3.0));
+
+    for (_, camera) in renderer.cameras_mut() {
+        camera.set_priority(5);
+    }
+    assert_eq!(renderer.get_camera(camera_id).unwrap().priority(), 5);
+}
+
+#[test]
+fn frame_stats() {
+    let window = Window::new("frame_stats").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+    assert_eq!(renderer.frame_stats(), FrameStats::default());
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let mut light = Light::directional(Vector3::new(0.0, 0.0, -1.0), 1.0, Color::rgb(1.0, 1.0, 1.0));
+    renderer.register_light(light);
+    light.set_enabled(false);
+    renderer.register_light(light);
+
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    let gpu_mesh = renderer.register_mesh(&quad()).unwrap();
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(anchor_id);
+    mesh_instance.set_visible(false);
+    renderer.register_mesh_instance(mesh_instance);
+
+    let render_stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+
+    // The default material draws the visible quad once with all of the lights.
+    let stats = renderer.frame_stats();
+    assert_eq!(stats.render, render_stats);
+    assert_eq!(stats.render.draw_calls, 1);
+    assert_eq!(stats.triangles, 2);
+    assert_eq!(stats.instances_culled, 1);
+    assert_eq!(stats.lights_processed, 1);
+    assert_eq!(stats.lit_draws, 1);
+    assert_eq!(stats.draw_lights, 1);
+    assert_eq!(stats.average_lights_per_draw(), 1.0);
+
+    // The statistics only cover the last frame.
+    renderer.draw();
+    assert_eq!(renderer.frame_stats().triangles, 2);
+}
+
+#[test]
+fn draw_lines_and_points() {
+    let window = Window::new("draw_lines_and_points").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 256).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    // Shadows and several lights would each add draws for a triangle mesh.
+    let mut light = Light::directional(Vector3::new(0.0, -1.0, -1.0), 1.0, Color::rgb(1.0, 1.0, 1.0));
+    light.set_casts_shadows(true);
+    renderer.register_light(light);
+    renderer.register_light(Light::directional(Vector3::new(0.0, 0.0, -1.0), 1.0, Color::rgb(1.0, 1.0, 1.0)));
+
+    // The outline of the quad, as line segments, a line strip, and its corners as points.
+    let meshes = [
+        (Topology::Lines, &[0, 1, 1, 2, 2, 3, 3, 0][..]),
+        (Topology::LineStrip, &[0, 1, 2, 3, 0][..]),
+        (Topology::Points, &[0, 1, 2, 3][..]),
+    ];
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    for &(topology, indices) in &meshes {
+        let mesh = MeshBuilder::new()
+            .set_position_data(Point::slice_from_f32_slice(&QUAD_POSITIONS))
+            .set_indices(indices)
+            .set_topology(topology)
+            .build()
+            .unwrap();
+        let mut mesh_instance = MeshInstance::new(renderer.register_mesh(&mesh).unwrap(), renderer.default_material());
+        mesh_instance.set_anchor(anchor_id);
+        renderer.register_mesh_instance(mesh_instance);
+    }
+
+    // Each mesh is drawn once, unlit and without a shadow pass.
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, 3);
+    assert_eq!(renderer.frame_stats().triangles, 0);
+
+    // Incomplete line segments are rejected.
+    let mesh = MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&QUAD_POSITIONS))
+        .set_indices(&[0, 1, 2])
+        .set_topology(Topology::Lines)
+        .build()
+        .unwrap();
+    match renderer.register_mesh(&mesh) {
+        Err(MeshError::IncompletePrimitive { topology: Topology::Lines, index_count: 3 }) => {},
+        result => panic!("Expected an incomplete primitive error but got {:?}", result),
+    }
+}
+
+#[test]
+fn fog() {
+    let window = Window::new("fog").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(0.0, 0.0, 5.0));
+    let camera_anchor = renderer.register_anchor(camera_anchor);
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    // Custom materials can read the fog uniforms without declaring them.
+    let fogged_source = MaterialSource::from_str(r#"
+        property surface_color: Color = (1.0, 1.0, 1.0, 1.0);
+
+        program frag {
+            float fog = fog_mode == 0 ? 0.0 : clamp(-@vertex.view_position.z * fog_params.x, 0.0, 1.0);
+            @color = vec4(mix(surface_color.rgb, fog_color.rgb, fog), 1.0);
+        }
+    "#).unwrap();
+    let fogged_material = renderer.build_material(fogged_source).unwrap();
+
+    let gpu_mesh = renderer.register_mesh(&quad()).unwrap();
+    for material in vec![renderer.default_material(), fogged_material] {
+        let mut mesh_instance = MeshInstance::new(gpu_mesh, material);
+        mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+        renderer.register_mesh_instance(mesh_instance);
+    }
+
+    // Use more lights than fit in one batch so that the blended batches are drawn too.
+    for _ in 0..super::MAX_LIGHTS + 1 {
+        let mut light_anchor = Anchor::new();
+        light_anchor.set_position(Point::new(0.0, 0.0, 1.0));
+        let mut light = Light::point(1.0, 1.0, Color::rgb(1.0, 1.0, 1.0));
+        light.set_anchor(renderer.register_anchor(light_anchor));
+        renderer.register_light(light);
+    }
+
+    let fogs = [
+        Some(Fog { color: Color::rgb(0.5, 0.6, 0.7), mode: FogMode::Linear { start: 1.0, end: 10.0 } }),
+        Some(Fog { color: Color::rgb(0.5, 0.6, 0.7), mode: FogMode::Exp { density: 0.2 } }),
+        None,
+    ];
+    for &fog in &fogs {
+        renderer.set_fog(fog);
+        assert_eq!(renderer.fog, fog);
+
+        renderer.draw();
+        assert_eq!(renderer.context.check_error(), None);
+    }
+}
+
+/// Draws the scene from the hello_triangle example, a red triangle lit only by white ambient
+/// light, and compares it to a reference image.
+///
+/// The scene is drawn by a headless renderer so that no window is needed and nothing else can
+/// draw over the frame. The reference covers the center 192x192 pixels of an 800x800 frame, which
+/// holds the whole triangle.
+///
+/// `resources/captures/hello_triangle.tga` wasn't captured from a GPU. It was rasterized in
+/// software from the triangle's projected vertices (the camera is 10 units away with the default
+/// 60 degree field of view), with every pixel whose center is inside the triangle colored pure
+/// red and every other pixel black. Pixels on the triangle's edges may be rasterized differently
+/// by different drivers, so each channel may be off by a little and a small number of pixels are
+/// allowed to differ entirely.
+#[test]
+fn capture_matches_reference() {
+    const SIZE: usize = 192;
+    const FRAME_SIZE: usize = 800;
+
+    let mut renderer = GlRender::headless(FRAME_SIZE as i32, FRAME_SIZE as i32, true, 1024).unwrap();
+
+    let gpu_mesh = renderer.register_mesh(&triangle()).unwrap();
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+    mesh_instance.material_mut().set_color("surface_color", Color::rgb(1.0, 0.0, 0.0));
+    renderer.register_mesh_instance(mesh_instance);
+
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(0.0, 0.0, 10.0));
+    let mut camera = Camera::default();
+    camera.set_anchor(renderer.register_anchor(camera_anchor));
+    renderer.register_camera(camera);
+
+    renderer.set_ambient_light(Color::rgb(1.0, 1.0, 1.0));
+    renderer.draw();
+
+    let capture = renderer.capture_frame();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!((capture.width(), capture.height()), (FRAME_SIZE, FRAME_SIZE));
+
+    // Saving and loading the capture keeps every pixel.
+    let capture_path = env::temp_dir().join("polygon_capture_matches_reference.tga");
+    renderer.capture_to_file(&capture_path).unwrap();
+    assert_eq!(Image::load_tga(&capture_path).unwrap(), capture);
+
+    let reference = Image::load_tga("resources/captures/hello_triangle.tga").unwrap();
+    assert_eq!((reference.width(), reference.height()), (SIZE, SIZE));
+
+    // Alpha is left out of the comparison, only the color is drawn by the scene.
+    let offset = (capture.width() - SIZE) / 2;
+    let mut mismatches = 0;
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let expected = reference.pixel(x, y);
+            let actual = capture.pixel(x + offset, y + offset);
+            if (0..3).any(|channel| (expected[channel] as i32 - actual[channel] as i32).abs() > 8) {
+                mismatches += 1;
+            }
+        }
+    }
+
+    assert!(
+        mismatches <= SIZE * SIZE / 100,
+        "{} pixels differ from the reference, the capture was saved to {}",
+        mismatches,
+        capture_path.display());
+}
+
+#[test]
+fn bitmap_font() {
+    let texture = || Texture2d::new(32, 16, DataFormat::Rgba, TextureData::u8x4(vec![(255, 255, 255, 255); 32 * 16]));
+
+    let font = BitmapFont::new(texture(), r#"
+        # A tall glyph and a narrow one.
+        line_height 16
+        glyph 65 0 0 8 16 10
+        glyph 63 8 4 4 8 6
+    "#).unwrap();
+    assert_eq!(font.line_height(), 16);
+    assert_eq!(font.glyph('A'), Some(&Glyph { x: 0, y: 0, width: 8, height: 16, advance: 10 }));
+    assert_eq!(font.glyph('B'), None);
+
+    // Glyphs are scaled to the line size, missing glyphs fall back to '?', and each newline
+    // returns to the start of the line.
+    let mut quads = Vec::new();
+    font.layout("AB\r\nA", (100.0, 50.0), 32.0, &mut quads);
+    assert_eq!(quads, vec![
+        GlyphQuad { min: (100.0, 50.0), max: (116.0, 82.0), uv_min: (0.0, 1.0), uv_max: (0.25, 0.0) },
+        GlyphQuad { min: (120.0, 50.0), max: (128.0, 66.0), uv_min: (0.25, 0.75), uv_max: (0.375, 0.25) },
+        GlyphQuad { min: (100.0, 82.0), max: (116.0, 114.0), uv_min: (0.0, 1.0), uv_max: (0.25, 0.0) },
+    ]);
+
+    assert_eq!(
+        BitmapFont::new(texture(), "line_height 8\nglyph 65 0 0 8").unwrap_err(),
+        FontError::InvalidLine { line: 2, text: "glyph 65 0 0 8".into() });
+    assert_eq!(BitmapFont::new(texture(), "glyph 65 0 0 8 8 8").unwrap_err(), FontError::MissingLineHeight);
+    assert_eq!(
+        BitmapFont::new(texture(), "line_height 8\nglyph 65 30 0 8 8 8").unwrap_err(),
+        FontError::GlyphOutOfBounds { character: 'A' });
+
+    // The embedded font covers printable ASCII with 8x8 glyphs.
+    let embedded = BitmapFont::embedded();
+    assert_eq!(embedded.line_height(), 8);
+    for character in (0x20u8..0x7F).map(|code| code as char) {
+        let glyph = embedded.glyph(character).unwrap();
+        assert_eq!((glyph.width, glyph.height, glyph.advance), (8, 8, 8));
+    }
+    assert_eq!(embedded.glyph('\u{7F}'), None);
+}
+
+#[test]
+fn draw_text() {
+    let window = Window::new("draw_text").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    // Text is drawn even when there are no cameras.
+    renderer.draw_text("Hello,\nworld!", (10.0, 10.0), 16.0, Color::rgb(1.0, 1.0, 1.0));
+    renderer.draw_text("?", (10.0, 100.0), 32.0, Color::new(1.0, 0.0, 0.0, 0.5));
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+
+    // Both calls are drawn with one draw call, two triangles per visible character.
+    let stats = renderer.frame_stats();
+    assert_eq!(stats.render.draw_calls, 1);
+    assert_eq!(stats.triangles, 13 * 2);
+
+    // The vertices are cleared every frame, but their storage is kept.
+    assert!(renderer.text_vertices.is_empty());
+    let capacity = renderer.text_vertices.capacity();
+    renderer.draw_text("Hello", (10.0, 10.0), 16.0, Color::rgb(1.0, 1.0, 1.0));
+    assert_eq!(renderer.text_vertices.capacity(), capacity);
+
+    renderer.set_font(BitmapFont::embedded());
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(renderer.frame_stats().triangles, 5 * 2);
+
+    // Text isn't retained between frames.
+    renderer.draw();
+    assert_eq!(renderer.frame_stats().render.draw_calls, 0);
+}
+
+#[test]
+fn sprites() {
+    let window = Window::new("sprites").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let small = renderer.register_texture(&white_texture());
+    let large = renderer.register_texture(&Texture2d::new(
+        4,
+        2,
+        DataFormat::Rgba,
+        TextureData::u8x4(vec![(255, 255, 255, 255); 8])));
+
+    // Sprites start out the size of their texture.
+    let large_id = renderer.register_sprite(large);
+    assert_eq!(renderer.get_sprite(large_id).unwrap().size, Vector2::new(4.0, 2.0));
+    let small_ids = (0..2).map(|_| renderer.register_sprite(small)).collect::<Vec<_>>();
+    assert_eq!(renderer.sprites().count(), 3);
+
+    // The anchor point offsets the sprite from its position.
+    {
+        let sprite = renderer.get_sprite_mut(large_id).unwrap();
+        sprite.position = Vector2::new(100.0, 50.0);
+        sprite.anchor_point = Vector2::new(0.5, 1.0);
+        assert_eq!(sprite.corners(), (Vector2::new(98.0, 48.0), Vector2::new(102.0, 50.0)));
+    }
+
+    // Sprites are drawn with one draw call per texture, even without any cameras.
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    let stats = renderer.frame_stats();
+    assert_eq!(stats.render.draw_calls, 2);
+    assert_eq!(stats.render.program_switches, 1);
+    assert_eq!(stats.triangles, 3 * 2);
+
+    // Text shares the sprites' program.
+    renderer.draw_text("Hi", (0.0, 0.0), 8.0, Color::rgb(1.0, 1.0, 1.0));
+    renderer.draw();
+    assert_eq!(renderer.frame_stats().render.draw_calls, 3);
+    assert_eq!(renderer.frame_stats().render.program_switches, 1);
+
+    // Adding more sprites than the buffers were sized for grows them.
+    for _ in 0..RESOURCE_COUNT {
+        renderer.register_sprite(small);
+    }
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert!(renderer.sprite_capacity >= RESOURCE_COUNT + 3);
+    assert_eq!(renderer.frame_stats().triangles, (RESOURCE_COUNT + 3) * 2);
+
+    // Sprites with an unregistered texture are still drawn.
+    renderer.unregister_texture(large);
+    for &sprite_id in &small_ids {
+        renderer.destroy_sprite(sprite_id);
+    }
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(renderer.frame_stats().triangles, (RESOURCE_COUNT + 1) * 2);
+}
+
+/// Checks that the rotation and scale parts of two transforms match.
+fn assert_rotation_scale_eq(actual: Matrix4, expected: Matrix4) {
+    for row in 0..3 {
+        for col in 0..3 {
+            assert!(
+                (actual[row][col] - expected[row][col]).abs() < 1e-4,
+                "Rotation and scale differ at row {}, column {}, expected: {:?}, actual: {:?}",
+                row,
+                col,
+                expected,
+                actual);
+        }
+    }
+}
+
+#[test]
+fn billboards() {
+    let window = Window::new("billboards").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    // A grid of rotated and non-uniformly scaled quads, alternating between the billboard modes.
+    let scale = Vector3::new(0.5, 2.0, 1.0);
+    let gpu_mesh = renderer.register_mesh(&quad()).unwrap();
+    let mut billboards = Vec::new();
+    for x in -2..3 {
+        for z in -2..3 {
+            let mut anchor = Anchor::new();
+            anchor.set_position(Point::new(x as f32 * 2.0, 0.0, z as f32 * 2.0));
+            anchor.set_orientation(Orientation::from_eulers(Rad(0.3 * x as f32), Rad(0.7), Rad(0.2 * z as f32)));
+            anchor.set_scale(scale);
+            let anchor_id = renderer.register_anchor(anchor);
+
+            let billboard = if (x + z) % 2 == 0 { BillboardMode::Spherical } else { BillboardMode::Cylindrical };
+            let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+            mesh_instance.set_anchor(anchor_id);
+            mesh_instance.set_billboard(billboard);
+            renderer.register_mesh_instance(mesh_instance);
+
+            billboards.push((anchor_id, billboard));
+        }
+    }
+
+    let camera_anchor_id = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor_id);
+    renderer.register_camera(camera);
+
+    // Orbit the camera around the grid, looking down at it from above.
+    for step in 0..8 {
+        let angle = step as f32 * PI / 4.0;
+        {
+            let camera_anchor = renderer.get_anchor_mut(camera_anchor_id).unwrap();
+            camera_anchor.set_position(Point::new(angle.cos() * 10.0, 5.0, angle.sin() * 10.0));
+            camera_anchor.look_at(Point::origin(), Vector3::up());
+        }
+
+        renderer.draw();
+        assert_eq!(renderer.context.check_error(), None);
+
+        let camera_transform = renderer.world_transforms[&camera_anchor_id];
+        let view_transform = camera_transform.view_matrix();
+        let mut camera_back = camera_transform.inverse_view_matrix().z_part().set_y(0.0);
+        camera_back.normalize();
+
+        for &(anchor_id, billboard) in &billboards {
+            let anchor = renderer.world_transforms[&anchor_id];
+            let (model_transform, _) = super::billboard_transforms(billboard, &anchor, &camera_transform);
+            assert!((model_transform.translation_part() - anchor.position()).is_zero());
+
+            match billboard {
+                // Only the anchor's scale is left once the view transform is applied.
+                BillboardMode::Spherical => assert_rotation_scale_eq(
+                    view_transform * model_transform,
+                    Matrix4::from_scale_vector(scale)),
+
+                // The quad stays upright and turns to face the camera's horizontal direction.
+                BillboardMode::Cylindrical => {
+                    let mut facing = Matrix4::identity();
+                    let right = Vector3::cross(Vector3::up(), camera_back);
+                    for (col, axis) in [right, Vector3::up(), camera_back].iter().enumerate() {
+                        facing[0][col] = axis.x;
+                        facing[1][col] = axis.y;
+                        facing[2][col] = axis.z;
+                    }
+                    assert_rotation_scale_eq(model_transform, facing * Matrix4::from_scale_vector(scale));
+                },
+
+                BillboardMode::None => unreachable!(),
+            }
+        }
+    }
+}
+
+/// A cylinder of radius 1 along the Y axis from 0 to 2, with rings of vertices at 0, 1, and 2.
+///
+/// The bottom ring follows bone 0, the top ring follows bone 1, and the middle ring is split
+/// evenly between them.
+fn two_bone_cylinder(segments: usize) -> Mesh {
+    let mut positions = Vec::new();
+    let mut bone_indices = Vec::new();
+    let mut bone_weights = Vec::new();
+    for ring in 0..3 {
+        let weight = ring as f32 * 0.5;
+        for segment in 0..segments {
+            let angle = 2.0 * PI * segment as f32 / segments as f32;
+            positions.push(Point::new(angle.cos(), ring as f32, -angle.sin()));
+            bone_indices.push([0, 1, 0, 0]);
+            bone_weights.push([1.0 - weight, weight, 0.0, 0.0]);
+        }
+    }
+
+    let vertex = |ring: usize, segment: usize| (ring * segments + segment % segments) as u32;
+    let mut indices = Vec::new();
+    for ring in 0..2 {
+        for segment in 0..segments {
+            indices.extend(&[vertex(ring, segment), vertex(ring, segment + 1), vertex(ring + 1, segment + 1)]);
+            indices.extend(&[vertex(ring, segment), vertex(ring + 1, segment + 1), vertex(ring + 1, segment)]);
+        }
+    }
+
+    MeshBuilder::new()
+        .set_position_data(&*positions)
+        .set_bone_index_data(&*bone_indices)
+        .set_bone_weight_data(&*bone_weights)
+        .set_indices(&*indices)
+        .generate_normals(None)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn skinning() {
+    let window = Window::new("skinning").unwrap();
+    let mut renderer = GlRender::new(&window, true, 0, 1024).unwrap();
+
+    let camera_anchor = renderer.register_anchor(Anchor::new());
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor);
+    renderer.register_camera(camera);
+
+    let mesh = two_bone_cylinder(8);
+    assert!(mesh.is_skinned());
+
+    // Bend the upper bone 90 degrees around Z at the middle of the cylinder.
+    let mut skeleton = Skeleton::new();
+    let lower = skeleton.add_bone(None, Matrix4::identity());
+    let upper = skeleton.add_bone(Some(lower), Matrix4::translation(0.0, -1.0, 0.0));
+    let bind_pose = skeleton.pose(&[Matrix4::identity(), Matrix4::translation(0.0, 1.0, 0.0)]);
+    assert_eq!(bind_pose, Pose::new(2));
+    let pose = skeleton.pose(&[
+        Matrix4::identity(),
+        Matrix4::translation(0.0, 1.0, 0.0) * Matrix4::rotation(0.0, 0.0, PI / 2.0),
+    ]);
+    assert_eq!(skeleton.parent(upper), Some(lower));
+
+    // Check the vertices at the start of each ring against positions worked out by hand. The
+    // bottom doesn't move, the top swings over to the left, and the middle is halfway between.
+    let expected = [Point::new(1.0, 0.0, 0.0), Point::new(0.5, 1.5, 0.0), Point::new(-1.0, 2.0, 0.0)];
+    for (ring, &expected) in expected.iter().enumerate() {
+        let skinned = pose.skin_point(Point::new(1.0, ring as f32, 0.0), [0, 1, 0, 0], [1.0 - ring as f32 * 0.5, ring as f32 * 0.5, 0.0, 0.0]);
+        assert!((skinned - expected).magnitude() < 1e-5, "Expected {:?} but got {:?}", expected, skinned);
+    }
+
+    // Bones missing from the pose leave the vertex where it is.
+    let position = Point::new(1.0, 2.0, 3.0);
+    assert_eq!(Pose::new(0).skin_point(position, [3, 3, 3, 3], [0.25, 0.25, 0.25, 0.25]), position);
+
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
+    let anchor_id = renderer.register_anchor(Anchor::new());
+    let default_shader = *renderer.default_material().shader();
+    assert!(renderer.skinned_programs.contains_key(&default_shader));
+
+    // Skinned mesh instances are never instanced, and draw in their bind pose without a pose.
+    let mut ids = Vec::new();
+    for _ in 0..super::INSTANCING_THRESHOLD + 1 {
+        let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+        mesh_instance.set_anchor(anchor_id);
+        ids.push(renderer.register_mesh_instance(mesh_instance));
+    }
+    let stats = renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+    assert_eq!(stats.draw_calls, ids.len());
+    assert_eq!(stats.instanced_draw_calls, 0);
+    assert_eq!(stats.program_switches, 1);
+
+    for &id in &ids {
+        renderer.get_mesh_instance_mut(id).unwrap().set_pose(&pose);
+    }
+    assert_eq!(renderer.get_mesh_instance(ids[0]).unwrap().pose(), Some(&pose));
+    renderer.draw();
+    assert_eq!(renderer.context.check_error(), None);
+
+    // Bone indices past the end of the bone array are rejected.
+    let out_of_bounds = MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&VERTEX_POSITIONS))
+        .set_bone_index_data(&[[0, 0, 0, 0], [0, 0, 0, 0], [MAX_BONES as u32, 0, 0, 0]])
+        .set_bone_weight_data(&[[1.0, 0.0, 0.0, 0.0]; 3])
+        .set_indices(&INDICES)
+        .build()
+        .unwrap();
+    match renderer.register_mesh(&out_of_bounds) {
+        Err(MeshError::BoneOutOfBounds { vertex: 2, bone }) if bone == MAX_BONES as u32 => {},
+        result => panic!("Expected a bone out of bounds error but got {:?}", result),
+    }
+}
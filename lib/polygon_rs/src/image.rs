@@ -0,0 +1,160 @@
+//! Images read back from the renderer.
+//!
+//! Captured frames are saved as uncompressed TGA files, which nearly every image viewer and
+//! editor can open, so that no image encoding library is needed.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::u16;
+
+/// An image with 8 bit RGBA pixels, such as a frame captured with `GlRender::capture_frame()`.
+///
+/// The pixels are tightly packed and stored row by row starting from the top of the image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Creates an image from RGBA pixel data with the rows ordered top-down.
+    ///
+    /// # Panics
+    ///
+    /// - If `pixels` doesn't contain exactly `width * height * 4` bytes.
+    pub fn new(width: usize, height: usize, pixels: Vec<u8>) -> Image {
+        assert!(
+            pixels.len() == width * height * 4,
+            "Wrong amount of image data, width: {}, height: {}, expected bytes: {}, actual bytes: {}",
+            width,
+            height,
+            width * height * 4,
+            pixels.len());
+
+        Image {
+            width: width,
+            height: height,
+            pixels: pixels,
+        }
+    }
+
+    /// Returns the width of the image in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the image in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the RGBA pixel data, with the rows ordered top-down.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Returns the RGBA color of the pixel in column `x` and row `y`, counting rows from the top.
+    ///
+    /// # Panics
+    ///
+    /// - If `x` or `y` is outside the image.
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        assert!(x < self.width && y < self.height, "Pixel ({}, {}) is outside the {}x{} image", x, y, self.width, self.height);
+
+        let start = (y * self.width + x) * 4;
+        [self.pixels[start], self.pixels[start + 1], self.pixels[start + 2], self.pixels[start + 3]]
+    }
+
+    /// Loads an uncompressed 24 or 32 bit TGA file, such as one written by `save_tga()`.
+    pub fn load_tga<P: AsRef<Path>>(path: P) -> io::Result<Image> {
+        let file = File::open(path)?;
+        Image::read_tga(BufReader::new(file))
+    }
+
+    /// Reads an uncompressed 24 or 32 bit TGA image. 24 bit images are given an opaque alpha.
+    pub fn read_tga<R: Read>(mut reader: R) -> io::Result<Image> {
+        let mut header = [0u8; 18];
+        reader.read_exact(&mut header)?;
+
+        let id_length = header[0] as usize;
+        let color_map_type = header[1];
+        let image_type = header[2];
+        let width = header[12] as usize | (header[13] as usize) << 8;
+        let height = header[14] as usize | (header[15] as usize) << 8;
+        let bits_per_pixel = header[16];
+        let top_down = header[17] & 0x20 != 0;
+
+        if color_map_type != 0 || image_type != 2 {
+            return Err(invalid_data(format!("Unsupported TGA image type {} (color map type {}), only uncompressed true color images are supported", image_type, color_map_type)));
+        }
+
+        let bytes_per_pixel = match bits_per_pixel {
+            24 => 3,
+            32 => 4,
+            _ => return Err(invalid_data(format!("Unsupported TGA pixel depth {}, only 24 and 32 bits are supported", bits_per_pixel))),
+        };
+
+        // Skip the image ID, the color map is known to be empty.
+        let mut id = vec![0u8; id_length];
+        reader.read_exact(&mut id)?;
+
+        let mut data = vec![0u8; width * height * bytes_per_pixel];
+        reader.read_exact(&mut data)?;
+
+        // TGA stores pixels as BGR(A) with the rows ordered bottom-up unless the descriptor says
+        // otherwise.
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for row in 0..height {
+            let source_row = if top_down { row } else { height - row - 1 };
+            let row_data = &data[source_row * width * bytes_per_pixel..(source_row + 1) * width * bytes_per_pixel];
+            for pixel in row_data.chunks(bytes_per_pixel) {
+                let alpha = if bytes_per_pixel == 4 { pixel[3] } else { 255 };
+                pixels.extend_from_slice(&[pixel[2], pixel[1], pixel[0], alpha]);
+            }
+        }
+
+        Ok(Image::new(width, height, pixels))
+    }
+
+    /// Saves the image to `path` as an uncompressed 32 bit TGA file.
+    pub fn save_tga<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        self.write_tga(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Writes the image as an uncompressed 32 bit TGA image.
+    ///
+    /// Returns an error with `io::ErrorKind::InvalidInput` if the image is wider or taller than
+    /// the 65535 pixels that TGA supports.
+    pub fn write_tga<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        if self.width > u16::MAX as usize || self.height > u16::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("A {}x{} image is too large for a TGA file", self.width, self.height)));
+        }
+
+        let mut header = [0u8; 18];
+        header[2] = 2; // Uncompressed true color.
+        header[12] = self.width as u8;
+        header[13] = (self.width >> 8) as u8;
+        header[14] = self.height as u8;
+        header[15] = (self.height >> 8) as u8;
+        header[16] = 32;
+        header[17] = 0x20 | 8; // Top-down rows with 8 bits of alpha.
+        writer.write_all(&header)?;
+
+        let mut data = Vec::with_capacity(self.pixels.len());
+        for pixel in self.pixels.chunks(4) {
+            data.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+        }
+        writer.write_all(&data)
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -14,31 +14,210 @@ pub mod anchor;
 pub mod camera;
 pub mod geometry;
 pub mod gl;
+pub mod image;
 pub mod light;
 pub mod material;
 pub mod mesh_instance;
+pub mod queue;
 pub mod shader;
+pub mod skeleton;
+pub mod sprite;
+pub mod text;
 pub mod texture;
 
 use anchor::*;
 use bootstrap::window::Window;
 use camera::*;
-use geometry::mesh::Mesh;
+use geometry::mesh::{Mesh, MeshError};
+use image::Image;
 use light::*;
 use material::*;
-use math::Color;
+use math::{Color, Vector3};
 use mesh_instance::*;
+use sprite::*;
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+use std::time::Duration;
+use text::BitmapFont;
 use texture::*;
 
+/// Statistics about the work done by the renderer to draw a frame.
+///
+/// Useful for checking that changes to a scene or the renderer don't cause more state changes
+/// than expected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderStats {
+    /// The number of draw calls issued.
+    pub draw_calls: usize,
+
+    /// The number of draw calls that used a different shader program than the previous draw.
+    pub program_switches: usize,
+
+    /// The number of textures bound for draw calls.
+    ///
+    /// Every texture used by a draw is currently bound for each draw call, so this is the total
+    /// number of textures used across all draw calls.
+    pub texture_binds: usize,
+
+    /// The number of draw calls that drew a group of mesh instances at once using GPU instancing.
+    pub instanced_draw_calls: usize,
+
+    /// The number of draw calls that drew a single mesh instance.
+    pub individual_draw_calls: usize,
+
+    /// The total size in bytes of the vertex and index data of every registered mesh.
+    pub mesh_bytes: usize,
+}
+
+/// Statistics about the last frame drawn, see `Renderer::frame_stats()`.
+///
+/// Useful for showing a performance overlay in game, or for checking that a scene stays within
+/// a budget in integration tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameStats {
+    /// The draw calls and state changes for the frame, as returned from `Renderer::draw()`.
+    pub render: RenderStats,
+
+    /// The number of triangles submitted across all draw calls, counting each instance of an
+    /// instanced draw.
+    pub triangles: usize,
+
+    /// The number of mesh instances that cameras skipped because they were hidden or not on the
+    /// camera's layers.
+    ///
+    /// Mesh instances are counted once for each camera that skipped them, and once for each of
+    /// their submeshes.
+    pub instances_culled: usize,
+
+    /// The number of enabled lights gathered for drawing, summed over all cameras.
+    pub lights_processed: usize,
+
+    /// The number of mesh draws that lights were picked for, counting each submesh of each
+    /// mesh instance once per camera no matter how many draw calls it took.
+    pub lit_draws: usize,
+
+    /// The number of lights sent to each mesh draw, summed over all `lit_draws`.
+    ///
+    /// Point lights are only sent to the mesh instances that are inside their radius, so this
+    /// is usually much lower than `lights_processed` times `lit_draws`.
+    pub draw_lights: usize,
+
+    /// The number of shadow maps rendered into the shadow atlas, counting each light once per
+    /// camera.
+    pub shadow_maps_rendered: usize,
+
+    /// The number of static lights whose cached shadow map was used without re-rendering it,
+    /// counting each light once per camera. See `ShadowUpdate::Static`.
+    pub shadow_maps_reused: usize,
+
+    /// The CPU time spent in `Renderer::draw()`.
+    pub cpu_time: Duration,
+}
+
+impl FrameStats {
+    /// Gets the average number of lights sent to each mesh draw, or zero if nothing was drawn.
+    pub fn average_lights_per_draw(&self) -> f32 {
+        if self.lit_draws == 0 {
+            0.0
+        } else {
+            self.draw_lights as f32 / self.lit_draws as f32
+        }
+    }
+}
+
+/// Debug visualizations used to track down problems with meshes without editing materials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugMode {
+    /// Draws the scene normally.
+    None,
+
+    /// Draws every mesh as a wireframe.
+    ///
+    /// Each mesh is only drawn once, so materials only receive the first `MAX_LIGHTS` lights,
+    /// and materials that are drawn once per light only show the ambient light.
+    Wireframe,
+
+    /// Draws every mesh colored by its world space normals, mapping each axis from [-1, 1] to
+    /// [0, 1].
+    ///
+    /// Meshes with vertex animation are drawn normally.
+    Normals,
+}
+
+impl Default for DebugMode {
+    fn default() -> DebugMode {
+        DebugMode::None
+    }
+}
+
+/// Distance fog that fades the scene into a solid color, see `Renderer::set_fog()`.
+///
+/// Fog is based on each fragment's depth in view space, so it is the same for every point at
+/// the same distance along the camera's forward axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fog {
+    /// The color that fragments fade into.
+    pub color: Color,
+
+    /// How the amount of fog increases with depth.
+    pub mode: FogMode,
+}
+
+/// The falloff used for distance fog.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FogMode {
+    /// Fog increases linearly from none at `start` to solid at `end`.
+    Linear { start: f32, end: f32 },
+
+    /// Fog increases exponentially with depth, with `1.0 - e^(-density * depth)` of the
+    /// fragment's color being replaced by the fog color.
+    Exp { density: f32 },
+}
+
+/// The light that reaches every surface regardless of the scene's lights, see
+/// `Renderer::set_ambient()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AmbientMode {
+    /// The same ambient color reaches every surface.
+    Flat(Color),
+
+    /// Surfaces facing `up` get the sky color and surfaces facing away from it get the ground
+    /// color, blending between the two based on the angle of the surface's normal.
+    ///
+    /// Gives unlit sides of objects some shape without adding any lights. `up` is in world space
+    /// and doesn't need to be normalized.
+    Hemisphere { sky: Color, ground: Color, up: Vector3 },
+}
+
 /// Identifies mesh data that has been sent to the GPU.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct GpuMesh(usize);
 derive_Counter!(GpuMesh);
 
+/// An iterator over registered objects and their ids, e.g. from `Renderer::mesh_instances()`.
+///
+/// The iterator is boxed so that `Renderer` can still be used as a trait object.
+pub type RegisteredIter<'a, Id, T> = Box<Iterator<Item = (Id, &'a T)> + 'a>;
+
+/// A mutable iterator over registered objects and their ids, e.g. from
+/// `Renderer::mesh_instances_mut()`.
+pub type RegisteredIterMut<'a, Id, T> = Box<Iterator<Item = (Id, &'a mut T)> + 'a>;
+
 /// The common interface that all rendering systems must provide.
-pub trait Renderer: 'static + Send {
+///
+/// A renderer can only be used on the thread that created it. Use a `queue::RenderQueue` to send
+/// work to the renderer from other threads.
+pub trait Renderer: 'static {
     /// Renders one frame based on the renderer's current state to the current render target.
-    fn draw(&mut self);
+    ///
+    /// Returns statistics about the work done to render the frame.
+    fn draw(&mut self) -> RenderStats;
+
+    /// Gets the statistics for the last frame drawn.
+    ///
+    /// The statistics are replaced each time `draw()` finishes, so they never mix multiple
+    /// frames. Before the first frame is drawn every statistic is zero.
+    fn frame_stats(&self) -> FrameStats;
 
     /// Gets a copy of the default material for the renderer.
     fn default_material(&self) -> Material;
@@ -46,18 +225,105 @@ pub trait Renderer: 'static + Send {
     /// Parses a material source file and generates a material from it.
     fn build_material(&mut self, source: MaterialSource) -> Result<Material, BuildMaterialError>;
 
+    /// Rebuilds a material's shader from new source, e.g. after its material file was edited.
+    ///
+    /// The new programs replace the old ones under the material's existing shader, so every mesh
+    /// instance using the shader is drawn with the new programs from the next frame. Properties
+    /// that are still declared with the same type keep their values, new properties are given
+    /// default values, and properties that are no longer declared are removed from `material`.
+    /// Other materials using the same shader, such as copies of `material`, keep their
+    /// properties as they are.
+    ///
+    /// If the new source fails to build the error is returned and both the old programs and
+    /// `material` are left unchanged.
+    fn rebuild_material(&mut self, material: &mut Material, source: MaterialSource) -> Result<(), BuildMaterialError>;
+
     /// Registers a material to be used as a shared material.
     fn register_material(&mut self, material: Material) -> MaterialId;
 
     /// Gets a registered material.
     fn get_material(&self, material_id: MaterialId) -> Option<&Material>;
 
+    /// Iterates over the registered materials, in no particular order.
+    fn materials(&self) -> RegisteredIter<MaterialId, Material>;
+
     /// Registers mesh data with the renderer, returning a unique id for the mesh.
-    fn register_mesh(&mut self, mesh: &Mesh) -> GpuMesh;
+    ///
+    /// Every texcoord channel of the mesh is uploaded, but materials can only read the first two
+    /// as `@vertex.uv0` and `@vertex.uv1`. A warning is printed for meshes with more channels.
+    ///
+    /// Meshes are drawn using their `Topology`. Meshes made of lines or points are drawn once
+    /// without lighting and don't cast shadows. Skinned meshes are drawn in the pose of each mesh
+    /// instance, see `MeshInstance::set_pose()`.
+    ///
+    /// The mesh is checked with `Mesh::validate()` before it's uploaded, and isn't registered if
+    /// it's invalid.
+    fn register_mesh(&mut self, mesh: &Mesh) -> Result<GpuMesh, MeshError>;
+
+    /// Replaces the vertex and index data of a registered mesh.
+    ///
+    /// Mesh instances that use the mesh are drawn with the new data. The mesh's GPU buffers are
+    /// reused, and only grow if the new data doesn't fit in them. The new data doesn't need to
+    /// have the same vertex attributes as the old data.
+    ///
+    /// Like `register_mesh()` the new data is validated first, and the registered mesh is left
+    /// unchanged if it's invalid. Returns `MeshError::UnregisteredMesh` if `mesh_id` isn't a
+    /// registered mesh, e.g. because it was removed with `unregister_mesh()`.
+    fn update_mesh(&mut self, mesh_id: GpuMesh, mesh: &Mesh) -> Result<(), MeshError>;
+
+    /// Removes mesh data from the renderer, freeing the GPU memory it used.
+    ///
+    /// Mesh instances that still use the mesh are skipped with a warning when drawing. Does
+    /// nothing if the mesh isn't registered.
+    fn unregister_mesh(&mut self, mesh: GpuMesh);
+
+    /// Registers mesh data with a baked vertex animation, returning a unique id for the mesh.
+    ///
+    /// `positions` and `normals` are textures previously registered with `register_texture()`.
+    /// They should use `TextureData::f32` so that they're stored in a float format, byte data is
+    /// normalized and can't represent positions outside of the [0, 1] range. Each row of the
+    /// texture holds one frame of the animation and each column holds the data for one vertex, in
+    /// the same order as the vertices in `mesh`. Positions are read from the RGB channels and
+    /// replace the mesh's own vertex positions when drawn. If `normals` is `None` the mesh's own
+    /// normals are used for every frame.
+    ///
+    /// The current frame for each mesh instance is controlled with `MeshInstance::set_vat_time()`
+    /// or `MeshInstance::set_vat_autoplay()`.
+    ///
+    /// Returns `MeshError::VertexTexturesUnsupported` if the implementation can't sample enough
+    /// textures in the vertex stage to play the animation.
+    fn register_vat_mesh(
+        &mut self,
+        mesh: &Mesh,
+        positions: GpuTexture,
+        normals: Option<GpuTexture>,
+    ) -> Result<GpuMesh, MeshError>;
 
     /// Registers texture data with the renderer, returning a unique id for the texture.
     fn register_texture(&mut self, texture: &Texture2d) -> GpuTexture;
 
+    /// Registers the six faces of a cube map with the renderer, returning a unique id for the
+    /// texture.
+    ///
+    /// `faces` must be in the order +X, -X, +Y, -Y, +Z, -Z. The cube map is sampled with the
+    /// faces' filtering and wrap settings. Faces should use `TextureWrap::ClampToEdge`, other
+    /// wrap modes blend each edge with the opposite edge of the same face, showing seams where
+    /// the faces meet. Currently cube maps can only be used as the skybox.
+    ///
+    /// # Panics
+    ///
+    /// - If the faces aren't all square and the same size.
+    /// - If the faces don't all have the same format and type of data.
+    /// - If the faces don't all have the same filtering and wrap settings.
+    fn register_texture_cube(&mut self, faces: &[Texture2d; 6]) -> GpuTexture;
+
+    /// Removes texture data from the renderer, freeing the GPU memory it used.
+    ///
+    /// Materials that still use the texture are drawn with a plain white texture in its place,
+    /// and meshes that use it for vertex animation are drawn without animating. Does nothing if
+    /// the texture isn't registered.
+    fn unregister_texture(&mut self, texture: GpuTexture);
+
     /// Registers a mesh instance with the renderer, returning a unique id for that mesh instance.
     fn register_mesh_instance(&mut self, mesh_instance: MeshInstance) -> MeshInstanceId;
 
@@ -67,6 +333,17 @@ pub trait Renderer: 'static + Send {
     /// Gets a mutable reference to a registered mesh instance.
     fn get_mesh_instance_mut(&mut self, id: MeshInstanceId) -> Option<&mut MeshInstance>;
 
+    /// Removes a mesh instance from the renderer so that it's no longer drawn.
+    ///
+    /// Does nothing if the mesh instance isn't registered.
+    fn destroy_mesh_instance(&mut self, id: MeshInstanceId);
+
+    /// Iterates over the registered mesh instances, in no particular order.
+    fn mesh_instances(&self) -> RegisteredIter<MeshInstanceId, MeshInstance>;
+
+    /// Iterates mutably over the registered mesh instances, in no particular order.
+    fn mesh_instances_mut(&mut self) -> RegisteredIterMut<MeshInstanceId, MeshInstance>;
+
     /// Registers an anchor with the renderer, returning a unique id for the anchor.
     fn register_anchor(&mut self, anchor: Anchor) -> AnchorId;
 
@@ -76,6 +353,19 @@ pub trait Renderer: 'static + Send {
     /// Gets a mutable reference to a registered anchor.
     fn get_anchor_mut(&mut self, anchor_id: AnchorId) -> Option<&mut Anchor>;
 
+    /// Removes an anchor from the renderer.
+    ///
+    /// Mesh instances, cameras, and lights that are still attached to the anchor are skipped
+    /// with a warning when drawing. Anchors parented to the anchor are detached from it. Does
+    /// nothing if the anchor isn't registered.
+    fn destroy_anchor(&mut self, anchor_id: AnchorId);
+
+    /// Iterates over the registered anchors, in no particular order.
+    fn anchors(&self) -> RegisteredIter<AnchorId, Anchor>;
+
+    /// Iterates mutably over the registered anchors, in no particular order.
+    fn anchors_mut(&mut self) -> RegisteredIterMut<AnchorId, Anchor>;
+
     /// Registers a camera with the renderer, returning a unique id for the camera.
     fn register_camera(&mut self, camera: Camera) -> CameraId;
 
@@ -85,6 +375,17 @@ pub trait Renderer: 'static + Send {
     /// Gets a mutable reference to a registered camera.
     fn get_camera_mut(&mut self, camera_id: CameraId) -> Option<&mut Camera>;
 
+    /// Removes a camera from the renderer so that it's no longer rendered.
+    ///
+    /// Does nothing if the camera isn't registered.
+    fn destroy_camera(&mut self, camera_id: CameraId);
+
+    /// Iterates over the registered cameras, in no particular order.
+    fn cameras(&self) -> RegisteredIter<CameraId, Camera>;
+
+    /// Iterates mutably over the registered cameras, in no particular order.
+    fn cameras_mut(&mut self) -> RegisteredIterMut<CameraId, Camera>;
+
     /// Registers a light with the renderer, returning a unique id for the light.
     fn register_light(&mut self, light: Light) -> LightId;
 
@@ -94,28 +395,251 @@ pub trait Renderer: 'static + Send {
     /// Gets a mutable reference to a registered light.
     fn get_light_mut(&mut self, light_id: LightId) -> Option<&mut Light>;
 
-    fn set_ambient_light(&mut self, color: Color);
+    /// Removes a light from the renderer so that it no longer lights the scene.
+    ///
+    /// Does nothing if the light isn't registered.
+    fn destroy_light(&mut self, light_id: LightId);
+
+    /// Iterates over the registered lights, in no particular order.
+    fn lights(&self) -> RegisteredIter<LightId, Light>;
+
+    /// Iterates mutably over the registered lights, in no particular order.
+    fn lights_mut(&mut self) -> RegisteredIterMut<LightId, Light>;
+
+    /// Registers a sprite showing `texture`, returning a unique id for the sprite.
+    ///
+    /// The sprite starts out the size of the texture in pixels, with its top-left corner at the
+    /// top-left corner of the window. Use `get_sprite_mut()` to move it and change how it looks.
+    ///
+    /// Sprites are drawn grouped by texture, and sprites with the same texture are drawn in the
+    /// order they were registered. Sprites that overlap should share a texture, such as a
+    /// texture atlas, so that the one on top is predictable.
+    fn register_sprite(&mut self, texture: GpuTexture) -> SpriteId;
+
+    /// Gets a reference to a registered sprite.
+    fn get_sprite(&self, sprite_id: SpriteId) -> Option<&Sprite>;
+
+    /// Gets a mutable reference to a registered sprite.
+    fn get_sprite_mut(&mut self, sprite_id: SpriteId) -> Option<&mut Sprite>;
+
+    /// Removes a sprite from the renderer so that it's no longer drawn.
+    ///
+    /// Does nothing if the sprite isn't registered.
+    fn destroy_sprite(&mut self, sprite_id: SpriteId);
+
+    /// Iterates over the registered sprites, in no particular order.
+    fn sprites(&self) -> RegisteredIter<SpriteId, Sprite>;
+
+    /// Iterates mutably over the registered sprites, in no particular order.
+    fn sprites_mut(&mut self) -> RegisteredIterMut<SpriteId, Sprite>;
+
+    /// Sets the ambient light that reaches every surface.
+    ///
+    /// The ambient light is passed to every material through the `ambient_mode`,
+    /// `ambient_sky_color`, `ambient_ground_color`, and `ambient_up` uniforms, and materials can
+    /// evaluate it for a world space normal with the built-in `ambient_light()` function.
+    /// `global_ambient` still holds a single color for materials that don't support hemisphere
+    /// lighting, which is the average of the sky and ground colors. The default material uses
+    /// `ambient_light()`.
+    fn set_ambient(&mut self, ambient: AmbientMode);
+
+    /// Sets a flat ambient color, the same as `set_ambient(AmbientMode::Flat(color))`.
+    fn set_ambient_light(&mut self, color: Color) {
+        self.set_ambient(AmbientMode::Flat(color));
+    }
+
+    /// Sets the cube map drawn behind the scene, or removes the skybox if `skybox` is `None`.
+    ///
+    /// The skybox is drawn for every camera after the rest of the scene, centered on the camera
+    /// so that it appears infinitely far away. `skybox` must be registered with
+    /// `register_texture_cube()`, otherwise the skybox isn't drawn.
+    fn set_skybox(&mut self, skybox: Option<GpuTexture>);
+
+    /// Sets the distance fog applied to the scene, or removes the fog if `fog` is `None`.
+    ///
+    /// The fog is passed to every material through the `fog_color`, `fog_params`, and `fog_mode`
+    /// uniforms. `fog_mode` is 0 when there is no fog, 1 for `FogMode::Linear` with `fog_params`
+    /// holding the start and end depth, and 2 for `FogMode::Exp` with `fog_params.x` holding the
+    /// density. The default material applies the fog, other materials have to read the uniforms
+    /// themselves. The skybox is never fogged, so set the clear color to the fog color to fade
+    /// distant objects into the background.
+    fn set_fog(&mut self, fog: Option<Fog>);
+
+    /// Sets the materials run over the whole frame after the cameras have drawn the scene, in
+    /// order, replacing any previous post effects. Passing an empty list removes the effects.
+    ///
+    /// While there are post effects the scene is drawn into an offscreen color and depth target
+    /// the size of the window. Each effect's material is then drawn over a triangle covering the
+    /// screen, reading the output of the previous effect, and the last effect draws into the
+    /// window. Sprites and text are drawn after the effects, so the effects don't apply to them.
+    ///
+    /// Materials that read `scene_color` or `scene_depth` in their fragment program get these
+    /// built-in uniforms:
+    ///
+    /// - `scene_color: sampler2D` - The scene as drawn by the cameras or the previous effect.
+    /// - `scene_depth: sampler2D` - The depth of the scene as drawn by the cameras.
+    /// - `screen_size: Vector2` - The size of the frame in pixels.
+    /// - `time: f32` - The time in seconds since the renderer was created.
+    ///
+    /// `@vertex.uv0` goes from `(0, 0)` in the bottom-left corner of the screen to `(1, 1)` in
+    /// the top-right, so it can be used to sample the scene. Effects should use the default
+    /// vertex program. Materials that aren't registered are skipped.
+    fn set_post_effects(&mut self, effects: Vec<MaterialId>);
+
+    /// Notifies the renderer that the window has been resized.
+    ///
+    /// The renderer draws to the full window and adjusts each camera's aspect ratio to match
+    /// the new dimensions. Headless renderers replace their offscreen framebuffer with one of the
+    /// new size.
+    fn resize(&mut self, width: i32, height: i32);
+
+    /// Sets the debug visualization used to draw the scene.
+    ///
+    /// The debug mode can be changed at any time, and takes effect the next time the scene is
+    /// drawn. Defaults to `DebugMode::None`.
+    fn set_debug_mode(&mut self, debug_mode: DebugMode);
+
+    /// Draws `text` on top of the scene in the next frame.
+    ///
+    /// `position` is the top-left corner of the first line in pixels from the top-left corner
+    /// of the window, and `size` is the height of a line in pixels. Text isn't retained between
+    /// frames, so it must be drawn again every frame that it should be visible. Text is drawn
+    /// after every camera and sprite, blended over the scene without depth testing, in the order
+    /// that `draw_text()` was called.
+    fn draw_text(&mut self, text: &str, position: (f32, f32), size: f32, color: Color);
+
+    /// Sets the font used by `draw_text()`.
+    ///
+    /// Defaults to `BitmapFont::embedded()`.
+    fn set_font(&mut self, font: BitmapFont);
+
+    /// Sets the color that the screen is cleared to before drawing.
+    ///
+    /// Defaults to transparent black.
+    fn set_clear_color(&mut self, color: Color);
+
+    /// Gets the GPU time spent rendering the camera in a recent frame.
+    ///
+    /// GPU timings are measured asynchronously, so the result lags a frame or two behind the
+    /// frame currently being drawn. Returns `None` if the camera hasn't been rendered yet, or if
+    /// the renderer doesn't support GPU timing.
+    fn camera_gpu_time(&self, camera_id: CameraId) -> Option<Duration>;
+
+    /// Captures the frame drawn by the last call to `draw()`.
+    ///
+    /// See `GlRender::capture_frame()` for when the frame can be captured.
+    fn capture_frame(&self) -> Image;
+
+    /// Describes the graphics implementation the renderer is running on and its limits.
+    ///
+    /// The report is meant to be read by people, for example in crash reports, and its format
+    /// may change at any time.
+    fn capability_report(&self) -> String;
 }
 
 /// A helper struct for selecting and initializing the most suitable renderer for the client's
 /// needs.
 pub struct RendererBuilder<'a> {
-    window: &'a Window,
+    target: BuilderTarget<'a>,
+    gamma_correct: bool,
+    samples: u32,
+    shadow_resolution: u32,
+    verbose_material_errors: bool,
 }
 
 impl<'a> RendererBuilder<'a> {
     /// Creates a new builder object.
     pub fn new(window: &Window) -> RendererBuilder {
+        RendererBuilder::with_target(BuilderTarget::Window(window))
+    }
+
+    /// Creates a builder for a renderer that draws into a `width` by `height` offscreen
+    /// framebuffer instead of a window.
+    ///
+    /// Headless renderers don't need a display, which makes them useful for automated rendering
+    /// tests. Use `build_gl()` to get a renderer that can capture the drawn frames. The offscreen
+    /// framebuffer is never antialiased, so `samples()` has no effect.
+    pub fn headless(width: i32, height: i32) -> RendererBuilder<'static> {
+        RendererBuilder::with_target(BuilderTarget::Headless(width, height))
+    }
+
+    fn with_target(target: BuilderTarget) -> RendererBuilder {
         RendererBuilder {
-            window: window,
+            target: target,
+            gamma_correct: true,
+            samples: 0,
+            shadow_resolution: 1024,
+            verbose_material_errors: false,
         }
     }
 
+    /// Sets whether the renderer does gamma-correct rendering.
+    ///
+    /// Defaults to `true`. When enabled, shading is done in linear space and the output is
+    /// converted to sRGB when written to the window, and textures with `ColorSpace::Srgb` are
+    /// converted to linear when sampled. When disabled, shader output and texture data are used
+    /// as-is, which is only correct for shaders that were authored to output gamma encoded color.
+    pub fn gamma_correct(&mut self, gamma_correct: bool) -> &mut RendererBuilder<'a> {
+        self.gamma_correct = gamma_correct;
+        self
+    }
+
+    /// Sets the number of samples per pixel used to antialias the window.
+    ///
+    /// Defaults to 0, which disables antialiasing. If the platform doesn't support the requested
+    /// sample count the closest lower count is used instead.
+    pub fn samples(&mut self, samples: u32) -> &mut RendererBuilder<'a> {
+        self.samples = samples;
+        self
+    }
+
+    /// Sets the width and height of shadow maps, in texels.
+    ///
+    /// Defaults to 1024. Individual lights can override the resolution with
+    /// `Light::set_shadow_resolution()`. Every light's shadow map is packed into one shadow atlas,
+    /// which grows as needed up to the largest texture size the platform supports. Lights that
+    /// don't fit in the atlas don't cast shadows.
+    pub fn shadow_resolution(&mut self, shadow_resolution: u32) -> &mut RendererBuilder<'a> {
+        assert!(shadow_resolution > 0, "Shadow resolution must be greater than zero");
+        self.shadow_resolution = shadow_resolution;
+        self
+    }
+
+    /// Sets whether material build errors include the generated shader source.
+    ///
+    /// Defaults to `false`. The line numbers in shader compile logs refer to the source generated
+    /// from the material file rather than the material file itself, so when enabled the generated
+    /// source of the failing shaders is appended to the log with line numbers.
+    pub fn verbose_material_errors(&mut self, verbose: bool) -> &mut RendererBuilder<'a> {
+        self.verbose_material_errors = verbose;
+        self
+    }
+
     /// Constructs a new renderer using the options set in the builder.
     pub fn build(&mut self) -> Box<Renderer> {
-        let renderer = gl::GlRender::new(self.window).unwrap();
-        Box::new(renderer) as Box<Renderer>
+        Box::new(self.build_gl()) as Box<Renderer>
     }
+
+    /// Constructs a new OpenGL renderer using the options set in the builder.
+    ///
+    /// Unlike `build()` this gives access to the features specific to `GlRender`, such as
+    /// `GlRender::capture_frame()`.
+    pub fn build_gl(&mut self) -> gl::GlRender {
+        let mut renderer = match self.target {
+            BuilderTarget::Window(window) =>
+                gl::GlRender::new(window, self.gamma_correct, self.samples, self.shadow_resolution),
+            BuilderTarget::Headless(width, height) =>
+                gl::GlRender::headless(width, height, self.gamma_correct, self.shadow_resolution),
+        }.unwrap();
+        renderer.set_verbose_material_errors(self.verbose_material_errors);
+        renderer
+    }
+}
+
+/// What a `RendererBuilder` builds a renderer for.
+enum BuilderTarget<'a> {
+    Window(&'a Window),
+    Headless(i32, i32),
 }
 
 /// Extra special secret trait for keep counter functionality local to this crate.
@@ -136,5 +660,47 @@ trait Counter {
     fn next(&mut self) -> Self;
 }
 
+/// An error that occurred while building a material.
 #[derive(Debug)]
-pub struct BuildMaterialError;
+pub enum BuildMaterialError {
+    /// The material source doesn't contain a fragment program.
+    MissingFragmentProgram,
+
+    /// The shader generated from the material's vertex program failed to compile.
+    ///
+    /// The wrapped string is the compile log for the shader.
+    VertexCompile(String),
+
+    /// The shader generated from the material's fragment program failed to compile.
+    ///
+    /// The wrapped string is the compile log for the shader.
+    FragmentCompile(String),
+
+    /// The material's shaders failed to link.
+    ///
+    /// The wrapped string is the link log for the shader program.
+    Link(String),
+
+    /// The material loaded from `path` failed to build.
+    File {
+        path: PathBuf,
+        error: Box<BuildMaterialError>,
+    },
+}
+
+impl Display for BuildMaterialError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            BuildMaterialError::MissingFragmentProgram =>
+                write!(formatter, "Material has no fragment program"),
+            BuildMaterialError::VertexCompile(ref log) =>
+                write!(formatter, "Material vertex shader failed to compile:\n{}", log),
+            BuildMaterialError::FragmentCompile(ref log) =>
+                write!(formatter, "Material fragment shader failed to compile:\n{}", log),
+            BuildMaterialError::Link(ref log) =>
+                write!(formatter, "Material shader failed to link:\n{}", log),
+            BuildMaterialError::File { ref path, ref error } =>
+                write!(formatter, "{}: {}", path.display(), error),
+        }
+    }
+}
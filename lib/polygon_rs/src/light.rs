@@ -7,15 +7,25 @@ pub struct Light {
     pub color: Color,
     pub strength: f32,
     anchor: Option<AnchorId>,
+    enabled: bool,
+    casts_shadows: bool,
+
+    shadow_update: ShadowUpdate,
+    shadow_resolution: Option<u32>,
 }
 
 impl Light {
     pub fn point(radius: f32, strength: f32, color: Color) -> Light {
         Light {
-            data: LightData::Point { radius: radius },
+            data: LightData::Point { radius: radius, falloff: Falloff::default() },
             color: color,
             strength: strength,
             anchor: None,
+            enabled: true,
+            casts_shadows: false,
+
+            shadow_update: ShadowUpdate::Dynamic,
+            shadow_resolution: None,
         }
     }
 
@@ -25,6 +35,11 @@ impl Light {
             color: color,
             strength: strength,
             anchor: None,
+            enabled: true,
+            casts_shadows: false,
+
+            shadow_update: ShadowUpdate::Dynamic,
+            shadow_resolution: None,
         }
     }
 
@@ -35,15 +50,157 @@ impl Light {
     pub fn set_anchor(&mut self, anchor_id: AnchorId) {
         self.anchor = Some(anchor_id);
     }
+
+    /// Returns `true` if the light is enabled.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turns the light on or off.
+    ///
+    /// Disabled lights are skipped when rendering but stay registered with the renderer, so they
+    /// can be turned back on later. Lights are enabled by default.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Returns `true` if the light casts shadows.
+    pub fn casts_shadows(&self) -> bool {
+        self.casts_shadows
+    }
+
+    /// Sets whether the light casts shadows.
+    ///
+    /// Lights don't cast shadows by default. Currently only directional lights cast shadows. Each
+    /// shadow casting light gets its own tile in the renderer's shadow atlas for every camera.
+    pub fn set_casts_shadows(&mut self, casts_shadows: bool) {
+        self.casts_shadows = casts_shadows;
+    }
+
+    /// Sets how the light's intensity falls off with distance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the light isn't a point light, since directional lights don't fall off.
+    pub fn set_falloff(&mut self, falloff: Falloff) {
+        match self.data {
+            LightData::Point { falloff: ref mut point_falloff, .. } => *point_falloff = falloff,
+            LightData::Directional { .. } => panic!("Cannot set falloff for a directional light"),
+        }
+    }
+
+    /// Gets how often the light's shadow map is regenerated.
+    pub fn shadow_update(&self) -> ShadowUpdate {
+        self.shadow_update
+    }
+
+    /// Sets how often the light's shadow map is regenerated.
+    ///
+    /// Lights default to `ShadowUpdate::Dynamic`. See `ShadowUpdate` for more information.
+    pub fn set_shadow_update(&mut self, shadow_update: ShadowUpdate) {
+        self.shadow_update = shadow_update;
+    }
+
+    /// Gets the shadow map resolution override for the light, if any.
+    pub fn shadow_resolution(&self) -> Option<u32> {
+        self.shadow_resolution
+    }
+
+    /// Overrides the renderer's default shadow map resolution for this light.
+    ///
+    /// Passing `None` makes the light use the renderer's default resolution. Small static lights
+    /// can use a lower resolution to save space in the shadow atlas. Changing the resolution
+    /// moves the light's tile, so it's re-rendered even for static lights.
+    pub fn set_shadow_resolution(&mut self, resolution: Option<u32>) {
+        debug_assert!(
+            resolution.map(|res| res > 0).unwrap_or(true),
+            "Shadow resolution must be greater than zero");
+        self.shadow_resolution = resolution;
+    }
+}
+
+/// Specifies when a light's shadow map needs to be re-rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShadowUpdate {
+    /// The shadow map is re-rendered every frame.
+    Dynamic,
+
+    /// The shadow map is rendered once and reused until something in it changes.
+    ///
+    /// The cached shadow map is re-rendered when a shadow-casting mesh instance moves into, out
+    /// of, or within the region it covers, when mesh instances are registered, destroyed, or
+    /// modified, or when the light's direction or resolution changes. Shadow casters moving
+    /// elsewhere don't affect it, so a scene with many static lights only re-renders the shadow
+    /// maps that a moving object shows up in. The region covered by the shadow map follows the
+    /// camera in steps rather than smoothly, so the camera can move a little without the shadow
+    /// map being re-rendered. `FrameStats` reports how many shadow maps were rendered and reused.
+    Static,
+}
+
+impl Default for ShadowUpdate {
+    fn default() -> ShadowUpdate {
+        ShadowUpdate::Dynamic
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
 pub enum LightData {
-    Point { radius: f32 },
+    Point { radius: f32, falloff: Falloff },
     Directional { direction: Vector3 },
 }
 
+/// Specifies how a point light's intensity falls off with distance.
+///
+/// Regardless of the falloff the light has no effect beyond its radius. The falloff is sent to
+/// materials as the `light_falloff` and `light_attenuation` uniforms, see the `material` module
+/// for details.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Falloff {
+    /// The intensity falls off smoothly to zero at the light's radius, following
+    /// `(1 - distance / radius)^2`.
+    Smooth,
+
+    /// The intensity is scaled by `1 / (constant + linear * distance + quadratic * distance^2)`.
+    Attenuation {
+        constant: f32,
+        linear: f32,
+        quadratic: f32,
+    },
+
+    /// The intensity is scaled by `1 / distance^2`, which is how light falls off physically.
+    InverseSquare,
+}
+
+impl Falloff {
+    /// Gets the value of the `light_falloff` uniform for the falloff.
+    pub fn uniform_value(&self) -> i32 {
+        match *self {
+            Falloff::Smooth => 0,
+            Falloff::Attenuation { .. } => 1,
+            Falloff::InverseSquare => 2,
+        }
+    }
+
+    /// Gets the value of the `light_attenuation` uniform for the falloff.
+    ///
+    /// `InverseSquare` is equivalent to purely quadratic attenuation, and `Smooth` doesn't use
+    /// the attenuation coefficients so they're all zero.
+    pub fn attenuation(&self) -> [f32; 3] {
+        match *self {
+            Falloff::Smooth => [0.0, 0.0, 0.0],
+            Falloff::Attenuation { constant, linear, quadratic } => [constant, linear, quadratic],
+            Falloff::InverseSquare => [0.0, 0.0, 1.0],
+        }
+    }
+}
+
+impl Default for Falloff {
+    fn default() -> Falloff {
+        Falloff::Smooth
+    }
+}
+
 /// Identifies a light that has been registered with the renderer.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct LightId(usize);
 derive_Counter!(LightId);
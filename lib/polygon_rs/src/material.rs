@@ -24,6 +24,32 @@
 //! > NOTE: This information is incomplete, and will likely not be for a long time. It's meant
 //! > more as reference information about the current material syntax than as a proper tutorial.
 //!
+//! ## Properties
+//!
+//! Properties are declared with a name and a type, and optionally a default value that materials
+//! built from the source start out with:
+//!
+//! ```text
+//! property surface_color: Color = (1.0, 0.5, 0.25, 1.0);
+//! property surface_shininess: f32 = 4.0;
+//! property surface_offset: Vector3 = (0.0, 1.0, 0.0);
+//! property uv_scroll: Vector2 = (0.5, 0.0);
+//! property tint: Vector4 = (1.0, 1.0, 1.0, 1.0);
+//! property uv_transform: Matrix4 = (
+//!     1.0, 0.0, 0.0, 0.0,
+//!     0.0, 1.0, 0.0, 0.0,
+//!     0.0, 0.0, 1.0, 0.0,
+//!     0.0, 0.0, 0.0, 1.0
+//! );
+//! property surface_diffuse: Texture2d = "textures/diffuse.bmp";
+//! ```
+//!
+//! In GLSL the property types are `vec4`, `sampler2D`, `float`, `vec2`, `vec3`, `vec4`, and `mat4`
+//! respectively, and matrices are given in row-major order like `Matrix4`. Properties without a
+//! default start out zeroed, except for matrices which start out as the identity. Texture defaults are paths relative to the
+//! material file, which the renderer doesn't load itself, so texture properties start out with
+//! the default texture until the texture is loaded and set on the material.
+//!
 //! ## Programs
 //!
 //! TODO: How do you specify vertex and frag shaders. What are their inputs and outputs?
@@ -32,6 +58,20 @@
 //!
 //! TODO: What are the input and output vertex attributes?
 //!
+//! The mesh's first texcoord channel is available as `@vertex.uv0`. Materials that use
+//! `@vertex.uv1` also get the second texcoord channel, e.g. for lightmaps. The default vertex
+//! program passes it through automatically, while custom vertex programs have to set it from
+//! `vertex_uv1` themselves. Meshes without a second channel read `(0.0, 0.0)`.
+//!
+//! The mesh's vertex colors are available as `@vertex.color`, set from `vertex_color` by the
+//! default vertex program. Meshes without vertex colors read opaque white.
+//!
+//! The mesh's tangents are available as `vertex_tangent`, and the default vertex program sets
+//! `@vertex.world_tangent` to the tangent in world space with the handedness of the tangent
+//! space in `w`, so that the bitangent is `cross(normal, tangent.xyz) * tangent.w`. Meshes
+//! without tangents read `(1.0, 0.0, 0.0, 1.0)`. See `diffuse_normal_mapped.material` for a
+//! material that uses them to apply a normal map.
+//!
 //! ## Built-In Uniforms and Attributes
 //!
 //! Polygon injects a number of uniforms and vertex attributes into your materials automatically
@@ -48,9 +88,25 @@
 //! - `projection_transform: Matrix4` - The transform converting points in view space to projection space.
 //! - `model_view_project: Matrix4` - The transform converting points in model space to projection space.
 //!
+//! Mesh instances that share a mesh and material may be drawn together with GPU instancing, in
+//! which case the transforms involving the model transform are per-instance values that are only
+//! available in the vertex program. Materials whose fragment program reads them are never
+//! instanced.
+//!
 //! Lighting:
 //!
-//! - `global_ambient: Color` - The ambient light given as a color.
+//! - `global_ambient: Color` - The ambient light given as a color. With hemisphere ambient
+//!   light this is the average of the sky and ground colors.
+//! - `ambient_mode: i32` - 0 means flat ambient light, 1 means hemisphere ambient light, see
+//!   `AmbientMode`.
+//! - `ambient_sky_color: Color` - The ambient color of surfaces facing up (only for hemisphere
+//!   ambient light).
+//! - `ambient_ground_color: Color` - The ambient color of surfaces facing down (only for
+//!   hemisphere ambient light).
+//! - `ambient_up: Vector3` - The normalized up direction in world space (only for hemisphere
+//!   ambient light).
+//! - `ambient_light(world_normal: Vector3) -> Color` - A built-in function giving the ambient
+//!   light reaching a surface with the given normalized world space normal, for either mode.
 //! - `camera_position: Point` - The position of the camera in world space. In view space the
 //!   camera is at `(0.0, 0.0, 0.0)`.
 //! - `light_position: Point` - The position of the current light in world space.
@@ -62,10 +118,68 @@
 //!   regardless of the light type, but uniforms not used for the current light type will not be
 //!   set, so reading them will yield some kind of garbage.
 //! - `light_radius: f32` - The radius of the current light (only for point lights).
+//! - `light_falloff: u32` - An integer constant specifying how the current light falls off with
+//!   distance: 0 means smooth falloff to zero at `light_radius`, 1 means attenuation using
+//!   `light_attenuation`, 2 means inverse-square falloff (only for point lights).
+//! - `light_attenuation: Vector3` - The constant, linear, and quadratic attenuation coefficients
+//!   of the current light. Inverse-square falloff is given as `(0.0, 0.0, 1.0)` (only for point
+//!   lights).
 //! - `light_direction: Vector3` - The normalized direction in world space of the current light (only
 //!   for directional lights).
 //! - `light_direction_view` - The normalized direction in view space of the current light (only
 //!   for directional lights).
+//! - `light_has_shadow: i32` - 1 if the current light casts shadows into `shadow_map`, 0 otherwise.
+//! - `light_space_transform: Matrix4` - The transform converting points in world space to the
+//!   current light's tile in `shadow_map`, giving texture coordinates in x and y and the depth in
+//!   z (only for lights that cast shadows).
+//! - `shadow_region: Vector4` - The bottom-left and top-right corners of the current light's tile
+//!   in `shadow_map`, in texture coordinates (only for lights that cast shadows).
+//!
+//! Shadows:
+//!
+//! - `shadow_enabled: bool` - Whether the mesh instance receives shadows and any light is
+//!   casting them. `shadow_map` should only be sampled if this is set.
+//! - `shadow_map: sampler2DShadow` - The shadow atlas, holding a tile for each shadow casting light
+//!   with the depth of the nearest shadow caster as seen by that light. The sampler compares the
+//!   reference depth to the stored depth, giving 1.0 if the point is lit and 0.0 if it's in
+//!   shadow.
+//!
+//! Materials can use the built-in `float shadow_light(mat4 transform, vec4 region, vec4
+//! world_position)` function to sample a light's shadow, passing the light's
+//! `light_space_transform` and `shadow_region`. It returns how much of the light reaches the
+//! point, softening the shadow's edges and treating points outside of the light's tile as lit.
+//!
+//! Multiple lights:
+//!
+//! Materials that read the `light_count` uniform receive up to `MAX_LIGHTS` (currently 8) lights
+//! per draw through arrays of light data, and are expected to loop over them. Materials that
+//! don't read `light_count` are instead drawn once per light with the single light uniforms
+//! above, which is much slower with many lights. If there are more than `MAX_LIGHTS` lights the
+//! mesh is drawn again for each additional batch of lights with `global_ambient` set to black, so
+//! ambient light should be added unconditionally.
+//!
+//! - `light_count: i32` - The number of lights in the arrays for the current draw.
+//! - `light_types: [i32; MAX_LIGHTS]` - The type of each light: 1 means point light, 2 means
+//!   directional light.
+//! - `light_colors: [Color; MAX_LIGHTS]`
+//! - `light_strengths: [f32; MAX_LIGHTS]`
+//! - `light_positions: [Point; MAX_LIGHTS]`
+//! - `light_positions_view: [Point; MAX_LIGHTS]`
+//! - `light_radii: [f32; MAX_LIGHTS]`
+//! - `light_falloffs: [i32; MAX_LIGHTS]`
+//! - `light_attenuations: [Vector3; MAX_LIGHTS]`
+//! - `light_directions: [Vector3; MAX_LIGHTS]`
+//! - `light_directions_view: [Vector3; MAX_LIGHTS]`
+//! - `light_has_shadows: [i32; MAX_LIGHTS]`
+//! - `light_space_transforms: [Matrix4; MAX_LIGHTS]`
+//! - `shadow_regions: [Vector4; MAX_LIGHTS]`
+//!
+//! Each array holds the same data as the corresponding single light uniform.
+//!
+//! Post effects:
+//!
+//! Materials whose fragment program reads `scene_color` or `scene_depth` can be used as post
+//! effects, and also get `screen_size` and `time`. See `Renderer::set_post_effects()`.
 
 use math::*;
 use shader::Shader;
@@ -80,7 +194,7 @@ pub struct MaterialId(usize);
 derive_Counter!(MaterialId);
 
 /// Represents combination of a shader and set values for its uniform properties.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Material {
     shader: Shader,
     properties: HashMap<String, MaterialProperty>,
@@ -136,6 +250,19 @@ impl Material {
         }
     }
 
+    /// Sets a property value to be the specified `Vector2` value.
+    pub fn set_vector2<S: Into<String>>(&mut self, name: S, value: Vector2) {
+        self.properties.insert(name.into(), MaterialProperty::Vector2(value));
+    }
+
+    /// Gets the value of a `Vector2` material property.
+    pub fn get_vector2(&self, name: &str) -> Option<&Vector2> {
+        match self.properties.get(name) {
+            Some(&MaterialProperty::Vector2(ref value)) => Some(value),
+            _ => None,
+        }
+    }
+
     /// Sets a property value to be the specified `Vector3` value.
     pub fn set_vector3<S: Into<String>>(&mut self, name: S, value: Vector3) {
         self.properties.insert(name.into(), MaterialProperty::Vector3(value));
@@ -149,6 +276,32 @@ impl Material {
         }
     }
 
+    /// Sets a property value to be the specified four component vector.
+    pub fn set_vector4<S: Into<String>>(&mut self, name: S, value: [f32; 4]) {
+        self.properties.insert(name.into(), MaterialProperty::Vector4(value));
+    }
+
+    /// Gets the value of a `Vector4` material property.
+    pub fn get_vector4(&self, name: &str) -> Option<&[f32; 4]> {
+        match self.properties.get(name) {
+            Some(&MaterialProperty::Vector4(ref value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Sets a property value to be the specified `Matrix4` value.
+    pub fn set_matrix4<S: Into<String>>(&mut self, name: S, value: Matrix4) {
+        self.properties.insert(name.into(), MaterialProperty::Matrix4(value));
+    }
+
+    /// Gets the value of a `Matrix4` material property.
+    pub fn get_matrix4(&self, name: &str) -> Option<&Matrix4> {
+        match self.properties.get(name) {
+            Some(&MaterialProperty::Matrix4(ref value)) => Some(value),
+            _ => None,
+        }
+    }
+
     /// Sets a property value to be the specified texture.
     pub fn set_texture<S: Into<String>>(&mut self, name: S, texture: GpuTexture) {
         self.properties.insert(name.into(), MaterialProperty::Texture(texture));
@@ -163,11 +316,18 @@ impl Material {
 }
 
 /// Represents a value that can be sent to the GPU and used in shader programs.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 #[allow(bad_style)]
 pub enum MaterialProperty {
     Color(Color),
     Texture(GpuTexture),
     f32(f32),
+    Vector2(Vector2),
     Vector3(Vector3),
+
+    /// A four component vector. There's no four component vector type in the math library, so
+    /// the components are given as an array.
+    Vector4([f32; 4]),
+
+    Matrix4(Matrix4),
 }
@@ -9,14 +9,34 @@
 
 use {GpuMesh};
 use anchor::AnchorId;
-use material::Material;
+use material::{Material, MaterialProperty};
+use skeleton::Pose;
+use std::cmp;
+use std::collections::HashMap;
+use std::collections::hash_map::Iter as HashMapIter;
 
 /// Represents an instance of a mesh in the scene.
+///
+/// A mesh instance has a material for each of its mesh's submeshes, see
+/// `set_submesh_materials()`. Most meshes have a single submesh, and the mesh instance a single
+/// material.
 #[derive(Debug)]
 pub struct MeshInstance {
     mesh: GpuMesh,
-    material: Material,
-    anchor: Option<AnchorId>
+
+    /// The materials for the mesh's submeshes, which is never empty.
+    materials: Vec<Material>,
+    anchor: Option<AnchorId>,
+    vat_playback: VatPlayback,
+    casts_shadows: bool,
+    receives_shadows: bool,
+    visible: bool,
+    layers: u32,
+    billboard: BillboardMode,
+    pose: Option<Pose>,
+
+    /// Uniform values that replace the material's properties when drawing this instance.
+    uniform_overrides: HashMap<String, MaterialProperty>,
 }
 
 impl MeshInstance {
@@ -28,8 +48,16 @@ impl MeshInstance {
     pub fn new(mesh: GpuMesh, material: Material) -> MeshInstance {
         MeshInstance {
             mesh: mesh,
-            material: material,
+            materials: vec![material],
             anchor: None,
+            vat_playback: VatPlayback::default(),
+            casts_shadows: true,
+            receives_shadows: true,
+            visible: true,
+            layers: 1,
+            billboard: BillboardMode::None,
+            pose: None,
+            uniform_overrides: HashMap::new(),
         }
     }
 
@@ -43,19 +71,80 @@ impl MeshInstance {
         &self.mesh
     }
 
-    /// Sets the material used by the mesh instance.
+    /// Sets the material used by the mesh instance, replacing the materials for every submesh.
     pub fn set_material(&mut self, material: Material) {
-        self.material = material;
+        self.materials = vec![material];
     }
 
     /// Gets a reference to the material used by the mesh instance.
+    ///
+    /// If the mesh instance has a material for each submesh this is the first submesh's material.
     pub fn material(&self) -> &Material {
-        &self.material
+        &self.materials[0]
     }
 
     /// Gets a mutable reference to the material used by the mesh instance.
+    ///
+    /// If the mesh instance has a material for each submesh this is the first submesh's material.
     pub fn material_mut(&mut self) -> &mut Material {
-        &mut self.material
+        &mut self.materials[0]
+    }
+
+    /// Sets the materials for each of the mesh's submeshes, in the same order as
+    /// `Mesh::submeshes()`.
+    ///
+    /// Submeshes without a material of their own use the last material.
+    ///
+    /// # Panics
+    ///
+    /// - If `materials` is empty.
+    pub fn set_submesh_materials(&mut self, materials: Vec<Material>) {
+        assert!(!materials.is_empty(), "Mesh instance must have at least one material");
+        self.materials = materials;
+    }
+
+    /// Gets the materials for the mesh's submeshes.
+    pub fn submesh_materials(&self) -> &[Material] {
+        &*self.materials
+    }
+
+    /// Gets the material used to draw the submesh at index `submesh`.
+    pub fn submesh_material(&self, submesh: usize) -> &Material {
+        let last = self.materials.len() - 1;
+        &self.materials[cmp::min(submesh, last)]
+    }
+
+    /// Gets a mutable reference to the material used to draw the submesh at index `submesh`.
+    pub fn submesh_material_mut(&mut self, submesh: usize) -> &mut Material {
+        let last = self.materials.len() - 1;
+        &mut self.materials[cmp::min(submesh, last)]
+    }
+
+    /// Overrides the uniform `name` for this mesh instance only.
+    ///
+    /// The override replaces the material's property of the same name when drawing, without
+    /// changing the material itself, so the material can stay shared with other mesh instances.
+    /// Overrides apply to the materials of every submesh. Mesh instances with overrides are
+    /// never drawn instanced.
+    pub fn set_uniform_override<S: Into<String>>(&mut self, name: S, property: MaterialProperty) {
+        self.uniform_overrides.insert(name.into(), property);
+    }
+
+    /// Removes the override for the uniform `name`, so that the material's value is used again.
+    ///
+    /// The removed override is returned if any.
+    pub fn clear_uniform_override(&mut self, name: &str) -> Option<MaterialProperty> {
+        self.uniform_overrides.remove(name)
+    }
+
+    /// Gets the override for the uniform `name`, if there is one.
+    pub fn get_uniform_override(&self, name: &str) -> Option<&MaterialProperty> {
+        self.uniform_overrides.get(name)
+    }
+
+    /// Gets an iterator yielding the mesh instance's uniform overrides.
+    pub fn uniform_overrides(&self) -> HashMapIter<String, MaterialProperty> {
+        self.uniform_overrides.iter()
     }
 
     /// Attaches the mesh instance to the specified anchor.
@@ -67,8 +156,159 @@ impl MeshInstance {
     pub fn anchor(&self) -> Option<&AnchorId> {
         self.anchor.as_ref()
     }
+
+    /// Returns `true` if the mesh instance is drawn into shadow maps.
+    pub fn casts_shadows(&self) -> bool {
+        self.casts_shadows
+    }
+
+    /// Sets whether the mesh instance is drawn into shadow maps, defaults to `true`.
+    ///
+    /// Vertex animation and skinning aren't applied when drawing shadows, so animated meshes cast
+    /// the shadow of their unanimated pose.
+    pub fn set_casts_shadows(&mut self, casts_shadows: bool) {
+        self.casts_shadows = casts_shadows;
+    }
+
+    /// Returns `true` if shadows are drawn on the mesh instance.
+    pub fn receives_shadows(&self) -> bool {
+        self.receives_shadows
+    }
+
+    /// Sets whether shadows are drawn on the mesh instance, defaults to `true`.
+    ///
+    /// Only materials that sample the `shadow_map` uniform show shadows.
+    pub fn set_receives_shadows(&mut self, receives_shadows: bool) {
+        self.receives_shadows = receives_shadows;
+    }
+
+    /// Returns `true` if the mesh instance is drawn.
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Sets whether the mesh instance is drawn, defaults to `true`.
+    ///
+    /// Hidden mesh instances are skipped entirely, including when drawing shadow maps, but stay
+    /// registered with the renderer so that they can be shown again.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Gets the bitmask of render layers the mesh instance is on.
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// Sets the bitmask of render layers the mesh instance is on, defaults to `1`.
+    ///
+    /// A camera only draws the mesh instance if the mesh instance is on at least one of the
+    /// camera's layers, see `Camera::set_layers()`.
+    pub fn set_layers(&mut self, layers: u32) {
+        self.layers = layers;
+    }
+
+    /// Gets how the mesh instance is turned to face the camera.
+    pub fn billboard(&self) -> BillboardMode {
+        self.billboard
+    }
+
+    /// Sets how the mesh instance is turned to face the camera, defaults to `BillboardMode::None`.
+    ///
+    /// Billboarded mesh instances replace the rotation of their anchor with one facing the camera
+    /// that's drawing them, but keep the anchor's position and scale. They're never drawn
+    /// instanced, and they cast the shadow of their anchor's orientation.
+    pub fn set_billboard(&mut self, billboard: BillboardMode) {
+        self.billboard = billboard;
+    }
+
+    /// Sets the pose that a skinned mesh is drawn in, see `Mesh::is_skinned()`.
+    ///
+    /// The pose is copied into the mesh instance, reusing the memory of the previous pose, so it
+    /// can be set every frame. Skinned meshes are drawn in their bind pose until a pose is set,
+    /// and are never drawn instanced. Has no effect if the instance's mesh isn't skinned.
+    pub fn set_pose(&mut self, pose: &Pose) {
+        self.pose.get_or_insert_with(|| Pose::new(0)).clone_from(pose);
+    }
+
+    /// Gets the pose that a skinned mesh is drawn in, if one has been set.
+    pub fn pose(&self) -> Option<&Pose> {
+        self.pose.as_ref()
+    }
+
+    /// Removes the mesh instance's pose, so that a skinned mesh is drawn in its bind pose.
+    pub fn clear_pose(&mut self) {
+        self.pose = None;
+    }
+
+    /// Sets the playback time for a vertex animation mesh, measured in frames.
+    ///
+    /// The time may be fractional, in which case the renderer interpolates between the two
+    /// nearest frames of the animation. Times past the last frame wrap back around to the start
+    /// of the animation. Setting the time switches the instance to manual playback.
+    ///
+    /// Has no effect if the instance's mesh was not registered with
+    /// `Renderer::register_vat_mesh()`.
+    pub fn set_vat_time(&mut self, time: f32) {
+        self.vat_playback = VatPlayback::Manual(time);
+    }
+
+    /// Sets the vertex animation to play automatically at the specified rate.
+    ///
+    /// Has no effect if the instance's mesh was not registered with
+    /// `Renderer::register_vat_mesh()`.
+    pub fn set_vat_autoplay(&mut self, frames_per_second: f32) {
+        self.vat_playback = VatPlayback::Auto { frames_per_second: frames_per_second };
+    }
+
+    /// Gets the vertex animation playback mode for the mesh instance.
+    pub fn vat_playback(&self) -> VatPlayback {
+        self.vat_playback
+    }
+}
+
+/// Controls how a vertex animation texture is played back for a mesh instance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VatPlayback {
+    /// The animation is held at the specified time, measured in frames.
+    Manual(f32),
+
+    /// The animation advances automatically based on the time since the renderer was created.
+    Auto {
+        frames_per_second: f32,
+    },
+}
+
+impl Default for VatPlayback {
+    fn default() -> VatPlayback {
+        VatPlayback::Manual(0.0)
+    }
+}
+
+/// Controls how a mesh instance is turned to face the camera, e.g. for particles and name tags.
+///
+/// Billboards are rotated so that the mesh's +Z axis points back toward the camera and its +Y
+/// axis points up, so a quad in the XY plane faces the camera head-on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BillboardMode {
+    /// The mesh instance uses its anchor's orientation.
+    None,
+
+    /// The mesh instance is rotated to match the camera's orientation, so it always faces the
+    /// camera head-on.
+    Spherical,
+
+    /// The mesh instance is only rotated around the world Y axis to face the camera, so that it
+    /// stays upright when the camera looks up or down.
+    Cylindrical,
+}
+
+impl Default for BillboardMode {
+    fn default() -> BillboardMode {
+        BillboardMode::None
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct MeshInstanceId(usize);
 derive_Counter!(MeshInstanceId);
@@ -0,0 +1,94 @@
+//! Sending work to a renderer from other threads.
+//!
+//! Renderers are tied to the thread that created them, since their OpenGL context can only be
+//! used on that thread. Other threads instead send commands through a `RenderQueue`, and the
+//! thread that owns the renderer runs them with `RenderQueueReceiver::run_pending()`, or
+//! `RenderQueueReceiver::run()` if the thread is dedicated to rendering.
+
+use Renderer;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Creates a new render queue, returning the sending and receiving halves.
+///
+/// The `RenderQueue` can be cloned and sent to any thread. The `RenderQueueReceiver` stays on
+/// the thread that owns the renderer.
+pub fn render_queue() -> (RenderQueue, RenderQueueReceiver) {
+    let (sender, receiver) = mpsc::channel();
+    (RenderQueue(sender), RenderQueueReceiver(receiver))
+}
+
+/// The sending half of a render queue.
+#[derive(Clone)]
+pub struct RenderQueue(Sender<Box<RenderCommand>>);
+
+impl RenderQueue {
+    /// Queues `command` to be run with the renderer.
+    ///
+    /// Returns `false` if the receiver has been dropped, in which case the command is never run.
+    pub fn send<F>(&self, command: F) -> bool
+        where F: 'static + Send + FnOnce(&mut Renderer)
+    {
+        self.0.send(Box::new(command)).is_ok()
+    }
+
+    /// Queues `command` and blocks the calling thread until it has run, returning its result.
+    ///
+    /// Returns `None` if the receiver is dropped before the command runs.
+    pub fn call<F, T>(&self, command: F) -> Option<T>
+        where
+        F: 'static + Send + FnOnce(&mut Renderer) -> T,
+        T: 'static + Send,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let sent = self.send(move |renderer| {
+            let _ = sender.send(command(renderer));
+        });
+
+        if sent {
+            receiver.recv().ok()
+        } else {
+            None
+        }
+    }
+}
+
+/// The receiving half of a render queue.
+pub struct RenderQueueReceiver(Receiver<Box<RenderCommand>>);
+
+impl RenderQueueReceiver {
+    /// Runs all pending commands with `renderer`, in the order they were sent.
+    ///
+    /// Doesn't block if there are no pending commands. Returns the number of commands run.
+    pub fn run_pending(&self, renderer: &mut Renderer) -> usize {
+        let mut count = 0;
+        while let Ok(command) = self.0.try_recv() {
+            command.run(renderer);
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Runs commands with `renderer` as they're sent, blocking while there are none.
+    ///
+    /// Returns once every `RenderQueue` for the receiver has been dropped and all commands have
+    /// been run.
+    pub fn run(&self, renderer: &mut Renderer) {
+        while let Ok(command) = self.0.recv() {
+            command.run(renderer);
+        }
+    }
+}
+
+/// A boxed command that can be sent across threads.
+///
+/// `Box<FnOnce>` can't be called directly, so commands are run through this trait instead.
+trait RenderCommand: Send {
+    fn run(self: Box<Self>, renderer: &mut Renderer);
+}
+
+impl<F> RenderCommand for F where F: Send + FnOnce(&mut Renderer) {
+    fn run(self: Box<Self>, renderer: &mut Renderer) {
+        (*self)(renderer)
+    }
+}
@@ -0,0 +1,193 @@
+//! Skeletons and poses for skinned meshes.
+//!
+//! A skinned mesh has up to four bone influences for each vertex, set with
+//! `MeshBuilder::set_bone_index_data()` and `MeshBuilder::set_bone_weight_data()`. When the mesh
+//! is drawn each vertex is moved by a weighted blend of the transforms of its bones, which are
+//! taken from the mesh instance's `Pose`, see `MeshInstance::set_pose()`.
+//!
+//! A `Skeleton` describes the hierarchy of bones and their bind pose, and builds a pose from the
+//! transform of each bone relative to its parent.
+
+use math::{Matrix4, Point};
+
+/// The maximum number of bones in a skeleton or pose.
+pub const MAX_BONES: usize = 64;
+
+/// The bones of a skinned mesh, and the pose the mesh's vertex data was modelled in.
+#[derive(Debug, Clone, Default)]
+pub struct Skeleton {
+    bones: Vec<Bone>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bone {
+    parent: Option<usize>,
+    inverse_bind_transform: Matrix4,
+}
+
+impl Skeleton {
+    /// Creates a skeleton with no bones.
+    pub fn new() -> Skeleton {
+        Skeleton {
+            bones: Vec::new(),
+        }
+    }
+
+    /// Adds a bone to the skeleton, returning the bone's index.
+    ///
+    /// `inverse_bind_transform` transforms from model space into the bone's space when the mesh
+    /// is in its bind pose, i.e. it's the inverse of the bone's model space transform in the pose
+    /// that the mesh's vertex data is in.
+    ///
+    /// # Panics
+    ///
+    /// - If `parent` isn't the index of a bone that has already been added, so parents always
+    ///   come before their children.
+    /// - If the skeleton already has `MAX_BONES` bones.
+    pub fn add_bone(&mut self, parent: Option<usize>, inverse_bind_transform: Matrix4) -> usize {
+        assert!(self.bones.len() < MAX_BONES, "Skeleton cannot have more than {} bones", MAX_BONES);
+        if let Some(parent) = parent {
+            assert!(parent < self.bones.len(), "Bone parent {} has not been added to the skeleton", parent);
+        }
+
+        self.bones.push(Bone {
+            parent: parent,
+            inverse_bind_transform: inverse_bind_transform,
+        });
+        self.bones.len() - 1
+    }
+
+    /// Gets the number of bones in the skeleton.
+    pub fn bone_count(&self) -> usize {
+        self.bones.len()
+    }
+
+    /// Gets the index of the parent of `bone`, or `None` if it's a root bone.
+    ///
+    /// # Panics
+    ///
+    /// - If `bone` is out of bounds.
+    pub fn parent(&self, bone: usize) -> Option<usize> {
+        self.bones[bone].parent
+    }
+
+    /// Builds a pose from the transform of each bone relative to its parent.
+    ///
+    /// Root bones are relative to the mesh's model space. Passing the bind pose transforms gives a
+    /// pose that leaves the mesh unchanged.
+    ///
+    /// # Panics
+    ///
+    /// - If there isn't exactly one transform for each bone.
+    pub fn pose(&self, local_transforms: &[Matrix4]) -> Pose {
+        assert!(
+            local_transforms.len() == self.bones.len(),
+            "Expected {} bone transforms but got {}",
+            self.bones.len(),
+            local_transforms.len());
+
+        // Parents come before their children, so each parent's model transform is already known.
+        let mut model_transforms: Vec<Matrix4> = Vec::with_capacity(self.bones.len());
+        for (bone, &local_transform) in self.bones.iter().zip(local_transforms) {
+            let model_transform = match bone.parent {
+                Some(parent) => model_transforms[parent] * local_transform,
+                None => local_transform,
+            };
+            model_transforms.push(model_transform);
+        }
+
+        let bone_transforms = self.bones
+            .iter()
+            .zip(model_transforms)
+            .map(|(bone, model_transform)| model_transform * bone.inverse_bind_transform)
+            .collect();
+        Pose {
+            bone_transforms: bone_transforms,
+        }
+    }
+}
+
+/// The transform of each bone of a skinned mesh, see `MeshInstance::set_pose()`.
+///
+/// Each bone transform moves vertices from where they are in the mesh's vertex data to where
+/// the bone puts them, both in model space. Identity transforms leave the mesh in its bind pose.
+#[derive(Debug, PartialEq)]
+pub struct Pose {
+    bone_transforms: Vec<Matrix4>,
+}
+
+impl Clone for Pose {
+    fn clone(&self) -> Pose {
+        Pose {
+            bone_transforms: self.bone_transforms.clone(),
+        }
+    }
+
+    // Reuses the memory of the bone transforms, since poses are usually copied every frame.
+    fn clone_from(&mut self, source: &Pose) {
+        self.bone_transforms.clone_from(&source.bone_transforms);
+    }
+}
+
+impl Pose {
+    /// Creates a pose with `bone_count` bones that are all in their bind pose.
+    ///
+    /// # Panics
+    ///
+    /// - If `bone_count` is greater than `MAX_BONES`.
+    pub fn new(bone_count: usize) -> Pose {
+        assert!(bone_count <= MAX_BONES, "Pose cannot have more than {} bones", MAX_BONES);
+        Pose {
+            bone_transforms: vec![Matrix4::identity(); bone_count],
+        }
+    }
+
+    /// Creates a pose from the model space transform of each bone.
+    ///
+    /// # Panics
+    ///
+    /// - If there are more than `MAX_BONES` transforms.
+    pub fn from_bone_transforms(bone_transforms: &[Matrix4]) -> Pose {
+        assert!(bone_transforms.len() <= MAX_BONES, "Pose cannot have more than {} bones", MAX_BONES);
+        Pose {
+            bone_transforms: bone_transforms.to_vec(),
+        }
+    }
+
+    /// Gets the number of bones in the pose.
+    pub fn bone_count(&self) -> usize {
+        self.bone_transforms.len()
+    }
+
+    /// Gets the transform of each bone.
+    pub fn bone_transforms(&self) -> &[Matrix4] {
+        &*self.bone_transforms
+    }
+
+    /// Sets the transform of `bone`.
+    ///
+    /// # Panics
+    ///
+    /// - If `bone` is out of bounds.
+    pub fn set_bone_transform(&mut self, bone: usize, transform: Matrix4) {
+        self.bone_transforms[bone] = transform;
+    }
+
+    /// Moves a point of a skinned mesh into the pose, the same way the renderer does.
+    ///
+    /// Useful for finding where part of a skinned mesh is drawn, e.g. to attach an object to a
+    /// character's hand. Bones that aren't in the pose don't move the point.
+    pub fn skin_point(&self, position: Point, bone_indices: [u32; 4], bone_weights: [f32; 4]) -> Point {
+        let mut skinned = Point::new(0.0, 0.0, 0.0);
+        for (&bone, &weight) in bone_indices.iter().zip(&bone_weights) {
+            let moved = match self.bone_transforms.get(bone as usize) {
+                Some(&transform) => position * transform,
+                None => position,
+            };
+            skinned.x += moved.x * weight;
+            skinned.y += moved.y * weight;
+            skinned.z += moved.z * weight;
+        }
+        skinned
+    }
+}
@@ -0,0 +1,105 @@
+use math::{Color, Vector2};
+use texture::GpuTexture;
+
+/// A textured rectangle drawn on top of the scene, such as a crosshair or a health bar.
+///
+/// Sprites are positioned in pixels from the top-left corner of the window, so they keep their
+/// size and position in pixels when the window is resized. Sprites are drawn after every camera,
+/// without depth testing and blended over the scene using their alpha.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprite {
+    /// The position of the sprite's anchor point, in pixels from the top-left corner of the
+    /// window.
+    pub position: Vector2,
+
+    /// The width and height of the sprite in pixels.
+    pub size: Vector2,
+
+    /// The region of the texture that is drawn on the sprite.
+    pub uv_rect: UvRect,
+
+    /// Multiplied with the texture's color, which can be used to tint the sprite or fade it out.
+    pub color: Color,
+
+    /// The point on the sprite that is placed at `position`, as a fraction of the sprite's size.
+    ///
+    /// `(0, 0)` is the top-left corner of the sprite, `(0.5, 0.5)` is its center, and `(1, 1)` is
+    /// its bottom-right corner.
+    pub anchor_point: Vector2,
+
+    texture: GpuTexture,
+}
+
+impl Sprite {
+    /// Creates a white sprite showing all of `texture`, with its top-left corner at the top-left
+    /// corner of the window.
+    pub fn new(texture: GpuTexture, size: Vector2) -> Sprite {
+        Sprite {
+            position: Vector2::new(0.0, 0.0),
+            size: size,
+            uv_rect: UvRect::default(),
+            color: Color::rgb(1.0, 1.0, 1.0),
+            anchor_point: Vector2::new(0.0, 0.0),
+
+            texture: texture,
+        }
+    }
+
+    /// Gets the texture drawn on the sprite.
+    pub fn texture(&self) -> GpuTexture {
+        self.texture
+    }
+
+    /// Changes the texture drawn on the sprite.
+    ///
+    /// The texture must be registered with the renderer, sprites with an unregistered texture
+    /// are drawn with a plain white texture.
+    pub fn set_texture(&mut self, texture: GpuTexture) {
+        self.texture = texture;
+    }
+
+    /// Gets the top-left and bottom-right corners of the sprite, in pixels from the top-left
+    /// corner of the window.
+    pub fn corners(&self) -> (Vector2, Vector2) {
+        let min = Vector2::new(
+            self.position.x - self.anchor_point.x * self.size.x,
+            self.position.y - self.anchor_point.y * self.size.y);
+        let max = Vector2::new(min.x + self.size.x, min.y + self.size.y);
+        (min, max)
+    }
+}
+
+/// A region of a texture, in texture coordinates.
+///
+/// Texture coordinates are the same as those used by meshes: `(0, 0)` is the bottom-left corner
+/// of the texture and `(1, 1)` is its top-right corner. The bottom of the rect is drawn at the
+/// bottom of the sprite, so the sprite isn't flipped. The default rect covers the whole texture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl UvRect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> UvRect {
+        UvRect {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        }
+    }
+}
+
+impl Default for UvRect {
+    fn default() -> UvRect {
+        UvRect::new(0.0, 0.0, 1.0, 1.0)
+    }
+}
+
+/// Identifies a sprite that has been registered with the renderer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct SpriteId(usize);
+derive_Counter!(SpriteId);
@@ -0,0 +1,374 @@
+//! Bitmap fonts for drawing text with `Renderer::draw_text()`.
+//!
+//! A bitmap font is a texture holding an image of every glyph, along with metrics that give
+//! where each glyph is in the texture and how far to move along the line after drawing it.
+//! `BitmapFont::embedded()` provides a small ASCII font built into the crate, so text can be
+//! drawn without loading any assets.
+//!
+//! # Metrics Format
+//!
+//! Font metrics are plain text with one entry per line. Blank lines and lines starting with `#`
+//! are ignored.
+//!
+//! ```text
+//! # The height of a line, in texels.
+//! line_height 8
+//!
+//! # glyph <character code> <x> <y> <width> <height> <advance>
+//! glyph 65 8 32 8 8 8
+//! ```
+//!
+//! Character codes are decimal Unicode code points. Glyph rectangles are given in texels with
+//! the origin at the top-left corner of the image, and the advance is the horizontal distance
+//! from the start of the glyph to the start of the next one. Each glyph is drawn with its top
+//! at the top of the line. Texture data is stored with the bottom row first, as it is for
+//! bitmaps, so the rectangles are flipped when calculating texture coordinates.
+
+use std::char;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use texture::*;
+
+/// The location and spacing of a glyph in a `BitmapFont`, in texels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyph {
+    /// The left edge of the glyph in the font texture.
+    pub x: u32,
+
+    /// The top edge of the glyph in the font texture.
+    pub y: u32,
+
+    pub width: u32,
+    pub height: u32,
+
+    /// The horizontal distance from the start of the glyph to the start of the next glyph.
+    pub advance: u32,
+}
+
+/// A glyph positioned on screen by `BitmapFont::layout()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphQuad {
+    /// The top-left corner of the quad, in pixels from the top-left corner of the screen.
+    pub min: (f32, f32),
+
+    /// The bottom-right corner of the quad, in pixels from the top-left corner of the screen.
+    pub max: (f32, f32),
+
+    /// The texture coordinates of the top-left corner of the quad.
+    pub uv_min: (f32, f32),
+
+    /// The texture coordinates of the bottom-right corner of the quad.
+    pub uv_max: (f32, f32),
+}
+
+/// A font made of glyph images packed into a texture.
+#[derive(Debug)]
+pub struct BitmapFont {
+    texture: Texture2d,
+    line_height: u32,
+    glyphs: HashMap<char, Glyph>,
+}
+
+impl BitmapFont {
+    /// Creates a font from the texture holding its glyphs and the font's metrics, see the
+    /// module documentation for the metrics format.
+    pub fn new(texture: Texture2d, metrics: &str) -> Result<BitmapFont, FontError> {
+        let mut line_height = None;
+        let mut glyphs = HashMap::new();
+
+        for (index, line) in metrics.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid_line = || FontError::InvalidLine {
+                line: index + 1,
+                text: line.into(),
+            };
+
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap();
+            let values = words
+                .map(|word| word.parse::<u32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid_line())?;
+
+            match (keyword, values.len()) {
+                ("line_height", 1) => line_height = Some(values[0]),
+                ("glyph", 6) => {
+                    let character = char::from_u32(values[0]).ok_or_else(|| invalid_line())?;
+                    let glyph = Glyph {
+                        x: values[1],
+                        y: values[2],
+                        width: values[3],
+                        height: values[4],
+                        advance: values[5],
+                    };
+
+                    if (glyph.x + glyph.width) as usize > texture.width()
+                        || (glyph.y + glyph.height) as usize > texture.height()
+                    {
+                        return Err(FontError::GlyphOutOfBounds { character: character });
+                    }
+
+                    glyphs.insert(character, glyph);
+                },
+                _ => return Err(invalid_line()),
+            }
+        }
+
+        Ok(BitmapFont {
+            texture: texture,
+            line_height: line_height.ok_or(FontError::MissingLineHeight)?,
+            glyphs: glyphs,
+        })
+    }
+
+    /// Creates the font built into the crate, an 8x8 pixel monospace font covering the
+    /// printable ASCII characters.
+    ///
+    /// The glyphs are white with the coverage in the alpha channel, so the text takes on the
+    /// color it's drawn with.
+    pub fn embedded() -> BitmapFont {
+        const COLUMNS: usize = 16;
+        const ROWS: usize = (EMBEDDED_GLYPH_COUNT + COLUMNS - 1) / COLUMNS;
+        const WIDTH: usize = COLUMNS * 8;
+        const HEIGHT: usize = ROWS * 8;
+
+        let mut pixels = vec![(255, 255, 255, 0); WIDTH * HEIGHT];
+        let mut glyphs = HashMap::new();
+        for (index, bitmap) in EMBEDDED_GLYPHS.iter().enumerate() {
+            let x = index % COLUMNS * 8;
+            let y = index / COLUMNS * 8;
+
+            // Each byte is a row of the glyph from the top down, with the lowest bit on the left.
+            for (row, &bits) in bitmap.iter().enumerate() {
+                let data_row = HEIGHT - 1 - (y + row);
+                for column in 0..8 {
+                    if bits & (1 << column) != 0 {
+                        pixels[data_row * WIDTH + x + column].3 = 255;
+                    }
+                }
+            }
+
+            let character = (EMBEDDED_FIRST_CHAR as u8 + index as u8) as char;
+            glyphs.insert(character, Glyph {
+                x: x as u32,
+                y: y as u32,
+                width: 8,
+                height: 8,
+                advance: 8,
+            });
+        }
+
+        let mut texture = Texture2d::new(WIDTH, HEIGHT, DataFormat::Rgba, TextureData::u8x4(pixels));
+        texture.set_filtering(TextureFilter::Nearest, TextureFilter::Nearest);
+        texture.set_wrap(TextureWrap::ClampToEdge, TextureWrap::ClampToEdge);
+        texture.set_color_space(ColorSpace::Linear);
+
+        BitmapFont {
+            texture: texture,
+            line_height: 8,
+            glyphs: glyphs,
+        }
+    }
+
+    /// Gets the texture holding the font's glyphs.
+    pub fn texture(&self) -> &Texture2d {
+        &self.texture
+    }
+
+    /// Gets the height of a line of text, in texels.
+    pub fn line_height(&self) -> u32 {
+        self.line_height
+    }
+
+    /// Gets the metrics for `character`, or `None` if the font doesn't have a glyph for it.
+    pub fn glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs.get(&character)
+    }
+
+    /// Lays out `text` with the top-left corner of its first line at `position`, appending a
+    /// quad to `quads` for each glyph.
+    ///
+    /// `position` and the quads are in pixels from the top-left corner of the screen, and `size`
+    /// is the height of a line in pixels. Each `'\n'` starts a new line below the previous one.
+    /// Characters that the font doesn't have are drawn as `'?'`, or skipped if the font doesn't
+    /// have `'?'` either. Kerning isn't supported.
+    pub fn layout(&self, text: &str, position: (f32, f32), size: f32, quads: &mut Vec<GlyphQuad>) {
+        let scale = size / self.line_height as f32;
+        let texture_width = self.texture.width() as f32;
+        let texture_height = self.texture.height() as f32;
+
+        let (mut x, mut y) = position;
+        for character in text.chars() {
+            match character {
+                '\n' => {
+                    x = position.0;
+                    y += size;
+                    continue;
+                },
+                '\r' => continue,
+                _ => {},
+            }
+
+            let glyph = match self.glyph(character).or_else(|| self.glyph('?')) {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+
+            if glyph.width > 0 && glyph.height > 0 {
+                quads.push(GlyphQuad {
+                    min: (x, y),
+                    max: (x + glyph.width as f32 * scale, y + glyph.height as f32 * scale),
+                    uv_min: (glyph.x as f32 / texture_width, 1.0 - glyph.y as f32 / texture_height),
+                    uv_max: (
+                        (glyph.x + glyph.width) as f32 / texture_width,
+                        1.0 - (glyph.y + glyph.height) as f32 / texture_height,
+                    ),
+                });
+            }
+
+            x += glyph.advance as f32 * scale;
+        }
+    }
+}
+
+/// An error that occurred while loading a `BitmapFont`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontError {
+    /// A line of the metrics isn't a valid entry.
+    InvalidLine {
+        line: usize,
+        text: String,
+    },
+
+    /// The metrics don't give the font's line height.
+    MissingLineHeight,
+
+    /// A glyph's rectangle extends past the edge of the font texture.
+    GlyphOutOfBounds {
+        character: char,
+    },
+}
+
+impl Display for FontError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            FontError::InvalidLine { line, ref text } =>
+                write!(formatter, "Font metrics line {} is not a valid entry: \"{}\"", line, text),
+            FontError::MissingLineHeight =>
+                write!(formatter, "Font metrics have no line_height entry"),
+            FontError::GlyphOutOfBounds { character } =>
+                write!(formatter, "Font glyph {:?} extends past the edge of the font texture", character),
+        }
+    }
+}
+
+/// The first character in `EMBEDDED_GLYPHS`.
+const EMBEDDED_FIRST_CHAR: char = ' ';
+
+const EMBEDDED_GLYPH_COUNT: usize = 95;
+
+/// The glyphs of the embedded font, covering `' '` through `'~'`.
+///
+/// Based on the public domain font8x8 basic Latin set by Daniel Hepper, derived from the IBM PC
+/// BIOS font.
+static EMBEDDED_GLYPHS: [[u8; 8]; EMBEDDED_GLYPH_COUNT] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00], // !
+    [0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // "
+    [0x36, 0x36, 0x7F, 0x36, 0x7F, 0x36, 0x36, 0x00], // #
+    [0x0C, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x0C, 0x00], // $
+    [0x00, 0x63, 0x33, 0x18, 0x0C, 0x66, 0x63, 0x00], // %
+    [0x1C, 0x36, 0x1C, 0x6E, 0x3B, 0x33, 0x6E, 0x00], // &
+    [0x06, 0x06, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00], // '
+    [0x18, 0x0C, 0x06, 0x06, 0x06, 0x0C, 0x18, 0x00], // (
+    [0x06, 0x0C, 0x18, 0x18, 0x18, 0x0C, 0x06, 0x00], // )
+    [0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00], // *
+    [0x00, 0x0C, 0x0C, 0x3F, 0x0C, 0x0C, 0x00, 0x00], // +
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ,
+    [0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00], // -
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // .
+    [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00], // /
+    [0x3E, 0x63, 0x73, 0x7B, 0x6F, 0x67, 0x3E, 0x00], // 0
+    [0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x00], // 1
+    [0x1E, 0x33, 0x30, 0x1C, 0x06, 0x33, 0x3F, 0x00], // 2
+    [0x1E, 0x33, 0x30, 0x1C, 0x30, 0x33, 0x1E, 0x00], // 3
+    [0x38, 0x3C, 0x36, 0x33, 0x7F, 0x30, 0x78, 0x00], // 4
+    [0x3F, 0x03, 0x1F, 0x30, 0x30, 0x33, 0x1E, 0x00], // 5
+    [0x1C, 0x06, 0x03, 0x1F, 0x33, 0x33, 0x1E, 0x00], // 6
+    [0x3F, 0x33, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x00], // 7
+    [0x1E, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x1E, 0x00], // 8
+    [0x1E, 0x33, 0x33, 0x3E, 0x30, 0x18, 0x0E, 0x00], // 9
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x00], // :
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ;
+    [0x18, 0x0C, 0x06, 0x03, 0x06, 0x0C, 0x18, 0x00], // <
+    [0x00, 0x00, 0x3F, 0x00, 0x00, 0x3F, 0x00, 0x00], // =
+    [0x06, 0x0C, 0x18, 0x30, 0x18, 0x0C, 0x06, 0x00], // >
+    [0x1E, 0x33, 0x30, 0x18, 0x0C, 0x00, 0x0C, 0x00], // ?
+    [0x3E, 0x63, 0x7B, 0x7B, 0x7B, 0x03, 0x1E, 0x00], // @
+    [0x0C, 0x1E, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x00], // A
+    [0x3F, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x3F, 0x00], // B
+    [0x3C, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x00], // C
+    [0x1F, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x00], // D
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x46, 0x7F, 0x00], // E
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x06, 0x0F, 0x00], // F
+    [0x3C, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7C, 0x00], // G
+    [0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x00], // H
+    [0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // I
+    [0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, 0x00], // J
+    [0x67, 0x66, 0x36, 0x1E, 0x36, 0x66, 0x67, 0x00], // K
+    [0x0F, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7F, 0x00], // L
+    [0x63, 0x77, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00], // M
+    [0x63, 0x67, 0x6F, 0x7B, 0x73, 0x63, 0x63, 0x00], // N
+    [0x1C, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x00], // O
+    [0x3F, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00], // P
+    [0x1E, 0x33, 0x33, 0x33, 0x3B, 0x1E, 0x38, 0x00], // Q
+    [0x3F, 0x66, 0x66, 0x3E, 0x36, 0x66, 0x67, 0x00], // R
+    [0x1E, 0x33, 0x07, 0x0E, 0x38, 0x33, 0x1E, 0x00], // S
+    [0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // T
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x00], // U
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // V
+    [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00], // W
+    [0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x00], // X
+    [0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x1E, 0x00], // Y
+    [0x7F, 0x63, 0x31, 0x18, 0x4C, 0x66, 0x7F, 0x00], // Z
+    [0x1E, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1E, 0x00], // [
+    [0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x40, 0x00], // \
+    [0x1E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1E, 0x00], // ]
+    [0x08, 0x1C, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00], // ^
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF], // _
+    [0x0C, 0x0C, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00], // `
+    [0x00, 0x00, 0x1E, 0x30, 0x3E, 0x33, 0x6E, 0x00], // a
+    [0x07, 0x06, 0x06, 0x3E, 0x66, 0x66, 0x3B, 0x00], // b
+    [0x00, 0x00, 0x1E, 0x33, 0x03, 0x33, 0x1E, 0x00], // c
+    [0x38, 0x30, 0x30, 0x3E, 0x33, 0x33, 0x6E, 0x00], // d
+    [0x00, 0x00, 0x1E, 0x33, 0x3F, 0x03, 0x1E, 0x00], // e
+    [0x1C, 0x36, 0x06, 0x0F, 0x06, 0x06, 0x0F, 0x00], // f
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x1F], // g
+    [0x07, 0x06, 0x36, 0x6E, 0x66, 0x66, 0x67, 0x00], // h
+    [0x0C, 0x00, 0x0E, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // i
+    [0x30, 0x00, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E], // j
+    [0x07, 0x06, 0x66, 0x36, 0x1E, 0x36, 0x67, 0x00], // k
+    [0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // l
+    [0x00, 0x00, 0x33, 0x7F, 0x7F, 0x6B, 0x63, 0x00], // m
+    [0x00, 0x00, 0x1F, 0x33, 0x33, 0x33, 0x33, 0x00], // n
+    [0x00, 0x00, 0x1E, 0x33, 0x33, 0x33, 0x1E, 0x00], // o
+    [0x00, 0x00, 0x3B, 0x66, 0x66, 0x3E, 0x06, 0x0F], // p
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x78], // q
+    [0x00, 0x00, 0x3B, 0x6E, 0x66, 0x06, 0x0F, 0x00], // r
+    [0x00, 0x00, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x00], // s
+    [0x08, 0x0C, 0x3E, 0x0C, 0x0C, 0x2C, 0x18, 0x00], // t
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x6E, 0x00], // u
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // v
+    [0x00, 0x00, 0x63, 0x6B, 0x7F, 0x7F, 0x36, 0x00], // w
+    [0x00, 0x00, 0x63, 0x36, 0x1C, 0x36, 0x63, 0x00], // x
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x1F], // y
+    [0x00, 0x00, 0x3F, 0x19, 0x0C, 0x26, 0x3F, 0x00], // z
+    [0x38, 0x0C, 0x0C, 0x07, 0x0C, 0x0C, 0x38, 0x00], // {
+    [0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00], // |
+    [0x07, 0x0C, 0x0C, 0x38, 0x0C, 0x0C, 0x07, 0x00], // }
+    [0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ~
+];
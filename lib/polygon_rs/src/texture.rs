@@ -4,7 +4,7 @@ use parse_bmp::{
 };
 
 /// Represents texture data that has been sent to the GPU.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct GpuTexture(usize);
 derive_Counter!(GpuTexture);
 
@@ -21,27 +21,70 @@ pub struct Texture2d {
     height: usize,
     format: DataFormat,
     data: TextureData,
+    min_filter: TextureFilter,
+    mag_filter: TextureFilter,
+    wrap_s: TextureWrap,
+    wrap_t: TextureWrap,
+    color_space: ColorSpace,
 }
 
 impl Texture2d {
+    /// Creates a new `Texture2d` from raw texture data.
+    ///
+    /// # Panics
+    ///
+    /// - If `data` doesn't contain exactly `width * height` pixels in the specified format.
+    pub fn new(width: usize, height: usize, format: DataFormat, data: TextureData) -> Texture2d {
+        let channels = match format {
+            DataFormat::Rgb | DataFormat::Bgr => 3,
+            DataFormat::Rgba | DataFormat::Bgra => 4,
+        };
+
+        let elements = match data {
+            TextureData::f32(ref data) => data.len(),
+            TextureData::u8(ref data) => data.len(),
+            TextureData::u8x3(ref data) => data.len() * 3,
+            TextureData::u8x4(ref data) => data.len() * 4,
+        };
+
+        assert!(
+            elements == width * height * channels,
+            "Wrong amount of texture data, width: {}, height: {}, format: {:?}, expected elements: {}, actual elements: {}",
+            width,
+            height,
+            format,
+            width * height * channels,
+            elements);
+
+        Texture2d {
+            width: width,
+            height: height,
+            format: format,
+            data: data,
+            min_filter: TextureFilter::default(),
+            mag_filter: TextureFilter::default(),
+            wrap_s: TextureWrap::default(),
+            wrap_t: TextureWrap::default(),
+            color_space: ColorSpace::default(),
+        }
+    }
+
     /// Loads a new `Texture` from a bitmap file.
     pub fn from_bitmap(bitmap: Bitmap) -> Texture2d {
         let texture = match bitmap.data() {
             &BitmapData::Bgr(ref data) => {
-                Texture2d {
-                    width: bitmap.width(),
-                    height: bitmap.height(),
-                    format: DataFormat::Bgr,
-                    data: TextureData::u8x3(data.clone()), // TODO: Don't clone the data.
-                }
+                Texture2d::new(
+                    bitmap.width(),
+                    bitmap.height(),
+                    DataFormat::Bgr,
+                    TextureData::u8x3(data.clone())) // TODO: Don't clone the data.
             },
             &BitmapData::Bgra(ref data) => {
-                Texture2d {
-                    width: bitmap.width(),
-                    height: bitmap.height(),
-                    format: DataFormat::Bgra,
-                    data: TextureData::u8x4(data.clone()), // TODO: Don't clone the data.
-                }
+                Texture2d::new(
+                    bitmap.width(),
+                    bitmap.height(),
+                    DataFormat::Bgra,
+                    TextureData::u8x4(data.clone())) // TODO: Don't clone the data.
             },
         };
 
@@ -67,6 +110,112 @@ impl Texture2d {
     pub fn data(&self) -> &TextureData {
         &self.data
     }
+
+    /// Sets the filters used when the texture is minified and magnified.
+    ///
+    /// Defaults to `TextureFilter::Linear` for both. Mipmaps are generated when the texture is
+    /// registered if `min` uses them.
+    ///
+    /// # Panics
+    ///
+    /// - If `mag` is a mipmap filter, mipmaps are never used for magnification.
+    pub fn set_filtering(&mut self, min: TextureFilter, mag: TextureFilter) {
+        assert!(!mag.uses_mipmaps(), "Magnification filter cannot use mipmaps: {:?}", mag);
+
+        self.min_filter = min;
+        self.mag_filter = mag;
+    }
+
+    /// Gets the minification and magnification filters for the texture.
+    pub fn filtering(&self) -> (TextureFilter, TextureFilter) {
+        (self.min_filter, self.mag_filter)
+    }
+
+    /// Sets the wrap modes for the horizontal and vertical texture coordinates.
+    ///
+    /// Defaults to `TextureWrap::Repeat` for both.
+    pub fn set_wrap(&mut self, s: TextureWrap, t: TextureWrap) {
+        self.wrap_s = s;
+        self.wrap_t = t;
+    }
+
+    /// Gets the horizontal and vertical wrap modes for the texture.
+    pub fn wrap(&self) -> (TextureWrap, TextureWrap) {
+        (self.wrap_s, self.wrap_t)
+    }
+
+    /// Sets whether the texture's data is color or linear data.
+    ///
+    /// Defaults to `ColorSpace::Srgb`, which is correct for albedo and other color textures.
+    /// Textures that hold non-color data, such as normal maps or masks, should use
+    /// `ColorSpace::Linear`. Textures with `TextureData::f32` data are always treated as linear.
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.color_space = color_space;
+    }
+
+    /// Gets the color space of the texture's data.
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+}
+
+/// The color space that a texture's data is stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// The data is gamma encoded color, as produced by most image editors.
+    ///
+    /// The data is converted to linear values when the texture is sampled.
+    Srgb,
+
+    /// The data is used as-is when the texture is sampled.
+    Linear,
+}
+
+impl Default for ColorSpace {
+    fn default() -> ColorSpace {
+        ColorSpace::Srgb
+    }
+}
+
+/// The filter used when sampling a texture at a different size than its native resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureFilter {
+    Nearest,
+    Linear,
+    NearestMipmapNearest,
+    LinearMipmapNearest,
+    NearestMipmapLinear,
+    LinearMipmapLinear,
+}
+
+impl TextureFilter {
+    /// Returns `true` if the filter samples from the texture's mipmaps.
+    pub fn uses_mipmaps(self) -> bool {
+        match self {
+            TextureFilter::Nearest | TextureFilter::Linear => false,
+            _ => true,
+        }
+    }
+}
+
+impl Default for TextureFilter {
+    fn default() -> TextureFilter {
+        TextureFilter::Linear
+    }
+}
+
+/// Determines how texture coordinates outside of the [0, 1] range are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureWrap {
+    Repeat,
+    ClampToEdge,
+    MirroredRepeat,
+}
+
+impl Default for TextureWrap {
+    fn default() -> TextureWrap {
+        TextureWrap::Repeat
+    }
 }
 
 /// An enum representing the supported data formats for a texture.
@@ -0,0 +1,666 @@
+extern crate polygon;
+
+use polygon::*;
+use polygon::anchor::*;
+use polygon::camera::*;
+use polygon::geometry::mesh::*;
+use polygon::gl::GlRender;
+use polygon::light::*;
+use polygon::material::*;
+use polygon::math::*;
+use polygon::mesh_instance::*;
+use polygon::queue::*;
+use polygon::skeleton::*;
+use polygon::sprite::*;
+use polygon::text::*;
+use polygon::texture::*;
+use std::thread;
+
+static VERTEX_POSITIONS: [f32; 12] = [
+    -1.0, -1.0, 0.0, 1.0,
+     1.0, -1.0, 0.0, 1.0,
+     0.0,  1.0, 0.0, 1.0,
+];
+
+static INDICES: [u32; 3] = [0, 1, 2];
+
+const SIZE: i32 = 64;
+
+/// Builds a headless renderer with a white triangle facing a camera, lit only by a point light
+/// between them. Returns the renderer and the light.
+fn lit_triangle() -> (GlRender, LightId) {
+    let mut renderer = RendererBuilder::headless(SIZE, SIZE).build_gl();
+
+    let mesh = MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&VERTEX_POSITIONS))
+        .set_indices(&INDICES)
+        .generate_normals(None)
+        .build()
+        .unwrap();
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
+
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+    mesh_instance.material_mut().set_color("surface_color", Color::rgb(1.0, 1.0, 1.0));
+    renderer.register_mesh_instance(mesh_instance);
+
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(0.0, 0.0, 5.0));
+    let mut camera = Camera::default();
+    camera.set_anchor(renderer.register_anchor(camera_anchor));
+    renderer.register_camera(camera);
+
+    let mut light_anchor = Anchor::new();
+    light_anchor.set_position(Point::new(0.0, 0.0, 1.0));
+    let mut light = Light::point(5.0, 1.0, Color::rgb(1.0, 1.0, 1.0));
+    light.set_anchor(renderer.register_anchor(light_anchor));
+    let light_id = renderer.register_light(light);
+
+    // Without ambient light only the point light can light the triangle.
+    renderer.set_ambient_light(Color::rgb(0.0, 0.0, 0.0));
+
+    (renderer, light_id)
+}
+
+fn brightness(pixel: [u8; 4]) -> u32 {
+    pixel[0] as u32 + pixel[1] as u32 + pixel[2] as u32
+}
+
+#[test]
+fn draw_lit_triangle() {
+    let (mut renderer, _) = lit_triangle();
+    renderer.draw();
+
+    let capture = renderer.capture_frame();
+    assert_eq!((capture.width(), capture.height()), (SIZE as usize, SIZE as usize));
+
+    // The center of the frame is the middle of the triangle, right behind the light.
+    let center = capture.pixel(SIZE as usize / 2, SIZE as usize / 2);
+    assert!(center[0] > 100 && center[1] > 100 && center[2] > 100, "Center isn't lit: {:?}", center);
+
+    // The corners are outside the triangle, so they keep the clear color.
+    for &(x, y) in &[(0, 0), (SIZE - 1, 0), (0, SIZE - 1), (SIZE - 1, SIZE - 1)] {
+        let corner = capture.pixel(x as usize, y as usize);
+        assert_eq!(brightness(corner), 0, "Corner ({}, {}) isn't empty: {:?}", x, y, corner);
+    }
+}
+
+#[test]
+fn draw_unlit_triangle() {
+    let (mut renderer, light_id) = lit_triangle();
+    renderer.get_light_mut(light_id).unwrap().set_enabled(false);
+    renderer.draw();
+
+    let capture = renderer.capture_frame();
+    let center = capture.pixel(SIZE as usize / 2, SIZE as usize / 2);
+    assert_eq!(brightness(center), 0, "Center is lit without any lights: {:?}", center);
+}
+
+/// Builds a unit sphere out of `rings` rings of `segments` quads, with normals pointing out.
+fn sphere(rings: usize, segments: usize) -> Mesh {
+    let mut positions = Vec::new();
+    for ring in 0..rings + 1 {
+        let polar = ring as f32 / rings as f32 * PI;
+        for segment in 0..segments + 1 {
+            let azimuth = segment as f32 / segments as f32 * 2.0 * PI;
+            positions.push(Point::new(polar.sin() * azimuth.cos(), polar.cos(), -polar.sin() * azimuth.sin()));
+        }
+    }
+    let normals = positions.iter().map(|&position| Vector3::from(position)).collect::<Vec<_>>();
+
+    // Each quad is split into two triangles, wound counter-clockwise when seen from outside.
+    let mut indices = Vec::new();
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let top = (ring * (segments + 1) + segment) as u32;
+            let bottom = top + segments as u32 + 1;
+            indices.extend(&[top, bottom, bottom + 1, top, bottom + 1, top + 1]);
+        }
+    }
+
+    MeshBuilder::new()
+        .set_position_data(&positions)
+        .set_normal_data(&normals)
+        .set_indices(&indices)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn draw_hemisphere_ambient() {
+    let mut renderer = RendererBuilder::headless(SIZE, SIZE).build_gl();
+
+    let gpu_mesh = renderer.register_mesh(&sphere(16, 16)).unwrap();
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+    mesh_instance.material_mut().set_color("surface_color", Color::rgb(1.0, 1.0, 1.0));
+    renderer.register_mesh_instance(mesh_instance);
+
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(0.0, 0.0, 5.0));
+    let mut camera = Camera::default();
+    camera.set_anchor(renderer.register_anchor(camera_anchor));
+    renderer.register_camera(camera);
+
+    // There are no lights, so the sphere only shows the ambient light.
+    renderer.set_ambient(AmbientMode::Hemisphere {
+        sky: Color::rgb(0.0, 0.0, 1.0),
+        ground: Color::rgb(1.0, 0.0, 0.0),
+        up: Vector3::new(0.0, 2.0, 0.0),
+    });
+    renderer.draw();
+
+    // The top of the sphere is mostly sky, the bottom is mostly ground, and the middle faces the
+    // horizon so it's an even blend of both.
+    let capture = renderer.capture_frame();
+    let middle = SIZE as usize / 2;
+    let top = capture.pixel(middle, middle - 8);
+    let center = capture.pixel(middle, middle);
+    let bottom = capture.pixel(middle, middle + 8);
+    assert!(top[2] > 150 && top[0] < 100, "Top isn't lit by the sky: {:?}", top);
+    assert!(bottom[0] > 150 && bottom[2] < 100, "Bottom isn't lit by the ground: {:?}", bottom);
+    assert!((center[0] as i32 - center[2] as i32).abs() < 16, "Center isn't an even blend: {:?}", center);
+    assert!(center[0] > 100, "Center is too dark: {:?}", center);
+
+    // A flat ambient color lights the whole sphere evenly.
+    renderer.set_ambient_light(Color::rgb(0.0, 1.0, 0.0));
+    renderer.draw();
+
+    let capture = renderer.capture_frame();
+    assert_eq!(capture.pixel(middle, middle - 8), capture.pixel(middle, middle + 8));
+    assert_eq!(capture.pixel(middle, middle)[1], 255);
+}
+
+#[test]
+fn cull_distant_point_light() {
+    let (mut renderer, _) = lit_triangle();
+    renderer.draw();
+    let without_light = renderer.capture_frame();
+
+    // A point light whose radius doesn't reach the triangle isn't sent to its draw, and the frame
+    // looks exactly the same as without it.
+    let mut light_anchor = Anchor::new();
+    light_anchor.set_position(Point::new(20.0, 0.0, 1.0));
+    let mut light = Light::point(5.0, 1.0, Color::rgb(1.0, 0.0, 0.0));
+    light.set_anchor(renderer.register_anchor(light_anchor));
+    let far_light = renderer.register_light(light);
+    renderer.draw();
+
+    let stats = renderer.frame_stats();
+    assert_eq!(stats.lights_processed, 2);
+    assert_eq!(stats.lit_draws, 1);
+    assert_eq!(stats.average_lights_per_draw(), 1.0);
+    assert!(renderer.capture_frame() == without_light, "Distant light changed the frame");
+
+    // Once the light is moved within reach of the triangle it's sent with the other light.
+    let anchor_id = *renderer.get_light(far_light).unwrap().anchor().unwrap();
+    renderer.get_anchor_mut(anchor_id).unwrap().set_position(Point::new(2.0, 0.0, 1.0));
+    renderer.draw();
+    assert_eq!(renderer.frame_stats().average_lights_per_draw(), 2.0);
+}
+
+#[test]
+fn cache_static_shadow_map() {
+    let (mut renderer, _) = lit_triangle();
+    let mut light = Light::directional(Vector3::new(0.0, -1.0, -1.0), 1.0, Color::rgb(1.0, 1.0, 1.0));
+    light.set_casts_shadows(true);
+    light.set_shadow_update(ShadowUpdate::Static);
+    let light_id = renderer.register_light(light);
+
+    let shadow_counts = |renderer: &GlRender| {
+        let stats = renderer.frame_stats();
+        (stats.shadow_maps_rendered, stats.shadow_maps_reused)
+    };
+
+    // The shadow map is rendered the first frame, then reused while nothing moves.
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (1, 0));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (0, 1));
+
+    // Moving the camera a little stays within the region the cached shadow map covers.
+    let camera_anchor = renderer.cameras().next().unwrap().1.anchor().unwrap();
+    renderer.get_anchor_mut(camera_anchor).unwrap().set_position(Point::new(0.1, 0.0, 5.0));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (0, 1));
+
+    // Moving the shadow caster re-renders the shadow map once.
+    let mesh_instance_id = renderer.mesh_instances().next().unwrap().0;
+    let anchor_id = *renderer.get_mesh_instance(mesh_instance_id).unwrap().anchor().unwrap();
+    renderer.get_anchor_mut(anchor_id).unwrap().set_position(Point::new(0.5, 0.0, 0.0));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (1, 0));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (0, 1));
+
+    // Dynamic lights re-render the shadow map every frame.
+    renderer.get_light_mut(light_id).unwrap().set_shadow_update(ShadowUpdate::Dynamic);
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (1, 0));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (1, 0));
+}
+
+#[test]
+fn cache_static_shadow_maps_per_light() {
+    let (mut renderer, _) = lit_triangle();
+
+    // Many static lights pointing straight down, plus one at an angle. The shadow maps are
+    // centered on the camera, and only the angled light's shadow map reaches 12 units along x.
+    let mut directions = vec![Vector3::new(0.0, -1.0, 0.0); 8];
+    directions.push(Vector3::new(1.0, -1.0, 0.0));
+    for &direction in &directions {
+        let mut light = Light::directional(direction, 1.0, Color::rgb(1.0, 1.0, 1.0));
+        light.set_casts_shadows(true);
+        light.set_shadow_update(ShadowUpdate::Static);
+        light.set_shadow_resolution(Some(64));
+        renderer.register_light(light);
+    }
+
+    let mesh_instance_id = renderer.mesh_instances().next().unwrap().0;
+    let (gpu_mesh, material) = {
+        let mesh_instance = renderer.get_mesh_instance(mesh_instance_id).unwrap();
+        (*mesh_instance.mesh(), mesh_instance.material().clone())
+    };
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, material);
+    let moving_anchor = renderer.register_anchor(Anchor::new());
+    mesh_instance.set_anchor(moving_anchor);
+    renderer.register_mesh_instance(mesh_instance);
+
+    let shadow_counts = |renderer: &GlRender| {
+        let stats = renderer.frame_stats();
+        (stats.shadow_maps_rendered, stats.shadow_maps_reused)
+    };
+
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (9, 0));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (0, 9));
+
+    // Moving the object out of every shadow map re-renders all of them once, since it was in
+    // all of them.
+    renderer.get_anchor_mut(moving_anchor).unwrap().set_position(Point::new(100.0, 0.0, 5.0));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (9, 0));
+
+    // Moving it around where no shadow map reaches doesn't re-render anything.
+    renderer.get_anchor_mut(moving_anchor).unwrap().set_position(Point::new(-100.0, 0.0, 5.0));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (0, 9));
+
+    // Moving it into the angled light's shadow map only re-renders that one.
+    renderer.get_anchor_mut(moving_anchor).unwrap().set_position(Point::new(12.0, 0.0, 5.0));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (1, 8));
+    renderer.get_anchor_mut(moving_anchor).unwrap().set_position(Point::new(12.0, 0.5, 5.0));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (1, 8));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (0, 9));
+
+    // Destroying the object's anchor removes it from the shadow map it was in, after which the
+    // shadow maps are reused again.
+    renderer.destroy_anchor(moving_anchor);
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (1, 8));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (0, 9));
+
+    // A second camera gets its own shadow maps, which are cached separately from the first
+    // camera's. The atlas grows to fit them, so every shadow map is rendered once more.
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(50.0, 0.0, 5.0));
+    let mut camera = Camera::default();
+    camera.set_anchor(renderer.register_anchor(camera_anchor));
+    renderer.register_camera(camera);
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (18, 0));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (0, 18));
+}
+
+#[test]
+fn unregister_mesh_invalidates_shadow_map() {
+    let (mut renderer, _) = lit_triangle();
+    let mut light = Light::directional(Vector3::new(0.0, -1.0, -1.0), 1.0, Color::rgb(1.0, 1.0, 1.0));
+    light.set_casts_shadows(true);
+    light.set_shadow_update(ShadowUpdate::Static);
+    renderer.register_light(light);
+
+    let shadow_counts = |renderer: &GlRender| {
+        let stats = renderer.frame_stats();
+        (stats.shadow_maps_rendered, stats.shadow_maps_reused)
+    };
+
+    renderer.draw();
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (0, 1));
+
+    // The triangle disappears along with its mesh, so its shadow has to go too.
+    let mesh_instance_id = renderer.mesh_instances().next().unwrap().0;
+    let gpu_mesh = *renderer.get_mesh_instance(mesh_instance_id).unwrap().mesh();
+    renderer.unregister_mesh(gpu_mesh);
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (1, 0));
+    renderer.draw();
+    assert_eq!(shadow_counts(&renderer), (0, 1));
+}
+
+#[test]
+fn draw_skinned_triangle() {
+    let mut renderer = RendererBuilder::headless(SIZE, SIZE).build_gl();
+
+    // A triangle that follows bone 1 entirely.
+    let mesh = MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&VERTEX_POSITIONS))
+        .set_bone_index_data(&[[1, 0, 0, 0]; 3])
+        .set_bone_weight_data(&[[1.0, 0.0, 0.0, 0.0]; 3])
+        .set_indices(&INDICES)
+        .generate_normals(None)
+        .build()
+        .unwrap();
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
+
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+    mesh_instance.material_mut().set_color("surface_color", Color::rgb(1.0, 1.0, 1.0));
+    let mesh_instance_id = renderer.register_mesh_instance(mesh_instance);
+
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(0.0, 0.0, 5.0));
+    let mut camera = Camera::default();
+    camera.set_anchor(renderer.register_anchor(camera_anchor));
+    renderer.register_camera(camera);
+
+    renderer.set_ambient_light(Color::rgb(1.0, 1.0, 1.0));
+
+    // Without a pose the triangle is drawn where it was modelled, covering the center.
+    renderer.draw();
+    let capture = renderer.capture_frame();
+    assert!(brightness(capture.pixel(SIZE as usize / 2, SIZE as usize / 2)) > 300);
+
+    // Moving bone 1 out of view moves the whole triangle with it, while bone 0 doesn't matter.
+    let pose = Pose::from_bone_transforms(&[Matrix4::identity(), Matrix4::translation(10.0, 0.0, 0.0)]);
+    renderer.get_mesh_instance_mut(mesh_instance_id).unwrap().set_pose(&pose);
+    renderer.draw();
+    let capture = renderer.capture_frame();
+    let center = capture.pixel(SIZE as usize / 2, SIZE as usize / 2);
+    assert_eq!(brightness(center), 0, "Skinned triangle wasn't moved: {:?}", center);
+}
+
+/// Gets the average position of the lit pixels in the last frame, in pixels.
+fn lit_centroid(renderer: &GlRender) -> (f32, f32) {
+    let capture = renderer.capture_frame();
+    let (mut x_sum, mut y_sum, mut count) = (0.0, 0.0, 0.0);
+    for y in 0..capture.height() {
+        for x in 0..capture.width() {
+            if brightness(capture.pixel(x, y)) > 0 {
+                x_sum += x as f32;
+                y_sum += y as f32;
+                count += 1.0;
+            }
+        }
+    }
+
+    assert!(count > 0.0, "Nothing was drawn");
+    (x_sum / count, y_sum / count)
+}
+
+#[test]
+fn vertex_animation_continuity() {
+    const FRAMES: usize = 8;
+    const RADIUS: f32 = 1.0;
+    const STEPS_PER_FRAME: usize = 4;
+
+    let mut renderer = RendererBuilder::headless(SIZE, SIZE).build_gl();
+
+    let mesh = MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&VERTEX_POSITIONS))
+        .set_indices(&INDICES)
+        .generate_normals(None)
+        .build()
+        .unwrap();
+
+    // Bake a small copy of the triangle circling the center, one lap over the whole animation
+    // so that the last frame leads back into the first.
+    let mut positions = Vec::with_capacity(FRAMES * 3 * 3);
+    for frame in 0..FRAMES {
+        let angle = frame as f32 / FRAMES as f32 * 2.0 * PI;
+        for vertex in VERTEX_POSITIONS.chunks(4) {
+            positions.push(vertex[0] * 0.5 + angle.cos() * RADIUS);
+            positions.push(vertex[1] * 0.5 + angle.sin() * RADIUS);
+            positions.push(vertex[2]);
+        }
+    }
+    let positions = Texture2d::new(3, FRAMES, DataFormat::Rgb, TextureData::f32(positions));
+    let gpu_positions = renderer.register_texture(&positions);
+    let gpu_mesh = renderer.register_vat_mesh(&mesh, gpu_positions, None).unwrap();
+
+    let mut mesh_instance = MeshInstance::new(gpu_mesh, renderer.default_material());
+    mesh_instance.set_anchor(renderer.register_anchor(Anchor::new()));
+    mesh_instance.material_mut().set_color("surface_color", Color::rgb(1.0, 1.0, 1.0));
+    let mesh_instance_id = renderer.register_mesh_instance(mesh_instance);
+
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(0.0, 0.0, 5.0));
+    let mut camera = Camera::default();
+    camera.set_anchor(renderer.register_anchor(camera_anchor));
+    renderer.register_camera(camera);
+
+    renderer.set_ambient_light(Color::rgb(1.0, 1.0, 1.0));
+
+    let mut centroid_at = |time: f32| {
+        renderer.get_mesh_instance_mut(mesh_instance_id).unwrap().set_vat_time(time);
+        renderer.draw();
+        lit_centroid(&renderer)
+    };
+
+    // Step through two laps so that the wrap from the last frame to the first is crossed in the
+    // middle. A triangle that snaps between frames, or jumps back across the circle when wrapping,
+    // moves a whole frame's distance in one step.
+    let steps = (0..2 * FRAMES * STEPS_PER_FRAME + 1)
+        .map(|step| step as f32 / STEPS_PER_FRAME as f32)
+        .map(|time| (time, centroid_at(time)))
+        .collect::<Vec<_>>();
+
+    // One frame moves the triangle along a chord of the circle, measured in pixels with the
+    // camera's 60 degree field of view 5 units away.
+    let pixels_per_unit = SIZE as f32 / (2.0 * 5.0 * (PI / 6.0).tan());
+    let frame_distance = 2.0 * RADIUS * (PI / FRAMES as f32).sin() * pixels_per_unit;
+    let max_step = frame_distance / STEPS_PER_FRAME as f32 + 1.5;
+
+    for pair in steps.windows(2) {
+        let (from_time, (from_x, from_y)) = pair[0];
+        let (to_time, (to_x, to_y)) = pair[1];
+        let step = ((to_x - from_x).powi(2) + (to_y - from_y).powi(2)).sqrt();
+        assert!(
+            step <= max_step,
+            "Triangle jumped {} pixels between times {} and {}, max step: {}",
+            step,
+            from_time,
+            to_time,
+            max_step);
+    }
+
+    // Playing past the end of the animation repeats it exactly.
+    let lap = FRAMES * STEPS_PER_FRAME;
+    for (&(time, (x, y)), &(_, (wrapped_x, wrapped_y))) in steps.iter().zip(&steps[lap..]) {
+        assert!(
+            (wrapped_x - x).abs() < 0.01 && (wrapped_y - y).abs() < 0.01,
+            "Time {} doesn't match time {} after wrapping",
+            time,
+            time + FRAMES as f32);
+    }
+}
+
+#[test]
+fn resize_headless() {
+    let (mut renderer, _) = lit_triangle();
+    renderer.resize(SIZE * 2, SIZE);
+    renderer.draw();
+
+    let capture = renderer.capture_frame();
+    assert_eq!((capture.width(), capture.height()), (SIZE as usize * 2, SIZE as usize));
+
+    let center = capture.pixel(SIZE as usize, SIZE as usize / 2);
+    assert!(brightness(center) > 300, "Center isn't lit after resizing: {:?}", center);
+}
+
+#[test]
+fn draw_post_effects() {
+    // Light the triangle with only ambient light, so that it's pure red.
+    let (mut renderer, light_id) = lit_triangle();
+    renderer.get_light_mut(light_id).unwrap().set_enabled(false);
+    renderer.set_ambient_light(Color::rgb(1.0, 1.0, 1.0));
+    let mesh_instance_id = renderer.mesh_instances().next().unwrap().0;
+    renderer
+        .get_mesh_instance_mut(mesh_instance_id)
+        .unwrap()
+        .material_mut()
+        .set_color("surface_color", Color::rgb(1.0, 0.0, 0.0));
+
+    let grayscale_source = MaterialSource::from_str(include_str!("../resources/materials/grayscale.material")).unwrap();
+    let grayscale = renderer.build_material(grayscale_source).unwrap();
+    let grayscale = renderer.register_material(grayscale);
+
+    let tint_source = MaterialSource::from_str(r#"
+        property tint: Color = (0.0, 0.0, 1.0, 1.0);
+
+        program frag {
+            @color = texture(scene_color, @vertex.uv0) * tint;
+        }
+    "#).unwrap();
+    let tint = renderer.build_material(tint_source).unwrap();
+    let tint = renderer.register_material(tint);
+
+    let is_gray = |pixel: [u8; 4]| pixel[0] > 50 && pixel[0] == pixel[1] && pixel[1] == pixel[2];
+
+    // The grayscale effect turns the red triangle gray, and leaves the empty corners black.
+    renderer.set_post_effects(vec![grayscale]);
+    renderer.draw();
+    let capture = renderer.capture_frame();
+    let center = capture.pixel(SIZE as usize / 2, SIZE as usize / 2);
+    assert!(is_gray(center), "Center isn't gray: {:?}", center);
+    assert_eq!(brightness(capture.pixel(0, 0)), 0);
+
+    // Effects run in order. Tinting the red triangle blue leaves nothing for the grayscale
+    // effect, while tinting the gray triangle leaves it blue.
+    renderer.set_post_effects(vec![tint, grayscale]);
+    renderer.draw();
+    let center = renderer.capture_frame().pixel(SIZE as usize / 2, SIZE as usize / 2);
+    assert_eq!(brightness(center), 0, "Tint didn't run first: {:?}", center);
+
+    renderer.set_post_effects(vec![grayscale, tint]);
+    renderer.draw();
+    let center = renderer.capture_frame().pixel(SIZE as usize / 2, SIZE as usize / 2);
+    assert!(center[0] == 0 && center[1] == 0 && center[2] > 50, "Grayscale didn't run first: {:?}", center);
+
+    // The effects cover the whole frame after it's resized.
+    renderer.set_post_effects(vec![grayscale]);
+    renderer.resize(SIZE * 2, SIZE);
+    renderer.draw();
+    let capture = renderer.capture_frame();
+    assert_eq!((capture.width(), capture.height()), (SIZE as usize * 2, SIZE as usize));
+    let center = capture.pixel(SIZE as usize, SIZE as usize / 2);
+    assert!(is_gray(center), "Center isn't gray after resizing: {:?}", center);
+
+    // Without effects the scene is drawn straight into the frame again.
+    renderer.set_post_effects(Vec::new());
+    renderer.draw();
+    let center = renderer.capture_frame().pixel(SIZE as usize, SIZE as usize / 2);
+    assert!(center[0] > 100 && center[1] == 0 && center[2] == 0, "Center isn't red: {:?}", center);
+}
+
+#[test]
+fn draw_text_overlay() {
+    let mut renderer = RendererBuilder::headless(SIZE, SIZE).build_gl();
+
+    // A font with a single solid glyph makes it easy to tell which pixels the text covers.
+    let texture = Texture2d::new(8, 8, DataFormat::Rgba, TextureData::u8x4(vec![(255, 255, 255, 255); 64]));
+    let font = BitmapFont::new(texture, "line_height 8\nglyph 35 0 0 8 8 8").unwrap();
+    renderer.set_font(font);
+
+    renderer.draw_text("#", (16.0, 8.0), 16.0, Color::rgb(1.0, 0.0, 0.0));
+    renderer.draw();
+
+    // Text is positioned from the top-left corner of the frame.
+    let capture = renderer.capture_frame();
+    let inside = capture.pixel(24, 16);
+    assert!(inside[0] > 200 && inside[1] == 0 && inside[2] == 0, "Text isn't drawn: {:?}", inside);
+    for &(x, y) in &[(8, 16), (40, 16), (24, 4), (24, 28), (24, SIZE - 16)] {
+        let outside = capture.pixel(x as usize, y as usize);
+        assert_eq!(brightness(outside), 0, "Pixel ({}, {}) is covered by text: {:?}", x, y, outside);
+    }
+
+    // Text isn't retained between frames.
+    renderer.draw();
+    let capture = renderer.capture_frame();
+    assert_eq!(brightness(capture.pixel(24, 16)), 0);
+}
+
+#[test]
+fn draw_sprites() {
+    let mut renderer = RendererBuilder::headless(SIZE, SIZE).build_gl();
+
+    // A texture with a red bottom row and a blue top row shows whether the sprite is flipped.
+    let mut texture = Texture2d::new(1, 2, DataFormat::Rgba, TextureData::u8x4(vec![(255, 0, 0, 255), (0, 0, 255, 255)]));
+    texture.set_filtering(TextureFilter::Nearest, TextureFilter::Nearest);
+    let texture = renderer.register_texture(&texture);
+
+    // Center a 16x16 sprite on (24, 16), counting from the top-left corner.
+    let sprite_id = renderer.register_sprite(texture);
+    {
+        let sprite = renderer.get_sprite_mut(sprite_id).unwrap();
+        sprite.position = Vector2::new(24.0, 16.0);
+        sprite.size = Vector2::new(16.0, 16.0);
+        sprite.anchor_point = Vector2::new(0.5, 0.5);
+    }
+
+    for &(width, height) in &[(SIZE, SIZE), (SIZE * 2, SIZE / 2)] {
+        // Sprites keep their position in pixels when the frame is resized.
+        renderer.resize(width, height);
+        renderer.draw();
+
+        let capture = renderer.capture_frame();
+        let top = capture.pixel(24, 12);
+        let bottom = capture.pixel(24, 20);
+        assert!(top[2] > 200 && top[0] == 0, "Top half of the sprite isn't blue: {:?}", top);
+        assert!(bottom[0] > 200 && bottom[2] == 0, "Bottom half of the sprite isn't red: {:?}", bottom);
+
+        for &(x, y) in &[(12, 16), (36, 16), (24, 4), (24, 28)] {
+            let outside = capture.pixel(x, y);
+            assert_eq!(brightness(outside), 0, "Pixel ({}, {}) is covered by the sprite: {:?}", x, y, outside);
+        }
+    }
+}
+
+#[test]
+fn render_queue_from_other_thread() {
+    let (queue, receiver) = render_queue();
+
+    // The renderer lives on the thread that created it, other threads can only reach it through
+    // the queue.
+    let render_thread = thread::spawn(move || {
+        let (mut renderer, _) = lit_triangle();
+        receiver.run(&mut renderer);
+    });
+
+    let worker = thread::spawn(move || {
+        let anchor_id = queue.call(|renderer| renderer.register_anchor(Anchor::new())).unwrap();
+        queue.send(move |renderer| renderer.get_anchor_mut(anchor_id).unwrap().set_position(Point::new(1.0, 0.0, 0.0)));
+
+        // Commands run in the order they were sent, so the anchor has been moved by the time the
+        // frame is drawn.
+        let (position, stats) = queue.call(move |renderer| {
+            let position = renderer.get_anchor(anchor_id).unwrap().position();
+            (position, renderer.draw())
+        }).unwrap();
+        assert_eq!(position, Point::new(1.0, 0.0, 0.0));
+        assert!(stats.draw_calls > 0);
+    });
+
+    worker.join().unwrap();
+
+    // Dropping the last queue stops the render thread.
+    render_thread.join().unwrap();
+}
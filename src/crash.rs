@@ -0,0 +1,342 @@
+//! Crash reporting.
+//!
+//! When the engine panics the crash handler writes a report describing the state of the engine
+//! at the time of the crash into a timestamped directory under `crash_reports/`. The path to the
+//! most recent report is also written to `crash_reports/last_crash.txt` so that tools like the
+//! loader can find it after the process has gone down.
+//!
+//! The panic hook runs on whichever thread panicked while the rest of the engine may still be
+//! running, so it never touches the engine instance directly. Instead the main loop publishes a
+//! small summary of the scene at the end of every frame with `record_frame()`, and the report
+//! is built from that summary. The same goes for the renderer: its capability report is published
+//! once at startup with `record_capabilities()`, since the GL context can only be used from the
+//! thread that owns it.
+//!
+//! Builds with the `crash-screenshots` feature also publish a screenshot every
+//! `SCREENSHOT_INTERVAL` frames with `record_screenshot()`. Reading back a frame stalls the GPU,
+//! so it's left out of shipping builds.
+
+use backtrace::Backtrace;
+use polygon::image::Image;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering, ATOMIC_BOOL_INIT, ATOMIC_USIZE_INIT};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The directory that crash reports are written to, relative to the working directory.
+pub const CRASH_REPORT_DIR: &'static str = "crash_reports";
+
+/// The file in `CRASH_REPORT_DIR` that holds the path to the most recent crash report.
+pub const LAST_CRASH_FILE: &'static str = "last_crash.txt";
+
+/// The number of log lines kept for crash reports, older lines are discarded.
+pub const LOG_TAIL_LINES: usize = 64;
+
+/// The number of frames between the screenshots published for crash reports.
+///
+/// Reading back a frame stalls the GPU, so the final screenshot in a report may be up to this
+/// many frames old.
+pub const SCREENSHOT_INTERVAL: usize = 60;
+
+static HANDLING_CRASH: AtomicBool = ATOMIC_BOOL_INIT;
+
+static FRAME_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+static LAST_FRAME_MICROS: AtomicUsize = ATOMIC_USIZE_INIT;
+static MESH_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+static LIGHT_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+static CAMERA_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+static BEHAVIOR_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+lazy_static! {
+    static ref LOG_TAIL: Mutex<VecDeque<String>> = Mutex::new(VecDeque::with_capacity(LOG_TAIL_LINES));
+    static ref CAPABILITIES: Mutex<Option<String>> = Mutex::new(None);
+    static ref SCREENSHOT: Mutex<Option<(usize, Image)>> = Mutex::new(None);
+}
+
+/// A summary of the scene, published once per frame for use in crash reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SceneSummary {
+    pub meshes: usize,
+    pub lights: usize,
+    pub cameras: usize,
+    pub behaviors: usize,
+}
+
+/// Installs the crash handler as the process' panic hook.
+///
+/// The previously installed panic hook is still invoked after the report has been written, so
+/// the panic message is printed as normal.
+pub fn install_panic_hook() {
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        // Only the first panic gets a report. If writing the report panics, or if another thread
+        // panics while we're writing it, we fall straight through to the previous hook.
+        if !HANDLING_CRASH.swap(true, Ordering::SeqCst) {
+            let message = match info.payload().downcast_ref::<&str>() {
+                Some(message) => *message,
+                None => match info.payload().downcast_ref::<String>() {
+                    Some(message) => &**message,
+                    None => "<non-string panic payload>",
+                },
+            };
+            let location = info.location().map(|location| (location.file(), location.line()));
+
+            match write_report(Path::new(CRASH_REPORT_DIR), message, location) {
+                Ok(path) => println!("Crash report written to {}", path.display()),
+                Err(error) => println!("Failed to write crash report: {:?}", error),
+            }
+        }
+
+        previous_hook(info);
+    }));
+}
+
+/// Prints a line of log output, keeping the most recent `LOG_TAIL_LINES` lines for crash reports.
+pub fn log<S: Into<String>>(line: S) {
+    let line = line.into();
+    println!("{}", line);
+
+    let mut log_tail = lock(&LOG_TAIL);
+    if log_tail.len() == LOG_TAIL_LINES {
+        log_tail.pop_front();
+    }
+    log_tail.push_back(line);
+}
+
+/// Records the statistics for the frame that just finished.
+///
+/// This is called by the main loop at the end of every frame so that the most recent state is
+/// available if the engine crashes.
+pub fn record_frame(frame_time_micros: usize, summary: SceneSummary) {
+    FRAME_COUNT.fetch_add(1, Ordering::Relaxed);
+    LAST_FRAME_MICROS.store(frame_time_micros, Ordering::Relaxed);
+    MESH_COUNT.store(summary.meshes, Ordering::Relaxed);
+    LIGHT_COUNT.store(summary.lights, Ordering::Relaxed);
+    CAMERA_COUNT.store(summary.cameras, Ordering::Relaxed);
+    BEHAVIOR_COUNT.store(summary.behaviors, Ordering::Relaxed);
+}
+
+/// Records the renderer's capability report, see `Renderer::capability_report()`.
+pub fn record_capabilities(report: String) {
+    *lock(&CAPABILITIES) = Some(report);
+}
+
+/// Records a capture of the most recent frame to be saved with the crash report.
+pub fn record_screenshot(screenshot: Image) {
+    *lock(&SCREENSHOT) = Some((FRAME_COUNT.load(Ordering::Relaxed), screenshot));
+}
+
+/// Returns `true` if the main loop should publish a screenshot after the frame that was just
+/// recorded.
+///
+/// Always `false` unless the engine is built with the `crash-screenshots` feature.
+pub fn wants_screenshot() -> bool {
+    cfg!(feature = "crash-screenshots") && FRAME_COUNT.load(Ordering::Relaxed) % SCREENSHOT_INTERVAL == 1
+}
+
+/// Locks `mutex`, ignoring poisoning.
+///
+/// A panic while logging shouldn't stop the engine from logging, or the report from including
+/// what was logged before the panic.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<T> {
+    mutex.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+/// Locks `mutex` without blocking.
+///
+/// The crash handler may run on a thread that panicked while holding one of the locks, in which
+/// case that section of the report is skipped rather than deadlocking.
+fn try_lock<T>(mutex: &Mutex<T>) -> Option<MutexGuard<T>> {
+    match mutex.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(error)) => Some(error.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+/// Writes a crash report for a panic into a new directory in `root`, returning the directory the
+/// report was written to.
+fn write_report(root: &Path, message: &str, location: Option<(&str, u32)>) -> io::Result<PathBuf> {
+    let timestamp = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duration) => format!("{}.{:03}", duration.as_secs(), duration.subsec_nanos() / 1_000_000),
+        Err(_) => "0".into(),
+    };
+
+    // Reports written within the same millisecond, e.g. by several processes crashing at once,
+    // get a numbered suffix instead of overwriting each other.
+    fs::create_dir_all(root)?;
+    let mut directory = root.join(&timestamp);
+    let mut suffix = 1;
+    loop {
+        match fs::create_dir(&directory) {
+            Ok(()) => break,
+            Err(ref error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                directory = root.join(format!("{}-{}", timestamp, suffix));
+                suffix += 1;
+            },
+            Err(error) => return Err(error),
+        }
+    }
+
+    // Write directly to the file rather than formatting the report into a string first to avoid
+    // allocating more than we have to while the process is in a bad state.
+    let mut report = File::create(directory.join("report.txt"))?;
+
+    writeln!(report, "[panic]")?;
+    writeln!(report, "thread: {}", thread::current().name().unwrap_or("<unnamed>"))?;
+    writeln!(report, "message: {}", message)?;
+    if let Some((file, line)) = location {
+        writeln!(report, "location: {}:{}", file, line)?;
+    }
+    writeln!(report, "")?;
+
+    writeln!(report, "[frame]")?;
+    writeln!(report, "frames completed: {}", FRAME_COUNT.load(Ordering::Relaxed))?;
+    writeln!(report, "last frame time (us): {}", LAST_FRAME_MICROS.load(Ordering::Relaxed))?;
+    writeln!(report, "")?;
+
+    writeln!(report, "[scene]")?;
+    writeln!(report, "meshes: {}", MESH_COUNT.load(Ordering::Relaxed))?;
+    writeln!(report, "lights: {}", LIGHT_COUNT.load(Ordering::Relaxed))?;
+    writeln!(report, "cameras: {}", CAMERA_COUNT.load(Ordering::Relaxed))?;
+    writeln!(report, "behaviors: {}", BEHAVIOR_COUNT.load(Ordering::Relaxed))?;
+    writeln!(report, "")?;
+
+    writeln!(report, "[log]")?;
+    match try_lock(&LOG_TAIL) {
+        Some(log_tail) => for line in log_tail.iter() {
+            writeln!(report, "{}", line)?;
+        },
+        None => writeln!(report, "<log was locked by the panicking thread>")?,
+    }
+    writeln!(report, "")?;
+
+    writeln!(report, "[graphics]")?;
+    match try_lock(&CAPABILITIES).as_ref().map(|capabilities| capabilities.as_ref()) {
+        Some(Some(capabilities)) => write!(report, "{}", capabilities)?,
+        Some(None) => writeln!(report, "<renderer was not initialized>")?,
+        None => writeln!(report, "<capability report was locked by the panicking thread>")?,
+    }
+    writeln!(report, "")?;
+
+    // The screenshot is saved next to the report, the report only notes which frame it's from.
+    writeln!(report, "[screenshot]")?;
+    match try_lock(&SCREENSHOT).as_ref().map(|screenshot| screenshot.as_ref()) {
+        Some(Some(&(frame, ref screenshot))) => {
+            match screenshot.save_tga(directory.join("screenshot.tga")) {
+                Ok(()) => writeln!(report, "screenshot.tga, captured after frame {}", frame)?,
+                Err(error) => writeln!(report, "<failed to save screenshot: {:?}>", error)?,
+            }
+        },
+        Some(None) if !cfg!(feature = "crash-screenshots") => {
+            writeln!(report, "<screenshots are only captured with the crash-screenshots feature>")?
+        },
+        Some(None) => writeln!(report, "<no frame has been captured>")?,
+        None => writeln!(report, "<screenshot was locked by the panicking thread>")?,
+    }
+    writeln!(report, "")?;
+
+    // Resolving the backtrace's symbols allocates, so it goes last. If it fails the rest of the
+    // report has already been written.
+    writeln!(report, "[backtrace]")?;
+    report.flush()?;
+    writeln!(report, "{:?}", Backtrace::new())?;
+
+    report.flush()?;
+
+    // Leave a marker pointing at the report so that it can be found after the process exits.
+    let mut last_crash = File::create(root.join(LAST_CRASH_FILE))?;
+    write!(last_crash, "{}", directory.display())?;
+
+    Ok(directory)
+}
+
+#[test]
+fn crash_report_sections() {
+    use std::io::Read;
+
+    let root = ::std::env::temp_dir().join("gunship_crash_report_test");
+    let _ = fs::remove_dir_all(&root);
+
+    log("Loaded level");
+    record_capabilities("vendor: test\n".into());
+    record_frame(16_000, SceneSummary { meshes: 3, lights: 2, cameras: 1, behaviors: 4 });
+    record_screenshot(Image::new(1, 1, vec![255, 0, 0, 255]));
+
+    // Tests are built with `panic = "abort"`, so instead of panicking this calls the same report
+    // writer that the panic hook uses. `crash_report_from_panic_hook` covers the hook itself.
+    let directory = write_report(&root, "test panic", Some(("src/crash.rs", 10))).unwrap();
+
+    let mut report = String::new();
+    File::open(directory.join("report.txt")).unwrap().read_to_string(&mut report).unwrap();
+    for section in &["[panic]", "[frame]", "[scene]", "[log]", "[graphics]", "[screenshot]", "[backtrace]"] {
+        assert!(report.contains(section), "Report is missing {} section:\n{}", section, report);
+    }
+    assert!(report.contains("message: test panic"));
+    assert!(report.contains("location: src/crash.rs:10"));
+    assert!(report.contains("meshes: 3"));
+    assert!(report.contains("Loaded level"));
+    assert!(report.contains("vendor: test"));
+    assert!(report.contains("crash::"), "Backtrace doesn't include the crash module:\n{}", report);
+
+    assert!(directory.join("screenshot.tga").exists());
+
+    let mut last_crash = String::new();
+    File::open(root.join(LAST_CRASH_FILE)).unwrap().read_to_string(&mut last_crash).unwrap();
+    assert_eq!(Path::new(&last_crash), &*directory);
+
+    // Reports written in quick succession each get their own directory.
+    let second_directory = write_report(&root, "second panic", None).unwrap();
+    assert!(second_directory != directory);
+    assert!(directory.join("report.txt").exists());
+    assert!(second_directory.join("report.txt").exists());
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn crash_report_from_panic_hook() {
+    use std::env;
+    use std::io::Read;
+    use std::process::Command;
+
+    // Panics abort the process, so the panic happens in a second run of this test and the report
+    // it leaves behind is checked from here.
+    if env::var_os("GUNSHIP_CRASH_HOOK_CHILD").is_some() {
+        install_panic_hook();
+        log("About to panic");
+        panic!("Panic from the crash hook test");
+    }
+
+    let root = env::temp_dir().join("gunship_crash_hook_test");
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+
+    let status = Command::new(env::current_exe().unwrap())
+        .args(&["crash::crash_report_from_panic_hook", "--exact", "--nocapture"])
+        .env("GUNSHIP_CRASH_HOOK_CHILD", "1")
+        .current_dir(&root)
+        .status()
+        .unwrap();
+    assert!(!status.success(), "Test process didn't crash");
+
+    // The report is written relative to the working directory of the crashed process.
+    let mut last_crash = String::new();
+    File::open(root.join(CRASH_REPORT_DIR).join(LAST_CRASH_FILE))
+        .unwrap()
+        .read_to_string(&mut last_crash)
+        .unwrap();
+
+    let mut report = String::new();
+    File::open(root.join(&last_crash).join("report.txt")).unwrap().read_to_string(&mut report).unwrap();
+    assert!(report.contains("message: Panic from the crash hook test"), "Wrong panic message:\n{}", report);
+    assert!(report.contains("location: src/crash.rs:"), "Wrong panic location:\n{}", report);
+    assert!(report.contains("About to panic"));
+
+    fs::remove_dir_all(&root).unwrap();
+}
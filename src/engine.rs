@@ -5,12 +5,14 @@ use scheduler::{self, WorkId};
 use transform::{TransformInnerHandle, TransformGraph};
 use bootstrap::window::{Message, Window};
 use cell_extras::{AtomicInitCell, InitCell};
+use crash::{self, SceneSummary};
 use input::{self, Input, ScanCode};
 use light::LightInner;
 use polygon::{GpuMesh, Renderer, RendererBuilder};
 use polygon::anchor::Anchor;
 use polygon::camera::{Camera as RenderCamera, CameraId};
 use polygon::mesh_instance::MeshInstance;
+use polygon::queue::{self, RenderQueue};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
@@ -52,6 +54,8 @@ impl EngineBuilder {
     pub fn build<F>(self, func: F)
         where F: FnOnce()
     {
+        crash::install_panic_hook();
+
         let window = {
             let mut window = unsafe { mem::uninitialized() };
             let mut out = unsafe { Unique::new(&mut window as *mut _) };
@@ -83,7 +87,29 @@ impl EngineBuilder {
             window
         };
 
-        let renderer = RendererBuilder::new(&window).build();
+        // The renderer's OpenGL context can only be used on the thread that created it, but the
+        // main loop runs on a fiber that may be resumed on any worker thread. The renderer gets a
+        // thread of its own instead, and the main loop sends it work through a `RenderQueue`.
+        let (render_queue, render_receiver) = queue::render_queue();
+        let window = {
+            let (window_sender, window_receiver) = mpsc::channel();
+            thread::Builder::new()
+                .name("render".into())
+                .spawn(move || {
+                    let mut renderer = RendererBuilder::new(&window).build();
+                    crash::record_capabilities(renderer.capability_report());
+
+                    // Hand the window back, the renderer only needs it while it's being built.
+                    window_sender.send(window).expect("Failed to return window from render thread");
+
+                    // Run render commands until the main loop exits and drops the engine's queue.
+                    render_receiver.run(&mut *renderer);
+                })
+                .expect("Failed to spawn render thread");
+
+            window_receiver.recv().expect("Render thread exited before creating the renderer")
+        };
+
         let (sender, receiever) = mpsc::channel();
 
         // Init aysnc subsystem.
@@ -108,7 +134,7 @@ impl EngineBuilder {
 
         let mut engine = Box::new(Engine {
             window: window,
-            renderer: renderer,
+            render_queue: render_queue,
             channel: receiever,
 
             mesh_map: HashMap::new(),
@@ -148,7 +174,8 @@ impl EngineBuilder {
 pub struct Engine {
     window: Window,
 
-    renderer: Box<Renderer>,
+    /// Sends work to the renderer, which lives on the render thread.
+    render_queue: RenderQueue,
     channel: Receiver<EngineMessage>,
 
     mesh_map: HashMap<MeshId, GpuMesh>,
@@ -253,6 +280,9 @@ fn main_loop(mut engine: Box<Engine>) {
                 match message {
                     Message::Close => break 'main,
                     Message::Activate => {}, // We don't handle window focus currently.
+                    Message::Resize(width, height) => {
+                        engine.render_queue.send(move |renderer| renderer.resize(width, height));
+                    },
                     _ => engine.input.push_input(message),
                 }
             }
@@ -286,130 +316,169 @@ fn main_loop(mut engine: Box<Engine>) {
                 scheduler::suspend();
             }
 
-            // Before drawing, process any pending render messages. These will be resources that were
-            // loaded but need to be registered with the renderer before the next draw.
-            while let Ok(message) = engine.channel.try_recv() {
-                match message {
-                    EngineMessage::Anchor(transform_inner) => {
-                        let anchor = Anchor::new();
-                        let anchor_id = engine.renderer.register_anchor(anchor);
+            // Everything that touches the renderer runs on the render thread. The main loop blocks
+            // until the frame has been drawn, so the render thread has the engine to itself in the
+            // meantime.
+            {
+                let _stopwatch = Stopwatch::new("render frame");
+                let mut engine_ptr = unsafe { Unique::new(&mut *engine as *mut Engine) };
+                engine.render_queue
+                    .call(move |renderer| render_frame(unsafe { engine_ptr.get_mut() }, renderer))
+                    .expect("Render thread exited while the main loop was running");
+            }
 
-                        transform_inner.set_anchor(anchor_id);
-                    },
-                    EngineMessage::Camera(camera_data, transform_inner) => {
-                        assert!(engine.camera.is_none(), "Can't add camera, one is already registered");
+            // Publish the frame's stats in case we crash before the next frame.
+            let frame_time = frame_start.elapsed();
+            crash::record_frame(
+                frame_time.as_secs() as usize * 1_000_000 + frame_time.subsec_nanos() as usize / 1_000,
+                SceneSummary {
+                    meshes: engine.mesh_map.len(),
+                    lights: engine.lights.len(),
+                    cameras: if engine.camera.is_some() { 1 } else { 0 },
+                    behaviors: engine.behaviors.len(),
+                },
+            );
+        }
 
-                        let anchor_id = match transform_inner.anchor() {
-                            Some(anchor) => anchor,
-                            None => unimplemented!(), // TODO: Create the anchor.
-                        };
+        // Determine the next frame's start time, even if we blew the frame time.
+        while frame_start < Instant::now() {
+            frame_start += target_frame_time;
+        }
 
-                        let mut camera = RenderCamera::default();
-                        camera.set_anchor(anchor_id);
-                        let camera_id = engine.renderer.register_camera(camera);
+        // Now wait until we've returned to the frame cadence before beginning the next frame.
+        while Instant::now() < frame_start {}
+    }
+}
 
-                        engine.camera = Some((camera_data, camera_id));
-                    },
-                    EngineMessage::Light(light_inner) => {
-                        {
-                            let &(ref id, ref light) = &*light_inner;
-                            let light = light.borrow().clone();
-
-                            let light_id = engine.renderer.register_light(light);
-                            id.init(light_id);
-                        }
-
-                        engine.lights.push(light_inner);
-                    }
-                    EngineMessage::Material(_material_id, material_source) => {
-                        let material = engine.renderer.build_material(material_source).expect("TODO: Handle material compilation failure");
-                        let _gpu_material = engine.renderer.register_material(material);
-
-                        // TODO: Create an association between `material_id` and `material_source`.
-                    },
-                    EngineMessage::Mesh(mesh_id, mesh_data) => {
-                        let gpu_mesh = engine.renderer.register_mesh(&mesh_data);
-                        let last = engine.mesh_map.insert(mesh_id, gpu_mesh);
-                        assert!(last.is_none(), "Duplicate mesh_id found: {:?}", mesh_id);
-                    },
-                    EngineMessage::MeshInstance(mesh_renderer_data, transform_inner) => {
-                        let anchor_id = match transform_inner.anchor() {
-                            Some(anchor) => anchor,
-                            None => unimplemented!(), // TODO: Create the anchor.
-                        };
-
-                        let gpu_mesh = *engine
-                            .mesh_map
-                            .get(&mesh_renderer_data.mesh_id())
-                            .expect("No gpu mesh found for mesh id");
-
-                        let mut mesh_instance = MeshInstance::new(
-                            gpu_mesh,
-                            engine.renderer.default_material(),
-                        );
-
-                        // HACK HACK HACK ---------------------------------------------------------
-                        mesh_instance.material_mut().set_color("surface_color", ::math::Color::rgb(1.0, 0.0, 0.0));
-                        mesh_instance.material_mut().set_color("surface_specular", ::math::Color::rgb(1.0, 1.0, 1.0));
-                        mesh_instance.material_mut().set_f32("surface_shininess", 4.0);
-                        // HACK HACK HACK ---------------------------------------------------------
-
-                        mesh_instance.set_anchor(anchor_id);
-
-                        let _ = engine.renderer.register_mesh_instance(mesh_instance);
-                    }
-                    EngineMessage::Behavior(func) => {
-                        engine.behaviors.push(func);
-                    }
+/// Registers newly loaded resources with the renderer, syncs the scene with it, and draws a frame.
+///
+/// Runs on the render thread, see `EngineBuilder::build()`.
+fn render_frame(engine: &mut Engine, renderer: &mut Renderer) {
+    // Before drawing, process any pending render messages. These will be resources that were
+    // loaded but need to be registered with the renderer before the next draw.
+    while let Ok(message) = engine.channel.try_recv() {
+        match message {
+            EngineMessage::Anchor(transform_inner) => {
+                let anchor = Anchor::new();
+                let anchor_id = renderer.register_anchor(anchor);
+
+                transform_inner.set_anchor(anchor_id);
+            },
+            EngineMessage::Camera(camera_data, transform_inner) => {
+                assert!(engine.camera.is_none(), "Can't add camera, one is already registered");
+
+                let anchor_id = match transform_inner.anchor() {
+                    Some(anchor) => anchor,
+                    None => unimplemented!(), // TODO: Create the anchor.
+                };
+
+                let mut camera = RenderCamera::default();
+                camera.set_anchor(anchor_id);
+                let camera_id = renderer.register_camera(camera);
+
+                engine.camera = Some((camera_data, camera_id));
+            },
+            EngineMessage::Light(light_inner) => {
+                {
+                    let &(ref id, ref light) = &*light_inner;
+                    let light = light.borrow().clone();
+
+                    let light_id = renderer.register_light(light);
+                    id.init(light_id);
                 }
-            }
 
-            // Update renderer's anchors with flattened scene graph.
-            for node in engine.scene_graph.roots() {
-                let node = node.borrow();
-
-                // TODO: Do something like pre-sorting so we only try to update out of
-                // date nodes.
-                if let Some(anchor_id) = node.anchor() {
-                    // Send position/rotation/scale to renderer anchor.
-                    let anchor = engine.renderer
-                        .get_anchor_mut(anchor_id)
-                        .expect("Node had anchor id but render did not have specified anchor");
-                    anchor.set_position(node.position);
-                    anchor.set_orientation(node.orientation);
-                    anchor.set_scale(node.scale);
-                }
+                engine.lights.push(light_inner);
             }
-
-            // Update the camera.
-            if let Some((ref camera_data, ref camera_id)) = engine.camera {
-                let render_camera = engine.renderer
-                    .get_camera_mut(*camera_id)
-                    .expect("Camera didn't exist for camera id");
-
-                render_camera.set_fov(camera_data.fov());
-                render_camera.set_aspect(camera_data.aspect());
-                render_camera.set_near(camera_data.near());
-                render_camera.set_far(camera_data.far());
+            EngineMessage::Material(_material_id, material_source) => {
+                let material = renderer
+                    .build_material(material_source)
+                    .expect("Failed to build a material sent to the renderer");
+                let _gpu_material = renderer.register_material(material);
+
+                // TODO: Create an association between `material_id` and `material_source`.
+            },
+            EngineMessage::Mesh(mesh_id, mesh_data) => {
+                let gpu_mesh = renderer
+                    .register_mesh(&mesh_data)
+                    .expect("Failed to register a mesh sent to the renderer");
+                let last = engine.mesh_map.insert(mesh_id, gpu_mesh);
+                assert!(last.is_none(), "Duplicate mesh_id found: {:?}", mesh_id);
+            },
+            EngineMessage::MeshInstance(mesh_renderer_data, transform_inner) => {
+                let anchor_id = match transform_inner.anchor() {
+                    Some(anchor) => anchor,
+                    None => unimplemented!(), // TODO: Create the anchor.
+                };
+
+                let gpu_mesh = *engine
+                    .mesh_map
+                    .get(&mesh_renderer_data.mesh_id())
+                    .expect("No gpu mesh found for mesh id");
+
+                let mut mesh_instance = MeshInstance::new(
+                    gpu_mesh,
+                    renderer.default_material(),
+                );
+
+                // HACK HACK HACK ---------------------------------------------------------
+                mesh_instance.material_mut().set_color("surface_color", ::math::Color::rgb(1.0, 0.0, 0.0));
+                mesh_instance.material_mut().set_color("surface_specular", ::math::Color::rgb(1.0, 1.0, 1.0));
+                mesh_instance.material_mut().set_f32("surface_shininess", 4.0);
+                // HACK HACK HACK ---------------------------------------------------------
+
+                mesh_instance.set_anchor(anchor_id);
+
+                let _ = renderer.register_mesh_instance(mesh_instance);
             }
-
-            // Update lights.
-            for light in &engine.lights {
-                let &(ref id, ref data) = &**light;
-                let light = engine.renderer.get_light_mut(*id.borrow()).expect("Renderer has no such light");
-                *light = data.borrow().clone();
+            EngineMessage::Behavior(func) => {
+                engine.behaviors.push(func);
             }
-
-            // Draw.
-            engine.renderer.draw();
         }
+    }
 
-        // Determine the next frame's start time, even if we blew the frame time.
-        while frame_start < Instant::now() {
-            frame_start += target_frame_time;
+    // Update renderer's anchors with flattened scene graph.
+    for node in engine.scene_graph.roots() {
+        let node = node.borrow();
+
+        // TODO: Do something like pre-sorting so we only try to update out of
+        // date nodes.
+        if let Some(anchor_id) = node.anchor() {
+            // Send position/rotation/scale to renderer anchor.
+            let anchor = renderer
+                .get_anchor_mut(anchor_id)
+                .expect("Node had anchor id but render did not have specified anchor");
+            anchor.set_position(node.position);
+            anchor.set_orientation(node.orientation);
+            anchor.set_scale(node.scale);
         }
+    }
 
-        // Now wait until we've returned to the frame cadence before beginning the next frame.
-        while Instant::now() < frame_start {}
+    // Update the camera.
+    if let Some((ref camera_data, ref camera_id)) = engine.camera {
+        let render_camera = renderer
+            .get_camera_mut(*camera_id)
+            .expect("Camera didn't exist for camera id");
+
+        // The renderer matches the camera's aspect ratio to the window.
+        render_camera.set_fov(::math::Rad(camera_data.fov()));
+        render_camera.set_near(camera_data.near());
+        render_camera.set_far(camera_data.far());
+    }
+
+    // Update lights.
+    for light in &engine.lights {
+        let &(ref id, ref data) = &**light;
+        let light = renderer.get_light_mut(*id.borrow()).expect("Renderer has no such light");
+        *light = data.borrow().clone();
+    }
+
+    // Draw.
+    renderer.draw();
+
+    // Keep a recent frame around for the crash report, the panic hook can't capture one
+    // itself because it may run on a thread that doesn't own the GL context. Only builds with
+    // the `crash-screenshots` feature want one, since reading back the frame stalls the GPU.
+    if crash::wants_screenshot() {
+        crash::record_screenshot(renderer.capture_frame());
     }
 }
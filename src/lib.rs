@@ -14,6 +14,7 @@
 // Useful when sending raw pointers between threads, could be replaced.
 #![feature(unique)]
 
+extern crate backtrace;
 extern crate bootstrap_rs as bootstrap;
 extern crate bootstrap_audio as bs_audio;
 extern crate cell_extras;
@@ -32,6 +33,7 @@ pub mod macros;
 
 pub mod camera;
 pub mod collections;
+pub mod crash;
 pub mod engine;
 pub mod input;
 pub mod light;
@@ -55,7 +55,7 @@ macro_rules! warn_once {
         unsafe {
             if !HAS_WARNED {
                 HAS_WARNED = true;
-                println!($message);
+                $crate::crash::log($message);
             }
         }
     }
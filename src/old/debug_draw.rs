@@ -195,6 +195,14 @@ pub fn box_min_max_color(min: Point, max: Point, color: Color) {
     });
 }
 
+pub fn box_aabb(aabb: Aabb) {
+    box_min_max_color(aabb.min, aabb.max, color::WHITE);
+}
+
+pub fn box_aabb_color(aabb: Aabb, color: Color) {
+    box_min_max_color(aabb.min, aabb.max, color);
+}
+
 pub fn box_center_widths(center: Point, widths: Vector3) {
     let transform = Matrix4::from_point(center) * Matrix4::from_scale_vector(widths);
     draw_command(DebugDrawCommand::Box {
@@ -2,6 +2,7 @@
 
 extern crate parse_collada as collada;
 
+use crash;
 use math::*;
 use polygon::geometry::mesh::*;
 pub use self::collada::{
@@ -118,7 +119,7 @@ pub fn load_resources<T: Into<String>>(source: T) -> Result<Mesh> {
 
 fn collada_mesh_to_mesh(mesh: &collada::Mesh) -> Result<Mesh> {
     if mesh.primitive_elements.len() > 1 {
-        println!("WARNING: Mesh is composed of more than one geometric primitive, which is not currently supported, only part of the mesh will be loaded");
+        crash::log("WARNING: Mesh is composed of more than one geometric primitive, which is not currently supported, only part of the mesh will be loaded");
     }
 
     // Grab the first primitive element in the mesh.
@@ -240,7 +241,7 @@ fn collada_mesh_to_mesh(mesh: &collada::Mesh) -> Result<Mesh> {
                     },
                     _ => if !unsupported_semantic_flag {
                         unsupported_semantic_flag = true;
-                        println!("WARNING: Unsupported vertex semantic {} in mesh will not be used", mapper.semantic);
+                        crash::log(format!("WARNING: Unsupported vertex semantic {} in mesh will not be used", mapper.semantic));
                     },
                 }
             }
@@ -1,7 +1,7 @@
 use engine::{self, EngineMessage};
 use scheduler::{self, Async};
 use polygon::geometry::mesh::{BuildMeshError, MeshBuilder};
-use polygon::math::Vector2;
+use polygon::math::{PI, Vector2};
 use obj::{self, Obj};
 use std::fs::File;
 use std::io;
@@ -9,6 +9,7 @@ use std::io::prelude::*;
 use std::path::Path;
 use std::string::FromUtf8Error;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::u16;
 
 pub mod collada;
 
@@ -115,15 +116,22 @@ pub fn load_mesh<'a, P>(path: P) -> Async<'a, Result<Mesh, LoadMeshError>>
                     }
                 }
 
-                // Create indices list.
+                // Create indices list, using 16 bit indices if there are few enough vertices.
                 let indices_count = obj.position_indices().len() as u32 * 3;
-                let indices: Vec<u32> = (0..indices_count).collect();
-
-                MeshBuilder::new()
+                let builder = if indices_count <= u16::MAX as u32 + 1 {
+                    let indices: Vec<u16> = (0..indices_count).map(|index| index as u16).collect();
+                    MeshBuilder::new().set_indices_u16(&*indices)
+                } else {
+                    let indices: Vec<u32> = (0..indices_count).collect();
+                    MeshBuilder::new().set_indices(&*indices)
+                };
+
+                builder
                     .set_position_data(&*positions)
                     .set_normal_data(&*normals)
                     .set_texcoord_data(&*texcoords)
-                    .set_indices(&*indices)
+                    // Keep edges sharper than 60 degrees hard if the file has no normals.
+                    .generate_normals(Some(PI / 3.0))
                     .build()?
             },
             _ => {
@@ -198,8 +206,10 @@ pub fn load_material<'a, P>(path: P) -> Async<'a, Result<Material, LoadMaterialE
 {
     scheduler::start(move || {
         // Load and parse material data.
+        let path_buf = path.as_ref().to_path_buf();
         let text = load_file_text(path).await()?;
-        let material_source = ::polygon::material::MaterialSource::from_str(text)?;
+        let mut material_source = ::polygon::material::MaterialSource::from_str(text)?;
+        material_source.path = Some(path_buf);
 
         let material_id = MATERIAL_ID_COUNTER.fetch_add(1, Ordering::Relaxed);
 
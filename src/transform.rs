@@ -70,7 +70,7 @@ impl Transform {
     ///
     /// TODO: Do the number represent clockwise or anitclockwise rotation around each axis? That
     /// might be determined by the math library, but it should be noted in the module docs.
-    pub fn rotate_eulers(&mut self, x: f32, y: f32, z: f32) {
+    pub fn rotate_eulers<A: Into<Rad>>(&mut self, x: A, y: A, z: A) {
         self.rotate(Orientation::from_eulers(x, y, z));
     }
 